@@ -108,6 +108,27 @@ macro_rules! bench_file {
                     )
                 });
 
+                // the reader is streamed in chunks, compared with reading it into memory first
+                group.bench_with_input("sonic_rs::from_reader", &vec, |b, data| {
+                    b.iter_batched(
+                        || data,
+                        |bytes| sonic_rs::from_reader::<_, $structure>(&bytes[..]),
+                        BatchSize::SmallInput,
+                    )
+                });
+
+                group.bench_with_input("sonic_rs::read_to_end+from_slice", &vec, |b, data| {
+                    b.iter_batched(
+                        || data.as_slice(),
+                        |mut bytes| {
+                            let mut buf = Vec::new();
+                            bytes.read_to_end(&mut buf).unwrap();
+                            sonic_rs_parse_struct::<$structure>(&buf).map(|_| ())
+                        },
+                        BatchSize::SmallInput,
+                    )
+                });
+
                 group.bench_with_input("simd_json::from_slice", &vec, |b, data| {
                     b.iter_batched(
                         || data.clone(),
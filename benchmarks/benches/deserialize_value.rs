@@ -185,11 +185,39 @@ macro_rules! bench_file {
     };
 }
 
+// The tiny documents without objects are parsed into the owned nodes, and the others are parsed
+// in the arena. The `use_raw` mode always parses in the arena, so it is the arena path on the same
+// inputs side by side.
+fn tiny_docs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tiny_docs");
+    for (name, json) in [
+        ("bool", "true"),
+        ("string", r#""request-id""#),
+        ("array", "[1, 2.5, \"x\", null]"),
+        ("object", r#"{"ok":true}"#),
+    ] {
+        group.bench_function(format!("sonic_rs_dom::from_slice/{name}"), |b| {
+            b.iter(|| sonic_rs_from_slice(json.as_bytes(), SONIC_DEFAULT_CFG))
+        });
+        group.bench_function(format!("sonic_rs_dom::arena/{name}"), |b| {
+            b.iter(|| sonic_rs_from_slice(json.as_bytes(), SONIC_USE_RAW_CFG))
+        });
+    }
+}
+
 bench_file!(book);
 bench_file!(canada);
 bench_file!(citm_catalog);
 bench_file!(twitter);
 bench_file!(github_events);
 
-criterion_group!(benches, canada, citm_catalog, twitter, github_events, book);
+criterion_group!(
+    benches,
+    canada,
+    citm_catalog,
+    twitter,
+    github_events,
+    book,
+    tiny_docs
+);
 criterion_main!(benches);
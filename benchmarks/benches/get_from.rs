@@ -41,6 +41,31 @@ fn bench_get(c: &mut Criterion) {
         )
     });
 
+    let bytes = data.as_bytes();
+    group.bench_with_input("sonic-rs::get_unchecked_from_slice", bytes, |b, data| {
+        b.iter_batched(
+            || data,
+            |json| unsafe { sonic_rs::get_unchecked(json, &rpath) },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_with_input("sonic-rs::get_structural_from_slice", bytes, |b, data| {
+        b.iter_batched(
+            || data,
+            |json| unsafe { sonic_rs::get_structural(json, &rpath) },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_with_input("sonic-rs::get_from_slice", bytes, |b, data| {
+        b.iter_batched(
+            || data,
+            |json| sonic_rs::get(json, &rpath),
+            BatchSize::SmallInput,
+        )
+    });
+
     group.bench_with_input("gjson::get_from_str", data, |b, data| {
         b.iter_batched(
             || data,
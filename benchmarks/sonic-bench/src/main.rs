@@ -0,0 +1,314 @@
+//! Measure the parse, serialize and get throughput of sonic-rs on the user-provided JSON files.
+//!
+//! The SIMD backend of sonic-rs is selected when compiling, so each build measures one backend.
+//! Build it with the different `RUSTFLAGS` to compare the backends, see the README.
+
+use std::{
+    env, fs,
+    hint::black_box,
+    path::Path,
+    process,
+    time::{Duration, Instant},
+};
+
+use schema::{canada::Canada, citm_catalog::CitmCatalog, twitter::Twitter};
+use sonic_rs::{
+    from_slice, get_from_slice, to_array_iter, to_object_iter, to_vec, to_vec_pretty, Deserializer,
+    PointerNode, Value,
+};
+
+const USAGE: &str = "\
+Usage: sonic-bench [OPTIONS] <FILE>...
+
+Measure the throughput of sonic-rs on the JSON files.
+
+Options:
+  -g, --get <POINTER>   Also measure getting the JSON pointer, such as `/statuses/0/id`.
+                        Can be repeated. The all-digit tokens are the array indices.
+  -c, --cases <LIST>    The comma-separated cases to run, all by default:
+                        dom,dom-rawnumber,dom-raw,lazy,typed,serialize,serialize-pretty
+  -s, --schema <NAME>   The struct types of the `typed` case: twitter, citm_catalog or canada.
+                        By default it is the file name without the extension, and the
+                        `typed` case is skipped if there are no struct types for the file.
+  -t, --time <SECS>     The measuring time of each case, 1 second by default.
+  -h, --help            Print this help.
+";
+
+const CASES: &[&str] = &[
+    "dom",
+    "dom-rawnumber",
+    "dom-raw",
+    "lazy",
+    "typed",
+    "serialize",
+    "serialize-pretty",
+];
+
+// The struct types of the test data in the benchmarks, which are deserialized in the `typed` case.
+const SCHEMAS: &[&str] = &["twitter", "citm_catalog", "canada"];
+
+struct Options {
+    files: Vec<String>,
+    pointers: Vec<String>,
+    cases: Vec<String>,
+    schema: Option<String>,
+    time: Duration,
+}
+
+struct Measured {
+    case: String,
+    iters: u64,
+    elapsed: Duration,
+}
+
+fn main() {
+    let opts = match parse_args(env::args().skip(1)) {
+        Ok(opts) => opts,
+        Err(msg) => {
+            eprintln!("error: {msg}\n\n{USAGE}");
+            process::exit(2);
+        }
+    };
+
+    println!("sonic-rs backend: {}", backend());
+    if let Some(hint) = backend_hint() {
+        println!("{hint}");
+    }
+
+    let mut failed = false;
+    for file in &opts.files {
+        println!();
+        if let Err(msg) = bench_file(file, &opts) {
+            eprintln!("error: {file}: {msg}");
+            failed = true;
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut opts = Options {
+        files: Vec::new(),
+        pointers: Vec::new(),
+        cases: CASES.iter().map(|c| c.to_string()).collect(),
+        schema: None,
+        time: Duration::from_secs(1),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{name} requires a value"));
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print!("{USAGE}");
+                process::exit(0);
+            }
+            "-g" | "--get" => {
+                let pointer = value(&arg)?;
+                parse_pointer(&pointer)?;
+                opts.pointers.push(pointer);
+            }
+            "-c" | "--cases" => {
+                let cases = value(&arg)?;
+                opts.cases = cases.split(',').map(|c| c.trim().to_string()).collect();
+                if let Some(c) = opts.cases.iter().find(|c| !CASES.contains(&c.as_str())) {
+                    return Err(format!("unknown case `{c}`"));
+                }
+            }
+            "-s" | "--schema" => {
+                let schema = value(&arg)?;
+                if !SCHEMAS.contains(&schema.as_str()) {
+                    return Err(format!("unknown schema `{schema}`"));
+                }
+                opts.schema = Some(schema);
+            }
+            "-t" | "--time" => {
+                let secs = value(&arg)?;
+                let secs: f64 = secs
+                    .parse()
+                    .ok()
+                    .filter(|s: &f64| s.is_finite() && *s > 0.0)
+                    .ok_or(format!("invalid time `{secs}`"))?;
+                opts.time = Duration::from_secs_f64(secs);
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ => opts.files.push(arg),
+        }
+    }
+    if opts.files.is_empty() {
+        return Err("no JSON files".to_string());
+    }
+    Ok(opts)
+}
+
+// Parse the JSON pointer as RFC 6901, the all-digit tokens are parsed as the array indices.
+fn parse_pointer(pointer: &str) -> Result<Vec<PointerNode>, String> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(tokens) = pointer.strip_prefix('/') else {
+        return Err(format!(
+            "the JSON pointer `{pointer}` should start with `/`"
+        ));
+    };
+    Ok(tokens
+        .split('/')
+        .map(|token| match token.parse::<usize>() {
+            Ok(i) if token.bytes().all(|b| b.is_ascii_digit()) => PointerNode::Index(i),
+            _ => PointerNode::from(token.replace("~1", "/").replace("~0", "~").as_str()),
+        })
+        .collect())
+}
+
+fn bench_file(file: &str, opts: &Options) -> Result<(), String> {
+    let json = fs::read(file).map_err(|e| e.to_string())?;
+    let value: Value = from_slice(&json).map_err(|e| e.to_string())?;
+    println!("{file} ({} bytes)", json.len());
+
+    let schema = match &opts.schema {
+        Some(schema) => schema.as_str(),
+        None => Path::new(file)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default(),
+    };
+
+    let mut results = Vec::new();
+    for case in &opts.cases {
+        let measured = match case.as_str() {
+            "dom" => measure(case, opts.time, || from_slice::<Value>(&json).is_ok()),
+            "dom-rawnumber" => measure(case, opts.time, || {
+                let mut de = Deserializer::from_slice(&json).use_rawnumber();
+                de.deserialize::<Value>().is_ok()
+            }),
+            "dom-raw" => measure(case, opts.time, || {
+                let mut de = Deserializer::from_slice(&json).use_raw();
+                de.deserialize::<Value>().is_ok()
+            }),
+            "lazy" => measure(case, opts.time, || lazy_visit(&json)),
+            "typed" => {
+                let Some(typed) = typed_parser(schema) else {
+                    println!("skip typed: no struct types for `{schema}`, see `--schema`");
+                    continue;
+                };
+                if !typed(&json) {
+                    return Err(format!(
+                        "the JSON doesn't match the `{schema}` struct types"
+                    ));
+                }
+                measure(&format!("typed {schema}"), opts.time, || typed(&json))
+            }
+            "serialize" => measure(case, opts.time, || to_vec(&value).is_ok()),
+            "serialize-pretty" => measure(case, opts.time, || to_vec_pretty(&value).is_ok()),
+            _ => unreachable!("the cases are checked when parsing the options"),
+        };
+        results.push(measured);
+    }
+    for pointer in &opts.pointers {
+        let path = parse_pointer(pointer)?;
+        if let Err(e) = get_from_slice(&json, &path) {
+            return Err(format!("get `{pointer}`: {e}"));
+        }
+        let case = format!("get {pointer}");
+        results.push(measure(&case, opts.time, || {
+            get_from_slice(&json, &path).is_ok()
+        }));
+    }
+
+    print_table(json.len(), &results);
+    Ok(())
+}
+
+// Deserialize the JSON into the struct types of `schema`, which are the same as the benches.
+fn typed_parser(schema: &str) -> Option<fn(&[u8]) -> bool> {
+    let parse: fn(&[u8]) -> bool = match schema {
+        "twitter" => |json| from_slice::<Twitter>(json).is_ok(),
+        "citm_catalog" => |json| from_slice::<CitmCatalog>(json).is_ok(),
+        "canada" => |json| from_slice::<Canada>(json).is_ok(),
+        _ => return None,
+    };
+    Some(parse)
+}
+
+// Iterate the top-level elements lazily, without parsing the nested values.
+fn lazy_visit(json: &[u8]) -> bool {
+    let first = json.iter().find(|b| !b.is_ascii_whitespace());
+    match first {
+        Some(b'{') => to_object_iter(json).all(|kv| black_box(kv).is_ok()),
+        Some(b'[') => to_array_iter(json).all(|v| black_box(v).is_ok()),
+        _ => get_from_slice(json, &[] as &[&str]).is_ok(),
+    }
+}
+
+fn measure(case: &str, time: Duration, mut f: impl FnMut() -> bool) -> Measured {
+    // warm up the caches and the allocator
+    let warmup = Instant::now();
+    while warmup.elapsed() < time / 10 {
+        assert!(black_box(f()), "`{case}` failed");
+    }
+
+    let mut iters = 0;
+    let start = Instant::now();
+    let elapsed = loop {
+        black_box(f());
+        iters += 1;
+        let elapsed = start.elapsed();
+        if elapsed >= time {
+            break elapsed;
+        }
+    };
+    Measured {
+        case: case.to_string(),
+        iters,
+        elapsed,
+    }
+}
+
+fn print_table(len: usize, results: &[Measured]) {
+    let width = results
+        .iter()
+        .map(|r| r.case.len())
+        .chain(Some("case".len()))
+        .max()
+        .unwrap_or_default();
+    println!(
+        "{:<width$}  {:>12}  {:>12}  {:>10}",
+        "case", "MB/s", "time/iter", "iters"
+    );
+    println!("{}", "-".repeat(width + 42));
+    for r in results {
+        let per_iter = r.elapsed.div_f64(r.iters as f64);
+        let mbps = (len as f64 * r.iters as f64) / r.elapsed.as_secs_f64() / 1e6;
+        println!(
+            "{:<width$}  {:>12.1}  {:>12}  {:>10}",
+            r.case,
+            mbps,
+            format!("{per_iter:.2?}"),
+            r.iters
+        );
+    }
+}
+
+// The SIMD backend is selected by the target features when compiling sonic-rs.
+fn backend() -> &'static str {
+    if cfg!(target_feature = "avx2") {
+        "avx2"
+    } else if cfg!(target_feature = "sse2") {
+        "sse2"
+    } else if cfg!(all(target_feature = "neon", target_arch = "aarch64")) {
+        "neon"
+    } else {
+        "scalar"
+    }
+}
+
+fn backend_hint() -> Option<&'static str> {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    if !cfg!(target_feature = "avx2") && std::arch::is_x86_feature_detected!("avx2") {
+        return Some(
+            "note: this CPU supports avx2, rebuild with `RUSTFLAGS=\"-C target-cpu=native\"` to \
+             measure the avx2 backend",
+        );
+    }
+    None
+}
@@ -0,0 +1,14 @@
+use sonic_rs::{include_json, IncludedJson, JsonContainerTrait, JsonValueTrait};
+
+// validated at compile time, and parsed at the first use
+static PERSON: &IncludedJson = include_json!("examples/testdata/person.json");
+
+fn main() {
+    assert_eq!(PERSON.get("name").as_str(), Some("Xiaoming"));
+    assert_eq!(PERSON.get("age").as_u64(), Some(18));
+    assert_eq!(PERSON.get("phones").as_array().map(|a| a.len()), Some(1));
+
+    // the original text is embedded as is
+    assert!(PERSON.text().starts_with("{\n  \"name\""));
+    println!("{}", PERSON.text());
+}
@@ -0,0 +1,53 @@
+sonic_rs::json_schema! {
+    #[derive(Debug, PartialEq)]
+    struct Order = r#"{
+        "type": "object",
+        "description": "A order in the shop.",
+        "properties": {
+            "orderId": {"type": "integer", "format": "uint64"},
+            "price": {"type": "number"},
+            "tags": {"type": "array", "items": {"type": "string"}},
+            "buyer": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "vip": {"type": ["boolean", "null"]}
+                },
+                "required": ["name", "vip"]
+            },
+            "extra": {}
+        },
+        "required": ["orderId", "price"]
+    }"#;
+}
+
+fn main() {
+    let data = br#"{
+        "orderId": 1024,
+        "ignored": {"a": [1, 2, 3]},
+        "price": 9.5,
+        "tags": ["new", "sale\/50%"],
+        "buyer": {"name": "sonic", "vip": null}
+    }"#;
+
+    // extract the fields in one scan, without building a DOM
+    let order = Order::from_json(&data[..]).unwrap();
+    assert_eq!(
+        order,
+        Order {
+            order_id: 1024,
+            price: 9.5,
+            tags: Some(vec!["new".to_string(), "sale/50%".to_string()]),
+            buyer: Some(OrderBuyer {
+                name: "sonic".to_string(),
+                vip: None,
+            }),
+            extra: None,
+        }
+    );
+
+    // the missing required fields and mismatched types are errors
+    assert!(Order::from_json(r#"{"orderId": 1}"#).is_err());
+    assert!(Order::from_json(r#"{"orderId": -1, "price": 1}"#).is_err());
+    assert!(Order::from_json(r#"{"orderId": 1, "price": 1, "buyer": {"name": "a"}}"#).is_err());
+}
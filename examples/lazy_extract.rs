@@ -0,0 +1,41 @@
+use sonic_rs::LazyExtract;
+
+#[derive(Debug, PartialEq, LazyExtract)]
+struct Tick {
+    symbol: String,
+    #[lazy(rename = "p")]
+    price: f64,
+    #[lazy(rename = "q")]
+    quantity: u32,
+    is_buyer: bool,
+    trade_id: Option<u64>,
+}
+
+fn main() {
+    let data = br#"{
+        "symbol": "BTC\/USDT",
+        "p": 66500.25,
+        "extra": {"ignored": [1, 2, 3]},
+        "q": 3,
+        "is_buyer": true,
+        "trade_id": null
+    }"#;
+
+    // extract the fields in one scan, without building a DOM
+    let tick = Tick::from_json_lazily(data).unwrap();
+    assert_eq!(
+        tick,
+        Tick {
+            symbol: "BTC/USDT".to_string(),
+            price: 66500.25,
+            quantity: 3,
+            is_buyer: true,
+            trade_id: None,
+        }
+    );
+
+    // the missing fields and mismatched types are errors
+    assert!(Tick::from_json_lazily(br#"{"symbol": "x", "p": 1.0}"#).is_err());
+    let data = br#"{"symbol": "x", "p": 1.0, "q": -1, "is_buyer": false, "trade_id": 1}"#;
+    assert!(Tick::from_json_lazily(data).is_err());
+}
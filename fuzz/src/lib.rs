@@ -48,6 +48,8 @@ pub fn sonic_rs_fuzz_data(data: &[u8]) {
         Ok(jv) => {
             // compare from_slice result
             let sv: Value = from_slice(data).unwrap();
+            #[cfg(debug_assertions)]
+            sonic_rs::internals::check_value_invariants(&sv).unwrap();
             let eq = compare_value(&jv, &sv);
 
             // compare to_string result
@@ -12,11 +12,33 @@ cargo test --features use_raw
 
 cargo test --features utf8_lossy
 
+cargo test --features skip_bom
+
+cargo test --features derive
+
+cargo test --features rust_decimal,bigdecimal
+
+cargo test --features uuid
+
+cargo test --features stream
+
+cargo test --features arrow
+
+cargo test --features parallel-io
+
+cargo test --features rayon
+
+cargo test --features schema
+
+cargo test --features testing
+
+cargo test --features preserve_order_on_mut
+
 examples=$(cargo build --example 2>&1 | grep -v ":")
 
 for example in $examples; do
     echo "Running example $example"
-    cargo run --example $example
+    cargo run --features derive --example $example
 done
 
 
@@ -327,6 +327,85 @@ pub fn parse_number(data: &[u8], index: &mut usize, negative: bool) -> Result<Pa
     parse_float(significant, exponent, negative, trunc, raw_num)
 }
 
+/// Parse the number with the extensions which are not allowed in JSON, such as the hexadecimal
+/// integers `0x1F` and the integers with underscore separators `1_000_000`. They are mapped to
+/// integers. The other numbers are parsed as [`parse_number`].
+#[inline]
+pub fn parse_number_ext(
+    data: &[u8],
+    index: &mut usize,
+    negative: bool,
+) -> Result<ParserNumber, Error> {
+    let start = *index;
+    if match_digit!(data, start, b'0') && match_digit!(data, start + 1, b'x' | b'X') {
+        *index += 2;
+        return parse_ext_integer(data, index, negative, 16);
+    }
+
+    let mut i = start;
+    let mut has_underscore = false;
+    while match_digit!(data, i, b'0'..=b'9' | b'_') {
+        has_underscore |= data[i] == b'_';
+        i += 1;
+    }
+    if !has_underscore {
+        return parse_number(data, index, negative);
+    }
+
+    // the leading zeros are not allowed
+    if match_digit!(data, start, b'0') {
+        return Err(Error::InvalidNumber);
+    }
+    parse_ext_integer(data, index, negative, 10)
+}
+
+// parse the integer with radix, the underscores are only allowed between digits.
+#[inline(always)]
+fn parse_ext_integer(
+    data: &[u8],
+    index: &mut usize,
+    negative: bool,
+    radix: u64,
+) -> Result<ParserNumber, Error> {
+    let mut significant: u64 = 0;
+    let mut digits_cnt = 0;
+    let mut last_underscore = true;
+    while *index < data.len() {
+        let c = data[*index];
+        let digit = match c {
+            b'_' if !last_underscore => {
+                last_underscore = true;
+                *index += 1;
+                continue;
+            }
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' if radix == 16 => c - b'a' + 10,
+            b'A'..=b'F' if radix == 16 => c - b'A' + 10,
+            _ => break,
+        };
+        significant = significant
+            .checked_mul(radix)
+            .and_then(|v| v.checked_add(digit as u64))
+            .ok_or(Error::InvalidNumber)?;
+        digits_cnt += 1;
+        last_underscore = false;
+        *index += 1;
+    }
+
+    if digits_cnt == 0 || last_underscore || match_digit!(data, *index, b'.' | b'e' | b'E' | b'_') {
+        return Err(Error::InvalidNumber);
+    }
+
+    if !negative || significant == 0 {
+        Ok(ParserNumber::Unsigned(significant))
+    } else if significant <= (1u64 << 63) {
+        // if significant is 0x8000_0000_0000_0000, it will overflow here.
+        Ok(ParserNumber::Signed(0_i64.wrapping_sub(significant as i64)))
+    } else {
+        Err(Error::InvalidNumber)
+    }
+}
+
 #[inline(always)]
 fn parse_float(
     significant: u64,
@@ -472,7 +551,7 @@ const POW10_FLOAT: [f64; 23] = [
 
 #[cfg(test)]
 mod test {
-    use crate::{parse_number, ParserNumber};
+    use crate::{parse_number, parse_number_ext, Error, ParserNumber};
 
     fn test_parse_ok(input: &str, expect: f64) {
         assert_eq!(input.parse::<f64>().unwrap(), expect);
@@ -539,4 +618,80 @@ mod test {
             3.469446951536142e-26,
         );
     }
+
+    #[test]
+    fn test_parse_number_ext() {
+        fn parse(input: &str) -> Result<ParserNumber, Error> {
+            let mut data = input.as_bytes().to_vec();
+            data.push(b' ');
+            let negative = data[0] == b'-';
+            let mut index = negative as usize;
+            let num = parse_number_ext(&data, &mut index, negative)?;
+            assert_eq!(data[index], b' ', "failed num is {}", input);
+            Ok(num)
+        }
+
+        let unsigned = [
+            ("0x1F", 0x1F),
+            ("0Xab_CD", 0xabcd),
+            ("0x0", 0),
+            ("0xFFFF_FFFF_FFFF_FFFF", u64::MAX),
+            ("1_000_000", 1_000_000),
+            ("1_2_3", 123),
+            ("123", 123),
+            ("0", 0),
+            ("-0x0", 0),
+        ];
+        for (input, expect) in unsigned {
+            assert!(
+                matches!(parse(input), Ok(ParserNumber::Unsigned(v)) if v == expect),
+                "failed num is {}",
+                input
+            );
+        }
+
+        let signed = [
+            ("-0x1f", -0x1f),
+            ("-1_000", -1000),
+            ("-0x8000_0000_0000_0000", i64::MIN),
+            ("-12", -12),
+        ];
+        for (input, expect) in signed {
+            assert!(
+                matches!(parse(input), Ok(ParserNumber::Signed(v)) if v == expect),
+                "failed num is {}",
+                input
+            );
+        }
+        assert!(matches!(parse("1.5e3"), Ok(ParserNumber::Float(f)) if f == 1500.0));
+
+        let invalids = [
+            "0x",
+            "0x_1",
+            "0x1_",
+            "0x1__2",
+            "1_",
+            "1__0",
+            "01_0",
+            "1_0.5",
+            "1_0e3",
+            "0x1G",
+            "0x1_0000_0000_0000_0000",
+            "-0x8000_0000_0000_0001",
+            "18_446_744_073_709_551_616",
+        ];
+        for input in invalids {
+            let mut data = input.as_bytes().to_vec();
+            data.push(b' ');
+            let negative = data[0] == b'-';
+            let mut index = negative as usize;
+            let ret = parse_number_ext(&data, &mut index, negative);
+            // the trailing chars are checked by the caller
+            assert!(
+                ret.is_err() || data[index] != b' ',
+                "failed num is {}",
+                input
+            );
+        }
+    }
 }
@@ -0,0 +1,89 @@
+//! Embed a JSON file which is validated at compile time.
+
+use std::path::PathBuf;
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Error, LitStr};
+
+pub(crate) fn expand_include_json(rel: &LitStr) -> syn::Result<TokenStream2> {
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let path = PathBuf::from(root).join(rel.value());
+    let text = std::fs::read_to_string(&path).map_err(|e| {
+        Error::new(
+            rel.span(),
+            format!("failed to read {}: {e}", path.display()),
+        )
+    })?;
+    let value = match const_value(text.trim_matches(is_space)) {
+        Some(value) => quote! { ::core::option::Option::Some(#value) },
+        None => quote! { ::core::option::Option::None },
+    };
+    // the text is included by `include_str!`, so the crate is rebuilt when the file is changed
+    let path = path.to_string_lossy().into_owned();
+    // the text is validated by sonic-rs in const, the message is a format string of `assert!`
+    let msg = format!("invalid JSON in {path}")
+        .replace('{', "{{")
+        .replace('}', "}}");
+    Ok(quote! {
+        {
+            const TEXT: &str = ::core::include_str!(#path);
+            #[allow(unknown_lints, long_running_const_eval)]
+            const _: () = ::core::assert!(::sonic_rs::IncludedJson::is_valid(TEXT), #msg);
+            static JSON: ::sonic_rs::IncludedJson = ::sonic_rs::IncludedJson::new(TEXT, #value);
+            &JSON
+        }
+    })
+}
+
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+// The value constructed in const from the trimmed text, only for the literals, the integers, the
+// strings without escapes and the empty containers. The others are parsed by sonic-rs at runtime,
+// so that they are the same as parsed by sonic-rs. The text is validated in const, and the invalid
+// text is not constructed here.
+fn const_value(text: &str) -> Option<TokenStream2> {
+    let value = match text {
+        "null" => quote! { ::sonic_rs::Value::new_null() },
+        "true" => quote! { ::sonic_rs::Value::new_bool(true) },
+        "false" => quote! { ::sonic_rs::Value::new_bool(false) },
+        _ if is_integer(text) => {
+            if let Ok(u) = text.parse::<u64>() {
+                quote! { ::sonic_rs::Value::new_u64(#u) }
+            } else if let Ok(i) = text.parse::<i64>() {
+                quote! { ::sonic_rs::Value::new_i64(#i) }
+            } else {
+                return None;
+            }
+        }
+        _ if text.len() >= 2 && text.len() < u32::MAX as usize => {
+            let inner = &text[1..text.len() - 1];
+            match (text.as_bytes()[0], text.as_bytes()[text.len() - 1]) {
+                (b'"', b'"') if !inner.bytes().any(|c| c == b'"' || c == b'\\' || c < 0x20) => {
+                    quote! { ::sonic_rs::Value::new_static_str(#inner) }
+                }
+                (b'[', b']') if inner.chars().all(is_space) => {
+                    quote! { ::sonic_rs::Value::new_array() }
+                }
+                (b'{', b'}') if inner.chars().all(is_space) => {
+                    quote! { ::sonic_rs::Value::new_object() }
+                }
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+// `-?(0|[1-9][0-9]*)`, "-0" is a float in sonic-rs.
+fn is_integer(text: &str) -> bool {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    match digits.as_bytes() {
+        [b'0'] => text == "0",
+        [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+        _ => false,
+    }
+}
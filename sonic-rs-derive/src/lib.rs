@@ -0,0 +1,157 @@
+//! Derive macros for sonic-rs. Use them by the `derive` feature of sonic-rs.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr};
+
+mod include;
+mod schema;
+
+/// Derive a `from_json_lazily` constructor, which extracts the fields from a JSON object in one
+/// scan without building a DOM. See `sonic_rs::FromLazyValue` for the supported field types.
+///
+/// The field is renamed by `#[lazy(rename = "name")]`.
+#[proc_macro_derive(LazyExtract, attributes(lazy))]
+pub fn derive_lazy_extract(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_lazy_extract(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_lazy_extract(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    input,
+                    "LazyExtract only supports structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "LazyExtract only supports structs with named fields",
+            ))
+        }
+    };
+
+    let mut keys = Vec::with_capacity(fields.len());
+    let mut inits = Vec::with_capacity(fields.len());
+    for (i, field) in fields.iter().enumerate() {
+        let ident = field.ident.as_ref().unwrap();
+        let key = field_key(field)?.unwrap_or_else(|| {
+            let name = ident.to_string();
+            name.strip_prefix("r#").unwrap_or(&name).to_string()
+        });
+        keys.push(key);
+        inits.push(quote! {
+            #ident: ::sonic_rs::FromLazyValue::from_lazy_value(&nodes[#i])?
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Extract the fields from the JSON object in one scan, without building a DOM.
+            pub fn from_json_lazily(json: &[u8]) -> ::sonic_rs::Result<Self> {
+                static TREE: ::std::sync::OnceLock<::sonic_rs::PointerTree> =
+                    ::std::sync::OnceLock::new();
+                let tree = TREE.get_or_init(|| {
+                    let mut tree = ::sonic_rs::PointerTree::new();
+                    #(tree.add_path(&[#keys]);)*
+                    tree
+                });
+                let nodes = ::sonic_rs::get_many(json, tree)?;
+                Ok(Self {
+                    #(#inits,)*
+                })
+            }
+        }
+    })
+}
+
+/// Generate the structs described by a JSON Schema at compile time, with a `from_json`
+/// constructor which extracts the document in one scan without building a DOM.
+///
+/// The schema is a string literal, or `include_str!("path")` relative to the crate root. The
+/// attributes and the visibility are applied to all the generated structs.
+///
+/// ```ignore
+/// sonic_rs::json_schema! {
+///     #[derive(Debug)]
+///     pub struct Order = include_str!("schemas/order.json");
+/// }
+///
+/// let order = Order::from_json(r#"{"id": 1, "buyer": {"name": "a"}}"#)?;
+/// ```
+///
+/// Each struct implements `sonic_rs::FromLazyValue` by matching the keys with the generated
+/// `match`, so the unknown fields are skipped without parsing. The types are mapped as:
+///
+/// - `string`: `String`, `boolean`: `bool`.
+/// - `integer`: `i64`, or by the `format`, such as `int32` and `uint64`.
+/// - `number`: `f64`, or `f32` with the `float` format.
+/// - `array`: `Vec` of the `items`.
+/// - `object` with `properties`: a nested struct named by the parent and the field, such as
+///   `OrderBuyer`.
+/// - the other schemas: `sonic_rs::Value`.
+///
+/// The fields not in `required`, and the nullable types such as `["string", "null"]`, are
+/// `Option`. The field names are converted into snake case. `$ref` is not supported.
+#[proc_macro]
+pub fn json_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as schema::SchemaInput);
+    schema::expand_json_schema(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Embed a JSON file at compile time, as a `&'static sonic_rs::IncludedJson`. The path is
+/// relative to the crate root.
+///
+/// The file is validated by sonic-rs in const when compiling, the invalid JSON is a compile error.
+/// The nesting depth is limited to 65536 layers in the validation. The text is embedded as
+/// `include_str!`, and the `Value` is parsed at the first use, so the embedded resources are not
+/// parsed at startup.
+///
+/// The `Value` is only constructed at compile time for `null`, the booleans, the integers, the
+/// strings without escapes, and the empty arrays and objects. The non-empty arrays and objects,
+/// the floats and the escaped strings are always parsed at the first use.
+///
+/// ```ignore
+/// static DEFAULTS: &sonic_rs::IncludedJson = sonic_rs::include_json!("config/defaults.json");
+///
+/// let timeout = DEFAULTS.get("timeout").as_u64();
+/// ```
+#[proc_macro]
+pub fn include_json(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as LitStr);
+    include::expand_include_json(&path)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+// parse `#[lazy(rename = "name")]`
+fn field_key(field: &syn::Field) -> syn::Result<Option<String>> {
+    let mut key = None;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("lazy") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let lit: LitStr = meta.value()?.parse()?;
+                key = Some(lit.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported lazy attribute, expected `rename`"))
+            }
+        })?;
+    }
+    Ok(key)
+}
@@ -0,0 +1,326 @@
+//! Generate the structs and the specialized extractors from a JSON Schema.
+
+use std::{collections::HashSet, path::PathBuf};
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use serde_json::{Map, Value};
+use syn::{
+    parse::{Parse, ParseStream},
+    Attribute, Error, Ident, LitStr, Macro, Token, Visibility,
+};
+
+/// `#[attrs] vis struct Name = "schema";` or `#[attrs] vis struct Name = include_str!("path");`
+pub(crate) struct SchemaInput {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+    schema: LitStr,
+    // the absolute path of the schema file, if it is included
+    path: Option<String>,
+}
+
+impl Parse for SchemaInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+
+        let (schema, path) = if input.peek(LitStr) {
+            (input.parse()?, None)
+        } else {
+            let mac: Macro = input.parse()?;
+            if !mac.path.is_ident("include_str") {
+                return Err(Error::new_spanned(
+                    mac.path,
+                    "expected a string literal or `include_str!(\"path\")`",
+                ));
+            }
+            let rel: LitStr = mac.parse_body()?;
+            let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+            let path = PathBuf::from(root).join(rel.value());
+            let text = std::fs::read_to_string(&path).map_err(|e| {
+                Error::new(
+                    rel.span(),
+                    format!("failed to read {}: {e}", path.display()),
+                )
+            })?;
+            (
+                LitStr::new(&text, rel.span()),
+                Some(path.to_string_lossy().into_owned()),
+            )
+        };
+        if input.peek(Token![;]) {
+            input.parse::<Token![;]>()?;
+        }
+
+        Ok(Self {
+            attrs,
+            vis,
+            ident,
+            schema,
+            path,
+        })
+    }
+}
+
+struct Generator<'a> {
+    input: &'a SchemaInput,
+    items: Vec<TokenStream2>,
+}
+
+pub(crate) fn expand_json_schema(input: &SchemaInput) -> syn::Result<TokenStream2> {
+    let span = input.schema.span();
+    let schema: Value = serde_json::from_str(&input.schema.value())
+        .map_err(|e| Error::new(span, format!("invalid JSON Schema: {e}")))?;
+    let Some(schema) = schema.as_object().filter(|s| object_type(s)) else {
+        return Err(Error::new(
+            span,
+            "the root of the JSON Schema should be an object",
+        ));
+    };
+
+    let mut gen = Generator {
+        input,
+        items: Vec::new(),
+    };
+    gen.gen_struct(&input.ident, schema)?;
+
+    let items = &gen.items;
+    let ident = &input.ident;
+    let vis = &input.vis;
+    // rebuild when the schema file is changed
+    let include = input.path.as_ref().map(|path| {
+        quote! {
+            const _: &str = ::core::include_str!(#path);
+        }
+    });
+    Ok(quote! {
+        #(#items)*
+
+        impl #ident {
+            /// Extract the document described by the JSON Schema in one scan, without building a
+            /// DOM.
+            #vis fn from_json<'de, I: ::sonic_rs::JsonInput<'de>>(json: I) -> ::sonic_rs::Result<Self> {
+                let lv = ::sonic_rs::get(json, &[] as &[&str])?;
+                ::sonic_rs::FromLazyValue::from_lazy_value(&lv)
+            }
+        }
+
+        #include
+    })
+}
+
+impl Generator<'_> {
+    fn error(&self, msg: impl std::fmt::Display) -> Error {
+        Error::new(self.input.schema.span(), msg)
+    }
+
+    fn gen_struct(&mut self, name: &Ident, schema: &Map<String, Value>) -> syn::Result<()> {
+        let empty = Map::new();
+        let props = match schema.get("properties") {
+            Some(Value::Object(props)) => props,
+            None => &empty,
+            Some(_) => return Err(self.error(format!("`properties` of {name} is not an object"))),
+        };
+        let required: HashSet<&str> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|keys| keys.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut fields = Vec::with_capacity(props.len());
+        let mut vars = Vec::with_capacity(props.len());
+        let mut arms = Vec::with_capacity(props.len());
+        let mut inits = Vec::with_capacity(props.len());
+        let mut idents = HashSet::new();
+        for (key, prop) in props {
+            let ident = field_ident(key);
+            if !idents.insert(ident.to_string()) {
+                return Err(self.error(format!("duplicated field `{ident}` in {name}")));
+            }
+            let hint = format!("{name}{}", pascal_case(key));
+            let (ty, nullable) = self.field_type(&hint, prop)?;
+            let doc = prop.as_object().and_then(description);
+            let vis = &self.input.vis;
+            let var = format_ident!("__{}", ident.to_string().trim_start_matches("r#"));
+
+            if required.contains(key.as_str()) {
+                let ty = option_if(ty, nullable);
+                fields.push(quote! { #doc #vis #ident: #ty });
+                vars.push(quote! { let mut #var: ::core::option::Option<#ty> = None; });
+                arms.push(quote! {
+                    #key => #var = Some(::sonic_rs::FromLazyValue::from_lazy_value(&value)?),
+                });
+                inits.push(quote! {
+                    #ident: #var.ok_or_else(|| ::sonic_rs::__private::missing_field(#key))?
+                });
+            } else {
+                // the optional field is missing or `null`
+                let ty = quote! { ::core::option::Option<#ty> };
+                fields.push(quote! { #doc #vis #ident: #ty });
+                vars.push(quote! { let mut #var: #ty = None; });
+                arms.push(quote! {
+                    #key => #var = ::sonic_rs::FromLazyValue::from_lazy_value(&value)?,
+                });
+                inits.push(quote! { #ident: #var });
+            }
+        }
+
+        // the value is unused if there are no properties
+        let value = if arms.is_empty() {
+            quote! { _ }
+        } else {
+            quote! { value }
+        };
+        let attrs = &self.input.attrs;
+        let vis = &self.input.vis;
+        let doc = description(schema);
+        self.items.push(quote! {
+            #(#attrs)*
+            #doc
+            #vis struct #name {
+                #(#fields,)*
+            }
+
+            impl ::sonic_rs::FromLazyValue for #name {
+                fn from_lazy_value(lv: &::sonic_rs::LazyValue<'_>) -> ::sonic_rs::Result<Self> {
+                    #(#vars)*
+                    for entry in ::sonic_rs::__private::object_iter(lv)? {
+                        let (key, #value) = entry?;
+                        match &*key {
+                            #(#arms)*
+                            _ => {}
+                        }
+                    }
+                    Ok(Self {
+                        #(#inits,)*
+                    })
+                }
+            }
+        });
+        Ok(())
+    }
+
+    // The type of the non-null values and whether the value is nullable, `hint` is the name of
+    // the nested struct.
+    fn field_type(&mut self, hint: &str, prop: &Value) -> syn::Result<(TokenStream2, bool)> {
+        let Some(prop) = prop.as_object() else {
+            // `true` accepts any value
+            return Ok((quote! { ::sonic_rs::Value }, false));
+        };
+        if prop.contains_key("$ref") {
+            return Err(self.error(format!("`$ref` in {hint} is not supported")));
+        }
+
+        let nullable = types(prop).any(|t| t == "null");
+        let ty = match types(prop).find(|t| *t != "null") {
+            Some("string") => quote! { ::std::string::String },
+            Some("boolean") => quote! { bool },
+            Some("integer") => match prop.get("format").and_then(Value::as_str) {
+                Some("int8") => quote! { i8 },
+                Some("int16") => quote! { i16 },
+                Some("int32") => quote! { i32 },
+                Some("uint8") => quote! { u8 },
+                Some("uint16") => quote! { u16 },
+                Some("uint32") => quote! { u32 },
+                Some("uint64") => quote! { u64 },
+                _ => quote! { i64 },
+            },
+            Some("number") => match prop.get("format").and_then(Value::as_str) {
+                Some("float") => quote! { f32 },
+                _ => quote! { f64 },
+            },
+            Some("array") => match prop.get("items") {
+                Some(items) => {
+                    let (elem, nullable) = self.field_type(&format!("{hint}Item"), items)?;
+                    let elem = option_if(elem, nullable);
+                    quote! { ::std::vec::Vec<#elem> }
+                }
+                None => quote! { ::std::vec::Vec<::sonic_rs::Value> },
+            },
+            Some("object") if prop.contains_key("properties") => {
+                let name = Ident::new(hint, Span::call_site());
+                self.gen_struct(&name, prop)?;
+                quote! { #name }
+            }
+            Some("object") | None => quote! { ::sonic_rs::Value },
+            Some(t) => return Err(self.error(format!("unknown type `{t}` in {hint}"))),
+        };
+        Ok((ty, nullable))
+    }
+}
+
+fn option_if(ty: TokenStream2, nullable: bool) -> TokenStream2 {
+    if nullable {
+        quote! { ::core::option::Option<#ty> }
+    } else {
+        ty
+    }
+}
+
+// `"type": "string"` or `"type": ["string", "null"]`
+fn types(schema: &Map<String, Value>) -> impl Iterator<Item = &str> {
+    let types = match schema.get("type") {
+        Some(t @ Value::String(_)) => std::slice::from_ref(t),
+        Some(Value::Array(types)) => types.as_slice(),
+        _ => &[],
+    };
+    types.iter().filter_map(Value::as_str)
+}
+
+fn object_type(schema: &Map<String, Value>) -> bool {
+    types(schema).any(|t| t == "object") || schema.contains_key("properties")
+}
+
+fn description(schema: &Map<String, Value>) -> Option<TokenStream2> {
+    let desc = schema.get("description")?.as_str()?;
+    Some(quote! { #[doc = #desc] })
+}
+
+// Convert the JSON key into a snake case field name, such as `userId` into `user_id`.
+fn field_ident(key: &str) -> Ident {
+    let mut name = String::with_capacity(key.len() + 4);
+    let mut prev_lower = false;
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            name.push(c);
+        } else {
+            name.push('_');
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+    }
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+
+    match name.as_str() {
+        "_" | "self" | "Self" | "super" | "crate" => format_ident!("{name}_"),
+        _ => syn::parse_str::<Ident>(&name)
+            .unwrap_or_else(|_| Ident::new_raw(&name, Span::call_site())),
+    }
+}
+
+// Convert the JSON key into a part of the struct name, such as `shipping_address` into
+// `ShippingAddress`.
+fn pascal_case(key: &str) -> String {
+    let mut name = String::with_capacity(key.len());
+    for part in key.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            name.push(first.to_ascii_uppercase());
+            name.extend(chars);
+        }
+    }
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert_str(0, "Field");
+    }
+    name
+}
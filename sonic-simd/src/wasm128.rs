@@ -0,0 +1,140 @@
+use std::{
+    arch::wasm32::*,
+    ops::{BitAnd, BitOr, BitOrAssign},
+};
+
+use super::{Mask, Simd};
+
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct Simd128i(v128);
+
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct Simd128u(v128);
+
+impl Simd for Simd128i {
+    const LANES: usize = 16;
+    type Mask = Mask128;
+    type Element = i8;
+
+    #[inline(always)]
+    unsafe fn loadu(ptr: *const u8) -> Self {
+        Self(v128_load(ptr as *const v128))
+    }
+
+    #[inline(always)]
+    unsafe fn storeu(&self, ptr: *mut u8) {
+        v128_store(ptr as *mut v128, self.0)
+    }
+
+    #[inline(always)]
+    fn eq(&self, rhs: &Self) -> Self::Mask {
+        Mask128(i8x16_eq(self.0, rhs.0))
+    }
+
+    #[inline(always)]
+    fn splat(elem: i8) -> Self {
+        Self(i8x16_splat(elem))
+    }
+
+    #[inline(always)]
+    fn le(&self, rhs: &Self) -> Self::Mask {
+        Mask128(i8x16_le(self.0, rhs.0))
+    }
+
+    #[inline(always)]
+    fn gt(&self, rhs: &Self) -> Self::Mask {
+        Mask128(i8x16_gt(self.0, rhs.0))
+    }
+}
+
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct Mask128(pub(crate) v128);
+
+impl Mask for Mask128 {
+    type BitMask = u16;
+    type Element = u8;
+
+    #[inline(always)]
+    fn bitmask(self) -> Self::BitMask {
+        u8x16_bitmask(self.0)
+    }
+
+    #[inline(always)]
+    fn splat(b: bool) -> Self {
+        let v: i8 = if b { -1 } else { 0 };
+        Mask128(i8x16_splat(v))
+    }
+}
+
+impl BitAnd<Mask128> for Mask128 {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitand(self, rhs: Mask128) -> Self::Output {
+        Mask128(v128_and(self.0, rhs.0))
+    }
+}
+
+impl BitOr<Mask128> for Mask128 {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitor(self, rhs: Mask128) -> Self::Output {
+        Mask128(v128_or(self.0, rhs.0))
+    }
+}
+
+impl BitOrAssign<Mask128> for Mask128 {
+    #[inline(always)]
+    fn bitor_assign(&mut self, rhs: Mask128) {
+        self.0 = v128_or(self.0, rhs.0);
+    }
+}
+
+impl Simd for Simd128u {
+    const LANES: usize = 16;
+    type Mask = Mask128;
+    type Element = u8;
+
+    #[inline(always)]
+    unsafe fn loadu(ptr: *const u8) -> Self {
+        Self(v128_load(ptr as *const v128))
+    }
+
+    #[inline(always)]
+    unsafe fn storeu(&self, ptr: *mut u8) {
+        v128_store(ptr as *mut v128, self.0)
+    }
+
+    #[inline(always)]
+    fn eq(&self, rhs: &Self) -> Self::Mask {
+        Mask128(u8x16_eq(self.0, rhs.0))
+    }
+
+    #[inline(always)]
+    fn splat(ch: u8) -> Self {
+        Self(u8x16_splat(ch))
+    }
+
+    #[inline(always)]
+    fn le(&self, rhs: &Self) -> Self::Mask {
+        Mask128(u8x16_le(self.0, rhs.0))
+    }
+
+    #[inline(always)]
+    fn gt(&self, rhs: &Self) -> Self::Mask {
+        Mask128(u8x16_gt(self.0, rhs.0))
+    }
+}
+
+/// Combine the four 16-lanes masks into a 64-bits bitmask.
+#[inline(always)]
+pub fn to_bitmask64(v0: v128, v1: v128, v2: v128, v3: v128) -> u64 {
+    (u8x16_bitmask(v0) as u64)
+        | ((u8x16_bitmask(v1) as u64) << 16)
+        | ((u8x16_bitmask(v2) as u64) << 32)
+        | ((u8x16_bitmask(v3) as u64) << 48)
+}
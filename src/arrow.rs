@@ -0,0 +1,376 @@
+//! Convert the JSON array of flat objects into arrow [`RecordBatch`], which is enabled by the
+//! `arrow` feature.
+//!
+//! The rows are traversed by the lazy iterators, and the arrays are built from the raw JSON
+//! values directly, without building the intermediate [`Value`][`crate::Value`]s.
+
+use std::{collections::HashMap, sync::Arc};
+
+use arrow_array::{
+    types::{
+        Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type,
+        UInt32Type, UInt64Type, UInt8Type,
+    },
+    ArrayRef, BooleanArray, LargeStringArray, NullArray, PrimitiveArray, RecordBatch,
+    RecordBatchOptions, StringArray,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use serde::de::Error as _;
+
+use crate::{
+    error::{Error, Result},
+    input::JsonInput,
+    lazyvalue::{to_array_iter, FromLazyValue},
+    serde::tri,
+    JsonType, JsonValueTrait, LazyValue,
+};
+
+// The values of a field in all rows, `None` if the field is missing in the row.
+struct Cells<'de> {
+    name: String,
+    values: Vec<Option<LazyValue<'de>>>,
+}
+
+// Collect the fields of the rows. If `schema` is `None`, all the fields are collected in the
+// order of their first occurrences.
+fn collect_rows<'de, I: JsonInput<'de>>(
+    json: I,
+    schema: Option<&Schema>,
+) -> Result<(Vec<Cells<'de>>, usize)> {
+    let mut columns: Vec<Cells<'de>> = Vec::new();
+    let mut indexes: HashMap<String, usize> = HashMap::new();
+    if let Some(schema) = schema {
+        for (i, field) in schema.fields().iter().enumerate() {
+            indexes.insert(field.name().clone(), i);
+            columns.push(Cells {
+                name: field.name().clone(),
+                values: Vec::new(),
+            });
+        }
+    }
+
+    let mut rows = 0;
+    for elem in to_array_iter(json) {
+        let elem = tri!(elem);
+        if !elem.is_object() {
+            return Err(Error::custom(format!(
+                "expected a JSON object in row {rows}, found {}",
+                elem.as_raw_str()
+            )));
+        }
+        for entry in elem.into_object_iter().into_iter().flatten() {
+            let (key, value) = tri!(entry);
+            let idx = match indexes.get(&*key) {
+                Some(idx) => *idx,
+                None if schema.is_none() => {
+                    indexes.insert(key.to_string(), columns.len());
+                    columns.push(Cells {
+                        name: key.to_string(),
+                        values: vec![None; rows],
+                    });
+                    columns.len() - 1
+                }
+                None => continue,
+            };
+            // the last one is used if the field is duplicated
+            let values = &mut columns[idx].values;
+            if values.len() > rows {
+                values[rows] = Some(value);
+            } else {
+                values.push(Some(value));
+            }
+        }
+        rows += 1;
+        for col in columns.iter_mut() {
+            if col.values.len() < rows {
+                col.values.push(None);
+            }
+        }
+    }
+    Ok((columns, rows))
+}
+
+fn infer_type(cells: &Cells<'_>) -> DataType {
+    let mut ty = DataType::Null;
+    for lv in cells.values.iter().flatten() {
+        let cur = match lv.get_type() {
+            JsonType::Null => continue,
+            JsonType::Boolean => DataType::Boolean,
+            JsonType::Number if lv.as_i64().is_some() => DataType::Int64,
+            JsonType::Number => DataType::Float64,
+            _ => DataType::Utf8,
+        };
+        ty = match (ty, cur) {
+            (DataType::Null, cur) => cur,
+            (ty, cur) if ty == cur => ty,
+            (DataType::Int64, DataType::Float64) | (DataType::Float64, DataType::Int64) => {
+                DataType::Float64
+            }
+            _ => DataType::Utf8,
+        };
+    }
+    ty
+}
+
+fn extract<T: FromLazyValue>(cells: &Cells<'_>) -> Result<Vec<Option<T>>> {
+    cells
+        .values
+        .iter()
+        .map(|lv| match lv {
+            Some(lv) => Option::<T>::from_lazy_value(lv).map_err(|e| {
+                Error::custom(format!("invalid value in field {:?}: {e}", cells.name))
+            }),
+            None => Ok(None),
+        })
+        .collect()
+}
+
+// The strings are unescaped, and the other values are kept as the raw JSON text.
+fn extract_str<'a>(cells: &'a Cells<'_>) -> Vec<Option<&'a str>> {
+    cells
+        .values
+        .iter()
+        .map(|lv| match lv {
+            Some(lv) if lv.is_str() => lv.as_str(),
+            Some(lv) if !lv.is_null() => Some(lv.as_raw_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn build_array(cells: &Cells<'_>, ty: &DataType) -> Result<ArrayRef> {
+    macro_rules! primitive {
+        ($ty:ty, $native:ty) => {
+            Arc::new(PrimitiveArray::<$ty>::from(tri!(extract::<$native>(cells)))) as ArrayRef
+        };
+    }
+
+    let array = match ty {
+        DataType::Null => Arc::new(NullArray::new(cells.values.len())) as ArrayRef,
+        DataType::Boolean => Arc::new(BooleanArray::from(tri!(extract::<bool>(cells)))),
+        DataType::Int8 => primitive!(Int8Type, i8),
+        DataType::Int16 => primitive!(Int16Type, i16),
+        DataType::Int32 => primitive!(Int32Type, i32),
+        DataType::Int64 => primitive!(Int64Type, i64),
+        DataType::UInt8 => primitive!(UInt8Type, u8),
+        DataType::UInt16 => primitive!(UInt16Type, u16),
+        DataType::UInt32 => primitive!(UInt32Type, u32),
+        DataType::UInt64 => primitive!(UInt64Type, u64),
+        DataType::Float32 => primitive!(Float32Type, f32),
+        DataType::Float64 => primitive!(Float64Type, f64),
+        DataType::Utf8 => Arc::new(StringArray::from(extract_str(cells))),
+        DataType::LargeUtf8 => Arc::new(LargeStringArray::from(extract_str(cells))),
+        _ => {
+            return Err(Error::custom(format!(
+                "unsupported data type {ty} of field {:?}",
+                cells.name
+            )))
+        }
+    };
+    Ok(array)
+}
+
+fn build_batch(columns: &[Cells<'_>], rows: usize, schema: SchemaRef) -> Result<RecordBatch> {
+    let mut arrays = Vec::with_capacity(columns.len());
+    for (cells, field) in columns.iter().zip(schema.fields().iter()) {
+        arrays.push(tri!(build_array(cells, field.data_type())));
+    }
+    let options = RecordBatchOptions::new().with_row_count(Some(rows));
+    RecordBatch::try_new_with_options(schema, arrays, &options).map_err(Error::custom)
+}
+
+/// Infer the arrow schema from the JSON array of flat objects.
+///
+/// The fields are in the order of their first occurrences, and all of them are nullable. The
+/// data type of a field is inferred from its non-null values:
+///
+/// - `Boolean` for the booleans.
+/// - `Int64` for the integers in the `i64` range, and `Float64` if there are other numbers.
+/// - `Utf8` for the strings, or the mixed types and the nested arrays or objects, which are kept as
+///   the raw JSON text.
+/// - `Null` if all the values are null or missing.
+///
+/// # Examples
+///
+/// ```
+/// use arrow_schema::DataType;
+/// use sonic_rs::arrow::infer_schema;
+///
+/// let schema = infer_schema(r#"[{"a": 1, "b": "x"}, {"a": 1.5, "c": null}]"#).unwrap();
+/// assert_eq!(schema.field(0).data_type(), &DataType::Float64);
+/// assert_eq!(schema.field(1).data_type(), &DataType::Utf8);
+/// assert_eq!(schema.field(2).data_type(), &DataType::Null);
+/// ```
+pub fn infer_schema<'de, I: JsonInput<'de>>(json: I) -> Result<Schema> {
+    let (columns, _) = tri!(collect_rows(json, None));
+    Ok(infer_fields(&columns))
+}
+
+fn infer_fields(columns: &[Cells<'_>]) -> Schema {
+    let fields: Vec<Field> = columns
+        .iter()
+        .map(|cells| Field::new(cells.name.clone(), infer_type(cells), true))
+        .collect();
+    Schema::new(fields)
+}
+
+/// Convert the JSON array of flat objects into a [`RecordBatch`], with the schema inferred as
+/// [`infer_schema`].
+///
+/// # Errors
+///
+/// Return an error if the JSON is invalid, not an array or has non-object elements.
+///
+/// # Examples
+///
+/// ```
+/// use arrow_array::{cast::AsArray, types::Int64Type};
+/// use sonic_rs::arrow::to_record_batch;
+///
+/// let batch = to_record_batch(r#"[{"id": 1, "tags": ["a"]}, {"id": 2}]"#).unwrap();
+/// assert_eq!(batch.num_rows(), 2);
+/// let ids = batch.column(0).as_primitive::<Int64Type>();
+/// assert_eq!(&ids.values()[..], &[1, 2]);
+/// assert_eq!(batch.column(1).as_string::<i32>().value(0), r#"["a"]"#);
+/// ```
+pub fn to_record_batch<'de, I: JsonInput<'de>>(json: I) -> Result<RecordBatch> {
+    let (columns, rows) = tri!(collect_rows(json, None));
+    let schema = Arc::new(infer_fields(&columns));
+    build_batch(&columns, rows, schema)
+}
+
+/// Convert the JSON array of flat objects into a [`RecordBatch`] with the given `schema`. The
+/// fields not in the schema are skipped.
+///
+/// The supported data types are `Null`, `Boolean`, the integers, `Float32`, `Float64`, `Utf8`
+/// and `LargeUtf8`. The JSON `null` and the missing fields are converted into nulls. For the
+/// string types, the non-string values are kept as the raw JSON text.
+///
+/// # Errors
+///
+/// Return an error if the JSON is invalid, a value doesn't match the data type of its field, or
+/// a non-nullable field has nulls.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use arrow_array::{cast::AsArray, types::UInt8Type, Array};
+/// use arrow_schema::{DataType, Field, Schema};
+/// use sonic_rs::arrow::to_record_batch_with_schema;
+///
+/// let schema = Arc::new(Schema::new(vec![
+///     Field::new("age", DataType::UInt8, false),
+///     Field::new("name", DataType::Utf8, true),
+/// ]));
+/// let json = r#"[{"name": "a", "age": 18, "extra": 1}, {"age": 20}]"#;
+/// let batch = to_record_batch_with_schema(json, schema.clone()).unwrap();
+/// assert_eq!(
+///     &batch.column(0).as_primitive::<UInt8Type>().values()[..],
+///     &[18, 20]
+/// );
+/// assert!(batch.column(1).is_null(1));
+///
+/// assert!(to_record_batch_with_schema(r#"[{"age": 256}]"#, schema).is_err());
+/// ```
+pub fn to_record_batch_with_schema<'de, I: JsonInput<'de>>(
+    json: I,
+    schema: SchemaRef,
+) -> Result<RecordBatch> {
+    let (columns, rows) = tri!(collect_rows(json, Some(&*schema)));
+    build_batch(&columns, rows, schema)
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{cast::AsArray, Array};
+
+    use super::*;
+
+    #[test]
+    fn test_to_record_batch() {
+        let json = r#"[
+            {"i": 1, "f": 1, "b": true, "s": "a\"b", "m": 1, "n": null},
+            {"i": -2, "f": 2.5, "s": "c", "m": "x", "o": {"k": [1]}},
+            {"i": 3, "i": 4, "b": false, "m": [true]}
+        ]"#;
+        let batch = to_record_batch(json).unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        let schema = batch.schema();
+        let types: Vec<(&str, &DataType)> = schema
+            .fields()
+            .iter()
+            .map(|f| (f.name().as_str(), f.data_type()))
+            .collect();
+        assert_eq!(
+            types,
+            [
+                ("i", &DataType::Int64),
+                ("f", &DataType::Float64),
+                ("b", &DataType::Boolean),
+                ("s", &DataType::Utf8),
+                ("m", &DataType::Utf8),
+                ("n", &DataType::Null),
+                ("o", &DataType::Utf8),
+            ]
+        );
+
+        let col = |name: &str| batch.column_by_name(name).unwrap();
+        assert_eq!(
+            &col("i").as_primitive::<Int64Type>().values()[..],
+            &[1, -2, 4]
+        );
+        let f = col("f").as_primitive::<Float64Type>();
+        assert_eq!((f.value(0), f.value(1)), (1.0, 2.5));
+        assert!(f.is_null(2));
+        let b = col("b").as_boolean();
+        assert!(b.value(0) && b.is_null(1) && !b.value(2));
+        let s = col("s").as_string::<i32>();
+        assert_eq!((s.value(0), s.value(1)), ("a\"b", "c"));
+        assert!(s.is_null(2));
+        let m = col("m").as_string::<i32>();
+        assert_eq!((m.value(0), m.value(1), m.value(2)), ("1", "x", "[true]"));
+        assert_eq!(col("n").null_count(), 3);
+        assert_eq!(col("o").as_string::<i32>().value(1), r#"{"k": [1]}"#);
+
+        let batch = to_record_batch("[{}, {}]").unwrap();
+        assert_eq!((batch.num_rows(), batch.num_columns()), (2, 0));
+
+        assert!(to_record_batch("[1]").is_err());
+        assert!(to_record_batch(r#"{"a": 1}"#).is_err());
+        assert!(to_record_batch(r#"[{"a": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_to_record_batch_with_schema() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Float32, true),
+            Field::new("c", DataType::LargeUtf8, true),
+            Field::new("d", DataType::UInt64, false),
+        ]));
+        let json = r#"[{"a": 1, "b": 0.5, "c": "x", "d": 18446744073709551615, "e": 1},
+            {"a": null, "c": 1, "d": 0}]"#;
+        let batch = to_record_batch_with_schema(json, schema.clone()).unwrap();
+        assert_eq!(batch.schema(), schema);
+        assert_eq!(batch.column(0).as_primitive::<Int32Type>().value(0), 1);
+        assert!(batch.column(0).is_null(1));
+        assert_eq!(batch.column(1).as_primitive::<Float32Type>().value(0), 0.5);
+        let c = batch.column(2).as_string::<i64>();
+        assert_eq!((c.value(0), c.value(1)), ("x", "1"));
+        assert_eq!(
+            &batch.column(3).as_primitive::<UInt64Type>().values()[..],
+            &[u64::MAX, 0]
+        );
+
+        // mismatched types
+        assert!(to_record_batch_with_schema(r#"[{"a": "1"}]"#, schema.clone()).is_err());
+        assert!(to_record_batch_with_schema(r#"[{"a": 1.5}]"#, schema.clone()).is_err());
+        // the non-nullable field is missing
+        assert!(to_record_batch_with_schema(r#"[{"a": 1}]"#, schema).is_err());
+        // unsupported data type
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Date32, true)]));
+        assert!(to_record_batch_with_schema(r#"[{"a": 1}]"#, schema).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+//! Convert the JSON array of objects into columns.
+
+use std::{borrow::Cow, collections::HashMap};
+
+use serde::de::{Error as _, Unexpected};
+
+use crate::{
+    error::{Error, Result},
+    input::{JsonInput, JsonSlice},
+    parser::{as_str, Pair, Parser},
+    reader::{Read, Reader},
+    serde::tri,
+    util::scratch::ScratchBuf,
+    JsonValueTrait, LazyValue,
+};
+
+/// The values of a field in all rows. The row is `None` in the typed views if the field is
+/// missing, `null` or has another JSON type.
+#[derive(Debug, Clone, Default)]
+pub struct Column<'de> {
+    name: String,
+    numbers: Vec<Option<f64>>,
+    strings: Vec<Option<Cow<'de, str>>>,
+}
+
+impl<'de> Column<'de> {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// The field name of the column.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The count of rows in the column.
+    pub fn len(&self) -> usize {
+        self.numbers.len()
+    }
+
+    /// Whether the column has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.numbers.is_empty()
+    }
+
+    /// The number values of the column, as `f64`.
+    pub fn as_f64s(&self) -> &[Option<f64>] {
+        &self.numbers
+    }
+
+    /// The string values of the column. The strings without escaped chars borrow the JSON text.
+    pub fn as_strs(&self) -> Vec<Option<&str>> {
+        self.strings.iter().map(|s| s.as_deref()).collect()
+    }
+
+    fn push(&mut self, lv: Option<&LazyValue<'de>>) {
+        let (num, s) = match lv {
+            Some(lv) if lv.is_number() => (lv.as_f64(), None),
+            Some(lv) if lv.is_str() => (None, borrow_str(lv)),
+            _ => (None, None),
+        };
+        self.numbers.push(num);
+        self.strings.push(s);
+    }
+}
+
+// Borrow the string from the origin JSON if it has no escaped chars.
+fn borrow_str<'de>(lv: &LazyValue<'de>) -> Option<Cow<'de, str>> {
+    match &lv.raw {
+        JsonSlice::Raw(raw) if lv.inner.no_escaped() => {
+            let raw: &'de [u8] = raw;
+            // # Safety
+            // the JSON text is validated by the array iterator
+            Some(Cow::Borrowed(unsafe {
+                std::str::from_utf8_unchecked(&raw[1..raw.len() - 1])
+            }))
+        }
+        _ => lv.as_str().map(|s| Cow::Owned(s.to_string())),
+    }
+}
+
+/// The columns of the requested fields, converted from a JSON array of objects.
+#[derive(Debug, Clone, Default)]
+pub struct Columns<'de> {
+    rows: usize,
+    columns: Vec<Column<'de>>,
+}
+
+impl<'de> Columns<'de> {
+    /// The count of rows, which is the length of the JSON array.
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    /// Whether the JSON array is empty.
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Get the column by the field name.
+    pub fn get(&self, name: &str) -> Option<&Column<'de>> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// All the columns, in the order of the requested fields.
+    pub fn columns(&self) -> &[Column<'de>] {
+        &self.columns
+    }
+}
+
+/// Convert the JSON array of objects into the columns of `fields`, in one pass of the JSON text
+/// without building the per-row values. The fields are looked up by a hash map.
+///
+/// The unrequested fields are skipped, and the last one is used if a field is duplicated in an
+/// object.
+///
+/// # Errors
+///
+/// Return an error if the JSON is invalid, not an array, or has non-object elements.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::columns::from_array;
+///
+/// let json = r#"[
+///     {"name": "a", "price": 1.5},
+///     {"price": 2, "name": "b!", "extra": [1, 2]},
+///     {"name": null}
+/// ]"#;
+/// let columns = from_array(json, &["name", "price"]).unwrap();
+/// assert_eq!(columns.len(), 3);
+/// assert_eq!(
+///     columns.get("name").unwrap().as_strs(),
+///     [Some("a"), Some("b!"), None]
+/// );
+/// assert_eq!(
+///     columns.get("price").unwrap().as_f64s(),
+///     [Some(1.5), Some(2.0), None]
+/// );
+/// ```
+pub fn from_array<'de, I: JsonInput<'de>>(json: I, fields: &[&str]) -> Result<Columns<'de>> {
+    let mut columns = Columns {
+        rows: 0,
+        columns: fields.iter().map(|f| Column::new(f)).collect(),
+    };
+    // the first column is used if a field is requested twice
+    let mut index: HashMap<&str, usize> = HashMap::with_capacity(fields.len());
+    for (i, field) in fields.iter().enumerate() {
+        index.entry(*field).or_insert(i);
+    }
+    let mut row: Vec<Option<LazyValue<'de>>> = vec![None; fields.len()];
+
+    // the entries are parsed from the array directly, so each element is scanned once
+    let validate_utf8 = json.need_utf8_valid();
+    let mut parser = Parser::new(Read::new_in(json.to_json_slice(), validate_utf8));
+    tri!(parser.read.check_utf8_final());
+    let mut strbuf = ScratchBuf::new();
+    let mut first = true;
+    while tri!(parser.parse_elem_start(&mut first)) {
+        if parser.skip_space_peek() != Some(b'{') {
+            let (raw, _) = tri!(parser.skip_one());
+            return Err(Error::invalid_type(
+                Unexpected::Other(as_str(raw)),
+                &"an object",
+            ));
+        }
+        let mut first_entry = true;
+        while let Some(Pair { key, val, status }) =
+            tri!(parser.parse_entry_lazy(&mut strbuf, &mut first_entry, true))
+        {
+            if let Some(&i) = index.get(&*key) {
+                let val = parser.read.slice_ref(val);
+                row[i] = Some(LazyValue::new(val, status.into()));
+            }
+        }
+        for (col, cell) in columns.columns.iter_mut().zip(row.iter_mut()) {
+            col.push(cell.take().as_ref());
+        }
+        columns.rows += 1;
+    }
+    Ok(columns)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_array() {
+        let json = r#"[{"a": 1, "b": "x", "c": true}, {}, {"b": "\"y\"", "a": -2.5e1, "a": 3},
+            {"a": "1", "b": 2}]"#;
+        let columns = from_array(json.as_bytes(), &["a", "b", "d"]).unwrap();
+        assert_eq!(columns.len(), 4);
+        let names: Vec<&str> = columns.columns().iter().map(|c| c.name()).collect();
+        assert_eq!(names, ["a", "b", "d"]);
+
+        let a = columns.get("a").unwrap();
+        assert_eq!(a.as_f64s(), [Some(1.0), None, Some(3.0), None]);
+        assert_eq!(a.as_strs(), [None, None, None, Some("1")]);
+        let b = columns.get("b").unwrap();
+        assert_eq!(b.as_strs(), [Some("x"), None, Some("\"y\""), None]);
+        assert_eq!(b.as_f64s(), [None, None, None, Some(2.0)]);
+        assert!(matches!(b.strings[0], Some(Cow::Borrowed(_))));
+        assert!(matches!(b.strings[2], Some(Cow::Owned(_))));
+        let d = columns.get("d").unwrap();
+        assert_eq!(d.len(), 4);
+        assert!(d.as_f64s().iter().all(Option::is_none));
+        assert!(columns.get("c").is_none());
+
+        // the escaped keys, and the field requested twice
+        let columns = from_array(r#"[{"a\u0062": 1}]"#, &["ab", "ab"]).unwrap();
+        assert_eq!(columns.columns()[0].as_f64s(), [Some(1.0)]);
+        assert_eq!(columns.columns()[1].as_f64s(), [None]);
+
+        let columns = from_array("[]", &["a"]).unwrap();
+        assert!(columns.is_empty());
+        assert!(columns.get("a").unwrap().is_empty());
+
+        assert!(from_array("[{}, 1]", &["a"]).is_err());
+        assert!(from_array(r#"{"a": 1}"#, &["a"]).is_err());
+        assert!(from_array(r#"[{"a": 1}"#, &["a"]).is_err());
+    }
+}
@@ -0,0 +1,429 @@
+//! Compare the JSON texts in one streaming pass, without building a DOM.
+
+use std::borrow::Cow;
+
+use crate::{
+    error::{ErrorCode, Result},
+    parser::{as_str, ParsedSlice, Parser},
+    reader::{Read, Reader},
+    serde::{de::MAX_ALLOWED_DEPTH, rawnumber::number_text_eq},
+    util::{scratch::ScratchBuf, utf8::from_utf8},
+};
+
+// The texts are walked in lockstep: `a` is checked by the grammar, and `b` should have the same
+// tokens as `a`.
+struct Lockstep<'de> {
+    a: Parser<Read<'de>>,
+    b: Parser<Read<'de>>,
+    buf_a: ScratchBuf,
+    buf_b: ScratchBuf,
+    // the open containers, `true` for the objects
+    stack: Vec<bool>,
+    // the count of the containers around the compared values
+    depth: usize,
+    unordered: bool,
+}
+
+// Skip the number whose first char has been read, and return its text.
+fn number<'de>(p: &mut Parser<Read<'de>>, first: u8) -> Result<&'de str> {
+    let start = p.read.index() - 1;
+    p.skip_number(first)?;
+    Ok(as_str(p.read.slice_unchecked(start, p.read.index())))
+}
+
+impl<'de> Lockstep<'de> {
+    fn new(a: &'de [u8], b: &'de [u8], unordered: bool, depth: usize) -> Self {
+        Self {
+            a: Parser::new(Read::new(a, false)),
+            b: Parser::new(Read::new(b, false)),
+            buf_a: ScratchBuf::new(),
+            buf_b: ScratchBuf::new(),
+            stack: Vec::new(),
+            depth,
+            unordered,
+        }
+    }
+
+    // Check the depth before opening a container, and return the count of the containers around
+    // its values.
+    fn enter(&self) -> Result<usize> {
+        let depth = self.depth + self.stack.len() + 1;
+        if depth > MAX_ALLOWED_DEPTH as usize {
+            return Err(self.a.error(ErrorCode::RecursionLimitExceeded));
+        }
+        Ok(depth)
+    }
+
+    // Compare the strings after the quotes, the escapes are compared by the unescaped chars.
+    fn string_eq(&mut self) -> Result<bool> {
+        let a = self.a.parse_string_raw(&mut self.buf_a)?;
+        let b = self.b.parse_string_raw(&mut self.buf_b)?;
+        Ok(*a == *b)
+    }
+
+    // Compare the keys after the quotes, and skip the colons.
+    fn key_eq(&mut self) -> Result<bool> {
+        if !self.string_eq()? {
+            return Ok(false);
+        }
+        self.a.parse_object_clo()?;
+        self.b.parse_object_clo()?;
+        Ok(true)
+    }
+
+    fn compare(&mut self) -> Result<bool> {
+        'value: loop {
+            if self.unordered && self.a.skip_space_peek() == Some(b'{') {
+                if self.b.skip_space_peek() != Some(b'{') {
+                    return Ok(false);
+                }
+                let depth = self.enter()?;
+                let a = entries(&mut self.a)?;
+                let b = entries(&mut self.b)?;
+                if !entries_eq_unordered(a, b, depth)? {
+                    return Ok(false);
+                }
+            } else {
+                let ca = self.a.skip_space();
+                let cb = self.b.skip_space();
+                match ca {
+                    Some(b'{') => {
+                        let na = self.a.skip_space();
+                        match na {
+                            Some(b'"' | b'}') => {}
+                            None => return Err(self.a.error(ErrorCode::EofWhileParsing)),
+                            Some(_) => return Err(self.a.error(ErrorCode::ExpectObjectKeyOrEnd)),
+                        }
+                        if cb != ca || self.b.skip_space() != na {
+                            return Ok(false);
+                        }
+                        if na == Some(b'"') {
+                            self.enter()?;
+                            if !self.key_eq()? {
+                                return Ok(false);
+                            }
+                            self.stack.push(true);
+                            continue 'value;
+                        }
+                    }
+                    Some(b'[') => {
+                        let na = self.a.skip_space_peek();
+                        if na.is_none() {
+                            return Err(self.a.error(ErrorCode::EofWhileParsing));
+                        }
+                        let nb = self.b.skip_space_peek();
+                        if cb != ca || (na == Some(b']')) != (nb == Some(b']')) {
+                            return Ok(false);
+                        }
+                        if na != Some(b']') {
+                            self.enter()?;
+                            self.stack.push(false);
+                            continue 'value;
+                        }
+                        self.a.read.eat(1);
+                        self.b.read.eat(1);
+                    }
+                    Some(b'"') => {
+                        if cb != ca || !self.string_eq()? {
+                            return Ok(false);
+                        }
+                    }
+                    Some(c @ (b'-' | b'0'..=b'9')) => {
+                        let a = number(&mut self.a, c)?;
+                        let Some(d @ (b'-' | b'0'..=b'9')) = cb else {
+                            return Ok(false);
+                        };
+                        let b = number(&mut self.b, d)?;
+                        if !number_text_eq(a, b) {
+                            return Ok(false);
+                        }
+                    }
+                    Some(c @ (b't' | b'f' | b'n')) => {
+                        let rest = match c {
+                            b't' => "rue",
+                            b'f' => "alse",
+                            _ => "ull",
+                        };
+                        self.a.parse_literal(rest)?;
+                        if cb != ca {
+                            return Ok(false);
+                        }
+                        self.b.parse_literal(rest)?;
+                    }
+                    Some(_) => return Err(self.a.error(ErrorCode::InvalidJsonValue)),
+                    None => return Err(self.a.error(ErrorCode::EofWhileParsing)),
+                }
+            }
+
+            // close the containers after the value
+            loop {
+                let Some(is_object) = self.stack.last().copied() else {
+                    self.a.parse_trailing()?;
+                    self.b.parse_trailing()?;
+                    return Ok(true);
+                };
+                let ca = self.a.skip_space();
+                match ca {
+                    Some(b',') => {
+                        if self.b.skip_space() != ca {
+                            return Ok(false);
+                        }
+                        if is_object {
+                            if self.a.skip_space() != Some(b'"') {
+                                return Err(self.a.error(ErrorCode::ExpectObjectKeyOrEnd));
+                            }
+                            if self.b.skip_space() != Some(b'"') || !self.key_eq()? {
+                                return Ok(false);
+                            }
+                        }
+                        continue 'value;
+                    }
+                    Some(c @ (b'}' | b']')) if (c == b'}') == is_object => {
+                        if self.b.skip_space() != ca {
+                            return Ok(false);
+                        }
+                        self.stack.pop();
+                    }
+                    None => return Err(self.a.error(ErrorCode::EofWhileParsing)),
+                    Some(_) if is_object => {
+                        return Err(self.a.error(ErrorCode::ExpectedObjectCommaOrEnd))
+                    }
+                    Some(_) => return Err(self.a.error(ErrorCode::ExpectedArrayCommaOrEnd)),
+                }
+            }
+        }
+    }
+}
+
+// The unescaped key and the raw value of an object entry.
+type Entry<'a> = (Cow<'a, [u8]>, &'a [u8]);
+
+// Parse the entries of the object at the start of `p`. The values are skipped by the brackets
+// without the recursion, and their grammar is checked when they are compared.
+fn entries<'de>(p: &mut Parser<Read<'de>>) -> Result<Vec<Entry<'de>>> {
+    let mut buf = ScratchBuf::new();
+    let mut entries = Vec::new();
+    // skip the `{`
+    p.skip_space();
+    match p.skip_space() {
+        Some(b'"') => {}
+        Some(b'}') => return Ok(entries),
+        None => return Err(p.error(ErrorCode::EofWhileParsing)),
+        Some(_) => return Err(p.error(ErrorCode::ExpectObjectKeyOrEnd)),
+    }
+    loop {
+        let key = match p.parse_string_raw(&mut buf)? {
+            ParsedSlice::Borrowed { slice, .. } => Cow::Borrowed(slice),
+            copied => Cow::Owned(copied.to_vec()),
+        };
+        p.parse_object_clo()?;
+        let (value, _) = p.skip_one_unchecked()?;
+        entries.push((key, value));
+        match p.skip_space() {
+            Some(b',') => {
+                if p.skip_space() != Some(b'"') {
+                    return Err(p.error(ErrorCode::ExpectObjectKeyOrEnd));
+                }
+            }
+            Some(b'}') => return Ok(entries),
+            None => return Err(p.error(ErrorCode::EofWhileParsing)),
+            Some(_) => return Err(p.error(ErrorCode::ExpectedObjectCommaOrEnd)),
+        }
+    }
+}
+
+// Compare the entries sorted by the keys, the duplicate keys are compared in order. The values are
+// inside `depth` containers.
+fn entries_eq_unordered(
+    mut a: Vec<Entry<'_>>,
+    mut b: Vec<Entry<'_>>,
+    depth: usize,
+) -> Result<bool> {
+    if a.len() != b.len() {
+        return Ok(false);
+    }
+    a.sort_by(|x, y| x.0.cmp(&y.0));
+    b.sort_by(|x, y| x.0.cmp(&y.0));
+    for ((ka, va), (kb, vb)) in a.iter().zip(&b) {
+        if ka != kb || !Lockstep::new(va, vb, true, depth).compare()? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn text_eq(a: &[u8], b: &[u8], unordered: bool) -> Result<bool> {
+    if !Lockstep::new(a, b, unordered, 0).compare()? {
+        return Ok(false);
+    }
+    from_utf8(a)?;
+    from_utf8(b)?;
+    Ok(true)
+}
+
+/// Compare two JSON texts for the semantic equality, by scanning both texts in lockstep without
+/// building a DOM.
+///
+/// The whitespaces are ignored, the strings are compared after unescaping, and the numbers are
+/// compared by the decimal values, so `1`, `1.0` and `10e-1` are equal. The keys of the objects
+/// are compared in order, use [`json_text_eq_unordered`] to ignore the key order.
+///
+/// # Errors
+///
+/// Return the syntax error if the JSON is invalid. The texts are compared until the first
+/// difference, so the invalid JSON after the difference may not be reported.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::format::json_text_eq;
+///
+/// let a = br#"{"a": [1, 2.50, "\u0078"], "b": null}"#;
+/// let b = br#"{ "a":[1,2.5,"x"],"b":null }"#;
+/// assert!(json_text_eq(a, b).unwrap());
+/// assert!(!json_text_eq(a, br#"{"b": null, "a": [1, 2.5, "x"]}"#).unwrap());
+/// assert!(json_text_eq(b"[1, 2", b"[1, 2").is_err());
+/// ```
+pub fn json_text_eq(a: &[u8], b: &[u8]) -> Result<bool> {
+    text_eq(a, b, false)
+}
+
+/// Compare two JSON texts for the semantic equality as [`json_text_eq`], but ignore the key order
+/// of the objects.
+///
+/// The entries of each object are collected and sorted by the keys before being compared, and
+/// the duplicate keys are compared in order, so an object of `n` entries is compared in
+/// `O(n log n)`. The values of the entries are skipped and then scanned again, so the documents
+/// with deeply nested objects are slower to compare.
+///
+/// # Errors
+///
+/// Return the syntax error if the JSON is invalid, or the recursion limit error if the JSON is
+/// nested deeper than the limit of deserializing.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::format::json_text_eq_unordered;
+///
+/// let a = br#"{"a": {"x": 1, "y": [{"p": true, "q": false}]}, "b": null}"#;
+/// let b = br#"{"b": null, "a": {"y": [{"q": false, "p": true}], "x": 1.0}}"#;
+/// assert!(json_text_eq_unordered(a, b).unwrap());
+/// assert!(!json_text_eq_unordered(a, br#"{"a": {}, "b": null}"#).unwrap());
+/// ```
+pub fn json_text_eq_unordered(a: &[u8], b: &[u8]) -> Result<bool> {
+    text_eq(a, b, true)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_json_text_eq() {
+        let eqs = [
+            ("1", " 1.0 "),
+            ("-0", "0e5"),
+            (
+                "123456789012345678901234567890",
+                "1.2345678901234567890123456789e29",
+            ),
+            (r#""\u00e9\n""#, "\"é\\n\""),
+            ("[]", "[ ]"),
+            ("{}", "{ }"),
+            (
+                r#"{"a": [true, false, null, {"b": {}}], "c": "d"}"#,
+                "{\"a\":[true,false,null,{\"\\u0062\":{}}],\n\"c\":\"d\"}",
+            ),
+            ("[[1, [2]], [[]]]", "[[1,[2]],[[]]]"),
+        ];
+        for (a, b) in eqs {
+            assert!(json_text_eq(a.as_bytes(), b.as_bytes()).unwrap(), "{a} {b}");
+            assert!(json_text_eq(b.as_bytes(), a.as_bytes()).unwrap(), "{b} {a}");
+            assert!(
+                json_text_eq_unordered(a.as_bytes(), b.as_bytes()).unwrap(),
+                "{a} {b}"
+            );
+        }
+
+        let nes = [
+            ("1", "2"),
+            ("1", "\"1\""),
+            ("true", "false"),
+            ("null", "[]"),
+            ("[]", "[1]"),
+            ("[1]", "[1, 2]"),
+            ("{}", r#"{"a": 1}"#),
+            (r#"{"a": 1}"#, r#"{"b": 1}"#),
+            (r#"{"a": 1}"#, r#"{"a": 1, "b": 2}"#),
+            (r#"{"a": [1, {"b": "x"}]}"#, r#"{"a": [1, {"b": "y"}]}"#),
+            ("[1, 2]", "[2, 1]"),
+        ];
+        for (a, b) in nes {
+            assert!(
+                !json_text_eq(a.as_bytes(), b.as_bytes()).unwrap(),
+                "{a} {b}"
+            );
+            assert!(
+                !json_text_eq(b.as_bytes(), a.as_bytes()).unwrap(),
+                "{b} {a}"
+            );
+            assert!(
+                !json_text_eq_unordered(a.as_bytes(), b.as_bytes()).unwrap(),
+                "{a} {b}"
+            );
+        }
+
+        // the key order
+        let a = br#"[{"a": 1, "b": {"c": 2, "d": [{"e": 3, "f": 4}]}}, {}]"#;
+        let b = br#"[{"b": {"d": [{"f": 4, "e": 3.0}], "c": 2}, "a": 1}, {}]"#;
+        assert!(!json_text_eq(a, b).unwrap());
+        assert!(json_text_eq_unordered(a, b).unwrap());
+        let dups = br#"{"a": 1, "b": 2, "a": 3}"#;
+        assert!(json_text_eq_unordered(dups, br#"{"b": 2, "a": 1, "a": 3}"#).unwrap());
+        assert!(!json_text_eq_unordered(dups, br#"{"b": 2, "a": 3, "a": 1}"#).unwrap());
+
+        for invalid in [
+            "",
+            "[1,]",
+            "{\"a\" 1}",
+            "[1] x",
+            "tru",
+            "{\"a\": 1,}",
+            "\"\\x\"",
+        ] {
+            assert!(
+                json_text_eq(invalid.as_bytes(), invalid.as_bytes()).is_err(),
+                "{invalid}"
+            );
+        }
+        assert!(json_text_eq(b"[1, 2]", b"[1, 2] x").is_err());
+        assert!(json_text_eq(b"\"\xff\"", b"\"\xff\"").is_err());
+        for invalid in ["{\"a\": 1", "{\"a\": 1 2}", "{1: 2}", "{\"a\": 1,}"] {
+            assert!(
+                json_text_eq_unordered(invalid.as_bytes(), invalid.as_bytes()).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_text_eq_depth() {
+        let nested = |n: usize, open: &str, close: &str| {
+            format!("{}null{}", open.repeat(n), close.repeat(n))
+        };
+        for (open, close) in [("[", "]"), ("{\"a\":", "}"), ("[{\"a\":", "}]")] {
+            let n = MAX_ALLOWED_DEPTH as usize / close.len();
+            let ok = nested(n, open, close);
+            assert!(json_text_eq(ok.as_bytes(), ok.as_bytes()).unwrap());
+            assert!(json_text_eq_unordered(ok.as_bytes(), ok.as_bytes()).unwrap());
+
+            // the deep input is an error instead of the stack overflow
+            let deep = nested(10_000, open, close);
+            for unordered in [false, true] {
+                let err = text_eq(deep.as_bytes(), deep.as_bytes(), unordered).unwrap_err();
+                assert!(err.to_string().contains("nesting"), "{err}");
+            }
+        }
+    }
+}
@@ -1,6 +1,345 @@
-#[derive(Debug, Clone, Copy, Default)]
-pub(crate) struct DeserializeCfg {
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::Utf8Policy;
+
+/// The options of deserializing, which is used in [`from_str_with_cfg`][crate::from_str_with_cfg]
+/// and [`Deserializer::with_cfg`][crate::Deserializer::with_cfg].
+///
+/// Each option is the same as the builder method with the same name in
+/// [`Deserializer`][crate::Deserializer]. The options enabled by the crate features are always
+/// enabled.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::DeserializeCfg;
+///
+/// let mut cfg = DeserializeCfg::default();
+/// cfg.use_rawnumber = true;
+/// cfg.skip_bom = true;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DeserializeCfg {
+    /// Parse all the numbers as [`RawNumber`][crate::RawNumber].
     pub use_rawnumber: bool,
+    /// Parse all the numbers as `RawNumber` and record the raw text of all the JSON strings.
     pub use_raw: bool,
+    /// Replace the invalid UTF-8 and UTF-16 characters with `\uFFFD`. It is the same as
+    /// [`Utf8Policy::Lossy`] for the invalid UTF-8.
     pub utf8_lossy: bool,
+    /// The policy of the invalid UTF-8 in the bytes inputs, the same as
+    /// [`Deserializer::utf8_policy`][crate::Deserializer::utf8_policy].
+    pub utf8_policy: Utf8Policy,
+    /// Allow the lone UTF-16 surrogates, which are replaced with `\uFFFD` in the parsed strings,
+    /// but kept in the raw strings of [`Value`][crate::Value] when serialized again.
+    pub preserve_surrogates: bool,
+    /// Allow the hexadecimal integers and the integers with underscore separators.
+    pub extended_numbers: bool,
+    /// Skip the UTF-8 BOM at the start of the JSON text.
+    pub skip_bom: bool,
+    /// Allow the unknown escapes in the JSON strings, such as `\x41` and `\'`.
+    pub lenient_escapes: bool,
+    /// Allow the unescaped control chars in the JSON strings, such as the raw newlines and tabs.
+    pub allow_control_chars: bool,
+    /// Parse the empty strings as `None` for the `Option` fields.
+    pub empty_string_as_none: bool,
+    /// The policy of the duplicate keys in the objects parsed into [`Value`][crate::Value].
+    pub dedup_keys: DedupPolicy,
+    /// The bytes of the JSON text shown on each side of the error position in the error message,
+    /// `0` to show no JSON text. The default is `8`.
+    pub error_snippet_len: usize,
+    /// Only record the code and the offset of the errors, without the line, column and the JSON
+    /// text around the error position.
+    pub lightweight_errors: bool,
+}
+
+impl Default for DeserializeCfg {
+    fn default() -> Self {
+        Self {
+            use_rawnumber: false,
+            use_raw: false,
+            utf8_lossy: false,
+            utf8_policy: Utf8Policy::Validate,
+            preserve_surrogates: false,
+            extended_numbers: false,
+            // the BOM is skipped in all the APIs, such as `get`, if the feature is enabled
+            skip_bom: cfg!(feature = "skip_bom"),
+            lenient_escapes: false,
+            allow_control_chars: false,
+            empty_string_as_none: false,
+            dedup_keys: DedupPolicy::KeepAll,
+            error_snippet_len: crate::error::DEFAULT_SNIPPET_LEN,
+            lightweight_errors: false,
+        }
+    }
+}
+
+impl DeserializeCfg {
+    // enable the options which are enabled by the crate features
+    #[inline(always)]
+    pub(crate) fn with_features(mut self) -> Self {
+        self.use_rawnumber |= cfg!(feature = "arbitrary_precision");
+        self.use_raw |= cfg!(feature = "use_raw");
+        self.utf8_lossy |= cfg!(feature = "utf8_lossy");
+        self.skip_bom |= cfg!(feature = "skip_bom");
+        self.resolve_utf8_policy()
+    }
+
+    // make `utf8_lossy` and `utf8_policy` agree, because the parser only checks `utf8_lossy`
+    #[inline(always)]
+    pub(crate) fn resolve_utf8_policy(mut self) -> Self {
+        if self.utf8_lossy && self.utf8_policy == Utf8Policy::Validate {
+            self.utf8_policy = Utf8Policy::Lossy;
+        }
+        self.utf8_lossy = self.utf8_policy == Utf8Policy::Lossy;
+        self
+    }
+
+    // Pack the options into the bits, which are passed to the visitors of the raw spans, such as
+    // `Untagged`, to parse the spans again with the same options. The `skip_bom` is not packed,
+    // because the spans are not at the start of the input.
+    pub(crate) fn to_bits(self) -> u64 {
+        let flags = [
+            self.use_rawnumber,
+            self.use_raw,
+            self.utf8_lossy,
+            self.preserve_surrogates,
+            self.extended_numbers,
+            self.lenient_escapes,
+            self.allow_control_chars,
+            self.empty_string_as_none,
+            self.lightweight_errors,
+        ];
+        let mut bits = 0;
+        for (i, flag) in flags.into_iter().enumerate() {
+            bits |= (flag as u64) << i;
+        }
+        let dedup = match self.dedup_keys {
+            DedupPolicy::KeepAll => 0,
+            DedupPolicy::FirstWins => 1,
+            DedupPolicy::LastWins => 2,
+            DedupPolicy::Error => 3,
+        };
+        let snippet_len = self.error_snippet_len.min(u32::MAX as usize) as u64;
+        bits | dedup << 12 | snippet_len << 32
+    }
+
+    // Unpack the options packed by `to_bits`.
+    pub(crate) fn from_bits(bits: u64) -> Self {
+        let flag = |i: u32| (bits >> i) & 1 == 1;
+        let cfg = Self {
+            use_rawnumber: flag(0),
+            use_raw: flag(1),
+            utf8_lossy: flag(2),
+            utf8_policy: Utf8Policy::Validate,
+            preserve_surrogates: flag(3),
+            extended_numbers: flag(4),
+            skip_bom: false,
+            lenient_escapes: flag(5),
+            allow_control_chars: flag(6),
+            empty_string_as_none: flag(7),
+            dedup_keys: match (bits >> 12) & 3 {
+                0 => DedupPolicy::KeepAll,
+                1 => DedupPolicy::FirstWins,
+                2 => DedupPolicy::LastWins,
+                _ => DedupPolicy::Error,
+            },
+            error_snippet_len: (bits >> 32) as usize,
+            lightweight_errors: flag(8),
+        };
+        cfg.resolve_utf8_policy()
+    }
+
+    // whether to replace the lone surrogates with `\uFFFD` instead of reporting errors
+    #[inline(always)]
+    pub(crate) fn lossy_surrogates(&self) -> bool {
+        self.utf8_lossy || self.preserve_surrogates
+    }
+
+    // whether to record the raw text for the escaped strings in `Value`
+    #[inline(always)]
+    pub(crate) fn keep_raw_str(&self) -> bool {
+        self.use_raw || self.preserve_surrogates
+    }
+}
+
+/// The options of serializing, which is used in [`to_string_with_cfg`][crate::to_string_with_cfg].
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::SerializeCfg;
+///
+/// let mut cfg = SerializeCfg::default();
+/// cfg.pretty = true;
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SerializeCfg {
+    /// Write the pretty-printed JSON, as [`to_string_pretty`][crate::to_string_pretty].
+    pub pretty: bool,
+    /// The style of the pretty-printed JSON, only used if `pretty` is true.
+    pub pretty_style: PrettyStyle,
+    /// Write the fields of structs sorted by name, as
+    /// [`Serializer::sort_struct_fields`][crate::Serializer::sort_struct_fields].
+    pub sort_struct_fields: bool,
+    /// Escape U+2028 and U+2029 in the strings, as
+    /// [`Serializer::escape_line_separators`][crate::Serializer::escape_line_separators].
+    pub escape_line_separators: bool,
+    /// Escape DEL and the C1 control chars in the strings, as
+    /// [`Serializer::escape_controls`][crate::Serializer::escape_controls].
+    pub escape_controls: bool,
+    /// The format of the floats, as [`Serializer::float_format`][crate::Serializer::float_format].
+    pub float_format: FloatFormat,
+}
+
+/// The style of the pretty-printed JSON, which is used in [`SerializeCfg::pretty_style`].
+///
+/// # Examples
+///
+/// ```
+/// use serde::Serialize;
+/// use sonic_rs::{to_string_with_cfg, PrettyStyle, SerializeCfg};
+///
+/// #[derive(Serialize)]
+/// struct Repo {
+///     name: &'static str,
+///     tags: Vec<&'static str>,
+///     nested: Vec<Vec<u8>>,
+/// }
+///
+/// let value = Repo {
+///     name: "sonic",
+///     tags: vec!["json", "simd"],
+///     nested: vec![vec![1, 2]],
+/// };
+/// let mut cfg = SerializeCfg::default();
+/// cfg.pretty = true;
+/// cfg.pretty_style = PrettyStyle::Human {
+///     max_width: 30,
+///     align_keys: true,
+/// };
+/// assert_eq!(
+///     to_string_with_cfg(&value, cfg).unwrap(),
+///     r#"{
+///   "name":   "sonic",
+///   "tags":   ["json", "simd"],
+///   "nested": [[1, 2]]
+/// }"#
+/// );
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PrettyStyle {
+    /// Expand all the non-empty objects and arrays, one element or one entry per line.
+    #[default]
+    Expanded,
+    /// Keep the objects and arrays on one line if they fit within `max_width` columns, with a
+    /// space after `,` and `:`. The others are expanded, and their nested containers are checked
+    /// again. The width is counted in chars.
+    Human {
+        /// The max columns of a line, including the indent.
+        max_width: usize,
+        /// Pad the keys of the expanded objects, so that their values are aligned.
+        align_keys: bool,
+    },
+    /// Expand the non-empty objects with one entry per line, and write each element of the
+    /// non-empty arrays compact on its own line, such as `[\n  {"a":1},\n  [2,3]\n]`. It keeps the
+    /// line-based diffs of the config dumps small.
+    ElementPerLine,
+}
+
+/// The format of the finite floats, which is used in [`SerializeCfg::float_format`]. NaN and
+/// infinity are always written as `null`.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{to_string_with_cfg, FloatFormat, SerializeCfg};
+///
+/// let floats = [0.1 + 0.2, 1e21, 100.0, 1.0 / 3.0];
+/// let mut cfg = SerializeCfg::default();
+/// assert_eq!(
+///     to_string_with_cfg(&floats, cfg).unwrap(),
+///     "[0.30000000000000004,1e21,100.0,0.3333333333333333]"
+/// );
+///
+/// cfg.float_format = FloatFormat::Fixed(2);
+/// assert_eq!(
+///     to_string_with_cfg(&floats, cfg).unwrap(),
+///     "[0.30,1000000000000000000000.00,100.00,0.33]"
+/// );
+///
+/// cfg.float_format = FloatFormat::EcmaScript;
+/// assert_eq!(
+///     to_string_with_cfg(&floats, cfg).unwrap(),
+///     "[0.30000000000000004,1e+21,100,0.3333333333333333]"
+/// );
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FloatFormat {
+    /// The shortest text which is parsed back to the same float, such as `1.0` and `1e-7`.
+    #[default]
+    Shortest,
+    /// The decimal text with the fixed number of digits after the point, such as `1.50` for 2
+    /// digits, which is rounded from the exact value of the float. It never uses the exponent, and
+    /// the integer digits of the large floats are written in full.
+    Fixed(usize),
+    /// The same text as `Number.prototype.toString` in ECMAScript, such as `1` and `1e+21`. The
+    /// exponent is used if the float is less than `1e-6` or not less than `1e21`, and the integers
+    /// have no fraction part.
+    EcmaScript,
+}
+
+/// The policy of the duplicate keys in a JSON object, which is used in
+/// [`Deserializer::dedup_keys`][crate::Deserializer::dedup_keys] and
+/// [`ObjectJsonIter::dedup_keys`][crate::ObjectJsonIter::dedup_keys].
+///
+/// The keys are compared after unescaping, such as `"a"` and `"\u0061"`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DedupPolicy {
+    /// Keep all the duplicate keys in order, which is the default.
+    #[default]
+    KeepAll,
+    /// Keep the first entry of the duplicate keys.
+    FirstWins,
+    /// Keep the value of the last entry of the duplicate keys, at the position of the first one,
+    /// the same as inserting the entries into [`Object`][crate::Object] in order.
+    LastWins,
+    /// Return an error whose [`duplicate_key`][crate::Error::duplicate_key] is the pointer of the
+    /// duplicate key.
+    Error,
+}
+
+/// A token to cancel the parsing cooperatively, which is used in
+/// [`Deserializer::with_cancel`][`crate::Deserializer::with_cancel`].
+///
+/// The token can be cloned and shared across threads. The parsing checks the token in the loops
+/// of objects and arrays, and returns an error if it is cancelled, such as
+/// [`Error::is_cancelled`][`crate::Error::is_cancelled`].
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Create a new token, which is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the parsing which uses this token or its clones.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Return true if the token is cancelled.
+    #[inline(always)]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
 }
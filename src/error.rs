@@ -15,7 +15,10 @@ use serde::{
 use sonic_number::Error as NumberError;
 use thiserror::Error as ErrorTrait;
 
-use crate::reader::Position;
+use crate::{
+    pointer::{JsonPointer, PointerNode},
+    reader::Position,
+};
 
 /// This type represents all possible errors that can occur when serializing or
 /// deserializing JSON data.
@@ -66,15 +69,25 @@ impl Error {
     /// - `Category::Syntax` - input that is not syntactically valid JSON
     /// - `Category::Data` - input data that is semantically incorrect
     /// - `Category::Eof` - unexpected end of the input data
+    /// - `Category::Cancelled` - the parsing was cancelled by a `CancelToken`
     pub fn classify(&self) -> Category {
         match self.err.code {
-            ErrorCode::Message(_) | ErrorCode::UnexpectedVisitType => Category::TypeUnmatched,
+            ErrorCode::Message(_)
+            | ErrorCode::UnexpectedVisitType
+            | ErrorCode::IncompatibleLazyValue
+            | ErrorCode::IncompatibleUntagged
+            | ErrorCode::IncompatibleFlatten => Category::TypeUnmatched,
             ErrorCode::GetInEmptyObject
             | ErrorCode::GetInEmptyArray
             | ErrorCode::GetIndexOutOfArray
-            | ErrorCode::GetUnknownKeyInObject => Category::NotFound,
+            | ErrorCode::GetIndexOverflow
+            | ErrorCode::GetUnknownKeyInObject
+            | ErrorCode::GetWithWildcard
+            | ErrorCode::GetInArraySlice
+            | ErrorCode::GetUnsupportedInTree => Category::NotFound,
             ErrorCode::Io(_) => Category::Io,
             ErrorCode::EofWhileParsing => Category::Eof,
+            ErrorCode::Cancelled => Category::Cancelled,
             ErrorCode::ExpectedColon
             | ErrorCode::ExpectedObjectCommaOrEnd
             | ErrorCode::InvalidEscape
@@ -96,6 +109,7 @@ impl Error {
             | ErrorCode::FloatMustBeFinite
             | ErrorCode::ExpectedQuote
             | ErrorCode::ExpectedNumericKey
+            | ErrorCode::DuplicateKey(_)
             | ErrorCode::RecursionLimitExceeded => Category::Syntax,
         }
     }
@@ -128,6 +142,22 @@ impl Error {
         self.classify() == Category::NotFound
     }
 
+    /// Returns true if this error was caused by the cancelled
+    /// [`CancelToken`][`crate::CancelToken`].
+    pub fn is_cancelled(&self) -> bool {
+        self.classify() == Category::Cancelled
+    }
+
+    /// Returns the pointer of the duplicated key, if this error was caused by a duplicated key
+    /// in [`validate_unique_keys`][`crate::validate_unique_keys`].
+    pub fn duplicate_key(&self) -> Option<&JsonPointer> {
+        if let ErrorCode::DuplicateKey(pointer) = &self.err.code {
+            Some(pointer)
+        } else {
+            None
+        }
+    }
+
     /// Returns true if this error was caused by prematurely reaching the end of
     /// the input data.
     ///
@@ -153,6 +183,7 @@ impl From<Error> for std::io::Error {
         match j.err.code {
             ErrorCode::Io(err) => err,
             ErrorCode::EofWhileParsing => std::io::Error::new(std::io::ErrorKind::UnexpectedEof, j),
+            ErrorCode::Cancelled => std::io::Error::new(std::io::ErrorKind::Interrupted, j),
             _ => std::io::Error::new(std::io::ErrorKind::InvalidData, j),
         }
     }
@@ -187,6 +218,10 @@ pub enum Category {
     /// Callers that process streaming input may be interested in retrying the
     /// deserialization once more data is available.
     Eof,
+
+    /// The error was caused by cancelling the parsing with a
+    /// [`CancelToken`][`crate::CancelToken`].
+    Cancelled,
 }
 
 struct ErrorImpl {
@@ -194,8 +229,26 @@ struct ErrorImpl {
     index: usize,
     line: usize,
     column: usize,
-    // the descript of the error position
-    descript: Option<String>,
+    // the JSON text around the error position, rendered only when displayed
+    snippet: Option<Snippet>,
+}
+
+// The default bytes of the JSON text shown on each side of the error position.
+pub(crate) const DEFAULT_SNIPPET_LEN: usize = 8;
+
+struct Snippet {
+    text: Box<[u8]>,
+    // the offset of the error position in `text`
+    caret: usize,
+}
+
+impl Display for Snippet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> FmtResult {
+        let fragment = String::from_utf8_lossy(&self.text);
+        let left = self.caret;
+        let right = self.text.len().saturating_sub(self.caret + 1);
+        write!(f, "\n\n\t{fragment}\n\t{:.<left$}^{:.<right$}\n", "", "")
+    }
 }
 
 #[derive(ErrorTrait, Debug)]
@@ -272,6 +325,18 @@ pub(crate) enum ErrorCode {
     #[error("Get index out of the array")]
     GetIndexOutOfArray,
 
+    #[error("Get index overflows `usize`, which is never in the array")]
+    GetIndexOverflow,
+
+    #[error("Get value by the path with wildcards, use `get_many_all` instead")]
+    GetWithWildcard,
+
+    #[error("Get value inside an array slice, the slice must be the last node of the path")]
+    GetInArraySlice,
+
+    #[error("The index from the end or the array slice is not supported by `PointerTree`")]
+    GetUnsupportedInTree,
+
     #[error("Unexpected visited type in JSON visitor")]
     UnexpectedVisitType,
 
@@ -289,6 +354,49 @@ pub(crate) enum ErrorCode {
 
     #[error("Expected the key to be string/bool/number when serializing map, now is {0}")]
     SerExpectKeyIsStrOrNum(Unexpected<'static>),
+
+    #[error("The parsing was cancelled")]
+    Cancelled,
+
+    #[error("Duplicate key at {}", pointer_string(.0))]
+    DuplicateKey(Vec<PointerNode>),
+
+    #[error(
+        "LazyValue is only deserialized by the sonic-rs deserializer of a compatible version, use \
+         `sonic_rs::serde::with::json_string` to exchange it with other deserializers"
+    )]
+    IncompatibleLazyValue,
+
+    #[error(
+        "Untagged is only deserialized by the sonic-rs deserializer, use `#[serde(untagged)]` \
+         with other deserializers"
+    )]
+    IncompatibleUntagged,
+
+    #[error(
+        "Flatten is only deserialized by the sonic-rs deserializer, and not in a \
+         `#[serde(flatten)]` field"
+    )]
+    IncompatibleFlatten,
+}
+
+// Format the pointer as RFC 6901, such as `/a/0/b`.
+pub(crate) fn pointer_string(pointer: &JsonPointer) -> String {
+    let mut s = String::new();
+    for node in pointer {
+        s.push('/');
+        match node {
+            PointerNode::Key(key) => s.push_str(&key.replace('~', "~0").replace('/', "~1")),
+            PointerNode::Index(index) => s.push_str(itoa::Buffer::new().format(*index)),
+            PointerNode::FromEnd(n) => {
+                s.push('-');
+                s.push_str(itoa::Buffer::new().format(*n))
+            }
+            PointerNode::Wildcard => s.push('*'),
+            PointerNode::Slice(slice) => s.push_str(&slice.to_string()),
+        }
+    }
+    s
 }
 
 impl From<NumberError> for ErrorCode {
@@ -303,35 +411,44 @@ impl From<NumberError> for ErrorCode {
 impl Error {
     #[cold]
     pub(crate) fn syntax(code: ErrorCode, json: &[u8], index: usize) -> Self {
-        let position = Position::from_index(index, json);
-        // generate descript about 16 characters
-        let mut start = if index < 8 { 0 } else { index - 8 };
-        let mut end = if index + 8 > json.len() {
-            json.len()
-        } else {
-            index + 8
-        };
-
-        // find the nearest valid utf-8 character
-        while start > 0 && index - start <= 16 && (json[start] & 0b1100_0000) == 0b1000_0000 {
-            start -= 1;
-        }
-
-        // find the nearest valid utf-8 character
-        while end < json.len() && end - index <= 16 && (json[end - 1] & 0b1100_0000) == 0b1000_0000
-        {
-            end += 1;
-        }
+        Self::syntax_with(code, json, index, DEFAULT_SNIPPET_LEN)
+    }
 
-        let fragment = String::from_utf8_lossy(&json[start..end]).to_string();
-        let left = index - start;
-        let right = if end - index > 1 {
-            end - (index + 1)
-        } else {
-            0
-        };
-        let mask = ".".repeat(left) + "^" + &".".repeat(right);
-        let descript = format!("\n\n\t{}\n\t{}\n", fragment, mask);
+    // Record the line and column of the error, and about `snippet_len` bytes of the JSON text on
+    // each side of the error position. No snippet if `snippet_len` is zero.
+    #[cold]
+    pub(crate) fn syntax_with(
+        code: ErrorCode,
+        json: &[u8],
+        index: usize,
+        snippet_len: usize,
+    ) -> Self {
+        let position = Position::from_index(index, json);
+        let snippet = (snippet_len > 0).then(|| {
+            let mut start = index.saturating_sub(snippet_len);
+            let mut end = json.len().min(index.saturating_add(snippet_len));
+
+            // find the nearest valid utf-8 character
+            while start > 0
+                && index - start <= snippet_len.saturating_mul(2)
+                && (json[start] & 0b1100_0000) == 0b1000_0000
+            {
+                start -= 1;
+            }
+
+            // find the nearest valid utf-8 character
+            while end < json.len()
+                && end - index <= snippet_len.saturating_mul(2)
+                && (json[end - 1] & 0b1100_0000) == 0b1000_0000
+            {
+                end += 1;
+            }
+
+            Snippet {
+                text: json[start..end].into(),
+                caret: index - start,
+            }
+        });
 
         Error {
             err: Box::new(ErrorImpl {
@@ -339,7 +456,22 @@ impl Error {
                 line: position.line,
                 column: position.column,
                 index,
-                descript: Some(descript),
+                snippet,
+            }),
+        }
+    }
+
+    // Only record the code and the offset of the error, without scanning the JSON text for the
+    // line and column.
+    #[cold]
+    pub(crate) fn lightweight(code: ErrorCode, index: usize) -> Self {
+        Error {
+            err: Box::new(ErrorImpl {
+                code,
+                line: 0,
+                column: 0,
+                index,
+                snippet: None,
             }),
         }
     }
@@ -352,7 +484,7 @@ impl Error {
                 line: 0,
                 column: 0,
                 index: 0,
-                descript: None,
+                snippet: None,
             }),
         }
     }
@@ -365,11 +497,30 @@ impl Error {
                 line: 0,
                 index: 0,
                 column: 0,
-                descript: None,
+                snippet: None,
             }),
         }
     }
 
+    // Move the position of the error in a part of the JSON text to the whole JSON text, where the
+    // part starts at `index`, `line` and `column`.
+    #[cold]
+    pub(crate) fn shift_position(mut self, index: usize, line: usize, column: usize) -> Self {
+        if matches!(self.err.code, ErrorCode::Io(_)) {
+            return self;
+        }
+        let err = &mut self.err;
+        err.index += index;
+        // no line and column in the lightweight errors
+        if err.line > 0 {
+            if err.line == 1 {
+                err.column += column;
+            }
+            err.line += line - 1;
+        }
+        self
+    }
+
     #[cold]
     pub(crate) fn error_code(self) -> ErrorCode {
         self.err.code
@@ -396,12 +547,13 @@ impl Display for ErrorImpl {
         if self.line != 0 {
             write!(
                 f,
-                "{} at line {} column {}{}",
-                self.code,
-                self.line,
-                self.column,
-                self.descript.as_ref().unwrap_or(&"".to_string())
-            )
+                "{} at line {} column {}",
+                self.code, self.line, self.column
+            )?;
+            match &self.snippet {
+                Some(snippet) => Display::fmt(snippet, f),
+                None => Ok(()),
+            }
         } else {
             write!(f, "{}", self.code)
         }
@@ -449,7 +601,7 @@ pub(crate) fn make_error(mut msg: String) -> Error {
             line,
             index: 0,
             column,
-            descript: None,
+            snippet: None,
         }),
     }
 }
@@ -590,6 +742,48 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_error_snippet_len() {
+        use crate::Deserializer;
+
+        #[allow(unused)]
+        #[derive(Debug, Deserialize)]
+        struct Foo {
+            a: Vec<i32>,
+        }
+
+        let json = "{\"a\": [1, 2x, 3, 4, 5]}";
+        let parse = |de: Deserializer<_>| de.deserialize::<Foo>().unwrap_err();
+        let err = parse(Deserializer::from_str(json).error_snippet_len(2));
+        assert_eq!(
+            err.to_string(),
+            "Expected this character to be either a ',' or a ']' while parsing at line 1 column \
+             11\n\n\t 2x,\n\t..^.\n"
+        );
+        let err = parse(Deserializer::from_str(json).error_snippet_len(100));
+        assert!(err.to_string().ends_with(&format!(
+            "\n\n\t{json}\n\t{}^{}\n",
+            ".".repeat(11),
+            ".".repeat(11)
+        )));
+        let err = parse(Deserializer::from_str(json).error_snippet_len(usize::MAX));
+        assert!(err.to_string().contains(json));
+        let err = parse(Deserializer::from_str(json).error_snippet_len(0));
+        assert!(err.to_string().ends_with("at line 1 column 11"));
+
+        // the lightweight errors only have the code and offset
+        let err = parse(Deserializer::from_str(json).lightweight_errors());
+        assert!(err.is_syntax());
+        assert_eq!((err.offset(), err.line(), err.column()), (11, 0, 0));
+        assert_eq!(
+            err.to_string(),
+            "Expected this character to be either a ',' or a ']' while parsing"
+        );
+        let err = parse(Deserializer::from_str("{\"a\": null}").lightweight_errors());
+        assert!(err.is_unmatched_type());
+        assert_eq!(err.offset(), 9);
+    }
+
     #[test]
     fn test_other_errors() {
         let err = crate::Value::try_from(f64::NAN).unwrap_err();
@@ -1,10 +1,29 @@
-//! Formtter for JSON serialization.
+//! Formtter and builder for JSON serialization, and the comparison of the JSON texts.
 
 // The code is cloned from [serde_json](https://github.com/serde-rs/json) and modified necessary parts.
 
 use std::io::{self, Write};
 
-use crate::{serde::tri, util::string::format_string, writer::WriteExt};
+use serde::Serialize;
+
+pub use crate::compare::{json_text_eq, json_text_eq_unordered};
+use crate::{
+    config::FloatFormat,
+    error::{make_error, Error, Result},
+    input::JsonInput,
+    parser::Parser,
+    reader::Read,
+    serde::tri,
+    util::{
+        string::{
+            format_string, format_string_capacity, format_string_capacity_with, format_string_with,
+            Escapes,
+        },
+        utf8::from_utf8,
+    },
+    writer::WriteExt,
+    Serializer,
+};
 
 /// This trait abstracts away serializing the JSON control characters, which allows the user to
 /// optionally pretty print the JSON output.
@@ -184,8 +203,10 @@ pub trait Formatter {
     where
         W: ?Sized + WriteExt,
     {
-        let buf = writer.reserve_with(value.len() * 6 + 32 + 3)?;
-        let cnt = format_string(value, buf, need_quote);
+        let need = format_string_capacity(value);
+        let buf = writer.reserve_with(writer.grow_hint(need).max(need))?;
+        // the `buf` has enough capacity for the formatted string
+        let cnt = unsafe { format_string(value, buf, need_quote) };
         unsafe { writer.flush_len(cnt)? };
         Ok(())
     }
@@ -344,6 +365,160 @@ pub trait Formatter {
     }
 }
 
+// Write the string with the extra escapes of the serializer, which bypasses
+// `Formatter::write_string_fast`.
+#[inline]
+pub(crate) fn write_string_escaped<W>(
+    writer: &mut W,
+    value: &str,
+    need_quote: bool,
+    escapes: Escapes,
+) -> io::Result<()>
+where
+    W: ?Sized + WriteExt,
+{
+    let need = format_string_capacity_with(value, escapes);
+    let buf = writer.reserve_with(writer.grow_hint(need).max(need))?;
+    // the `buf` has enough capacity for the formatted string
+    let cnt = unsafe { format_string_with(value, buf, need_quote, escapes) };
+    unsafe { writer.flush_len(cnt)? };
+    Ok(())
+}
+
+// Render the finite float in the format other than `FloatFormat::Shortest`, which is written by
+// `Formatter::write_f64` instead.
+pub(crate) fn format_float<T>(value: T, format: FloatFormat, out: &mut String)
+where
+    T: ryu::Float + std::fmt::Display,
+{
+    match format {
+        FloatFormat::Fixed(decimals) => {
+            // writing into a `String` never fails
+            let _ = std::fmt::Write::write_fmt(out, format_args!("{value:.decimals$}"));
+        }
+        _ => {
+            let mut buffer = ryu::Buffer::new();
+            format_ecma(buffer.format_finite(value), out);
+        }
+    }
+}
+
+// Rewrite the shortest text from `ryu` in the format of `Number.prototype.toString`.
+fn format_ecma(shortest: &str, out: &mut String) {
+    let (neg, s) = match shortest.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, shortest),
+    };
+    let (mant, exp) = match s.split_once('e') {
+        Some((mant, exp)) => (mant, exp.parse::<i32>().unwrap_or_default()),
+        None => (s, 0),
+    };
+    let (int, frac) = mant.split_once('.').unwrap_or((mant, ""));
+    let all = [int, frac].concat();
+
+    // the value is `0.{digits} * 10^n`
+    let digits = all.trim_start_matches('0');
+    let n = int.len() as i32 + exp - (all.len() - digits.len()) as i32;
+    let digits = digits.trim_end_matches('0');
+    if digits.is_empty() {
+        // the negative zero is `0` too
+        out.push('0');
+        return;
+    }
+    if neg {
+        out.push('-');
+    }
+
+    let k = digits.len() as i32;
+    if k <= n && n <= 21 {
+        out.push_str(digits);
+        out.extend(std::iter::repeat('0').take((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat('0').take(-n as usize));
+        out.push_str(digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        let e = n - 1;
+        out.push('e');
+        out.push(if e < 0 { '-' } else { '+' });
+        out.push_str(itoa::Buffer::new().format(e.unsigned_abs()));
+    }
+}
+
+/// Remove the whitespaces outside the strings of the JSON text, without building a
+/// [`Value`][crate::Value].
+///
+/// The JSON is validated by the SIMD scan of [`get`][crate::get] first, then the values are
+/// copied by skipping the whitespaces and strings. The strings, numbers and escapes are kept as
+/// they are in the input, so it is much faster than parsing and serializing a `Value`.
+///
+/// # Errors
+///
+/// Return an error if `json` is not a single valid JSON value.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::format::minify;
+///
+/// let json = "{\n  \"a\": [1.50, \"x y\"],\n  \"b\": null\n}\n";
+/// assert_eq!(minify(json).unwrap(), br#"{"a":[1.50,"x y"],"b":null}"#);
+/// assert!(minify("[1, 2").is_err());
+/// ```
+pub fn minify<'de, Input: JsonInput<'de>>(json: Input) -> Result<Vec<u8>> {
+    let slice = json.to_u8_slice();
+    tri!(check_value(slice));
+    if json.need_utf8_valid() {
+        tri!(from_utf8(slice));
+    }
+
+    let mut out = Vec::with_capacity(slice.len());
+    let mut parser = Parser::new(Read::new(slice, false));
+    tri!(parser.minify_into(&mut out));
+    Ok(out)
+}
+
+/// Reformat the JSON text in the pretty style, the same as [`to_vec_pretty`][crate::to_vec_pretty]
+/// of its [`Value`][crate::Value], without building the `Value`.
+///
+/// The JSON is minified as [`minify`], and then indented. The strings, numbers and escapes are
+/// kept as they are in the input.
+///
+/// # Errors
+///
+/// Return an error if `json` is not a single valid JSON value.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::format::prettify;
+///
+/// let json = r#"{"a":[1,{}], "b" : "\u0041"}"#;
+/// assert_eq!(
+///     String::from_utf8(prettify(json).unwrap()).unwrap(),
+///     r#"{
+///   "a": [
+///     1,
+///     {}
+///   ],
+///   "b": "\u0041"
+/// }"#
+/// );
+/// ```
+pub fn prettify<'de, Input: JsonInput<'de>>(json: Input) -> Result<Vec<u8>> {
+    let compact = tri!(minify(json));
+    Ok(crate::serde::human::reformat(&compact, 0, false))
+}
+
 /// This structure compacts a JSON value with no extra whitespace.
 #[derive(Clone, Debug)]
 pub struct CompactFormatter;
@@ -476,6 +651,425 @@ impl<'a> Formatter for PrettyFormatter<'a> {
     }
 }
 
+/// A push-style builder to write a JSON text incrementally, for the handwritten encoders where
+/// the serde derive is too inflexible.
+///
+/// The arrays and objects are opened and closed by the methods, and the keys are escaped. The
+/// commas and colons are written by the builder, and the values are serialized by the
+/// [`Serializer`][crate::Serializer] with the same formatter. The misuses, such as a value
+/// without a key in an object, are returned as errors.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::format::JsonBuilder;
+///
+/// let mut builder = JsonBuilder::new(Vec::new());
+/// builder.begin_object()?;
+/// builder.key("id")?.value(&1)?;
+/// builder.key("tags")?.begin_array()?;
+/// for tag in ["a", "b\"c"] {
+///     builder.value(tag)?;
+/// }
+/// builder.end()?.end()?;
+/// assert_eq!(builder.into_inner()?, br#"{"id":1,"tags":["a","b\"c"]}"#);
+///
+/// let mut builder = JsonBuilder::pretty(Vec::new());
+/// builder.begin_array()?.value(&[1])?.end()?;
+/// assert_eq!(builder.into_inner()?, b"[\n  [\n    1\n  ]\n]");
+///
+/// // a key is required before the value in an object
+/// let mut builder = JsonBuilder::new(Vec::new());
+/// assert!(builder.begin_object()?.value(&1).is_err());
+/// # Ok::<(), sonic_rs::Error>(())
+/// ```
+pub struct JsonBuilder<W, F = CompactFormatter> {
+    writer: W,
+    formatter: F,
+    // the opened arrays and objects
+    levels: Vec<Level>,
+    // the key of the current object is written, and its value is expected
+    has_key: bool,
+    // the top-level value is written
+    finished: bool,
+}
+
+struct Level {
+    is_object: bool,
+    first: bool,
+}
+
+impl<W: WriteExt> JsonBuilder<W> {
+    /// Create a builder writing the compact JSON into `writer`.
+    pub fn new(writer: W) -> Self {
+        Self::with_formatter(writer, CompactFormatter)
+    }
+}
+
+impl<'a, W: WriteExt> JsonBuilder<W, PrettyFormatter<'a>> {
+    /// Create a builder writing the pretty-printed JSON into `writer`.
+    pub fn pretty(writer: W) -> Self {
+        Self::with_formatter(writer, PrettyFormatter::new())
+    }
+}
+
+impl<W: WriteExt, F: Formatter + Clone> JsonBuilder<W, F> {
+    /// Create a builder writing the JSON into `writer` with the `formatter`.
+    pub fn with_formatter(writer: W, formatter: F) -> Self {
+        Self {
+            writer,
+            formatter,
+            levels: Vec::new(),
+            has_key: false,
+            finished: false,
+        }
+    }
+
+    /// Open an object, as a value.
+    pub fn begin_object(&mut self) -> Result<&mut Self> {
+        tri!(self.begin_value());
+        tri!(self
+            .formatter
+            .begin_object(&mut self.writer)
+            .map_err(Error::io));
+        self.levels.push(Level {
+            is_object: true,
+            first: true,
+        });
+        Ok(self)
+    }
+
+    /// Open an array, as a value.
+    pub fn begin_array(&mut self) -> Result<&mut Self> {
+        tri!(self.begin_value());
+        tri!(self
+            .formatter
+            .begin_array(&mut self.writer)
+            .map_err(Error::io));
+        self.levels.push(Level {
+            is_object: false,
+            first: true,
+        });
+        Ok(self)
+    }
+
+    /// Write the key in the current object, the next value is the value of the key.
+    pub fn key(&mut self, key: &str) -> Result<&mut Self> {
+        let Some(level) = self
+            .levels
+            .last_mut()
+            .filter(|l| l.is_object && !self.has_key)
+        else {
+            return Err(make_error(
+                "the key is only written before a value in an object".into(),
+            ));
+        };
+        let first = std::mem::replace(&mut level.first, false);
+        let f = &mut self.formatter;
+        tri!(f
+            .begin_object_key(&mut self.writer, first)
+            .map_err(Error::io));
+        tri!(f
+            .write_string_fast(&mut self.writer, key, true)
+            .map_err(Error::io));
+        tri!(f.end_object_key(&mut self.writer).map_err(Error::io));
+        self.has_key = true;
+        Ok(self)
+    }
+
+    /// Serialize the `value` as the value of the key, the element of the array, or the top-level
+    /// value.
+    pub fn value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<&mut Self> {
+        tri!(self.begin_value());
+        let mut ser = Serializer::with_formatter(&mut self.writer, self.formatter.clone());
+        tri!(value.serialize(&mut ser));
+        tri!(self.end_value());
+        Ok(self)
+    }
+
+    /// Close the current array or object.
+    pub fn end(&mut self) -> Result<&mut Self> {
+        if self.has_key {
+            return Err(make_error("the value of the key is not written".into()));
+        }
+        let Some(level) = self.levels.pop() else {
+            return Err(make_error("no array or object to end".into()));
+        };
+        tri!(if level.is_object {
+            self.formatter.end_object(&mut self.writer)
+        } else {
+            self.formatter.end_array(&mut self.writer)
+        }
+        .map_err(Error::io));
+        tri!(self.end_value());
+        Ok(self)
+    }
+
+    /// Whether the top-level value is complete, and all the arrays and objects are closed.
+    pub fn is_complete(&self) -> bool {
+        self.finished
+    }
+
+    /// Return the writer after the JSON is complete.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the top-level value is not written, or some arrays or objects are not
+    /// closed.
+    pub fn into_inner(self) -> Result<W> {
+        if self.finished {
+            Ok(self.writer)
+        } else {
+            Err(make_error("the JSON is incomplete".into()))
+        }
+    }
+
+    fn begin_value(&mut self) -> Result<()> {
+        let Some(level) = self.levels.last_mut() else {
+            if self.finished {
+                return Err(make_error("the top-level value is already written".into()));
+            }
+            return Ok(());
+        };
+        if level.is_object {
+            if !self.has_key {
+                return Err(make_error(
+                    "the key is required before the value in an object".into(),
+                ));
+            }
+            self.has_key = false;
+            self.formatter
+                .begin_object_value(&mut self.writer)
+                .map_err(Error::io)
+        } else {
+            let first = std::mem::replace(&mut level.first, false);
+            self.formatter
+                .begin_array_value(&mut self.writer, first)
+                .map_err(Error::io)
+        }
+    }
+
+    fn end_value(&mut self) -> Result<()> {
+        match self.levels.last() {
+            None => {
+                self.finished = true;
+                Ok(())
+            }
+            Some(level) if level.is_object => self.formatter.end_object_value(&mut self.writer),
+            Some(_) => self.formatter.end_array_value(&mut self.writer),
+        }
+        .map_err(Error::io)
+    }
+}
+
+/// A JSON template with the `$name` placeholders, which is validated once and rendered many
+/// times by splicing the values into the placeholders.
+///
+/// A placeholder is a `$` followed by the ASCII letters, digits or underscores, and it is only
+/// allowed at the position of a JSON value. The `$` in the strings is a plain character. The
+/// static parts of the template are copied as they are, and the values are serialized with the
+/// escaping of the [`Serializer`][crate::Serializer], so the rendered text is always valid JSON.
+///
+/// The placeholders are filled in the order of the template by [`Render`], and a placeholder
+/// appearing twice is filled twice.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{format::Template, json};
+///
+/// let tpl = Template::compile(r#"{"code":0,"user":$user,"ts":$ts,"msg":"$ok"}"#)?;
+/// assert_eq!(tpl.placeholders().collect::<Vec<_>>(), ["user", "ts"]);
+///
+/// let mut render = tpl.render();
+/// render
+///     .value("user", &json!({"name": "a\"b"}))?
+///     .raw("ts", "1700000000")?;
+/// let out = render.finish()?;
+/// assert_eq!(
+///     out,
+///     r#"{"code":0,"user":{"name":"a\"b"},"ts":1700000000,"msg":"$ok"}"#
+/// );
+///
+/// // the placeholders are only allowed as the values
+/// assert!(Template::compile(r#"{$key:1}"#).is_err());
+/// # Ok::<(), sonic_rs::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Template {
+    // the static texts around the placeholders, one more than the placeholders
+    parts: Vec<Box<str>>,
+    names: Vec<Box<str>>,
+    // the total length of the static texts
+    static_len: usize,
+}
+
+impl Template {
+    /// Parse and validate the `template`.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if a `$` is not followed by a name, or the template is not a single valid
+    /// JSON value when the placeholders are replaced by `null`. The error position is in the
+    /// replaced text.
+    pub fn compile(template: &str) -> Result<Self> {
+        let bytes = template.as_bytes();
+        let mut parts: Vec<Box<str>> = Vec::new();
+        let mut names: Vec<Box<str>> = Vec::new();
+        let mut skeleton = String::with_capacity(template.len());
+        let (mut start, mut i) = (0, 0);
+        let mut in_str = false;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' if in_str => i += 1,
+                b'"' => in_str = !in_str,
+                b'$' if !in_str => {
+                    let end = bytes[i + 1..]
+                        .iter()
+                        .position(|c| !(c.is_ascii_alphanumeric() || *c == b'_'))
+                        .map_or(bytes.len(), |n| i + 1 + n);
+                    if end == i + 1 {
+                        return Err(make_error(format!(
+                            "the placeholder at {i} has no name in the template"
+                        )));
+                    }
+                    parts.push(template[start..i].into());
+                    names.push(template[i + 1..end].into());
+                    skeleton.push_str(&template[start..i]);
+                    skeleton.push_str("null");
+                    start = end;
+                    i = end;
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        parts.push(template[start..].into());
+        skeleton.push_str(&template[start..]);
+        tri!(check_value(skeleton.as_bytes()));
+
+        let static_len = parts.iter().map(|p| p.len()).sum();
+        Ok(Self {
+            parts,
+            names,
+            static_len,
+        })
+    }
+
+    /// Iterate the names of the placeholders, in the order of the template.
+    pub fn placeholders(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|n| &**n)
+    }
+
+    /// Start rendering into a new buffer.
+    pub fn render(&self) -> Render<'_> {
+        self.render_into(Vec::new())
+    }
+
+    /// Start rendering by appending to `buf`, which reuses its allocation.
+    pub fn render_into(&self, mut buf: Vec<u8>) -> Render<'_> {
+        buf.reserve(self.static_len + self.names.len() * 16);
+        Render {
+            template: self,
+            buf,
+            next: 0,
+        }
+    }
+}
+
+/// The rendering of a [`Template`], which fills the placeholders in order.
+///
+/// Each method writes the static text before the next placeholder and then the value, so the
+/// output is built in one buffer without the intermediate strings. A failed call writes nothing,
+/// and the same placeholder can be filled again.
+#[derive(Debug)]
+pub struct Render<'a> {
+    template: &'a Template,
+    buf: Vec<u8>,
+    // the index of the next placeholder
+    next: usize,
+}
+
+impl Render<'_> {
+    /// Serialize the `value` into the next placeholder, which must be named `name`.
+    pub fn value<T: ?Sized + Serialize>(&mut self, name: &str, value: &T) -> Result<&mut Self> {
+        let len = tri!(self.begin(name));
+        if let Err(err) = value.serialize(&mut Serializer::new(&mut self.buf)) {
+            self.buf.truncate(len);
+            return Err(err);
+        }
+        self.next += 1;
+        Ok(self)
+    }
+
+    /// Copy the raw JSON text into the next placeholder, which must be named `name`.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if `raw` is not a single valid JSON value.
+    pub fn raw(&mut self, name: &str, raw: &str) -> Result<&mut Self> {
+        tri!(check_value(raw.as_bytes()));
+        tri!(self.begin(name));
+        self.buf.extend_from_slice(raw.as_bytes());
+        self.next += 1;
+        Ok(self)
+    }
+
+    /// Return the rendered JSON text.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if some placeholders are not filled.
+    pub fn finish(self) -> Result<String> {
+        let buf = tri!(self.finish_vec());
+        // SAFETY: the template, the raw texts and the serialized values are all valid UTF-8
+        Ok(unsafe { String::from_utf8_unchecked(buf) })
+    }
+
+    /// Return the rendered JSON text as bytes, the same as [`finish`][Render::finish].
+    pub fn finish_vec(mut self) -> Result<Vec<u8>> {
+        let names = &self.template.names;
+        if let Some(name) = names.get(self.next) {
+            return Err(make_error(format!(
+                "the placeholder `${name}` in the template is not filled"
+            )));
+        }
+        self.buf
+            .extend_from_slice(self.template.parts[self.next].as_bytes());
+        Ok(self.buf)
+    }
+
+    // check the name and write the static text before the placeholder, return the length of the
+    // buffer before writing
+    fn begin(&mut self, name: &str) -> Result<usize> {
+        match self.template.names.get(self.next) {
+            Some(expect) if **expect == *name => {}
+            Some(expect) => {
+                return Err(make_error(format!(
+                    "expected the placeholder `${expect}` in the template, found `${name}`"
+                )))
+            }
+            None => {
+                return Err(make_error(format!(
+                    "all placeholders are filled, found `${name}`"
+                )))
+            }
+        }
+        let len = self.buf.len();
+        self.buf
+            .extend_from_slice(self.template.parts[self.next].as_bytes());
+        Ok(len)
+    }
+}
+
+// check the text is a single valid JSON value
+fn check_value(json: &[u8]) -> Result<()> {
+    let mut parser = Parser::new(Read::new(json, false));
+    tri!(parser.skip_one());
+    parser.parse_trailing()
+}
+
 fn indent<W>(wr: &mut W, n: usize, s: &[u8]) -> io::Result<()>
 where
     W: ?Sized + Write,
@@ -486,3 +1080,126 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::{minify, prettify, JsonBuilder, Template};
+    use crate::{json, Value};
+
+    #[test]
+    fn test_minify_prettify() {
+        let json = " {\n\t\"a\" : [ 1 , -2.5 , true , null , \"s t\" ] ,\r\n \"b\" : { \"c\" : { \
+                    } , \"d\" : [ ] } } \n";
+        let value: Value = crate::from_str(json).unwrap();
+        assert_eq!(minify(json).unwrap(), crate::to_vec(&value).unwrap());
+        assert_eq!(
+            prettify(json).unwrap(),
+            crate::to_vec_pretty(&value).unwrap()
+        );
+
+        // the strings and numbers are kept as they are
+        let json = r#"[ "a \" ] \u00e9 \\" , 1.0E+2 , -0 ]"#;
+        assert_eq!(minify(json).unwrap(), br#"["a \" ] \u00e9 \\",1.0E+2,-0]"#);
+        assert_eq!(minify(" \"x\" ").unwrap(), br#""x""#);
+        assert_eq!(minify("0").unwrap(), b"0");
+        assert_eq!(prettify("[]").unwrap(), b"[]");
+        assert_eq!(
+            minify(&b"[\"\xe4\xb8\xad\", {}]"[..]).unwrap(),
+            "[\"中\",{}]".as_bytes()
+        );
+
+        assert!(minify("").is_err());
+        assert!(minify("[1,]").is_err());
+        assert!(minify("{} []").is_err());
+        assert!(minify(r#"{"a" 1}"#).is_err());
+        assert!(minify(&b"[\"\xff\"]"[..]).is_err());
+        assert!(prettify("[tru]").is_err());
+    }
+
+    #[test]
+    fn test_json_builder() {
+        let mut builder = JsonBuilder::pretty(Vec::new());
+        builder.begin_object().unwrap();
+        builder.key("a\n").unwrap().begin_array().unwrap();
+        builder.value(&json!({"b": [1, null]})).unwrap();
+        builder.begin_object().unwrap().end().unwrap();
+        builder.end().unwrap();
+        builder.key("c").unwrap().value("d").unwrap();
+        assert!(!builder.is_complete());
+        builder.end().unwrap();
+        assert!(builder.is_complete());
+
+        let expect = json!({"a\n": [{"b": [1, null]}, {}], "c": "d"});
+        let out = builder.into_inner().unwrap();
+        assert!(out.starts_with(b"{\n  \"a\\n\": [\n    {\n      \"b\": [\n        1,"));
+        assert_eq!(crate::from_slice::<Value>(&out).unwrap(), expect);
+
+        // the misuses
+        let mut builder = JsonBuilder::new(Vec::new());
+        assert!(builder.key("a").is_err());
+        assert!(builder.end().is_err());
+        builder.begin_array().unwrap();
+        assert!(builder.key("a").is_err());
+        builder.value(&1).unwrap().begin_object().unwrap();
+        builder.key("k").unwrap();
+        assert!(builder.key("k").is_err());
+        assert!(builder.end().is_err());
+        builder.value(&2).unwrap().end().unwrap();
+        assert!(builder.value(&3).unwrap().end().is_ok());
+        assert!(builder.value(&4).is_err());
+        assert_eq!(builder.into_inner().unwrap(), br#"[1,{"k":2},3]"#);
+
+        let mut builder = JsonBuilder::new(Vec::new());
+        builder.begin_array().unwrap();
+        assert!(builder.into_inner().is_err());
+    }
+
+    #[test]
+    fn test_template() {
+        let tpl = Template::compile(
+            "{ \"a\" : $a , \"b\": [$b, $a_1, \"\\\"$x\"], \"c\": {\"$d\": $b} }",
+        )
+        .unwrap();
+        assert_eq!(
+            tpl.placeholders().collect::<Vec<_>>(),
+            ["a", "b", "a_1", "b"]
+        );
+
+        let mut buf = Vec::new();
+        for i in 0..2 {
+            buf.clear();
+            let mut render = tpl.render_into(buf);
+            render.value("a", "s\n\"").unwrap();
+            render.raw("b", " [1, {}] ").unwrap();
+            render.value("a_1", &json!({"k": null})).unwrap();
+            render.value("b", &i).unwrap();
+            buf = render.finish_vec().unwrap();
+            let expect = json!({"a": "s\n\"", "b": [[1, {}], {"k": null}, "\"$x"], "c": {"$d": i}});
+            assert_eq!(crate::from_slice::<Value>(&buf).unwrap(), expect);
+        }
+
+        // the misuses are errors and write nothing
+        let mut render = tpl.render();
+        assert!(render.value("b", &1).is_err());
+        assert!(render.raw("a", "[1,").is_err());
+        assert!(render.raw("a", "").is_err());
+        assert!(render.raw("a", "1 2").is_err());
+        render.raw("a", "true").unwrap();
+        assert!(render.value("b", &f64::NAN).is_ok());
+        assert!(render.finish().is_err());
+
+        // no placeholders
+        let tpl = Template::compile(r#"["$a"]"#).unwrap();
+        assert_eq!(tpl.placeholders().count(), 0);
+        assert!(tpl.render().value("a", &1).is_err());
+        assert_eq!(tpl.render().finish().unwrap(), r#"["$a"]"#);
+        let tpl = Template::compile(" $v ").unwrap();
+        let mut render = tpl.render();
+        render.value("v", &[1]).unwrap();
+        assert_eq!(render.finish().unwrap(), " [1] ");
+
+        for bad in ["", "[$]", "[$a $b]", "{$a: 1}", "[$a,]", "$a-1", "[\"$a]"] {
+            assert!(Template::compile(bad).is_err(), "{bad}");
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     util::{private::Sealed, reborrow::DormantMutRef},
-    JsonValueMutTrait, JsonValueTrait, PointerNode, Value,
+    ArraySlice, JsonValueMutTrait, JsonValueTrait, PointerNode, Value,
 };
 
 impl<I> std::ops::Index<I> for Value
@@ -121,6 +121,33 @@ pub trait Index: Sealed {
     fn as_index(&self) -> Option<usize> {
         None
     }
+
+    /// Return the count from the end of an array, `1` is the last element.
+    #[doc(hidden)]
+    fn as_index_from_end(&self) -> Option<usize> {
+        None
+    }
+
+    /// Return whether it matches every element of an array or every value of an object.
+    #[doc(hidden)]
+    fn is_wildcard(&self) -> bool {
+        false
+    }
+
+    /// Return the slice of an array.
+    #[doc(hidden)]
+    fn as_slice(&self) -> Option<ArraySlice> {
+        None
+    }
+
+    /// Resolve the position in an array of `len` elements, the position may be out of bounds.
+    #[doc(hidden)]
+    fn as_array_index(&self, len: usize) -> Option<usize> {
+        match self.as_index() {
+            Some(i) => Some(i),
+            None => self.as_index_from_end().and_then(|n| len.checked_sub(n)),
+        }
+    }
 }
 
 impl Index for usize {
@@ -153,16 +180,18 @@ impl Index for usize {
     }
 }
 
+// The keys are implemented for the owned and unsized types, and the references are covered by
+// the impl of `&T`, so `value[key]`, `value[&key]` and `value.get(&&key)` are all supported.
 macro_rules! impl_str_index {
     ($($t: ty),*) => {
         $(
-            impl Index for &$t {
+            impl Index for $t {
                 #[inline]
                 fn value_index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
                     if !v.is_object() {
                         return None;
                     }
-                    v.get_key(*self)
+                    v.get_key(self.as_ref())
                 }
 
                 #[inline]
@@ -170,7 +199,7 @@ macro_rules! impl_str_index {
                     if !v.is_object() {
                         return None;
                     }
-                    v.get_key_mut(*self)
+                    v.get_key_mut(self.as_ref())
                 }
 
                 #[inline]
@@ -184,9 +213,9 @@ macro_rules! impl_str_index {
                     obj.as_object_mut()
                         .expect(&format!("cannot access key in non-object value {:?}", typ))
                         .0
-                        .get_key_mut(*self).unwrap_or_else(|| {
+                        .get_key_mut(self.as_ref()).unwrap_or_else(|| {
                             let o =  unsafe { dormant_obj.reborrow() };
-                            let inserted = o.insert(&self, Value::new_null());
+                            let inserted = o.insert(self.as_ref(), Value::new_null());
                             inserted
                         })
                 }
@@ -202,12 +231,62 @@ macro_rules! impl_str_index {
 
 impl_str_index!(str, String, faststr::FastStr);
 
+// The other integers are converted into `PointerNode` without wrapping: the negative indexes count
+// from the end of the array, and the too large indexes are saturated, which are always out of
+// bounds. `i32` is required as the fallback type of integer literals, such as `value[0]`.
+macro_rules! impl_int_index {
+    ($($t: ty),*) => {
+        $(
+            impl Index for $t {
+                #[inline]
+                fn value_index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+                    PointerNode::from(*self).value_index_into(v)
+                }
+
+                #[inline]
+                fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+                    PointerNode::from(*self).index_into_mut(v)
+                }
+
+                #[inline]
+                fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+                    PointerNode::from(*self).index_or_insert(v)
+                }
+
+                #[inline]
+                fn as_index(&self) -> Option<usize> {
+                    PointerNode::from(*self).as_index()
+                }
+
+                #[inline]
+                fn as_index_from_end(&self) -> Option<usize> {
+                    PointerNode::from(*self).as_index_from_end()
+                }
+            }
+        )*
+    };
+}
+
+impl_int_index!(u64, i32, i64, isize);
+
+// resolve the position counted from the end, `None` if out of bounds
+fn from_end(v: &Value, n: usize) -> Option<usize> {
+    v.len().checked_sub(n)
+}
+
 impl Index for PointerNode {
     #[inline]
     fn value_index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
         match self {
             PointerNode::Index(i) => i.value_index_into(v),
+            PointerNode::FromEnd(n) => {
+                if !v.is_array() {
+                    return None;
+                }
+                v.get_index(from_end(v, *n)?)
+            }
             PointerNode::Key(k) => k.value_index_into(v),
+            PointerNode::Wildcard | PointerNode::Slice(_) => None,
         }
     }
 
@@ -215,7 +294,14 @@ impl Index for PointerNode {
     fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
         match self {
             PointerNode::Index(i) => i.index_into_mut(v),
+            PointerNode::FromEnd(n) => {
+                if !v.is_array() {
+                    return None;
+                }
+                v.get_index_mut(from_end(v, *n)?)
+            }
             PointerNode::Key(k) => k.index_into_mut(v),
+            PointerNode::Wildcard | PointerNode::Slice(_) => None,
         }
     }
 
@@ -223,7 +309,19 @@ impl Index for PointerNode {
     fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
         match self {
             PointerNode::Index(i) => i.index_or_insert(v),
+            PointerNode::FromEnd(n) => {
+                let typ = v.get_type();
+                let len = v.len();
+                if !v.is_array() {
+                    panic!("cannot access index in non-array value type {:?}", typ);
+                }
+                let index = from_end(v, *n)
+                    .unwrap_or_else(|| panic!("index -{} out of bounds (len: {})", *n, len));
+                v.get_index_mut(index).unwrap()
+            }
             PointerNode::Key(k) => k.index_or_insert(v),
+            PointerNode::Wildcard => panic!("cannot index a value with the wildcard"),
+            PointerNode::Slice(_) => panic!("cannot index a value with the array slice"),
         }
     }
 
@@ -231,17 +329,47 @@ impl Index for PointerNode {
     fn as_index(&self) -> Option<usize> {
         match self {
             PointerNode::Index(i) => Some(*i),
-            PointerNode::Key(_) => None,
+            PointerNode::FromEnd(_)
+            | PointerNode::Key(_)
+            | PointerNode::Wildcard
+            | PointerNode::Slice(_) => None,
+        }
+    }
+
+    #[inline]
+    fn as_index_from_end(&self) -> Option<usize> {
+        match self {
+            PointerNode::FromEnd(n) => Some(*n),
+            PointerNode::Index(_)
+            | PointerNode::Key(_)
+            | PointerNode::Wildcard
+            | PointerNode::Slice(_) => None,
         }
     }
 
     #[inline]
     fn as_key(&self) -> Option<&str> {
         match self {
-            PointerNode::Index(_) => None,
+            PointerNode::Index(_)
+            | PointerNode::FromEnd(_)
+            | PointerNode::Wildcard
+            | PointerNode::Slice(_) => None,
             PointerNode::Key(k) => Some(k.as_ref()),
         }
     }
+
+    #[inline]
+    fn is_wildcard(&self) -> bool {
+        matches!(self, PointerNode::Wildcard)
+    }
+
+    #[inline]
+    fn as_slice(&self) -> Option<ArraySlice> {
+        match self {
+            PointerNode::Slice(slice) => Some(*slice),
+            _ => None,
+        }
+    }
 }
 
 impl<T> Index for &T
@@ -268,8 +396,23 @@ where
         (**self).as_index()
     }
 
+    #[inline]
+    fn as_index_from_end(&self) -> Option<usize> {
+        (**self).as_index_from_end()
+    }
+
     #[inline]
     fn as_key(&self) -> Option<&str> {
         (**self).as_key()
     }
+
+    #[inline]
+    fn is_wildcard(&self) -> bool {
+        (**self).is_wildcard()
+    }
+
+    #[inline]
+    fn as_slice(&self) -> Option<ArraySlice> {
+        (**self).as_slice()
+    }
 }
@@ -1,7 +1,9 @@
+use std::{rc::Rc, sync::Arc};
+
 use bytes::Bytes;
 use faststr::FastStr;
 
-use crate::{parser::as_str, util::private::Sealed};
+use crate::{parser::as_str, reader::Utf16Text, util::private::Sealed};
 
 /// JsonSlice is a wrapper for different json input.
 #[doc(hidden)]
@@ -20,6 +22,14 @@ impl<'de> JsonSlice<'de> {
             JsonSlice::FastStr(f) => f.clone(),
         }
     }
+
+    #[inline(always)]
+    pub(crate) unsafe fn into_faststr(self) -> FastStr {
+        match self {
+            JsonSlice::Raw(sub) => FastStr::new(as_str(sub)),
+            JsonSlice::FastStr(f) => f,
+        }
+    }
 }
 
 impl Default for JsonSlice<'_> {
@@ -74,6 +84,18 @@ impl<'de> AsRef<[u8]> for JsonSlice<'de> {
 }
 
 /// A trait for string/bytes-like types that can be parsed into JSON.
+///
+/// The [`LazyValue`][crate::LazyValue]s, such as the results of [`get`][crate::get] and
+/// [`to_object_iter`][crate::to_object_iter], never copy the JSON text of the input:
+///
+/// - `&Bytes`, `&FastStr`, `&Arc<str>` and `&Arc<[u8]>`: the `LazyValue`s share the ownership of
+///   the input buffer, so [`as_raw_faststr`][crate::LazyValue::as_raw_faststr] and
+///   [`into_owned`][crate::LazyValue::into_owned] are zero-copy, and the results can outlive the
+///   input.
+/// - `&str`, `&[u8]`, `&String`, `&Rc<str>` and `&Rc<[u8]>`: the `LazyValue`s borrow the input, and
+///   are copied when converted into the owned types.
+///
+/// The inputs of bytes, such as `&[u8]` and `&Arc<[u8]>`, are validated as UTF-8 when parsing.
 pub trait JsonInput<'de>: Sealed {
     fn need_utf8_valid(&self) -> bool;
     fn to_json_slice(&self) -> JsonSlice<'de>;
@@ -155,6 +177,24 @@ impl<'de> JsonInput<'de> for &'de FastStr {
     }
 }
 
+impl<'de> JsonInput<'de> for &'de Utf16Text {
+    fn need_utf8_valid(&self) -> bool {
+        false
+    }
+
+    fn to_json_slice(&self) -> JsonSlice<'de> {
+        JsonSlice::FastStr(self.json.clone())
+    }
+
+    fn from_subset(&self, sub: &'de [u8]) -> JsonSlice<'de> {
+        self.json.slice_ref(as_str(sub)).into()
+    }
+
+    fn to_u8_slice(&self) -> &'de [u8] {
+        (*self).as_bytes()
+    }
+}
+
 impl<'de> JsonInput<'de> for &'de String {
     fn need_utf8_valid(&self) -> bool {
         false
@@ -172,3 +212,88 @@ impl<'de> JsonInput<'de> for &'de String {
         (*self).as_bytes()
     }
 }
+
+// The owner of the `Arc<str>` buffer in `Bytes`.
+struct ArcStrOwner(Arc<str>);
+
+impl AsRef<[u8]> for ArcStrOwner {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl<'de> JsonInput<'de> for &'de Arc<str> {
+    fn need_utf8_valid(&self) -> bool {
+        false
+    }
+
+    fn to_json_slice(&self) -> JsonSlice<'de> {
+        let bytes = Bytes::from_owner(ArcStrOwner((*self).clone()));
+        JsonSlice::FastStr(unsafe { FastStr::from_bytes_unchecked(bytes) })
+    }
+
+    fn from_subset(&self, sub: &'de [u8]) -> JsonSlice<'de> {
+        Bytes::from_owner(ArcStrOwner((*self).clone()))
+            .slice_ref(sub)
+            .into()
+    }
+
+    fn to_u8_slice(&self) -> &'de [u8] {
+        (*self).as_bytes()
+    }
+}
+
+impl<'de> JsonInput<'de> for &'de Arc<[u8]> {
+    fn need_utf8_valid(&self) -> bool {
+        true
+    }
+
+    fn to_json_slice(&self) -> JsonSlice<'de> {
+        let bytes = Bytes::from_owner((*self).clone());
+        JsonSlice::FastStr(unsafe { FastStr::from_bytes_unchecked(bytes) })
+    }
+
+    fn from_subset(&self, sub: &'de [u8]) -> JsonSlice<'de> {
+        Bytes::from_owner((*self).clone()).slice_ref(sub).into()
+    }
+
+    fn to_u8_slice(&self) -> &'de [u8] {
+        (*self).as_ref()
+    }
+}
+
+impl<'de> JsonInput<'de> for &'de Rc<str> {
+    fn need_utf8_valid(&self) -> bool {
+        false
+    }
+
+    fn to_json_slice(&self) -> JsonSlice<'de> {
+        JsonSlice::Raw(self.to_u8_slice())
+    }
+
+    fn from_subset(&self, sub: &'de [u8]) -> JsonSlice<'de> {
+        sub.into()
+    }
+
+    fn to_u8_slice(&self) -> &'de [u8] {
+        (*self).as_bytes()
+    }
+}
+
+impl<'de> JsonInput<'de> for &'de Rc<[u8]> {
+    fn need_utf8_valid(&self) -> bool {
+        true
+    }
+
+    fn to_json_slice(&self) -> JsonSlice<'de> {
+        JsonSlice::Raw(self.to_u8_slice())
+    }
+
+    fn from_subset(&self, sub: &'de [u8]) -> JsonSlice<'de> {
+        sub.into()
+    }
+
+    fn to_u8_slice(&self) -> &'de [u8] {
+        (*self).as_ref()
+    }
+}
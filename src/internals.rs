@@ -0,0 +1,109 @@
+//! Internal helpers for fuzzing and stress tests.
+//!
+//! These APIs are only available in debug builds, and are not covered by semver guarantees.
+
+use std::{fs, io, path::Path};
+
+use crate::{error::Result, Value};
+
+/// Check the structural invariants of a `Value`, such as the type tags, the lengths of strings and
+/// containers, the canaries and shared pointers of the nodes in the parsed document.
+///
+/// Return an error describing the first broken invariant, so that the corruption can be detected
+/// before it causes a crash later.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{internals::check_value_invariants, Value};
+///
+/// let value: Value = sonic_rs::from_str(r#"{"a": [1, "b\n", {}]}"#).unwrap();
+/// assert!(check_value_invariants(&value).is_ok());
+/// ```
+pub fn check_value_invariants(value: &Value) -> Result<()> {
+    value.check_invariants(None)
+}
+
+/// Replay all the inputs in the corpus directory with `f`, in the order of the file names, so the
+/// replay is deterministic. Return the count of replayed inputs.
+///
+/// The subdirectories are not visited.
+pub fn replay_corpus<P, F>(dir: P, mut f: F) -> io::Result<usize>
+where
+    P: AsRef<Path>,
+    F: FnMut(&Path, &[u8]),
+{
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+
+    for path in &paths {
+        let data = fs::read(path)?;
+        f(path, &data);
+    }
+    Ok(paths.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{json, JsonValueMutTrait};
+
+    #[test]
+    fn test_check_value_invariants() {
+        let jsons = [
+            "null",
+            "123",
+            r#""""#,
+            r#""hello\nworld""#,
+            "[]",
+            "{}",
+            r#"{"a": [1, -2, 3.5, true, null, "b", {"c": {}}], "d\u0000": []}"#,
+        ];
+        for json in jsons {
+            let value: Value = crate::from_str(json).unwrap();
+            check_value_invariants(&value).unwrap();
+
+            let mut de = crate::Deserializer::from_str(json).use_raw();
+            let value: Value = de.deserialize().unwrap();
+            check_value_invariants(&value).unwrap();
+
+            // the cloned and mutated value
+            let mut cloned = value.clone();
+            check_value_invariants(&cloned).unwrap();
+            if let Some(obj) = cloned.as_object_mut() {
+                obj.insert(&"new", json!([1, "short", "a string longer than 14 bytes"]));
+            }
+            check_value_invariants(&cloned).unwrap();
+        }
+
+        let value: Value = crate::from_str(r#"{"a": {"b": [1, 2, "c"]}}"#).unwrap();
+        check_value_invariants(&value["a"]["b"].clone()).unwrap();
+        check_value_invariants(&json!({"a": 1})).unwrap();
+    }
+
+    #[test]
+    fn test_replay_corpus() {
+        let dir = std::env::temp_dir().join(format!("sonic_rs_corpus_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("b"), "[1, 2]").unwrap();
+        fs::write(dir.join("a"), r#"{"a": 1}"#).unwrap();
+
+        let mut replayed = Vec::new();
+        let cnt = replay_corpus(&dir, |path, data| {
+            let value: Value = crate::from_slice(data).unwrap();
+            check_value_invariants(&value).unwrap();
+            replayed.push(path.file_name().unwrap().to_string_lossy().into_owned());
+        })
+        .unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(cnt, 2);
+        assert_eq!(replayed, ["a", "b"]);
+    }
+}
@@ -4,9 +4,12 @@ use ::serde::{de, de::Visitor, Deserialize, Deserializer};
 use faststr::FastStr;
 
 use super::{owned::OwnedLazyValue, value::LazyValue};
-use crate::lazyvalue::value::HasEsc;
+use crate::{error::ErrorCode, lazyvalue::value::HasEsc};
 
-impl<'de: 'a, 'a> Deserialize<'de> for LazyValue<'a> {
+// The raw JSON text of `LazyValue` is always copied when deserializing, so the `LazyValue<'a>`
+// fields need not borrow from the input, and `LazyValue<'static>` is `DeserializeOwned`. It makes
+// the same struct work with `from_str` and `from_reader`.
+impl<'de, 'a> Deserialize<'de> for LazyValue<'a> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
@@ -15,7 +18,7 @@ impl<'de: 'a, 'a> Deserialize<'de> for LazyValue<'a> {
             _marker: PhantomData<LazyValue<'a>>,
         }
 
-        impl<'de: 'a, 'a> Visitor<'de> for LazyValueVisitor<'a> {
+        impl<'de, 'a> Visitor<'de> for LazyValueVisitor<'a> {
             type Value = LazyValue<'a>;
 
             fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -30,12 +33,21 @@ impl<'de: 'a, 'a> Deserialize<'de> for LazyValue<'a> {
                 Ok(LazyValue::new(FastStr::new(v).into(), HasEsc::Yes))
             }
 
+            // copy the span, because the input may not outlive `'a`
             fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
             where
                 E: de::Error,
             {
                 Ok(LazyValue::new(FastStr::new(v).into(), HasEsc::None))
             }
+
+            // the deserializer does not know the token, such as other JSON libraries
+            fn visit_newtype_struct<D>(self, _: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                Err(de::Error::custom(ErrorCode::IncompatibleLazyValue))
+            }
         }
 
         let visit = LazyValueVisitor {
@@ -65,13 +77,11 @@ impl<'de> Deserialize<'de> for OwnedLazyValue {
             where
                 E: de::Error,
             {
+                if value_binary.len() != std::mem::size_of::<Self::Value>() {
+                    return Err(E::custom(ErrorCode::IncompatibleLazyValue));
+                }
                 // we pass the value from value_binary
                 unsafe {
-                    assert!(
-                        value_binary.len() == std::mem::size_of::<Self::Value>(),
-                        "invalid value size {}",
-                        value_binary.len()
-                    );
                     let mut dom: MaybeUninit<Self::Value> = MaybeUninit::zeroed();
                     std::ptr::copy_nonoverlapping(
                         value_binary.as_ptr() as *const Self::Value,
@@ -81,6 +91,15 @@ impl<'de> Deserialize<'de> for OwnedLazyValue {
                     Ok(dom.assume_init())
                 }
             }
+
+            // the deserializer does not know the versioned token, such as another sonic-rs
+            // version, fall back to the raw JSON
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                LazyValue::deserialize(deserializer).map(OwnedLazyValue::from)
+            }
         }
 
         deserializer.deserialize_newtype_struct(super::OWNED_LAZY_VALUE_TOKEN, visit)
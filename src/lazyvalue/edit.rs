@@ -0,0 +1,193 @@
+use std::ops::Range;
+
+use serde::Serialize;
+
+use super::{get::get_unchecked, wrap::check_raw, LazyValue};
+use crate::{
+    error::{make_error, Result},
+    index::Index,
+    serde::tri,
+};
+
+/// An editor to replace the values in a [`LazyValue`], and re-emit the JSON by splicing the
+/// replaced texts into the untouched raw JSON text.
+///
+/// Each path is located by skipping the raw JSON text, the same as [`get`][crate::get], and only
+/// the new values are serialized. It avoids parsing and serializing the whole document when only
+/// a few fields of a large JSON are changed. The whitespaces and the other values are kept as
+/// they are in the raw JSON text.
+///
+/// The paths are located in the original JSON text, so a path inside a value replaced before can
+/// not be set, and setting a value replaces the edits inside it.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{pointer, LazyValue, LazyValueEditor};
+///
+/// let json = r#"{"id": 1, "user": {"name": "foo", "tags": ["a"]}, "data": [1, 2, 3]}"#;
+/// let lv: LazyValue = sonic_rs::from_str(json).unwrap();
+///
+/// let mut editor = LazyValueEditor::new(&lv);
+/// editor.set(&pointer!["user", "name"], "bar").unwrap();
+/// editor.set_raw(&pointer!["data", -1], "[4, 5]").unwrap();
+/// assert_eq!(
+///     editor.into_string(),
+///     r#"{"id": 1, "user": {"name": "bar", "tags": ["a"]}, "data": [1, 2, [4, 5]]}"#
+/// );
+///
+/// // the path must exist
+/// let mut editor = LazyValueEditor::new(&lv);
+/// assert!(editor.set(&pointer!["user", "age"], &18).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyValueEditor<'de> {
+    value: LazyValue<'de>,
+    // the replaced ranges in the raw JSON text, sorted and disjoint
+    edits: Vec<(Range<usize>, String)>,
+}
+
+impl<'de> LazyValueEditor<'de> {
+    /// Create an editor of the raw JSON text of `value`.
+    pub fn new(value: &LazyValue<'de>) -> Self {
+        Self {
+            value: value.clone(),
+            edits: Vec::new(),
+        }
+    }
+
+    /// Replace the value at `path` with the serialized `value`.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the `path` is not found, the `path` is inside a value replaced before,
+    /// or `value` fails to serialize.
+    pub fn set<P, T>(&mut self, path: P, value: &T) -> Result<()>
+    where
+        P: IntoIterator,
+        P::Item: Index,
+        T: ?Sized + Serialize,
+    {
+        let json = tri!(crate::to_string(value));
+        self.splice(path, json)
+    }
+
+    /// Replace the value at `path` with the raw JSON text, which is validated to be a single JSON
+    /// value.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the `raw` is invalid, the `path` is not found, or the `path` is inside
+    /// a value replaced before.
+    pub fn set_raw<P>(&mut self, path: P, raw: &str) -> Result<()>
+    where
+        P: IntoIterator,
+        P::Item: Index,
+    {
+        tri!(check_raw(raw));
+        self.splice(path, raw.to_owned())
+    }
+
+    /// Return the JSON text with all the replaced values.
+    pub fn into_string(self) -> String {
+        let raw = self.value.as_raw_str();
+        let len = self.edits.iter().fold(raw.len(), |len, (range, json)| {
+            len - range.len() + json.len()
+        });
+        let mut out = String::with_capacity(len);
+        let mut pos = 0;
+        for (range, json) in &self.edits {
+            out.push_str(&raw[pos..range.start]);
+            out.push_str(json);
+            pos = range.end;
+        }
+        out.push_str(&raw[pos..]);
+        out
+    }
+
+    fn splice<P>(&mut self, path: P, json: String) -> Result<()>
+    where
+        P: IntoIterator,
+        P::Item: Index,
+    {
+        let raw = self.value.as_raw_str();
+        // SAFETY: the raw JSON text of `LazyValue` is validated when it is created
+        let sub = tri!(unsafe { get_unchecked(raw, path) });
+        let start = sub.as_raw_str().as_ptr() as usize - raw.as_ptr() as usize;
+        let range = start..start + sub.as_raw_str().len();
+
+        // the ranges of JSON values are either nested or disjoint
+        if self
+            .edits
+            .iter()
+            .any(|(r, _)| r.start <= range.start && range.end <= r.end && *r != range)
+        {
+            return Err(make_error(
+                "the path is inside a value replaced before".to_string(),
+            ));
+        }
+        self.edits
+            .retain(|(r, _)| !(range.start <= r.start && r.end <= range.end));
+        let pos = self.edits.partition_point(|(r, _)| r.start < range.start);
+        self.edits.insert(pos, (range, json));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{pointer, JsonValueTrait, PointerNode};
+
+    #[test]
+    fn test_lazy_value_editor() {
+        let json = r#"{"a": {"b": [1, {"c": "d"}], "e": null}, "f": "g", "h": [] }"#;
+        let lv: LazyValue = crate::from_str(json).unwrap();
+
+        // no edits
+        assert_eq!(LazyValueEditor::new(&lv).into_string(), json);
+
+        let mut editor = LazyValueEditor::new(&lv);
+        editor.set(&pointer!["f"], &vec![1, 2]).unwrap();
+        editor.set(&pointer!["a", "b", 1, "c"], "x\"y").unwrap();
+        editor.set_raw(&pointer!["h"], " {} ").unwrap();
+        editor.set(&pointer!["a", "e"], &true).unwrap();
+        // the same path is replaced again
+        editor.set(&pointer!["f"], &None::<u8>).unwrap();
+        let out = editor.clone().into_string();
+        assert_eq!(
+            out,
+            r#"{"a": {"b": [1, {"c": "x\"y"}], "e": true}, "f": null, "h":  {}  }"#
+        );
+        let value: crate::Value = crate::from_str(&out).unwrap();
+        assert_eq!(value["a"]["b"][1]["c"].as_str(), Some("x\"y"));
+
+        // the edits inside a replaced value are dropped
+        editor.set_raw(&pointer!["a"], "0").unwrap();
+        assert_eq!(editor.into_string(), r#"{"a": 0, "f": null, "h":  {}  }"#);
+
+        // the root value
+        let mut editor = LazyValueEditor::new(&lv);
+        editor.set(&pointer!["a", "e"], "x").unwrap();
+        editor.set_raw(&pointer![], "[]").unwrap();
+        assert_eq!(editor.into_string(), "[]");
+
+        // the errors
+        let mut editor = LazyValueEditor::new(&lv);
+        editor.set(&pointer!["a"], &1).unwrap();
+        assert!(editor.set(&pointer!["a", "e"], &1).is_err());
+        assert!(editor.set(&pointer!["x"], &1).is_err());
+        assert!(editor
+            .set(&pointer!["h", PointerNode::Wildcard], &1)
+            .is_err());
+        assert!(editor.set_raw(&pointer!["f"], "[1").is_err());
+        assert!(editor.set_raw(&pointer!["f"], "").is_err());
+        assert_eq!(editor.into_string(), r#"{"a": 1, "f": "g", "h": [] }"#);
+
+        // the value borrowed from a part of the JSON
+        let sub = crate::get(json, &pointer!["a", "b"]).unwrap();
+        let mut editor = LazyValueEditor::new(&sub);
+        editor.set(&pointer![0], &2.5).unwrap();
+        assert_eq!(editor.into_string(), r#"[2.5, {"c": "d"}]"#);
+    }
+}
@@ -0,0 +1,202 @@
+use faststr::FastStr;
+use serde::de::{Error as _, Unexpected};
+
+use crate::{
+    error::{Error, Result},
+    serde::{tri, Number},
+    JsonNumberTrait, JsonValueTrait, LazyValue, ObjectJsonIter, Value,
+};
+
+/// Convert a [`LazyValue`] into a typed value, which is used by the fields of
+/// `#[derive(LazyExtract)]` in the `derive` feature.
+///
+/// The numbers are parsed from the raw JSON text directly, and the strings are unescaped only if
+/// they have escaped chars.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{get, FromLazyValue};
+///
+/// let lv = get(r#"{"a": 255}"#, &["a"]).unwrap();
+/// assert_eq!(u8::from_lazy_value(&lv).unwrap(), 255);
+/// assert!(i8::from_lazy_value(&lv).is_err());
+/// assert_eq!(Option::<f64>::from_lazy_value(&lv).unwrap(), Some(255.0));
+/// ```
+pub trait FromLazyValue: Sized {
+    /// Convert the lazyvalue, return an error if the JSON type is mismatched.
+    fn from_lazy_value(lv: &LazyValue<'_>) -> Result<Self>;
+}
+
+// The numbers longer than this are rare, and parsed by the deserializer.
+const MAX_INLINE_NUMBER_LEN: usize = 48;
+
+// Parse the raw number with sonic-number. The raw text is copied into a padded buffer, because
+// the SIMD parsing may read beyond the end of the number.
+fn parse_number(lv: &LazyValue<'_>) -> Option<Number> {
+    let raw = lv.as_raw_str().as_bytes();
+    if raw.len() > MAX_INLINE_NUMBER_LEN {
+        return lv.as_number();
+    }
+
+    let mut buf = [0u8; MAX_INLINE_NUMBER_LEN + 16];
+    buf[..raw.len()].copy_from_slice(raw);
+    let negative = raw.first() == Some(&b'-');
+    let mut index = negative as usize;
+    match sonic_number::parse_number(&buf, &mut index, negative) {
+        Ok(num) if index == raw.len() => Some(num.into()),
+        _ => None,
+    }
+}
+
+fn invalid_type(lv: &LazyValue<'_>, exp: &'static str) -> Error {
+    Error::invalid_type(Unexpected::Other(lv.as_raw_str()), &exp)
+}
+
+macro_rules! impl_from_lazy_int {
+    ($($ty:ty),*) => {
+        $(
+            impl FromLazyValue for $ty {
+                fn from_lazy_value(lv: &LazyValue<'_>) -> Result<Self> {
+                    let num = tri!(parse_number(lv).ok_or_else(|| invalid_type(lv, "an integer")));
+                    let val = if let Some(u) = num.as_u64() {
+                        <$ty>::try_from(u).ok()
+                    } else if let Some(i) = num.as_i64() {
+                        <$ty>::try_from(i).ok()
+                    } else {
+                        None
+                    };
+                    val.ok_or_else(|| {
+                        Error::invalid_value(Unexpected::Other(lv.as_raw_str()), &stringify!($ty))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_from_lazy_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl FromLazyValue for f64 {
+    fn from_lazy_value(lv: &LazyValue<'_>) -> Result<Self> {
+        parse_number(lv)
+            .and_then(|num| num.as_f64())
+            .ok_or_else(|| invalid_type(lv, "a number"))
+    }
+}
+
+impl FromLazyValue for f32 {
+    fn from_lazy_value(lv: &LazyValue<'_>) -> Result<Self> {
+        f64::from_lazy_value(lv).map(|f| f as f32)
+    }
+}
+
+impl FromLazyValue for bool {
+    fn from_lazy_value(lv: &LazyValue<'_>) -> Result<Self> {
+        lv.as_bool().ok_or_else(|| invalid_type(lv, "a boolean"))
+    }
+}
+
+impl FromLazyValue for String {
+    fn from_lazy_value(lv: &LazyValue<'_>) -> Result<Self> {
+        lv.as_str()
+            .map(String::from)
+            .ok_or_else(|| invalid_type(lv, "a string"))
+    }
+}
+
+impl FromLazyValue for FastStr {
+    fn from_lazy_value(lv: &LazyValue<'_>) -> Result<Self> {
+        lv.as_str()
+            .map(FastStr::new)
+            .ok_or_else(|| invalid_type(lv, "a string"))
+    }
+}
+
+impl FromLazyValue for Value {
+    fn from_lazy_value(lv: &LazyValue<'_>) -> Result<Self> {
+        crate::from_str(lv.as_raw_str())
+    }
+}
+
+impl<T: FromLazyValue> FromLazyValue for Vec<T> {
+    fn from_lazy_value(lv: &LazyValue<'_>) -> Result<Self> {
+        let Some(iter) = lv.clone().into_array_iter() else {
+            return Err(invalid_type(lv, "an array"));
+        };
+        iter.map(|elem| T::from_lazy_value(&tri!(elem))).collect()
+    }
+}
+
+impl<T: FromLazyValue> FromLazyValue for Option<T> {
+    fn from_lazy_value(lv: &LazyValue<'_>) -> Result<Self> {
+        if lv.is_null() {
+            Ok(None)
+        } else {
+            T::from_lazy_value(lv).map(Some)
+        }
+    }
+}
+
+/// The helpers used by the code generated by `json_schema!`.
+#[doc(hidden)]
+pub mod __private {
+    use serde::de::Error as _;
+
+    use super::*;
+
+    pub fn object_iter<'a>(lv: &LazyValue<'a>) -> Result<ObjectJsonIter<'a>> {
+        lv.clone()
+            .into_object_iter()
+            .ok_or_else(|| invalid_type(lv, "an object"))
+    }
+
+    pub fn missing_field(field: &'static str) -> Error {
+        Error::missing_field(field)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::get;
+
+    fn extract<T: FromLazyValue>(json: &str) -> Result<T> {
+        T::from_lazy_value(&get(json, &[] as &[&str]).unwrap())
+    }
+
+    #[test]
+    fn test_from_lazy_value() {
+        assert_eq!(extract::<u8>("255").unwrap(), 255);
+        assert_eq!(extract::<i64>("-9223372036854775808").unwrap(), i64::MIN);
+        assert_eq!(extract::<u64>("18446744073709551615").unwrap(), u64::MAX);
+        assert_eq!(extract::<f64>("-1.5e3").unwrap(), -1500.0);
+        assert_eq!(extract::<f64>("3").unwrap(), 3.0);
+        assert_eq!(extract::<f32>("0.5").unwrap(), 0.5);
+        let long = format!("0.{}1", "0".repeat(60));
+        assert_eq!(extract::<f64>(&long).unwrap(), 1e-61);
+        assert!(extract::<bool>("true").unwrap());
+        assert_eq!(extract::<String>(r#""a\nb""#).unwrap(), "a\nb");
+        assert_eq!(extract::<FastStr>(r#""ab""#).unwrap().as_str(), "ab");
+        assert_eq!(extract::<Option<u32>>("null").unwrap(), None);
+        assert_eq!(extract::<Option<u32>>("1").unwrap(), Some(1));
+        assert_eq!(
+            extract::<Vec<Option<u8>>>("[1, null, 3]").unwrap(),
+            [Some(1), None, Some(3)]
+        );
+        assert_eq!(
+            extract::<Value>(r#"{"a": [true]}"#).unwrap(),
+            crate::json!({"a": [true]})
+        );
+
+        assert!(extract::<u8>("256").is_err());
+        assert!(extract::<u8>("-1").is_err());
+        assert!(extract::<i32>("1.0").is_err());
+        assert!(extract::<u32>(r#""1""#).is_err());
+        assert!(extract::<f64>("null").is_err());
+        assert!(extract::<bool>("1").is_err());
+        assert!(extract::<String>("1").is_err());
+        assert!(extract::<Vec<u8>>("[1, -1]").is_err());
+        assert!(extract::<Vec<u8>>("{}").is_err());
+    }
+}
@@ -1,14 +1,16 @@
+use std::borrow::Cow;
+
 use bytes::Bytes;
 use faststr::FastStr;
 
-use super::LazyValue;
+use super::{value::HasEsc, LazyValue};
 use crate::{
     error::Result,
     index::Index,
-    input::JsonInput,
-    parser::Parser,
+    input::{JsonInput, JsonSlice},
+    parser::{Parser, PathValue},
     pointer::PointerTree,
-    reader::{Read, Reader},
+    reader::{check_utf8, Read, Reader, Utf8Policy},
     util::utf8::from_utf8,
 };
 
@@ -179,8 +181,33 @@ where
     let slice = json.to_u8_slice();
     let reader = Read::new(slice, false);
     let mut parser = Parser::new(reader);
-    let (sub, status) = parser.get_from_with_iter_unchecked(path)?;
-    Ok(LazyValue::new(json.from_subset(sub), status.into()))
+    let got = parser.get_from_with_iter_unchecked(path)?;
+    Ok(path_value(got, |sub| json.from_subset(sub)))
+}
+
+// Create the `LazyValue` of the value got by a path, and the elements of an array slice are joined
+// into a new JSON array.
+pub(super) fn path_value<'de, F>(got: PathValue<'de>, from_subset: F) -> LazyValue<'de>
+where
+    F: FnOnce(&'de [u8]) -> JsonSlice<'de>,
+{
+    match got {
+        PathValue::Raw(sub, status) => LazyValue::new(from_subset(sub), status.into()),
+        PathValue::Slice(elems) => {
+            let len = elems.iter().map(|e| e.len() + 1).sum::<usize>() + 2;
+            let mut json = String::with_capacity(len);
+            json.push('[');
+            for (i, elem) in elems.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                // SAFETY: the JSON is valid UTF-8, which is validated or required by the callers
+                json.push_str(unsafe { std::str::from_utf8_unchecked(elem) });
+            }
+            json.push(']');
+            LazyValue::new(JsonSlice::from(json), HasEsc::None)
+        }
+    }
 }
 
 /// get_many returns multiple fields from the `PointerTree`.
@@ -388,15 +415,126 @@ where
     let slice = json.to_u8_slice();
     let reader = Read::new(slice, false);
     let mut parser = Parser::new(reader);
-    let (sub, status) = parser.get_from_with_iter(path)?;
-    let lv = LazyValue::new(json.from_subset(sub), status.into());
+    let got = parser.get_from_with_iter(path)?;
 
     // validate the utf-8 if slice
     let index = parser.read.index();
     if json.need_utf8_valid() {
         from_utf8(&slice[..index])?;
     }
-    Ok(lv)
+    Ok(path_value(got, |sub| json.from_subset(sub)))
+}
+
+/// Gets a field from a `path` as [`get`], and handles the invalid UTF-8 in the JSON with the
+/// `policy`.
+///
+/// - [`Utf8Policy::Validate`]: the same as [`get`].
+/// - [`Utf8Policy::Lossy`]: if the JSON has invalid UTF-8, the whole JSON is copied with the
+///   invalid UTF-8 replaced by `\uFFFD` (displayed as �), and the returned `LazyValue` is owned by
+///   the copy.
+/// - [`Utf8Policy::Trust`]: the same as [`get_structural`].
+///
+/// # Examples
+/// ```
+/// use sonic_rs::{get_with_utf8_policy, JsonValueTrait, Utf8Policy};
+///
+/// let json = &b"{\"a\": \"x\xffy\", \"b\": 1}"[..];
+/// assert!(get_with_utf8_policy(json, &["a"], Utf8Policy::Validate).is_err());
+///
+/// let lv = get_with_utf8_policy(json, &["a"], Utf8Policy::Lossy).unwrap();
+/// assert_eq!(lv.as_str(), Some("x\u{FFFD}y"));
+/// ```
+pub fn get_with_utf8_policy<'de, Input, Path: IntoIterator>(
+    json: Input,
+    path: Path,
+    policy: Utf8Policy,
+) -> Result<LazyValue<'de>>
+where
+    Input: JsonInput<'de>,
+    Path::Item: Index,
+{
+    match policy {
+        Utf8Policy::Validate => get(json, path),
+        // SAFETY: the JSON is trusted as valid UTF-8 by the policy
+        Utf8Policy::Trust(_) => unsafe { get_structural(json, path) },
+        Utf8Policy::Lossy => match check_utf8(json.to_u8_slice(), policy)? {
+            // SAFETY: the JSON is valid UTF-8
+            Cow::Borrowed(_) => unsafe { get_structural(json, path) },
+            Cow::Owned(fixed) => {
+                let reader = Read::new_in(FastStr::new(fixed).into(), false);
+                let mut parser = Parser::new(reader);
+                let got = parser.get_from_with_iter(path)?;
+                Ok(path_value(got, |sub| parser.read.slice_ref(sub)))
+            }
+        },
+    }
+}
+
+/// Gets a field from a `path`, which validates the JSON grammar as [`get`] but skips the UTF-8
+/// validation. It is a middle option between [`get`] and [`get_unchecked`], for the pipelines
+/// which validate the UTF-8 later or already have the valid UTF-8 bytes.
+///
+/// The guarantees of the scanned JSON text, before the end of the returned value:
+///
+/// | API               | JSON grammar | strings (control chars, escapes) | UTF-8     |
+/// |-------------------|--------------|----------------------------------|-----------|
+/// | [`get`]           | validated    | validated                        | validated |
+/// | `get_structural`  | validated    | validated                        | skipped   |
+/// | [`get_unchecked`] | skipped      | only the quotes are matched      | skipped   |
+///
+/// The `&str` and `&FastStr` inputs are always valid UTF-8, so `get_structural` is the same as
+/// [`get`] for them.
+///
+/// # Safety
+///
+/// The JSON must be valid UTF-8, otherwise the `&str` returned by the `LazyValue`, such as
+/// [`as_raw_str`][crate::LazyValue::as_raw_str], may be invalid UTF-8.
+///
+/// # Examples
+/// ```
+/// use sonic_rs::{get_structural, get_unchecked};
+///
+/// let json = br#"{"a": "\q", "b": 1}"#;
+/// let lv = unsafe { get_structural(&json[..], &["b"]) };
+/// assert!(lv.is_err());
+///
+/// // the invalid escape is not checked
+/// let lv = unsafe { get_unchecked(&json[..], &["b"]) };
+/// assert_eq!(lv.unwrap().as_raw_str(), "1");
+/// ```
+pub unsafe fn get_structural<'de, Input, Path: IntoIterator>(
+    json: Input,
+    path: Path,
+) -> Result<LazyValue<'de>>
+where
+    Input: JsonInput<'de>,
+    Path::Item: Index,
+{
+    let slice = json.to_u8_slice();
+    let reader = Read::new(slice, false);
+    let mut parser = Parser::new(reader);
+    let got = parser.get_from_with_iter(path)?;
+    Ok(path_value(got, |sub| json.from_subset(sub)))
+}
+
+/// get_many returns multiple fields from the [`PointerTree`], which validates the JSON grammar as
+/// [`get_many`] but skips the UTF-8 validation. See [`get_structural`] for the details.
+///
+/// # Safety
+///
+/// The JSON must be valid UTF-8, otherwise the `&str` returned by the `LazyValue`, such as
+/// [`as_raw_str`][crate::LazyValue::as_raw_str], may be invalid UTF-8.
+pub unsafe fn get_many_structural<'de, Input>(
+    json: Input,
+    tree: &PointerTree,
+) -> Result<Vec<LazyValue<'de>>>
+where
+    Input: JsonInput<'de>,
+{
+    let slice = json.to_u8_slice();
+    let reader = Read::new(slice, false);
+    let mut parser = Parser::new(reader);
+    parser.get_many(tree, true)
 }
 
 /// get_many returns multiple fields from the [`PointerTree`].
@@ -436,12 +574,116 @@ where
     Ok(nodes)
 }
 
+/// get_many_all returns all the values matching each path in the [`PointerTree`], the paths can
+/// have [`PointerNode::Wildcard`][crate::PointerNode::Wildcard] to match every element of an
+/// array or every value of an object.
+///
+/// The result is a `Result<Vec<Vec<LazyValue>>>`. The order of the outer `Vec` is same as the
+/// order of the tree, and the values of each path are in the order of the JSON. A missing path,
+/// or a path indexing into a value of another type, matches nothing, so its `Vec` is empty.
+///
+/// If the scanned JSON is invalid, it will return an error.
+///
+/// # Examples
+/// ```
+/// use sonic_rs::{get_many_all, pointer, PointerNode, PointerTree};
+///
+/// let json = r#"{"orders": [{"id": 1}, {"id": 2, "tags": ["a"]}, {"tags": []}], "total": 3}"#;
+/// let mut tree = PointerTree::new();
+/// tree.add_path(&pointer!["orders", PointerNode::Wildcard, "id"]);
+/// tree.add_path(&pointer!["orders", PointerNode::Wildcard, "tags", 0]);
+/// tree.add_path(&pointer!["total"]);
+///
+/// let found = get_many_all(json, &tree).unwrap();
+/// let raw: Vec<Vec<&str>> = found
+///     .iter()
+///     .map(|values| values.iter().map(|v| v.as_raw_str()).collect())
+///     .collect();
+/// assert_eq!(raw, [vec!["1", "2"], vec!["\"a\""], vec!["3"]]);
+/// ```
+pub fn get_many_all<'de, Input>(json: Input, tree: &PointerTree) -> Result<Vec<Vec<LazyValue<'de>>>>
+where
+    Input: JsonInput<'de>,
+{
+    let slice = json.to_u8_slice();
+    let reader = Read::new(slice, false);
+    let mut parser = Parser::new(reader);
+    let found = parser.get_many_all(tree)?;
+
+    // validate the utf-8 if slice
+    let index = parser.read.index();
+    if json.need_utf8_valid() {
+        from_utf8(&slice[..index])?;
+    }
+    Ok(found)
+}
+
+/// get_first returns the value of the first existing path in `paths`, and its position in
+/// `paths`. If none of the paths exists, return `Ok(None)`.
+///
+/// The paths are merged into a [`PointerTree`] and the JSON is scanned only once. The scanning
+/// stops early once the result is determined, such as the first path is found. It is useful for
+/// the fallback of renamed fields, such as "the new field name, else the old field name".
+///
+/// If the scanned JSON is invalid, it will return an error.
+///
+/// # Examples
+/// ```
+/// use sonic_rs::{get_first, pointer};
+///
+/// let json = r#"{"old_name": "foo", "meta": {"names": ["bar"]}}"#;
+/// let (pos, lv) = get_first(json, [&pointer!["new_name"], &pointer!["old_name"]])
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(pos, 1);
+/// assert_eq!(lv.as_raw_str(), "\"foo\"");
+///
+/// // the paths with different lengths
+/// let paths = [&pointer!["meta", "names", 0][..], &pointer!["old_name"][..]];
+/// let (pos, lv) = get_first(json, paths).unwrap().unwrap();
+/// assert_eq!(pos, 0);
+/// assert_eq!(lv.as_raw_str(), "\"bar\"");
+///
+/// assert!(get_first(json, [&["a"], &["b"]]).unwrap().is_none());
+/// ```
+pub fn get_first<'de, Input, Paths>(
+    json: Input,
+    paths: Paths,
+) -> Result<Option<(usize, LazyValue<'de>)>>
+where
+    Input: JsonInput<'de>,
+    Paths: IntoIterator,
+    Paths::Item: IntoIterator,
+    <Paths::Item as IntoIterator>::Item: Index,
+{
+    let mut tree = PointerTree::new();
+    for path in paths {
+        tree.add_path(path);
+    }
+
+    let slice = json.to_u8_slice();
+    let reader = Read::new(slice, false);
+    let mut parser = Parser::new(reader);
+    let found = parser.get_first(&tree)?;
+
+    // validate the utf-8 if slice
+    let index = parser.read.index();
+    if json.need_utf8_valid() {
+        from_utf8(&slice[..index])?;
+    }
+    Ok(found)
+}
+
 #[cfg(test)]
 mod test {
-    use std::str::{from_utf8_unchecked, FromStr};
+    use std::{
+        rc::Rc,
+        str::{from_utf8_unchecked, FromStr},
+        sync::Arc,
+    };
 
     use super::*;
-    use crate::{pointer, JsonPointer};
+    use crate::{error::ErrorCode, pointer, JsonPointer, PointerNode};
 
     fn test_get_ok(json: &str, path: &JsonPointer, expect: &str) {
         // get from str
@@ -470,6 +712,16 @@ mod test {
         let out = get_from_faststr(&fstr, path).unwrap();
         assert_eq!(out.as_raw_str(), expect);
 
+        // get from the shared buffers
+        let arc_str: Arc<str> = Arc::from(json);
+        assert_eq!(get(&arc_str, path).unwrap().as_raw_str(), expect);
+        let arc_bytes: Arc<[u8]> = Arc::from(json.as_bytes());
+        assert_eq!(get(&arc_bytes, path).unwrap().as_raw_str(), expect);
+        let rc_str: Rc<str> = Rc::from(json);
+        assert_eq!(get(&rc_str, path).unwrap().as_raw_str(), expect);
+        let rc_bytes: Rc<[u8]> = Rc::from(json.as_bytes());
+        assert_eq!(get(&rc_bytes, path).unwrap().as_raw_str(), expect);
+
         // get from traits
         let out = unsafe { get_unchecked(&fstr, path).unwrap() };
         assert_eq!(out.as_raw_str(), expect);
@@ -590,6 +842,56 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_get_from_end() {
+        use crate::JsonValueTrait;
+
+        let json = r#"{"a": [1, [2, 3], {"b": "last"}]}"#;
+        test_get_ok(json, &pointer!["a", -1, "b"], r#""last""#);
+        test_get_ok(json, &pointer!["a", -2, PointerNode::LAST], "3");
+        test_get_ok(json, &pointer!["a", -3], "1");
+        test_get_ok(json, &pointer!["a", 1u64, 0i64], "2");
+
+        let code = |path: &JsonPointer| get(json, path).unwrap_err().error_code();
+        assert!(matches!(
+            code(&pointer!["a", -4]),
+            ErrorCode::GetIndexOutOfArray
+        ));
+        assert!(matches!(
+            code(&pointer!["a", i64::MIN]),
+            ErrorCode::GetIndexOutOfArray
+        ));
+        assert!(matches!(
+            code(&pointer!["a", u64::MAX]),
+            ErrorCode::GetIndexOverflow
+        ));
+        assert!(matches!(
+            code(&[PointerNode::from("a"), PointerNode::FromEnd(usize::MAX)]),
+            ErrorCode::GetIndexOverflow
+        ));
+        let err = unsafe { get_unchecked(json, &pointer!["a", u64::MAX]) }.unwrap_err();
+        assert!(matches!(err.error_code(), ErrorCode::GetIndexOverflow));
+        assert!(matches!(
+            code(&[PointerNode::from("a"), PointerNode::FromEnd(0)]),
+            ErrorCode::GetIndexOutOfArray
+        ));
+        assert!(get("[]", &pointer![-1]).is_err());
+        assert!(get(r#"{"a": 1}"#, &pointer![-1]).is_err());
+        assert!(unsafe { get_unchecked("[1, 2", &pointer![-1]) }.is_err());
+
+        let lv = get(json, &pointer!["a"]).unwrap();
+        assert_eq!(lv.get(-1).get("b").as_str(), Some("last"));
+        assert!(lv.get(-4).is_none());
+        assert!(lv.get(u64::MAX).is_none());
+
+        let value: crate::Value = crate::from_str(json).unwrap();
+        assert_eq!(value["a"][-1]["b"], "last");
+        assert_eq!(value["a"][2i64], value["a"][PointerNode::LAST]);
+        assert!(value["a"].get(-4).is_none());
+        assert!(value["a"].get(i64::MIN).is_none());
+        assert_eq!(value.pointer(&pointer!["a", -2, -1]).unwrap(), 3);
+    }
+
     fn build_tree() -> PointerTree {
         let mut tree = PointerTree::default();
         tree.add_path(["a", "a_b", "a_b_c"].iter()); // 0
@@ -603,6 +905,23 @@ mod test {
         tree
     }
 
+    #[test]
+    fn test_get_from_arc() {
+        let long = "a long string which is not inlined in FastStr";
+        let json: Arc<str> = Arc::from(format!(r#"{{"a": ["{long}"]}}"#));
+        let lv = get(&json, pointer!["a", 0]).unwrap();
+        // the value shares the buffer of the input, and outlives it
+        let raw = lv.as_raw_faststr();
+        assert_eq!(raw.as_ptr(), json[7..].as_ptr());
+        drop(lv);
+        drop(json);
+        assert_eq!(raw, format!(r#""{long}""#));
+
+        let json: Arc<[u8]> = Arc::from(&b"[1, \"\xff\"]"[..]);
+        assert!(get(&json, [1]).is_err());
+        assert!(crate::to_array_iter(&json).next().unwrap().is_err());
+    }
+
     #[test]
     fn test_get_many() {
         let json = Bytes::from(
@@ -636,4 +955,327 @@ mod test {
             assert_eq!(many[6].as_raw_str(), "{\n                \"b\": [0, 1, true],\n                \"a\": {\n                    \"a_b\":{\n                        \"a_b_c\":\"hello, world!\"\n                    },\n                    \"a_a\": [0, 1, 2]\n                }\n            }");
         }
     }
+
+    #[test]
+    fn test_get_many_overlapping() {
+        let json = r#"{"a": {"x": [1, {"y": "\u0061"}], "z": 2}, "b": [3, 4]}"#;
+        let mut tree = PointerTree::new();
+        tree.add_path(pointer!["a", "x", 1, "y"]); // 0
+        tree.add_path(pointer!["a"]); // 1
+        tree.add_path(pointer!["a", "x"]); // 2
+        tree.add_path(pointer!["a", "x", 1, "y"]); // 3
+        tree.add_path(pointer!["b", 1]); // 4
+        tree.add_path(pointer!["b"]); // 5
+
+        let check = |many: Vec<LazyValue<'_>>| {
+            assert_eq!(many[0].as_raw_str(), r#""\u0061""#);
+            assert_eq!(
+                many[1].as_raw_str(),
+                r#"{"x": [1, {"y": "\u0061"}], "z": 2}"#
+            );
+            assert_eq!(many[2].as_raw_str(), r#"[1, {"y": "\u0061"}]"#);
+            assert_eq!(many[3].as_raw_str(), many[0].as_raw_str());
+            assert_eq!(many[4].as_raw_str(), "4");
+            assert_eq!(many[5].as_raw_str(), "[3, 4]");
+        };
+        check(get_many(json, &tree).unwrap());
+        check(unsafe { get_many_unchecked(json, &tree).unwrap() });
+
+        // the descendant path is not found, even if the prefix is found
+        tree.add_path(pointer!["a", "w"]);
+        assert!(get_many(json, &tree).is_err());
+
+        // a value can not be both an object and an array
+        let mut tree = PointerTree::new();
+        tree.add_path(pointer!["a", "z"]);
+        tree.add_path(pointer!["a", 0]);
+        assert!(get_many(json, &tree).is_err());
+        assert!(unsafe { get_many_unchecked(json, &tree) }.is_err());
+
+        let (pos, lv) = get_first(json, [&pointer!["b", 0], &pointer!["b", "c"]])
+            .unwrap()
+            .unwrap();
+        assert_eq!((pos, lv.as_raw_str()), (0, "3"));
+        let (pos, lv) = get_first(json, [&pointer!["a", 0], &pointer!["a", "z"]])
+            .unwrap()
+            .unwrap();
+        assert_eq!((pos, lv.as_raw_str()), (1, "2"));
+    }
+
+    #[test]
+    fn test_get_many_all() {
+        use PointerNode::Wildcard;
+
+        use crate::JsonValueTrait;
+
+        let json = r#"{"a": [{"id": 1, "x": [5]}, 2, {"id": "\u0033"}, []], "b": {"c": {"id": 4}, "d": [6, 7]}}"#;
+        let mut tree = PointerTree::new();
+        tree.add_path(pointer!["a", Wildcard, "id"]); // 0
+        tree.add_path(pointer!["a", 0, "id"]); // 1
+        tree.add_path(pointer!["a", Wildcard]); // 2
+        tree.add_path(pointer![Wildcard, Wildcard, "id"]); // 3
+        tree.add_path(pointer!["b", Wildcard, 0]); // 4
+        tree.add_path(pointer!["a", 2, "id"]); // 5
+        tree.add_path(pointer!["missing", Wildcard]); // 6
+        tree.add_path(pointer![Wildcard, Wildcard, Wildcard, 0]); // 7
+
+        let found: Vec<Vec<&str>> = get_many_all(json, &tree)
+            .unwrap()
+            .iter()
+            .map(|values| values.iter().map(|v| v.as_raw_str()).collect())
+            .collect();
+        let expect: [&[&str]; 8] = [
+            &["1", r#""\u0033""#],
+            &["1"],
+            &[r#"{"id": 1, "x": [5]}"#, "2", r#"{"id": "\u0033"}"#, "[]"],
+            &["1", r#""\u0033""#, "4"],
+            &["6"],
+            &[r#""\u0033""#],
+            &[],
+            &["5"],
+        ];
+        assert_eq!(found, expect);
+
+        // the whole JSON is scanned and validated
+        assert!(get_many_all(r#"{"a": [1, 2}"#, &tree).is_err());
+        assert!(get_many_all(r#"{"b": 1, "x": [}"#, &tree).is_err());
+        assert!(get_many_all(&b"{\"a\": [\"\xff\"]}"[..], &tree).is_err());
+
+        // the wildcard is not supported by the APIs getting a single value
+        let err = get(json, &pointer!["a", Wildcard]).unwrap_err();
+        assert!(matches!(err.error_code(), ErrorCode::GetWithWildcard));
+        assert!(get(json, &pointer!["b"]).unwrap().get(Wildcard).is_none());
+        let value: crate::Value = crate::from_str(json).unwrap();
+        assert!(value.pointer(&pointer!["a", Wildcard]).is_none());
+    }
+
+    #[test]
+    fn test_get_array_slice() {
+        use crate::{ArraySlice, JsonValueTrait};
+
+        let json = r#"{"a": [1, "x\ny", {"b": [2, 3]}, [], null]}"#;
+        let slice = |s: &str| PointerNode::Slice(s.parse::<ArraySlice>().unwrap());
+        test_get_ok(json, &pointer!["a", 0..2], r#"[1,"x\ny"]"#);
+        test_get_ok(json, &pointer!["a", 3..], "[[],null]");
+        test_get_ok(
+            json,
+            &pointer!["a", ..100],
+            r#"[1,"x\ny",{"b": [2, 3]},[],null]"#,
+        );
+        test_get_ok(
+            json,
+            &pointer!["a", slice("::2")],
+            r#"[1,{"b": [2, 3]},null]"#,
+        );
+        test_get_ok(json, &pointer!["a", slice("-2:")], "[[],null]");
+        test_get_ok(
+            json,
+            &pointer!["a", slice("::-2")],
+            r#"[null,{"b": [2, 3]},1]"#,
+        );
+        test_get_ok(json, &pointer!["a", 2, "b", 5..], "[]");
+        test_get_ok(json, &pointer!["a", 3, ..], "[]");
+
+        // the elements after the end of slice are not scanned
+        let lv = get("[1, 2, tru", &pointer![..2]).unwrap();
+        assert_eq!(lv.as_raw_str(), "[1,2]");
+        assert!(get("[1, 2, tru", &pointer![slice("-1:")]).is_err());
+        assert!(get("[1, x", &pointer![..2]).is_err());
+        assert!(unsafe { get_unchecked("[1, 2", &pointer![1..]) }.is_err());
+
+        // the slice is the new JSON array
+        let lv = get(json, &pointer!["a", 1..3]).unwrap();
+        assert_eq!(lv.get(0).as_str(), Some("x\ny"));
+        assert_eq!(lv.get(0).unwrap().as_raw_str(), r#""x\ny""#);
+        assert_eq!(lv.pointer(&pointer![1, "b", 1]).as_u64(), Some(3));
+        let lv = get(json, &pointer!["a"]).unwrap();
+        assert_eq!(
+            lv.get(slice("1::2")).unwrap().as_raw_str(),
+            r#"["x\ny",[]]"#
+        );
+
+        let code = |path: &JsonPointer| get(json, path).unwrap_err().error_code();
+        assert!(matches!(
+            code(&pointer!["a", 0..2, 0]),
+            ErrorCode::GetInArraySlice
+        ));
+        assert!(get(json, &pointer![0..1]).unwrap_err().is_unmatched_type());
+        assert!(get_with_utf8_policy(
+            &b"[\"\xff\", 1]"[..],
+            &pointer![1..],
+            crate::Utf8Policy::Lossy
+        )
+        .is_ok());
+        let value: crate::Value = crate::from_str(json).unwrap();
+        assert!(value.pointer(&pointer!["a", 0..1]).is_none());
+    }
+
+    #[test]
+    fn test_get_many_unsupported() {
+        let code = |tree: &PointerTree| get_many("[1]", tree).unwrap_err().error_code();
+        let mut tree = PointerTree::new();
+        tree.add_path(pointer![PointerNode::Wildcard]);
+        assert!(matches!(code(&tree), ErrorCode::GetWithWildcard));
+        assert!(get_first("[1]", [&pointer![PointerNode::Wildcard]]).is_err());
+
+        let paths: [&JsonPointer; 3] = [
+            &pointer![0, -1],
+            &pointer![PointerNode::LAST],
+            &pointer![0..1],
+        ];
+        for path in paths {
+            let mut tree = PointerTree::new();
+            tree.add_path(pointer![0]);
+            tree.add_path(path);
+            assert!(matches!(code(&tree), ErrorCode::GetUnsupportedInTree));
+            let err = unsafe { get_many_unchecked("[1]", &tree) }.unwrap_err();
+            assert!(matches!(err.error_code(), ErrorCode::GetUnsupportedInTree));
+            assert!(get_many_all("[1]", &tree).is_err());
+            assert!(get_first("[1]", [path]).is_err());
+        }
+    }
+
+    #[cfg(feature = "skip_bom")]
+    #[test]
+    fn test_get_skip_bom() {
+        let json = "\u{FEFF}{\"a\": [1, 2]}";
+        assert_eq!(get(json, &["a"]).unwrap().as_raw_str(), "[1, 2]");
+        assert_eq!(
+            get(json, &[] as &[&str]).unwrap().as_raw_str(),
+            r#"{"a": [1, 2]}"#
+        );
+        assert_eq!(
+            unsafe { get_unchecked(json, &pointer!["a", 1]) }
+                .unwrap()
+                .as_raw_str(),
+            "2"
+        );
+
+        let mut tree = PointerTree::new();
+        tree.add_path(&["a"]);
+        assert_eq!(get_many(json, &tree).unwrap()[0].as_raw_str(), "[1, 2]");
+
+        let mut iter = crate::to_object_iter(json);
+        let (k, v) = iter.next().unwrap().unwrap();
+        assert_eq!((&*k, v.as_raw_str()), ("a", "[1, 2]"));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_get_structural() {
+        let get_all = |json: &[u8], path: &[&str]| {
+            let checked = get(json, path).map(|lv| lv.as_raw_str().to_string());
+            let structural =
+                unsafe { get_structural(json, path) }.map(|lv| lv.as_raw_str().to_string());
+            let unchecked =
+                unsafe { get_unchecked(json, path) }.map(|lv| lv.as_raw_str().to_string());
+            (checked.ok(), structural.ok(), unchecked.ok())
+        };
+        let some = |s: &str| Some(s.to_string());
+
+        // the escaped quotes and backslashes around the SIMD chunk boundaries
+        for n in 0..70 {
+            let pad = "x".repeat(n);
+            for s in [r#"\""#, r#"\\"#, r#"\\\""#, r#"\/\\\\"#, ""] {
+                let json = format!(r#"{{"a": "{pad}{s}", "b": ["{s}{pad}"], "c": 1}}"#);
+                let expect = some("1");
+                assert_eq!(
+                    get_all(json.as_bytes(), &["c"]),
+                    (expect.clone(), expect.clone(), expect),
+                    "{json}"
+                );
+            }
+        }
+
+        // the strings are validated, but the UTF-8 is not
+        let invalid_utf8 = b"{\"a\": \"\xff\", \"c\": 1}";
+        assert_eq!(get_all(invalid_utf8, &["c"]), (None, some("1"), some("1")));
+        for json in [
+            &b"{\"a\": \"\x01\", \"c\": 1}"[..],
+            br#"{"a": "\q", "c": 1}"#,
+            br#"{"a": [tru], "c": 1}"#,
+            br#"{"a": {"b" 1}, "c": 1}"#,
+        ] {
+            assert_eq!(get_all(json, &["c"]), (None, None, some("1")));
+        }
+
+        let mut tree = PointerTree::new();
+        tree.add_path(&["c"]);
+        let nodes = unsafe { get_many_structural(&invalid_utf8[..], &tree) }.unwrap();
+        assert_eq!(nodes[0].as_raw_str(), "1");
+        assert!(get_many(&invalid_utf8[..], &tree).is_err());
+        let invalid = br#"{"a": "\q", "c": 1}"#;
+        assert!(unsafe { get_many_structural(&invalid[..], &tree) }.is_err());
+    }
+
+    #[test]
+    fn test_get_first() {
+        let json = r#"{"b": [0, {"c": "d"}], "a": 1, "e": {"f": null}, "g": "\u0068"}"#;
+        let first = |paths: &[&[PointerNode]]| {
+            get_first(json, paths.iter().copied())
+                .unwrap()
+                .map(|(pos, lv)| (pos, lv.as_raw_str().to_string()))
+        };
+
+        assert_eq!(first(&[]), None);
+        assert_eq!(first(&[&pointer!["x"], &pointer!["y", 1]]), None);
+        assert_eq!(first(&[&pointer!["a"]]), Some((0, "1".into())));
+        assert_eq!(
+            first(&[&pointer!["x"], &pointer!["a"], &pointer!["b"]]),
+            Some((1, "1".into()))
+        );
+        assert_eq!(
+            first(&[&pointer!["e", "x"], &pointer!["b", 1, "c"], &pointer!["a"]]),
+            Some((1, "\"d\"".into()))
+        );
+        assert_eq!(
+            first(&[&pointer!["b", 1], &pointer!["b"]]),
+            Some((0, r#"{"c": "d"}"#.into()))
+        );
+        assert_eq!(
+            first(&[&pointer!["b", 5], &pointer!["a", "x"], &pointer!["e", "f"]]),
+            Some((2, "null".into()))
+        );
+        assert_eq!(first(&[&pointer!["g"]]), Some((0, r#""\u0068""#.into())));
+        assert_eq!(first(&[&pointer![]]), Some((0, json.into())));
+
+        // stop early once the first path is found, the remaining invalid JSON is not scanned
+        let json = r#"{"a": 1, "b": x"#;
+        let (pos, lv) = get_first(json, [&["a"], &["b"]]).unwrap().unwrap();
+        assert_eq!((pos, lv.as_raw_str()), (0, "1"));
+        assert!(get_first(json, [&["b"], &["a"]]).is_err());
+        assert!(get_first(r#"{"a": [1, 2"#, [&pointer!["a", 5]]).is_err());
+    }
+
+    #[test]
+    fn test_get_with_utf8_policy() {
+        use crate::JsonValueTrait;
+
+        let json = &b"{\"a\": \"x\xff\", \"\xfe\": [1, \"\xc3\xa9\"], \"c\": 2}"[..];
+        for path in [&pointer!["a"], &pointer!["c"]] {
+            assert!(get_with_utf8_policy(json, path, Utf8Policy::Validate).is_err());
+        }
+        // the invalid UTF-8 after the value is not checked, as `get`
+        let valid_prefix = &b"{\"c\": 2, \"a\": \"x\xff\"}"[..];
+        let lv = get_with_utf8_policy(valid_prefix, &["c"], Utf8Policy::Validate).unwrap();
+        assert_eq!(lv.as_raw_str(), "2");
+
+        let lv = get_with_utf8_policy(json, &["a"], Utf8Policy::Lossy).unwrap();
+        assert_eq!(lv.as_raw_str(), "\"x\u{FFFD}\"");
+        assert_eq!(lv.as_str(), Some("x\u{FFFD}"));
+        let lv = get_with_utf8_policy(json, &pointer!["\u{FFFD}", 1], Utf8Policy::Lossy).unwrap();
+        assert_eq!(lv.as_str(), Some("é"));
+        let lv = get_with_utf8_policy(json, &["c"], Utf8Policy::Lossy).unwrap();
+        assert_eq!(lv.as_u64(), Some(2));
+
+        // the valid JSON is not copied
+        let valid = &br#"{"a": "xy"}"#[..];
+        let lv = get_with_utf8_policy(valid, &["a"], Utf8Policy::Lossy).unwrap();
+        assert_eq!(lv.as_raw_str().as_ptr(), valid[6..].as_ptr());
+
+        let policy = unsafe { Utf8Policy::trust() };
+        let lv = get_with_utf8_policy(valid, &["a"], policy).unwrap();
+        assert_eq!(lv.as_str(), Some("xy"));
+        assert!(get_with_utf8_policy(&br#"{"a": x}"#[..], &["a"], policy).is_err());
+    }
 }
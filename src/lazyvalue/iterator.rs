@@ -1,11 +1,20 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use faststr::FastStr;
 
 use crate::{
-    error::Result,
+    config::DedupPolicy,
+    error::{ErrorCode, Result},
     input::{JsonInput, JsonSlice},
     lazyvalue::LazyValue,
-    parser::{Pair, Parser, DEFAULT_KEY_BUF_CAPACITY},
+    parser::{Pair, Parser},
     reader::{Read, Reader},
+    serde::tri,
+    util::scratch::ScratchBuf,
+    PointerNode,
 };
 /// A lazied iterator for JSON object text. It will parse the JSON when iterating.
 ///
@@ -35,12 +44,21 @@ use crate::{
 /// ```
 pub struct ObjectJsonIter<'de> {
     parser: Parser<Read<'de>>,
-    strbuf: Vec<u8>,
+    strbuf: ScratchBuf,
     first: bool,
     ending: bool,
     skip_strict: bool,
+    dedup: DedupPolicy,
+    // the keys visited, for `DedupPolicy::FirstWins` and `DedupPolicy::Error`
+    seen: HashSet<Cow<'de, str>>,
+    // the deduplicated entries, for `DedupPolicy::LastWins`
+    buffered: Option<std::vec::IntoIter<ObjectEntry<'de>>>,
+    // only the entries of these keys are yielded, for `to_object_iter_filtered`
+    keys: Option<Vec<String>>,
 }
 
+type ObjectEntry<'de> = Result<(Cow<'de, str>, LazyValue<'de>)>;
+
 /// A lazied iterator for JSON array text. It will parse the JSON when iterating.
 ///
 /// The item of the iterator is [`Result<LazyValue>`][`crate::LazyValue`].
@@ -80,10 +98,14 @@ impl<'de> ObjectJsonIter<'de> {
     pub(crate) fn new_inner(input: JsonSlice<'de>) -> Self {
         Self {
             parser: Parser::new(Read::new_in(input, false)),
-            strbuf: Vec::with_capacity(DEFAULT_KEY_BUF_CAPACITY),
+            strbuf: ScratchBuf::new(),
             first: true,
             ending: false,
             skip_strict: false,
+            dedup: DedupPolicy::KeepAll,
+            seen: HashSet::new(),
+            buffered: None,
+            keys: None,
         }
     }
 
@@ -94,14 +116,95 @@ impl<'de> ObjectJsonIter<'de> {
 
         Self {
             parser: Parser::new(Read::new_in(input.to_json_slice(), validate_utf8)),
-            strbuf: Vec::with_capacity(DEFAULT_KEY_BUF_CAPACITY),
+            strbuf: ScratchBuf::new(),
             first: true,
             ending: false,
             skip_strict,
+            dedup: DedupPolicy::KeepAll,
+            seen: HashSet::new(),
+            buffered: None,
+            keys: None,
+        }
+    }
+
+    /// Set the policy of the duplicate keys in the object. The duplicate keys are kept by
+    /// default.
+    ///
+    /// With [`DedupPolicy::LastWins`], the whole object is scanned at the first `next`, because
+    /// the last value of a key is known only at the end of the object. The entries before an
+    /// error are still yielded before the error. The values are lazy and not parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::{pointer, to_object_iter, DedupPolicy};
+    ///
+    /// let json = r#"{"a": 1, "b": 2, "a": 3}"#;
+    /// let entries = |policy| -> sonic_rs::Result<Vec<String>> {
+    ///     to_object_iter(json)
+    ///         .dedup_keys(policy)
+    ///         .map(|ret| ret.map(|(k, v)| format!("{k}={}", v.as_raw_str())))
+    ///         .collect()
+    /// };
+    ///
+    /// assert_eq!(entries(DedupPolicy::FirstWins).unwrap(), ["a=1", "b=2"]);
+    /// assert_eq!(entries(DedupPolicy::LastWins).unwrap(), ["a=3", "b=2"]);
+    ///
+    /// let err = entries(DedupPolicy::Error).unwrap_err();
+    /// assert_eq!(err.duplicate_key(), Some(&pointer!["a"][..]));
+    /// ```
+    pub fn dedup_keys(mut self, policy: DedupPolicy) -> Self {
+        self.dedup = policy;
+        self
+    }
+
+    // Skip the entries whose keys are visited, or return an error with `DedupPolicy::Error`.
+    fn next_unique(&mut self) -> Option<ObjectEntry<'de>> {
+        loop {
+            let (key, val) = match self.next_entry_impl()? {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err)),
+            };
+            if self.seen.insert(key.clone()) {
+                return Some(Ok((key, val)));
+            }
+            if self.dedup == DedupPolicy::Error {
+                self.ending = true;
+                let pointer = vec![PointerNode::Key(FastStr::new(key))];
+                return Some(Err(self.parser.error(ErrorCode::DuplicateKey(pointer))));
+            }
+        }
+    }
+
+    // Collect all the entries at first, the duplicate key keeps the first position and the last
+    // value.
+    fn next_last_wins(&mut self) -> Option<ObjectEntry<'de>> {
+        if self.buffered.is_none() {
+            let mut entries: Vec<ObjectEntry<'de>> = Vec::new();
+            let mut positions = HashMap::new();
+            while let Some(ret) = self.next_entry_impl() {
+                let Ok((key, val)) = ret else {
+                    entries.push(ret);
+                    break;
+                };
+                match positions.get(&key) {
+                    Some(&i) => {
+                        if let Some(Ok((_, old))) = entries.get_mut(i) {
+                            *old = val;
+                        }
+                    }
+                    None => {
+                        positions.insert(key.clone(), entries.len());
+                        entries.push(Ok((key, val)));
+                    }
+                }
+            }
+            self.buffered = Some(entries.into_iter());
         }
+        self.buffered.as_mut()?.next()
     }
 
-    fn next_entry_impl(&mut self) -> Option<Result<(Cow<'de, str>, LazyValue<'de>)>> {
+    fn next_entry_impl(&mut self) -> Option<ObjectEntry<'de>> {
         if self.ending {
             return None;
         }
@@ -114,10 +217,19 @@ impl<'de> ObjectJsonIter<'de> {
             }
         }
 
-        match self
-            .parser
-            .parse_entry_lazy(&mut self.strbuf, &mut self.first, self.skip_strict)
-        {
+        let ret = match &self.keys {
+            Some(keys) => self.parser.parse_entry_lazy_filtered(
+                &mut self.strbuf,
+                &mut self.first,
+                self.skip_strict,
+                keys,
+            ),
+            None => {
+                self.parser
+                    .parse_entry_lazy(&mut self.strbuf, &mut self.first, self.skip_strict)
+            }
+        };
+        match ret {
             Ok(ret) => {
                 if let Some(Pair { key, val, status }) = ret {
                     let val = self.parser.read.slice_ref(val);
@@ -236,6 +348,44 @@ pub fn to_object_iter<'de, I: JsonInput<'de>>(json: I) -> ObjectJsonIter<'de> {
     ObjectJsonIter::new(json, true)
 }
 
+/// Traverse the JSON object text through a lazy iterator, which only yields the entries of the
+/// `keys` in the order of the JSON text.
+///
+/// The values of the other keys are skipped, and their keys are compared in place without being
+/// copied. It is a lighter [`get_many`][crate::get_many] for the flat objects, without building a
+/// [`PointerTree`][crate::PointerTree]. The skipped values are still validated, the same as
+/// [`to_object_iter`].
+///
+/// # Errors
+///
+/// If the JSON is empty, not a object or parse error, the result will be Err and the `next()` will
+/// return `None`.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{to_object_iter_filtered, JsonValueTrait};
+///
+/// let json = r#"{"id": 1, "payload": {"large": [1, 2, 3]}, "name": "a", "id": 2}"#;
+/// let entries: Vec<_> = to_object_iter_filtered(json, &["name", "id"])
+///     .map(|ret| ret.unwrap())
+///     .map(|(k, v)| format!("{k}={}", v.as_raw_str()))
+///     .collect();
+/// assert_eq!(entries, ["id=1", r#"name="a""#, "id=2"]);
+///
+/// // the skipped values are validated
+/// let ret: sonic_rs::Result<Vec<_>> = to_object_iter_filtered(r#"{"a": [1,}"#, &["b"]).collect();
+/// assert!(ret.is_err());
+/// ```
+pub fn to_object_iter_filtered<'de, I: JsonInput<'de>>(
+    json: I,
+    keys: &[&str],
+) -> ObjectJsonIter<'de> {
+    let mut iter = ObjectJsonIter::new(json, true);
+    iter.keys = Some(keys.iter().map(|k| k.to_string()).collect());
+    iter
+}
+
 /// Traverse the JSON array text through a lazy iterator. The JSON parsing will doing when
 /// iterating.
 ///
@@ -344,11 +494,76 @@ pub unsafe fn to_array_iter_unchecked<'de, I: JsonInput<'de>>(json: I) -> ArrayJ
     ArrayJsonIter::new(json, false)
 }
 
+/// Build a lookup map of the top-level entries in the JSON object text, by a single pass of
+/// [`to_object_iter`]. The values are lazy and not parsed.
+///
+/// If there are duplicate keys, the last one wins. The keys are borrowed from the JSON text unless
+/// they are escaped.
+///
+/// # Errors
+///
+/// If the JSON is empty, not a object or parse error, it will return Err.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{to_object_map, JsonValueTrait};
+///
+/// let map = to_object_map(r#"{"a": null, "b": [1, 2, 3], "c\n": "d"}"#).unwrap();
+/// assert!(map["a"].is_null());
+/// assert_eq!(map["b"].as_raw_str(), "[1, 2, 3]");
+/// assert_eq!(map["c\n"].as_str(), Some("d"));
+///
+/// assert!(to_object_map(r#"[1, 2]"#).is_err());
+/// ```
+pub fn to_object_map<'de, I: JsonInput<'de>>(
+    json: I,
+) -> Result<HashMap<Cow<'de, str>, LazyValue<'de>>> {
+    let mut map = HashMap::new();
+    for ret in to_object_iter(json) {
+        let (k, v) = tri!(ret);
+        map.insert(k, v);
+    }
+    Ok(map)
+}
+
+/// Build a lookup map of the top-level entries in the JSON object text, with the
+/// [FastStr][`faststr::FastStr`] keys. See [`to_object_map`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use faststr::FastStr;
+/// use sonic_rs::{to_object_map_faststr, JsonValueTrait};
+///
+/// let map = to_object_map_faststr(r#"{"a": 1, "b": true}"#).unwrap();
+/// assert_eq!(map[&FastStr::new("a")].as_u64(), Some(1));
+/// assert_eq!(map.get("b").unwrap().as_bool(), Some(true));
+/// ```
+pub fn to_object_map_faststr<'de, I: JsonInput<'de>>(
+    json: I,
+) -> Result<HashMap<FastStr, LazyValue<'de>>> {
+    let mut map = HashMap::new();
+    for ret in to_object_iter(json) {
+        let (k, v) = tri!(ret);
+        let key = match k {
+            Cow::Borrowed(k) => FastStr::new(k),
+            Cow::Owned(k) => FastStr::from(k),
+        };
+        map.insert(key, v);
+    }
+    Ok(map)
+}
+
 impl<'de> Iterator for ObjectJsonIter<'de> {
     type Item = Result<(Cow<'de, str>, LazyValue<'de>)>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.next_entry_impl()
+        match self.dedup {
+            DedupPolicy::KeepAll => self.next_entry_impl(),
+            DedupPolicy::LastWins => self.next_last_wins(),
+            _ => self.next_unique(),
+        }
     }
 }
 
@@ -555,4 +770,95 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_object_map() {
+        let json = r#"{"a": 1, "b\"": [2], "a": "dup", "": {}}"#;
+        let map = to_object_map(json).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["a"].as_str(), Some("dup"));
+        assert_eq!(map["b\""].as_raw_str(), "[2]");
+        assert_eq!(map[""].get_type(), JsonType::Object);
+        assert!(matches!(
+            map.get_key_value("a").unwrap().0,
+            Cow::Borrowed(_)
+        ));
+
+        let map = to_object_map_faststr(json).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get("b\"").unwrap().as_raw_str(), "[2]");
+
+        assert!(to_object_map("").is_err());
+        assert!(to_object_map(r#"{"a": 1,"#).is_err());
+        assert!(to_object_map_faststr("[]").is_err());
+        assert!(to_object_map("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_object_iter_dedup_keys() {
+        let json = r#"{"a": 1, "\u0061": [2], "b": {"a": 3, "a": 4}, "b": 5, "c": 6"#;
+        let collect = |policy| -> Vec<Result<String>> {
+            to_object_iter(json)
+                .dedup_keys(policy)
+                .map(|ret| ret.map(|(k, v)| format!("{k}={}", v.as_raw_str())))
+                .collect()
+        };
+
+        let keep = collect(DedupPolicy::KeepAll);
+        assert_eq!(keep.len(), 6);
+        assert!(keep[5].is_err());
+
+        // the nested objects are not deduplicated, and the error is at the end
+        let first = collect(DedupPolicy::FirstWins);
+        let ok: Vec<_> = first[..2]
+            .iter()
+            .map(|e| e.as_ref().unwrap().as_str())
+            .collect();
+        assert_eq!(ok, ["a=1", r#"b={"a": 3, "a": 4}"#]);
+        assert!(first[3].is_err());
+        assert_eq!(first.len(), 4);
+
+        let last = collect(DedupPolicy::LastWins);
+        let ok: Vec<_> = last[..3]
+            .iter()
+            .map(|e| e.as_ref().unwrap().as_str())
+            .collect();
+        assert_eq!(ok, ["a=[2]", "b=5", "c=6"]);
+        assert!(last[3].is_err());
+        assert_eq!(last.len(), 4);
+
+        let err = collect(DedupPolicy::Error);
+        assert_eq!(err.len(), 2);
+        let err = err[1].as_ref().unwrap_err();
+        assert_eq!(err.duplicate_key(), Some(&crate::pointer!["a"][..]));
+    }
+
+    #[test]
+    fn test_object_iter_filtered() {
+        let json = r#"{"a": 1, "\u0062": "x", "skip\n": {"a": [2]}, "c": null, "a": 3}"#;
+        let collect = |keys: &[&str]| -> Vec<String> {
+            to_object_iter_filtered(json, keys)
+                .map(|ret| ret.unwrap())
+                .map(|(k, v)| format!("{k}={}", v.as_raw_str()))
+                .collect()
+        };
+        assert_eq!(collect(&["a"]), ["a=1", "a=3"]);
+        // the escaped keys are compared after unescaping
+        assert_eq!(collect(&["b", "c"]), [r#"b="x""#, "c=null"]);
+        assert_eq!(collect(&["skip\n"]), ["skip\n={\"a\": [2]}"]);
+        assert!(collect(&["d"]).is_empty());
+        assert!(collect(&[]).is_empty());
+
+        // the filter works with the deduplication
+        let last: Vec<_> = to_object_iter_filtered(json, &["a", "c"])
+            .dedup_keys(DedupPolicy::LastWins)
+            .map(|ret| ret.unwrap().1.as_raw_str().to_string())
+            .collect();
+        assert_eq!(last, ["3", "null"]);
+
+        for json in ["", "[]", r#"{"a": 1,}"#, r#"{"b": [1, x], "a": 1}"#] {
+            let ret: Vec<_> = to_object_iter_filtered(json, &["a"]).collect();
+            assert!(ret.last().unwrap().is_err(), "{json}");
+        }
+    }
 }
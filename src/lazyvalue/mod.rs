@@ -1,27 +1,51 @@
 //! A lazy type to representing a unparsed raw JSON text for lazy operators.
 
+mod edit;
+mod extract;
 mod get;
 mod iterator;
 pub(crate) mod owned;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "stream")]
+mod stream;
 pub(crate) mod value;
+mod wrap;
 
+#[doc(hidden)]
+pub use self::extract::__private;
+#[cfg(feature = "rayon")]
+#[doc(inline)]
+pub use self::parallel::{par_array_iter, ParArrayIter};
+#[cfg(feature = "stream")]
+#[doc(inline)]
+pub use self::stream::StreamGetter;
 #[doc(inline)]
 pub use self::{
+    edit::LazyValueEditor,
+    extract::FromLazyValue,
     get::{
-        get, get_from_bytes, get_from_bytes_unchecked, get_from_faststr,
+        get, get_first, get_from_bytes, get_from_bytes_unchecked, get_from_faststr,
         get_from_faststr_unchecked, get_from_slice, get_from_slice_unchecked, get_from_str,
-        get_from_str_unchecked, get_many, get_many_unchecked, get_unchecked,
+        get_from_str_unchecked, get_many, get_many_all, get_many_structural, get_many_unchecked,
+        get_structural, get_unchecked, get_with_utf8_policy,
     },
     iterator::{
-        to_array_iter, to_array_iter_unchecked, to_object_iter, to_object_iter_unchecked,
-        ArrayJsonIter, ObjectJsonIter,
+        to_array_iter, to_array_iter_unchecked, to_object_iter, to_object_iter_filtered,
+        to_object_iter_unchecked, to_object_map, to_object_map_faststr, ArrayJsonIter,
+        ObjectJsonIter,
     },
     owned::{LazyArray, LazyObject, OwnedLazyValue},
     value::LazyValue,
+    wrap::{wrap_in_array, wrap_in_array_unchecked, wrap_in_object, wrap_in_object_unchecked},
 };
 
 pub(crate) mod de;
 pub(crate) mod ser;
 pub(crate) const TOKEN: &str = "$sonic_rs::LazyValue";
 
-pub(crate) const OWNED_LAZY_VALUE_TOKEN: &str = "$sonic::OwnedLv";
+// The `OwnedLazyValue` is passed through the deserializer by its memory layout, which may change
+// between versions. The token is versioned, so the deserializer of another sonic-rs version does
+// not recognize it, and falls back to the raw JSON by `TOKEN`, which is stable.
+pub(crate) const OWNED_LAZY_VALUE_TOKEN: &str =
+    concat!("$sonic::OwnedLv@", env!("CARGO_PKG_VERSION"));
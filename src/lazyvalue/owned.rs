@@ -1,7 +1,10 @@
 use std::{
     fmt::{self, Debug, Display},
     str::from_utf8_unchecked,
-    sync::atomic::{AtomicPtr, Ordering},
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Arc,
+    },
 };
 
 use faststr::FastStr;
@@ -10,8 +13,8 @@ use serde::ser::{SerializeMap, SerializeStruct};
 
 use super::value::HasEsc;
 use crate::{
-    index::Index, input::JsonSlice, prelude::*, serde::Number, JsonType, JsonValueTrait, LazyValue,
-    RawNumber, Result,
+    index::Index, input::JsonSlice, pointer::tree::PointerTreeNode, prelude::*, serde::Number,
+    util::scratch::ScratchBuf, JsonType, JsonValueTrait, LazyValue, PointerTree, RawNumber, Result,
 };
 
 /// OwnedLazyValue wrappers a unparsed raw JSON text. It is owned and support `Get, Set`
@@ -89,13 +92,13 @@ impl From<Number> for OwnedLazyValue {
 
 impl From<Vec<(FastStr, OwnedLazyValue)>> for OwnedLazyValue {
     fn from(v: Vec<(FastStr, OwnedLazyValue)>) -> Self {
-        Self(LazyPacked::Parsed(Parsed::LazyObject(v)))
+        Self(LazyPacked::Parsed(Parsed::LazyObject(Arc::new(v))))
     }
 }
 
 impl From<Vec<OwnedLazyValue>> for OwnedLazyValue {
     fn from(v: Vec<OwnedLazyValue>) -> Self {
-        Self(LazyPacked::Parsed(Parsed::LazyArray(v)))
+        Self(LazyPacked::Parsed(Parsed::LazyArray(Arc::new(v))))
     }
 }
 
@@ -151,7 +154,7 @@ impl LazyRaw {
         }
 
         let mut parser = crate::parser::Parser::new(crate::Read::from(&self.raw));
-        let mut strbuf: Vec<u8> = Vec::new();
+        let mut strbuf = ScratchBuf::new();
         let olv: OwnedLazyValue = parser.load_owned_lazyvalue(&mut strbuf)?;
         let OwnedLazyValue(LazyPacked::Parsed(v)) = olv else {
             unreachable!("must be lazy parsed");
@@ -181,7 +184,7 @@ impl LazyRaw {
         }
 
         let mut parser = crate::parser::Parser::new(crate::Read::from(&self.raw));
-        let mut strbuf: Vec<u8> = Vec::new();
+        let mut strbuf = ScratchBuf::new();
         let olv: OwnedLazyValue = parser.load_owned_lazyvalue(&mut strbuf)?;
         let OwnedLazyValue(LazyPacked::Parsed(v)) = olv else {
             unreachable!("must be lazy parsed");
@@ -191,7 +194,7 @@ impl LazyRaw {
 
     fn get<I: Index>(&self, idx: I) -> Option<&OwnedLazyValue> {
         match self.get_type() {
-            JsonType::Array if idx.as_index().is_some() => {
+            JsonType::Array if idx.as_index().is_some() || idx.as_index_from_end().is_some() => {
                 let parsed = self.load().ok()?;
                 parsed.get(idx)
             }
@@ -250,7 +253,8 @@ impl LazyRaw {
             })
         } else {
             // # Safety
-            // the pointer is immutable here, and we can clone it
+            // the pointer is immutable here, and we can clone it. Cloning the parsed object or
+            // array is cheap, because their bodies are shared.
             Err(unsafe { (*parsed).clone() })
         }
     }
@@ -280,10 +284,12 @@ impl Clone for LazyPacked {
     }
 }
 
+// The bodies of objects and arrays are shared by `Arc`, so that cloning a parsed value is cheap.
+// They are copied on write when mutated.
 #[derive(Debug, Clone)]
 pub(crate) enum Parsed {
-    LazyObject(Vec<(FastStr, OwnedLazyValue)>),
-    LazyArray(Vec<OwnedLazyValue>),
+    LazyObject(Arc<Vec<(FastStr, OwnedLazyValue)>>),
+    LazyArray(Arc<Vec<OwnedLazyValue>>),
     String(FastStr),
     Number(Number),
     Null,
@@ -306,7 +312,7 @@ impl Parsed {
         match self {
             Parsed::LazyObject(obj) => {
                 if let Some(key) = index.as_key() {
-                    for (k, v) in obj {
+                    for (k, v) in obj.iter() {
                         if k == key {
                             return Some(v);
                         }
@@ -315,7 +321,7 @@ impl Parsed {
                 None
             }
             Parsed::LazyArray(arr) => {
-                if let Some(index) = index.as_index() {
+                if let Some(index) = index.as_array_index(arr.len()) {
                     arr.get(index)
                 } else {
                     None
@@ -329,7 +335,7 @@ impl Parsed {
         match self {
             Parsed::LazyObject(obj) => {
                 if let Some(key) = index.as_key() {
-                    for (k, v) in obj {
+                    for (k, v) in Arc::make_mut(obj) {
                         if k == key {
                             return Some(v);
                         }
@@ -338,8 +344,8 @@ impl Parsed {
                 None
             }
             Parsed::LazyArray(arr) => {
-                if let Some(index) = index.as_index() {
-                    arr.get_mut(index)
+                if let Some(index) = index.as_array_index(arr.len()) {
+                    Arc::make_mut(arr).get_mut(index)
                 } else {
                     None
                 }
@@ -538,6 +544,75 @@ impl OwnedLazyValue {
         std::mem::take(self)
     }
 
+    /// Returns the raw JSON text, if the value is not parsed or mutated yet. The parsed values,
+    /// such as the objects after mutation or the values created from the Rust types, return
+    /// `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::OwnedLazyValue;
+    ///
+    /// let lv: OwnedLazyValue = sonic_rs::from_str(r#" {"a": [1, 2]} "#).unwrap();
+    /// assert_eq!(lv.as_raw_str(), Some(r#"{"a": [1, 2]}"#));
+    /// assert_eq!(OwnedLazyValue::from(true).as_raw_str(), None);
+    /// ```
+    pub fn as_raw_str(&self) -> Option<&str> {
+        match &self.0 {
+            LazyPacked::Raw(raw) => Some(raw.raw.as_str()),
+            LazyPacked::NonEscStrRaw(raw) => Some(raw.as_str()),
+            LazyPacked::Parsed(_) => None,
+        }
+    }
+
+    /// Get the values of all the paths in the [`PointerTree`], as
+    /// [`get_many`][crate::get_many] for the JSON text. The result is in the order of the paths,
+    /// and `None` if the path is not found.
+    ///
+    /// Only the objects and arrays on the paths are parsed, and the parsed nodes are cached. Use
+    /// [`pointer`][JsonValueTrait::pointer] for a single path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::{pointer, JsonValueTrait, OwnedLazyValue, PointerTree};
+    ///
+    /// let json = r#"{"a": {"b": [1, "x", {"c": null}]}, "d": true}"#;
+    /// let lv: OwnedLazyValue = sonic_rs::from_str(json).unwrap();
+    ///
+    /// let mut tree = PointerTree::new();
+    /// tree.add_path(&pointer!["a", "b", 1]);
+    /// tree.add_path(&pointer!["d"]);
+    /// tree.add_path(&pointer!["a", "z"]);
+    /// let nodes = lv.get_many(&tree);
+    /// assert_eq!(nodes[0].unwrap().as_str(), Some("x"));
+    /// assert_eq!(nodes[1].unwrap().as_bool(), Some(true));
+    /// assert!(nodes[2].is_none());
+    ///
+    /// assert!(lv.pointer(&pointer!["a", "b", 2, "c"]).unwrap().is_null());
+    /// ```
+    pub fn get_many(&self, tree: &PointerTree) -> Vec<Option<&OwnedLazyValue>> {
+        let mut out = vec![None; tree.size()];
+        self.get_many_rec(&tree.root, &mut out);
+        out
+    }
+
+    fn get_many_rec<'a>(&'a self, node: &PointerTreeNode, out: &mut [Option<&'a Self>]) {
+        for &order in &node.order {
+            out[order] = Some(self);
+        }
+        for (key, child) in &node.keys {
+            if let Some(v) = self.get(key.as_str()) {
+                v.get_many_rec(child, out);
+            }
+        }
+        for (&index, child) in &node.indexes {
+            if let Some(v) = self.get(index) {
+                v.get_many_rec(child, out);
+            }
+        }
+    }
+
     pub(crate) fn new(raw: JsonSlice, status: HasEsc) -> Self {
         let raw = match raw {
             JsonSlice::Raw(r) => FastStr::new(unsafe { from_utf8_unchecked(r) }),
@@ -562,7 +637,9 @@ impl OwnedLazyValue {
         };
 
         match raw.get_type() {
-            JsonType::Array if index.as_index().is_some() => {
+            JsonType::Array
+                if index.as_index().is_some() || index.as_index_from_end().is_some() =>
+            {
                 let parsed = raw.parse().ok()?;
                 *self = Self(LazyPacked::Parsed(parsed));
             }
@@ -583,8 +660,10 @@ impl OwnedLazyValue {
 
 impl<'de> From<LazyValue<'de>> for OwnedLazyValue {
     fn from(lv: LazyValue<'de>) -> Self {
-        let raw = unsafe { lv.raw.as_faststr() };
-        if lv.inner.no_escaped() && raw.as_bytes()[0] == b'"' {
+        let no_escaped = lv.inner.no_escaped();
+        // the refcounted input is moved without copying
+        let raw = unsafe { lv.raw.into_faststr() };
+        if no_escaped && raw.as_bytes()[0] == b'"' {
             return Self(LazyPacked::NonEscStrRaw(raw));
         }
 
@@ -624,12 +703,12 @@ impl serde::ser::Serialize for OwnedLazyValue {
             LazyPacked::Parsed(Parsed::LazyObject(vec)) => {
                 // if expected to be sort-keys, should use `sonic_rs::Value`
                 let mut map = serializer.serialize_map(Some(vec.len()))?;
-                for (k, v) in vec {
+                for (k, v) in vec.iter() {
                     map.serialize_entry(k, v)?;
                 }
                 map.end()
             }
-            LazyPacked::Parsed(Parsed::LazyArray(vec)) => vec.serialize(serializer),
+            LazyPacked::Parsed(Parsed::LazyArray(vec)) => vec.as_slice().serialize(serializer),
             LazyPacked::Parsed(Parsed::String(s)) => s.serialize(serializer),
             LazyPacked::Parsed(Parsed::Number(n)) => n.serialize(serializer),
             LazyPacked::Parsed(Parsed::Bool(b)) => b.serialize(serializer),
@@ -656,7 +735,7 @@ impl std::ops::Deref for LazyObject {
 impl std::ops::DerefMut for LazyObject {
     fn deref_mut(&mut self) -> &mut Self::Target {
         if let LazyPacked::Parsed(Parsed::LazyObject(obj)) = &mut self.0 .0 {
-            obj
+            Arc::make_mut(obj)
         } else {
             unreachable!("must be a lazy object");
         }
@@ -672,19 +751,19 @@ impl Default for LazyObject {
 impl LazyObject {
     pub fn new() -> Self {
         Self(OwnedLazyValue(LazyPacked::Parsed(Parsed::LazyObject(
-            Vec::new(),
+            Arc::new(Vec::new()),
         ))))
     }
 
     pub fn with_capacity(cap: usize) -> Self {
         Self(OwnedLazyValue(LazyPacked::Parsed(Parsed::LazyObject(
-            Vec::with_capacity(cap),
+            Arc::new(Vec::with_capacity(cap)),
         ))))
     }
 
     pub fn append_pair(&mut self, key: FastStr, value: OwnedLazyValue) {
         if let LazyPacked::Parsed(Parsed::LazyObject(obj)) = &mut self.0 .0 {
-            obj.push((key, value));
+            Arc::make_mut(obj).push((key, value));
         } else {
             unreachable!("must be a lazy object");
         }
@@ -693,7 +772,9 @@ impl LazyObject {
 
 impl From<Vec<(FastStr, OwnedLazyValue)>> for LazyObject {
     fn from(v: Vec<(FastStr, OwnedLazyValue)>) -> Self {
-        Self(OwnedLazyValue(LazyPacked::Parsed(Parsed::LazyObject(v))))
+        Self(OwnedLazyValue(LazyPacked::Parsed(Parsed::LazyObject(
+            Arc::new(v),
+        ))))
     }
 }
 
@@ -709,7 +790,9 @@ pub struct LazyArray(OwnedLazyValue);
 
 impl From<Vec<OwnedLazyValue>> for LazyArray {
     fn from(v: Vec<OwnedLazyValue>) -> Self {
-        Self(OwnedLazyValue(LazyPacked::Parsed(Parsed::LazyArray(v))))
+        Self(OwnedLazyValue(LazyPacked::Parsed(Parsed::LazyArray(
+            Arc::new(v),
+        ))))
     }
 }
 
@@ -722,7 +805,7 @@ impl From<LazyArray> for OwnedLazyValue {
 impl std::ops::DerefMut for LazyArray {
     fn deref_mut(&mut self) -> &mut Self::Target {
         if let LazyPacked::Parsed(Parsed::LazyArray(obj)) = &mut self.0 .0 {
-            obj
+            Arc::make_mut(obj)
         } else {
             unreachable!("must be a lazy array");
         }
@@ -749,19 +832,20 @@ impl Default for LazyArray {
 impl LazyArray {
     pub fn new() -> Self {
         Self(OwnedLazyValue(LazyPacked::Parsed(Parsed::LazyArray(
-            Vec::new(),
+            Arc::new(Vec::new()),
         ))))
     }
 
     pub fn with_capacity(cap: usize) -> Self {
         Self(OwnedLazyValue(LazyPacked::Parsed(Parsed::LazyArray(
-            Vec::with_capacity(cap),
+            Arc::new(Vec::with_capacity(cap)),
         ))))
     }
 }
 
 #[cfg(test)]
 mod test {
+    use super::LazyPacked;
     use crate::{get, pointer, prelude::*, to_lazyvalue, to_string, FastStr, OwnedLazyValue};
     #[test]
     fn test_owned_lazy_value() {
@@ -798,6 +882,46 @@ mod test {
         assert_eq!(own.get("a\\").as_str().unwrap(), "\\hello \" world");
         assert_eq!(own_c.as_str(), None);
         assert!(own_c.is_array());
+
+        // the raw JSON is kept until parsed by the mutations
+        assert_eq!(own_a.as_raw_str(), Some(r#""hello world""#));
+        assert_eq!(own_c.as_raw_str(), Some("[0, 1, 2]"));
+        let mut own_d = OwnedLazyValue::from(get(input, &["d"]).unwrap());
+        assert!(own_d.as_raw_str().unwrap().starts_with('{'));
+        own_d
+            .as_object_mut()
+            .unwrap()
+            .append_pair(FastStr::new("x"), true.into());
+        assert_eq!(own_d.as_raw_str(), None);
+    }
+
+    #[test]
+    fn test_owned_get_many() {
+        let json = r#"{"a": [{"b": 1}, "\u0041"], "c": {"d": [true]}, "a": 2}"#;
+        let mut lv: OwnedLazyValue = crate::from_str(json).unwrap();
+        lv.as_object_mut()
+            .unwrap()
+            .append_pair(FastStr::new("e"), to_lazyvalue("[3]").unwrap());
+
+        let mut tree = crate::PointerTree::new();
+        tree.add_path(&pointer!["a", 0, "b"]);
+        tree.add_path(&pointer!["a", 1]);
+        tree.add_path(&pointer!["c", "d", 0]);
+        tree.add_path(&pointer!["c", "d", 1]);
+        tree.add_path(&pointer!["e"]);
+        tree.add_path(&pointer!["a", 1]);
+        tree.add_path(&pointer![]);
+        let nodes = lv.get_many(&tree);
+        assert_eq!(nodes.len(), 7);
+        assert_eq!(nodes[0].unwrap().as_u64(), Some(1));
+        assert_eq!(nodes[1].unwrap().as_str(), Some("A"));
+        assert_eq!(nodes[2].unwrap().as_bool(), Some(true));
+        assert!(nodes[3].is_none());
+        assert_eq!(to_string(nodes[4].unwrap()).unwrap(), r#""[3]""#);
+        assert_eq!(nodes[5].unwrap().as_str(), Some("A"));
+        assert!(nodes[6].unwrap().is_object());
+
+        assert!(lv.get_many(&crate::PointerTree::new()).is_empty());
     }
 
     #[test]
@@ -891,6 +1015,103 @@ mod test {
         assert_eq!(to_string(&root).unwrap(), to_string(&root3).unwrap());
     }
 
+    #[test]
+    fn test_owned_value_shared_clone() {
+        use std::sync::Arc;
+
+        use super::{LazyPacked, Parsed};
+
+        fn shared(a: &OwnedLazyValue, b: &OwnedLazyValue) -> bool {
+            match (&a.0, &b.0) {
+                (
+                    LazyPacked::Parsed(Parsed::LazyObject(a)),
+                    LazyPacked::Parsed(Parsed::LazyObject(b)),
+                ) => Arc::ptr_eq(a, b),
+                (
+                    LazyPacked::Parsed(Parsed::LazyArray(a)),
+                    LazyPacked::Parsed(Parsed::LazyArray(b)),
+                ) => Arc::ptr_eq(a, b),
+                _ => false,
+            }
+        }
+
+        let input = FastStr::new(r#"{"a": [1, 2, 3], "b": {"c": "d"}}"#);
+        let mut root: OwnedLazyValue = crate::get_from_faststr(&input, pointer![]).unwrap().into();
+        root.as_object_mut().unwrap();
+        root.get_mut("a").unwrap().as_array_mut().unwrap();
+
+        // the clones share the parsed bodies
+        let mut cloned = root.clone();
+        assert!(shared(&root, &cloned));
+        assert!(shared(root.get("a").unwrap(), cloned.get("a").unwrap()));
+
+        // the mutation copies the bodies on write, and does not affect the other clones
+        cloned
+            .get_mut("a")
+            .unwrap()
+            .as_array_mut()
+            .unwrap()
+            .push(to_lazyvalue(&4).unwrap());
+        assert!(!shared(&root, &cloned));
+        assert!(!shared(root.get("a").unwrap(), cloned.get("a").unwrap()));
+        assert_eq!(to_string(&root).unwrap(), r#"{"a":[1,2,3],"b":{"c": "d"}}"#);
+        assert_eq!(
+            to_string(&cloned).unwrap(),
+            r#"{"a":[1,2,3,4],"b":{"c": "d"}}"#
+        );
+
+        // the lazily loaded value is also shared by the clones
+        let lazy: OwnedLazyValue = crate::get_from_faststr(&input, pointer!["b"])
+            .unwrap()
+            .into();
+        assert_eq!(lazy.get("c").as_str(), Some("d"));
+        let (c1, c2) = (lazy.clone(), lazy.clone());
+        assert!(shared(&c1, &c2));
+    }
+
+    #[test]
+    fn test_lazyvalue_into_owned() {
+        fn raw_ptr(lv: &OwnedLazyValue) -> *const u8 {
+            match &lv.0 {
+                LazyPacked::Raw(raw) => raw.raw.as_ptr(),
+                LazyPacked::NonEscStrRaw(raw) => raw.as_ptr(),
+                _ => unreachable!(),
+            }
+        }
+
+        let json =
+            r#"{"a": {"b": [1, 2, 3], "c": "a long string which is not inlined"}, "d": "\u0064"}"#;
+        let input = FastStr::new(json);
+        let range = input.as_bytes().as_ptr_range();
+        for path in [&pointer!["a"][..], &pointer!["a", "c"], &pointer!["d"]] {
+            let lv = crate::get_from_faststr(&input, path).unwrap();
+            let expect = lv.as_raw_str().to_string();
+            let owned = lv.into_owned();
+            // the short raw texts are inlined by `FastStr`
+            assert_eq!(
+                range.contains(&raw_ptr(&owned)),
+                expect.len() > 30,
+                "{path:?}"
+            );
+            assert_eq!(to_string(&owned).unwrap(), expect);
+        }
+
+        let bytes = bytes::Bytes::from(json);
+        let range = bytes.as_ptr_range();
+        let owned = crate::get_from_bytes(&bytes, pointer!["a"])
+            .unwrap()
+            .into_owned();
+        assert!(range.contains(&raw_ptr(&owned)));
+        drop(bytes);
+        assert_eq!(owned.get("b").unwrap().get(2).as_u64(), Some(3));
+        assert!(owned.get("d").is_none());
+
+        // the borrowed input is copied
+        let owned = get(json, pointer!["a", "c"]).unwrap().into_owned();
+        assert!(!json.as_bytes().as_ptr_range().contains(&raw_ptr(&owned)));
+        assert_eq!(owned.as_str(), Some("a long string which is not inlined"));
+    }
+
     #[test]
     fn test_owned_from_invalid() {
         for json in [
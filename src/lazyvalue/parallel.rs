@@ -0,0 +1,114 @@
+//! Iterate the elements of a JSON array in parallel with rayon.
+
+use rayon::iter::{
+    plumbing::{Consumer, ProducerCallback, UnindexedConsumer},
+    IndexedParallelIterator, IntoParallelIterator, ParallelIterator,
+};
+
+use super::{iterator::ArrayJsonIter, LazyValue};
+use crate::{error::Result, input::JsonInput};
+
+/// A parallel iterator over the elements of a JSON array text, created by [`par_array_iter`].
+/// Requires the `rayon` feature.
+///
+/// The item of the iterator is [`Result<LazyValue>`][`crate::LazyValue`].
+pub struct ParArrayIter<'de> {
+    elems: rayon::vec::IntoIter<Result<LazyValue<'de>>>,
+}
+
+impl<'de> ParallelIterator for ParArrayIter<'de> {
+    type Item = Result<LazyValue<'de>>;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.elems.drive_unindexed(consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.elems.len())
+    }
+}
+
+impl IndexedParallelIterator for ParArrayIter<'_> {
+    fn len(&self) -> usize {
+        self.elems.len()
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        self.elems.drive(consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        self.elems.with_producer(callback)
+    }
+}
+
+/// Traverse the JSON array text in parallel with rayon. Requires the `rayon` feature.
+///
+/// The boundaries of the elements are found by skipping them on the current thread, which is much
+/// cheaper than parsing them. Then the elements are split into contiguous chunks for the rayon
+/// threads, and each element is yielded as a [`LazyValue`] borrowing the JSON text. It is useful
+/// when processing each element of a large array is CPU-heavy.
+///
+/// The iterator is indexed, so the elements keep their order in the array, such as in
+/// `enumerate` and `collect`.
+///
+/// # Errors
+///
+/// If the JSON is empty, not an array or invalid, the elements before the error are yielded, and
+/// then the error is yielded as the last item, the same as [`to_array_iter`][crate::to_array_iter].
+///
+/// # Examples
+///
+/// ```
+/// use rayon::prelude::*;
+/// use sonic_rs::{par_array_iter, JsonValueTrait};
+///
+/// let json = r#"[{"n": 1}, {"n": 2}, {"n": 3}]"#;
+/// let sum: u64 = par_array_iter(json)
+///     .map(|elem| elem.unwrap().get("n").as_u64().unwrap())
+///     .sum();
+/// assert_eq!(sum, 6);
+///
+/// let ret: sonic_rs::Result<Vec<_>> = par_array_iter("[1, 2, x]").collect();
+/// assert!(ret.is_err());
+/// ```
+pub fn par_array_iter<'de, I: JsonInput<'de>>(json: I) -> ParArrayIter<'de> {
+    let elems: Vec<_> = ArrayJsonIter::new(json, true).collect();
+    ParArrayIter {
+        elems: elems.into_par_iter(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::JsonValueTrait;
+
+    #[test]
+    fn test_par_array_iter() {
+        let json: String = format!(
+            "[{}]",
+            (0..1000)
+                .map(|i| format!(r#"{{"id": {i}, "s": "\u00{:02x}"}}"#, i % 128 + 32))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let ids: Vec<u64> = par_array_iter(&json)
+            .map(|elem| elem.unwrap().get("id").as_u64().unwrap())
+            .collect();
+        assert_eq!(ids, (0..1000).collect::<Vec<_>>());
+        assert_eq!(par_array_iter(&json).len(), 1000);
+
+        assert_eq!(par_array_iter("[]").count(), 0);
+        assert_eq!(par_array_iter(" [ 1 ] ").count(), 1);
+
+        for json in ["", "{}", "[1, 2", "[1, 2,]", "[1, {]"] {
+            let elems: Vec<_> = par_array_iter(json).collect();
+            assert!(elems.last().unwrap().is_err(), "{json}");
+            assert!(elems[..elems.len() - 1].iter().all(|e| e.is_ok()), "{json}");
+        }
+    }
+}
@@ -19,7 +19,7 @@ impl<'a> serde::ser::Serialize for LazyValue<'a> {
 mod test {
     use serde::{Deserialize, Serialize};
 
-    use crate::{from_str, to_string, LazyValue, OwnedLazyValue, Result};
+    use crate::{from_str, to_string, JsonValueTrait, LazyValue, OwnedLazyValue, Result};
 
     #[test]
     fn test_lazyvalue_serde() {
@@ -104,4 +104,52 @@ mod test {
         test_json_failed(r#" ]"#);
         test_json_failed(r#"{"":[], ["", "", []]}"#);
     }
+
+    #[test]
+    fn test_lazyvalue_incompatible_deserializer() {
+        // the deserializer of `Value` does not know the tokens
+        let value = crate::json!({"a": 1});
+        let err = crate::from_value::<LazyValue>(&value).unwrap_err();
+        assert!(err.to_string().contains("json_string"), "{err}");
+        let err = crate::from_value::<OwnedLazyValue>(&value).unwrap_err();
+        assert!(err.to_string().contains("compatible version"), "{err}");
+
+        // the versioned token of `OwnedLazyValue`
+        assert!(crate::lazyvalue::OWNED_LAZY_VALUE_TOKEN.ends_with(env!("CARGO_PKG_VERSION")));
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Message<'a> {
+        id: u64,
+        payload: LazyValue<'a>,
+        items: Vec<LazyValue<'a>>,
+    }
+
+    #[test]
+    fn test_lazyvalue_from_reader() {
+        let json = r#"{"id": 1, "payload": {"a": "\n", "b": [1, 2]}, "items": [true, "x\"y", {}]}"#;
+        let check = |msg: &Message<'_>| {
+            assert_eq!(msg.id, 1);
+            assert_eq!(msg.payload.as_raw_str(), r#"{"a": "\n", "b": [1, 2]}"#);
+            let items: Vec<_> = msg.items.iter().map(|lv| lv.as_raw_str()).collect();
+            assert_eq!(items, ["true", r#""x\"y""#, "{}"]);
+            assert_eq!(msg.items[1].as_str(), Some("x\"y"));
+        };
+
+        let msg: Message = from_str(json).unwrap();
+        check(&msg);
+
+        // the spans are copied from the buffer of the reader
+        let msg: Message<'static> = crate::from_reader(json.as_bytes()).unwrap();
+        check(&msg);
+        let msg: Message<'static> = {
+            let owned = json.to_string();
+            from_str(&owned).unwrap()
+        };
+        check(&msg);
+
+        assert!(
+            crate::from_reader::<_, Message<'static>>(&b"{\"id\": 1, \"payload\": [}"[..]).is_err()
+        );
+    }
 }
@@ -0,0 +1,411 @@
+//! Get the fields from a JSON body which is received as a stream of frames, such as a HTTP body.
+
+use std::{future::poll_fn, io, pin::Pin};
+
+use bytes::Bytes;
+use futures_core::Stream;
+
+use super::{get::path_value, OwnedLazyValue};
+use crate::{
+    error::{Error, ErrorCode, Result},
+    index::Index,
+    parser::{Parser, PathValue},
+    reader::{Read, Reader},
+    util::{scratch::ScratchBuf, utf8::from_utf8},
+};
+
+/// Get the fields from a JSON body received as a [`Stream`] of [`Bytes`] frames, such as a HTTP
+/// body. Requires the `stream` feature.
+///
+/// The frames are buffered, and [`get`][StreamGetter::get] resolves as soon as the value of the
+/// path has been fully received, without waiting for the whole body. It is useful to extract the
+/// routing fields at the beginning of a large body early.
+///
+/// When a new frame arrives and the value is not complete yet, the path is resumed from the last
+/// member or element scanned in the buffered JSON, so each byte is scanned once per `get`. The
+/// negative indexes and the slices of the path are resumed from the start of their arrays.
+///
+/// # Examples
+///
+/// ```
+/// use bytes::Bytes;
+/// use futures_core::Stream;
+/// use sonic_rs::{JsonValueTrait, StreamGetter};
+///
+/// async fn route<S>(body: S) -> sonic_rs::Result<(String, Bytes)>
+/// where
+///     S: Stream<Item = Result<Bytes, std::io::Error>> + Unpin,
+/// {
+///     let mut getter = StreamGetter::new(body);
+///     // resolved once the field is received
+///     let route = getter.get(&["route"]).await?;
+///     let route = route.as_str().unwrap_or_default().to_string();
+///
+///     // read the whole body after routing
+///     let body = getter.into_bytes().await?;
+///     Ok((route, body))
+/// }
+/// ```
+pub struct StreamGetter<S> {
+    stream: S,
+    buf: Vec<u8>,
+    eof: bool,
+}
+
+impl<S, E> StreamGetter<S>
+where
+    S: Stream<Item = std::result::Result<Bytes, E>> + Unpin,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    /// Create a getter over the stream, nothing is read until the first `get`.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            buf: Vec::new(),
+            eof: false,
+        }
+    }
+
+    /// The bytes received from the stream so far.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Get the value of the `path` as [`get`][crate::get], once it has been fully received.
+    ///
+    /// The frames already buffered are reused, so that several paths can be got in turn. If the
+    /// stream ends before the value is complete, the error is the same as getting from the
+    /// truncated JSON. The errors of the stream are returned as the IO errors.
+    pub async fn get<Path>(&mut self, path: Path) -> Result<OwnedLazyValue>
+    where
+        Path: IntoIterator + Clone,
+        Path::Item: Index,
+    {
+        let path: Vec<Path::Item> = path.into_iter().collect();
+        let mut resume = Resume::default();
+        loop {
+            if let Some(ret) = self.try_get(&path, &mut resume) {
+                return ret;
+            }
+            // the error of the truncated JSON is returned by `try_get` at the end of the stream
+            self.fill().await?;
+        }
+    }
+
+    /// Read the remaining frames, and return the whole body.
+    pub async fn into_bytes(mut self) -> Result<Bytes> {
+        while self.fill().await? {}
+        Ok(Bytes::from(self.buf))
+    }
+
+    /// Split into the buffered bytes and the stream, which has the remaining frames.
+    pub fn into_parts(self) -> (Vec<u8>, S) {
+        (self.buf, self.stream)
+    }
+
+    // Return `None` if the value may be incomplete, and `resume` is kept to get in the next frames.
+    fn try_get<I: Index>(&self, path: &[I], resume: &mut Resume) -> Option<Result<OwnedLazyValue>> {
+        let mut parser = Parser::new(Read::new(&self.buf, false));
+        parser.read.set_index(resume.pos);
+        let got = walk(&mut parser, path, resume).and_then(|got| {
+            from_utf8(&self.buf[..parser.read.index()])?;
+            Ok(path_value(got, |sub| sub.into()))
+        });
+        match got {
+            Ok(lv) => {
+                // the number at the end may be continued in the next frames
+                let raw = lv.as_raw_str().as_bytes();
+                let at_end = raw.as_ptr_range().end == self.buf.as_ptr_range().end;
+                if at_end && !self.eof && matches!(raw[0], b'-' | b'0'..=b'9') {
+                    None
+                } else {
+                    Some(Ok(lv.into()))
+                }
+            }
+            Err(e) if e.is_eof() && !self.eof => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    // Read the next frame, and return false if the stream is ended.
+    async fn fill(&mut self) -> Result<bool> {
+        if self.eof {
+            return Ok(false);
+        }
+        match poll_fn(|cx| Pin::new(&mut self.stream).poll_next(cx)).await {
+            Some(Ok(frame)) => {
+                self.buf.extend_from_slice(&frame);
+                Ok(true)
+            }
+            Some(Err(e)) => Err(Error::io(io::Error::new(io::ErrorKind::Other, e))),
+            None => {
+                self.eof = true;
+                Ok(false)
+            }
+        }
+    }
+}
+
+// The position to resume getting a path from. It is moved forward after each member or element is
+// skipped, so that the buffered JSON is not scanned again after an EOF error.
+#[derive(Debug, Default)]
+struct Resume {
+    // the number of the resolved segments of the path
+    depth: usize,
+    // the index in the buffer
+    pos: usize,
+    // whether `pos` is in the container of the next segment, after the `{`, `[` or `,`
+    inner: bool,
+    // the elements to skip before the index of the next segment
+    skip: usize,
+}
+
+// Get the `path` from the `resume` position as `Parser::get_from_with_iter`.
+fn walk<'de, I: Index>(
+    parser: &mut Parser<Read<'de>>,
+    path: &[I],
+    resume: &mut Resume,
+) -> Result<PathValue<'de>> {
+    let mut temp_buf = ScratchBuf::new();
+    while let Some(seg) = path.get(resume.depth) {
+        if let Some(key) = seg.as_key() {
+            if !resume.inner {
+                match parser.skip_space() {
+                    Some(b'{') => {}
+                    Some(peek) => return Err(parser.peek_invalid_type(peek, &"a JSON object")),
+                    None => return Err(parser.error(ErrorCode::EofWhileParsing)),
+                }
+                if parser.skip_space_peek() == Some(b'}') {
+                    return Err(parser.error(ErrorCode::GetInEmptyObject));
+                }
+                resume.inner = true;
+                resume.pos = parser.read.index();
+            }
+            loop {
+                match parser.skip_space() {
+                    Some(b'"') => {}
+                    None => return Err(parser.error(ErrorCode::EofWhileParsing)),
+                    _ => return Err(parser.error(ErrorCode::ExpectObjectKeyOrEnd)),
+                }
+                let found = *parser.parse_string_raw(&mut temp_buf)? == *key.as_bytes();
+                parser.parse_object_clo()?;
+                if found {
+                    break;
+                }
+                parser.skip_one()?;
+                match parser.skip_space() {
+                    Some(b',') => resume.pos = parser.read.index(),
+                    Some(b'}') => return Err(parser.error(ErrorCode::GetUnknownKeyInObject)),
+                    None => return Err(parser.error(ErrorCode::EofWhileParsing)),
+                    _ => return Err(parser.error(ErrorCode::ExpectedObjectCommaOrEnd)),
+                }
+            }
+        } else if let Some(index) = seg.as_index() {
+            if !resume.inner {
+                match parser.skip_space() {
+                    Some(b'[') => {}
+                    Some(peek) => return Err(parser.peek_invalid_type(peek, &"a JSON array")),
+                    None => return Err(parser.error(ErrorCode::EofWhileParsing)),
+                }
+                match parser.skip_space_peek() {
+                    Some(b']') => return Err(parser.error(ErrorCode::GetInEmptyArray)),
+                    None => return Err(parser.error(ErrorCode::EofWhileParsing)),
+                    _ if index == usize::MAX => {
+                        return Err(parser.error(ErrorCode::GetIndexOverflow))
+                    }
+                    _ => {}
+                }
+                resume.inner = true;
+                resume.pos = parser.read.index();
+                resume.skip = index;
+            }
+            while resume.skip > 0 {
+                parser.skip_one()?;
+                match parser.skip_space() {
+                    Some(b',') => {
+                        resume.pos = parser.read.index();
+                        resume.skip -= 1;
+                    }
+                    Some(b']') => return Err(parser.error(ErrorCode::GetIndexOutOfArray)),
+                    None => return Err(parser.error(ErrorCode::EofWhileParsing)),
+                    _ => return Err(parser.error(ErrorCode::ExpectedArrayCommaOrEnd)),
+                }
+            }
+        } else {
+            // the other segments need the whole array
+            return parser.get_from_with_iter(&path[resume.depth..]);
+        }
+        resume.depth += 1;
+        resume.inner = false;
+        resume.pos = parser.read.index();
+    }
+    let (sub, status) = parser.skip_one()?;
+    Ok(PathValue::Raw(sub, status))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::VecDeque,
+        future::Future,
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    use super::*;
+    use crate::{pointer, JsonValueTrait, PointerNode};
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    // the frames are ready one by one, and pending between them
+    struct Frames {
+        frames: VecDeque<std::result::Result<Bytes, io::Error>>,
+        pending: bool,
+    }
+
+    impl Frames {
+        fn new(frames: &[&'static str]) -> Self {
+            Self {
+                frames: frames.iter().map(|f| Ok(Bytes::from(*f))).collect(),
+                pending: false,
+            }
+        }
+    }
+
+    impl Stream for Frames {
+        type Item = std::result::Result<Bytes, io::Error>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.pending = !self.pending;
+            if self.pending {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            Poll::Ready(self.frames.pop_front())
+        }
+    }
+
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = Box::pin(fut);
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+    }
+
+    #[test]
+    fn test_stream_get() {
+        let frames = [
+            r#"{"a": {"b": 12"#,
+            r#"3, "c": "x"#,
+            r#"yz"}, "d": [1, "#,
+            "2]}",
+        ];
+
+        // resolved before the body is fully received
+        let mut getter = StreamGetter::new(Frames::new(&frames));
+        let b = block_on(getter.get(&pointer!["a", "b"]));
+        assert_eq!(b.unwrap().as_u64(), Some(123));
+        assert_eq!(
+            getter.buffered(),
+            (frames[0].to_string() + frames[1]).as_bytes()
+        );
+        let c = block_on(getter.get(&["a", "c"]));
+        assert_eq!(c.unwrap().as_str(), Some("xyz"));
+        assert_eq!(getter.buffered().len(), frames[..3].concat().len());
+        let d = block_on(getter.get(&pointer!["d", 1]));
+        assert_eq!(d.unwrap().as_u64(), Some(2));
+        let body = block_on(getter.into_bytes());
+        assert_eq!(body.unwrap(), frames.concat());
+
+        // the number at the end of the body
+        let mut getter = StreamGetter::new(Frames::new(&["1", "2"]));
+        let v = block_on(getter.get(&[] as &[&str]));
+        assert_eq!(v.unwrap().as_u64(), Some(12));
+
+        // not found or truncated
+        let mut getter = StreamGetter::new(Frames::new(&[r#"{"a": 1}"#, "garbage"]));
+        let v = block_on(getter.get(&["b"]));
+        assert!(v.unwrap_err().is_not_found());
+        assert_eq!(getter.buffered(), br#"{"a": 1}"#);
+        let mut getter = StreamGetter::new(Frames::new(&[r#"{"a": [1, "#]));
+        let v = block_on(getter.get(&["a"]));
+        assert!(v.unwrap_err().is_eof());
+
+        // the errors of the stream
+        let mut stream = Frames::new(&[r#"{"a": "#]);
+        stream
+            .frames
+            .push_back(Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset")));
+        let mut getter = StreamGetter::new(stream);
+        let v = block_on(getter.get(&["a"]));
+        let err = v.unwrap_err();
+        assert!(err.is_io());
+        assert!(err.to_string().contains("reset"), "{err}");
+
+        let mut getter = StreamGetter::new(Frames::new(&[]));
+        let v = block_on(getter.get(&["a"]));
+        assert!(v.unwrap_err().is_eof());
+        let (_, stream) = getter.into_parts();
+        assert!(stream.frames.is_empty());
+    }
+
+    #[test]
+    fn test_stream_get_resume() {
+        let json = r#"{"a": [1, {"b": "x\"y"}, [2, 3]], "c": {"d": -1.5e3, "e": []}, "f": "😀"}"#;
+        let paths: [&[PointerNode]; 8] = [
+            &pointer!["a", 1, "b"],
+            &pointer!["a", 2, 1],
+            &pointer!["c", "d"],
+            &pointer!["c", "e"],
+            &pointer!["f"],
+            &pointer!["a", 3],
+            &pointer!["c", "g"],
+            &pointer!["a", "b"],
+        ];
+        // split the body at each byte
+        for at in 0..=json.len() {
+            for path in paths {
+                let expect = crate::get(json, path);
+                let frames = [&json.as_bytes()[..at], &json.as_bytes()[at..]]
+                    .iter()
+                    .map(|f| Ok(Bytes::copy_from_slice(f)))
+                    .collect();
+                let mut getter = StreamGetter::new(Frames {
+                    frames,
+                    pending: false,
+                });
+                let got = block_on(getter.get(path));
+                match (got, expect) {
+                    (Ok(got), Ok(expect)) => {
+                        assert_eq!(got.as_raw_str(), Some(expect.as_raw_str()))
+                    }
+                    (Err(got), Err(expect)) => assert_eq!(got.to_string(), expect.to_string()),
+                    (got, expect) => panic!("split at {at}: {got:?} {expect:?}"),
+                }
+            }
+        }
+
+        // the scanned members are not scanned again
+        let getter = StreamGetter::<Frames> {
+            stream: Frames::new(&[]),
+            buf: br#"{"a": [1, {"b": 2}, 3"#.to_vec(),
+            eof: false,
+        };
+        let mut resume = Resume::default();
+        assert!(getter.try_get(&pointer!["a", 2][..], &mut resume).is_none());
+        assert_eq!((resume.depth, resume.skip), (2, 0));
+        assert_eq!(resume.pos, getter.buf.len() - 2);
+        let mut resume = Resume::default();
+        assert!(getter.try_get(&pointer!["a", 5][..], &mut resume).is_none());
+        assert_eq!((resume.depth, resume.skip), (1, 3));
+        assert_eq!(resume.pos, getter.buf.len() - 2);
+    }
+}
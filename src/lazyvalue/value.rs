@@ -16,8 +16,9 @@ use crate::{
     index::Index,
     input::JsonSlice,
     lazyvalue::iterator::{ArrayJsonIter, ObjectJsonIter},
+    parser::is_whitespace,
     serde::Number,
-    JsonType, JsonValueTrait, RawNumber,
+    JsonType, JsonValueTrait, OwnedLazyValue, RawNumber,
 };
 
 /// LazyValue wrappers a unparsed raw JSON text. It is borrowed from the origin JSON text.
@@ -54,8 +55,11 @@ use crate::{
 ///
 /// # Serde Examples
 ///
-/// `LazyValue<'a>` can only be deserialized with borrowed.
-/// If need to be owned, use [`OwnedLazyValue`](crate::OwnedLazyValue).
+/// The raw JSON text is copied when deserializing `LazyValue<'a>`, so it does not borrow from the
+/// input. The same struct works with [`from_str`](crate::from_str) and
+/// [`from_reader`](crate::from_reader), the latter needs `LazyValue<'static>` and the struct
+/// without `#[serde(borrow)]`. If need to be owned and shared, use
+/// [`OwnedLazyValue`](crate::OwnedLazyValue).
 ///
 /// ```
 /// # use sonic_rs::LazyValue;
@@ -71,6 +75,19 @@ use crate::{
 ///
 /// let data: TestLazyValue = sonic_rs::from_str(input).unwrap();
 /// assert_eq!(data.borrowed_lv.as_raw_str(), "\"hello\"");
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Message<'a> {
+///     id: u64,
+///     payload: LazyValue<'a>,
+/// }
+///
+/// let input = r#"{"id": 1, "payload": {"a": [1, 2]}}"#;
+/// let msg: Message = sonic_rs::from_str(input).unwrap();
+/// assert_eq!(msg.payload.as_raw_str(), r#"{"a": [1, 2]}"#);
+///
+/// let msg: Message<'static> = sonic_rs::from_reader(input.as_bytes()).unwrap();
+/// assert_eq!(msg.payload.as_raw_str(), r#"{"a": [1, 2]}"#);
 /// ```
 ///
 /// # Convert to serde_json::Value
@@ -313,6 +330,10 @@ impl<'a> JsonValueTrait for LazyValue<'a> {
             self.get_key(key)
         } else if let Some(index) = index.as_index() {
             self.get_index(index)
+        } else if index.as_index_from_end().is_some() || index.as_slice().is_some() {
+            self.pointer([index])
+        } else if index.is_wildcard() {
+            None
         } else {
             unreachable!("index must be key or index")
         }
@@ -335,6 +356,12 @@ impl<'a> JsonValueTrait for LazyValue<'a> {
 impl<'a> LazyValue<'a> {
     /// Export the raw JSON text as `str`.
     ///
+    /// The raw JSON text of a `LazyValue` from the APIs of sonic-rs, such as
+    /// [`get`][crate::get], the iterators and deserializing, always starts at the first byte of the
+    /// value and ends at its last byte, without the surrounding whitespaces. The whitespaces and
+    /// escapes inside the value are kept as they are in the input, so the same value may have
+    /// different raw JSON texts. The comparison and hashing of `LazyValue` use the raw JSON text.
+    ///
     /// # Examples
     ///
     /// ```
@@ -342,6 +369,10 @@ impl<'a> LazyValue<'a> {
     ///
     /// let lv: LazyValue = sonic_rs::get(r#"{"a": "hello world"}"#, &["a"]).unwrap();
     /// assert_eq!(lv.as_raw_str(), "\"hello world\"");
+    ///
+    /// // the surrounding whitespaces are not included
+    /// let lv: LazyValue = sonic_rs::get(" [1, 2] \n", &[] as &[&str]).unwrap();
+    /// assert_eq!(lv.as_raw_str(), "[1, 2]");
     /// ```
     pub fn as_raw_str(&self) -> &str {
         // # Safety
@@ -350,6 +381,37 @@ impl<'a> LazyValue<'a> {
         unsafe { from_utf8_unchecked(self.raw.as_ref()) }
     }
 
+    /// Export the raw JSON text as `str` without the leading and trailing JSON whitespaces.
+    ///
+    /// It is the same as [`as_raw_str`][LazyValue::as_raw_str] for the `LazyValue`s from the
+    /// APIs of sonic-rs, whose raw JSON texts never have surrounding whitespaces, and makes the
+    /// guarantee explicit where the text is compared or hashed as bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::{to_array_iter, JsonValueTrait};
+    ///
+    /// for lv in to_array_iter("[ 1 ,\n{ }\t]") {
+    ///     let lv = lv.unwrap();
+    ///     assert_eq!(lv.as_raw_str_trimmed(), lv.as_raw_str());
+    ///     assert!(lv.is_number() || lv.as_raw_str_trimmed() == "{ }");
+    /// }
+    /// ```
+    pub fn as_raw_str_trimmed(&self) -> &str {
+        let raw = self.raw.as_ref();
+        let start = raw
+            .iter()
+            .position(|&c| !is_whitespace(c))
+            .unwrap_or(raw.len());
+        let end = raw
+            .iter()
+            .rposition(|&c| !is_whitespace(c))
+            .map_or(start, |i| i + 1);
+        // SAFETY: the JSON whitespaces are ASCII, so the boundaries are char boundaries
+        unsafe { from_utf8_unchecked(&raw[start..end]) }
+    }
+
     /// Export the raw JSON text as `Cow<'de, str>`.  The lifetime `'de` is the origin JSON.
     ///
     /// # Examples
@@ -393,6 +455,28 @@ impl<'a> LazyValue<'a> {
         }
     }
 
+    /// Convert into an [`OwnedLazyValue`], without validating the JSON text again.
+    ///
+    /// If the lazyvalue is from a `FastStr` or `Bytes` input, such as
+    /// [`get_from_faststr`][`crate::get_from_faststr`], the owned value shares the refcounted
+    /// buffer without copying. Otherwise, the raw JSON text is copied. The raw JSON texts not
+    /// longer than 30 bytes are always inlined, as [`FastStr`] does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use faststr::FastStr;
+    /// use sonic_rs::{get_from_faststr, JsonValueTrait, OwnedLazyValue};
+    ///
+    /// let fs = FastStr::new(r#"{"a": {"b": [1, 2, 3], "c": "a long string in the object"}}"#);
+    /// let owned: OwnedLazyValue = get_from_faststr(&fs, &["a"]).unwrap().into_owned(); // zero-copy
+    /// drop(fs);
+    /// assert_eq!(owned.get("c").as_str(), Some("a long string in the object"));
+    /// ```
+    pub fn into_owned(self) -> OwnedLazyValue {
+        self.into()
+    }
+
     pub fn into_object_iter(mut self) -> Option<ObjectJsonIter<'a>> {
         if self.is_object() {
             Some(ObjectJsonIter::new_inner(std::mem::take(&mut self.raw)))
@@ -432,6 +516,12 @@ impl<'a> LazyValue<'a> {
     }
 
     pub(crate) fn new(raw: JsonSlice<'a>, status: HasEsc) -> Self {
+        // the raw spans start at the first byte of the value and end at its last byte
+        debug_assert!(
+            !raw.as_ref().first().is_some_and(|&c| is_whitespace(c))
+                && !raw.as_ref().last().is_some_and(|&c| is_whitespace(c)),
+            "the raw JSON text has surrounding whitespaces"
+        );
         Self {
             raw,
             inner: Inner {
@@ -537,4 +627,80 @@ mod test {
 
         assert_eq!(get_cow("[true]").unwrap(), "true");
     }
+
+    #[test]
+    fn test_raw_span_canonical() {
+        use serde::Deserialize;
+
+        use crate::{
+            from_str, get, get_first, get_many, get_many_all, to_object_iter,
+            to_object_iter_unchecked, Flatten, PointerNode, PointerTree,
+        };
+
+        let json = " \n{ \"a\" :\t[ 1 , { \"b\" : \"x\" }\r\n] ,\"c\": -1.5e3 \n, \"d\" :null}\t ";
+        fn check(lv: &LazyValue) {
+            let raw = lv.as_raw_str();
+            assert_eq!(raw, raw.trim_matches([' ', '\t', '\n', '\r']), "{raw:?}");
+            assert_eq!(lv.as_raw_str_trimmed(), raw);
+        }
+
+        let root = get(json, pointer![].iter()).unwrap();
+        check(&root);
+        assert!(root.as_raw_str().starts_with("{ \"a\""));
+        let a = get(json, &["a"]).unwrap();
+        check(&a);
+        assert_eq!(a.as_raw_str(), "[ 1 , { \"b\" : \"x\" }\r\n]");
+        let b = unsafe { get_unchecked(json, pointer!["a", 1, "b"].iter()) }.unwrap();
+        check(&b);
+        assert_eq!(b.as_raw_str(), "\"x\"");
+        check(&get(json, pointer!["a", -1]).unwrap());
+
+        let mut tree = PointerTree::new();
+        tree.add_path(pointer!["a", 0]);
+        tree.add_path(pointer!["c"]);
+        tree.add_path(pointer![]);
+        get_many(json, &tree).unwrap().iter().for_each(check);
+        let mut tree = PointerTree::new();
+        tree.add_path(pointer![PointerNode::Wildcard]);
+        tree.add_path(pointer!["a", PointerNode::Wildcard]);
+        let all = get_many_all(json, &tree).unwrap();
+        assert_eq!(all.iter().map(Vec::len).sum::<usize>(), 5);
+        all.iter().flatten().for_each(check);
+        let (_, lv) = get_first(json, [pointer!["x"], pointer!["d"]])
+            .unwrap()
+            .unwrap();
+        check(&lv);
+        assert_eq!(lv.as_raw_str(), "null");
+
+        for ret in to_object_iter(json).chain(to_object_iter_unchecked(json)) {
+            let (_, lv) = ret.unwrap();
+            check(&lv);
+            for elem in lv.into_array_iter().into_iter().flatten() {
+                check(&elem.unwrap());
+            }
+        }
+        let elems: Vec<_> = to_array_iter(" [ 1 ,\n\"s\" , [ ] ] ").collect();
+        assert_eq!(elems.len(), 3);
+        elems.iter().for_each(|elem| check(elem.as_ref().unwrap()));
+
+        #[derive(Deserialize)]
+        struct Data<'a> {
+            #[serde(borrow)]
+            a: LazyValue<'a>,
+            c: LazyValue<'a>,
+        }
+        let data: Data = from_str(json).unwrap();
+        check(&data.a);
+        check(&data.c);
+        assert_eq!(data.c.as_raw_str(), "-1.5e3");
+        let lv: LazyValue = from_str(json).unwrap();
+        check(&lv);
+        let fields: Flatten = from_str(json).unwrap();
+        fields.iter().for_each(|(_, lv)| check(lv));
+
+        // the equal values from the different positions are equal
+        let x: LazyValue = get(" [ true ]", &[0]).unwrap();
+        let y: LazyValue = get("[true\n]", &[0]).unwrap();
+        assert_eq!(x, y);
+    }
 }
@@ -0,0 +1,202 @@
+use std::convert::Infallible;
+
+use crate::{
+    error::Result,
+    serde::tri,
+    util::string::{format_string, format_string_capacity},
+    LazyValue,
+};
+
+// check the raw text is a single valid JSON value, the empty or blank text is an EOF error
+pub(super) fn check_raw(raw: &str) -> Result<()> {
+    let _: LazyValue<'_> = tri!(crate::from_str(raw));
+    Ok(())
+}
+
+// skip the check of the unchecked APIs, which never fails
+fn no_check(_: &str) -> std::result::Result<(), Infallible> {
+    Ok(())
+}
+
+fn wrap_array_impl<I, E>(
+    raws: I,
+    check: fn(&str) -> std::result::Result<(), E>,
+) -> std::result::Result<String, E>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut buf = Vec::with_capacity(128);
+    buf.push(b'[');
+    for (i, raw) in raws.into_iter().enumerate() {
+        let raw = raw.as_ref();
+        tri!(check(raw));
+        if i > 0 {
+            buf.push(b',');
+        }
+        buf.extend_from_slice(raw.as_bytes());
+    }
+    buf.push(b']');
+    // all the parts are valid UTF-8
+    Ok(unsafe { String::from_utf8_unchecked(buf) })
+}
+
+fn wrap_object_impl<I, K, V, E>(
+    pairs: I,
+    check: fn(&str) -> std::result::Result<(), E>,
+) -> std::result::Result<String, E>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut buf = Vec::with_capacity(128);
+    buf.push(b'{');
+    for (i, (key, raw)) in pairs.into_iter().enumerate() {
+        let raw = raw.as_ref();
+        tri!(check(raw));
+        if i > 0 {
+            buf.push(b',');
+        }
+        let key = key.as_ref();
+        buf.reserve(format_string_capacity(key));
+        // the spare capacity is enough for the escaped key
+        unsafe {
+            let cnt = format_string(key, buf.spare_capacity_mut(), true);
+            buf.set_len(buf.len() + cnt);
+        }
+        buf.push(b':');
+        buf.extend_from_slice(raw.as_bytes());
+    }
+    buf.push(b'}');
+    // all the parts are valid UTF-8
+    Ok(unsafe { String::from_utf8_unchecked(buf) })
+}
+
+/// Wrap the raw JSON texts into a JSON array by concatenation, without reparsing the texts into
+/// values.
+///
+/// Each raw text is validated to be a single JSON value. Use
+/// [`wrap_in_array_unchecked`][`crate::wrap_in_array_unchecked`] to skip the validation if the
+/// texts are already validated.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::wrap_in_array;
+///
+/// let json = wrap_in_array([r#"{"a":1}"#, "[2]", r#""3""#]).unwrap();
+/// assert_eq!(json, r#"[{"a":1},[2],"3"]"#);
+///
+/// assert!(wrap_in_array(["1", "[2"]).is_err());
+/// ```
+pub fn wrap_in_array<I>(raws: I) -> Result<String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    wrap_array_impl(raws, check_raw)
+}
+
+/// Wrap the raw JSON texts into a JSON array by concatenation, without validating the texts.
+///
+/// # Safety
+///
+/// Each raw text must be a single valid JSON value, otherwise the result is invalid JSON.
+pub unsafe fn wrap_in_array_unchecked<I>(raws: I) -> String
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    match wrap_array_impl(raws, no_check) {
+        Ok(json) => json,
+        Err(never) => match never {},
+    }
+}
+
+/// Wrap the key and raw JSON text pairs into a JSON object by concatenation, without reparsing
+/// the texts into values.
+///
+/// The keys are plain strings and will be escaped. Each raw text is validated to be a single
+/// JSON value. Use [`wrap_in_object_unchecked`][`crate::wrap_in_object_unchecked`] to skip the
+/// validation if the texts are already validated.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::wrap_in_object;
+///
+/// let json = wrap_in_object([("a", "1"), ("b\n", r#"{"c":null}"#)]).unwrap();
+/// assert_eq!(json, r#"{"a":1,"b\n":{"c":null}}"#);
+///
+/// assert!(wrap_in_object([("a", "nul")]).is_err());
+/// ```
+pub fn wrap_in_object<I, K, V>(pairs: I) -> Result<String>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    wrap_object_impl(pairs, check_raw)
+}
+
+/// Wrap the key and raw JSON text pairs into a JSON object by concatenation, without validating
+/// the texts.
+///
+/// The keys are plain strings and will be escaped.
+///
+/// # Safety
+///
+/// Each raw text must be a single valid JSON value, otherwise the result is invalid JSON.
+pub unsafe fn wrap_in_object_unchecked<I, K, V>(pairs: I) -> String
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    match wrap_object_impl(pairs, no_check) {
+        Ok(json) => json,
+        Err(never) => match never {},
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{from_str, to_array_iter, Value};
+
+    #[test]
+    fn test_wrap_in_array() {
+        assert_eq!(wrap_in_array(Vec::<&str>::new()).unwrap(), "[]");
+        assert_eq!(wrap_in_array(["1"]).unwrap(), "[1]");
+
+        let raws: Vec<String> = to_array_iter(r#"[1, "a", {"b": [null]}, true]"#)
+            .map(|lv| lv.unwrap().as_raw_str().to_string())
+            .collect();
+        let json = wrap_in_array(&raws).unwrap();
+        assert_eq!(json, r#"[1,"a",{"b": [null]},true]"#);
+        assert!(from_str::<Value>(&json).is_ok());
+
+        assert!(wrap_in_array(["1", ""]).is_err());
+        assert!(wrap_in_array([" \n"]).is_err());
+        assert!(wrap_in_array(["1 2"]).is_err());
+        assert_eq!(unsafe { wrap_in_array_unchecked(["1", "2"]) }, "[1,2]");
+    }
+
+    #[test]
+    fn test_wrap_in_object() {
+        assert_eq!(wrap_in_object(Vec::<(&str, &str)>::new()).unwrap(), "{}");
+
+        let json = wrap_in_object([("a\"b", "[1,2]"), ("", "{}")]).unwrap();
+        assert_eq!(json, r#"{"a\"b":[1,2],"":{}}"#);
+        let value: Value = from_str(&json).unwrap();
+        assert_eq!(value["a\"b"][1], 2);
+
+        assert!(wrap_in_object([("a", "{")]).is_err());
+        assert!(wrap_in_object([("a", "")]).is_err());
+        assert_eq!(
+            unsafe { wrap_in_object_unchecked([("a\n", "1")]) },
+            r#"{"a\n":1}"#
+        );
+    }
+}
@@ -2,6 +2,10 @@
 #![allow(clippy::needless_lifetimes)]
 #![doc(test(attr(warn(unused))))]
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod columns;
+mod compare;
 mod config;
 pub mod error;
 mod index;
@@ -10,10 +14,19 @@ mod parser;
 mod pointer;
 mod reader;
 mod util;
+mod validate;
 
 pub mod format;
+#[cfg(debug_assertions)]
+pub mod internals;
 pub mod lazyvalue;
+#[cfg(feature = "schema")]
+pub mod schema;
 pub mod serde;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "tracing-integration")]
+pub mod tracing;
 pub mod value;
 pub mod writer;
 
@@ -22,36 +35,70 @@ pub use ::faststr::FastStr;
 // re-export the serde trait
 pub use ::serde::{Deserialize, Serialize};
 #[doc(inline)]
-pub use reader::Read;
+pub use parser::{Checkpoint, ChunkedParser, FeedStatus};
+#[doc(inline)]
+pub use reader::{Read, TrustedUtf8, Utf16Endian, Utf16Read, Utf16Text, Utf8Policy};
+#[cfg(feature = "derive")]
+#[doc(inline)]
+pub use sonic_rs_derive::{include_json, json_schema, LazyExtract};
 
+#[doc(inline)]
+pub use crate::config::{
+    CancelToken, DedupPolicy, DeserializeCfg, FloatFormat, PrettyStyle, SerializeCfg,
+};
 #[doc(inline)]
 pub use crate::error::{Error, Result};
 #[doc(inline)]
 pub use crate::index::Index;
 #[doc(inline)]
 pub use crate::input::JsonInput;
+#[cfg(feature = "stream")]
+#[doc(inline)]
+pub use crate::lazyvalue::StreamGetter;
 #[doc(inline)]
 pub use crate::lazyvalue::{
-    get, get_from_bytes, get_from_bytes_unchecked, get_from_faststr, get_from_faststr_unchecked,
-    get_from_slice, get_from_slice_unchecked, get_from_str, get_from_str_unchecked, get_many,
-    get_many_unchecked, get_unchecked, to_array_iter, to_array_iter_unchecked, to_object_iter,
-    to_object_iter_unchecked, ArrayJsonIter, LazyArray, LazyObject, LazyValue, ObjectJsonIter,
-    OwnedLazyValue,
+    get, get_first, get_from_bytes, get_from_bytes_unchecked, get_from_faststr,
+    get_from_faststr_unchecked, get_from_slice, get_from_slice_unchecked, get_from_str,
+    get_from_str_unchecked, get_many, get_many_all, get_many_structural, get_many_unchecked,
+    get_structural, get_unchecked, get_with_utf8_policy, to_array_iter, to_array_iter_unchecked,
+    to_object_iter, to_object_iter_filtered, to_object_iter_unchecked, to_object_map,
+    to_object_map_faststr, wrap_in_array, wrap_in_array_unchecked, wrap_in_object,
+    wrap_in_object_unchecked, ArrayJsonIter, FromLazyValue, LazyArray, LazyObject, LazyValue,
+    LazyValueEditor, ObjectJsonIter, OwnedLazyValue,
 };
+#[cfg(feature = "rayon")]
 #[doc(inline)]
-pub use crate::pointer::{JsonPointer, PointerNode, PointerTree};
+pub use crate::lazyvalue::{par_array_iter, ParArrayIter};
+#[doc(inline)]
+pub use crate::pointer::{ArraySlice, JsonPointer, PointerNode, PointerTree};
 #[doc(inline)]
 pub use crate::serde::de::{MapAccess, SeqAccess};
+#[cfg(feature = "parallel-io")]
+#[doc(inline)]
+pub use crate::serde::from_reader_parallel;
 #[doc(inline)]
 pub use crate::serde::{
-    from_reader, from_slice, from_slice_unchecked, from_str, to_lazyvalue, to_string,
-    to_string_pretty, to_vec, to_vec_pretty, to_writer, to_writer_pretty, Deserializer,
-    JsonNumberTrait, Number, RawNumber, Serializer, StreamDeserializer,
+    current_pointer, from_reader, from_reader_with_buffer, from_reader_with_capacity,
+    from_reader_with_chunk_size, from_slice, from_slice_padded_unchecked, from_slice_unchecked,
+    from_slice_with_cfg, from_slice_with_defaults, from_str, from_str_with_cfg, to_fmt_writer,
+    to_lazyvalue, to_raw_json, to_string, to_string_pretty, to_string_with_cfg, to_vec,
+    to_vec_pretty, to_vec_with_capacity, to_vec_with_cfg, to_writer, to_writer_buffered,
+    to_writer_pretty, Deserializer, Flatten, Framed, FramedReader, FramedStream, FramedWriter,
+    JsonNumberTrait, MultiMap, Number, NumberClass, NumberKind, RawJson, RawKey, RawNumber,
+    Serializer, StreamDeserializer, Untagged, PADDING_SIZE,
 };
 #[doc(inline)]
+pub use crate::validate::{validate_each, validate_each_parallel, validate_unique_keys};
+#[doc(inline)]
 pub use crate::value::{
-    from_value, get::get_by_schema, to_value, Array, JsonContainerTrait, JsonType,
-    JsonValueMutTrait, JsonValueTrait, Object, Value, ValueRef,
+    canonical_hash, canonical_hash_value, from_value,
+    get::{get_by_schema, get_by_schema_with_report, SchemaReport},
+    to_string_filtered, to_value, Array, ArrayBuilder, FieldMask, IncludedJson, JsonContainerTrait,
+    JsonType, JsonValueMutTrait, JsonValueTrait, Object, ObjectBuilder, ParseStats, Value,
+    ValueBuilder, ValueRef,
 };
 
 pub mod prelude;
+
+#[doc(hidden)]
+pub use crate::lazyvalue::__private;
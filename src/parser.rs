@@ -9,13 +9,13 @@ use std::{
 
 use faststr::FastStr;
 use serde::de::{self, Expected, Unexpected};
-use sonic_number::{parse_number, ParserNumber};
+use sonic_number::{parse_number, parse_number_ext, ParserNumber};
 #[cfg(all(target_feature = "neon", target_arch = "aarch64"))]
 use sonic_simd::bits::NeonBits;
 use sonic_simd::{i8x32, m8x32, u8x32, u8x64, Mask, Simd};
 
 use crate::{
-    config::DeserializeCfg,
+    config::{CancelToken, DeserializeCfg},
     error::{
         invalid_utf8, Error,
         ErrorCode::{self, *},
@@ -24,13 +24,15 @@ use crate::{
     index::Index,
     lazyvalue::value::HasEsc,
     pointer::{
-        tree::{MultiIndex, MultiKey, PointerTreeInner, PointerTreeNode},
+        point::{ArraySlice, PointerNode},
+        tree::{MultiIndex, MultiKey, PointerTreeNode},
         PointerTree,
     },
     reader::Reader,
     serde::de::invalid_type_number,
     util::{
         arch::{get_nonspace_bits, prefix_xor},
+        scratch::ScratchBuf,
         string::*,
         unicode::{codepoint_to_utf8, hex_to_u32_nocheck},
     },
@@ -38,6 +40,10 @@ use crate::{
     JsonValueMutTrait, JsonValueTrait, LazyValue, Number, OwnedLazyValue,
 };
 
+mod chunked;
+
+pub use self::chunked::{ChunkedParser, FeedStatus};
+
 // support borrow for owned deserizlie or skip
 pub(crate) enum Reference<'b, 'c, T>
 where
@@ -73,9 +79,9 @@ where
 pub(crate) enum ParsedSlice<'b, 'c> {
     Borrowed {
         slice: &'b [u8],
-        buf: &'c mut Vec<u8>,
+        buf: &'c mut ScratchBuf,
     },
-    Copied(&'c mut Vec<u8>),
+    Copied(&'c mut ScratchBuf),
 }
 
 impl<'b, 'c> Deref for ParsedSlice<'b, 'c> {
@@ -89,7 +95,12 @@ impl<'b, 'c> Deref for ParsedSlice<'b, 'c> {
     }
 }
 
-pub(crate) const DEFAULT_KEY_BUF_CAPACITY: usize = 128;
+// The UTF-8 byte order mark.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// The cancel token is polled once per this number of the elements or the 64-byte chunks.
+const CANCEL_POLL_INTERVAL: usize = 64;
+
 pub(crate) fn as_str(data: &[u8]) -> &str {
     unsafe { from_utf8_unchecked(data) }
 }
@@ -196,6 +207,102 @@ fn skip_container_loop(
     None
 }
 
+// The result of scanning a number in a 32-byte chunk, the offsets are relative to the chunk.
+enum NumberScan {
+    // the number ends at the offset
+    End(usize),
+    // the byte at the offset is not a digit, but a digit is expected
+    Invalid(usize),
+    // all the bytes are digits of the integer part
+    Digits,
+    // the number may continue after the chunk
+    Partial,
+}
+
+// The length of the consecutive bits starting at `n`.
+#[inline(always)]
+fn run_len(mask: u32, n: usize) -> usize {
+    if n >= 32 {
+        0
+    } else {
+        (mask >> n).trailing_ones() as usize
+    }
+}
+
+#[inline(always)]
+fn has_bit(mask: u32, n: usize) -> bool {
+    n < 32 && mask & (1 << n) != 0
+}
+
+// Scan the remaining of a number, after the first digit of the integer part. The digits, dots,
+// exponents and signs are classified in one vector pass, and the grammar is checked by a small
+// state machine over the bitmasks, such as `digits* ('.' digits+)? ([eE] [+-]? digits+)?`.
+#[inline(always)]
+fn scan_number_chunk(chunk: &[u8]) -> NumberScan {
+    let v = unsafe { i8x32::from_slice_unaligned_unchecked(chunk) };
+    let eq = |c: u8| v.eq(&i8x32::splat(c as i8));
+    let digits = !(i8x32::splat(b'0' as i8).gt(&v) | v.gt(&i8x32::splat(b'9' as i8))).bitmask();
+    let dot = eq(b'.').bitmask();
+    let exp = (eq(b'e') | eq(b'E')).bitmask();
+    let sign = (eq(b'+') | eq(b'-')).bitmask();
+
+    // the integer part
+    let mut n = run_len(digits, 0);
+    if n == 32 {
+        return NumberScan::Digits;
+    }
+
+    // the fraction part
+    if has_bit(dot, n) {
+        n += 1;
+        let len = run_len(digits, n);
+        if len == 0 {
+            return if n == 32 {
+                NumberScan::Partial
+            } else {
+                NumberScan::Invalid(n)
+            };
+        }
+        n += len;
+    }
+
+    // the exponent part
+    if has_bit(exp, n) {
+        n += 1;
+        if has_bit(sign, n) {
+            n += 1;
+        }
+        let len = run_len(digits, n);
+        if len == 0 {
+            return if n == 32 {
+                NumberScan::Partial
+            } else {
+                NumberScan::Invalid(n)
+            };
+        }
+        n += len;
+    }
+
+    if n == 32 {
+        NumberScan::Partial
+    } else {
+        NumberScan::End(n)
+    }
+}
+
+/// A saved position of the [`Deserializer`][crate::Deserializer], which is created by
+/// [`checkpoint`][crate::Deserializer::checkpoint] and restored by
+/// [`rewind`][crate::Deserializer::rewind].
+///
+/// It is only valid for the deserializer that created it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    // the address of the input, to check the checkpoint is from the same input
+    input: usize,
+    index: usize,
+    next_invalid_utf8: usize,
+}
+
 pub(crate) struct Pair<'de> {
     pub key: Cow<'de, str>,
     pub val: &'de [u8],
@@ -208,6 +315,54 @@ pub(crate) struct Parser<R> {
     nospace_bits: u64,    // SIMD marked nospace bitmap
     nospace_start: isize, // the start position of nospace_bits
     pub(crate) cfg: DeserializeCfg,
+    pub(crate) cancel: Option<CancelToken>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PathState {
+    Unknown,
+    Found,
+    Missing,
+}
+
+// The scanning state of `get_first`, the paths are indexed by their orders in the tree.
+struct GetFirstState<'de> {
+    states: Vec<PathState>,
+    best: Option<(usize, LazyValue<'de>)>,
+}
+
+impl<'de> GetFirstState<'de> {
+    fn mark_found(&mut self, orders: &[usize], lv: LazyValue<'de>) {
+        for &order in orders {
+            if self.states[order] != PathState::Unknown {
+                continue;
+            }
+            self.states[order] = PathState::Found;
+            if self.best.as_ref().is_none_or(|(best, _)| order < *best) {
+                self.best = Some((order, lv.clone()));
+            }
+        }
+    }
+
+    // mark all the unknown paths in the subtree as missing
+    fn mark_missing(&mut self, node: &PointerTreeNode) {
+        for &order in &node.order {
+            if self.states[order] == PathState::Unknown {
+                self.states[order] = PathState::Missing;
+            }
+        }
+        node.keys.values().for_each(|n| self.mark_missing(n));
+        node.indexes.values().for_each(|n| self.mark_missing(n));
+    }
+
+    // the paths before the best one are all missing, so the best one is the result
+    fn is_finished(&self) -> bool {
+        let end = self
+            .best
+            .as_ref()
+            .map_or(self.states.len(), |(best, _)| *best);
+        self.states[..end].iter().all(|s| *s == PathState::Missing)
+    }
 }
 
 /// Records the parse status
@@ -217,6 +372,13 @@ pub(crate) enum ParseStatus {
     HasEscaped,
 }
 
+/// The value got by a path, which is a raw JSON value in the input, or the elements of an array
+/// slice at the end of the path.
+pub(crate) enum PathValue<'de> {
+    Raw(&'de [u8], ParseStatus),
+    Slice(Vec<&'de [u8]>),
+}
+
 impl From<ParseStatus> for HasEsc {
     fn from(value: ParseStatus) -> Self {
         match value {
@@ -231,18 +393,91 @@ where
     R: Reader<'de>,
 {
     pub fn new(read: R) -> Self {
-        Self {
+        Self::with_config(read, DeserializeCfg::default())
+    }
+
+    // Create the parser with `cfg`, the BOM is skipped only by `cfg`, so the nested parsers of
+    // the skipped input do not skip it again.
+    pub(crate) fn with_config(read: R, cfg: DeserializeCfg) -> Self {
+        let mut parser = Self {
             read,
             error_index: usize::MAX,
             nospace_bits: 0,
             nospace_start: -128,
-            cfg: DeserializeCfg::default(),
+            cfg,
+            cancel: None,
+        };
+        if parser.cfg.skip_bom {
+            parser.skip_bom();
+        }
+        parser
+    }
+
+    // Skip the UTF-8 BOM at the start of the document.
+    #[inline]
+    pub(crate) fn skip_bom(&mut self) {
+        if self.read.index() == 0 && self.read.as_u8_slice().starts_with(UTF8_BOM) {
+            self.read.eat(UTF8_BOM.len());
+        }
+    }
+
+    // Whether the parser is at the start of the document, after the skipped BOM.
+    #[inline]
+    pub(crate) fn at_doc_start(&self) -> bool {
+        match self.read.index() {
+            0 => true,
+            n => {
+                self.cfg.skip_bom
+                    && n == UTF8_BOM.len()
+                    && self.read.as_u8_slice().starts_with(UTF8_BOM)
+            }
+        }
+    }
+
+    pub(crate) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            input: self.read.as_u8_slice().as_ptr() as usize,
+            index: self.read.index(),
+            next_invalid_utf8: self.read.next_invalid_utf8(),
+        }
+    }
+
+    // Restore the position, and reset the states after it, such as the cached SIMD bitmap.
+    pub(crate) fn rewind(&mut self, cp: Checkpoint) {
+        let input = self.read.as_u8_slice();
+        assert!(
+            cp.input == input.as_ptr() as usize && cp.index <= input.len(),
+            "the checkpoint is not created by this deserializer"
+        );
+        self.read.set_index(cp.index);
+        // the invalid UTF-8 before the position may have been replaced in the lossy mode
+        if self.read.next_invalid_utf8() != cp.next_invalid_utf8 {
+            self.read.check_invalid_utf8();
         }
+        self.error_index = usize::MAX;
+        self.nospace_bits = 0;
+        self.nospace_start = -128;
     }
 
-    pub(crate) fn with_config(mut self, cfg: DeserializeCfg) -> Self {
-        self.cfg = cfg;
-        self
+    // check the cancel token
+    #[inline(always)]
+    pub(crate) fn check_cancelled(&self) -> Result<()> {
+        match &self.cancel {
+            Some(token) if token.is_cancelled() => perr!(self, Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    // poll the cancel token in the loops of objects and arrays, where `count` is the number of the
+    // visited elements. The token is checked at the start of each container and then every
+    // `CANCEL_POLL_INTERVAL` elements, so the hot loops only test the counter.
+    #[inline(always)]
+    pub(crate) fn poll_cancelled(&self, count: usize) -> Result<()> {
+        if count % CANCEL_POLL_INTERVAL == 0 {
+            self.check_cancelled()
+        } else {
+            Ok(())
+        }
     }
 
     #[inline(always)]
@@ -269,7 +504,15 @@ where
             reason = EofWhileParsing;
             index = len;
         }
-        Error::syntax(reason, self.read.as_u8_slice(), index)
+        if self.cfg.lightweight_errors {
+            return Error::lightweight(reason, index);
+        }
+        Error::syntax_with(
+            reason,
+            self.read.as_u8_slice(),
+            index,
+            self.cfg.error_snippet_len,
+        )
     }
 
     // maybe error in generated in visitor, so we need fix the position.
@@ -288,18 +531,46 @@ where
         let neg = first == b'-';
         let mut now = reader.index() - (!neg as usize);
         let data = reader.as_u8_slice();
-        let ret = parse_number(data, &mut now, neg);
+        let ret = if self.cfg.extended_numbers {
+            parse_number_ext(data, &mut now, neg)
+        } else {
+            parse_number(data, &mut now, neg)
+        };
         reader.set_index(now);
         ret.map_err(|err| self.error(err.into()))
     }
 
+    // Skip the number whose first char is consumed, and return its raw text. The extended
+    // numbers, such as `0xFF` and `1_000`, are not valid JSON, so their raw texts are normalized
+    // into the decimal integers.
+    #[inline(always)]
+    pub(crate) fn parse_raw_number(&mut self, first: u8) -> Result<Cow<'de, str>> {
+        let start = self.read.index() - 1;
+        if !self.cfg.extended_numbers {
+            self.skip_number(first)?;
+            let slice = self.read.slice_unchecked(start, self.read.index());
+            return Ok(Cow::Borrowed(as_str(slice)));
+        }
+
+        let num = self.parse_number(first)?;
+        let raw = as_str(self.read.slice_unchecked(start, self.read.index()));
+        if !raw.bytes().any(|c| matches!(c, b'x' | b'X' | b'_')) {
+            return Ok(Cow::Borrowed(raw));
+        }
+        Ok(Cow::Owned(match num {
+            ParserNumber::Unsigned(n) => n.to_string(),
+            ParserNumber::Signed(n) => n.to_string(),
+            ParserNumber::Float(_) => unreachable!("the extended numbers are integers"),
+        }))
+    }
+
     // TODO: optimize me, avoid clone twice.
     #[inline(always)]
-    fn parse_string_owned<V>(&mut self, vis: &mut V, strbuf: &mut Vec<u8>) -> Result<()>
+    fn parse_string_owned<V>(&mut self, vis: &mut V, strbuf: &mut ScratchBuf) -> Result<()>
     where
         V: JsonVisitor<'de>,
     {
-        if !self.cfg.use_raw {
+        if !self.cfg.keep_raw_str() {
             let rs = self.parse_str_impl(strbuf)?;
             return check_visit!(self, vis.visit_str(rs.as_ref()));
         }
@@ -329,14 +600,24 @@ where
         }
     }
 
+    // The option of the control chars is checked once here, instead of in the block loops.
+    #[inline(always)]
+    unsafe fn unescape_inplace(&self, src: &mut *mut u8) -> std::result::Result<usize, ErrorCode> {
+        let (repr, lenient) = (self.cfg.lossy_surrogates(), self.cfg.lenient_escapes);
+        if self.cfg.allow_control_chars {
+            parse_string_inplace::<true>(src, repr, lenient)
+        } else {
+            parse_string_inplace::<false>(src, repr, lenient)
+        }
+    }
+
     #[inline(always)]
     fn parse_string_inplace<V: JsonVisitor<'de>>(&mut self, vis: &mut V) -> Result<()> {
-        if !self.cfg.use_raw {
+        if !self.cfg.keep_raw_str() {
             unsafe {
                 let mut src = self.read.cur_ptr();
                 let start = self.read.cur_ptr();
-                let cnt = parse_string_inplace(&mut src, self.cfg.utf8_lossy)
-                    .map_err(|e| self.error(e))?;
+                let cnt = self.unescape_inplace(&mut src).map_err(|e| self.error(e))?;
                 self.read.set_ptr(src);
                 let slice = from_raw_parts(start, cnt);
                 let s = from_utf8_unchecked(slice);
@@ -351,14 +632,19 @@ where
             match self.skip_string_unchecked()? {
                 ParseStatus::HasEscaped => {
                     let end = self.check_string_eof_inpadding()?;
-                    let raw = as_str(&self.read.as_u8_slice()[start_idx - 1..end]);
-                    let alloc = vis.allocator().unwrap();
-                    let raw = RawStr::new_in(alloc, raw);
-                    let cnt = parse_string_inplace(&mut src, self.cfg.utf8_lossy)
-                        .map_err(|e| self.error(e))?;
+                    let raw = &self.read.as_u8_slice()[start_idx - 1..end];
+                    // the raw text with the control chars is invalid JSON, so it is not kept
+                    let keep_raw =
+                        !self.cfg.allow_control_chars || find_control_char(raw).is_none();
+                    let raw =
+                        keep_raw.then(|| RawStr::new_in(vis.allocator().unwrap(), as_str(raw)));
+                    let cnt = self.unescape_inplace(&mut src).map_err(|e| self.error(e))?;
                     self.read.set_ptr(src);
                     let s = str_from_raw_parts(start, cnt);
-                    check_visit!(self, vis.visit_raw_str(s, raw))
+                    match raw {
+                        Some(raw) => check_visit!(self, vis.visit_raw_str(s, raw)),
+                        None => check_visit!(self, vis.visit_borrowed_str(s)),
+                    }
                 }
                 ParseStatus::None => {
                     let end = self.check_string_eof_inpadding()?;
@@ -375,10 +661,8 @@ where
         V: JsonVisitor<'de>,
     {
         if self.cfg.use_rawnumber || self.cfg.use_raw {
-            let start = self.read.index() - 1;
-            self.skip_number(first)?;
-            let slice = self.read.slice_unchecked(start, self.read.index());
-            check_visit!(self, vis.visit_raw_number(as_str(slice)))
+            let raw = self.parse_raw_number(first)?;
+            check_visit!(self, vis.visit_raw_number(&raw))
         } else {
             let ok = match self.parse_number(first)? {
                 ParserNumber::Float(f) => vis.visit_f64(f),
@@ -395,10 +679,11 @@ where
         V: JsonVisitor<'de>,
     {
         if self.cfg.use_rawnumber || self.cfg.use_raw {
-            let start = self.read.index() - 1;
-            self.skip_number(first)?;
-            let slice = self.read.slice_unchecked(start, self.read.index());
-            check_visit!(self, vis.visit_borrowed_raw_number(as_str(slice)))
+            let ok = match self.parse_raw_number(first)? {
+                Cow::Borrowed(raw) => vis.visit_borrowed_raw_number(raw),
+                Cow::Owned(raw) => vis.visit_raw_number(&raw),
+            };
+            check_visit!(self, ok)
         } else {
             let ok = match self.parse_number(first)? {
                 ParserNumber::Float(f) => vis.visit_f64(f),
@@ -424,6 +709,7 @@ where
 
         let mut count = 0;
         loop {
+            self.poll_cancelled(count)?;
             match first {
                 Some(c @ b'-' | c @ b'0'..=b'9') => self.parse_number_inplace(c, vis),
                 Some(b'"') => self.parse_string_inplace(vis),
@@ -457,6 +743,7 @@ where
 
         // loop for each object key and value
         loop {
+            self.poll_cancelled(count)?;
             self.parse_string_inplace(vis)?;
             self.parse_object_clo()?;
             self.parse_value(vis)?;
@@ -508,22 +795,9 @@ where
         first: &mut bool,
         check: bool,
     ) -> Result<Option<(&'de [u8], ParseStatus)>> {
-        if *first && self.skip_space() != Some(b'[') {
-            return perr!(self, ExpectedArrayStart);
+        if !self.parse_elem_start(first)? {
+            return Ok(None);
         }
-        match self.skip_space_peek() {
-            Some(b']') => {
-                self.read.eat(1);
-                return Ok(None);
-            }
-            Some(b',') if !(*first) => {
-                self.read.eat(1);
-            }
-            Some(_) if *first => {
-                *first = false;
-            }
-            _ => return perr!(self, ExpectedArrayCommaOrEnd),
-        };
         let (raw, status) = if check {
             self.skip_one()
         } else {
@@ -535,15 +809,82 @@ where
     #[inline]
     pub(crate) fn parse_entry_lazy(
         &mut self,
-        strbuf: &mut Vec<u8>,
+        strbuf: &mut ScratchBuf,
+        first: &mut bool,
+        check: bool,
+    ) -> Result<Option<Pair<'de>>> {
+        if !self.parse_entry_start(first)? {
+            return Ok(None);
+        }
+
+        let parsed = self.parse_str_impl(strbuf)?;
+        self.parse_object_clo()?;
+        let (raw, status) = self.skip_entry_value(check)?;
+
+        Ok(Some(Pair {
+            key: parsed.into(),
+            val: raw,
+            status,
+        }))
+    }
+
+    // Same as `parse_entry_lazy`, but the entries whose keys are not in `keys` are skipped, and
+    // their keys are not copied.
+    pub(crate) fn parse_entry_lazy_filtered<K: AsRef<str>>(
+        &mut self,
+        strbuf: &mut ScratchBuf,
         first: &mut bool,
         check: bool,
+        keys: &[K],
     ) -> Result<Option<Pair<'de>>> {
+        loop {
+            if !self.parse_entry_start(first)? {
+                return Ok(None);
+            }
+
+            let parsed = self.parse_str_impl(strbuf)?;
+            let wanted = keys.iter().any(|k| k.as_ref() == &*parsed);
+            self.parse_object_clo()?;
+            let (raw, status) = self.skip_entry_value(check)?;
+            if wanted {
+                return Ok(Some(Pair {
+                    key: parsed.into(),
+                    val: raw,
+                    status,
+                }));
+            }
+        }
+    }
+
+    // Parse the array start or the comma before the element, and return false at the array end.
+    pub(crate) fn parse_elem_start(&mut self, first: &mut bool) -> Result<bool> {
+        if *first && self.skip_space() != Some(b'[') {
+            return perr!(self, ExpectedArrayStart);
+        }
+        match self.skip_space_peek() {
+            Some(b']') => {
+                self.read.eat(1);
+                Ok(false)
+            }
+            Some(b',') if !(*first) => {
+                self.read.eat(1);
+                Ok(true)
+            }
+            Some(_) if *first => {
+                *first = false;
+                Ok(true)
+            }
+            _ => perr!(self, ExpectedArrayCommaOrEnd),
+        }
+    }
+
+    // Parse the object start or the comma before the key, and return false at the object end.
+    fn parse_entry_start(&mut self, first: &mut bool) -> Result<bool> {
         if *first && self.skip_space() != Some(b'{') {
             return perr!(self, ExpectedObjectStart);
         }
         match self.skip_space() {
-            Some(b'}') => return Ok(None),
+            Some(b'}') => return Ok(false),
             Some(b'"') if *first => *first = false,
             Some(b',') if !*first => {
                 if self.skip_space() != Some(b'"') {
@@ -552,20 +893,16 @@ where
             }
             _ => return perr!(self, ExpectedObjectCommaOrEnd),
         }
+        Ok(true)
+    }
 
-        let parsed = self.parse_str_impl(strbuf)?;
-        self.parse_object_clo()?;
-        let (raw, status) = if check {
+    #[inline(always)]
+    fn skip_entry_value(&mut self, check: bool) -> Result<(&'de [u8], ParseStatus)> {
+        if check {
             self.skip_one()
         } else {
             self.skip_one_unchecked()
-        }?;
-
-        Ok(Some(Pair {
-            key: parsed.into(),
-            val: raw,
-            status,
-        }))
+        }
     }
 
     // Not use non-recurse version here, because it maybe 5% slower than recurse version.
@@ -607,8 +944,10 @@ where
                 match self.skip_string()? {
                     ParseStatus::None => {
                         let slice = self.read.slice_unchecked(start, self.read.index());
-                        let raw = unsafe { self.read.slice_ref(slice).as_faststr() };
-                        return Ok(OwnedLazyValue::from_non_esc_str(raw));
+                        if !self.cfg.allow_control_chars || find_control_char(slice).is_none() {
+                            let raw = unsafe { self.read.slice_ref(slice).as_faststr() };
+                            return Ok(OwnedLazyValue::from_non_esc_str(raw));
+                        }
                     }
                     ParseStatus::HasEscaped => {}
                 }
@@ -631,12 +970,21 @@ where
         };
         let end = self.read.index();
         let sub = self.read.slice_unchecked(start, end);
+        // escape the raw control chars, so that the raw JSON is valid
+        if self.cfg.allow_control_chars {
+            if let Some(escaped) = escape_control_chars(as_str(sub)) {
+                return Ok(OwnedLazyValue::new(
+                    FastStr::new(escaped).into(),
+                    HasEsc::Yes,
+                ));
+            }
+        }
         let raw = unsafe { self.read.slice_ref(sub).as_faststr() };
         Ok(OwnedLazyValue::new(raw.into(), HasEsc::Possible))
     }
 
     #[inline(always)]
-    fn parse_faststr(&mut self, strbuf: &mut Vec<u8>) -> Result<FastStr> {
+    fn parse_faststr(&mut self, strbuf: &mut ScratchBuf) -> Result<FastStr> {
         match self.parse_str_impl(strbuf)? {
             Reference::Borrowed(s) => {
                 return Ok(unsafe { self.read.slice_ref(s.as_bytes()).as_faststr() });
@@ -646,7 +994,10 @@ where
     }
 
     #[inline(always)]
-    pub(crate) fn load_owned_lazyvalue(&mut self, strbuf: &mut Vec<u8>) -> Result<OwnedLazyValue> {
+    pub(crate) fn load_owned_lazyvalue(
+        &mut self,
+        strbuf: &mut ScratchBuf,
+    ) -> Result<OwnedLazyValue> {
         match self.skip_space() {
             Some(c @ b'-' | c @ b'0'..=b'9') => {
                 let num: Number = self.parse_number(c)?.into();
@@ -718,7 +1069,7 @@ where
     }
 
     #[inline(always)]
-    pub(crate) fn parse_dom2<V>(&mut self, vis: &mut V, strbuf: &mut Vec<u8>) -> Result<()>
+    pub(crate) fn parse_dom2<V>(&mut self, vis: &mut V, strbuf: &mut ScratchBuf) -> Result<()>
     where
         V: JsonVisitor<'de>,
     {
@@ -730,7 +1081,7 @@ where
     pub(crate) fn parse_value2<V: JsonVisitor<'de>>(
         &mut self,
         vis: &mut V,
-        strbuf: &mut Vec<u8>,
+        strbuf: &mut ScratchBuf,
     ) -> Result<()> {
         match self.skip_space() {
             Some(c @ b'-' | c @ b'0'..=b'9') => self.parse_number_visit(c, vis),
@@ -745,7 +1096,7 @@ where
     pub(crate) fn parse_object2<V: JsonVisitor<'de>>(
         &mut self,
         vis: &mut V,
-        strbuf: &mut Vec<u8>,
+        strbuf: &mut ScratchBuf,
     ) -> Result<()> {
         // parsing empty object
         let mut count: usize = 0;
@@ -758,6 +1109,7 @@ where
 
         // loop for each object key and value
         loop {
+            self.poll_cancelled(count)?;
             self.parse_string_owned(vis, strbuf)?;
             self.parse_object_clo()?;
             self.parse_value2(vis, strbuf)?;
@@ -776,7 +1128,7 @@ where
     pub(crate) fn parse_array2<V: JsonVisitor<'de>>(
         &mut self,
         visitor: &mut V,
-        strbuf: &mut Vec<u8>,
+        strbuf: &mut ScratchBuf,
     ) -> Result<()> {
         // parsing empty array
         check_visit!(self, visitor.visit_array_start(0))?;
@@ -788,6 +1140,7 @@ where
 
         let mut count = 0;
         loop {
+            self.poll_cancelled(count)?;
             match first {
                 Some(c @ b'-' | c @ b'0'..=b'9') => self.parse_number_visit(c, visitor),
                 Some(b'"') => self.parse_string_owned(visitor, strbuf),
@@ -808,14 +1161,15 @@ where
     #[inline(always)]
     pub(crate) fn parse_str_impl<'own>(
         &mut self,
-        buf: &'own mut Vec<u8>,
+        buf: &'own mut ScratchBuf,
     ) -> Result<Reference<'de, 'own, str>> {
         match self.parse_string_raw(buf) {
             Ok(ParsedSlice::Copied(buf)) => {
                 if self.check_invalid_utf8(self.cfg.utf8_lossy)? {
                     // repr the invalid utf-8
-                    let repr = String::from_utf8_lossy(buf.as_ref()).into_owned();
-                    *buf = repr.into_bytes();
+                    let repr = String::from_utf8_lossy(buf.as_slice()).into_owned();
+                    buf.clear();
+                    buf.extend_from_slice(repr.as_bytes());
                 }
                 let slice = unsafe { from_utf8_unchecked(buf.as_slice()) };
                 Ok(Reference::Copied(slice))
@@ -824,8 +1178,9 @@ where
                 if self.check_invalid_utf8(self.cfg.utf8_lossy)? {
                     // repr the invalid utf-8
                     let repr = String::from_utf8_lossy(slice).into_owned();
-                    *buf = repr.into_bytes();
-                    let slice = unsafe { from_utf8_unchecked(buf) };
+                    buf.clear();
+                    buf.extend_from_slice(repr.as_bytes());
+                    let slice = unsafe { from_utf8_unchecked(buf.as_slice()) };
                     Ok(Reference::Copied(slice))
                 } else {
                     Ok(Reference::Borrowed(unsafe { from_utf8_unchecked(slice) }))
@@ -861,18 +1216,20 @@ where
         // only check surrogate here, and we will check the code pointer later when use
         // `codepoint_to_utf8`
         if (0xD800..0xDC00).contains(&point1) {
-            // parse the second utf8 code point of surrogate
-            let point2 = if let Some(asc) = self.read.next_n(6) {
+            // parse the second utf8 code point of surrogate, only consume it when it is a valid
+            // low surrogate, so that the following escaped chars will not be lost in lossy mode
+            let point2 = if let Some(asc) = self.read.peek_n(6) {
                 if asc[0] != b'\\' || asc[1] != b'u' {
-                    if self.cfg.utf8_lossy {
+                    if self.cfg.lossy_surrogates() {
                         return Ok(0xFFFD);
                     } else {
                         // invalid surrogate
+                        self.read.eat(6);
                         return perr!(self, InvalidSurrogateUnicodeCodePoint);
                     }
                 }
                 unsafe { hex_to_u32_nocheck(&*(asc.as_ptr().add(2) as *const _ as *const [u8; 4])) }
-            } else if self.cfg.utf8_lossy {
+            } else if self.cfg.lossy_surrogates() {
                 return Ok(0xFFFD);
             } else {
                 // invalid surrogate
@@ -882,17 +1239,19 @@ where
             /* calcute the real code point */
             let low_bit = point2.wrapping_sub(0xdc00);
             if (low_bit >> 10) != 0 {
-                if self.cfg.utf8_lossy {
+                if self.cfg.lossy_surrogates() {
                     return Ok(0xFFFD);
                 } else {
                     // invalid surrogate
+                    self.read.eat(6);
                     return perr!(self, InvalidSurrogateUnicodeCodePoint);
                 }
             }
 
+            self.read.eat(6);
             Ok((((point1 - 0xd800) << 10) | low_bit).wrapping_add(0x10000))
         } else if (0xDC00..0xE000).contains(&point1) {
-            if self.cfg.utf8_lossy {
+            if self.cfg.lossy_surrogates() {
                 return Ok(0xFFFD);
             } else {
                 // invalid surrogate
@@ -903,7 +1262,7 @@ where
         }
     }
 
-    pub(crate) unsafe fn parse_escaped_char(&mut self, buf: &mut Vec<u8>) -> Result<()> {
+    pub(crate) unsafe fn parse_escaped_char(&mut self, buf: &mut ScratchBuf) -> Result<()> {
         'escape: loop {
             match self.read.next() {
                 Some(b'u') => {
@@ -920,6 +1279,14 @@ where
                     buf.push(ESCAPED_TAB[c as usize]);
                 }
                 None => return perr!(self, EofWhileParsing),
+                Some(c) if self.cfg.lenient_escapes => {
+                    let next = self.read.peek_n(2).unwrap_or_default();
+                    let Some((bytes, len, eaten)) = lenient_escape(c, next) else {
+                        return perr!(self, InvalidEscape);
+                    };
+                    buf.extend_from_slice(&bytes[..len]);
+                    self.read.eat(eaten);
+                }
                 _ => return perr!(self, InvalidEscape),
             }
 
@@ -935,7 +1302,18 @@ where
 
     pub(crate) unsafe fn parse_string_escaped<'own>(
         &mut self,
-        buf: &'own mut Vec<u8>,
+        buf: &'own mut ScratchBuf,
+    ) -> Result<ParsedSlice<'de, 'own>> {
+        if self.cfg.allow_control_chars {
+            self.parse_string_escaped_impl::<true>(buf)
+        } else {
+            self.parse_string_escaped_impl::<false>(buf)
+        }
+    }
+
+    unsafe fn parse_string_escaped_impl<'own, const ALLOW_CONTROL: bool>(
+        &mut self,
+        buf: &'own mut ScratchBuf,
     ) -> Result<ParsedSlice<'de, 'own>> {
         #[cfg(all(target_feature = "neon", target_arch = "aarch64"))]
         let mut block: StringBlock<NeonBits>;
@@ -947,7 +1325,7 @@ where
         while let Some(chunk) = self.read.peek_n(StringBlock::LANES) {
             buf.reserve(StringBlock::LANES);
             let v = unsafe { load(chunk.as_ptr()) };
-            block = StringBlock::new(&v);
+            block = StringBlock::new(&v).allow_control(ALLOW_CONTROL);
 
             if block.has_unescaped() {
                 self.read.eat(block.unescaped_index());
@@ -992,7 +1370,9 @@ where
                     self.read.eat(1);
                     self.parse_escaped_char(buf)?;
                 }
-                b'\x00'..=b'\x1f' => return perr!(self, ControlCharacterWhileParsingString),
+                b'\x00'..=b'\x1f' if !ALLOW_CONTROL => {
+                    return perr!(self, ControlCharacterWhileParsingString)
+                }
                 _ => {
                     buf.push(c);
                     self.read.eat(1);
@@ -1007,7 +1387,19 @@ where
     // parse_string_raw maybe borrowed, maybe copied into buf(buf will be clear at first).
     pub(crate) fn parse_string_raw<'own>(
         &mut self,
-        buf: &'own mut Vec<u8>,
+        buf: &'own mut ScratchBuf,
+    ) -> Result<ParsedSlice<'de, 'own>> {
+        if self.cfg.allow_control_chars {
+            self.parse_string_raw_impl::<true>(buf)
+        } else {
+            self.parse_string_raw_impl::<false>(buf)
+        }
+    }
+
+    #[inline(always)]
+    fn parse_string_raw_impl<'own, const ALLOW_CONTROL: bool>(
+        &mut self,
+        buf: &'own mut ScratchBuf,
     ) -> Result<ParsedSlice<'de, 'own>> {
         // now reader is start after `"`, so we can directly skipstring
         let start = self.read.index();
@@ -1018,7 +1410,7 @@ where
 
         while let Some(chunk) = self.read.peek_n(StringBlock::LANES) {
             let v = unsafe { load(chunk.as_ptr()) };
-            block = StringBlock::new(&v);
+            block = StringBlock::new(&v).allow_control(ALLOW_CONTROL);
 
             if block.has_quote_first() {
                 let cnt = block.quote_index();
@@ -1041,7 +1433,7 @@ where
                 buf.clear();
                 buf.extend_from_slice(&self.read.as_u8_slice()[start..self.read.index() - 1]);
 
-                return unsafe { self.parse_string_escaped(buf) };
+                return unsafe { self.parse_string_escaped_impl::<ALLOW_CONTROL>(buf) };
             }
 
             self.read.eat(StringBlock::LANES);
@@ -1060,9 +1452,11 @@ where
                     buf.clear();
                     buf.extend_from_slice(self.read.slice_unchecked(start, self.read.index()));
                     self.read.eat(1);
-                    return unsafe { self.parse_string_escaped(buf) };
+                    return unsafe { self.parse_string_escaped_impl::<ALLOW_CONTROL>(buf) };
+                }
+                b'\x00'..=b'\x1f' if !ALLOW_CONTROL => {
+                    return perr!(self, ControlCharacterWhileParsingString)
                 }
-                b'\x00'..=b'\x1f' => return perr!(self, ControlCharacterWhileParsingString),
                 _ => self.read.eat(1),
             }
         }
@@ -1173,7 +1567,9 @@ where
                 if self.read.next().is_none() {
                     return perr!(self, EofWhileParsing);
                 }
-                if ESCAPED_TAB[c as usize] == 0 {
+                if ESCAPED_TAB[c as usize] == 0
+                    && !(self.cfg.lenient_escapes && lenient_escape(c, &[]).is_some())
+                {
                     return perr!(self, InvalidEscape);
                 }
             }
@@ -1184,7 +1580,16 @@ where
 
     // skip_string skips a JSON string with validation.
     #[inline(always)]
-    fn skip_string(&mut self) -> Result<ParseStatus> {
+    pub(crate) fn skip_string(&mut self) -> Result<ParseStatus> {
+        if self.cfg.allow_control_chars {
+            self.skip_string_impl::<true>()
+        } else {
+            self.skip_string_impl::<false>()
+        }
+    }
+
+    #[inline(always)]
+    fn skip_string_impl<const ALLOW_CONTROL: bool>(&mut self) -> Result<ParseStatus> {
         const LANS: usize = u8x32::LANES;
 
         let mut status = ParseStatus::None;
@@ -1192,8 +1597,10 @@ where
             let v = unsafe { u8x32::from_slice_unaligned_unchecked(chunk) };
             let v_bs = v.eq(&u8x32::splat(b'\\'));
             let v_quote = v.eq(&u8x32::splat(b'"'));
-            let v_cc = v.le(&u8x32::splat(0x1f));
-            let mask = (v_bs | v_quote | v_cc).bitmask();
+            let mut mask = (v_bs | v_quote).bitmask();
+            if !ALLOW_CONTROL {
+                mask |= v.le(&u8x32::splat(0x1f)).bitmask();
+            }
 
             // check the mask
             if mask != 0 {
@@ -1222,7 +1629,9 @@ where
                     status = ParseStatus::HasEscaped;
                 }
                 b'"' => return Ok(status),
-                0..=0x1f => return perr!(self, ControlCharacterWhileParsingString),
+                0..=0x1f if !ALLOW_CONTROL => {
+                    return perr!(self, ControlCharacterWhileParsingString)
+                }
                 _ => {}
             }
         }
@@ -1268,7 +1677,10 @@ where
             Some(_) => return perr!(self, ExpectObjectKeyOrEnd),
         }
 
+        let mut count = 0;
         loop {
+            self.poll_cancelled(count)?;
+            count += 1;
             self.skip_string()?;
             self.parse_object_clo()?;
             self.skip_one()?;
@@ -1296,7 +1708,10 @@ where
             _ => {}
         }
 
+        let mut count = 0;
         loop {
+            self.poll_cancelled(count)?;
+            count += 1;
             self.skip_one()?;
             match self.skip_space() {
                 Some(b']') => return Ok(()),
@@ -1314,9 +1729,12 @@ where
         let mut prev_escaped = 0;
         let mut rbrace_num = 0;
         let mut lbrace_num = 0;
-        let reader = &mut self.read;
+        let mut chunks = 0;
 
-        while let Some(chunk) = reader.peek_n(64) {
+        // the token is polled by the 64-byte chunks
+        while let Some(chunk) = self.read.peek_n(64) {
+            self.poll_cancelled(chunks)?;
+            chunks += 1;
             let input = unsafe { &*(chunk.as_ptr() as *const [_; 64]) };
             if let Some(count) = skip_container_loop(
                 input,
@@ -1327,12 +1745,13 @@ where
                 left,
                 right,
             ) {
-                reader.eat(count.get() as usize);
+                self.read.eat(count.get() as usize);
                 return Ok(());
             }
-            reader.eat(64);
+            self.read.eat(64);
         }
 
+        let reader = &mut self.read;
         let mut remain = [0u8; 64];
         unsafe {
             let n = reader.remain();
@@ -1471,6 +1890,10 @@ where
 
     #[inline(always)]
     pub(crate) fn skip_number(&mut self, mut first: u8) -> Result<()> {
+        if self.cfg.extended_numbers {
+            return self.parse_number(first).map(|_| ());
+        }
+
         // check eof after the sign
         if first == b'-' {
             first = self.skip_single_digit()?;
@@ -1483,104 +1906,53 @@ where
         }
 
         // fast path for the single digit
-        let mut is_float: bool = false;
-        match second {
-            Some(b'0'..=b'9') => self.read.eat(1),
-            Some(b'.') => {
-                is_float = true;
-                self.read.eat(1);
-                self.skip_single_digit()?;
-            }
-            Some(b'e' | b'E') => {
-                self.read.eat(1);
-                return self.skip_exponent();
-            }
-            _ => return Ok(()),
+        if !matches!(second, Some(b'0'..=b'9' | b'.' | b'e' | b'E')) {
+            return Ok(());
         }
 
-        // SIMD path for long number
+        // SIMD path, the long integer part is skipped by chunks
         const LANES: usize = i8x32::LANES;
         while let Some(chunk) = self.read.peek_n(LANES) {
-            let v = unsafe { i8x32::from_slice_unaligned_unchecked(chunk) };
-            let zero = i8x32::splat(b'0' as i8);
-            let nine = i8x32::splat(b'9' as i8);
-            let mut nondigits = (zero.gt(&v) | v.gt(&nine)).bitmask();
-            if nondigits != 0 {
-                let mut cnt = nondigits.trailing_zeros() as usize;
-                let ch = chunk[cnt];
-                if ch == b'.' && !is_float {
-                    self.read.eat(cnt + 1);
-                    // check the first digit after the dot
-                    self.skip_single_digit()?;
-
-                    // check the overflow
-                    cnt += 2;
-                    if cnt >= LANES {
-                        is_float = true;
-                        continue;
-                    }
-
-                    nondigits = nondigits.wrapping_shr(cnt as u32);
-                    if nondigits != 0 {
-                        let offset = nondigits.trailing_zeros() as usize;
-                        let ch = chunk[cnt + offset];
-                        if ch == b'e' || ch == b'E' {
-                            self.read.eat(offset + 1);
-                            return self.skip_exponent();
-                        } else {
-                            self.read.eat(offset);
-                            return Ok(());
-                        }
-                    } else {
-                        self.read.eat(32 - cnt);
-                        is_float = true;
-                        continue;
-                    }
-                } else if ch == b'e' || ch == b'E' {
-                    self.read.eat(cnt + 1);
-                    return self.skip_exponent();
-                } else {
-                    self.read.eat(cnt);
+            match scan_number_chunk(chunk) {
+                NumberScan::End(n) => {
+                    self.read.eat(n);
                     return Ok(());
                 }
+                NumberScan::Invalid(n) => {
+                    self.read.eat(n + 1);
+                    return perr!(self, InvalidNumber);
+                }
+                NumberScan::Digits => self.read.eat(LANES),
+                NumberScan::Partial => break,
             }
-            // long digits
-            self.read.eat(32);
         }
 
-        // has less than 32 bytes
+        // has less than 32 bytes, or the number crosses the chunk
         while matches!(self.read.peek(), Some(b'0'..=b'9')) {
             self.read.eat(1);
         }
-
-        match self.read.peek() {
-            Some(b'.') if !is_float => {
-                self.read.eat(1);
-                self.skip_single_digit()?;
-                while matches!(self.read.peek(), Some(b'0'..=b'9')) {
-                    self.read.eat(1);
-                }
-                match self.read.peek() {
-                    Some(b'e' | b'E') => {
-                        self.read.eat(1);
-                        return self.skip_exponent();
-                    }
-                    _ => return Ok(()),
-                }
-            }
-            Some(b'e' | b'E') => {
+        if self.read.peek() == Some(b'.') {
+            self.read.eat(1);
+            self.skip_single_digit()?;
+            while matches!(self.read.peek(), Some(b'0'..=b'9')) {
                 self.read.eat(1);
-                return self.skip_exponent();
             }
-            _ => {}
+        }
+        if matches!(self.read.peek(), Some(b'e' | b'E')) {
+            self.read.eat(1);
+            return self.skip_exponent();
         }
         Ok(())
     }
 
+    // skip_one skips a JSON value with the full validation of the JSON grammar, such as the
+    // numbers, literals, the escapes and control chars in strings, and the nested values. The UTF-8
+    // is validated by the reader or the callers, not here.
     #[inline(always)]
     pub(crate) fn skip_one(&mut self) -> Result<(&'de [u8], ParseStatus)> {
         let ch = self.skip_space();
-        let start = self.read.index() - 1;
+        // the index is not moved at the EOF of the empty input
+        let start = self.read.index().saturating_sub(1);
         let mut status = ParseStatus::None;
         match ch {
             Some(c @ b'-' | c @ b'0'..=b'9') => self.skip_number(c),
@@ -1600,10 +1972,15 @@ where
         Ok((slice, status))
     }
 
+    // skip_one_unchecked skips a JSON value by scanning the structural chars only. It only
+    // guarantees to find the end of the value if the JSON is valid: the strings are skipped by the
+    // quotes and backslashes, and the containers are skipped by the matched brackets outside the
+    // strings. The invalid values inside the containers, the control chars and invalid escapes are
+    // not reported.
     #[inline(always)]
     pub(crate) fn skip_one_unchecked(&mut self) -> Result<(&'de [u8], ParseStatus)> {
         let ch = self.skip_space();
-        let start = self.read.index() - 1;
+        let start = self.read.index().saturating_sub(1);
         let mut status = ParseStatus::None;
         match ch {
             Some(b'-' | b'0'..=b'9') => self.skip_number_unsafe(),
@@ -1648,19 +2025,60 @@ where
         }
     }
 
-    // get_from_object will make reader at the position after target key in JSON object.
-    #[inline(always)]
-    fn get_from_object(&mut self, target_key: &str, temp_buf: &mut Vec<u8>) -> Result<()> {
-        match self.skip_space() {
-            Some(b'{') => {}
-            Some(peek) => return Err(self.peek_invalid_type(peek, &"a JSON object")),
-            None => return perr!(self, EofWhileParsing),
-        }
-
-        // deal with the empty object
-        match self.get_next_token([b'"', b'}'], 1) {
-            Some(b'"') => {}
-            Some(b'}') => return perr!(self, GetInEmptyObject),
+    // Copy the next value into `out` without the whitespaces outside the strings. The value must
+    // be validated before, such as by `skip_one`. The whitespaces are skipped and the strings are
+    // copied by the SIMD scans.
+    pub(crate) fn minify_into(&mut self, out: &mut Vec<u8>) -> Result<()> {
+        let mut depth = 0usize;
+        loop {
+            let Some(c) = self.skip_space() else {
+                return perr!(self, EofWhileParsing);
+            };
+            let start = self.read.index() - 1;
+            match c {
+                b'"' => {
+                    // SAFETY: the string is validated before
+                    unsafe { self.skip_string_unchecked() }?;
+                    out.extend_from_slice(self.read.slice_unchecked(start, self.read.index()));
+                }
+                b'{' | b'[' => {
+                    out.push(c);
+                    depth += 1;
+                    continue;
+                }
+                b'}' | b']' => {
+                    out.push(c);
+                    depth -= 1;
+                }
+                b',' | b':' => {
+                    out.push(c);
+                    continue;
+                }
+                _ => {
+                    self.read.backward(1);
+                    let (raw, _) = self.skip_one_unchecked()?;
+                    out.extend_from_slice(raw);
+                }
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    // get_from_object will make reader at the position after target key in JSON object.
+    #[inline(always)]
+    fn get_from_object(&mut self, target_key: &str, temp_buf: &mut ScratchBuf) -> Result<()> {
+        match self.skip_space() {
+            Some(b'{') => {}
+            Some(peek) => return Err(self.peek_invalid_type(peek, &"a JSON object")),
+            None => return perr!(self, EofWhileParsing),
+        }
+
+        // deal with the empty object
+        match self.get_next_token([b'"', b'}'], 1) {
+            Some(b'"') => {}
+            Some(b'}') => return perr!(self, GetInEmptyObject),
             None => return perr!(self, EofWhileParsing),
             Some(_) => unreachable!(),
         }
@@ -1695,7 +2113,11 @@ where
 
     // get_from_object will make reader at the position after target key in JSON object.
     #[inline(always)]
-    fn get_from_object_checked(&mut self, target_key: &str, temp_buf: &mut Vec<u8>) -> Result<()> {
+    fn get_from_object_checked(
+        &mut self,
+        target_key: &str,
+        temp_buf: &mut ScratchBuf,
+    ) -> Result<()> {
         match self.skip_space() {
             Some(b'{') => {}
             Some(peek) => return Err(self.peek_invalid_type(peek, &"a JSON object")),
@@ -1733,6 +2155,9 @@ where
 
     #[inline(always)]
     fn get_from_array_checked(&mut self, index: usize) -> Result<()> {
+        if index == usize::MAX {
+            return perr!(self, GetIndexOverflow);
+        }
         let mut count = index;
         match self.skip_space() {
             Some(b'[') => {}
@@ -1771,6 +2196,9 @@ where
     // get_from_array will make reader at the position after target index in JSON array.
     #[inline(always)]
     fn get_from_array(&mut self, index: usize) -> Result<()> {
+        if index == usize::MAX {
+            return perr!(self, GetIndexOverflow);
+        }
         let mut count = index;
         match self.skip_space() {
             Some(b'[') => {}
@@ -1803,53 +2231,165 @@ where
         Ok(())
     }
 
+    // get_from_array_end counts the elements at first, and then gets the `n`-th element from the
+    // end of the JSON array.
+    fn get_from_array_end(&mut self, n: usize, checked: bool) -> Result<()> {
+        if n == usize::MAX {
+            return perr!(self, GetIndexOverflow);
+        }
+        let start = self.read.index();
+        let mut len = 0;
+        match self.skip_space() {
+            Some(b'[') => {}
+            Some(peek) => return Err(self.peek_invalid_type(peek, &"a JSON array")),
+            None => return perr!(self, EofWhileParsing),
+        }
+        match self.skip_space_peek() {
+            Some(b']') => return perr!(self, GetInEmptyArray),
+            Some(_) => {}
+            None => return perr!(self, EofWhileParsing),
+        }
+        loop {
+            self.skip_one()?;
+            len += 1;
+            match self.skip_space() {
+                Some(b']') => break,
+                Some(b',') => {}
+                Some(_) => return perr!(self, ExpectedArrayCommaOrEnd),
+                None => return perr!(self, EofWhileParsing),
+            }
+        }
+
+        let Some(index) = len.checked_sub(n).filter(|_| n > 0) else {
+            return perr!(self, GetIndexOutOfArray);
+        };
+        self.read.set_index(start);
+        if checked {
+            self.get_from_array_checked(index)
+        } else {
+            self.get_from_array(index)
+        }
+    }
+
+    // get_from_array_slice collects the elements of a slice of the JSON array. The elements after
+    // the end of the slice are not scanned if the slice is resolved without the array length.
+    fn get_from_array_slice(
+        &mut self,
+        slice: &ArraySlice,
+        checked: bool,
+    ) -> Result<Vec<&'de [u8]>> {
+        match self.skip_space() {
+            Some(b'[') => {}
+            Some(peek) => return Err(self.peek_invalid_type(peek, &"a JSON array")),
+            None => return perr!(self, EofWhileParsing),
+        }
+        if self.skip_space_peek() == Some(b']') {
+            self.read.eat(1);
+            return Ok(Vec::new());
+        }
+
+        let forward = slice.forward();
+        let mut elems = Vec::new();
+        let mut i = 0;
+        loop {
+            if matches!(forward, Some((_, Some(end), _)) if i >= end) {
+                break;
+            }
+            let (elem, _) = if checked {
+                self.skip_one()?
+            } else {
+                self.skip_one_unchecked()?
+            };
+            match forward {
+                Some((start, _, step)) if i < start || (i - start) % step != 0 => {}
+                _ => elems.push(elem),
+            }
+            i += 1;
+            match self.skip_space() {
+                Some(b']') => break,
+                Some(b',') => {}
+                Some(_) => return perr!(self, ExpectedArrayCommaOrEnd),
+                None => return perr!(self, EofWhileParsing),
+            }
+        }
+
+        if forward.is_none() {
+            let all = elems;
+            elems = slice.indices(all.len()).map(|i| all[i]).collect();
+        }
+        Ok(elems)
+    }
+
     pub(crate) fn get_from_with_iter_unchecked<P: IntoIterator>(
         &mut self,
         path: P,
-    ) -> Result<(&'de [u8], ParseStatus)>
+    ) -> Result<PathValue<'de>>
     where
         P::Item: Index,
     {
         // temp buf reused when parsing each escaped key
-        let mut temp_buf = Vec::with_capacity(DEFAULT_KEY_BUF_CAPACITY);
-        for jp in path.into_iter() {
+        let mut temp_buf = ScratchBuf::new();
+        let mut path = path.into_iter();
+        while let Some(jp) = path.next() {
             if let Some(key) = jp.as_key() {
                 self.get_from_object(key, &mut temp_buf)
             } else if let Some(index) = jp.as_index() {
                 self.get_from_array(index)
+            } else if let Some(n) = jp.as_index_from_end() {
+                self.get_from_array_end(n, false)
+            } else if jp.is_wildcard() {
+                perr!(self, GetWithWildcard)
+            } else if let Some(slice) = jp.as_slice() {
+                if path.next().is_some() {
+                    return perr!(self, GetInArraySlice);
+                }
+                return self
+                    .get_from_array_slice(&slice, false)
+                    .map(PathValue::Slice);
             } else {
                 unreachable!();
             }?;
         }
-        self.skip_one()
+        let (sub, status) = self.skip_one()?;
+        Ok(PathValue::Raw(sub, status))
     }
 
-    pub(crate) fn get_from_with_iter<P: IntoIterator>(
-        &mut self,
-        path: P,
-    ) -> Result<(&'de [u8], ParseStatus)>
+    pub(crate) fn get_from_with_iter<P: IntoIterator>(&mut self, path: P) -> Result<PathValue<'de>>
     where
         P::Item: Index,
     {
         // temp buf reused when parsing each escaped key
-        let mut temp_buf = Vec::with_capacity(DEFAULT_KEY_BUF_CAPACITY);
-        for jp in path.into_iter() {
+        let mut temp_buf = ScratchBuf::new();
+        let mut path = path.into_iter();
+        while let Some(jp) = path.next() {
             if let Some(key) = jp.as_key() {
                 self.get_from_object_checked(key, &mut temp_buf)
             } else if let Some(index) = jp.as_index() {
                 self.get_from_array_checked(index)
+            } else if let Some(n) = jp.as_index_from_end() {
+                self.get_from_array_end(n, true)
+            } else if jp.is_wildcard() {
+                perr!(self, GetWithWildcard)
+            } else if let Some(slice) = jp.as_slice() {
+                if path.next().is_some() {
+                    return perr!(self, GetInArraySlice);
+                }
+                return self
+                    .get_from_array_slice(&slice, true)
+                    .map(PathValue::Slice);
             } else {
                 unreachable!();
             }?;
         }
-        self.skip_one()
+        let (sub, status) = self.skip_one()?;
+        Ok(PathValue::Raw(sub, status))
     }
 
     fn get_many_rec(
         &mut self,
         node: &PointerTreeNode,
         out: &mut Vec<LazyValue<'de>>,
-        strbuf: &mut Vec<u8>,
+        strbuf: &mut ScratchBuf,
         remain: &mut usize,
         is_safe: bool,
     ) -> Result<()> {
@@ -1869,25 +2409,29 @@ where
         let slice: &'de [u8];
 
         let mut status = ParseStatus::None;
-        match &node.children {
-            PointerTreeInner::Empty => {
-                status = self.skip_one()?.1;
-            }
-            PointerTreeInner::Index(midxs) => {
-                if is_safe {
-                    self.get_many_index(midxs, strbuf, out, remain)?
-                } else {
-                    self.get_many_index_unchecked(midxs, strbuf, out, remain)?
-                }
-            }
-            PointerTreeInner::Key(mkeys) => {
-                if is_safe {
-                    self.get_many_keys(mkeys, strbuf, out, remain)?
-                } else {
-                    self.get_many_keys_unchecked(mkeys, strbuf, out, remain)?
-                }
+        let (mkeys, midxs) = (&node.keys, &node.indexes);
+        if node.is_leaf() {
+            status = self.skip_one()?.1;
+        } else if !mkeys.is_empty() && !midxs.is_empty() {
+            // a value is either an object or an array, so some paths are never found
+            let peek = ch.unwrap();
+            let exp: &dyn Expected = if peek == b'{' {
+                &"a JSON array"
+            } else {
+                &"a JSON object"
+            };
+            return Err(self.peek_invalid_type(peek, exp));
+        } else if !midxs.is_empty() {
+            if is_safe {
+                self.get_many_index(midxs, strbuf, out, remain)?
+            } else {
+                self.get_many_index_unchecked(midxs, strbuf, out, remain)?
             }
-        };
+        } else if is_safe {
+            self.get_many_keys(mkeys, strbuf, out, remain)?
+        } else {
+            self.get_many_keys_unchecked(mkeys, strbuf, out, remain)?
+        }
 
         if !node.order.is_empty() {
             slice = self.read.slice_unchecked(start, self.read.index());
@@ -1904,7 +2448,7 @@ where
     fn get_many_keys_unchecked(
         &mut self,
         mkeys: &MultiKey,
-        strbuf: &mut Vec<u8>,
+        strbuf: &mut ScratchBuf,
         out: &mut Vec<LazyValue<'de>>,
         remain: &mut usize,
     ) -> Result<()> {
@@ -1965,7 +2509,7 @@ where
     fn get_many_keys(
         &mut self,
         mkeys: &MultiKey,
-        strbuf: &mut Vec<u8>,
+        strbuf: &mut ScratchBuf,
         out: &mut Vec<LazyValue<'de>>,
         remain: &mut usize,
     ) -> Result<()> {
@@ -2034,7 +2578,7 @@ where
     fn get_many_index_unchecked(
         &mut self,
         midx: &MultiIndex,
-        strbuf: &mut Vec<u8>,
+        strbuf: &mut ScratchBuf,
         out: &mut Vec<LazyValue<'de>>,
         remain: &mut usize,
     ) -> Result<()> {
@@ -2093,7 +2637,7 @@ where
     fn get_many_index(
         &mut self,
         midx: &MultiIndex,
-        strbuf: &mut Vec<u8>,
+        strbuf: &mut ScratchBuf,
         out: &mut Vec<LazyValue<'de>>,
         remain: &mut usize,
     ) -> Result<()> {
@@ -2147,7 +2691,13 @@ where
         tree: &PointerTree,
         is_safe: bool,
     ) -> Result<Vec<LazyValue<'de>>> {
-        let mut strbuf = Vec::with_capacity(DEFAULT_KEY_BUF_CAPACITY);
+        if tree.has_unsupported() {
+            return perr!(self, GetUnsupportedInTree);
+        }
+        if tree.has_wildcard() {
+            return perr!(self, GetWithWildcard);
+        }
+        let mut strbuf = ScratchBuf::new();
         let mut remain = tree.size();
         let mut out: Vec<LazyValue<'de>> = Vec::with_capacity(tree.size());
         out.resize(tree.size(), LazyValue::default());
@@ -2156,6 +2706,259 @@ where
         Ok(out)
     }
 
+    // Collect the values matching the `nodes`. A value matches several nodes if it is reached by
+    // both its key or index and the wildcard.
+    fn get_all_rec(
+        &mut self,
+        nodes: &[&PointerTreeNode],
+        out: &mut [Vec<LazyValue<'de>>],
+        strbuf: &mut ScratchBuf,
+    ) -> Result<()> {
+        let peek = match self.skip_space_peek() {
+            Some(peek) => peek,
+            None => return perr!(self, EofWhileParsing),
+        };
+
+        let start = self.read.index();
+        let mut status = ParseStatus::None;
+        let has_keys = nodes
+            .iter()
+            .any(|n| !n.keys.is_empty() || n.wildcard.is_some());
+        let has_indexes = nodes
+            .iter()
+            .any(|n| !n.indexes.is_empty() || n.wildcard.is_some());
+        if peek == b'{' && has_keys {
+            self.get_all_keys(nodes, out, strbuf)?;
+        } else if peek == b'[' && has_indexes {
+            self.get_all_index(nodes, out, strbuf)?;
+        } else {
+            status = self.skip_one()?.1;
+        }
+
+        let slice = self.read.slice_unchecked(start, self.read.index());
+        for node in nodes {
+            for &order in &node.order {
+                out[order].push(LazyValue::new(slice.into(), status.into()));
+            }
+        }
+        Ok(())
+    }
+
+    fn get_all_keys(
+        &mut self,
+        nodes: &[&PointerTreeNode],
+        out: &mut [Vec<LazyValue<'de>>],
+        strbuf: &mut ScratchBuf,
+    ) -> Result<()> {
+        // eat the '{'
+        self.read.eat(1);
+        match self.skip_space() {
+            Some(b'"') => {}
+            Some(b'}') => return Ok(()),
+            _ => return perr!(self, ExpectObjectKeyOrEnd),
+        }
+
+        let mut children = Vec::new();
+        loop {
+            let key = self.parse_str_impl(strbuf)?;
+            self.parse_object_clo()?;
+            children.clear();
+            for node in nodes {
+                children.extend(node.keys.get(key.deref()));
+                children.extend(node.wildcard.as_deref());
+            }
+            if children.is_empty() {
+                self.skip_one()?;
+            } else {
+                self.get_all_rec(&children, out, strbuf)?;
+            }
+
+            match self.skip_space() {
+                Some(b',') if self.skip_space() == Some(b'"') => continue,
+                Some(b',') => return perr!(self, ExpectObjectKeyOrEnd),
+                Some(b'}') => return Ok(()),
+                Some(_) => return perr!(self, ExpectedObjectCommaOrEnd),
+                None => return perr!(self, EofWhileParsing),
+            }
+        }
+    }
+
+    fn get_all_index(
+        &mut self,
+        nodes: &[&PointerTreeNode],
+        out: &mut [Vec<LazyValue<'de>>],
+        strbuf: &mut ScratchBuf,
+    ) -> Result<()> {
+        // eat the '['
+        self.read.eat(1);
+        if self.skip_space_peek() == Some(b']') {
+            self.read.eat(1);
+            return Ok(());
+        }
+
+        let mut index = 0;
+        let mut children = Vec::new();
+        loop {
+            children.clear();
+            for node in nodes {
+                children.extend(node.indexes.get(&index));
+                children.extend(node.wildcard.as_deref());
+            }
+            if children.is_empty() {
+                self.skip_one()?;
+            } else {
+                self.get_all_rec(&children, out, strbuf)?;
+            }
+
+            match self.skip_space() {
+                Some(b']') => return Ok(()),
+                Some(b',') => index += 1,
+                Some(_) => return perr!(self, ExpectedArrayCommaOrEnd),
+                None => return perr!(self, EofWhileParsing),
+            }
+        }
+    }
+
+    // Return all the values matching each path in the tree, the missing or mismatched paths
+    // match nothing.
+    pub(crate) fn get_many_all(&mut self, tree: &PointerTree) -> Result<Vec<Vec<LazyValue<'de>>>> {
+        if tree.has_unsupported() {
+            return perr!(self, GetUnsupportedInTree);
+        }
+        let mut strbuf = ScratchBuf::new();
+        let mut out = vec![Vec::new(); tree.size()];
+        self.get_all_rec(&[&tree.root], &mut out, &mut strbuf)?;
+        Ok(out)
+    }
+
+    fn get_first_rec(
+        &mut self,
+        node: &PointerTreeNode,
+        st: &mut GetFirstState<'de>,
+        strbuf: &mut ScratchBuf,
+    ) -> Result<bool> {
+        let peek = self.skip_space_peek();
+        if peek.is_none() {
+            return perr!(self, EofWhileParsing);
+        }
+
+        let start = self.read.index();
+        let mut status = ParseStatus::None;
+        if peek == Some(b'{') && !node.keys.is_empty() {
+            if self.get_first_keys(&node.keys, st, strbuf)? {
+                return Ok(true);
+            }
+        } else if peek == Some(b'[') && !node.indexes.is_empty() {
+            if self.get_first_index(&node.indexes, st, strbuf)? {
+                return Ok(true);
+            }
+        } else {
+            status = self.skip_one()?.1;
+        }
+        // the descendant paths not found in the value are missing
+        node.keys.values().for_each(|child| st.mark_missing(child));
+        node.indexes
+            .values()
+            .for_each(|child| st.mark_missing(child));
+
+        if !node.order.is_empty() {
+            let slice = self.read.slice_unchecked(start, self.read.index());
+            st.mark_found(&node.order, LazyValue::new(slice.into(), status.into()));
+        }
+        Ok(st.is_finished())
+    }
+
+    #[allow(clippy::mutable_key_type)]
+    fn get_first_keys(
+        &mut self,
+        mkeys: &MultiKey,
+        st: &mut GetFirstState<'de>,
+        strbuf: &mut ScratchBuf,
+    ) -> Result<bool> {
+        // eat the '{'
+        self.read.eat(1);
+        match self.skip_space() {
+            Some(b'"') => {}
+            Some(b'}') => return Ok(false),
+            _ => return perr!(self, ExpectObjectKeyOrEnd),
+        }
+
+        loop {
+            let key = self.parse_str_impl(strbuf)?;
+            self.parse_object_clo()?;
+            if let Some(child) = mkeys.get(key.deref()) {
+                if self.get_first_rec(child, st, strbuf)? {
+                    return Ok(true);
+                }
+            } else {
+                self.skip_one()?;
+            }
+
+            match self.skip_space() {
+                Some(b',') if self.skip_space() == Some(b'"') => continue,
+                Some(b',') => return perr!(self, ExpectObjectKeyOrEnd),
+                Some(b'}') => return Ok(false),
+                Some(_) => return perr!(self, ExpectedObjectCommaOrEnd),
+                None => return perr!(self, EofWhileParsing),
+            }
+        }
+    }
+
+    fn get_first_index(
+        &mut self,
+        midx: &MultiIndex,
+        st: &mut GetFirstState<'de>,
+        strbuf: &mut ScratchBuf,
+    ) -> Result<bool> {
+        // eat the '['
+        self.read.eat(1);
+        if self.skip_space_peek() == Some(b']') {
+            self.read.eat(1);
+            return Ok(false);
+        }
+
+        let mut index = 0;
+        loop {
+            if let Some(child) = midx.get(&index) {
+                if self.get_first_rec(child, st, strbuf)? {
+                    return Ok(true);
+                }
+            } else {
+                self.skip_one()?;
+            }
+
+            match self.skip_space() {
+                Some(b']') => return Ok(false),
+                Some(b',') => index += 1,
+                Some(_) => return perr!(self, ExpectedArrayCommaOrEnd),
+                None => return perr!(self, EofWhileParsing),
+            }
+        }
+    }
+
+    // Return the value of the path which has the smallest order in the tree. Stop scanning once
+    // all the paths before the found one are known to be missing.
+    pub(crate) fn get_first(
+        &mut self,
+        tree: &PointerTree,
+    ) -> Result<Option<(usize, LazyValue<'de>)>> {
+        if tree.has_unsupported() {
+            return perr!(self, GetUnsupportedInTree);
+        }
+        if tree.has_wildcard() {
+            return perr!(self, GetWithWildcard);
+        }
+        let mut st = GetFirstState {
+            states: vec![PathState::Unknown; tree.size()],
+            best: None,
+        };
+        if tree.size() > 0 {
+            let mut strbuf = ScratchBuf::new();
+            self.get_first_rec(&tree.root, &mut st, &mut strbuf)?;
+        }
+        Ok(st.best)
+    }
+
     #[cold]
     pub(crate) fn peek_invalid_type(&mut self, peek: u8, exp: &dyn Expected) -> Error {
         let err = match peek {
@@ -2182,7 +2985,7 @@ where
                 Err(err) => return err,
             },
             b'"' => {
-                let mut scratch = Vec::new();
+                let mut scratch = ScratchBuf::new();
                 match self.parse_str_impl(&mut scratch) {
                     Ok(s) if std::str::from_utf8(s.as_bytes()).is_ok() => {
                         de::Error::invalid_type(Unexpected::Str(&s), exp)
@@ -2213,11 +3016,27 @@ where
     }
 }
 
+// The schema of every element in the JSON array, which is written as `{"$items": schema}`.
+fn schema_items(schema: &crate::Value) -> Option<&crate::Value> {
+    let object = schema.as_object()?;
+    if object.len() == 1 {
+        object.get(&"$items")
+    } else {
+        None
+    }
+}
+
 impl<'de, R> Parser<R>
 where
     R: Reader<'de>,
 {
-    pub fn get_by_schema(&mut self, schema: &mut crate::Value) -> Result<()> {
+    // Parse the JSON into the schema in place, and collect the paths of the schema keys and
+    // indexes which are not found in the JSON into `missing`.
+    pub fn get_by_schema(
+        &mut self,
+        schema: &mut crate::Value,
+        missing: &mut Vec<Vec<PointerNode>>,
+    ) -> Result<()> {
         if !schema.is_object() {
             return perr!(
                 self,
@@ -2225,11 +3044,18 @@ where
             );
         }
 
-        let mut strbuf = Vec::with_capacity(DEFAULT_KEY_BUF_CAPACITY);
-        self.get_by_schema_rec(schema, &mut strbuf)
+        let mut strbuf = ScratchBuf::new();
+        let mut path = Vec::new();
+        self.get_by_schema_rec(schema, &mut strbuf, &mut path, missing)
     }
 
-    fn get_by_schema_rec(&mut self, schema: &mut crate::Value, strbuf: &mut Vec<u8>) -> Result<()> {
+    fn get_by_schema_rec(
+        &mut self,
+        schema: &mut crate::Value,
+        strbuf: &mut ScratchBuf,
+        path: &mut Vec<PointerNode>,
+        missing: &mut Vec<Vec<PointerNode>>,
+    ) -> Result<()> {
         let ch = self.skip_space_peek();
         if ch.is_none() {
             return perr!(self, EofWhileParsing);
@@ -2238,32 +3064,42 @@ where
         let mut should_replace = true;
         let start = self.read.index();
 
-        match (schema.as_object_mut(), ch) {
-            (Some(object), Some(b'{')) => {
-                let mut key_values = HashMap::new();
-                for (key, value) in object.iter_mut() {
-                    key_values.insert(key, value);
-                }
-
-                // We should replace the schema object if the object is empty
-                should_replace = key_values.is_empty();
-                if should_replace {
-                    self.skip_one()?;
-                } else {
-                    self.read.eat(1);
-                    match self.skip_space() {
-                        Some(b'"') => {}
-                        Some(b'}') => return Ok(()),
-                        _ => {
-                            return perr!(self, ExpectObjectKeyOrEnd);
-                        }
-                    }
-
-                    loop {
+        if let (Some(array), Some(b'[')) = (schema.as_array_mut(), ch) {
+            // We should replace the schema array if the array is empty
+            should_replace = array.is_empty();
+            if should_replace {
+                self.skip_one()?;
+            } else {
+                self.get_array_by_schema(array, None, strbuf, path, missing)?;
+            }
+        } else if let (Some(items), Some(b'[')) = (schema_items(schema), ch) {
+            let items = items.clone();
+            let mut array = crate::Array::new();
+            self.get_array_by_schema(&mut array, Some(&items), strbuf, path, missing)?;
+            *schema = array.into();
+            should_replace = false;
+        } else if let (Some(object), Some(b'{')) = (schema.as_object_mut(), ch) {
+            // the flag records whether the key is found in the JSON
+            let mut key_values = HashMap::new();
+            for (key, value) in object.iter_mut() {
+                key_values.insert(key, (value, false));
+            }
+
+            // We should replace the schema object if the object is empty
+            should_replace = key_values.is_empty();
+            if should_replace {
+                self.skip_one()?;
+            } else {
+                self.read.eat(1);
+                match self.skip_space() {
+                    Some(b'"') => loop {
                         let key = self.parse_str_impl(strbuf)?;
                         self.parse_object_clo()?;
-                        if let Some(val) = key_values.get_mut(key.deref()) {
-                            self.get_by_schema_rec(val, strbuf)?;
+                        if let Some((val, found)) = key_values.get_mut(key.deref()) {
+                            *found = true;
+                            path.push(PointerNode::Key(FastStr::new(key.deref())));
+                            self.get_by_schema_rec(val, strbuf, path, missing)?;
+                            path.pop();
                         } else {
                             self.skip_one()?;
                         }
@@ -2277,12 +3113,23 @@ where
                             Some(_) => return perr!(self, ExpectedObjectCommaOrEnd),
                             None => return perr!(self, EofWhileParsing),
                         }
+                    },
+                    Some(b'}') => {}
+                    _ => {
+                        return perr!(self, ExpectObjectKeyOrEnd);
+                    }
+                }
+
+                for (key, (_, found)) in key_values {
+                    if !found {
+                        let mut p = path.clone();
+                        p.push(PointerNode::Key(FastStr::new(key)));
+                        missing.push(p);
                     }
                 }
             }
-            _ => {
-                self.skip_one()?;
-            }
+        } else {
+            self.skip_one()?;
         }
 
         let end = self.read.index();
@@ -2292,4 +3139,57 @@ where
         }
         Ok(())
     }
+
+    // The `items` schema is applied to every element in the JSON array. Otherwise, the schema
+    // elements are applied to the JSON elements at the same index, the extra JSON elements are
+    // kept as they are, and the schema elements without the corresponding JSON elements are
+    // missing.
+    fn get_array_by_schema(
+        &mut self,
+        array: &mut crate::Array,
+        items: Option<&crate::Value>,
+        strbuf: &mut ScratchBuf,
+        path: &mut Vec<PointerNode>,
+        missing: &mut Vec<Vec<PointerNode>>,
+    ) -> Result<()> {
+        self.read.eat(1);
+        let mut count = 0;
+        if self.skip_space_peek() == Some(b']') {
+            self.read.eat(1);
+        } else {
+            loop {
+                path.push(PointerNode::Index(count));
+                if let Some(items) = items {
+                    let mut elem = items.clone();
+                    self.get_by_schema_rec(&mut elem, strbuf, path, missing)?;
+                    array.push(elem);
+                } else if count < array.len() {
+                    self.get_by_schema_rec(&mut array[count], strbuf, path, missing)?;
+                } else {
+                    // the null schema is always replaced by the JSON value
+                    let mut elem = crate::Value::new();
+                    self.get_by_schema_rec(&mut elem, strbuf, path, missing)?;
+                    array.push(elem);
+                }
+                path.pop();
+                count += 1;
+
+                match self.skip_space() {
+                    Some(b',') => continue,
+                    Some(b']') => break,
+                    Some(_) => return perr!(self, ExpectedArrayCommaOrEnd),
+                    None => return perr!(self, EofWhileParsing),
+                }
+            }
+        }
+
+        if items.is_none() {
+            for i in count..array.len() {
+                let mut p = path.clone();
+                p.push(PointerNode::Index(i));
+                missing.push(p);
+            }
+        }
+        Ok(())
+    }
 }
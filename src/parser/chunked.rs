@@ -0,0 +1,330 @@
+//! Parse the JSON values from the chunks pushed by the caller, such as the TCP frames.
+
+use serde::de::DeserializeOwned;
+
+use super::{get_string_bits, is_whitespace, skip_container_loop};
+use crate::{error::Result, serde::from_slice};
+
+/// The status returned by [`ChunkedParser::feed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedStatus {
+    /// The buffered JSON value is incomplete, feed more chunks.
+    NeedMore,
+    /// A complete JSON value is buffered, get it by [`ChunkedParser::next_value`].
+    Complete,
+}
+
+/// A push-style parser for the JSON values received in chunks, such as the frames of a
+/// non-blocking socket.
+///
+/// The chunks are appended into an internal buffer, and the end of the value is found by the
+/// same SIMD scanner as skipping the values, so the boundaries are tracked across the chunks. The
+/// full 64-byte blocks are scanned only once, only the incomplete block at the end is scanned
+/// again with the next chunk. Once a value is complete, it is parsed from the contiguous buffer
+/// by the SIMD parser and removed from the buffer. Several values can be pushed one after another,
+/// such as NDJSON, and the whitespaces between them are removed from the buffer.
+///
+/// The top-level numbers have no end marker, they are complete when followed by a whitespace or
+/// another value, or after [`finish`][ChunkedParser::finish].
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{ChunkedParser, FeedStatus, Value};
+///
+/// let mut parser = ChunkedParser::new();
+/// assert_eq!(parser.feed(br#"{"a": [1, "]"#), FeedStatus::NeedMore);
+/// assert!(parser.next_value::<Value>().is_none());
+///
+/// assert_eq!(parser.feed(br#"", 2]} {"b""#), FeedStatus::Complete);
+/// let value: Value = parser.next_value().unwrap().unwrap();
+/// assert_eq!(value, sonic_rs::json!({"a": [1, "]", 2]}));
+///
+/// // the next value is still incomplete
+/// assert!(parser.next_value::<Value>().is_none());
+/// assert_eq!(parser.feed(b": null} 123"), FeedStatus::Complete);
+/// assert_eq!(parser.next_value::<Value>().unwrap().unwrap(), sonic_rs::json!({"b": null}));
+///
+/// // the number at the end is complete after finishing
+/// assert_eq!(parser.finish(), FeedStatus::Complete);
+/// assert_eq!(parser.next_value::<u32>().unwrap().unwrap(), 123);
+/// assert!(parser.next_value::<Value>().is_none());
+/// ```
+#[derive(Debug, Default)]
+pub struct ChunkedParser {
+    // the buffered bytes, starting with the current value if it is started
+    buf: Vec<u8>,
+    // the length of the scanned bytes in `buf`, `0` if the value is not started
+    scanned: usize,
+    // the end of the complete value
+    end: Option<usize>,
+    // the state after the scanned blocks of the strings, arrays and objects
+    blocks: BlockState,
+    eof: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct BlockState {
+    prev_instring: u64,
+    prev_escaped: u64,
+    lbrace_num: usize,
+    rbrace_num: usize,
+}
+
+impl BlockState {
+    // Scan the block of the value starting with `first`, and return the length of the value in
+    // the block if the value ends in it.
+    fn scan(&mut self, block: &[u8; 64], first: u8) -> Option<usize> {
+        let (left, right) = match first {
+            b'"' => {
+                // the first char not in the string is the closing quote
+                let instring =
+                    get_string_bits(block, &mut self.prev_instring, &mut self.prev_escaped);
+                let n = (!instring).trailing_zeros() as usize;
+                return (n < 64).then_some(n + 1);
+            }
+            b'{' => (b'{', b'}'),
+            _ => (b'[', b']'),
+        };
+        skip_container_loop(
+            block,
+            &mut self.prev_instring,
+            &mut self.prev_escaped,
+            &mut self.lbrace_num,
+            &mut self.rbrace_num,
+            left,
+            right,
+        )
+        .map(|n| n.get() as usize)
+    }
+}
+
+impl ChunkedParser {
+    /// Create a parser with an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the `chunk` into the buffer, and return whether a complete value is buffered.
+    pub fn feed(&mut self, chunk: &[u8]) -> FeedStatus {
+        self.buf.extend_from_slice(chunk);
+        self.scan();
+        self.status()
+    }
+
+    /// Mark the end of the input, the value at the end of the buffer is complete now. The
+    /// incomplete arrays, objects or strings are returned as the EOF errors by
+    /// [`next_value`][ChunkedParser::next_value].
+    pub fn finish(&mut self) -> FeedStatus {
+        self.eof = true;
+        self.status()
+    }
+
+    /// The bytes buffered and not parsed yet, without the whitespaces before the next value.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Parse the next complete value, and remove it from the buffer. Return `None` if no complete
+    /// value is buffered.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the value is invalid, or incomplete after
+    /// [`finish`][ChunkedParser::finish]. The invalid value is still removed from the buffer.
+    pub fn next_value<T: DeserializeOwned>(&mut self) -> Option<Result<T>> {
+        let end = match self.end {
+            Some(end) => end,
+            None if self.eof && !self.buf.is_empty() => self.buf.len(),
+            None => return None,
+        };
+
+        let ret = from_slice(&self.buf[..end]);
+        self.buf.drain(..end);
+        self.scanned = 0;
+        self.end = None;
+        self.blocks = BlockState::default();
+        self.scan();
+        Some(ret)
+    }
+
+    fn status(&self) -> FeedStatus {
+        if self.end.is_some() || (self.eof && !self.buf.is_empty()) {
+            FeedStatus::Complete
+        } else {
+            FeedStatus::NeedMore
+        }
+    }
+
+    // Scan the new bytes until the end of the current value is found.
+    fn scan(&mut self) {
+        if self.end.is_some() {
+            return;
+        }
+
+        if self.scanned == 0 {
+            // drain the whitespaces before the value, so they are not buffered
+            let spaces = self.buf.iter().take_while(|ch| is_whitespace(**ch)).count();
+            self.buf.drain(..spaces);
+            let Some(&first) = self.buf.first() else {
+                return;
+            };
+            self.scanned = 1;
+            match first {
+                b'"' => self.blocks.prev_instring = u64::MAX,
+                // the stray closing bracket is a complete and invalid value
+                b'}' | b']' => {
+                    self.end = Some(1);
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        match self.buf[0] {
+            first @ (b'"' | b'{' | b'[') => self.scan_blocks(first),
+            first => self.scan_scalar(first),
+        }
+    }
+
+    // Scan the string, array or object by the 64-byte blocks.
+    fn scan_blocks(&mut self, first: u8) {
+        while let Some(block) = self.buf.get(self.scanned..self.scanned + 64) {
+            let block: &[u8; 64] = block.try_into().expect("the block is 64 bytes");
+            if let Some(n) = self.blocks.scan(block, first) {
+                self.end = Some(self.scanned + n);
+                return;
+            }
+            self.scanned += 64;
+        }
+
+        // the incomplete block is scanned in a copy of the state, and scanned again with the next
+        // chunk, the padding zeros are not quotes or brackets
+        let mut tail = [0u8; 64];
+        let rest = &self.buf[self.scanned..];
+        tail[..rest.len()].copy_from_slice(rest);
+        let mut blocks = self.blocks;
+        if let Some(n) = blocks.scan(&tail, first) {
+            self.end = Some(self.scanned + n);
+        }
+    }
+
+    // Scan the top-level number or literal, which ends before the delimiter.
+    fn scan_scalar(&mut self, first: u8) {
+        let literal = literal_len(first);
+        for i in self.scanned..self.buf.len() {
+            let ch = self.buf[i];
+            if is_whitespace(ch) || matches!(ch, b'{' | b'}' | b'[' | b']' | b'"' | b',') {
+                self.end = Some(i);
+                return;
+            }
+            if literal == Some(i + 1) {
+                self.end = Some(i + 1);
+                return;
+            }
+        }
+        self.scanned = self.buf.len();
+    }
+}
+
+// The length of `true`, `false` and `null`, which are complete without the delimiter.
+fn literal_len(first: u8) -> Option<usize> {
+    match first {
+        b't' | b'n' => Some(4),
+        b'f' => Some(5),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{json, Value};
+
+    #[test]
+    fn test_chunked_parser() {
+        let input = r#" {"a": "x\"}]\\", "b": [1, {"c": []}]} [true] "s\"" 1.5 -2 true null{}"#;
+        let expect = [
+            json!({"a": "x\"}]\\", "b": [1, {"c": []}]}),
+            json!([true]),
+            json!("s\""),
+            json!(1.5),
+            json!(-2),
+            json!(true),
+            json!(null),
+            json!({}),
+        ];
+
+        // feed the input byte by byte
+        let mut parser = ChunkedParser::new();
+        let mut values = Vec::new();
+        for ch in input.as_bytes() {
+            if parser.feed(&[*ch]) == FeedStatus::Complete {
+                while let Some(v) = parser.next_value::<Value>() {
+                    values.push(v.unwrap());
+                }
+            }
+        }
+        assert_eq!(parser.finish(), FeedStatus::NeedMore);
+        assert!(parser.next_value::<Value>().is_none());
+        assert_eq!(values, expect);
+
+        // feed the input at once
+        let mut parser = ChunkedParser::new();
+        assert_eq!(parser.feed(input.as_bytes()), FeedStatus::Complete);
+        let values: Vec<Value> =
+            std::iter::from_fn(|| parser.next_value().map(Result::unwrap)).collect();
+        assert_eq!(values, expect);
+        assert_eq!(parser.buffered(), b"");
+
+        // the invalid and incomplete values
+        let mut parser = ChunkedParser::new();
+        assert_eq!(parser.feed(b"] [1, 2"), FeedStatus::Complete);
+        assert!(parser.next_value::<Value>().unwrap().is_err());
+        assert!(parser.next_value::<Value>().is_none());
+        assert_eq!(parser.finish(), FeedStatus::Complete);
+        assert!(parser.next_value::<Value>().unwrap().unwrap_err().is_eof());
+        assert!(parser.next_value::<Value>().is_none());
+
+        // the whitespaces between the values are not buffered
+        let mut parser = ChunkedParser::new();
+        assert_eq!(parser.feed(b"[1]\n"), FeedStatus::Complete);
+        assert_eq!(parser.next_value::<Value>().unwrap().unwrap(), json!([1]));
+        assert_eq!(parser.feed(b" \r\n\t "), FeedStatus::NeedMore);
+        assert_eq!(parser.buffered(), b"");
+        assert_eq!(parser.feed(b" [2"), FeedStatus::NeedMore);
+        assert_eq!(parser.buffered(), b"[2");
+        assert_eq!(parser.finish(), FeedStatus::Complete);
+        assert!(parser.next_value::<Value>().unwrap().unwrap_err().is_eof());
+    }
+
+    #[test]
+    fn test_chunked_parser_blocks() {
+        // the values across the 64-byte blocks, with the escapes and brackets in the strings
+        let long = format!(r#""{}\\\"]}}""#, "\\".repeat(40));
+        let values = [
+            json!({"a": long.as_str(), "b": [[vec!["x"; 20]]], "c": {"d": "}]\\"}}),
+            json!([long.as_str(), {"e": vec!["]"; 30]}]),
+            json!("\\\"".repeat(50)),
+            json!([]),
+            json!("x".repeat(63)),
+        ];
+        let input: String = values
+            .iter()
+            .map(|v| format!("{v}{}", " ".repeat(70)))
+            .collect();
+
+        for size in [1, 2, 3, 7, 63, 64, 65, 100, input.len()] {
+            let mut parser = ChunkedParser::new();
+            let mut got = Vec::new();
+            for chunk in input.as_bytes().chunks(size) {
+                parser.feed(chunk);
+                while let Some(v) = parser.next_value::<Value>() {
+                    got.push(v.unwrap());
+                }
+            }
+            assert_eq!(parser.finish(), FeedStatus::NeedMore, "chunk size {size}");
+            assert_eq!(got, values, "chunk size {size}");
+        }
+    }
+}
@@ -1,6 +1,8 @@
+use std::ops::{Range, RangeFrom, RangeFull, RangeTo};
+
 use faststr::FastStr;
 
-use crate::PointerNode;
+use crate::{ArraySlice, PointerNode};
 
 impl From<usize> for PointerNode {
     fn from(value: usize) -> Self {
@@ -14,6 +16,32 @@ impl From<&usize> for PointerNode {
     }
 }
 
+// The unsigned integers larger than `usize::MAX` are saturated, and always out of bounds.
+impl From<u64> for PointerNode {
+    fn from(value: u64) -> Self {
+        PointerNode::Index(usize::try_from(value).unwrap_or(usize::MAX))
+    }
+}
+
+macro_rules! impl_from_signed {
+    ($($t: ty),*) => {
+        $(
+            impl From<$t> for PointerNode {
+                fn from(value: $t) -> Self {
+                    let n = usize::try_from(value.unsigned_abs() as u64).unwrap_or(usize::MAX);
+                    if value < 0 {
+                        PointerNode::FromEnd(n)
+                    } else {
+                        PointerNode::Index(n)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_signed!(i32, i64, isize);
+
 impl From<&str> for PointerNode {
     fn from(value: &str) -> Self {
         PointerNode::Key(FastStr::new(value))
@@ -31,3 +59,38 @@ impl From<&FastStr> for PointerNode {
         PointerNode::Key(value.clone())
     }
 }
+
+impl From<ArraySlice> for PointerNode {
+    fn from(value: ArraySlice) -> Self {
+        PointerNode::Slice(value)
+    }
+}
+
+// The bounds larger than `isize::MAX` are saturated, which are the same out of bounds.
+fn slice_bound(value: usize) -> Option<isize> {
+    Some(isize::try_from(value).unwrap_or(isize::MAX))
+}
+
+impl From<Range<usize>> for PointerNode {
+    fn from(value: Range<usize>) -> Self {
+        ArraySlice::new(slice_bound(value.start), slice_bound(value.end)).into()
+    }
+}
+
+impl From<RangeFrom<usize>> for PointerNode {
+    fn from(value: RangeFrom<usize>) -> Self {
+        ArraySlice::new(slice_bound(value.start), None).into()
+    }
+}
+
+impl From<RangeTo<usize>> for PointerNode {
+    fn from(value: RangeTo<usize>) -> Self {
+        ArraySlice::new(None, slice_bound(value.end)).into()
+    }
+}
+
+impl From<RangeFull> for PointerNode {
+    fn from(_: RangeFull) -> Self {
+        ArraySlice::default().into()
+    }
+}
@@ -1,13 +1,204 @@
+use std::{fmt, str::FromStr};
+
 use faststr::FastStr;
 
+use crate::error::{make_error, Error};
+
 /// Represents a json pointer path. It can be created by [`pointer!`] macro.
 pub type JsonPointer = [PointerNode];
 
 /// Represents a node in a json pointer path.
+///
+/// The negative integers are converted into [`PointerNode::FromEnd`], such as `-1` for the last
+/// element of an array, and the integers larger than `usize::MAX` are saturated to `usize::MAX`
+/// instead of wrapping. Getting by the index `usize::MAX` returns an overflow error, since it is
+/// never in an array.
+///
+/// The enum is non-exhaustive, since more kinds of nodes may be added.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
 pub enum PointerNode {
     Key(FastStr),
     Index(usize),
+    /// The `n`-th element from the end of an array, `FromEnd(1)` is the last element.
+    FromEnd(usize),
+    /// Every element of an array or every value of an object, such as `orders[*]` in JSONPath.
+    /// It is only supported by [`get_many_all`][crate::get_many_all], and matches nothing in the
+    /// other APIs.
+    Wildcard,
+    /// The elements of an array in a slice, such as `0:10` or `::2`. It is only supported as the
+    /// last node of the path by [`get`][crate::get], which returns the elements as a new array,
+    /// and matches nothing in the other APIs.
+    Slice(ArraySlice),
+}
+
+impl PointerNode {
+    /// The last element of an array, the same as `-1` in [`pointer!`].
+    pub const LAST: PointerNode = PointerNode::FromEnd(1);
+}
+
+/// A slice of an array as Python, which is `start:end:step` and each part is optional.
+///
+/// The negative `start` and `end` count from the end of the array, and the bounds out of the array
+/// are clamped, so a slice never fails for an array. The default `step` is `1`, and a negative
+/// `step` selects the elements in the reverse order. The elements after the end of the slice are
+/// not scanned if neither bound counts from the end and the `step` is positive, so taking the
+/// first elements of a large array is cheap.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{get, pointer, ArraySlice};
+///
+/// let json = r#"{"a": [0, 1, 2, 3, 4, 5]}"#;
+/// let first = get(json, &pointer!["a", 0..2]).unwrap();
+/// assert_eq!(first.as_raw_str(), "[0,1]");
+///
+/// let even: ArraySlice = "::2".parse().unwrap();
+/// assert_eq!(
+///     get(json, &pointer!["a", even]).unwrap().as_raw_str(),
+///     "[0,2,4]"
+/// );
+///
+/// let rev = ArraySlice::new(Some(-2), None).with_step(-1);
+/// assert_eq!(
+///     get(json, &pointer!["a", rev]).unwrap().as_raw_str(),
+///     "[4,3,2,1,0]"
+/// );
+///
+/// assert!("1:2:0".parse::<ArraySlice>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArraySlice {
+    start: Option<isize>,
+    end: Option<isize>,
+    step: isize,
+}
+
+impl ArraySlice {
+    /// Create a slice from `start` to `end` with the step of `1`.
+    pub fn new(start: Option<isize>, end: Option<isize>) -> Self {
+        Self {
+            start,
+            end,
+            step: 1,
+        }
+    }
+
+    /// Set the step of the slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `step` is zero.
+    pub fn with_step(mut self, step: isize) -> Self {
+        assert!(step != 0, "the step of slice must not be zero");
+        self.step = step;
+        self
+    }
+
+    /// Return the start of the slice.
+    pub fn start(&self) -> Option<isize> {
+        self.start
+    }
+
+    /// Return the end of the slice, which is excluded.
+    pub fn end(&self) -> Option<isize> {
+        self.end
+    }
+
+    /// Return the step of the slice.
+    pub fn step(&self) -> isize {
+        self.step
+    }
+
+    // Return the `start`, `end` and `step` if they are resolved without the length of array.
+    pub(crate) fn forward(&self) -> Option<(usize, Option<usize>, usize)> {
+        let start = usize::try_from(self.start.unwrap_or(0)).ok()?;
+        let end = match self.end {
+            Some(end) => Some(usize::try_from(end).ok()?),
+            None => None,
+        };
+        let step = usize::try_from(self.step).ok()?;
+        Some((start, end, step))
+    }
+
+    // Resolve the positions of the slice in an array of `len` elements.
+    pub(crate) fn indices(&self, len: usize) -> impl Iterator<Item = usize> {
+        // the length of an array in memory never exceeds `isize::MAX`
+        let len = len as isize;
+        let bound = |i: Option<isize>, default: isize, lo: isize, hi: isize| match i {
+            None => default,
+            Some(i) if i < 0 => (i + len).max(lo),
+            Some(i) => i.min(hi),
+        };
+        let step = self.step;
+        let (start, end) = if step > 0 {
+            (bound(self.start, 0, 0, len), bound(self.end, len, 0, len))
+        } else {
+            (
+                bound(self.start, len - 1, -1, len - 1),
+                bound(self.end, -1, -1, len - 1),
+            )
+        };
+        std::iter::successors(Some(start), move |i| i.checked_add(step))
+            .take_while(move |&i| if step > 0 { i < end } else { i > end })
+            .map(|i| i as usize)
+    }
+}
+
+impl Default for ArraySlice {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+impl fmt::Display for ArraySlice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(start) = self.start {
+            write!(f, "{start}")?;
+        }
+        f.write_str(":")?;
+        if let Some(end) = self.end {
+            write!(f, "{end}")?;
+        }
+        if self.step != 1 {
+            write!(f, ":{}", self.step)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ArraySlice {
+    type Err = Error;
+
+    /// Parse a slice as `start:end` or `start:end:step`, such as `1:`, `-3:` and `::-1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || make_error(format!("invalid array slice `{s}`"));
+        let part = |p: &str| -> Result<Option<isize>, Error> {
+            let p = p.trim();
+            if p.is_empty() {
+                Ok(None)
+            } else {
+                p.parse().map(Some).map_err(|_| invalid())
+            }
+        };
+
+        let mut parts = s.split(':');
+        let (Some(start), Some(end)) = (parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        let step = match (parts.next(), parts.next()) {
+            (None, _) => None,
+            (Some(step), None) => part(step)?,
+            (Some(_), Some(_)) => return Err(invalid()),
+        };
+        let slice = Self::new(part(start)?, part(end)?);
+        match step {
+            Some(0) => Err(make_error(format!("the step of array slice `{s}` is zero"))),
+            Some(step) => Ok(slice.with_step(step)),
+            None => Ok(slice),
+        }
+    }
 }
 
 /// Represents a json pointer path.
@@ -17,7 +208,8 @@ pub enum PointerNode {
 ///
 /// The path can includes both keys or indexes.
 /// - keys: string-like, used to indexing an object.
-/// - indexes: usize-like, used to indexing an array.
+/// - indexes: integers, used to indexing an array. The negative indexes count from the end of the
+///   array, `-1` or [`PointerNode::LAST`] is the last element.
 ///
 /// # Examples
 ///
@@ -42,6 +234,9 @@ pub enum PointerNode {
 /// let got = value.pointer(&path).unwrap();
 ///
 /// assert_eq!(got, 123);
+///
+/// let last = value.pointer(&pointer!["foo", -1, "bar"]).unwrap();
+/// assert_eq!(last, 123);
 /// ```
 #[macro_export]
 macro_rules! pointer {
@@ -62,5 +257,65 @@ mod test {
         let mut pointers = pointer![1, 2, 3, "foo", "bar"].to_vec();
         pointers.push(123.into());
         println!("{:?}", pointers);
+
+        use crate::PointerNode;
+        assert_eq!(
+            pointer![0, -2, usize::MAX][..],
+            [
+                PointerNode::Index(0),
+                PointerNode::FromEnd(2),
+                PointerNode::Index(usize::MAX)
+            ]
+        );
+        assert_eq!(PointerNode::from(i64::MIN), PointerNode::FromEnd(1 << 63));
+        assert_eq!(PointerNode::from(u64::MAX), PointerNode::Index(usize::MAX));
+        assert_eq!(PointerNode::from(-1), PointerNode::LAST);
+    }
+
+    #[test]
+    fn test_array_slice() {
+        use crate::{ArraySlice, PointerNode};
+
+        let indices = |s: &str, len: usize| -> Vec<usize> {
+            s.parse::<ArraySlice>().unwrap().indices(len).collect()
+        };
+        assert_eq!(indices(":", 3), [0, 1, 2]);
+        assert_eq!(indices("1:", 3), [1, 2]);
+        assert_eq!(indices(":2", 3), [0, 1]);
+        assert_eq!(indices("-2:", 3), [1, 2]);
+        assert_eq!(indices(":-1", 3), [0, 1]);
+        assert_eq!(indices("::2", 5), [0, 2, 4]);
+        assert_eq!(indices("1:100:3", 8), [1, 4, 7]);
+        assert_eq!(indices("-100:1", 3), [0]);
+        assert_eq!(indices("2:1", 3), Vec::<usize>::new());
+        assert_eq!(indices("::-1", 3), [2, 1, 0]);
+        assert_eq!(indices("1::-1", 3), [1, 0]);
+        assert_eq!(indices(":0:-1", 3), [2, 1]);
+        assert_eq!(indices("100:-100:-2", 5), [4, 2, 0]);
+        assert_eq!(indices(":", 0), Vec::<usize>::new());
+        assert_eq!(indices("::-1", 0), Vec::<usize>::new());
+
+        for s in [":", "1:", ":-2", "1:5:2", "::-1", "-3:-1:3"] {
+            assert_eq!(s.parse::<ArraySlice>().unwrap().to_string(), s);
+        }
+        assert_eq!(
+            " 1 : 2 : 1 ".parse::<ArraySlice>().unwrap().to_string(),
+            "1:2"
+        );
+        for s in ["", "1", "a:", "1:2:3:4", "::0", "1.5:"] {
+            assert!(s.parse::<ArraySlice>().is_err(), "{s}");
+        }
+
+        assert_eq!(ArraySlice::new(Some(1), None).forward(), Some((1, None, 1)));
+        assert_eq!(ArraySlice::new(Some(-1), None).forward(), None);
+        assert_eq!(ArraySlice::default().with_step(-1).forward(), None);
+        assert_eq!(
+            pointer![1..3, ..2, 3..],
+            [
+                PointerNode::Slice(ArraySlice::new(Some(1), Some(3))),
+                PointerNode::Slice(ArraySlice::new(None, Some(2))),
+                PointerNode::Slice(ArraySlice::new(Some(3), None)),
+            ]
+        );
     }
 }
@@ -4,8 +4,8 @@ use faststr::FastStr;
 
 use crate::index::Index;
 
-/// PointerTree is designed for [`get_many`][`crate::get_many`] and
-/// [`get_many_unchecked`][`crate::get_many_unchecked`].
+/// PointerTree is designed for [`get_many`][`crate::get_many`],
+/// [`get_many_unchecked`][`crate::get_many_unchecked`] and [`get_many_all`][`crate::get_many_all`].
 ///
 /// It is recommended to use `get_many` when you need to get multiple values from json. Instead of
 /// using `get` multiple times.
@@ -39,6 +39,10 @@ use crate::index::Index;
 pub struct PointerTree {
     // the count of path
     size: usize,
+    // whether any path has a wildcard
+    wildcard: bool,
+    // whether any path has a node not supported by the tree, which is not added
+    unsupported: bool,
     // the root of tree
     pub(crate) root: PointerTreeNode,
 }
@@ -50,12 +54,38 @@ impl PointerTree {
     }
 
     /// we build tree and return value according by the order of path.
-    /// Allow the repeated path.
+    ///
+    /// The repeated paths and the overlapping paths are allowed:
+    /// - the repeated paths get the same value, at their own positions.
+    /// - a path which is a prefix of other paths gets the whole raw JSON of the container, and the
+    ///   longer paths still get the values inside it. The container is scanned only once.
+    /// - the paths can index the same value both by keys and by indexes. Since a value is either an
+    ///   object or an array, `get_many` returns an error for them, and `get_first` falls back to
+    ///   the paths matching the value.
+    ///
+    /// The path with [`PointerNode::Wildcard`][crate::PointerNode::Wildcard] matches every
+    /// element of an array or every value of an object, and is only supported by
+    /// [`get_many_all`][crate::get_many_all].
+    ///
+    /// The path with an index counted from the end of array, such as
+    /// [`PointerNode::LAST`][crate::PointerNode::LAST], or an array slice is not supported, since
+    /// the tree is matched in one pass. `get_many`, `get_many_all` and `get_first` return an error
+    /// for the tree with such paths, and [`OwnedLazyValue::get_many`] finds nothing for them.
+    ///
+    /// [`OwnedLazyValue::get_many`]: crate::OwnedLazyValue::get_many
     pub fn add_path<Path: IntoIterator>(&mut self, path: Path)
     where
         Path::Item: Index,
     {
-        self.root.add_path(path, self.size);
+        let path: Vec<Path::Item> = path.into_iter().collect();
+        if path
+            .iter()
+            .any(|p| p.as_index_from_end().is_some() || p.as_slice().is_some())
+        {
+            self.unsupported = true;
+        } else {
+            self.wildcard |= self.root.add_path(path, self.size);
+        }
         self.size += 1;
     }
 
@@ -63,60 +93,57 @@ impl PointerTree {
     pub fn size(&self) -> usize {
         self.size
     }
-}
 
-#[derive(Debug, Default)]
-pub(crate) enum PointerTreeInner {
-    #[default]
-    Empty,
-    Key(MultiKey),
-    Index(MultiIndex),
+    /// Return whether any path has a [`PointerNode::Wildcard`][crate::PointerNode::Wildcard].
+    pub fn has_wildcard(&self) -> bool {
+        self.wildcard
+    }
+
+    // whether any path has an index from the end of array or an array slice
+    pub(crate) fn has_unsupported(&self) -> bool {
+        self.unsupported
+    }
 }
 
 // Note: support the repeat path
 #[derive(Debug, Default)]
 pub(crate) struct PointerTreeNode {
     pub(crate) order: Vec<usize>,
-    pub(crate) children: PointerTreeInner,
+    // a value is either an object or an array, both are kept so that the paths of different
+    // shapes can be added, such as the fallbacks in `get_first`
+    pub(crate) keys: MultiKey,
+    pub(crate) indexes: MultiIndex,
+    // the paths matching every element or value of the container
+    pub(crate) wildcard: Option<Box<PointerTreeNode>>,
 }
 
 impl PointerTreeNode {
-    pub fn add_path<Path: IntoIterator>(&mut self, path: Path, order: usize)
+    // add the path, and return whether it has a wildcard. The unsupported nodes are checked by
+    // `PointerTree::add_path`.
+    pub fn add_path<Path: IntoIterator>(&mut self, path: Path, order: usize) -> bool
     where
         Path::Item: Index,
     {
         let mut cur = self;
+        let mut wildcard = false;
         let iter = path.into_iter();
         for p in iter {
             if let Some(key) = p.as_key() {
-                if matches!(cur.children, PointerTreeInner::Empty) {
-                    cur.children = PointerTreeInner::Key(HashMap::new());
-                }
-                cur = cur.insert_key(key)
+                cur = cur.keys.entry(FastStr::new(key)).or_default();
             } else if let Some(index) = p.as_index() {
-                if matches!(cur.children, PointerTreeInner::Empty) {
-                    cur.children = PointerTreeInner::Index(HashMap::new());
-                }
-                cur = cur.insert_index(index)
+                cur = cur.indexes.entry(index).or_default();
+            } else if p.is_wildcard() {
+                cur = &mut **cur.wildcard.get_or_insert_with(Default::default);
+                wildcard = true;
             }
         }
         cur.order.push(order);
+        wildcard
     }
 
-    fn insert_key(&mut self, key: &str) -> &mut Self {
-        if let PointerTreeInner::Key(mkey) = &mut self.children {
-            mkey.entry(FastStr::new(key)).or_insert(Self::default())
-        } else {
-            unreachable!()
-        }
-    }
-
-    fn insert_index(&mut self, idx: usize) -> &mut Self {
-        if let PointerTreeInner::Index(midx) = &mut self.children {
-            midx.entry(idx).or_insert(Self::default())
-        } else {
-            unreachable!()
-        }
+    // whether the node has no descendant paths
+    pub(crate) fn is_leaf(&self) -> bool {
+        self.keys.is_empty() && self.indexes.is_empty() && self.wildcard.is_none()
     }
 }
 
@@ -128,7 +155,7 @@ pub(crate) type MultiIndex = HashMap<usize, PointerTreeNode>;
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::pointer;
+    use crate::{pointer, PointerNode};
 
     #[test]
     fn test_tree() {
@@ -140,7 +167,27 @@ mod test {
         tree.add_path(pointer!["a"].iter());
         tree.add_path(pointer!["b", 2].iter());
         tree.add_path(pointer![].iter());
-        assert_eq!(tree.size(), 7);
+        tree.add_path(pointer!["b", "c"].iter());
+        assert_eq!(tree.size(), 8);
+        assert_eq!(tree.root.keys["b"].indexes.len(), 1);
+        assert_eq!(tree.root.keys["b"].keys.len(), 1);
+        assert!(!tree.has_wildcard());
         println!("tree is {:#?}", tree);
+
+        tree.add_path(pointer!["b", PointerNode::Wildcard, "id"].iter());
+        tree.add_path(pointer!["b", PointerNode::Wildcard].iter());
+        assert!(tree.has_wildcard());
+        let any = tree.root.keys["b"].wildcard.as_ref().unwrap();
+        assert_eq!(any.order, [9]);
+        assert_eq!(any.keys["id"].order, [8]);
+        assert!(!tree.has_unsupported());
+
+        // the unsupported paths are counted but not added
+        tree.add_path(pointer!["c", PointerNode::LAST].iter());
+        tree.add_path(pointer!["d", 0..2].iter());
+        assert!(tree.has_unsupported());
+        assert_eq!(tree.size(), 12);
+        assert!(!tree.root.keys.contains_key("c"));
+        assert!(!tree.root.keys.contains_key("d"));
     }
 }
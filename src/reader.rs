@@ -1,13 +1,13 @@
-use std::{marker::PhantomData, pin::Pin, ptr::NonNull};
+use std::{borrow::Cow, cell::Cell, io, marker::PhantomData, pin::Pin, ptr::NonNull};
 
 use faststr::FastStr;
 
 use crate::{
-    error::invalid_utf8,
+    error::{invalid_utf8, Error, ErrorCode},
     input::JsonSlice,
     parser::as_str,
     util::{private::Sealed, utf8::from_utf8},
-    JsonInput, Result,
+    JsonInput, Result, Value,
 };
 
 pub(crate) struct Position {
@@ -35,6 +35,51 @@ impl Position {
     }
 }
 
+/// The policy of handling the invalid UTF-8 in the JSON input, which is used in
+/// [`Deserializer::utf8_policy`][crate::Deserializer::utf8_policy] and
+/// [`get_with_utf8_policy`][crate::get_with_utf8_policy].
+///
+/// The `&str` and `&FastStr` inputs are always valid UTF-8, so the policy only matters for the
+/// bytes inputs, such as `&[u8]` and `&Bytes`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Utf8Policy {
+    /// Return an error if the JSON has invalid UTF-8. It is the default policy.
+    #[default]
+    Validate,
+    /// Replace the invalid UTF-8 with `\uFFFD` (displayed as �), as the `utf8_lossy` feature.
+    Lossy,
+    /// Skip the UTF-8 validation, which is created by the unsafe [`Utf8Policy::trust`].
+    Trust(TrustedUtf8),
+}
+
+/// The marker of [`Utf8Policy::Trust`], which can only be created by [`Utf8Policy::trust`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustedUtf8(());
+
+impl Utf8Policy {
+    /// Create the policy that skips the UTF-8 validation, as the unchecked APIs such as
+    /// [`from_slice_unchecked`][crate::from_slice_unchecked].
+    ///
+    /// # Safety
+    ///
+    /// The JSON inputs which are parsed with this policy must be valid UTF-8, otherwise the
+    /// parsed strings may be invalid UTF-8.
+    pub const unsafe fn trust() -> Self {
+        Utf8Policy::Trust(TrustedUtf8(()))
+    }
+}
+
+// Check the UTF-8 of the whole JSON with the policy. Return the JSON with the invalid UTF-8
+// replaced, if the policy is `Lossy`.
+pub(crate) fn check_utf8(json: &[u8], policy: Utf8Policy) -> Result<Cow<'_, str>> {
+    match policy {
+        Utf8Policy::Validate => from_utf8(json).map(Cow::Borrowed),
+        Utf8Policy::Lossy => Ok(String::from_utf8_lossy(json)),
+        // SAFETY: the caller of `Utf8Policy::trust` guarantees the JSON is valid UTF-8
+        Utf8Policy::Trust(_) => Ok(Cow::Borrowed(as_str(json))),
+    }
+}
+
 /// Trait is used by the deserializer for iterating over input. And it is sealed and cannot be
 /// implemented for types outside of sonic_rs.
 #[doc(hidden)]
@@ -70,7 +115,16 @@ pub trait Reader<'de>: Sealed {
 
     fn check_invalid_utf8(&mut self);
 
+    // skip the UTF-8 validation, the input is trusted as valid UTF-8
+    fn trust_utf8(&mut self);
+
     fn slice_ref(&self, subset: &'de [u8]) -> JsonSlice<'de>;
+
+    // the defaults of the missing struct fields, which are borrowed as long as the input, see
+    // `Deserializer::with_defaults`
+    fn defaults(&self) -> Option<&'de Value>;
+
+    fn set_defaults(&mut self, defaults: &'de Value);
 }
 
 enum PinnedInput<'a> {
@@ -132,10 +186,15 @@ pub struct Read<'a> {
     input: PinnedInput<'a>,
     slice: NonNull<[u8]>,
     pub(crate) index: usize,
-    // next invalid utf8 position, if not found, will be usize::MAX
-    next_invalid_utf8: usize,
+    // next invalid utf8 position, if not found, will be usize::MAX. The input is validated when
+    // the position is used at first, so that the policy can be set before validating.
+    next_invalid_utf8: Cell<usize>,
+    defaults: Option<&'a Value>,
 }
 
+// the input needs to be validated
+const UTF8_UNCHECKED: usize = usize::MAX - 1;
+
 impl<'a> Read<'a> {
     /// Make a `Read` from string/bytes-like JSON input.
     pub fn from<I: JsonInput<'a>>(input: I) -> Self {
@@ -152,21 +211,18 @@ impl<'a> Read<'a> {
         // #safety: we pinned the input json
         let slice = unsafe { input.as_ptr() };
 
-        // validate the utf-8 at first for slice
-        let next_invalid_utf8 = validate_utf8
-            .then(|| {
-                from_utf8(unsafe { slice.as_ref() })
-                    .err()
-                    .map(|e| e.offset())
-            })
-            .flatten()
-            .unwrap_or(usize::MAX);
+        let next_invalid_utf8 = if validate_utf8 {
+            UTF8_UNCHECKED
+        } else {
+            usize::MAX
+        };
 
         Self {
             input,
             slice,
             index: 0,
-            next_invalid_utf8,
+            next_invalid_utf8: Cell::new(next_invalid_utf8),
+            defaults: None,
         }
     }
 
@@ -264,22 +320,391 @@ impl<'a> Reader<'a> for Read<'a> {
 
     #[inline(always)]
     fn check_utf8_final(&self) -> Result<()> {
-        if self.next_invalid_utf8 == usize::MAX {
-            Ok(())
-        } else {
-            Err(invalid_utf8(self.slice(), self.next_invalid_utf8))
+        match self.next_invalid_utf8() {
+            usize::MAX => Ok(()),
+            invalid => Err(invalid_utf8(self.slice(), invalid)),
         }
     }
 
     fn check_invalid_utf8(&mut self) {
-        self.next_invalid_utf8 = match from_utf8(&self.slice()[self.index..]) {
+        let next = match from_utf8(&self.slice()[self.index..]) {
             Ok(_) => usize::MAX,
             Err(e) => self.index + e.offset(),
         };
+        self.next_invalid_utf8.set(next);
     }
 
+    #[inline(always)]
     fn next_invalid_utf8(&self) -> usize {
-        self.next_invalid_utf8
+        let next = self.next_invalid_utf8.get();
+        if next != UTF8_UNCHECKED {
+            return next;
+        }
+        // validate the utf-8 of the whole input at first
+        let next = from_utf8(self.slice())
+            .err()
+            .map_or(usize::MAX, |e| e.offset());
+        self.next_invalid_utf8.set(next);
+        next
+    }
+
+    #[inline(always)]
+    fn trust_utf8(&mut self) {
+        self.next_invalid_utf8.set(usize::MAX);
+    }
+
+    #[inline(always)]
+    fn defaults(&self) -> Option<&'a Value> {
+        self.defaults
+    }
+
+    fn set_defaults(&mut self, defaults: &'a Value) {
+        self.defaults = Some(defaults);
+    }
+}
+
+/// The byte order of the UTF-16 input, which is used in [`Utf16Text::with_endian`] and
+/// [`Utf16Read::with_endian`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Utf16Endian {
+    /// The little endian, such as the text produced by Windows.
+    Little,
+    /// The big endian.
+    Big,
+}
+
+/// JSON input of UTF-16 text, which is transcoded into an owned UTF-8 copy up front when created.
+///
+/// It is not a streaming reader: the whole input is transcoded before parsing, so it takes the
+/// memory of the UTF-8 text besides the input. Use [`Utf16Read`] to transcode a reader on the fly
+/// instead. The parsing APIs then work on the UTF-8 copy as usual, such as
+/// [`Deserializer::from_json`][crate::Deserializer::from_json] with `&Utf16Text`,
+/// or [`from_slice`][crate::from_slice] with [`as_bytes`][Utf16Text::as_bytes].
+///
+/// The UTF-8 text is owned by `Utf16Text`, so the borrowed strings and the
+/// [`LazyValue`][crate::LazyValue]s of `&Utf16Text` share the ownership of it as `&FastStr`. The
+/// byte order mark, if any, is not included in the UTF-8 text.
+///
+/// The errors of the invalid UTF-16 input, such as the unpaired surrogates, only record the byte
+/// offset in the UTF-16 input, without the line and column.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{Deserializer, Read, Utf16Endian, Utf16Text};
+///
+/// // UTF-16 LE with BOM, such as the files written by Windows
+/// let mut input = vec![0xFF, 0xFE];
+/// input.extend(r#"{"name": "中文"}"#.encode_utf16().flat_map(u16::to_le_bytes));
+///
+/// let json = Utf16Text::new(&input).unwrap();
+/// assert_eq!(json.as_str(), r#"{"name": "中文"}"#);
+///
+/// let mut de = Deserializer::from_json(&json);
+/// let v: sonic_rs::Value = serde::Deserialize::deserialize(&mut de).unwrap();
+/// assert_eq!(v["name"], "中文");
+///
+/// // the byte order without BOM
+/// let input: Vec<u8> = "[1, 2]".encode_utf16().flat_map(u16::to_be_bytes).collect();
+/// let json = Utf16Text::with_endian(&input, Utf16Endian::Big).unwrap();
+/// let v: Vec<u8> = sonic_rs::from_slice(json.as_bytes()).unwrap();
+/// assert_eq!(v, [1, 2]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Utf16Text {
+    pub(crate) json: FastStr,
+}
+
+impl Utf16Text {
+    /// Transcode the UTF-16 `input` into UTF-8, detecting the byte order by the BOM.
+    ///
+    /// Without the BOM, the byte order is detected from the first character, which is ASCII in
+    /// JSON, and the little endian is the default.
+    pub fn new(input: &[u8]) -> Result<Self> {
+        let (endian, start) = detect_utf16(input);
+        Self::transcode(input, start, endian)
+    }
+
+    /// Transcode the UTF-16 `input` of the byte order `endian` into UTF-8. The BOM of the same
+    /// byte order is skipped.
+    pub fn with_endian(input: &[u8], endian: Utf16Endian) -> Result<Self> {
+        Self::transcode(input, utf16_bom_len(input, endian), endian)
+    }
+
+    fn transcode(input: &[u8], start: usize, endian: Utf16Endian) -> Result<Self> {
+        let mut json = Vec::with_capacity(input.len() / 2);
+        let used = match transcode_utf16(&input[start..], endian, &mut json) {
+            Ok(used) => start + used,
+            Err(at) => {
+                return Err(Error::lightweight(
+                    ErrorCode::InvalidSurrogateUnicodeCodePoint,
+                    start + at,
+                ))
+            }
+        };
+        match input.len() - used {
+            0 => {}
+            // the high surrogate at the end
+            2 => {
+                return Err(Error::lightweight(
+                    ErrorCode::InvalidSurrogateUnicodeCodePoint,
+                    used,
+                ))
+            }
+            _ => {
+                return Err(Error::lightweight(
+                    ErrorCode::EofWhileParsing,
+                    input.len() - 1,
+                ))
+            }
+        }
+        // SAFETY: the transcoded text is valid UTF-8
+        let json = unsafe { String::from_utf8_unchecked(json) };
+        Ok(Self {
+            json: FastStr::new(json),
+        })
+    }
+
+    /// Return the transcoded UTF-8 JSON text.
+    pub fn as_str(&self) -> &str {
+        self.json.as_str()
+    }
+
+    /// Return the transcoded UTF-8 JSON text as bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.json.as_bytes()
+    }
+
+    /// Return the transcoded UTF-8 JSON text as a [`FastStr`].
+    pub fn into_faststr(self) -> FastStr {
+        self.json
+    }
+}
+
+// Detect the byte order by the BOM or the first character, and return it with the length of BOM.
+fn detect_utf16(input: &[u8]) -> (Utf16Endian, usize) {
+    match input {
+        [0xFF, 0xFE, ..] => (Utf16Endian::Little, 2),
+        [0xFE, 0xFF, ..] => (Utf16Endian::Big, 2),
+        [0, b, ..] if *b != 0 => (Utf16Endian::Big, 0),
+        _ => (Utf16Endian::Little, 0),
+    }
+}
+
+// Return the length of the BOM of the byte order at the start of the input.
+fn utf16_bom_len(input: &[u8], endian: Utf16Endian) -> usize {
+    let bom: &[u8] = match endian {
+        Utf16Endian::Little => &[0xFF, 0xFE],
+        Utf16Endian::Big => &[0xFE, 0xFF],
+    };
+    if input.starts_with(bom) {
+        2
+    } else {
+        0
+    }
+}
+
+// Transcode the UTF-16 `input` into UTF-8 appended to `out`, and return the number of the bytes
+// used. The incomplete unit or surrogate pair at the end is not used. The ASCII runs, which are
+// most of the JSON text, are transcoded by 8 units at once. Return the offset of the invalid
+// surrogate as the error.
+fn transcode_utf16(
+    input: &[u8],
+    endian: Utf16Endian,
+    out: &mut Vec<u8>,
+) -> std::result::Result<usize, usize> {
+    // the mask of the non-ASCII bits of 8 units loaded as a little endian u128, and the index of
+    // the low byte in a unit
+    let (non_ascii, low) = match endian {
+        Utf16Endian::Little => (0xFF80_u128 * 0x0001_0001_0001_0001_0001_0001_0001_0001, 0),
+        Utf16Endian::Big => (0x80FF_u128 * 0x0001_0001_0001_0001_0001_0001_0001_0001, 1),
+    };
+    let unit = |i: usize| match endian {
+        Utf16Endian::Little => u16::from_le_bytes([input[i], input[i + 1]]),
+        Utf16Endian::Big => u16::from_be_bytes([input[i], input[i + 1]]),
+    };
+
+    out.reserve(input.len() / 2);
+    let mut i = 0;
+    while i + 2 <= input.len() {
+        if let Some(block) = input.get(i..i + 16) {
+            let block = u128::from_le_bytes(block.try_into().unwrap());
+            if block & non_ascii == 0 {
+                out.extend((0..8).map(|k| input[i + k * 2 + low]));
+                i += 16;
+                continue;
+            }
+        }
+
+        let cp = match unit(i) {
+            u @ 0xD800..=0xDBFF => {
+                if i + 4 > input.len() {
+                    break;
+                }
+                match unit(i + 2) {
+                    l @ 0xDC00..=0xDFFF => {
+                        0x10000 + ((u as u32 - 0xD800) << 10) + (l as u32 - 0xDC00)
+                    }
+                    _ => return Err(i),
+                }
+            }
+            0xDC00..=0xDFFF => return Err(i),
+            u => u as u32,
+        };
+        // SAFETY: the surrogates are excluded above
+        let ch = unsafe { char::from_u32_unchecked(cp) };
+        out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+        i += ch.len_utf16() * 2;
+    }
+    Ok(i)
+}
+
+/// A reader of UTF-16 JSON text, which transcodes the UTF-16 bytes read from the inner reader into
+/// UTF-8 on the fly. It is used with the reader APIs, such as [`from_reader`][crate::from_reader],
+/// so that the UTF-16 input is not copied up front as [`Utf16Text`].
+///
+/// The byte order is detected as [`Utf16Text::new`], or given by
+/// [`with_endian`][Utf16Read::with_endian]. The BOM is not included in the UTF-8 text. The invalid
+/// surrogates and the odd length of the input are returned as the IO errors of the
+/// [`io::ErrorKind::InvalidData`] kind, with the byte offset in the UTF-16 input.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{Utf16Endian, Utf16Read, Value};
+///
+/// // UTF-16 LE with BOM, such as the files written by Windows
+/// let mut input = vec![0xFF, 0xFE];
+/// input.extend(r#"{"name": "中文"}"#.encode_utf16().flat_map(u16::to_le_bytes));
+///
+/// let v: Value = sonic_rs::from_reader(Utf16Read::new(input.as_slice())).unwrap();
+/// assert_eq!(v["name"], "中文");
+///
+/// // the byte order without BOM
+/// let input: Vec<u8> = "[1, 2]".encode_utf16().flat_map(u16::to_be_bytes).collect();
+/// let read = Utf16Read::with_endian(input.as_slice(), Utf16Endian::Big);
+/// let v: Vec<u8> = sonic_rs::from_reader(read).unwrap();
+/// assert_eq!(v, [1, 2]);
+/// ```
+pub struct Utf16Read<R> {
+    reader: R,
+    // `None` if the byte order is detected from the input
+    endian: Option<Utf16Endian>,
+    started: bool,
+    // the UTF-16 bytes read and not transcoded yet, such as a split surrogate pair
+    input: Vec<u8>,
+    // the offset of `input` in the UTF-16 input
+    offset: usize,
+    out: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: io::Read> Utf16Read<R> {
+    // the size of the UTF-16 bytes read from the inner reader at once
+    const BLOCK_SIZE: usize = 16 * 1024;
+
+    /// Create a reader of the UTF-16 `reader`, detecting the byte order by the BOM.
+    pub fn new(reader: R) -> Self {
+        Self::with(reader, None)
+    }
+
+    /// Create a reader of the UTF-16 `reader` of the byte order `endian`. The BOM of the same byte
+    /// order is skipped.
+    pub fn with_endian(reader: R, endian: Utf16Endian) -> Self {
+        Self::with(reader, Some(endian))
+    }
+
+    fn with(reader: R, endian: Option<Utf16Endian>) -> Self {
+        Self {
+            reader,
+            endian,
+            started: false,
+            input: Vec::new(),
+            offset: 0,
+            out: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Return the inner reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // Read and transcode the next block into `out`, and return false at the end of the input.
+    fn fill(&mut self) -> io::Result<bool> {
+        let kept = self.input.len();
+        self.input.resize(kept + Self::BLOCK_SIZE, 0);
+        let n = loop {
+            match self.reader.read(&mut self.input[kept..]) {
+                Ok(n) => break n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.input.truncate(kept);
+                    return Err(e);
+                }
+            }
+        };
+        self.input.truncate(kept + n);
+        if n == 0 {
+            return match self.input.len() {
+                0 => Ok(false),
+                1 | 3 => {
+                    let at = self.input.len() - 1;
+                    Err(self.invalid("the odd length of the UTF-16 input", at))
+                }
+                _ => Err(self.invalid("an invalid UTF-16 surrogate", 0)),
+            };
+        }
+
+        let mut start = 0;
+        if !self.started {
+            // wait for the BOM or the first character
+            if self.input.len() < 2 {
+                return Ok(true);
+            }
+            let (endian, bom) = match self.endian {
+                Some(endian) => (endian, utf16_bom_len(&self.input, endian)),
+                None => detect_utf16(&self.input),
+            };
+            self.endian = Some(endian);
+            self.started = true;
+            start = bom;
+        }
+
+        let endian = self.endian.unwrap_or(Utf16Endian::Little);
+        self.out.clear();
+        self.pos = 0;
+        match transcode_utf16(&self.input[start..], endian, &mut self.out) {
+            Ok(used) => {
+                self.input.drain(..start + used);
+                self.offset += start + used;
+                Ok(true)
+            }
+            Err(at) => Err(self.invalid("an invalid UTF-16 surrogate", start + at)),
+        }
+    }
+
+    fn invalid(&self, msg: &str, at: usize) -> io::Error {
+        let offset = self.offset + at;
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{msg} at byte {offset}"),
+        )
+    }
+}
+
+impl<R: io::Read> io::Read for Utf16Read<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.out.len() {
+            if !self.fill()? {
+                return Ok(0);
+            }
+        }
+        let n = buf.len().min(self.out.len() - self.pos);
+        buf[..n].copy_from_slice(&self.out[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
     }
 }
 
@@ -287,6 +712,7 @@ pub(crate) struct PaddedSliceRead<'a> {
     base: NonNull<u8>,
     cur: NonNull<u8>,
     len: usize,
+    defaults: Option<&'a Value>,
     _life: PhantomData<&'a mut [u8]>,
 }
 
@@ -298,6 +724,7 @@ impl<'a> PaddedSliceRead<'a> {
             base,
             cur: base,
             len: slice.len() - Self::PADDING_SIZE,
+            defaults: None,
             _life: PhantomData,
         }
     }
@@ -391,6 +818,20 @@ impl<'a> Reader<'a> for PaddedSliceRead<'a> {
         /* need to nothing here */
     }
 
+    #[inline(always)]
+    fn trust_utf8(&mut self) {
+        /* need to nothing here */
+    }
+
+    #[inline(always)]
+    fn defaults(&self) -> Option<&'a Value> {
+        self.defaults
+    }
+
+    fn set_defaults(&mut self, defaults: &'a Value) {
+        self.defaults = Some(defaults);
+    }
+
     #[inline(always)]
     fn next_invalid_utf8(&self) -> usize {
         usize::MAX
@@ -462,5 +903,83 @@ mod test {
         test_deserialize_reader!(&b);
         test_deserialize_reader!(&f);
         test_deserialize_reader!(&s);
+        let u = Utf16Text::new(&[b'1', 0, b'2', 0, b'3', 0]).unwrap();
+        test_deserialize_reader!(&u);
+    }
+
+    // return at most `max` bytes in each read
+    struct Chunks<'a>(&'a [u8], usize);
+
+    impl io::Read for Chunks<'_> {
+        fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+            let n = out.len().min(self.1).min(self.0.len());
+            out[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_utf16_read() {
+        fn encode(s: &str, endian: Utf16Endian, bom: bool) -> Vec<u8> {
+            let to_bytes = match endian {
+                Utf16Endian::Little => u16::to_le_bytes,
+                Utf16Endian::Big => u16::to_be_bytes,
+            };
+            let bom = bom.then_some('\u{feff}');
+            bom.into_iter()
+                .collect::<String>()
+                .encode_utf16()
+                .chain(s.encode_utf16())
+                .flat_map(to_bytes)
+                .collect()
+        }
+
+        let json = r#"{"a": ["x\n", "中文", "😀"], "b": null}"#;
+        for endian in [Utf16Endian::Little, Utf16Endian::Big] {
+            for bom in [true, false] {
+                let input = encode(json, endian, bom);
+                assert_eq!(Utf16Text::new(&input).unwrap().as_str(), json);
+                let read = Utf16Text::with_endian(&input, endian).unwrap();
+                assert_eq!(read.as_bytes(), json.as_bytes());
+
+                let lv = crate::get(&read, &crate::pointer!["a", 2]).unwrap();
+                assert_eq!(lv.as_raw_str(), r#""😀""#);
+
+                // the surrogate pairs and the units split between the reads
+                for max in [1, 3, usize::MAX] {
+                    let mut got = String::new();
+                    let mut read = Utf16Read::new(Chunks(&input, max));
+                    io::Read::read_to_string(&mut read, &mut got).unwrap();
+                    assert_eq!(got, json, "{endian:?} {bom} {max}");
+                    let mut got = String::new();
+                    let mut read = Utf16Read::with_endian(Chunks(&input, max), endian);
+                    io::Read::read_to_string(&mut read, &mut got).unwrap();
+                    assert_eq!(got, json, "{endian:?} {bom} {max}");
+                }
+            }
+        }
+        let long = format!("[{}]", [r#""abcdefgh", "中文😀""#; 1000].join(", "));
+        let input = encode(&long, Utf16Endian::Big, false);
+        let v: crate::Value = crate::from_reader(Utf16Read::new(input.as_slice())).unwrap();
+        assert_eq!(v, crate::from_str::<crate::Value>(&long).unwrap());
+        assert_eq!(Utf16Text::new(&[]).unwrap().as_str(), "");
+
+        // the errors
+        let mut input = encode("[1]", Utf16Endian::Little, false);
+        input.push(0);
+        assert!(Utf16Text::new(&input).is_err());
+        let input = [b'"', 0, 0x00, 0xD8, b'"', 0];
+        let err = Utf16Text::new(&input).unwrap_err();
+        assert!(err.is_syntax());
+        assert_eq!(err.line(), 0);
+        let mut got = String::new();
+        let err = io::Read::read_to_string(&mut Utf16Read::new(&input[..]), &mut got).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("at byte 2"), "{err}");
+        for input in [&[b'1', 0, 0][..], &[b'1', 0, 0x00, 0xD8]] {
+            let err = io::Read::read_to_end(&mut Utf16Read::new(input), &mut Vec::new());
+            assert_eq!(err.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
     }
 }
@@ -0,0 +1,1159 @@
+//! Validate the JSON values against a JSON Schema.
+//!
+//! [`Schema::compile`] compiles a subset of the JSON Schema draft 2020-12 into a validator. The
+//! validator checks a parsed [`Value`], a [`LazyValue`] or the raw JSON text, and returns the
+//! [`ValidationError`]s with the JSON pointers of the invalid values.
+//!
+//! The supported keywords are:
+//!
+//! - `type`, `enum` and `const`.
+//! - `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum` and `multipleOf`.
+//! - `minLength` and `maxLength`, which count the Unicode code points.
+//! - `items`, `prefixItems`, `contains`, `minContains`, `maxContains`, `minItems`, `maxItems` and
+//!   `uniqueItems`.
+//! - `properties`, `required`, `dependentRequired`, `additionalProperties`, `propertyNames`,
+//!   `minProperties` and `maxProperties`.
+//! - `allOf`, `anyOf`, `oneOf`, `not`, `if`, `then` and `else`.
+//! - `$ref` to a JSON pointer in the same schema, such as `#/$defs/node`, and `$defs`.
+//!
+//! The annotations such as `title` and `format` and the unknown keywords are ignored. The
+//! keywords that can not be checked by this subset, such as `pattern` and
+//! `unevaluatedProperties`, are rejected when compiling, so that a schema is never partially
+//! checked. The `$ref` cycles which apply the schemas to the same value endlessly, such as
+//! `{"$ref": "#"}`, are rejected when compiling.
+//!
+//! The values nested deeper than the depth limit of the parser are reported with the `depth`
+//! keyword, and their nested values are not checked. Requires the `schema` feature.
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use faststr::FastStr;
+
+use crate::{
+    error::{make_error, pointer_string, Error, Result},
+    lazyvalue::LazyValue,
+    serde::de::MAX_ALLOWED_DEPTH,
+    JsonContainerTrait, JsonNumberTrait, JsonPointer, JsonType, JsonValueTrait, PointerNode, Value,
+};
+
+const NULL: u8 = 1 << 0;
+const BOOLEAN: u8 = 1 << 1;
+const OBJECT: u8 = 1 << 2;
+const ARRAY: u8 = 1 << 3;
+const NUMBER: u8 = 1 << 4;
+const INTEGER: u8 = 1 << 5;
+const STRING: u8 = 1 << 6;
+
+const TYPE_NAMES: [(u8, &str); 7] = [
+    (NULL, "null"),
+    (BOOLEAN, "boolean"),
+    (OBJECT, "object"),
+    (ARRAY, "array"),
+    (NUMBER, "number"),
+    (INTEGER, "integer"),
+    (STRING, "string"),
+];
+
+/// A compiled JSON Schema.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{json, pointer, schema::Schema};
+///
+/// let schema = Schema::compile(&json!({
+///     "type": "object",
+///     "properties": {
+///         "id": {"type": "integer", "minimum": 1},
+///         "tags": {"type": "array", "items": {"type": "string"}}
+///     },
+///     "required": ["id"]
+/// }))
+/// .unwrap();
+///
+/// assert!(schema.is_valid(&json!({"id": 1, "tags": ["a"]})));
+///
+/// let errors = schema.validate(&json!({"id": 0, "tags": ["a", 2]}));
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!(errors[0].pointer(), &pointer!["id"]);
+/// assert_eq!(errors[0].keyword(), "minimum");
+/// assert_eq!(errors[1].pointer(), &pointer!["tags", 1]);
+/// assert_eq!(errors[1].keyword(), "type");
+///
+/// // validate the JSON text without building a DOM
+/// let errors = schema.validate_slice(br#"{"tags": []}"#).unwrap();
+/// assert_eq!(errors[0].to_string(), r#"the required property "id" is missing"#);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Schema {
+    // the subschemas, the root schema is the first
+    nodes: Vec<Node>,
+}
+
+/// A value which does not match the schema.
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    pointer: Vec<PointerNode>,
+    keyword: &'static str,
+    message: String,
+}
+
+impl ValidationError {
+    /// The JSON pointer of the invalid value in the validated value.
+    pub fn pointer(&self) -> &JsonPointer {
+        &self.pointer
+    }
+
+    /// The schema keyword which is not matched, such as `type` and `required`, or `depth` if the
+    /// value is nested too deeply to check.
+    pub fn keyword(&self) -> &str {
+        self.keyword
+    }
+
+    /// The description of the error, without the pointer.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.pointer.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{} at {}", self.message, pointer_string(&self.pointer))
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+#[derive(Debug, Clone)]
+enum Node {
+    // the `true` and `false` schemas
+    Bool(bool),
+    Rules(Box<Rules>),
+}
+
+#[derive(Debug, Clone, Default)]
+struct Rules {
+    reference: Option<usize>,
+    // the bits of the allowed types, zero if any type is allowed
+    types: u8,
+    enumeration: Option<Vec<Value>>,
+    constant: Option<Value>,
+
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    exclusive_minimum: Option<f64>,
+    exclusive_maximum: Option<f64>,
+    multiple_of: Option<f64>,
+
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+
+    prefix_items: Vec<usize>,
+    items: Option<usize>,
+    contains: Option<usize>,
+    min_contains: Option<usize>,
+    max_contains: Option<usize>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+    unique_items: bool,
+
+    properties: HashMap<String, usize>,
+    required: Vec<String>,
+    dependent_required: Vec<(String, Vec<String>)>,
+    additional_properties: Option<usize>,
+    property_names: Option<usize>,
+    min_properties: Option<usize>,
+    max_properties: Option<usize>,
+
+    all_of: Vec<usize>,
+    any_of: Vec<usize>,
+    one_of: Vec<usize>,
+    not: Option<usize>,
+    condition: Option<usize>,
+    then: Option<usize>,
+    otherwise: Option<usize>,
+}
+
+impl Schema {
+    /// Compile the JSON Schema.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the schema is invalid, uses an unsupported keyword, or `$ref` points to
+    /// a missing subschema or makes a cycle applied to the same value.
+    pub fn compile(schema: &Value) -> Result<Self> {
+        let mut compiler = Compiler {
+            root: schema,
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+        };
+        compiler.compile(schema, "")?;
+        compiler.check_cycles()?;
+        Ok(Self {
+            nodes: compiler.nodes,
+        })
+    }
+
+    /// Validate the value, and return all the errors. The value is valid if no error is returned.
+    pub fn validate(&self, value: &Value) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        self.check(0, &value, &mut Vec::new(), &mut errors)
+            .expect("checking a Value never fails");
+        errors
+    }
+
+    /// Return whether the value is valid.
+    pub fn is_valid(&self, value: &Value) -> bool {
+        self.validate(value).is_empty()
+    }
+
+    /// Validate the lazy value, and return all the errors. The nested values are iterated from
+    /// the raw JSON text by skipping, without building a DOM.
+    ///
+    /// It is not a single pass over the JSON text: each array and object is skipped again when
+    /// its elements are checked, the values checked by `enum`, `const` and `uniqueItems` are
+    /// parsed, and the subschemas of `anyOf`, `oneOf`, `not` and `if` check the value again.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the raw JSON text is invalid, such as a `LazyValue` from the unchecked
+    /// APIs.
+    pub fn validate_lazy(&self, value: &LazyValue<'_>) -> Result<Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        self.check(0, value, &mut Vec::new(), &mut errors)?;
+        Ok(errors)
+    }
+
+    /// Validate the JSON text, and return all the errors, the same as
+    /// [`validate_lazy`][Schema::validate_lazy].
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the JSON text is invalid.
+    pub fn validate_slice(&self, json: &[u8]) -> Result<Vec<ValidationError>> {
+        let value: LazyValue = crate::from_slice(json)?;
+        self.validate_lazy(&value)
+    }
+
+    fn matches<I: Instance>(
+        &self,
+        id: usize,
+        inst: &I,
+        path: &mut Vec<PointerNode>,
+    ) -> Result<bool> {
+        let mut errors = Vec::new();
+        self.check(id, inst, path, &mut errors)?;
+        Ok(errors.is_empty())
+    }
+
+    fn check<I: Instance>(
+        &self,
+        id: usize,
+        inst: &I,
+        path: &mut Vec<PointerNode>,
+        errors: &mut Vec<ValidationError>,
+    ) -> Result<()> {
+        let rules = match &self.nodes[id] {
+            Node::Bool(true) => return Ok(()),
+            Node::Bool(false) => {
+                fail(errors, path, "false", "no value is allowed".to_string());
+                return Ok(());
+            }
+            Node::Rules(rules) => rules,
+        };
+
+        if let Some(reference) = rules.reference {
+            self.check(reference, inst, path, errors)?;
+        }
+
+        let bits = type_bits(inst);
+        if rules.types != 0 && rules.types & bits == 0 {
+            let msg = format!(
+                "expected {}, found {}",
+                type_names(rules.types),
+                type_names(bits & !INTEGER)
+            );
+            fail(errors, path, "type", msg);
+        }
+
+        if rules.enumeration.is_some() || rules.constant.is_some() {
+            let value = inst.to_value()?;
+            if let Some(values) = &rules.enumeration {
+                if !values.iter().any(|v| json_eq(v, &value)) {
+                    let msg = "the value is not one of the enum values".to_string();
+                    fail(errors, path, "enum", msg);
+                }
+            }
+            if let Some(constant) = &rules.constant {
+                if !json_eq(constant, &value) {
+                    let msg = "the value is not equal to the const value".to_string();
+                    fail(errors, path, "const", msg);
+                }
+            }
+        }
+
+        match inst.get_type() {
+            JsonType::Number => self.check_number(rules, inst, path, errors),
+            JsonType::String => self.check_string(rules, inst, path, errors),
+            JsonType::Array => self.check_array(rules, inst, path, errors)?,
+            JsonType::Object => self.check_object(rules, inst, path, errors)?,
+            JsonType::Null | JsonType::Boolean => {}
+        }
+
+        for &sub in &rules.all_of {
+            self.check(sub, inst, path, errors)?;
+        }
+        if !rules.any_of.is_empty() {
+            let mut matched = false;
+            for &sub in &rules.any_of {
+                if self.matches(sub, inst, path)? {
+                    matched = true;
+                    break;
+                }
+            }
+            if !matched {
+                let msg = "the value does not match any subschema of anyOf".to_string();
+                fail(errors, path, "anyOf", msg);
+            }
+        }
+        if !rules.one_of.is_empty() {
+            let mut count = 0;
+            for &sub in &rules.one_of {
+                if self.matches(sub, inst, path)? {
+                    count += 1;
+                }
+            }
+            if count != 1 {
+                let msg = format!("the value matches {count} subschemas of oneOf, expected one");
+                fail(errors, path, "oneOf", msg);
+            }
+        }
+        if let Some(not) = rules.not {
+            if self.matches(not, inst, path)? {
+                let msg = "the value matches the subschema of not".to_string();
+                fail(errors, path, "not", msg);
+            }
+        }
+        if let Some(condition) = rules.condition {
+            let branch = if self.matches(condition, inst, path)? {
+                rules.then
+            } else {
+                rules.otherwise
+            };
+            if let Some(branch) = branch {
+                self.check(branch, inst, path, errors)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_number<I: Instance>(
+        &self,
+        rules: &Rules,
+        inst: &I,
+        path: &[PointerNode],
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let Some(n) = inst.as_f64() else {
+            return;
+        };
+        if let Some(min) = rules.minimum {
+            if n < min {
+                fail(errors, path, "minimum", format!("{n} is less than {min}"));
+            }
+        }
+        if let Some(max) = rules.maximum {
+            if n > max {
+                fail(
+                    errors,
+                    path,
+                    "maximum",
+                    format!("{n} is greater than {max}"),
+                );
+            }
+        }
+        if let Some(min) = rules.exclusive_minimum {
+            if n <= min {
+                let msg = format!("{n} is not greater than {min}");
+                fail(errors, path, "exclusiveMinimum", msg);
+            }
+        }
+        if let Some(max) = rules.exclusive_maximum {
+            if n >= max {
+                let msg = format!("{n} is not less than {max}");
+                fail(errors, path, "exclusiveMaximum", msg);
+            }
+        }
+        if let Some(m) = rules.multiple_of {
+            if !is_multiple(n, m) {
+                fail(
+                    errors,
+                    path,
+                    "multipleOf",
+                    format!("{n} is not a multiple of {m}"),
+                );
+            }
+        }
+    }
+
+    fn check_string<I: Instance>(
+        &self,
+        rules: &Rules,
+        inst: &I,
+        path: &[PointerNode],
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if rules.min_length.is_none() && rules.max_length.is_none() {
+            return;
+        }
+        let Some(s) = inst.as_str() else {
+            return;
+        };
+        let len = s.chars().count();
+        if let Some(min) = rules.min_length {
+            if len < min {
+                let msg = format!("the string is shorter than {min} characters");
+                fail(errors, path, "minLength", msg);
+            }
+        }
+        if let Some(max) = rules.max_length {
+            if len > max {
+                let msg = format!("the string is longer than {max} characters");
+                fail(errors, path, "maxLength", msg);
+            }
+        }
+    }
+
+    fn check_array<I: Instance>(
+        &self,
+        rules: &Rules,
+        inst: &I,
+        path: &mut Vec<PointerNode>,
+        errors: &mut Vec<ValidationError>,
+    ) -> Result<()> {
+        let elems = inst.elements()?;
+        if let Some(min) = rules.min_items {
+            if elems.len() < min {
+                let msg = format!("the array has less than {min} items");
+                fail(errors, path, "minItems", msg);
+            }
+        }
+        if let Some(max) = rules.max_items {
+            if elems.len() > max {
+                let msg = format!("the array has more than {max} items");
+                fail(errors, path, "maxItems", msg);
+            }
+        }
+
+        let nested =
+            !rules.prefix_items.is_empty() || rules.items.is_some() || rules.contains.is_some();
+        if nested && too_deep(path, errors) {
+            return Ok(());
+        }
+        for (i, elem) in elems.iter().enumerate() {
+            let sub = match rules.prefix_items.get(i) {
+                Some(&sub) => sub,
+                None => match rules.items {
+                    Some(sub) => sub,
+                    None => break,
+                },
+            };
+            path.push(PointerNode::Index(i));
+            let ret = self.check(sub, elem, path, errors);
+            path.pop();
+            ret?;
+        }
+
+        if let Some(contains) = rules.contains {
+            let mut count = 0;
+            for (i, elem) in elems.iter().enumerate() {
+                path.push(PointerNode::Index(i));
+                let ret = self.matches(contains, elem, path);
+                path.pop();
+                if ret? {
+                    count += 1;
+                }
+            }
+            let min = rules.min_contains.unwrap_or(1);
+            if count < min {
+                let msg = format!("the array contains {count} matched items, less than {min}");
+                fail(errors, path, "contains", msg);
+            }
+            if let Some(max) = rules.max_contains {
+                if count > max {
+                    let msg = format!("the array contains {count} matched items, more than {max}");
+                    fail(errors, path, "maxContains", msg);
+                }
+            }
+        }
+
+        if rules.unique_items {
+            let values = elems
+                .iter()
+                .map(Instance::to_value)
+                .collect::<Result<Vec<_>>>()?;
+            'outer: for (i, a) in values.iter().enumerate() {
+                for (j, b) in values[..i].iter().enumerate() {
+                    if json_eq(a, b) {
+                        let msg = format!("the items at {j} and {i} are equal");
+                        fail(errors, path, "uniqueItems", msg);
+                        break 'outer;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_object<I: Instance>(
+        &self,
+        rules: &Rules,
+        inst: &I,
+        path: &mut Vec<PointerNode>,
+        errors: &mut Vec<ValidationError>,
+    ) -> Result<()> {
+        let entries = inst.entries()?;
+        let keys: HashSet<&str> =
+            if rules.required.is_empty() && rules.dependent_required.is_empty() {
+                HashSet::new()
+            } else {
+                entries.iter().map(|(k, _)| k.as_ref()).collect()
+            };
+        let has = |key: &str| keys.contains(key);
+        if let Some(min) = rules.min_properties {
+            if entries.len() < min {
+                let msg = format!("the object has less than {min} properties");
+                fail(errors, path, "minProperties", msg);
+            }
+        }
+        if let Some(max) = rules.max_properties {
+            if entries.len() > max {
+                let msg = format!("the object has more than {max} properties");
+                fail(errors, path, "maxProperties", msg);
+            }
+        }
+        for key in &rules.required {
+            if !has(key.as_str()) {
+                let msg = format!("the required property {key:?} is missing");
+                fail(errors, path, "required", msg);
+            }
+        }
+        for (key, deps) in &rules.dependent_required {
+            if !has(key.as_str()) {
+                continue;
+            }
+            for dep in deps.iter().filter(|dep| !has(dep.as_str())) {
+                let msg = format!("the property {dep:?} is required by {key:?}");
+                fail(errors, path, "dependentRequired", msg);
+            }
+        }
+
+        let nested = !rules.properties.is_empty()
+            || rules.additional_properties.is_some()
+            || rules.property_names.is_some();
+        if nested && too_deep(path, errors) {
+            return Ok(());
+        }
+        for (key, value) in &entries {
+            path.push(PointerNode::Key(FastStr::new(key)));
+            let ret = self.check_property(rules, key, value, path, errors);
+            path.pop();
+            ret?;
+        }
+        Ok(())
+    }
+
+    // Check the property and its name, the `path` is the pointer of the property.
+    fn check_property<I: Instance>(
+        &self,
+        rules: &Rules,
+        key: &str,
+        value: &I,
+        path: &mut Vec<PointerNode>,
+        errors: &mut Vec<ValidationError>,
+    ) -> Result<()> {
+        if let Some(names) = rules.property_names {
+            self.check(names, &&Value::copy_str(key), path, errors)?;
+        }
+        if let Some(&sub) = rules.properties.get(key) {
+            return self.check(sub, value, path, errors);
+        }
+        match rules.additional_properties {
+            Some(sub) if matches!(self.nodes[sub], Node::Bool(false)) => {
+                let msg = format!("the additional property {key:?} is not allowed");
+                fail(errors, path, "additionalProperties", msg);
+                Ok(())
+            }
+            Some(sub) => self.check(sub, value, path, errors),
+            None => Ok(()),
+        }
+    }
+}
+
+fn fail(
+    errors: &mut Vec<ValidationError>,
+    path: &[PointerNode],
+    keyword: &'static str,
+    message: String,
+) {
+    errors.push(ValidationError {
+        pointer: path.to_vec(),
+        keyword,
+        message,
+    });
+}
+
+// Report the array or object nested too deeply, whose nested values are not checked by the
+// subschemas, so that the recursion is bounded.
+fn too_deep(path: &[PointerNode], errors: &mut Vec<ValidationError>) -> bool {
+    let limit = MAX_ALLOWED_DEPTH as usize;
+    if path.len() < limit {
+        return false;
+    }
+    let msg = format!("the value is nested deeper than {limit} levels");
+    fail(errors, path, "depth", msg);
+    true
+}
+
+fn type_bits<I: JsonValueTrait>(inst: &I) -> u8 {
+    match inst.get_type() {
+        JsonType::Null => NULL,
+        JsonType::Boolean => BOOLEAN,
+        JsonType::Object => OBJECT,
+        JsonType::Array => ARRAY,
+        JsonType::String => STRING,
+        // the numbers with a zero fractional part are integers, such as `1.0`
+        JsonType::Number => match inst.as_number() {
+            Some(n) if n.is_i64() || n.is_u64() => NUMBER | INTEGER,
+            Some(n) if n.as_f64().is_some_and(|f| f.fract() == 0.0) => NUMBER | INTEGER,
+            _ => NUMBER,
+        },
+    }
+}
+
+fn type_names(bits: u8) -> String {
+    let names: Vec<&str> = TYPE_NAMES
+        .iter()
+        .filter(|(bit, _)| bits & bit != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    names.join(" or ")
+}
+
+fn is_multiple(n: f64, m: f64) -> bool {
+    let q = n / m;
+    if !q.is_finite() {
+        return false;
+    }
+    // tolerate the rounding errors, such as `0.3 / 0.1`
+    (q - q.round()).abs() <= 1e-9 * q.abs().max(1.0)
+}
+
+// Compare the JSON values, the numbers are equal if they have the same value, such as `1` and
+// `1.0`.
+fn json_eq(a: &Value, b: &Value) -> bool {
+    if a.get_type() != b.get_type() {
+        return false;
+    }
+    if let (Some(x), Some(y)) = (a.as_number(), b.as_number()) {
+        return x.canonical() == y.canonical();
+    }
+    if let (Some(x), Some(y)) = (a.as_array(), b.as_array()) {
+        return x.len() == y.len() && x.iter().zip(y.iter()).all(|(x, y)| json_eq(x, y));
+    }
+    if let (Some(x), Some(y)) = (a.as_object(), b.as_object()) {
+        return x.len() == y.len()
+            && x.iter()
+                .all(|(k, x)| y.get(&k).is_some_and(|y| json_eq(x, y)));
+    }
+    a == b
+}
+
+// The value to validate, a parsed `Value` or a `LazyValue` iterated from the raw JSON text.
+trait Instance: JsonValueTrait + Sized {
+    // The elements of the array, or empty if it is not an array.
+    fn elements(&self) -> Result<Vec<Self>>;
+
+    // The entries of the object, or empty if it is not an object.
+    fn entries(&self) -> Result<Vec<(Cow<'_, str>, Self)>>;
+
+    fn to_value(&self) -> Result<Cow<'_, Value>>;
+}
+
+impl<'a> Instance for &'a Value {
+    fn elements(&self) -> Result<Vec<Self>> {
+        let value: &'a Value = self;
+        Ok(value
+            .as_array()
+            .map(|arr| arr.iter().collect())
+            .unwrap_or_default())
+    }
+
+    fn entries(&self) -> Result<Vec<(Cow<'_, str>, Self)>> {
+        let value: &'a Value = self;
+        Ok(value
+            .as_object()
+            .map(|obj| obj.iter().map(|(k, v)| (Cow::Borrowed(k), v)).collect())
+            .unwrap_or_default())
+    }
+
+    fn to_value(&self) -> Result<Cow<'_, Value>> {
+        Ok(Cow::Borrowed(*self))
+    }
+}
+
+impl<'a> Instance for LazyValue<'a> {
+    fn elements(&self) -> Result<Vec<Self>> {
+        match self.clone().into_array_iter() {
+            Some(iter) => iter.collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn entries(&self) -> Result<Vec<(Cow<'_, str>, Self)>> {
+        match self.clone().into_object_iter() {
+            Some(iter) => iter.collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn to_value(&self) -> Result<Cow<'_, Value>> {
+        crate::from_str(self.as_raw_str()).map(Cow::Owned)
+    }
+}
+
+struct Compiler<'a> {
+    root: &'a Value,
+    nodes: Vec<Node>,
+    // the ids of the compiled subschemas by their JSON pointers in the root schema, so that the
+    // recursive `$ref`s are compiled once
+    ids: HashMap<String, usize>,
+}
+
+impl<'a> Compiler<'a> {
+    fn compile(&mut self, schema: &'a Value, ptr: &str) -> Result<usize> {
+        if let Some(&id) = self.ids.get(ptr) {
+            return Ok(id);
+        }
+        let id = self.nodes.len();
+        self.nodes.push(Node::Bool(true));
+        self.ids.insert(ptr.to_string(), id);
+
+        let node = if let Some(b) = schema.as_bool() {
+            Node::Bool(b)
+        } else if let Some(obj) = schema.as_object() {
+            let mut rules = Rules::default();
+            for (key, value) in obj.iter() {
+                self.keyword(&mut rules, key, value, ptr)?;
+            }
+            Node::Rules(Box::new(rules))
+        } else {
+            return Err(invalid(ptr, "a schema must be an object or a boolean"));
+        };
+        self.nodes[id] = node;
+        Ok(id)
+    }
+
+    fn keyword(&mut self, rules: &mut Rules, key: &str, value: &'a Value, ptr: &str) -> Result<()> {
+        let ptr = &format!("{ptr}/{}", escape(key));
+        match key {
+            "$ref" => rules.reference = Some(self.reference(value, ptr)?),
+            "type" => rules.types = parse_types(value, ptr)?,
+            "enum" => {
+                let values = value
+                    .as_array()
+                    .ok_or_else(|| invalid(ptr, "expected an array"))?;
+                rules.enumeration = Some(values.iter().cloned().collect());
+            }
+            "const" => rules.constant = Some(value.clone()),
+
+            "minimum" => rules.minimum = Some(number(value, ptr)?),
+            "maximum" => rules.maximum = Some(number(value, ptr)?),
+            "exclusiveMinimum" => rules.exclusive_minimum = Some(number(value, ptr)?),
+            "exclusiveMaximum" => rules.exclusive_maximum = Some(number(value, ptr)?),
+            "multipleOf" => match number(value, ptr)? {
+                m if m > 0.0 => rules.multiple_of = Some(m),
+                _ => return Err(invalid(ptr, "expected a positive number")),
+            },
+
+            "minLength" => rules.min_length = Some(count(value, ptr)?),
+            "maxLength" => rules.max_length = Some(count(value, ptr)?),
+
+            "prefixItems" => rules.prefix_items = self.subschemas(value, ptr)?,
+            "items" => rules.items = Some(self.compile(value, ptr)?),
+            "contains" => rules.contains = Some(self.compile(value, ptr)?),
+            "minContains" => rules.min_contains = Some(count(value, ptr)?),
+            "maxContains" => rules.max_contains = Some(count(value, ptr)?),
+            "minItems" => rules.min_items = Some(count(value, ptr)?),
+            "maxItems" => rules.max_items = Some(count(value, ptr)?),
+            "uniqueItems" => {
+                rules.unique_items = value
+                    .as_bool()
+                    .ok_or_else(|| invalid(ptr, "expected a boolean"))?
+            }
+
+            "properties" => {
+                let obj = value
+                    .as_object()
+                    .ok_or_else(|| invalid(ptr, "expected an object"))?;
+                for (name, sub) in obj.iter() {
+                    let id = self.compile(sub, &format!("{ptr}/{}", escape(name)))?;
+                    rules.properties.insert(name.to_string(), id);
+                }
+            }
+            "required" => rules.required = strings(value, ptr)?,
+            "dependentRequired" => {
+                let obj = value
+                    .as_object()
+                    .ok_or_else(|| invalid(ptr, "expected an object"))?;
+                for (name, deps) in obj.iter() {
+                    let deps = strings(deps, &format!("{ptr}/{}", escape(name)))?;
+                    rules.dependent_required.push((name.to_string(), deps));
+                }
+            }
+            "additionalProperties" => rules.additional_properties = Some(self.compile(value, ptr)?),
+            "propertyNames" => rules.property_names = Some(self.compile(value, ptr)?),
+            "minProperties" => rules.min_properties = Some(count(value, ptr)?),
+            "maxProperties" => rules.max_properties = Some(count(value, ptr)?),
+
+            "allOf" => rules.all_of = self.subschemas(value, ptr)?,
+            "anyOf" => rules.any_of = self.subschemas(value, ptr)?,
+            "oneOf" => rules.one_of = self.subschemas(value, ptr)?,
+            "not" => rules.not = Some(self.compile(value, ptr)?),
+            "if" => rules.condition = Some(self.compile(value, ptr)?),
+            "then" => rules.then = Some(self.compile(value, ptr)?),
+            "else" => rules.otherwise = Some(self.compile(value, ptr)?),
+
+            "pattern"
+            | "patternProperties"
+            | "dependentSchemas"
+            | "unevaluatedItems"
+            | "unevaluatedProperties"
+            | "$dynamicRef"
+            | "$dynamicAnchor"
+            | "$recursiveRef" => {
+                return Err(invalid(ptr, "the keyword is not supported"));
+            }
+            // the annotations and the unknown keywords
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn subschemas(&mut self, value: &'a Value, ptr: &str) -> Result<Vec<usize>> {
+        let arr = value
+            .as_array()
+            .ok_or_else(|| invalid(ptr, "expected an array"))?;
+        arr.iter()
+            .enumerate()
+            .map(|(i, sub)| self.compile(sub, &format!("{ptr}/{i}")))
+            .collect()
+    }
+
+    // Compile the subschema pointed by the `$ref`, such as `#/$defs/node`.
+    fn reference(&mut self, value: &'a Value, ptr: &str) -> Result<usize> {
+        let target = value
+            .as_str()
+            .ok_or_else(|| invalid(ptr, "expected a string"))?;
+        let Some(fragment) = target.strip_prefix('#') else {
+            return Err(invalid(
+                ptr,
+                "only the JSON pointers in the same schema are supported",
+            ));
+        };
+        if !fragment.is_empty() && !fragment.starts_with('/') {
+            return Err(invalid(
+                ptr,
+                "only the JSON pointers in the same schema are supported",
+            ));
+        }
+
+        let mut schema = self.root;
+        for token in fragment.split('/').skip(1) {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            let sub = if schema.is_array() {
+                token.parse::<usize>().ok().and_then(|i| schema.get(i))
+            } else {
+                schema.get(token.as_str())
+            };
+            schema = sub.ok_or_else(|| invalid(ptr, "the referenced subschema is not found"))?;
+        }
+        self.compile(schema, fragment)
+    }
+}
+
+impl Rules {
+    // The subschemas applied to the same value, not to the nested values.
+    fn in_place(&self) -> impl Iterator<Item = usize> + '_ {
+        self.reference
+            .into_iter()
+            .chain(self.all_of.iter().copied())
+            .chain(self.any_of.iter().copied())
+            .chain(self.one_of.iter().copied())
+            .chain(self.not)
+            .chain(self.condition)
+            .chain(self.then)
+            .chain(self.otherwise)
+    }
+}
+
+impl Compiler<'_> {
+    // Reject the cycles of the subschemas applied to the same value, such as `{"$ref": "#"}`,
+    // which never end when validating, by a depth-first search over the in-place subschemas.
+    fn check_cycles(&self) -> Result<()> {
+        const VISITING: u8 = 1;
+        const VISITED: u8 = 2;
+        let mut states = vec![0u8; self.nodes.len()];
+        for root in 0..self.nodes.len() {
+            if states[root] != 0 {
+                continue;
+            }
+            states[root] = VISITING;
+            // the node and the index of its next subschema
+            let mut stack = vec![(root, 0)];
+            while let Some(&(id, next)) = stack.last() {
+                let sub = match &self.nodes[id] {
+                    Node::Rules(rules) => rules.in_place().nth(next),
+                    Node::Bool(_) => None,
+                };
+                let Some(sub) = sub else {
+                    states[id] = VISITED;
+                    stack.pop();
+                    continue;
+                };
+                if let Some(last) = stack.last_mut() {
+                    last.1 += 1;
+                }
+                match states[sub] {
+                    0 => {
+                        states[sub] = VISITING;
+                        stack.push((sub, 0));
+                    }
+                    VISITING => {
+                        let ptr = self
+                            .ids
+                            .iter()
+                            .find(|(_, &v)| v == sub)
+                            .map_or("", |(k, _)| k.as_str());
+                        return Err(invalid(
+                            ptr,
+                            "the `$ref` makes a cycle applied to the same value",
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid(ptr: &str, msg: &str) -> Error {
+    make_error(format!("Invalid JSON Schema at `#{ptr}`: {msg}"))
+}
+
+// Escape the key as a JSON pointer token.
+fn escape(key: &str) -> Cow<'_, str> {
+    if key.contains(['~', '/']) {
+        Cow::Owned(key.replace('~', "~0").replace('/', "~1"))
+    } else {
+        Cow::Borrowed(key)
+    }
+}
+
+fn parse_types(value: &Value, ptr: &str) -> Result<u8> {
+    let parse = |name: &str| {
+        TYPE_NAMES
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(bit, _)| *bit)
+            .ok_or_else(|| invalid(ptr, "unknown type"))
+    };
+    if let Some(name) = value.as_str() {
+        return parse(name);
+    }
+    let names = value
+        .as_array()
+        .ok_or_else(|| invalid(ptr, "expected a string or an array"))?;
+    let mut bits = 0;
+    for name in names.iter() {
+        bits |= parse(
+            name.as_str()
+                .ok_or_else(|| invalid(ptr, "expected a string"))?,
+        )?;
+    }
+    Ok(bits)
+}
+
+fn number(value: &Value, ptr: &str) -> Result<f64> {
+    value
+        .as_f64()
+        .ok_or_else(|| invalid(ptr, "expected a number"))
+}
+
+fn count(value: &Value, ptr: &str) -> Result<usize> {
+    value
+        .as_u64()
+        .map(|n| n as usize)
+        .ok_or_else(|| invalid(ptr, "expected a non-negative integer"))
+}
+
+fn strings(value: &Value, ptr: &str) -> Result<Vec<String>> {
+    let arr = value
+        .as_array()
+        .ok_or_else(|| invalid(ptr, "expected an array"))?;
+    arr.iter()
+        .map(|s| {
+            s.as_str()
+                .map(str::to_string)
+                .ok_or_else(|| invalid(ptr, "expected a string"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{json, pointer};
+
+    fn keywords(schema: &Schema, value: &Value) -> Vec<(String, &'static str)> {
+        let errors = schema.validate(value);
+        // the lazy validation returns the same errors
+        let json = crate::to_string(value).unwrap();
+        let lazy = schema.validate_slice(json.as_bytes()).unwrap();
+        assert_eq!(errors.len(), lazy.len(), "{json}");
+        for (a, b) in errors.iter().zip(&lazy) {
+            assert_eq!(a.pointer(), b.pointer());
+            assert_eq!(a.keyword(), b.keyword());
+        }
+        errors
+            .iter()
+            .map(|e| (pointer_string(e.pointer()), e.keyword))
+            .collect()
+    }
+
+    #[test]
+    fn test_schema_validate() {
+        let schema = Schema::compile(&json!({
+            "$defs": {
+                "node": {
+                    "type": "object",
+                    "properties": {
+                        "value": {"type": ["integer", "null"], "multipleOf": 0.5},
+                        "children": {"type": "array", "items": {"$ref": "#/$defs/node"}}
+                    },
+                    "required": ["value"],
+                    "additionalProperties": false
+                }
+            },
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "minLength": 2, "maxLength": 3},
+                "kind": {"enum": ["a", 1, [1.0]]},
+                "tags": {"type": "array", "uniqueItems": true, "contains": {"const": "x"}},
+                "point": {"prefixItems": [{"type": "number"}], "items": false, "minItems": 1},
+                "tree": {"$ref": "#/$defs/node"},
+                "score": {"anyOf": [{"maximum": 0}, {"exclusiveMinimum": 10}]},
+                "id": {"oneOf": [{"type": "integer"}, {"minimum": 0}]}
+            },
+            "dependentRequired": {"name": ["kind"]},
+            "propertyNames": {"maxLength": 5},
+            "if": {"required": ["tree"]},
+            "then": {"required": ["name"]}
+        }))
+        .unwrap();
+
+        let valid = json!({
+            "name": "día",
+            "kind": [1],
+            "tags": ["x", "y"],
+            "point": [1.5],
+            "tree": {"value": 1, "children": [{"value": null}, {"value": 2.0, "children": []}]},
+            "score": 11,
+            "id": -1
+        });
+        assert!(keywords(&schema, &valid).is_empty());
+
+        let invalid = json!({
+            "kind": 2,
+            "tags": ["y", "y"],
+            "point": [1, 2],
+            "tree": {"children": [{"value": 1.25, "x": 1}]},
+            "score": 5,
+            "id": 1,
+            "long_key": null
+        });
+        assert_eq!(
+            keywords(&schema, &invalid),
+            [
+                ("/kind".to_string(), "enum"),
+                ("/tags".to_string(), "contains"),
+                ("/tags".to_string(), "uniqueItems"),
+                ("/point/1".to_string(), "false"),
+                ("/tree".to_string(), "required"),
+                ("/tree/children/0/value".to_string(), "type"),
+                ("/tree/children/0/value".to_string(), "multipleOf"),
+                ("/tree/children/0/x".to_string(), "additionalProperties"),
+                ("/score".to_string(), "anyOf"),
+                ("/id".to_string(), "oneOf"),
+                ("/long_key".to_string(), "maxLength"),
+                ("".to_string(), "required"),
+            ]
+        );
+
+        let errors = schema.validate(&json!({"name": "a"}));
+        assert_eq!(errors[0].pointer(), &pointer![]);
+        assert_eq!(errors[0].keyword(), "dependentRequired");
+        assert_eq!(errors[1].pointer(), &pointer!["name"]);
+        assert_eq!(
+            errors[1].to_string(),
+            "the string is shorter than 2 characters at /name"
+        );
+        assert!(!schema.is_valid(&json!([])));
+        assert!(schema.validate_slice(b"{\"name\": ").is_err());
+    }
+
+    #[test]
+    fn test_schema_compile() {
+        assert!(Schema::compile(&json!(true)).unwrap().is_valid(&json!(1)));
+        assert!(!Schema::compile(&json!(false)).unwrap().is_valid(&json!(1)));
+        assert!(Schema::compile(&json!({"title": "x", "format": "uuid"})).is_ok());
+
+        for schema in [
+            json!(1),
+            json!({"type": "int"}),
+            json!({"minimum": "1"}),
+            json!({"multipleOf": 0}),
+            json!({"properties": {"a": {"pattern": "^a"}}}),
+            json!({"$ref": "other.json"}),
+            json!({"$ref": "#/$defs/missing"}),
+            // the cycles applied to the same value
+            json!({"$ref": "#"}),
+            json!({"$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"allOf": [{"$ref": "#/$defs/a"}]}}, "$ref": "#/$defs/a"}),
+            json!({"anyOf": [{"type": "null"}, {"not": {"$ref": "#"}}]}),
+        ] {
+            assert!(Schema::compile(&schema).is_err(), "{schema}");
+        }
+        let err = Schema::compile(&json!({"items": {"minItems": -1}})).unwrap_err();
+        assert!(err.to_string().contains("#/items/minItems"), "{err}");
+
+        // the recursive schemas applied to the nested values are allowed
+        let schema = Schema::compile(&json!({"items": {"$ref": "#"}})).unwrap();
+        let mut deep = json!([]);
+        for _ in 0..1000 {
+            deep = Value::from(vec![deep]);
+        }
+        let errors = schema.validate(&deep);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].keyword(), "depth");
+        assert_eq!(errors[0].pointer().len(), MAX_ALLOWED_DEPTH as usize);
+    }
+}
@@ -1,8 +1,12 @@
 //! Deserialize JSON data to a Rust data structure.
 
 // The code is cloned from [serde_json](https://github.com/serde-rs/json) and modified necessary parts.
-use std::{marker::PhantomData, mem::ManuallyDrop, ptr::slice_from_raw_parts, sync::Arc};
+use std::{
+    borrow::Cow, cell::RefCell, marker::PhantomData, mem::ManuallyDrop, ptr::slice_from_raw_parts,
+    sync::Arc,
+};
 
+use faststr::FastStr;
 use serde::{
     de::{self, Expected, Unexpected},
     forward_to_deserialize_any,
@@ -10,26 +14,35 @@ use serde::{
 use sonic_number::ParserNumber;
 
 use crate::{
+    config::{CancelToken, DedupPolicy, DeserializeCfg},
     error::{
         Error,
         ErrorCode::{self, EofWhileParsing, RecursionLimitExceeded},
         Result,
     },
-    parser::{as_str, ParseStatus, ParsedSlice, Parser, Reference},
-    reader::{Read, Reader},
+    parser::{as_str, Checkpoint, ParseStatus, ParsedSlice, Parser, Reference},
+    reader::{Read, Reader, Utf8Policy},
+    util::{
+        scratch::ScratchBuf,
+        string::{escape_control_chars, find_control_char},
+    },
     value::{node::Value, shared::Shared},
-    JsonInput, OwnedLazyValue,
+    JsonInput, JsonValueTrait, OwnedLazyValue, PointerNode,
 };
-const MAX_ALLOWED_DEPTH: u8 = u8::MAX;
+pub(crate) const MAX_ALLOWED_DEPTH: u8 = u8::MAX;
 
 //////////////////////////////////////////////////////////////////////////////
 
 /// A structure that deserializes JSON into Rust values.
 pub struct Deserializer<R> {
     pub(crate) parser: Parser<R>,
-    scratch: Vec<u8>,
+    scratch: ScratchBuf,
     remaining_depth: u8,
-    shared: Option<Arc<Shared>>, // the shared allocator for `Value`
+    shared: Option<Arc<Shared>>,     // the shared allocator for `Value`
+    nodes: Vec<ManuallyDrop<Value>>, // the node buffer reused by the sibling `Value`s
+    pointer: Option<PointerTrack>,
+    // the path of the value being deserialized, only tracked if the defaults are set
+    defaults_path: Option<Vec<PointerNode>>,
 }
 
 // some functions only used for struct visitors.
@@ -38,9 +51,12 @@ impl<'de, R: Reader<'de>> Deserializer<R> {
     pub fn new(read: R) -> Self {
         Self {
             parser: Parser::new(read),
-            scratch: Vec::new(),
+            scratch: ScratchBuf::new(),
             remaining_depth: MAX_ALLOWED_DEPTH,
             shared: Option::None,
+            nodes: Vec::new(),
+            pointer: None,
+            defaults_path: None,
         }
     }
 
@@ -107,11 +123,542 @@ impl<'de, R: Reader<'de>> Deserializer<R> {
     ///     assert_eq!(&out, exp.next().unwrap());
     /// }
     /// ```
-    pub fn utf8_lossy(mut self) -> Self {
-        self.parser.cfg.utf8_lossy = true;
+    pub fn utf8_lossy(self) -> Self {
+        self.utf8_policy(Utf8Policy::Lossy)
+    }
+
+    /// Set the policy of handling the invalid UTF-8 in the JSON input. The default policy is
+    /// [`Utf8Policy::Validate`]. The input is validated when it is parsed, so the input is never
+    /// validated with [`Utf8Policy::Trust`]. It is the same as `utf8_policy` in
+    /// [`DeserializeCfg`].
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{Deserializer, Utf8Policy};
+    ///
+    /// let json = b"\"a\xffb\"";
+    ///
+    /// let mut de = Deserializer::from_slice(json).utf8_policy(Utf8Policy::Validate);
+    /// assert!(de.deserialize::<String>().is_err());
+    ///
+    /// let mut de = Deserializer::from_slice(json).utf8_policy(Utf8Policy::Lossy);
+    /// assert_eq!(de.deserialize::<String>().unwrap(), "a\u{FFFD}b");
+    ///
+    /// // SAFETY: the JSON is valid UTF-8
+    /// let policy = unsafe { Utf8Policy::trust() };
+    /// let mut de = Deserializer::from_slice(br#""ab""#).utf8_policy(policy);
+    /// assert_eq!(de.deserialize::<String>().unwrap(), "ab");
+    /// ```
+    pub fn utf8_policy(mut self, policy: Utf8Policy) -> Self {
+        self.parser.cfg.utf8_policy = policy;
+        self.parser.cfg.utf8_lossy = policy == Utf8Policy::Lossy;
+        self.apply_utf8_policy();
+        self
+    }
+
+    // The input is validated when it is parsed at first, so the trusted input is never validated.
+    fn apply_utf8_policy(&mut self) {
+        if let Utf8Policy::Trust(_) = self.parser.cfg.utf8_policy {
+            self.parser.read.trust_utf8();
+        }
+    }
+
+    /// Allow to parse JSON with lone UTF-16 surrogates in the `\u` escapes.
+    ///
+    /// The lone surrogates are replaced with `\uFFFD` (displayed as �) in the parsed strings,
+    /// including the typed strings such as `String` and the strings read by
+    /// [`as_str`][`crate::JsonValueTrait::as_str`]. Only a [`Value`][`crate::Value`] records the
+    /// raw text of its escaped strings, so serializing the unmodified `Value` again keeps the
+    /// original escapes, such as `\uD800`.
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{Deserializer, JsonValueTrait, Value};
+    ///
+    /// let json = r#"{"a":"\ud800","b":"x\udc00\u0041"}"#;
+    /// let mut de = Deserializer::from_str(json).preserve_surrogates();
+    /// let value: Value = de.deserialize().unwrap();
+    /// assert_eq!(value["a"].as_str(), Some("�"));
+    /// assert_eq!(value["b"].as_str(), Some("x�A"));
+    /// assert_eq!(sonic_rs::to_string(&value).unwrap(), json);
+    /// ```
+    pub fn preserve_surrogates(mut self) -> Self {
+        self.parser.cfg.preserve_surrogates = true;
+        self
+    }
+
+    /// Allow the number extensions which are not in JSON, such as the hexadecimal integers `0x1F`
+    /// and the integers with underscore separators `1_000_000`. They are parsed as integers.
+    ///
+    /// The raw numbers, such as [`RawNumber`][`crate::RawNumber`] or the numbers in the
+    /// [`use_rawnumber`][`Deserializer::use_rawnumber`] mode, keep the original text of the JSON
+    /// numbers, but the extended numbers are normalized into the decimal integers, such as `255`
+    /// for `0xFF`, so the raw numbers are always valid JSON.
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{Deserializer, Value};
+    ///
+    /// let json = r#"{"mask": 0xFF, "size": 1_000_000, "offset": -0x10}"#;
+    /// let mut de = Deserializer::from_str(json).extended_numbers();
+    /// let value: Value = de.deserialize().unwrap();
+    /// assert_eq!(value["mask"], 255);
+    /// assert_eq!(value["size"], 1000000);
+    /// assert_eq!(value["offset"], -16);
+    ///
+    /// // not allowed by default
+    /// assert!(sonic_rs::from_str::<Value>(json).is_err());
+    /// ```
+    pub fn extended_numbers(mut self) -> Self {
+        self.parser.cfg.extended_numbers = true;
+        self
+    }
+
+    /// Allow the unknown escapes in the JSON strings, which is invalid JSON by default, for the
+    /// sloppy producers.
+    ///
+    /// `\xHH` is decoded as the char `U+00HH`, and the other unknown escapes, such as `\'`, are
+    /// replaced with the escaped char literally. The escaped control chars are still invalid.
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{Deserializer, Value};
+    ///
+    /// let json = r#"{"a": "\x41\'\q\xé\xE9"}"#;
+    /// let mut de = Deserializer::from_str(json).lenient_escapes();
+    /// let value: Value = de.deserialize().unwrap();
+    /// assert_eq!(value["a"], "A'qxéé");
+    ///
+    /// // not allowed by default
+    /// assert!(sonic_rs::from_str::<Value>(json).is_err());
+    /// ```
+    pub fn lenient_escapes(mut self) -> Self {
+        self.parser.cfg.lenient_escapes = true;
+        self
+    }
+
+    /// Allow the unescaped control chars in the JSON strings, such as the raw newlines and tabs
+    /// in the hand-written or pasted JSON, which are invalid JSON by default.
+    ///
+    /// The control chars are kept in the parsed strings, and they are escaped when serialized
+    /// again. The raw JSON texts are kept valid: the control chars are escaped in
+    /// [`LazyValue`][crate::LazyValue] and [`OwnedLazyValue`], the raw strings of `use_raw` are not
+    /// kept, and the borrowed [`RawJson`][crate::RawJson] with the control chars is an error.
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{Deserializer, Value};
+    ///
+    /// let json = "{\"a\": \"line1\nline2\tend\"}";
+    /// let mut de = Deserializer::from_str(json).allow_control_chars();
+    /// let value: Value = de.deserialize().unwrap();
+    /// assert_eq!(value["a"], "line1\nline2\tend");
+    /// assert_eq!(
+    ///     sonic_rs::to_string(&value).unwrap(),
+    ///     r#"{"a":"line1\nline2\tend"}"#
+    /// );
+    ///
+    /// // not allowed by default
+    /// assert!(sonic_rs::from_str::<Value>(json).is_err());
+    /// ```
+    pub fn allow_control_chars(mut self) -> Self {
+        self.parser.cfg.allow_control_chars = true;
+        self
+    }
+
+    /// Skip the UTF-8 BOM (`EF BB BF`) at the start of the JSON text, which is an invalid JSON
+    /// value by default.
+    ///
+    /// Enable the `skip_bom` feature to skip the BOM by default in all the APIs, including the
+    /// lazy APIs such as [`get`][`crate::get`].
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{Deserializer, Value};
+    ///
+    /// let json = "\u{FEFF}{\"a\": 1}";
+    /// let mut de = Deserializer::from_str(json).skip_bom();
+    /// let value: Value = de.deserialize().unwrap();
+    /// assert_eq!(value["a"], 1);
+    /// ```
+    pub fn skip_bom(mut self) -> Self {
+        self.parser.cfg.skip_bom = true;
+        self.parser.skip_bom();
+        self
+    }
+
+    /// Parse the empty string `""` as `None` for the `Option` fields, which is common in the
+    /// JSON from some legacy backends.
+    ///
+    /// Only the `Option` types are affected, the empty strings are still parsed as the empty
+    /// strings for the other types, such as `String` and [`Value`][crate::Value].
+    ///
+    /// # Example
+    /// ```
+    /// use serde::Deserialize;
+    /// use sonic_rs::Deserializer;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct User {
+    ///     age: Option<u32>,
+    ///     name: Option<String>,
+    ///     email: String,
+    /// }
+    ///
+    /// let json = r#"{"age": "", "name": "", "email": ""}"#;
+    /// let mut de = Deserializer::from_str(json).empty_string_as_none();
+    /// let user: User = de.deserialize().unwrap();
+    /// assert_eq!(user.age, None);
+    /// assert_eq!(user.name, None);
+    /// assert_eq!(user.email, "");
+    ///
+    /// // `age` is not a number by default
+    /// assert!(sonic_rs::from_str::<User>(json).is_err());
+    /// ```
+    pub fn empty_string_as_none(mut self) -> Self {
+        self.parser.cfg.empty_string_as_none = true;
+        self
+    }
+
+    /// Set the policy of the duplicate keys in the objects parsed into [`Value`]. The duplicate
+    /// keys are kept by default, which is different from `serde_json`.
+    ///
+    /// The duplicates are removed when the object is parsed, so the parsed `Value` is still a
+    /// compact DOM. The other types, such as the structs and maps, are not affected. With
+    /// [`DedupPolicy::Error`], the pointer of the error is relative to the parsed `Value`.
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{json, pointer, DedupPolicy, Deserializer, Value};
+    ///
+    /// let json = r#"{"a": 1, "b": {"c": 2, "c": 3}, "a": 4}"#;
+    /// let parse = |policy| Deserializer::from_str(json).dedup_keys(policy).deserialize::<Value>();
+    ///
+    /// assert_eq!(parse(DedupPolicy::FirstWins).unwrap(), json!({"a": 1, "b": {"c": 2}}));
+    /// let value = parse(DedupPolicy::LastWins).unwrap();
+    /// assert_eq!(value, json!({"a": 4, "b": {"c": 3}}));
+    /// assert_eq!(sonic_rs::to_string(&value).unwrap(), r#"{"a":4,"b":{"c":3}}"#);
+    ///
+    /// let err = parse(DedupPolicy::Error).unwrap_err();
+    /// assert_eq!(err.duplicate_key(), Some(&pointer!["b", "c"][..]));
+    /// ```
+    pub fn dedup_keys(mut self, policy: DedupPolicy) -> Self {
+        self.parser.cfg.dedup_keys = policy;
+        self
+    }
+
+    /// Set the bytes of the JSON text shown on each side of the error position in the error
+    /// message, `0` to show no JSON text. The default is `8`.
+    ///
+    /// The JSON text is copied when the error is created, and rendered only when the error is
+    /// displayed.
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{Deserializer, Value};
+    ///
+    /// let json = r#"{"name": "sonic", "tags": [1, 2,, 3]}"#;
+    /// let err = Deserializer::from_str(json)
+    ///     .error_snippet_len(4)
+    ///     .deserialize::<Value>()
+    ///     .unwrap_err();
+    /// let msg = err.to_string();
+    /// assert!(msg.contains(",, 3") && !msg.contains("tags"));
+    ///
+    /// let err = Deserializer::from_str(json)
+    ///     .error_snippet_len(0)
+    ///     .deserialize::<Value>()
+    ///     .unwrap_err();
+    /// assert!(err.to_string().ends_with("at line 1 column 33"));
+    /// ```
+    pub fn error_snippet_len(mut self, len: usize) -> Self {
+        self.parser.cfg.error_snippet_len = len;
+        self
+    }
+
+    /// Only record the code and the offset of the errors, which is cheap for the hot paths that
+    /// expect failures, such as probing the input with several types.
+    ///
+    /// The line and column of the error are not counted from the JSON text, so they are zero,
+    /// and the error message has no position. The [`Error::offset`] and the categories, such as
+    /// [`Error::is_eof`], are still available.
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{Deserializer, Value};
+    ///
+    /// let json = "[1, 2, 3";
+    /// let err = Deserializer::from_str(json)
+    ///     .lightweight_errors()
+    ///     .deserialize::<Value>()
+    ///     .unwrap_err();
+    /// assert!(err.is_eof());
+    /// assert_eq!((err.offset(), err.line(), err.column()), (8, 0, 0));
+    /// assert_eq!(err.to_string(), "EOF while parsing");
+    /// ```
+    pub fn lightweight_errors(mut self) -> Self {
+        self.parser.cfg.lightweight_errors = true;
+        self
+    }
+
+    /// Set all the options in `cfg` at once, which replaces the options set by the other builder
+    /// methods before.
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{DeserializeCfg, Deserializer, Value};
+    ///
+    /// let mut cfg = DeserializeCfg::default();
+    /// cfg.use_rawnumber = true;
+    ///
+    /// let json = r#"{"a":1.2345678901234567890123}"#;
+    /// let mut de = Deserializer::from_str(json).with_cfg(cfg);
+    /// let value: Value = de.deserialize().unwrap();
+    /// assert_eq!(sonic_rs::to_string(&value).unwrap(), json);
+    /// ```
+    pub fn with_cfg(mut self, cfg: DeserializeCfg) -> Self {
+        self.parser.cfg = cfg.resolve_utf8_policy();
+        self.apply_utf8_policy();
+        if cfg.skip_bom {
+            self.parser.skip_bom();
+        }
+        self
+    }
+
+    /// Abort the parsing when the `token` is cancelled, such as from another thread when the
+    /// request is timeout.
+    ///
+    /// The token is checked at the start of each object and array, and then every 64 elements of
+    /// them, including the skipped values such as the ignored fields. The cancelled parsing returns
+    /// an error, and [`Error::is_cancelled`] is true.
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{CancelToken, Deserializer, Value};
+    ///
+    /// let token = CancelToken::new();
+    /// let json = r#"{"a": [1, 2, 3]}"#;
+    ///
+    /// let mut de = Deserializer::from_str(json).with_cancel(token.clone());
+    /// assert!(de.deserialize::<Value>().is_ok());
+    ///
+    /// token.cancel();
+    /// let mut de = Deserializer::from_str(json).with_cancel(token);
+    /// assert!(de.deserialize::<Value>().unwrap_err().is_cancelled());
+    /// ```
+    pub fn with_cancel(mut self, token: CancelToken) -> Self {
+        self.parser.cancel = Some(token);
+        self
+    }
+
+    /// Track the JSON pointer of the value being deserialized, which is returned by
+    /// [`current_pointer`][`Deserializer::current_pointer`], or the free function
+    /// [`current_pointer`][`crate::current_pointer`] in the custom `Deserialize` impls.
+    ///
+    /// The object keys and array indexes are tracked. If the deserialization fails, the pointer
+    /// is kept at the failed value.
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{pointer, Deserializer};
+    ///
+    /// #[derive(Debug, serde::Deserialize)]
+    /// struct Foo {
+    ///     a: Vec<u8>,
+    /// }
+    ///
+    /// let mut de = Deserializer::from_str(r#"{"a": [1, 2, 256]}"#).track_pointer();
+    /// assert!(de.deserialize::<Foo>().is_err());
+    /// assert_eq!(de.current_pointer(), pointer!["a", 2]);
+    /// ```
+    pub fn track_pointer(mut self) -> Self {
+        self.pointer = Some(PointerTrack {
+            path: Vec::new(),
+            lent: false,
+        });
         self
     }
 
+    /// Fill the missing fields of the structs from `defaults`, instead of the `#[serde(default)]`
+    /// attributes.
+    ///
+    /// The defaults of a struct are looked up by its JSON pointer. If a field is missing in the
+    /// JSON object and the defaults have the field at the same pointer, the field is deserialized
+    /// from the default value. The fields which are missing in both are handled by serde as usual.
+    ///
+    /// Only the structs are filled, the maps and the structs with `#[serde(flatten)]` fields are
+    /// not. The whole value of a missing field is taken from the defaults, it is not merged.
+    ///
+    /// # Example
+    /// ```
+    /// use serde::Deserialize;
+    /// use sonic_rs::{json, Deserializer};
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Server {
+    ///     host: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Config {
+    ///     name: String,
+    ///     servers: Vec<Server>,
+    ///     admin: Server,
+    /// }
+    ///
+    /// let defaults = json!({
+    ///     "servers": [],
+    ///     "admin": {"host": "localhost", "port": 8080}
+    /// });
+    /// let json = r#"{"name": "app", "admin": {"host": "example.com"}}"#;
+    /// let mut de = Deserializer::from_str(json).with_defaults(&defaults);
+    /// let config: Config = de.deserialize().unwrap();
+    /// assert!(config.servers.is_empty());
+    /// assert_eq!(config.admin.host, "example.com");
+    /// assert_eq!(config.admin.port, 8080);
+    /// ```
+    pub fn with_defaults(mut self, defaults: &'de Value) -> Self {
+        self.parser.read.set_defaults(defaults);
+        self.defaults_path = Some(Vec::new());
+        self
+    }
+
+    // Get the default value of the `field` in the struct being deserialized.
+    fn default_field(&self, field: &str) -> Option<&'de Value> {
+        let path = self.defaults_path.as_ref()?;
+        self.parser.read.defaults()?.pointer(path)?.get(field)
+    }
+
+    /// Save the current position, which can be restored by [`rewind`][Deserializer::rewind] to
+    /// parse the following JSON again, such as trying another type after the failed attempt.
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::Deserializer;
+    ///
+    /// let mut de = Deserializer::from_str(r#"[1, "2"] [3]"#);
+    /// let cp = de.checkpoint();
+    /// assert!(de.deserialize::<Vec<u64>>().is_err());
+    ///
+    /// // try again from the checkpoint
+    /// de.rewind(cp);
+    /// let (a, b): (u64, String) = de.deserialize().unwrap();
+    /// assert_eq!((a, b.as_str()), (1, "2"));
+    /// assert_eq!(de.deserialize::<Vec<u64>>().unwrap(), [3]);
+    /// ```
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.parser.checkpoint()
+    }
+
+    /// Restore the position saved by [`checkpoint`][Deserializer::checkpoint], the position may
+    /// be before or after the current one.
+    ///
+    /// The internal buffer of the unescaped strings is cleared, and the tracked pointer is reset,
+    /// so the deserializer is in the same state as the checkpoint is created between two values.
+    /// The values deserialized before are not affected, since they borrow the JSON input or own
+    /// their data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checkpoint is created by another deserializer.
+    pub fn rewind(&mut self, cp: Checkpoint) {
+        self.parser.rewind(cp);
+        self.scratch.clear();
+        if let Some(track) = &mut self.pointer {
+            track.path.clear();
+        }
+        if let Some(path) = &mut self.defaults_path {
+            path.clear();
+        }
+    }
+
+    /// Get the JSON pointer of the value being deserialized, or the failed value if the
+    /// deserialization failed. Return an empty pointer if
+    /// [`track_pointer`][`Deserializer::track_pointer`] is not enabled.
+    pub fn current_pointer(&self) -> Vec<PointerNode> {
+        match &self.pointer {
+            Some(track) if track.lent => current_pointer().unwrap_or_default(),
+            Some(track) => track.path.clone(),
+            None => Vec::new(),
+        }
+    }
+
+    // Get the key between the quotes at `start` and `end`, which is parsed already.
+    fn raw_key(&self, start: usize, end: usize) -> Result<FastStr> {
+        let raw = self.parser.read.slice_unchecked(start - 1, end);
+        if raw.contains(&b'\\') {
+            crate::from_slice(raw)
+        } else {
+            Ok(FastStr::new(String::from_utf8_lossy(
+                &raw[1..raw.len() - 1],
+            )))
+        }
+    }
+
+    // Deserialize the child value at `node` by `f`, and track its pointer if enabled.
+    #[inline(always)]
+    fn with_pointer<T, N, F>(&mut self, node: N, f: F) -> Result<T>
+    where
+        N: FnOnce() -> PointerNode,
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        if self.pointer.is_none() && self.defaults_path.is_none() {
+            return f(self);
+        }
+
+        let node = node();
+        let Some(path) = &mut self.defaults_path else {
+            return self.with_pointer_tracked(node, f);
+        };
+        path.push(node.clone());
+        let ret = self.with_pointer_tracked(node, f);
+        if let Some(path) = &mut self.defaults_path {
+            path.pop();
+        }
+        ret
+    }
+
+    fn with_pointer_tracked<T, F>(&mut self, node: PointerNode, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        let Some(track) = &mut self.pointer else {
+            return f(self);
+        };
+
+        if track.lent {
+            CURRENT_POINTER.with(|cur| cur.borrow_mut().path.push(node));
+            let ret = f(self);
+            if ret.is_ok() {
+                CURRENT_POINTER.with(|cur| cur.borrow_mut().path.pop());
+            }
+            return ret;
+        }
+
+        // move the path into the thread local, and keep the path of the outer deserializer
+        track.path.push(node);
+        track.lent = true;
+        let outer_active = CURRENT_POINTER.with(|cur| {
+            let mut cur = cur.borrow_mut();
+            std::mem::swap(&mut cur.path, &mut track.path);
+            std::mem::replace(&mut cur.active, true)
+        });
+        let ret = f(self);
+        if let Some(track) = &mut self.pointer {
+            CURRENT_POINTER.with(|cur| {
+                let mut cur = cur.borrow_mut();
+                std::mem::swap(&mut cur.path, &mut track.path);
+                cur.active = outer_active;
+            });
+            track.lent = false;
+            if ret.is_ok() {
+                track.path.pop();
+            }
+        }
+        ret
+    }
+
     /// Deserialize a JSON stream to a Rust data structure.
     ///
     /// It can be used repeatedly and we do not check trailing chars after deserilalized.
@@ -144,6 +691,12 @@ impl<'de, R: Reader<'de>> Deserializer<R> {
     where
         T: de::Deserialize<'de>,
     {
+        if let Some(track) = &mut self.pointer {
+            track.path.clear();
+        }
+        if let Some(path) = &mut self.defaults_path {
+            path.clear();
+        }
         de::Deserialize::deserialize(self)
     }
 
@@ -241,6 +794,73 @@ macro_rules! tri {
 
 pub(crate) use tri;
 
+// The path of the value being deserialized, used by `current_pointer`.
+pub(crate) struct PointerTrack {
+    path: Vec<PointerNode>,
+    // the path is moved into `CURRENT_POINTER` when deserializing the children
+    lent: bool,
+}
+
+struct CurrentPointer {
+    path: Vec<PointerNode>,
+    active: bool,
+}
+
+thread_local! {
+    static CURRENT_POINTER: RefCell<CurrentPointer> = const {
+        RefCell::new(CurrentPointer {
+            path: Vec::new(),
+            active: false,
+        })
+    };
+}
+
+/// Get the JSON pointer of the value being deserialized, in the custom `Deserialize` impls.
+///
+/// Return `None` if there is no [`Deserializer`] with
+/// [`track_pointer`][`Deserializer::track_pointer`] deserializing in the current thread. If the
+/// deserializers are nested, the path of the innermost one is returned.
+///
+/// # Example
+/// ```
+/// use serde::{Deserialize, Deserializer};
+/// use sonic_rs::{pointer, PointerNode};
+///
+/// struct Located(Vec<PointerNode>);
+///
+/// impl<'de> Deserialize<'de> for Located {
+///     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+///         serde::de::IgnoredAny::deserialize(d)?;
+///         Ok(Located(sonic_rs::current_pointer().unwrap_or_default()))
+///     }
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Outer {
+///     a: (u8, Inner),
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Inner {
+///     b: Located,
+/// }
+///
+/// let json = r#"{"a": [0, {"b": null}]}"#;
+/// let mut de = sonic_rs::Deserializer::from_str(json).track_pointer();
+/// let outer: Outer = de.deserialize().unwrap();
+/// assert_eq!(outer.a.1.b.0, pointer!["a", 1, "b"]);
+///
+/// // not tracked by default
+/// let outer: Outer = sonic_rs::from_str(json).unwrap();
+/// assert!(outer.a.1.b.0.is_empty());
+/// ```
+pub fn current_pointer() -> Option<Vec<PointerNode>> {
+    CURRENT_POINTER.with(|cur| {
+        let cur = cur.borrow();
+        cur.active.then(|| cur.path.clone())
+    })
+}
+
 struct DepthGuard<'a, R> {
     de: &'a mut Deserializer<R>,
 }
@@ -313,6 +933,14 @@ impl<'de, R: Reader<'de>> Deserializer<R> {
         }
     }
 
+    // Deserialize an object key, whose opening quote has been parsed.
+    pub(crate) fn deserialize_key_seed<K>(&mut self, seed: K) -> Result<K::Value>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(MapKey { de: self })
+    }
+
     #[cold]
     fn peek_invalid_type(&mut self, peek: u8, exp: &dyn Expected) -> Error {
         self.parser.peek_invalid_type(peek, exp)
@@ -362,6 +990,12 @@ impl<'de, R: Reader<'de>> Deserializer<R> {
         V: de::Visitor<'de>,
     {
         let (raw, status) = self.parser.skip_one()?;
+        // escape the raw control chars, so that the raw JSON is valid
+        if self.parser.cfg.allow_control_chars {
+            if let Some(escaped) = escape_control_chars(as_str(raw)) {
+                return visitor.visit_str(&escaped);
+            }
+        }
         if status == ParseStatus::HasEscaped {
             visitor.visit_str(as_str(raw))
         } else {
@@ -391,18 +1025,24 @@ impl<'de, R: Reader<'de>> Deserializer<R> {
         V: de::Visitor<'de>,
     {
         let mut val = Value::new();
-        if self.parser.read.index() == 0 {
-            // will parse the JSON inplace
-            let cfg = self.parser.cfg;
-            let json = self.parser.read.as_u8_slice();
+        if self.parser.at_doc_start() {
+            // will parse the JSON inplace, the BOM has been skipped by this parser
+            let cfg = DeserializeCfg {
+                skip_bom: false,
+                ..self.parser.cfg
+            };
+            let cancel = self.parser.cancel.clone();
+            let json = &self.parser.read.as_u8_slice()[self.parser.read.index()..];
 
             // get n to check trailing characters in later
             let n = if cfg.utf8_lossy && self.parser.read.next_invalid_utf8() != usize::MAX {
                 // repr the invalid utf8, not need to care about the invalid UTF8 char in non-string
                 // parts, it will cause errors when parsing.
-                val.parse_with_padding(String::from_utf8_lossy(json).as_bytes(), cfg)?
+                let repr = String::from_utf8_lossy(json);
+                let n = val.parse_with_padding(repr.as_bytes(), cfg, cancel)?;
+                lossy_index(json, n)
             } else {
-                val.parse_with_padding(json, cfg)?
+                val.parse_with_padding(json, cfg, cancel)?
             };
             self.parser.read.eat(n);
         } else {
@@ -415,7 +1055,7 @@ impl<'de, R: Reader<'de>> Deserializer<R> {
             };
             // deserialize some json parts into `Value`, not use padding buffer, avoid the memory
             // copy
-            val.parse_without_padding(shared, &mut self.scratch, &mut self.parser)?
+            val.parse_without_padding(shared, &mut self.nodes, &mut self.scratch, &mut self.parser)?
         };
 
         let val = ManuallyDrop::new(val);
@@ -436,23 +1076,15 @@ impl<'de, R: Reader<'de>> Deserializer<R> {
     {
         let raw = match self.parser.skip_space_peek() {
             Some(c @ b'-' | c @ b'0'..=b'9') => {
-                let start = self.parser.read.index();
                 self.parser.read.eat(1);
-                self.parser.skip_number(c)?;
-                let end = self.parser.read.index();
-                as_str(self.parser.read.slice_unchecked(start, end))
+                self.parser.parse_raw_number(c)?
             }
             Some(b'"') => {
                 self.parser.read.eat(1);
-                let start = self.parser.read.index();
-                match self.parser.read.next() {
-                    Some(c @ b'-' | c @ b'0'..=b'9') => {
-                        self.parser.skip_number(c)?;
-                    }
+                let raw = match self.parser.read.next() {
+                    Some(c @ b'-' | c @ b'0'..=b'9') => self.parser.parse_raw_number(c)?,
                     _ => return Err(self.parser.error(ErrorCode::InvalidNumber)),
-                }
-                let end = self.parser.read.index();
-                let raw = as_str(self.parser.read.slice_unchecked(start, end));
+                };
                 // match the right quote
                 if self.parser.read.next() != Some(b'"') {
                     return Err(self.parser.error(ErrorCode::InvalidNumber));
@@ -462,7 +1094,52 @@ impl<'de, R: Reader<'de>> Deserializer<R> {
             _ => return Err(self.parser.error(ErrorCode::InvalidNumber)),
         };
 
-        visitor.visit_borrowed_str(raw)
+        match raw {
+            Cow::Borrowed(raw) => visitor.visit_borrowed_str(raw),
+            // the normalized extended number
+            Cow::Owned(raw) => visitor.visit_str(&raw),
+        }
+    }
+
+    // Decode the base64 string from the JSON text directly, without unescaping it into the
+    // scratch buffer first. The strings with escapes, such as `\/`, are unescaped as usual.
+    fn deserialize_base64<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        use crate::serde::with::base64::decode;
+
+        let Some(peek) = self.parser.skip_space() else {
+            return Err(self.parser.error(ErrorCode::EofWhileParsing));
+        };
+
+        let value = match peek {
+            b'"' => {
+                let start = self.parser.read.index();
+                let decoded = if tri!(self.parser.skip_string()) == ParseStatus::HasEscaped {
+                    self.parser.read.set_index(start);
+                    let text = tri!(self.parser.parse_string_raw(&mut self.scratch));
+                    decode(&text)
+                } else {
+                    // the index is after the closing quote
+                    let end = self.parser.read.index() - 1;
+                    decode(self.parser.read.slice_unchecked(start, end))
+                };
+                match decoded {
+                    Some(bytes) => visitor.visit_byte_buf(bytes),
+                    None => Err(de::Error::invalid_value(
+                        Unexpected::Other("invalid base64 string"),
+                        &visitor,
+                    )),
+                }
+            }
+            _ => Err(self.peek_invalid_type(peek, &visitor)),
+        };
+
+        match value {
+            Ok(value) => Ok(value),
+            Err(err) => Err(self.parser.fix_position(err)),
+        }
     }
 }
 
@@ -703,7 +1380,8 @@ impl<'de, 'a, R: Reader<'de>> de::Deserializer<'de> for &'a mut Deserializer<R>
         self.deserialize_bytes(visitor)
     }
 
-    /// Parses a `null` as a None, and any other values as a `Some(...)`.
+    /// Parses a `null` as a None, and any other values as a `Some(...)`. The empty string is
+    /// also parsed as a None if `empty_string_as_none` is enabled.
     #[inline]
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
@@ -715,6 +1393,13 @@ impl<'de, 'a, R: Reader<'de>> de::Deserializer<'de> for &'a mut Deserializer<R>
                 tri!(self.parser.parse_literal("ull"));
                 visitor.visit_none()
             }
+            Some(b'"')
+                if self.parser.cfg.empty_string_as_none
+                    && self.parser.read.peek_n(2) == Some(&b"\"\""[..]) =>
+            {
+                self.parser.read.eat(2);
+                visitor.visit_none()
+            }
             _ => visitor.visit_some(self),
         }
     }
@@ -763,6 +1448,26 @@ impl<'de, 'a, R: Reader<'de>> de::Deserializer<'de> for &'a mut Deserializer<R>
                 return self.deserialize_owned_lazyvalue(visitor);
             } else if name == crate::value::de::TOKEN {
                 return self.deserialize_value(visitor);
+            } else if name == crate::serde::with::base64::TOKEN {
+                return self.deserialize_base64(visitor);
+            } else if name == crate::serde::untagged::TOKEN {
+                // the raw text is always borrowed, even if the value has escapes
+                let (raw, _) = tri!(self.parser.skip_one());
+                let span = crate::serde::untagged::RawSpan::new(&self.parser, raw);
+                return visitor.visit_seq(span.into_access());
+            } else if name == crate::serde::rawjson::TOKEN {
+                let (raw, _) = tri!(self.parser.skip_one());
+                // the borrowed raw JSON can't be escaped, so the control chars are rejected
+                if self.parser.cfg.allow_control_chars {
+                    if let Some(i) = find_control_char(raw) {
+                        self.parser.read.backward(raw.len() - i);
+                        return Err(self
+                            .parser
+                            .error(ErrorCode::ControlCharacterWhileParsingString));
+                    }
+                }
+                let access = crate::serde::rawjson::RawJsonAccess::new(as_str(raw));
+                return visitor.visit_map(access);
             }
         }
 
@@ -846,7 +1551,7 @@ impl<'de, 'a, R: Reader<'de>> de::Deserializer<'de> for &'a mut Deserializer<R>
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
@@ -870,7 +1575,11 @@ impl<'de, 'a, R: Reader<'de>> de::Deserializer<'de> for &'a mut Deserializer<R>
             b'{' => {
                 let ret = {
                     let _ = DepthGuard::guard(self);
-                    visitor.visit_map(MapAccess::new(self))
+                    let mut map = MapAccess::new(self);
+                    if map.de.defaults_path.is_some() {
+                        map.missing = fields.iter().map(|f| (*f, true)).collect();
+                    }
+                    visitor.visit_map(map)
                 };
                 match (ret, self.end_map()) {
                     (Ok(ret), Ok(())) => Ok(ret),
@@ -938,11 +1647,16 @@ impl<'de, 'a, R: Reader<'de>> de::Deserializer<'de> for &'a mut Deserializer<R>
 pub struct SeqAccess<'a, R: 'a> {
     de: &'a mut Deserializer<R>,
     first: bool, // first is marked as
+    index: usize,
 }
 
 impl<'a, R: 'a> SeqAccess<'a, R> {
     pub fn new(de: &'a mut Deserializer<R>) -> Self {
-        SeqAccess { de, first: true }
+        SeqAccess {
+            de,
+            first: true,
+            index: 0,
+        }
     }
 }
 
@@ -953,16 +1667,26 @@ impl<'de, 'a, R: Reader<'de> + 'a> de::SeqAccess<'de> for SeqAccess<'a, R> {
     where
         T: de::DeserializeSeed<'de>,
     {
+        tri!(self
+            .de
+            .parser
+            .poll_cancelled(self.index + !self.first as usize));
         match self.de.parser.skip_space_peek() {
             Some(b']') => Ok(None), // we will check the ending brace after `visit_seq`
             Some(b',') if !self.first => {
                 self.de.parser.read.eat(1);
-                Ok(Some(tri!(seed.deserialize(&mut *self.de))))
+                self.index += 1;
+                let index = self.index;
+                self.de
+                    .with_pointer(|| PointerNode::Index(index), |de| seed.deserialize(de))
+                    .map(Some)
             }
             Some(_) => {
                 if self.first {
                     self.first = false;
-                    Ok(Some(tri!(seed.deserialize(&mut *self.de))))
+                    self.de
+                        .with_pointer(|| PointerNode::Index(0), |de| seed.deserialize(de))
+                        .map(Some)
                 } else {
                     self.de.parser.read.eat(1); // makes the error position is correct
                     Err(self.de.parser.error(ErrorCode::ExpectedArrayCommaOrEnd))
@@ -976,11 +1700,46 @@ impl<'de, 'a, R: Reader<'de> + 'a> de::SeqAccess<'de> for SeqAccess<'a, R> {
 pub struct MapAccess<'a, R: 'a> {
     de: &'a mut Deserializer<R>,
     first: bool,
+    // the number of the visited keys, to poll the cancel token
+    count: usize,
+    // the last key, only recorded if the pointer or the defaults are tracked
+    key: FastStr,
+    // the struct fields and whether they are missing, only recorded if the defaults are set
+    missing: Vec<(&'static str, bool)>,
+    // the missing field whose value is taken from the defaults
+    filling: Option<&'static str>,
 }
 
 impl<'a, R: 'a> MapAccess<'a, R> {
     pub fn new(de: &'a mut Deserializer<R>) -> Self {
-        MapAccess { de, first: true }
+        MapAccess {
+            de,
+            first: true,
+            count: 0,
+            key: FastStr::empty(),
+            missing: Vec::new(),
+            filling: None,
+        }
+    }
+}
+
+impl<'de, 'a, R: Reader<'de> + 'a> MapAccess<'a, R> {
+    // Visit the next missing field which has a default value, after the end of the object.
+    fn next_default_key<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        while let Some(i) = self.missing.iter().position(|(_, missing)| *missing) {
+            let field = self.missing[i].0;
+            self.missing[i].1 = false;
+            if self.de.default_field(field).is_some() {
+                self.filling = Some(field);
+                self.key = FastStr::from_static_str(field);
+                let key = de::value::BorrowedStrDeserializer::new(field);
+                return seed.deserialize(key).map(Some);
+            }
+        }
+        Ok(None)
     }
 }
 
@@ -992,7 +1751,12 @@ impl<'de, 'a, R: Reader<'de> + 'a> de::MapAccess<'de> for MapAccess<'a, R> {
     where
         K: de::DeserializeSeed<'de>,
     {
+        tri!(self.de.parser.poll_cancelled(self.count));
+        self.count += 1;
         let peek = match self.de.parser.skip_space_peek() {
+            Some(b'}') if !self.missing.is_empty() => {
+                return self.next_default_key(seed);
+            }
             Some(b'}') => {
                 return Ok(None);
             }
@@ -1015,6 +1779,20 @@ impl<'de, 'a, R: Reader<'de> + 'a> de::MapAccess<'de> for MapAccess<'a, R> {
         };
 
         match peek {
+            Some(b'"') if self.de.pointer.is_some() || self.de.defaults_path.is_some() => {
+                let start = self.de.parser.read.index();
+                let key = tri!(seed.deserialize(MapKey { de: &mut *self.de }));
+                let end = self.de.parser.read.index();
+                self.key = tri!(self.de.raw_key(start, end));
+                if let Some((_, missing)) = self
+                    .missing
+                    .iter_mut()
+                    .find(|(f, _)| *f == self.key.as_str())
+                {
+                    *missing = false;
+                }
+                Ok(Some(key))
+            }
             Some(b'"') => seed.deserialize(MapKey { de: &mut *self.de }).map(Some),
             Some(b'}') => Err(self.de.parser.error(ErrorCode::TrailingComma)),
             Some(_) => Err(self.de.parser.error(ErrorCode::ExpectObjectKeyOrEnd)),
@@ -1046,8 +1824,24 @@ impl<'de, 'a, R: Reader<'de> + 'a> de::MapAccess<'de> for MapAccess<'a, R> {
     where
         V: de::DeserializeSeed<'de>,
     {
+        if let Some(field) = self.filling.take() {
+            let value = self
+                .de
+                .default_field(field)
+                .expect("the default value is checked");
+            let key = &mut self.key;
+            return self.de.with_pointer(
+                || PointerNode::Key(std::mem::take(key)),
+                |_| seed.deserialize(value),
+            );
+        }
+
         tri!(self.de.parser.parse_object_clo());
-        seed.deserialize(&mut *self.de)
+        let key = &mut self.key;
+        self.de.with_pointer(
+            || PointerNode::Key(std::mem::take(key)),
+            |de| seed.deserialize(de),
+        )
     }
 }
 
@@ -1320,9 +2114,51 @@ where
         identifier ignored_any
     }
 }
+
+// Map the index in the lossy repr of `json` back to the index in `json`, each invalid UTF-8
+// sequence is replaced with one `\uFFFD`.
+fn lossy_index(json: &[u8], index: usize) -> usize {
+    let (mut src, mut dst) = (0, 0);
+    loop {
+        let rest = &json[src..];
+        let (valid, invalid) = match std::str::from_utf8(rest) {
+            Ok(_) => (rest.len(), 0),
+            Err(e) => {
+                let valid = e.valid_up_to();
+                (valid, e.error_len().unwrap_or(rest.len() - valid))
+            }
+        };
+        if index <= dst + valid || invalid == 0 {
+            return src + index.saturating_sub(dst);
+        }
+        src += valid + invalid;
+        dst += valid + char::REPLACEMENT_CHARACTER.len_utf8();
+    }
+}
+
 //////////////////////////////////////////////////////////////////////////////
 
 fn from_trait<'de, R, T>(read: R) -> Result<T>
+where
+    R: Reader<'de>,
+    T: de::Deserialize<'de>,
+{
+    from_trait_with_cfg(read, DeserializeCfg::default())
+}
+
+fn from_trait_with_cfg<'de, R, T>(read: R, cfg: DeserializeCfg) -> Result<T>
+where
+    R: Reader<'de>,
+    T: de::Deserialize<'de>,
+{
+    from_trait_with_defaults(read, cfg, None)
+}
+
+fn from_trait_with_defaults<'de, R, T>(
+    read: R,
+    cfg: DeserializeCfg,
+    defaults: Option<&'de Value>,
+) -> Result<T>
 where
     R: Reader<'de>,
     T: de::Deserialize<'de>,
@@ -1336,29 +2172,19 @@ where
         )));
     }
 
-    let mut de = Deserializer::new(read);
-    #[cfg(feature = "arbitrary_precision")]
-    {
-        de = de.use_rawnumber();
+    let mut de = Deserializer::new(read).with_cfg(cfg.with_features());
+    if let Some(defaults) = defaults {
+        de = de.with_defaults(defaults);
     }
-
-    #[cfg(feature = "use_raw")]
-    {
-        de = de.use_raw();
-    }
-
-    #[cfg(feature = "utf8_lossy")]
-    {
-        de = de.utf8_lossy();
-    }
-
     let value = tri!(de::Deserialize::deserialize(&mut de));
 
     // Make sure the whole stream has been consumed.
     tri!(de.parser.parse_trailing());
 
-    // check invalid utf8
-    tri!(de.parser.read.check_utf8_final());
+    // check invalid utf8, which has been replaced in the lossy mode
+    if !de.parser.cfg.utf8_lossy {
+        tri!(de.parser.read.check_utf8_final());
+    }
     Ok(value)
 }
 
@@ -1382,6 +2208,43 @@ where
     from_trait(Read::new(json, false))
 }
 
+/// The size of the padding required by [`from_slice_padded_unchecked`].
+pub const PADDING_SIZE: usize = Value::PADDING_SIZE;
+
+/// Parse the JSON text into a [`Value`] in place. It is faster than [`from_slice`] for the large
+/// JSON, because the JSON text is not copied into an internal padded buffer.
+///
+/// The `json` is the JSON text followed by [`PADDING_SIZE`] bytes of padding, the content of the
+/// padding is overwritten when parsing. The JSON text is also modified when unescaping strings.
+///
+/// # Safety
+///
+/// - The JSON text must be valid UTF-8.
+/// - The `json` must outlive the returned `Value` and the values cloned from it, because the
+///   strings in them may be borrowed from `json`. And `json` must not be modified while they are
+///   alive.
+///
+/// # Panics
+///
+/// Panics if the length of `json` is less than [`PADDING_SIZE`].
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{from_slice_padded_unchecked, JsonValueTrait, PADDING_SIZE};
+///
+/// let mut buf = br#"{"a": [1, "b\n"]}"#.to_vec();
+/// buf.resize(buf.len() + PADDING_SIZE, 0);
+///
+/// let value = unsafe { from_slice_padded_unchecked(&mut buf) }.unwrap();
+/// assert_eq!(value["a"][0].as_u64(), Some(1));
+/// assert_eq!(value["a"][1].as_str(), Some("b\n"));
+/// drop(value);
+/// ```
+pub unsafe fn from_slice_padded_unchecked(json: &mut [u8]) -> Result<Value> {
+    Value::parse_padded_inplace(json, Default::default())
+}
+
 /// Deserialize an instance of type `T` from a string of JSON text.
 pub fn from_str<'a, T>(s: &'a str) -> Result<T>
 where
@@ -1390,15 +2253,170 @@ where
     from_trait(Read::new(s.as_bytes(), false))
 }
 
-/// Deserialize an instance of type `T` from a Reader
-pub fn from_reader<R, T>(mut reader: R) -> Result<T>
+/// Deserialize an instance of type `T` from a string of JSON text, with the options in `cfg`.
+///
+/// It is the same as [`Deserializer::with_cfg`] but checks the trailing characters, which is
+/// convenient in the generic code and macros.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{from_str_with_cfg, DeserializeCfg, Value};
+///
+/// let mut cfg = DeserializeCfg::default();
+/// cfg.extended_numbers = true;
+///
+/// let value: Value = from_str_with_cfg(r#"{"mask": 0xFF}"#, cfg).unwrap();
+/// assert_eq!(value["mask"], 255);
+/// assert!(from_str_with_cfg::<Value>("[1] 2", cfg).is_err());
+/// ```
+pub fn from_str_with_cfg<'a, T>(s: &'a str, cfg: DeserializeCfg) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    from_trait_with_cfg(Read::new(s.as_bytes(), false), cfg)
+}
+
+/// Deserialize an instance of type `T` from bytes of JSON text, with the options in `cfg`.
+///
+/// The same as [`from_str_with_cfg`], and the invalid UTF-8 is handled by `cfg.utf8_policy`, which
+/// is an error by default.
+pub fn from_slice_with_cfg<'a, T>(json: &'a [u8], cfg: DeserializeCfg) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    from_trait_with_cfg(Read::new(json, true), cfg)
+}
+
+/// Deserialize an instance of type `T` from bytes of JSON text, and fill the missing struct
+/// fields from `defaults` by their JSON pointers.
+///
+/// It is the same as [`Deserializer::with_defaults`] but checks the trailing characters.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use sonic_rs::{from_slice_with_defaults, json};
+///
+/// #[derive(Deserialize)]
+/// struct Log {
+///     level: String,
+///     path: String,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     log: Log,
+///     workers: u32,
+/// }
+///
+/// // the layered defaults, such as the site defaults over the builtin defaults
+/// let defaults = json!({"log": {"level": "info", "path": "/var/log/app"}, "workers": 4});
+///
+/// let config: Config = from_slice_with_defaults(br#"{"log": {"level": "debug"}}"#, &defaults).unwrap();
+/// assert_eq!(config.log.level, "debug");
+/// assert_eq!(config.log.path, "/var/log/app");
+/// assert_eq!(config.workers, 4);
+/// ```
+pub fn from_slice_with_defaults<'a, T>(json: &'a [u8], defaults: &'a Value) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    from_trait_with_defaults(
+        Read::new(json, true),
+        DeserializeCfg::default(),
+        Some(defaults),
+    )
+}
+
+/// Deserialize an instance of type `T` from a Reader.
+///
+/// The JSON text is read in chunks of 64 KiB. The text no longer than a chunk is parsed as a whole,
+/// and the large arrays and objects are deserialized element by element, so the memory is bounded
+/// by the largest element instead of the whole JSON text. See
+/// [`from_reader_with_chunk_size`][crate::from_reader_with_chunk_size] for the details.
+///
+/// With the `parallel-io` feature, `from_reader_parallel` also reads the next chunk on another
+/// thread while deserializing the current chunk.
+pub fn from_reader<R, T>(reader: R) -> Result<T>
+where
+    R: std::io::Read,
+    T: de::DeserializeOwned,
+{
+    super::from_reader_with_chunk_size(reader, super::incremental::DEFAULT_CHUNK_SIZE)
+}
+
+/// Deserialize an instance of type `T` from a Reader, the same as [`from_reader`], but the next
+/// chunk is read on another thread while the current chunk is deserialized.
+///
+/// It overlaps the IO and the parsing for the large bodies read from the network, and at most two
+/// chunks are read ahead. The JSON text no longer than a chunk is parsed on the current thread,
+/// without spawning a thread.
+///
+/// The reader thread is not joined, so an error is returned at once even if the reader is blocked,
+/// such as reading from an idle socket. The reader is dropped on the reader thread after its
+/// pending read returns.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// use sonic_rs::from_reader_parallel;
+///
+/// let json = format!("[{}]", vec![r#"{"id": 1}"#; 10000].join(","));
+/// let v: Vec<sonic_rs::Value> = from_reader_parallel(Cursor::new(json)).unwrap();
+/// assert_eq!(v.len(), 10000);
+/// ```
+#[cfg(feature = "parallel-io")]
+pub fn from_reader_parallel<R, T>(reader: R) -> Result<T>
+where
+    R: std::io::Read + Send + 'static,
+    T: de::DeserializeOwned,
+{
+    super::pipelined::from_reader(reader, super::incremental::DEFAULT_CHUNK_SIZE)
+}
+
+/// Deserialize an instance of type `T` from a Reader, pre-allocating `capacity` bytes for the
+/// JSON text read from the reader.
+///
+/// The whole JSON text is read into memory before parsing.
+pub fn from_reader_with_capacity<R, T>(reader: R, capacity: usize) -> Result<T>
+where
+    R: std::io::Read,
+    T: de::DeserializeOwned,
+{
+    let mut data = Vec::with_capacity(capacity);
+    from_reader_with_buffer(reader, &mut data)
+}
+
+/// Deserialize an instance of type `T` from a Reader, reading the JSON text into the caller
+/// provided `buf`.
+///
+/// The `buf` is cleared before reading, and holds the whole JSON text after returning. Reusing
+/// the same buffer for similar-sized inputs avoids allocating again.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::from_reader_with_buffer;
+///
+/// let mut buf = Vec::with_capacity(64);
+/// for json in [r#"[1, 2]"#, r#"[3, 4, 5]"#] {
+///     let v: Vec<u32> = from_reader_with_buffer(json.as_bytes(), &mut buf).unwrap();
+///     assert_eq!(buf, json.as_bytes());
+///     assert!(!v.is_empty());
+/// }
+/// ```
+pub fn from_reader_with_buffer<R, T>(mut reader: R, buf: &mut Vec<u8>) -> Result<T>
 where
     R: std::io::Read,
     T: de::DeserializeOwned,
 {
-    let mut data = Vec::new();
-    if let Err(e) = reader.read_to_end(&mut data) {
+    buf.clear();
+    if let Err(e) = reader.read_to_end(buf) {
         return Err(Error::io(e));
     };
-    from_slice(data.as_slice())
+    from_slice(buf.as_slice())
 }
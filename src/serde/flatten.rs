@@ -0,0 +1,354 @@
+//! Buffer the fields of a JSON object as raw spans, and deserialize several structs from them.
+
+use std::{borrow::Cow, fmt};
+
+use serde::{
+    de::{
+        self,
+        value::{BorrowedStrDeserializer, StrDeserializer},
+        DeserializeSeed, MapAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+
+use super::untagged::{RawSpan, RawVisitor, TOKEN};
+use crate::{
+    error::{Error, ErrorCode, Result},
+    input::JsonSlice,
+    lazyvalue::{value::HasEsc, LazyValue},
+};
+
+/// The fields of a JSON object buffered as raw JSON spans, to deserialize several structs from
+/// the same object, such as the `#[serde(flatten)]` fields.
+///
+/// The `#[serde(flatten)]` fields buffer the unknown fields into the serde `Content` tree, which
+/// parses every nested value and copies the strings before the flattened structs are
+/// deserialized. `Flatten` skips each value once and keeps its raw JSON text borrowed from the
+/// input as a [`LazyValue`], then [`extract`][Flatten::extract] deserializes a struct from the
+/// buffered fields, parsing only the values of its own fields. The borrowed strings,
+/// [`RawNumber`][crate::RawNumber]s and [`LazyValue`]s work in the extracted structs.
+///
+/// It requires the sonic-rs deserializer, the same as [`Untagged`][crate::Untagged], so it is
+/// used in a `Deserialize` impl instead of a `#[serde(flatten)]` field.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{de::Error, Deserialize, Deserializer};
+/// use sonic_rs::Flatten;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Meta<'a> {
+///     id: u64,
+///     source: &'a str,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Body {
+///     text: String,
+/// }
+///
+/// // the same as the struct with two `#[serde(flatten)]` fields
+/// #[derive(Debug)]
+/// struct Message<'a> {
+///     meta: Meta<'a>,
+///     body: Body,
+/// }
+///
+/// impl<'de> Deserialize<'de> for Message<'de> {
+///     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+///         let fields = Flatten::new(deserializer)?;
+///         Ok(Message {
+///             meta: fields.extract().map_err(D::Error::custom)?,
+///             body: fields.extract().map_err(D::Error::custom)?,
+///         })
+///     }
+/// }
+///
+/// let json = r#"{"id": 1, "source": "web", "text": "hi\n", "extra": [1, 2]}"#;
+/// let msg: Message = sonic_rs::from_str(json).unwrap();
+/// assert_eq!(msg.meta.id, 1);
+/// assert_eq!(msg.meta.source, "web");
+/// assert_eq!(msg.body.text, "hi\n");
+/// ```
+#[derive(Debug, Default)]
+pub struct Flatten<'de> {
+    fields: Vec<Field<'de>>,
+}
+
+// The buffered field, and the span of its value to parse it again with the options of the
+// deserializer.
+#[derive(Debug)]
+struct Field<'de> {
+    key: Cow<'de, str>,
+    value: LazyValue<'de>,
+    span: RawSpan<'de>,
+}
+
+impl<'de> Flatten<'de> {
+    /// Skip the fields of the JSON object from the deserializer, and buffer their raw JSON text.
+    pub fn new<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Self::deserialize(deserializer)
+    }
+
+    /// Return the number of the buffered fields.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Return whether no fields are buffered.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Return the value of the first field named `key`.
+    pub fn get(&self, key: &str) -> Option<&LazyValue<'de>> {
+        self.fields.iter().find(|f| f.key == key).map(|f| &f.value)
+    }
+
+    /// Iterate the buffered fields in the order of the JSON object.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &LazyValue<'de>)> {
+        self.fields.iter().map(|f| (f.key.as_ref(), &f.value))
+    }
+
+    /// Deserialize `T` from the buffered fields, as if `T` is deserialized from the JSON object.
+    /// Only the values of the fields visited by `T` are parsed, with the options of the
+    /// deserializer which buffered the fields, and the errors have the positions in its input.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if `T` can not be deserialized from the fields, such as a missing field or
+    /// a mismatched type.
+    pub fn extract<T>(&self) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        T::deserialize(FieldsDeserializer {
+            fields: self.fields.iter(),
+            value: None,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Flatten<'de> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FlattenVisitor)
+    }
+}
+
+struct FlattenVisitor;
+
+impl<'de> Visitor<'de> for FlattenVisitor {
+    type Value = Flatten<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut fields = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(Key(key)) = map.next_key()? {
+            let span = map.next_value_seed(RawSeed)?;
+            let value = LazyValue::new(JsonSlice::Raw(span.as_str().as_bytes()), HasEsc::Possible);
+            fields.push(Field { key, value, span });
+        }
+        Ok(Flatten { fields })
+    }
+}
+
+// The key borrowed from the input if it has no escapes.
+struct Key<'de>(Cow<'de, str>);
+
+impl<'de> Deserialize<'de> for Key<'de> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = Key<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a JSON object key")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Self::Value, E> {
+                Ok(Key(Cow::Owned(v.to_owned())))
+            }
+
+            fn visit_borrowed_str<E: de::Error>(
+                self,
+                v: &'de str,
+            ) -> std::result::Result<Self::Value, E> {
+                Ok(Key(Cow::Borrowed(v)))
+            }
+        }
+
+        deserializer.deserialize_str(KeyVisitor)
+    }
+}
+
+// The raw JSON text of the value, skipped by the sonic-rs deserializer.
+struct RawSeed;
+
+impl<'de> DeserializeSeed<'de> for RawSeed {
+    type Value = RawSpan<'de>;
+
+    fn deserialize<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let visitor = RawVisitor::new(ErrorCode::IncompatibleFlatten);
+        deserializer.deserialize_newtype_struct(TOKEN, visitor)
+    }
+}
+
+// Deserialize the buffered fields as a JSON object.
+struct FieldsDeserializer<'a, 'de> {
+    fields: std::slice::Iter<'a, Field<'de>>,
+    value: Option<&'a RawSpan<'de>>,
+}
+
+impl<'de> Deserializer<'de> for FieldsDeserializer<'_, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> MapAccess<'de> for FieldsDeserializer<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let Some(field) = self.fields.next() else {
+            return Ok(None);
+        };
+        self.value = Some(&field.span);
+        match &field.key {
+            Cow::Borrowed(key) => seed
+                .deserialize(BorrowedStrDeserializer::new(*key))
+                .map(Some),
+            Cow::Owned(key) => seed.deserialize(StrDeserializer::new(key)).map(Some),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.value
+            .take()
+            .expect("next_value_seed is called before next_key_seed")
+            .deserialize(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{from_str, RawNumber};
+
+    #[derive(Debug, Deserialize)]
+    struct Meta<'a> {
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+        id: RawNumber,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Debug)]
+    struct Item<'a> {
+        meta: Meta<'a>,
+        rest: Flatten<'a>,
+    }
+
+    impl<'de> Deserialize<'de> for Item<'de> {
+        fn deserialize<D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> std::result::Result<Self, D::Error> {
+            let rest = Flatten::new(deserializer)?;
+            let meta = rest.extract().map_err(de::Error::custom)?;
+            Ok(Item { meta, rest })
+        }
+    }
+
+    #[test]
+    fn test_flatten() {
+        let json = r#"{"id": 12345678901234567890, "name": "a", "tags": ["\"x\"", {"k": 1}]}"#;
+        let item: Item = from_str(json).unwrap();
+        // the strings are borrowed from the input, and the numbers are raw
+        assert!(matches!(item.meta.name, Cow::Borrowed("a")));
+        assert_eq!(item.meta.id.as_str(), "12345678901234567890");
+
+        let rest = &item.rest;
+        assert_eq!(rest.len(), 3);
+        let tags = rest.get("tags").unwrap().as_raw_str();
+        assert_eq!(tags, r#"["\"x\"", {"k": 1}]"#);
+        let tags: Vec<LazyValue> = from_str(tags).unwrap();
+        assert_eq!(tags[1].as_raw_str(), r#"{"k": 1}"#);
+
+        // the duplicate and escaped keys are kept in order
+        let fields: Flatten = from_str(r#"{"a": 1, "\u0062": true, "a": "x"}"#).unwrap();
+        let keys: Vec<_> = fields.iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, ["a", "b", "a"]);
+        assert_eq!(fields.get("a").unwrap().as_raw_str(), "1");
+
+        // the unknown fields
+        let point: Flatten = from_str(r#"{"x": 1, "y": -2}"#).unwrap();
+        let p: Point = point.extract().unwrap();
+        assert_eq!((p.x, p.y), (1, -2));
+        let point: Flatten = from_str(r#"{"x": 1, "y": -2, "z": 3}"#).unwrap();
+        assert!(point.extract::<Point>().is_err());
+        assert!(point.extract::<Meta>().is_err());
+
+        // the values are parsed with the options of the deserializer, and the errors have the
+        // positions in the input
+        let json = "{\n  \"x\": 0x10,\n  \"y\": \"a\"\n}";
+        let mut cfg = crate::DeserializeCfg::default();
+        cfg.extended_numbers = true;
+        let point: Flatten = crate::from_str_with_cfg(json, cfg).unwrap();
+        let err = point.extract::<Point>().unwrap_err();
+        let expect = crate::from_str_with_cfg::<Point>(json, cfg).unwrap_err();
+        assert_eq!((err.line(), err.column()), (expect.line(), expect.column()));
+        assert_eq!(err.line(), 3);
+
+        assert!(from_str::<Flatten>("[1]").is_err());
+        assert!(from_str::<Flatten>(r#"{"a": [1}"#).is_err());
+        assert!(serde_json::from_str::<Flatten>(r#"{"a": 1}"#).is_err());
+    }
+}
@@ -0,0 +1,347 @@
+//! Framing the JSON texts in a byte stream, such as a socket.
+
+use std::{
+    io::{self, BufRead, Read, Write},
+    marker::PhantomData,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{de::tri, from_slice, to_writer};
+use crate::error::{make_error, Error, Result};
+
+// The record separator in RFC 7464.
+const RS: u8 = 0x1E;
+
+/// The framing of the JSON texts in a byte stream, used by [`FramedWriter`] and [`FramedReader`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framed {
+    /// Each JSON text is prefixed with its length, as a big-endian `u32`.
+    LengthPrefixedU32,
+    /// The JSON text sequences in [RFC 7464](https://www.rfc-editor.org/rfc/rfc7464). Each JSON
+    /// text is prefixed with the record separator `0x1E` and followed by a line feed.
+    Rfc7464,
+}
+
+/// Serialize the values into an [`io::Write`] with the framing, one frame for each value.
+///
+/// Each value is serialized into an internal buffer at first, which is reused for the following
+/// values, and then written into the writer with the framing.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{Framed, FramedWriter};
+///
+/// let mut writer = FramedWriter::new(Vec::new(), Framed::Rfc7464);
+/// writer.write_value(&[1, 2]).unwrap();
+/// writer.write_value(&"a").unwrap();
+/// assert_eq!(writer.into_inner(), b"\x1e[1,2]\n\x1e\"a\"\n");
+///
+/// let mut writer = FramedWriter::new(Vec::new(), Framed::LengthPrefixedU32);
+/// writer.write_value(&[1, 2]).unwrap();
+/// assert_eq!(writer.into_inner(), b"\x00\x00\x00\x05[1,2]");
+/// ```
+pub struct FramedWriter<W> {
+    inner: W,
+    framing: Framed,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> FramedWriter<W> {
+    /// Create a framed writer over `inner`.
+    pub fn new(inner: W, framing: Framed) -> Self {
+        Self {
+            inner,
+            framing,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Serialize the `value` and write it as a frame.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the serialization or writing fails, or the serialized value is longer
+    /// than `u32::MAX` with [`Framed::LengthPrefixedU32`].
+    pub fn write_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.buf.clear();
+        tri!(to_writer(&mut self.buf, value));
+        match self.framing {
+            Framed::LengthPrefixedU32 => {
+                let Ok(len) = u32::try_from(self.buf.len()) else {
+                    return Err(make_error(format!(
+                        "the frame is too large, len is {}",
+                        self.buf.len()
+                    )));
+                };
+                tri!(self.inner.write_all(&len.to_be_bytes()).map_err(Error::io));
+                self.inner.write_all(&self.buf).map_err(Error::io)
+            }
+            Framed::Rfc7464 => {
+                tri!(self.inner.write_all(&[RS]).map_err(Error::io));
+                tri!(self.inner.write_all(&self.buf).map_err(Error::io));
+                self.inner.write_all(b"\n").map_err(Error::io)
+            }
+        }
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.flush().map_err(Error::io)
+    }
+
+    /// Unwrap the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Read the framed JSON texts from an [`io::BufRead`], which are written by [`FramedWriter`] or
+/// other producers with the same framing.
+///
+/// For [`Framed::Rfc7464`], the bytes before the first record separator and the empty records
+/// are skipped. An invalid JSON text only fails its own frame, so the following frames can
+/// still be read.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{Framed, FramedReader};
+///
+/// let input = &b"\x1e{\"a\": 1}\n\x1e[1, 2]\n"[..];
+/// let mut reader = FramedReader::new(input, Framed::Rfc7464);
+/// let value: sonic_rs::Value = reader.read_value().unwrap().unwrap();
+/// assert_eq!(value["a"], 1);
+/// assert_eq!(reader.next_frame().unwrap(), Some(&b"[1, 2]\n"[..]));
+/// assert_eq!(reader.next_frame().unwrap(), None);
+///
+/// // iterate the deserialized values
+/// let input = &b"\x00\x00\x00\x01\x31\x00\x00\x00\x01\x32"[..];
+/// let values: Vec<u32> = FramedReader::new(input, Framed::LengthPrefixedU32)
+///     .into_stream()
+///     .collect::<sonic_rs::Result<_>>()
+///     .unwrap();
+/// assert_eq!(values, [1, 2]);
+/// ```
+pub struct FramedReader<R> {
+    inner: R,
+    framing: Framed,
+    buf: Vec<u8>,
+    started: bool,
+}
+
+impl<R: BufRead> FramedReader<R> {
+    /// Create a framed reader over `inner`.
+    pub fn new(inner: R, framing: Framed) -> Self {
+        Self {
+            inner,
+            framing,
+            buf: Vec::new(),
+            started: false,
+        }
+    }
+
+    /// Read the next frame, without the framing bytes except the trailing line feed in
+    /// [`Framed::Rfc7464`]. Return `None` if the reader reaches the end.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if reading fails, or the stream ends in the middle of a length-prefixed
+    /// frame.
+    pub fn next_frame(&mut self) -> Result<Option<&[u8]>> {
+        self.buf.clear();
+        match self.framing {
+            Framed::LengthPrefixedU32 => {
+                let mut prefix = [0u8; 4];
+                let n = tri!(read_full(&mut self.inner, &mut prefix));
+                if n == 0 {
+                    return Ok(None);
+                }
+                if n < prefix.len() {
+                    return Err(unexpected_eof());
+                }
+
+                // don't trust the length to allocate the buffer
+                let len = u32::from_be_bytes(prefix) as u64;
+                let read = tri!((&mut self.inner)
+                    .take(len)
+                    .read_to_end(&mut self.buf)
+                    .map_err(Error::io));
+                if read as u64 != len {
+                    return Err(unexpected_eof());
+                }
+                Ok(Some(&self.buf))
+            }
+            Framed::Rfc7464 => {
+                if !self.started {
+                    // skip the garbage before the first record separator
+                    tri!(self.inner.read_until(RS, &mut self.buf).map_err(Error::io));
+                    if self.buf.last() != Some(&RS) {
+                        return Ok(None);
+                    }
+                    self.started = true;
+                }
+
+                loop {
+                    self.buf.clear();
+                    let n = tri!(self.inner.read_until(RS, &mut self.buf).map_err(Error::io));
+                    if n == 0 {
+                        return Ok(None);
+                    }
+                    if self.buf.last() == Some(&RS) {
+                        self.buf.pop();
+                    }
+                    if !self.buf.iter().all(u8::is_ascii_whitespace) {
+                        return Ok(Some(&self.buf));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Read and deserialize the next frame. Return `None` if the reader reaches the end.
+    pub fn read_value<T>(&mut self) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        match tri!(self.next_frame()) {
+            Some(frame) => from_slice(frame).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Convert into an iterator over the deserialized values of the frames.
+    pub fn into_stream<T>(self) -> FramedStream<R, T>
+    where
+        T: DeserializeOwned,
+    {
+        FramedStream {
+            reader: self,
+            data: PhantomData,
+            is_ending: false,
+        }
+    }
+
+    /// Unwrap the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// An iterator over the deserialized values of the frames, created by
+/// [`FramedReader::into_stream`].
+///
+/// The iteration stops after an I/O error, and continues after a frame with invalid JSON.
+pub struct FramedStream<R, T> {
+    reader: FramedReader<R>,
+    data: PhantomData<T>,
+    is_ending: bool,
+}
+
+impl<R, T> Iterator for FramedStream<R, T>
+where
+    R: BufRead,
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_ending {
+            return None;
+        }
+        match self.reader.read_value() {
+            Ok(value) => value.map(Ok),
+            Err(err) => {
+                // the stream is broken after an I/O error
+                self.is_ending = err.is_io();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+// Read until the buffer is full or EOF, return the read length.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(Error::io(e)),
+        }
+    }
+    Ok(read)
+}
+
+fn unexpected_eof() -> Error {
+    Error::io(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "the stream ends in the middle of a frame",
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{json, Value};
+
+    fn roundtrip(framing: Framed) {
+        let values = [json!({"a": [1, "\n"]}), json!(null), json!("\u{1e}")];
+        let mut writer = FramedWriter::new(Vec::new(), framing);
+        for v in &values {
+            writer.write_value(v).unwrap();
+        }
+        let out = writer.into_inner();
+
+        let got: Vec<Value> = FramedReader::new(&out[..], framing)
+            .into_stream()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(got, values);
+    }
+
+    #[test]
+    fn test_framed_roundtrip() {
+        roundtrip(Framed::LengthPrefixedU32);
+        roundtrip(Framed::Rfc7464);
+    }
+
+    #[test]
+    fn test_framed_reader_rfc7464() {
+        let input = b"garbage\x1e1\n\x1e\x1e \n\x1e[tru]\n\x1e\"a\"";
+        let mut stream = FramedReader::new(&input[..], Framed::Rfc7464).into_stream::<Value>();
+        assert_eq!(stream.next().unwrap().unwrap(), 1);
+        // the invalid text fails its own frame only
+        assert!(stream.next().unwrap().is_err());
+        assert_eq!(stream.next().unwrap().unwrap(), "a");
+        assert!(stream.next().is_none());
+
+        let mut reader = FramedReader::new(&b"no separator"[..], Framed::Rfc7464);
+        assert_eq!(reader.next_frame().unwrap(), None);
+    }
+
+    #[test]
+    fn test_framed_reader_length_prefixed() {
+        let mut reader = FramedReader::new(&b"\x00\x00\x00\x00"[..], Framed::LengthPrefixedU32);
+        assert_eq!(reader.next_frame().unwrap(), Some(&b""[..]));
+        assert_eq!(reader.next_frame().unwrap(), None);
+
+        // truncated prefix and payload
+        for input in [&b"\x00\x00"[..], b"\x00\x00\x00\x03[1"] {
+            let mut stream =
+                FramedReader::new(input, Framed::LengthPrefixedU32).into_stream::<Value>();
+            let err = stream.next().unwrap().unwrap_err();
+            assert_eq!(err.io_error_kind(), Some(io::ErrorKind::UnexpectedEof));
+            assert!(stream.next().is_none());
+        }
+
+        // the huge length is not allocated up front
+        let mut reader = FramedReader::new(&b"\xff\xff\xff\xff{}"[..], Framed::LengthPrefixedU32);
+        assert!(reader.next_frame().unwrap_err().is_io());
+    }
+}
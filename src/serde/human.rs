@@ -0,0 +1,460 @@
+//! Reformat the compact JSON into the styles of [`PrettyStyle::Human`] and
+//! [`PrettyStyle::ElementPerLine`].
+//!
+//! [`PrettyStyle::Human`]: crate::PrettyStyle::Human
+//! [`PrettyStyle::ElementPerLine`]: crate::PrettyStyle::ElementPerLine
+
+// The indent of the nested containers, the same as `to_string_pretty`.
+const INDENT: &[u8] = b"  ";
+
+// The measurement of a container, indexed by the order of its open bracket.
+#[derive(Debug, Clone, Copy, Default)]
+struct Measure {
+    // the width of the container written on one line, such as `{"a": 1, "b": [2, 3]}`
+    width: usize,
+    // the width of the widest key, only for the objects
+    key_width: usize,
+    // the index after the closing bracket
+    end: usize,
+    // the number of the containers inside, which are measured right after this one
+    inner: usize,
+}
+
+// The raw JSON written verbatim by the serializer, such as `LazyValue` and `RawNumber`, may have
+// whitespaces, which are skipped when reformatting.
+fn is_space(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+fn skip_space(json: &[u8], mut i: usize) -> usize {
+    while i < json.len() && is_space(json[i]) {
+        i += 1;
+    }
+    i
+}
+
+// The width of the text in chars, so that the non-ASCII chars are counted once.
+fn width(text: &[u8]) -> usize {
+    text.iter().filter(|&&b| b & 0xC0 != 0x80).count()
+}
+
+// The index after the closing quote of the string starting at `start`.
+fn string_end(json: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < json.len() {
+        match json[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    json.len()
+}
+
+// The end of the number or the literal starting at `start`.
+fn scalar_end(json: &[u8], start: usize) -> usize {
+    json[start..]
+        .iter()
+        .position(|&b| matches!(b, b',' | b':' | b']' | b'}') || is_space(b))
+        .map_or(json.len(), |n| start + n)
+}
+
+// Write `json[start..end]` without the whitespaces outside the strings.
+fn write_compact(json: &[u8], mut i: usize, end: usize, out: &mut Vec<u8>) {
+    while i < end {
+        match json[i] {
+            b'"' => {
+                let str_end = string_end(json, i);
+                out.extend_from_slice(&json[i..str_end]);
+                i = str_end;
+            }
+            b if is_space(b) => i += 1,
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+}
+
+fn newline(out: &mut Vec<u8>, depth: usize) {
+    out.push(b'\n');
+    for _ in 0..depth {
+        out.extend_from_slice(INDENT);
+    }
+}
+
+// Measure all the containers in one pass.
+fn measure(json: &[u8]) -> Vec<Measure> {
+    let mut measures = Vec::new();
+    // (the index in `measures`, the position of the open bracket, is an object)
+    let mut stack: Vec<(usize, usize, bool)> = Vec::new();
+    // the position in the JSON written on one line
+    let mut pos = 0;
+    // the last byte which is not a whitespace, to find the keys
+    let mut prev = 0;
+    let mut i = 0;
+    while i < json.len() {
+        let b = json[i];
+        match b {
+            b'{' | b'[' => {
+                stack.push((measures.len(), pos, b == b'{'));
+                measures.push(Measure::default());
+                pos += 1;
+                i += 1;
+            }
+            b']' | b'}' => {
+                pos += 1;
+                i += 1;
+                if let Some((idx, start, _)) = stack.pop() {
+                    let inner = measures.len() - idx - 1;
+                    let m = &mut measures[idx];
+                    m.width = pos - start;
+                    m.end = i;
+                    m.inner = inner;
+                }
+            }
+            // written as `, ` and `: `
+            b',' | b':' => {
+                pos += 2;
+                i += 1;
+            }
+            b'"' => {
+                let end = string_end(json, i);
+                let w = width(&json[i..end]);
+                if let Some(&(idx, _, true)) = stack.last() {
+                    if matches!(prev, b'{' | b',') {
+                        let m = &mut measures[idx];
+                        m.key_width = m.key_width.max(w);
+                    }
+                }
+                pos += w;
+                i = end;
+            }
+            b if is_space(b) => {
+                i += 1;
+                continue;
+            }
+            _ => {
+                let end = scalar_end(json, i);
+                pos += width(&json[i..end]);
+                i = end;
+            }
+        }
+        prev = b;
+    }
+    measures
+}
+
+struct Human<'a> {
+    json: &'a [u8],
+    measures: Vec<Measure>,
+    // the index of the next container in `measures`
+    next: usize,
+    max_width: usize,
+    align_keys: bool,
+    out: Vec<u8>,
+}
+
+impl Human<'_> {
+    fn newline(&mut self, depth: usize) {
+        newline(&mut self.out, depth);
+    }
+
+    // Write the value at `i` starting at the column `col`, and return the index after it.
+    fn value(&mut self, i: usize, depth: usize, col: usize) -> usize {
+        match self.json[i] {
+            b'{' | b'[' => {
+                let m = self.measures[self.next];
+                if col + m.width <= self.max_width || m.width == 2 {
+                    self.one_line(i)
+                } else {
+                    self.next += 1;
+                    self.expanded(i, depth, m)
+                }
+            }
+            b'"' => {
+                let end = string_end(self.json, i);
+                self.out.extend_from_slice(&self.json[i..end]);
+                end
+            }
+            _ => {
+                let end = scalar_end(self.json, i);
+                self.out.extend_from_slice(&self.json[i..end]);
+                end
+            }
+        }
+    }
+
+    // Write the container at `i` on one line, with a space after `,` and `:`.
+    fn one_line(&mut self, mut i: usize) -> usize {
+        let mut depth = 0;
+        loop {
+            match self.json[i] {
+                b @ (b'{' | b'[') => {
+                    self.next += 1;
+                    depth += 1;
+                    self.out.push(b);
+                    i += 1;
+                }
+                b @ (b']' | b'}') => {
+                    depth -= 1;
+                    self.out.push(b);
+                    i += 1;
+                    if depth == 0 {
+                        return i;
+                    }
+                }
+                b @ (b',' | b':') => {
+                    self.out.push(b);
+                    self.out.push(b' ');
+                    i += 1;
+                }
+                b'"' => {
+                    let end = string_end(self.json, i);
+                    self.out.extend_from_slice(&self.json[i..end]);
+                    i = end;
+                }
+                b if is_space(b) => i += 1,
+                _ => {
+                    let end = scalar_end(self.json, i);
+                    self.out.extend_from_slice(&self.json[i..end]);
+                    i = end;
+                }
+            }
+        }
+    }
+
+    // Write the container at `i` with one element or one entry per line.
+    fn expanded(&mut self, mut i: usize, depth: usize, m: Measure) -> usize {
+        let is_object = self.json[i] == b'{';
+        self.out.push(self.json[i]);
+        i += 1;
+        loop {
+            self.newline(depth + 1);
+            let mut col = INDENT.len() * (depth + 1);
+            i = skip_space(self.json, i);
+            if is_object {
+                let end = string_end(self.json, i);
+                let w = width(&self.json[i..end]);
+                self.out.extend_from_slice(&self.json[i..end]);
+                self.out.extend_from_slice(b": ");
+                let pad = if self.align_keys { m.key_width - w } else { 0 };
+                self.out.resize(self.out.len() + pad, b' ');
+                col += w + 2 + pad;
+                // skip the colon
+                i = skip_space(self.json, skip_space(self.json, end) + 1);
+            }
+            i = skip_space(self.json, self.value(i, depth + 1, col));
+            if self.json[i] == b',' {
+                self.out.push(b',');
+                i += 1;
+            } else {
+                break;
+            }
+        }
+        self.newline(depth);
+        self.out.push(self.json[i]);
+        i + 1
+    }
+}
+
+/// Reformat the compact JSON written by the serializer. The containers which fit within
+/// `max_width` columns are written on one line, and the others are expanded.
+pub(crate) fn reformat(json: &[u8], max_width: usize, align_keys: bool) -> Vec<u8> {
+    let mut human = Human {
+        json,
+        measures: measure(json),
+        next: 0,
+        max_width,
+        align_keys,
+        out: Vec::with_capacity(json.len() + json.len() / 2),
+    };
+    let start = skip_space(json, 0);
+    if start < json.len() {
+        human.value(start, 0, 0);
+    }
+    human.out
+}
+
+struct ElementPerLine<'a> {
+    json: &'a [u8],
+    measures: Vec<Measure>,
+    // the index of the next container in `measures`
+    next: usize,
+    out: Vec<u8>,
+}
+
+impl ElementPerLine<'_> {
+    // The end of the value at `i`, and skip the containers inside it.
+    fn skip_value(&mut self, i: usize) -> usize {
+        match self.json[i] {
+            b'{' | b'[' => {
+                let m = self.measures[self.next];
+                self.next += 1 + m.inner;
+                m.end
+            }
+            b'"' => string_end(self.json, i),
+            _ => scalar_end(self.json, i),
+        }
+    }
+
+    // Write the value at `i`, and return the index after it.
+    fn value(&mut self, i: usize, depth: usize) -> usize {
+        let b = self.json[i];
+        if !matches!(b, b'{' | b'[') || self.measures[self.next].width == 2 {
+            let end = self.skip_value(i);
+            write_compact(self.json, i, end, &mut self.out);
+            return end;
+        }
+
+        self.next += 1;
+        self.out.push(b);
+        let mut i = i + 1;
+        loop {
+            newline(&mut self.out, depth + 1);
+            i = skip_space(self.json, i);
+            if b == b'{' {
+                let key_end = string_end(self.json, i);
+                self.out.extend_from_slice(&self.json[i..key_end]);
+                self.out.extend_from_slice(b": ");
+                // skip the colon
+                let value = skip_space(self.json, skip_space(self.json, key_end) + 1);
+                i = self.value(value, depth + 1);
+            } else {
+                // the compact element
+                let end = self.skip_value(i);
+                write_compact(self.json, i, end, &mut self.out);
+                i = end;
+            }
+            i = skip_space(self.json, i);
+            if self.json[i] == b',' {
+                self.out.push(b',');
+                i += 1;
+            } else {
+                break;
+            }
+        }
+        newline(&mut self.out, depth);
+        self.out.push(self.json[i]);
+        i + 1
+    }
+}
+
+/// Reformat the compact JSON written by the serializer. The objects are expanded, and each
+/// element of the arrays is written compact on its own line.
+pub(crate) fn reformat_element_per_line(json: &[u8]) -> Vec<u8> {
+    let mut epl = ElementPerLine {
+        json,
+        measures: measure(json),
+        next: 0,
+        out: Vec::with_capacity(json.len() + json.len() / 4),
+    };
+    let start = skip_space(json, 0);
+    if start < json.len() {
+        epl.value(start, 0);
+    }
+    epl.out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn human(json: &str, max_width: usize, align_keys: bool) -> String {
+        String::from_utf8(reformat(json.as_bytes(), max_width, align_keys)).unwrap()
+    }
+
+    #[test]
+    fn test_human_reformat() {
+        let json =
+            r#"{"name":"sonic","tags":["a,b","c:]"],"nested":{"x":[1,2,{}],"long_key":[]},"e":{}}"#;
+        assert_eq!(
+            human(json, 120, false),
+            r#"{"name": "sonic", "tags": ["a,b", "c:]"], "nested": {"x": [1, 2, {}], "long_key": []}, "e": {}}"#
+        );
+        assert_eq!(
+            human(json, 50, false),
+            r#"{
+  "name": "sonic",
+  "tags": ["a,b", "c:]"],
+  "nested": {"x": [1, 2, {}], "long_key": []},
+  "e": {}
+}"#
+        );
+        assert_eq!(
+            human(json, 30, true),
+            r#"{
+  "name":   "sonic",
+  "tags":   ["a,b", "c:]"],
+  "nested": {
+    "x":        [1, 2, {}],
+    "long_key": []
+  },
+  "e":      {}
+}"#
+        );
+        // the width is counted in chars
+        assert_eq!(human(r#"["é\"","ü"]"#, 12, false), r#"["é\"", "ü"]"#);
+        assert_eq!(
+            human(r#"["é\"","ü"]"#, 11, false),
+            "[\n  \"é\\\"\",\n  \"ü\"\n]"
+        );
+        assert_eq!(human("[[],{}]", 0, false), "[\n  [],\n  {}\n]");
+        assert_eq!(human("1.5", 0, false), "1.5");
+        assert_eq!(human(r#""a""#, 0, false), r#""a""#);
+
+        // the raw JSON, such as `LazyValue`, is written verbatim with the whitespaces
+        let json = " { \"k\" : [ 1 , \"a b\" ] ,\n\t\"e\" : { } } ";
+        assert_eq!(human(json, 120, false), r#"{"k": [1, "a b"], "e": {}}"#);
+        assert_eq!(
+            human(json, 0, true),
+            "{\n  \"k\": [\n    1,\n    \"a b\"\n  ],\n  \"e\": {}\n}"
+        );
+        assert_eq!(human(" 1 ", 0, false), "1");
+    }
+
+    #[test]
+    fn test_element_per_line() {
+        let reformat =
+            |json: &str| String::from_utf8(reformat_element_per_line(json.as_bytes())).unwrap();
+
+        let json = r#"{"name":"sonic","deps":[{"name":"serde","features":["derive"]},"faststr"],"matrix":[[1,2],[]],"empty":[],"obj":{},"nested":{"x":["]","a,b"]}}"#;
+        assert_eq!(
+            reformat(json),
+            r#"{
+  "name": "sonic",
+  "deps": [
+    {"name":"serde","features":["derive"]},
+    "faststr"
+  ],
+  "matrix": [
+    [1,2],
+    []
+  ],
+  "empty": [],
+  "obj": {},
+  "nested": {
+    "x": [
+      "]",
+      "a,b"
+    ]
+  }
+}"#
+        );
+        assert_eq!(
+            reformat(r#"[{"a":"}"},1.5,null]"#),
+            "[\n  {\"a\":\"}\"},\n  1.5,\n  null\n]"
+        );
+        assert_eq!(reformat("[]"), "[]");
+        assert_eq!(reformat(r#""a""#), r#""a""#);
+        assert_eq!(reformat("-1"), "-1");
+
+        // the raw JSON, such as `LazyValue`, is written verbatim with the whitespaces
+        assert_eq!(
+            reformat(" { \"a\" : [ { \"b\" : \"x y\" } , [ ] ] ,\n\t\"c\" : { } } "),
+            "{\n  \"a\": [\n    {\"b\":\"x y\"},\n    []\n  ],\n  \"c\": {}\n}"
+        );
+        assert_eq!(reformat("[ [ 1 , [ 2 ] ] , 3 ]"), "[\n  [1,[2]],\n  3\n]");
+    }
+}
@@ -0,0 +1,1202 @@
+//! Deserialize the JSON text from a reader incrementally, with a bounded buffer.
+
+use std::{fmt, io, marker::PhantomData, result::Result as StdResult};
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer as _, Visitor};
+
+use super::de::{Deserializer, MAX_ALLOWED_DEPTH};
+use crate::{
+    config::DeserializeCfg,
+    error::{Error, ErrorCode, Result},
+    parser::Parser,
+    reader::{Position, Read, Reader},
+    serde::tri,
+};
+
+/// The default size of the chunks read by [`from_reader`][crate::from_reader].
+pub(crate) const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Deserialize an instance of type `T` from a Reader, reading the JSON text in chunks of
+/// `chunk_size` bytes.
+///
+/// The JSON text no longer than `chunk_size` is parsed as a whole, the same as reading it into
+/// memory and [`from_slice`][crate::from_slice]. Otherwise, the values no longer than `chunk_size`
+/// are parsed as a whole by the SIMD parser, from a window of the internal buffer. The longer
+/// arrays and objects are visited element by element,
+/// and the parsed bytes are dropped from the buffer, so the memory is bounded by the largest
+/// element instead of the whole JSON text, such as a large array of small objects.
+///
+/// The strings, numbers and the types parsed from the raw JSON text, such as [`Value`],
+/// [`LazyValue`] and [`RawValue`], are always buffered as a whole.
+///
+/// [`Value`]: crate::Value
+/// [`LazyValue`]: crate::LazyValue
+/// [`RawValue`]: crate::RawValue
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::from_reader_with_chunk_size;
+///
+/// let json = format!("[{}]", vec!["[1, 2]"; 1000].join(","));
+/// let v: Vec<Vec<u8>> = from_reader_with_chunk_size(json.as_bytes(), 64).unwrap();
+/// assert_eq!(v.len(), 1000);
+/// assert_eq!(v[999], [1, 2]);
+/// ```
+pub fn from_reader_with_chunk_size<R, T>(reader: R, chunk_size: usize) -> Result<T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    let mut de = IoDeserializer::new(reader, chunk_size, DeserializeCfg::default());
+    tri!(de.skip_bom());
+    if tri!(de.fill_whole()) {
+        return de.parse_whole();
+    }
+    let value = tri!(T::deserialize(&mut de));
+    tri!(de.end());
+    Ok(value)
+}
+
+// How the array or object longer than the chunk size is deserialized.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Stream {
+    // visited as a sequence
+    Seq,
+    // visited as a map
+    Map,
+    // visited as the type of the JSON value
+    Any,
+    // buffered as a whole
+    None,
+}
+
+// The value at the current position.
+enum Next {
+    // parse the buffered value ending at the index
+    Whole(usize),
+    // stream the array or object starting with the byte
+    Stream(u8),
+}
+
+pub(crate) struct IoDeserializer<R> {
+    reader: R,
+    // the bytes from `pos` are not parsed yet
+    buf: Vec<u8>,
+    pos: usize,
+    chunk_size: usize,
+    eof: bool,
+    // the position of `buf[0]` in the whole JSON text
+    offset: usize,
+    line: usize,
+    column: usize,
+    cfg: DeserializeCfg,
+    skip_bom: bool,
+    remaining_depth: u8,
+}
+
+// Parse the value in `buf[pos..end]` with the window deserializer `$de`, and move to the end of
+// the value.
+macro_rules! parse_window {
+    ($self:ident, $end:expr, |$de:ident| $parse:expr) => {{
+        let end = $end;
+        let ret = {
+            let read = Read::new(&$self.buf[$self.pos..end], true);
+            let mut $de = Deserializer::new(read).with_cfg($self.cfg);
+            $parse.and_then(|value| end_window(&mut $de).map(|_| value))
+        };
+        match ret {
+            Ok(value) => {
+                $self.pos = end;
+                Ok(value)
+            }
+            Err(err) => Err($self.shift($self.fix_window(err), $self.pos)),
+        }
+    }};
+}
+
+// Make sure the window has been consumed, the same as `from_slice`.
+fn end_window(de: &mut Deserializer<Read<'_>>) -> Result<()> {
+    tri!(de.parser.parse_trailing());
+    if !de.parser.cfg.utf8_lossy {
+        tri!(de.parser.read.check_utf8_final());
+    }
+    Ok(())
+}
+
+impl<R: io::Read> IoDeserializer<R> {
+    pub(crate) fn new(reader: R, chunk_size: usize, cfg: DeserializeCfg) -> Self {
+        let mut cfg = cfg.with_features();
+        let skip_bom = cfg.skip_bom;
+        // the BOM is only skipped at the start of the stream, not of each window
+        cfg.skip_bom = false;
+        Self {
+            reader,
+            buf: Vec::new(),
+            pos: 0,
+            chunk_size: chunk_size.max(1),
+            eof: false,
+            offset: 0,
+            line: 1,
+            column: 0,
+            cfg,
+            skip_bom,
+            remaining_depth: MAX_ALLOWED_DEPTH,
+        }
+    }
+
+    // Read the next chunk after dropping the parsed bytes. Return `false` at the end of stream.
+    fn fill(&mut self) -> Result<bool> {
+        if self.eof {
+            return Ok(false);
+        }
+        self.compact();
+
+        let len = self.buf.len();
+        self.buf.resize(len + self.chunk_size, 0);
+        loop {
+            match self.reader.read(&mut self.buf[len..]) {
+                Ok(n) => {
+                    self.buf.truncate(len + n);
+                    self.eof = n == 0;
+                    return Ok(n > 0);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.buf.truncate(len);
+                    return Err(Error::io(e));
+                }
+            }
+        }
+    }
+
+    // Read the whole JSON text if it is no longer than a chunk. Return `false` if it is longer,
+    // then the read bytes are kept in the buffer.
+    fn fill_whole(&mut self) -> Result<bool> {
+        while self.buf.len() - self.pos <= self.chunk_size {
+            if !tri!(self.fill()) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // Parse the whole JSON text in the buffer, the same as `from_slice`.
+    fn parse_whole<T: DeserializeOwned>(&mut self) -> Result<T> {
+        let end = self.buf.len();
+        parse_window!(self, end, |de| T::deserialize(&mut de))
+    }
+
+    fn compact(&mut self) {
+        if self.pos == 0 {
+            return;
+        }
+        // count the lines in bulk, which is vectorized, instead of branching on each byte
+        let parsed = &self.buf[..self.pos];
+        match parsed.iter().rposition(|&ch| ch == b'\n') {
+            Some(last) => {
+                self.line += parsed.iter().filter(|&&ch| ch == b'\n').count();
+                self.column = parsed.len() - last - 1;
+            }
+            None => self.column += parsed.len(),
+        }
+        self.offset += self.pos;
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+    }
+
+    fn skip_bom(&mut self) -> Result<()> {
+        if !self.skip_bom {
+            return Ok(());
+        }
+        while self.buf.len() - self.pos < UTF8_BOM.len() && tri!(self.fill()) {}
+        if self.buf[self.pos..].starts_with(UTF8_BOM) {
+            self.pos += UTF8_BOM.len();
+        }
+        Ok(())
+    }
+
+    // Make sure the whole stream has been consumed.
+    fn end(&mut self) -> Result<()> {
+        match tri!(self.peek()) {
+            None => Ok(()),
+            Some(_) => Err(self.error(ErrorCode::TrailingCharacters)),
+        }
+    }
+
+    // Skip the whitespaces and peek the next byte, reading more bytes if necessary.
+    fn peek(&mut self) -> Result<Option<u8>> {
+        loop {
+            while let Some(&ch) = self.buf.get(self.pos) {
+                if !matches!(ch, b' ' | b'\t' | b'\n' | b'\r') {
+                    return Ok(Some(ch));
+                }
+                self.pos += 1;
+            }
+            if !tri!(self.fill()) {
+                return Ok(None);
+            }
+        }
+    }
+
+    // Find the value at the current position. The arrays and objects longer than the chunk size
+    // are streamed if `stream` accepts them, otherwise the value is buffered as a whole.
+    fn next_value(&mut self, stream: Stream) -> Result<Next> {
+        let Some(first) = tri!(self.peek()) else {
+            return Err(self.error(ErrorCode::EofWhileParsing));
+        };
+        let streamed = match first {
+            b'[' => matches!(stream, Stream::Seq | Stream::Any),
+            b'{' => matches!(stream, Stream::Map | Stream::Any),
+            _ => false,
+        };
+        let limit = if streamed {
+            self.chunk_size
+        } else {
+            usize::MAX
+        };
+        match tri!(self.value_end(limit)) {
+            Some(end) => Ok(Next::Whole(end)),
+            None => Ok(Next::Stream(first)),
+        }
+    }
+
+    // Find the end of the value at the current position, reading more bytes if necessary. Return
+    // `None` if the value is an array or object longer than `limit`.
+    //
+    // The arrays, objects and strings are delimited by the SIMD skipping of the parser, and
+    // validated by the parser later. If the value is truncated in the buffer, it is skipped again
+    // after the buffered bytes are doubled, so each byte is skipped twice at most on average. The
+    // indexes are relative to `pos`, because the buffer is compacted when filling.
+    fn value_end(&mut self, limit: usize) -> Result<Option<usize>> {
+        match self.buf[self.pos] {
+            b'[' | b'{' | b'"' => {}
+            // the parser reports the unexpected delimiters
+            b']' | b'}' | b',' | b':' => return Ok(Some(self.pos + 1)),
+            _ => return self.scalar_end().map(Some),
+        }
+
+        loop {
+            let mut parser = Parser::new(Read::new(&self.buf[self.pos..], false));
+            match parser.skip_one_unchecked() {
+                Ok((value, _)) => return Ok(Some(self.pos + value.len())),
+                Err(err) if err.is_eof() && !self.eof => {}
+                // the parser reports the truncated value
+                Err(_) => return Ok(Some(self.buf.len())),
+            }
+
+            let skipped = self.buf.len() - self.pos;
+            if skipped > limit {
+                return Ok(None);
+            }
+            let want = skipped.saturating_mul(2).min(limit.saturating_add(1));
+            while self.buf.len() - self.pos < want && tri!(self.fill()) {}
+        }
+    }
+
+    // Find the end of the number or literal at the current position, which ends at the
+    // whitespaces or the delimiters.
+    fn scalar_end(&mut self) -> Result<usize> {
+        let mut i = 1;
+        loop {
+            while let Some(&ch) = self.buf.get(self.pos + i) {
+                if matches!(
+                    ch,
+                    b' ' | b'\t' | b'\n' | b'\r' | b'[' | b']' | b'{' | b'}' | b',' | b':' | b'"'
+                ) {
+                    return Ok(self.pos + i);
+                }
+                i += 1;
+            }
+            if !tri!(self.fill()) {
+                // the parser reports the truncated value
+                return Ok(self.buf.len());
+            }
+        }
+    }
+
+    // Record the error at the current position.
+    #[cold]
+    fn error(&self, code: ErrorCode) -> Error {
+        let err = if self.cfg.lightweight_errors {
+            Error::lightweight(code, self.pos)
+        } else {
+            Error::syntax_with(code, &self.buf, self.pos, self.cfg.error_snippet_len)
+        };
+        self.shift(err, 0)
+    }
+
+    // Move the position of the error in the text from `buf[start]` to the whole JSON text.
+    #[cold]
+    fn shift(&self, err: Error, start: usize) -> Error {
+        let position = Position::from_index(start, &self.buf);
+        let (line, column) = if position.line == 1 {
+            (self.line, self.column + position.column)
+        } else {
+            (self.line + position.line - 1, position.column)
+        };
+        err.shift_position(self.offset + start, line, column)
+    }
+
+    // The errors of the visitors in the window have no position, record them at the start of
+    // the window.
+    #[cold]
+    fn fix_window(&self, err: Error) -> Error {
+        if err.line() == 0 && !self.cfg.lightweight_errors {
+            Error::syntax_with(
+                err.error_code(),
+                &self.buf[self.pos..],
+                0,
+                self.cfg.error_snippet_len,
+            )
+        } else {
+            err
+        }
+    }
+
+    // Record the errors of the visitors at the current position, the same as
+    // `Parser::fix_position`.
+    #[cold]
+    fn fix_position(&self, err: Error) -> Error {
+        if err.line() == 0 {
+            self.error(err.error_code())
+        } else {
+            err
+        }
+    }
+
+    fn enter(&mut self) -> Result<()> {
+        self.remaining_depth -= 1;
+        if self.remaining_depth == 0 {
+            return Err(self.error(ErrorCode::RecursionLimitExceeded));
+        }
+        Ok(())
+    }
+
+    // Visit the array at the current position element by element.
+    fn stream_seq<'de, V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        tri!(self.enter());
+        self.pos += 1;
+        let ret = visitor.visit_seq(SeqAccess {
+            de: &mut *self,
+            first: true,
+        });
+        self.remaining_depth += 1;
+        let value = match ret {
+            Ok(value) => value,
+            Err(err) => return Err(self.fix_position(err)),
+        };
+
+        match tri!(self.peek()) {
+            Some(b']') => {
+                self.pos += 1;
+                Ok(value)
+            }
+            Some(_) => Err(self.error(ErrorCode::ExpectedArrayCommaOrEnd)),
+            None => Err(self.error(ErrorCode::EofWhileParsing)),
+        }
+    }
+
+    // Visit the object at the current position entry by entry.
+    fn stream_map<'de, V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        tri!(self.enter());
+        self.pos += 1;
+        let ret = visitor.visit_map(MapAccess {
+            de: &mut *self,
+            first: true,
+        });
+        self.remaining_depth += 1;
+        let value = match ret {
+            Ok(value) => value,
+            Err(err) => return Err(self.fix_position(err)),
+        };
+
+        match tri!(self.peek()) {
+            Some(b'}') => {
+                self.pos += 1;
+                Ok(value)
+            }
+            Some(b',') => Err(self.error(ErrorCode::TrailingComma)),
+            Some(_) => Err(self.error(ErrorCode::ExpectedObjectCommaOrEnd)),
+            None => Err(self.error(ErrorCode::EofWhileParsing)),
+        }
+    }
+}
+
+// The private types of sonic-rs are parsed from the raw JSON text by the parser.
+fn is_private_token(name: &str) -> bool {
+    name == crate::serde::rawnumber::TOKEN
+        || name == crate::lazyvalue::TOKEN
+        || name == crate::lazyvalue::OWNED_LAZY_VALUE_TOKEN
+        || name == crate::value::de::TOKEN
+        || name == crate::serde::with::base64::TOKEN
+        || name == crate::serde::untagged::TOKEN
+        || name == crate::serde::rawjson::TOKEN
+}
+
+macro_rules! deserialize_whole {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                match tri!(self.next_value(Stream::None)) {
+                    Next::Whole(end) => parse_window!(self, end, |de| {
+                        (&mut de).$method($($arg,)* Transient::<'de, V>::new(visitor))
+                    }),
+                    Next::Stream(_) => unreachable!("the value is buffered as a whole"),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de, R: io::Read> de::Deserializer<'de> for &mut IoDeserializer<R> {
+    type Error = Error;
+
+    deserialize_whole! {
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match tri!(self.next_value(Stream::Any)) {
+            Next::Whole(end) => parse_window!(self, end, |de| {
+                (&mut de).deserialize_any(Transient::<'de, V>::new(visitor))
+            }),
+            Next::Stream(b'[') => self.stream_seq(visitor),
+            Next::Stream(_) => self.stream_map(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match tri!(self.next_value(Stream::Any)) {
+            Next::Whole(end) => parse_window!(self, end, |de| {
+                (&mut de).deserialize_option(Transient::<'de, V>::new(visitor))
+            }),
+            Next::Stream(_) => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let stream = if is_private_token(name) {
+            Stream::None
+        } else {
+            Stream::Any
+        };
+        match tri!(self.next_value(stream)) {
+            Next::Whole(end) => parse_window!(self, end, |de| {
+                (&mut de).deserialize_newtype_struct(name, Transient::<'de, V>::new(visitor))
+            }),
+            Next::Stream(_) => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match tri!(self.next_value(Stream::Seq)) {
+            Next::Whole(end) => parse_window!(self, end, |de| {
+                (&mut de).deserialize_seq(Transient::<'de, V>::new(visitor))
+            }),
+            Next::Stream(_) => self.stream_seq(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match tri!(self.next_value(Stream::Seq)) {
+            Next::Whole(end) => parse_window!(self, end, |de| {
+                (&mut de).deserialize_tuple(len, Transient::<'de, V>::new(visitor))
+            }),
+            Next::Stream(_) => self.stream_seq(visitor),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match tri!(self.next_value(Stream::Seq)) {
+            Next::Whole(end) => parse_window!(self, end, |de| {
+                (&mut de).deserialize_tuple_struct(name, len, Transient::<'de, V>::new(visitor))
+            }),
+            Next::Stream(_) => self.stream_seq(visitor),
+        }
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match tri!(self.next_value(Stream::Map)) {
+            Next::Whole(end) => parse_window!(self, end, |de| {
+                (&mut de).deserialize_map(Transient::<'de, V>::new(visitor))
+            }),
+            Next::Stream(_) => self.stream_map(visitor),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match tri!(self.next_value(Stream::Map)) {
+            Next::Whole(end) => parse_window!(self, end, |de| {
+                (&mut de).deserialize_struct(name, fields, Transient::<'de, V>::new(visitor))
+            }),
+            Next::Stream(_) => self.stream_map(visitor),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        // the ignored values are validated and dropped element by element
+        match tri!(self.next_value(Stream::Any)) {
+            Next::Whole(end) => parse_window!(self, end, |de| {
+                (&mut de).deserialize_ignored_any(Transient::<'de, V>::new(visitor))
+            }),
+            Next::Stream(first) => {
+                tri!(if first == b'[' {
+                    self.stream_seq(de::IgnoredAny)
+                } else {
+                    self.stream_map(de::IgnoredAny)
+                });
+                visitor.visit_unit()
+            }
+        }
+    }
+}
+
+struct SeqAccess<'a, R> {
+    de: &'a mut IoDeserializer<R>,
+    first: bool,
+}
+
+impl<'de, R: io::Read> de::SeqAccess<'de> for SeqAccess<'_, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let de = &mut *self.de;
+        match tri!(de.peek()) {
+            Some(b']') => return Ok(None),
+            Some(b',') if !self.first => {
+                de.pos += 1;
+                if tri!(de.peek()) == Some(b']') {
+                    return Err(de.error(ErrorCode::TrailingComma));
+                }
+            }
+            Some(_) if !self.first => return Err(de.error(ErrorCode::ExpectedArrayCommaOrEnd)),
+            Some(_) => {}
+            None => return Err(de.error(ErrorCode::EofWhileParsing)),
+        }
+        self.first = false;
+        seed.deserialize(de).map(Some)
+    }
+}
+
+struct MapAccess<'a, R> {
+    de: &'a mut IoDeserializer<R>,
+    first: bool,
+}
+
+impl<'de, R: io::Read> de::MapAccess<'de> for MapAccess<'_, R> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let de = &mut *self.de;
+        let mut peek = tri!(de.peek());
+        if self.first {
+            if peek == Some(b'}') {
+                return Ok(None);
+            }
+        } else {
+            match peek {
+                Some(b'}') => return Ok(None),
+                Some(b',') => {
+                    de.pos += 1;
+                    peek = tri!(de.peek());
+                    if peek == Some(b'}') {
+                        return Err(de.error(ErrorCode::TrailingComma));
+                    }
+                }
+                Some(_) => return Err(de.error(ErrorCode::ExpectedObjectCommaOrEnd)),
+                None => return Err(de.error(ErrorCode::EofWhileParsing)),
+            }
+        }
+        self.first = false;
+
+        match peek {
+            Some(b'"') => {}
+            Some(_) => return Err(de.error(ErrorCode::ExpectObjectKeyOrEnd)),
+            None => return Err(de.error(ErrorCode::EofWhileParsing)),
+        }
+        let end = tri!(de.value_end(usize::MAX)).unwrap_or(de.buf.len());
+        parse_window!(de, end, |key| {
+            key.parser.read.eat(1);
+            key.deserialize_key_seed(Transient::<'de, K>::new(seed))
+        })
+        .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let de = &mut *self.de;
+        match tri!(de.peek()) {
+            Some(b':') => de.pos += 1,
+            Some(_) => return Err(de.error(ErrorCode::ExpectedColon)),
+            None => return Err(de.error(ErrorCode::EofWhileParsing)),
+        }
+        seed.deserialize(de)
+    }
+}
+
+// Adapt the visitors, seeds and accesses of the stream lifetime `'de` to the lifetime of a
+// parsed window. The window is dropped after parsing, so the borrowed strings and bytes are
+// visited as the transient ones, which the `DeserializeOwned` types accept.
+struct Transient<'a, T> {
+    inner: T,
+    marker: PhantomData<&'a ()>,
+}
+
+impl<T> Transient<'_, T> {
+    fn new(inner: T) -> Self {
+        Self {
+            inner,
+            marker: PhantomData,
+        }
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method<E>(self, v: $ty) -> StdResult<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, 'w, V> Visitor<'w> for Transient<'de, V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_str(&str);
+        visit_string(String);
+        visit_bytes(&[u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'w str) -> StdResult<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_str(v)
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'w [u8]) -> StdResult<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_bytes(v)
+    }
+
+    fn visit_none<E>(self) -> StdResult<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E>(self) -> StdResult<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> StdResult<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'w>,
+    {
+        self.inner.visit_some(Transient::<'w, D>::new(deserializer))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> StdResult<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'w>,
+    {
+        self.inner
+            .visit_newtype_struct(Transient::<'w, D>::new(deserializer))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> StdResult<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'w>,
+    {
+        self.inner.visit_seq(Transient::<'w, A>::new(seq))
+    }
+
+    fn visit_map<A>(self, map: A) -> StdResult<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'w>,
+    {
+        self.inner.visit_map(Transient::<'w, A>::new(map))
+    }
+
+    fn visit_enum<A>(self, data: A) -> StdResult<Self::Value, A::Error>
+    where
+        A: de::EnumAccess<'w>,
+    {
+        self.inner.visit_enum(Transient::<'w, A>::new(data))
+    }
+}
+
+impl<'de, 'w, S> DeserializeSeed<'w> for Transient<'de, S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = S::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> StdResult<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'w>,
+    {
+        self.inner
+            .deserialize(Transient::<'w, D>::new(deserializer))
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> StdResult<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.inner.$method($($arg,)* Transient::<'de, V>::new(visitor))
+            }
+        )*
+    };
+}
+
+impl<'de, 'w, D> de::Deserializer<'de> for Transient<'w, D>
+where
+    D: de::Deserializer<'w>,
+{
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+impl<'de, 'w, A> de::SeqAccess<'de> for Transient<'w, A>
+where
+    A: de::SeqAccess<'w>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> StdResult<Option<T::Value>, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.inner.next_element_seed(Transient::<'de, T>::new(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, 'w, A> de::MapAccess<'de> for Transient<'w, A>
+where
+    A: de::MapAccess<'w>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> StdResult<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.inner.next_key_seed(Transient::<'de, K>::new(seed))
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> StdResult<V::Value, A::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.inner.next_value_seed(Transient::<'de, V>::new(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, 'w, A> de::EnumAccess<'de> for Transient<'w, A>
+where
+    A: de::EnumAccess<'w>,
+{
+    type Error = A::Error;
+    type Variant = Transient<'w, A::Variant>;
+
+    fn variant_seed<V>(self, seed: V) -> StdResult<(V::Value, Self::Variant), A::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let (value, variant) = tri!(self.inner.variant_seed(Transient::<'de, V>::new(seed)));
+        Ok((value, Transient::new(variant)))
+    }
+}
+
+impl<'de, 'w, A> de::VariantAccess<'de> for Transient<'w, A>
+where
+    A: de::VariantAccess<'w>,
+{
+    type Error = A::Error;
+
+    fn unit_variant(self) -> StdResult<(), A::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> StdResult<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.inner
+            .newtype_variant_seed(Transient::<'de, T>::new(seed))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> StdResult<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner
+            .tuple_variant(len, Transient::<'de, V>::new(visitor))
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> StdResult<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner
+            .struct_variant(fields, Transient::<'de, V>::new(visitor))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, HashMap};
+
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{from_str, JsonValueTrait, LazyValue, Value};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        id: u32,
+        name: String,
+        tags: Vec<String>,
+        extra: Option<Value>,
+    }
+
+    // A reader returning one byte at a time.
+    struct ByteReader<'a>(&'a [u8]);
+
+    impl io::Read for ByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((&b, rest)) if !buf.is_empty() => {
+                    buf[0] = b;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    fn check<T>(json: &str)
+    where
+        T: DeserializeOwned + PartialEq + fmt::Debug,
+    {
+        let expect: T = from_str(json).unwrap();
+        for chunk_size in [1, 2, 7, 64, DEFAULT_CHUNK_SIZE] {
+            let got: T = from_reader_with_chunk_size(json.as_bytes(), chunk_size).unwrap();
+            assert_eq!(got, expect, "chunk size {chunk_size}");
+            let got: T =
+                from_reader_with_chunk_size(ByteReader(json.as_bytes()), chunk_size).unwrap();
+            assert_eq!(got, expect, "chunk size {chunk_size}");
+        }
+    }
+
+    #[test]
+    fn test_from_reader_chunked() {
+        let items: Vec<String> = (0..100)
+            .map(|i| {
+                format!(
+                    r#"{{"id": {i}, "name": "item \"{i}\"", "tags": ["a", "b\\n"], "extra": {}}}"#,
+                    if i % 2 == 0 {
+                        "null"
+                    } else {
+                        r#"{"k": [1, 2.5, "x"]}"#
+                    }
+                )
+            })
+            .collect();
+        let json = format!("\n[ {} ]\n", items.join(",\n"));
+        check::<Vec<Item>>(&json);
+        check::<Value>(&json);
+        check::<Vec<HashMap<String, Value>>>(&json);
+
+        // the numeric keys, the options and the ignored fields
+        check::<BTreeMap<u32, Option<Vec<u8>>>>(r#"{"1": [1, 2, 3], "2": null, "30": []}"#);
+        check::<Option<Vec<Option<bool>>>>("[true, null, false, true, null]");
+        check::<(u8, String, Vec<f64>)>(r#"[1, "two", [3.5, -4e2]]"#);
+        check::<Vec<u64>>("[]");
+        check::<HashMap<String, u8>>("{ }");
+        check::<String>(r#""a long string with \"escapes\" and 中文""#);
+        check::<i64>(" -12345 ");
+        check::<Option<u8>>("null");
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Partial {
+            id: u32,
+        }
+        check::<Vec<Partial>>(&json);
+
+        // the owned lazy values are buffered as a whole
+        let v: Vec<LazyValue<'static>> = from_reader_with_chunk_size(json.as_bytes(), 16).unwrap();
+        assert_eq!(v.len(), 100);
+        assert_eq!(v[1].get("id").unwrap().as_raw_str(), "1");
+
+        // the BOM is skipped only with the config
+        let bom = "\u{feff}[1]";
+        assert!(from_reader_with_chunk_size::<_, Vec<u8>>(bom.as_bytes(), 1).is_err());
+        let mut de = IoDeserializer::new(
+            bom.as_bytes(),
+            1,
+            DeserializeCfg {
+                skip_bom: true,
+                ..Default::default()
+            },
+        );
+        de.skip_bom().unwrap();
+        assert_eq!(Vec::<u8>::deserialize(&mut de).unwrap(), [1]);
+
+        // the JSON text no longer than a chunk is read and parsed as a whole
+        let cfg = DeserializeCfg::default();
+        let mut de = IoDeserializer::new(ByteReader(b"[1, 2]"), 6, cfg);
+        assert!(de.fill_whole().unwrap());
+        assert_eq!(de.parse_whole::<Vec<u8>>().unwrap(), [1, 2]);
+        let mut de = IoDeserializer::new(ByteReader(b"[1, 2, 3]"), 6, cfg);
+        assert!(!de.fill_whole().unwrap());
+        assert_eq!(Vec::<u8>::deserialize(&mut de).unwrap(), [1, 2, 3]);
+        de.end().unwrap();
+
+        // the values are delimited by skipping, with the brackets and quotes in the strings
+        let value = format!(
+            r#"[{}, "]\"}}"]"#,
+            vec![r#"{"a": "[x\\", "b": [{}]}"#; 50].join(",")
+        );
+        let json = format!("{value} 1");
+        for chunk_size in [1, 7, 64] {
+            let mut de = IoDeserializer::new(ByteReader(json.as_bytes()), chunk_size, cfg);
+            de.peek().unwrap();
+            assert_eq!(de.value_end(usize::MAX).unwrap(), Some(value.len()));
+            let mut de = IoDeserializer::new(ByteReader(json.as_bytes()), chunk_size, cfg);
+            de.peek().unwrap();
+            assert_eq!(de.value_end(chunk_size).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn test_from_reader_chunked_errors() {
+        // the same positions as parsing the whole JSON text
+        for json in [
+            "[1, 2,\n 3,]",
+            "[1, 2\n 3]",
+            "[1, 2, x]",
+            "[[1, 2], [3, 4],\n [5, 6}]",
+            "[1, 2] 3",
+            "[1, 2",
+            "{\"a\": [1], \"b\" [2]}",
+            "{\"a\": [1],\n \"b\": [2] \"c\": 3}",
+        ] {
+            let expect = from_str::<Value>(json).unwrap_err();
+            for chunk_size in [1, 2, 8, 64] {
+                let err = if json.starts_with('[') {
+                    from_reader_with_chunk_size::<_, Vec<Value>>(json.as_bytes(), chunk_size)
+                        .unwrap_err()
+                } else {
+                    from_reader_with_chunk_size::<_, BTreeMap<String, Value>>(
+                        json.as_bytes(),
+                        chunk_size,
+                    )
+                    .unwrap_err()
+                };
+                assert_eq!(
+                    (err.line(), err.column()),
+                    (expect.line(), expect.column()),
+                    "{json}: {err}"
+                );
+            }
+        }
+
+        fn check_err<T: DeserializeOwned>(json: &str) {
+            for chunk_size in [1, 4, 64] {
+                assert!(from_reader_with_chunk_size::<_, T>(json.as_bytes(), chunk_size).is_err());
+            }
+        }
+        check_err::<HashMap<String, u8>>(r#"{"a": 1,}"#);
+        check_err::<HashMap<String, u8>>(r#"{"a" 1}"#);
+        check_err::<HashMap<String, u8>>(r#"{"a": 1 "b": 2}"#);
+        check_err::<HashMap<String, u8>>(r#"{1: 1}"#);
+        check_err::<HashMap<String, u8>>(r#"{"a": "x"}"#);
+        check_err::<Vec<u8>>("[1, 256]");
+        check_err::<Vec<u8>>("[1, 2");
+        check_err::<Vec<u8>>("[1, 2,]");
+        check_err::<Vec<u8>>("");
+        check_err::<Vec<u8>>("[1] [2]");
+        check_err::<Vec<Item>>(&format!("[{}]", r#"{"id": 1}, "#.repeat(3)));
+        assert!(from_reader_with_chunk_size::<_, Vec<u8>>(&b"[1, 2"[..], 1)
+            .unwrap_err()
+            .is_eof());
+
+        // the errors of the reader
+        struct FailReader;
+        impl io::Read for FailReader {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::Other, "broken"))
+            }
+        }
+        let err = from_reader_with_chunk_size::<_, Value>(FailReader, 16).unwrap_err();
+        assert!(err.is_io());
+    }
+}
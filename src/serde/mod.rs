@@ -1,21 +1,43 @@
 //! Serde between JSON text and Rust data structure.
 
 pub(crate) mod de;
+mod flatten;
+mod framed;
+pub(crate) mod human;
+pub(crate) mod incremental;
+mod multimap;
 pub(crate) mod number;
+#[cfg(feature = "parallel-io")]
+mod pipelined;
+pub(crate) mod rawjson;
 pub(crate) mod rawnumber;
 pub(crate) mod ser;
+mod untagged;
+pub mod with;
 
+#[cfg(feature = "parallel-io")]
+pub use self::de::from_reader_parallel;
 pub(crate) use self::de::tri;
 pub use self::{
     de::{
-        from_reader, from_slice, from_slice_unchecked, from_str, Deserializer, StreamDeserializer,
+        current_pointer, from_reader, from_reader_with_buffer, from_reader_with_capacity,
+        from_slice, from_slice_padded_unchecked, from_slice_unchecked, from_slice_with_cfg,
+        from_slice_with_defaults, from_str, from_str_with_cfg, Deserializer, StreamDeserializer,
+        PADDING_SIZE,
     },
+    flatten::Flatten,
+    framed::{Framed, FramedReader, FramedStream, FramedWriter},
+    incremental::from_reader_with_chunk_size,
+    multimap::MultiMap,
     number::{JsonNumberTrait, Number},
-    rawnumber::RawNumber,
+    rawjson::{to_raw_json, RawJson},
+    rawnumber::{NumberClass, NumberKind, RawNumber},
     ser::{
-        to_lazyvalue, to_string, to_string_pretty, to_vec, to_vec_pretty, to_writer,
-        to_writer_pretty, Serializer,
+        to_fmt_writer, to_lazyvalue, to_string, to_string_pretty, to_string_with_cfg, to_vec,
+        to_vec_pretty, to_vec_with_capacity, to_vec_with_cfg, to_writer, to_writer_buffered,
+        to_writer_pretty, RawKey, Serializer,
     },
+    untagged::Untagged,
 };
 
 #[cfg(test)]
@@ -33,7 +55,7 @@ mod test {
     use serde::{de::IgnoredAny, Deserialize, Serialize};
 
     use super::*;
-    use crate::{Result, Value};
+    use crate::{JsonValueTrait, Result, Value};
 
     macro_rules! hashmap {
         () => {
@@ -324,6 +346,92 @@ mod test {
         assert_eq!(data, got_value);
     }
 
+    #[test]
+    fn test_number_eq_hash() {
+        use std::collections::HashSet;
+
+        // `Number` is compared by the type and the value, unless it is canonical
+        let nums = [
+            Number::from(1u64),
+            Number::from_f64(1.0).unwrap(),
+            Number::from(-2i64),
+            Number::from_f64(-2.0).unwrap(),
+            Number::from_f64(0.0).unwrap(),
+            Number::from_f64(-0.0).unwrap(),
+            Number::from(0u64),
+            Number::from_f64(1.5).unwrap(),
+            Number::from_f64(1e19).unwrap(),
+            Number::from(u64::MAX),
+        ];
+        assert_ne!(nums[0], nums[1]);
+        assert_eq!(nums[4], nums[5]);
+        let set: HashSet<&Number> = nums.iter().collect();
+        assert_eq!(set.len(), 9);
+        let set: HashSet<Number> = nums.iter().map(Number::canonical).collect();
+        assert_eq!(set.len(), 6);
+        assert_eq!(nums[1].canonical().as_u64(), Some(1));
+        assert_eq!(nums[3].canonical().as_i64(), Some(-2));
+        assert_eq!(nums[5].canonical().as_u64(), Some(0));
+        // out of the `i64` range
+        assert!(nums[8].canonical().is_f64());
+
+        // `RawNumber` is compared by the decimal value of the text
+        let raw = |s: &str| -> RawNumber { from_str(s).unwrap() };
+        let eqs = [
+            ("1", "1.0"),
+            ("1.50", "15e-1"),
+            ("-0.0", "0e10"),
+            ("100", "1E+2"),
+            ("0.00120", "1.2e-3"),
+            (
+                "123456789012345678901234567890",
+                "1.2345678901234567890123456789e29",
+            ),
+        ];
+        for (a, b) in eqs {
+            assert_eq!(raw(a), raw(b), "{a} {b}");
+            let set: HashSet<RawNumber> = [raw(a), raw(b)].into_iter().collect();
+            assert_eq!(set.len(), 1, "{a} {b}");
+        }
+        let nes = [
+            ("1", "-1"),
+            ("1", "10"),
+            ("0.1", "1"),
+            ("1.5", "1.5000000000000000000001"),
+            ("18446744073709551616", "18446744073709551617"),
+        ];
+        for (a, b) in nes {
+            assert_ne!(raw(a), raw(b), "{a} {b}");
+        }
+    }
+
+    #[test]
+    fn test_raw_number_classify() {
+        let classify = |s: &str| {
+            let c = from_str::<RawNumber>(s).unwrap().classify();
+            (c.kind, c.negative, c.int_digits, c.frac_digits, c.exponent)
+        };
+        use NumberKind::*;
+        assert_eq!(classify("0"), (Integer, false, 1, 0, 0));
+        assert_eq!(classify("-0"), (Integer, true, 1, 0, 0));
+        assert_eq!(classify("9223372036854775807"), (Integer, false, 19, 0, 0));
+        assert_eq!(classify("-9223372036854775808"), (Integer, true, 19, 0, 0));
+        assert_eq!(classify("-9223372036854775809"), (BigInt, true, 19, 0, 0));
+        assert_eq!(classify("18446744073709551615"), (Integer, false, 20, 0, 0));
+        assert_eq!(classify("18446744073709551616"), (BigInt, false, 20, 0, 0));
+        assert_eq!(classify("123456789012345678901"), (BigInt, false, 21, 0, 0));
+        assert_eq!(classify("-0.500"), (Float, true, 1, 3, 0));
+        assert_eq!(classify("1e-7"), (Scientific, false, 1, 0, -7));
+        assert_eq!(classify("10.25E+300"), (Scientific, false, 2, 2, 300));
+        assert_eq!(classify(r#""1.5""#), (Float, false, 1, 1, 0));
+
+        assert!(from_str::<RawNumber>("18446744073709551616")
+            .unwrap()
+            .is_integer());
+        assert!(!from_str::<RawNumber>("1.0").unwrap().is_integer());
+        assert!(!from_str::<RawNumber>("1e2").unwrap().is_integer());
+    }
+
     #[test]
     fn test_json_number_invalid() {
         fn test_json_failed(json: &str) {
@@ -343,6 +451,48 @@ mod test {
         test_json_failed(r#""-1e""#);
     }
 
+    #[test]
+    fn test_skip_number_across_chunks() {
+        // the numbers are skipped when deserializing `RawNumber`, test the numbers with
+        // different lengths, so that each part may cross the 32-byte chunks.
+        let tails = ["]", &format!(",{}1]", " ".repeat(40))];
+        for k in [0, 1, 7, 29, 30, 31, 32, 33, 63, 64, 65] {
+            let int = format!("1{}", "2".repeat(k));
+            let frac = format!(".{}", "3".repeat(k + 1));
+            for sign in ["", "-"] {
+                for rest in [
+                    "".to_string(),
+                    frac.clone(),
+                    "e5".to_string(),
+                    format!("E+{}", "4".repeat(k + 1)),
+                    format!("{frac}e-1"),
+                    format!("{frac}E{}", "4".repeat(k + 1)),
+                ] {
+                    let num = format!("{sign}{int}{rest}");
+                    for tail in tails {
+                        let json = format!("[{num}{tail}");
+                        let raws: Vec<RawNumber> = from_str(&json).expect(&json);
+                        assert_eq!(raws[0].as_str(), num);
+                    }
+                }
+
+                for rest in [
+                    ".", ".e5", "e", "E+", "e-x", ".x", "..1", "1.2.3", "e1e1", "-1",
+                ] {
+                    let num = format!("{sign}{int}{rest}");
+                    for tail in tails {
+                        let json = format!("[{num}{tail}");
+                        assert!(from_str::<Vec<RawNumber>>(&json).is_err(), "{json}");
+                    }
+                }
+            }
+        }
+
+        let raws: Vec<RawNumber> = from_str("[0,0.5,-0e0,0E-0]").unwrap();
+        assert_eq!(raws[3].as_str(), "0E-0");
+        assert!(from_str::<Vec<RawNumber>>("[01]").is_err());
+    }
+
     #[cfg(not(feature = "utf8_lossy"))]
     #[test]
     fn test_invalid_utf8() {
@@ -736,4 +886,808 @@ mod test {
             assert!(err.is_syntax());
         }
     }
+
+    #[test]
+    fn test_utf8_policy() {
+        use crate::Utf8Policy;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Foo {
+            a: String,
+            b: Vec<u32>,
+        }
+
+        let json = &b"{\"a\": \"x\xff\", \"b\": [1]}"[..];
+        let parse = |policy| {
+            let mut de = Deserializer::from_slice(json).utf8_policy(policy);
+            de.deserialize::<Foo>()
+        };
+        assert!(parse(Utf8Policy::Validate).unwrap_err().is_syntax());
+        assert_eq!(
+            parse(Utf8Policy::Lossy).unwrap(),
+            Foo {
+                a: "x\u{FFFD}".to_string(),
+                b: vec![1],
+            }
+        );
+
+        // the last policy is used
+        let mut de = Deserializer::from_slice(json)
+            .utf8_lossy()
+            .utf8_policy(Utf8Policy::Validate);
+        assert!(de.deserialize::<Foo>().is_err());
+
+        let valid = br#"{"a": "xy", "b": []}"#;
+        let policy = unsafe { Utf8Policy::trust() };
+        let mut de = Deserializer::from_slice(valid).utf8_policy(policy);
+        assert_eq!(de.deserialize::<Foo>().unwrap().a, "xy");
+
+        // the same policies in the cfg
+        let mut cfg = crate::DeserializeCfg::default();
+        cfg.utf8_policy = Utf8Policy::Lossy;
+        assert_eq!(
+            crate::from_slice_with_cfg::<Foo>(json, cfg).unwrap().a,
+            "x\u{FFFD}"
+        );
+        let mut de = Deserializer::from_slice(json).with_cfg(cfg);
+        assert_eq!(de.deserialize::<Foo>().unwrap().a, "x\u{FFFD}");
+        // the `Value` is parsed from the replaced JSON, and the trailing characters are checked in
+        // the origin JSON
+        for (json, expect) in [
+            (&b"\"\xff\""[..], crate::json!("\u{FFFD}")),
+            (
+                b"[\"a\xff\xfe\", {\"\xf0\x9f\": 1}] ",
+                crate::json!(["a\u{FFFD}\u{FFFD}", {"\u{FFFD}": 1}]),
+            ),
+        ] {
+            let value: Value = crate::from_slice_with_cfg(json, cfg).unwrap();
+            assert_eq!(value, expect);
+        }
+        let mut trailing = b"[\"\xff\"] x".to_vec();
+        assert!(crate::from_slice_with_cfg::<Value>(&trailing, cfg).is_err());
+        trailing.pop();
+        assert!(crate::from_slice_with_cfg::<Value>(&trailing, cfg).is_ok());
+        cfg.utf8_policy = policy;
+        assert_eq!(
+            crate::from_slice_with_cfg::<Foo>(valid, cfg).unwrap().a,
+            "xy"
+        );
+        if !cfg!(feature = "utf8_lossy") {
+            cfg.utf8_policy = Utf8Policy::Validate;
+            assert!(crate::from_slice_with_cfg::<Foo>(json, cfg).is_err());
+        }
+    }
+
+    #[test]
+    fn test_track_pointer() {
+        use crate::{pointer, PointerNode};
+
+        // record the pointer, and parse the nested JSON in the string with another deserializer
+        #[derive(Debug, PartialEq)]
+        struct Here(Vec<PointerNode>, Option<Vec<PointerNode>>);
+
+        impl<'de> Deserialize<'de> for Here {
+            fn deserialize<D>(d: D) -> std::result::Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let outer = current_pointer().unwrap();
+                let nested = String::deserialize(d)?;
+                let inner = if nested.is_empty() {
+                    None
+                } else {
+                    let mut de = Deserializer::from_str(&nested).track_pointer();
+                    let inner: Vec<Here> = de.deserialize().map_err(serde::de::Error::custom)?;
+                    Some(inner[0].0.clone())
+                };
+                assert_eq!(current_pointer().unwrap(), outer);
+                Ok(Here(outer, inner))
+            }
+        }
+
+        let json = r#"{"a": [{"b": ""}, {"x": 1, "cd": "[\"\"]"}]}"#;
+        let mut de = Deserializer::from_str(json).track_pointer();
+        #[derive(Debug, Deserialize)]
+        struct Row {
+            b: Option<Here>,
+            cd: Option<Here>,
+        }
+        let rows: HashMap<String, Vec<Row>> = de.deserialize().unwrap();
+        let rows = &rows["a"];
+        assert_eq!(rows[0].b, Some(Here(pointer!["a", 0, "b"].to_vec(), None)));
+        assert_eq!(
+            rows[1].cd,
+            Some(Here(
+                pointer!["a", 1, "cd"].to_vec(),
+                Some(pointer![0].to_vec())
+            ))
+        );
+        assert!(de.current_pointer().is_empty());
+        assert!(current_pointer().is_none());
+
+        // the pointer is kept at the failed value
+        let json = r#"{"d": {"e": [1, 256]}}"#;
+        let mut de = Deserializer::from_str(json).track_pointer();
+        assert!(de
+            .deserialize::<HashMap<String, HashMap<String, Vec<u8>>>>()
+            .is_err());
+        assert_eq!(de.current_pointer(), pointer!["d", "e", 1]);
+        assert!(current_pointer().is_none());
+
+        let mut de = Deserializer::from_str(json);
+        assert!(de
+            .deserialize::<HashMap<String, HashMap<String, Vec<u8>>>>()
+            .is_err());
+        assert!(de.current_pointer().is_empty());
+    }
+
+    #[test]
+    fn test_lenient_escapes() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Foo {
+            a: String,
+            b: Vec<String>,
+        }
+
+        let json = r#"{"\q": "\'", "a": "\x41\x7e\x7\x\xff\é", "b": ["\/\n", "\x4", "\xG1"]}"#;
+        let mut de = Deserializer::from_str(json).lenient_escapes();
+        let foo: Foo = de.deserialize().unwrap();
+        assert_eq!(
+            foo,
+            Foo {
+                a: "A~x7x\u{ff}é".to_string(),
+                b: vec!["/\n".to_string(), "x4".to_string(), "xG1".to_string()],
+            }
+        );
+
+        let mut de = Deserializer::from_str(json).lenient_escapes();
+        let value: Value = de.deserialize().unwrap();
+        assert_eq!(value["q"], "'");
+        assert_eq!(value["a"], foo.a.as_str());
+        assert_eq!(value["b"][2], "xG1");
+
+        let cfg = crate::DeserializeCfg {
+            lenient_escapes: true,
+            ..Default::default()
+        };
+        let got: HashMap<String, String> = from_str_with_cfg(r#"{"\a": "\b\c"}"#, cfg).unwrap();
+        assert_eq!(got["a"], "\x08c");
+
+        // the escaped control chars are invalid
+        let json = "[\"\\\u{1}\"]";
+        let mut de = Deserializer::from_str(json).lenient_escapes();
+        assert!(de.deserialize::<Vec<String>>().is_err());
+        let mut de = Deserializer::from_str(json).lenient_escapes();
+        assert!(de.deserialize::<Value>().is_err());
+        let mut de = Deserializer::from_str(json).lenient_escapes();
+        assert!(de.deserialize::<IgnoredAny>().is_err());
+
+        assert!(from_str::<Foo>(r#"{"a": "\x41", "b": []}"#).is_err());
+        assert!(from_str::<Value>(r#""\'""#).is_err());
+    }
+
+    #[test]
+    fn test_allow_control_chars() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Foo {
+            a: String,
+            b: Vec<String>,
+        }
+
+        // the control chars in the long strings are checked in the SIMD loops
+        let long = "x".repeat(40);
+        let json = format!(
+            "{{\"a\": \"1\n2\\\"\t\", \"b\": [\"{long}\r\n{long}\", \"\x00\x1f\\n\"], \"c\": \
+             \"\n\"}}"
+        );
+        let expect = Foo {
+            a: "1\n2\"\t".to_string(),
+            b: vec![format!("{long}\r\n{long}"), "\u{0}\u{1f}\n".to_string()],
+        };
+        let mut de = Deserializer::from_str(&json).allow_control_chars();
+        let foo: Foo = de.deserialize().unwrap();
+        assert_eq!(foo, expect);
+
+        let mut de = Deserializer::from_str(&json).allow_control_chars();
+        let value: Value = de.deserialize().unwrap();
+        assert_eq!(value["a"], expect.a.as_str());
+        assert_eq!(value["b"][0], expect.b[0].as_str());
+        assert_eq!(value["c"], "\n");
+        let out = to_string(&value).unwrap();
+        assert_eq!(from_str::<Value>(&out).unwrap(), value);
+
+        let mut de = Deserializer::from_str(&json).allow_control_chars();
+        de.deserialize::<IgnoredAny>().unwrap();
+
+        let cfg = crate::DeserializeCfg {
+            allow_control_chars: true,
+            ..Default::default()
+        };
+        let got: HashMap<String, String> = from_str_with_cfg("{\"\tk\": \"\tv\"}", cfg).unwrap();
+        assert_eq!(got["\tk"], "\tv");
+
+        assert!(from_str::<Foo>(&json).is_err());
+        assert!(from_str::<Value>(&json).is_err());
+        assert!(from_str::<IgnoredAny>(&json).is_err());
+
+        // the raw JSON texts are escaped, or rejected if borrowed
+        #[derive(Deserialize)]
+        struct Raw<'a> {
+            #[serde(borrow)]
+            lazy: crate::LazyValue<'a>,
+            owned: crate::OwnedLazyValue,
+            plain: crate::LazyValue<'a>,
+        }
+        let json = "{\"lazy\": [\"\t\\\"\", \"\\\\\"], \"owned\": \"a\nb\", \"plain\": [\"\\t\"]}";
+        let mut de = Deserializer::from_str(json).allow_control_chars();
+        let raw: Raw = de.deserialize().unwrap();
+        assert_eq!(raw.lazy.as_raw_str(), r#"["\t\"", "\\"]"#);
+        assert_eq!(raw.owned.as_raw_str(), Some(r#""a\nb""#));
+        assert_eq!(raw.plain.as_raw_str(), r#"["\t"]"#);
+
+        let json = "{\"a\": [\"x\ty\"]}";
+        let mut de = Deserializer::from_str(json).allow_control_chars();
+        let err = de
+            .deserialize::<HashMap<String, &crate::RawJson>>()
+            .unwrap_err();
+        assert!(err.is_syntax(), "{err}");
+        assert!(err.to_string().contains("Control character"), "{err}");
+
+        // the raw strings with the control chars are not kept in `Value`
+        let mut de = Deserializer::from_str("[\"\\u0041\t\", \"\\u0042\"]")
+            .allow_control_chars()
+            .use_raw();
+        let value: Value = de.deserialize().unwrap();
+        assert_eq!(to_string(&value).unwrap(), r#"["A\t","\u0042"]"#);
+    }
+
+    #[test]
+    fn test_empty_string_as_none() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Foo {
+            a: Option<u64>,
+            b: Option<String>,
+            c: String,
+            d: Vec<Option<bool>>,
+            e: Option<Option<String>>,
+        }
+
+        let json = r#"{"a": "", "b":  "" , "c": "", "d": ["", null, true], "e": ""}"#;
+        let expect = Foo {
+            a: None,
+            b: None,
+            c: String::new(),
+            d: vec![None, None, Some(true)],
+            e: None,
+        };
+        let mut de = Deserializer::from_str(json).empty_string_as_none();
+        assert_eq!(de.deserialize::<Foo>().unwrap(), expect);
+
+        let cfg = crate::DeserializeCfg {
+            empty_string_as_none: true,
+            ..Default::default()
+        };
+        let got: Foo = from_str_with_cfg(json, cfg).unwrap();
+        assert_eq!(got, expect);
+
+        // the non-empty strings and the other types
+        let got: Vec<Option<String>> = from_str_with_cfg(r#"["", " ", "\"", "x"]"#, cfg).unwrap();
+        assert_eq!(
+            got,
+            vec![None, Some(" ".into()), Some("\"".into()), Some("x".into())]
+        );
+        let got: Value = from_str_with_cfg(r#"{"a": ""}"#, cfg).unwrap();
+        assert_eq!(got["a"], "");
+
+        // disabled by default
+        assert!(from_str::<Foo>(json).is_err());
+        let got: Option<String> = from_str("\"\"").unwrap();
+        assert_eq!(got, Some(String::new()));
+    }
+
+    #[test]
+    fn test_from_slice_with_defaults() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Server {
+            host: String,
+            port: u16,
+            tags: Option<Vec<String>>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            name: String,
+            #[serde(rename = "main")]
+            primary: Server,
+            replicas: Vec<Server>,
+            zones: HashMap<String, Server>,
+            retries: u32,
+        }
+
+        let defaults = crate::json!({
+            "name": "default",
+            "main": {"host": "localhost", "port": 80, "tags": ["a"]},
+            "replicas": [{"port": 81}, {"port": 82}],
+            "zones": {"z1": {"host": "z1.local", "port": 90}},
+            "retries": 3,
+        });
+        let server = |host: &str, port, tags: Option<&[&str]>| Server {
+            host: host.to_string(),
+            port,
+            tags: tags.map(|t| t.iter().map(|s| s.to_string()).collect()),
+        };
+
+        let json = r#"{
+            "name": "app",
+            "main": {"host": "example.com"},
+            "replicas": [{"host": "r0"}, {"host": "r1", "port": 1}],
+            "zones": {"z1": {}, "z2": {"host": "z2", "port": 2}}
+        }"#;
+        let config: Config = from_slice_with_defaults(json.as_bytes(), &defaults).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                name: "app".to_string(),
+                primary: server("example.com", 80, Some(&["a"])),
+                replicas: vec![server("r0", 81, None), server("r1", 1, None)],
+                zones: hashmap! {
+                    "z1".to_string() => server("z1.local", 90, None),
+                    "z2".to_string() => server("z2", 2, None),
+                },
+                retries: 3,
+            }
+        );
+
+        // the whole missing field is taken from the defaults
+        let json = br#"{"replicas": [], "zones": {}}"#;
+        let config: Config = from_slice_with_defaults(json, &defaults).unwrap();
+        assert_eq!(config.name, "default");
+        assert_eq!(config.primary, server("localhost", 80, Some(&["a"])));
+        assert_eq!(config.retries, 3);
+        // the values taken from the defaults must be complete
+        assert!(from_slice_with_defaults::<Config>(b"{}", &defaults).is_err());
+
+        // the missing fields without defaults
+        let json = br#"{"replicas": [{"host": "r0"}, {"host": "r1"}, {"host": "r2"}]}"#;
+        let err = from_slice_with_defaults::<Config>(json, &defaults).unwrap_err();
+        assert!(err.to_string().contains("missing field `port`"), "{err}");
+        let mut de = Deserializer::from_slice(json)
+            .with_defaults(&defaults)
+            .track_pointer();
+        assert!(de.deserialize::<Config>().is_err());
+        assert_eq!(de.current_pointer(), crate::pointer!["replicas", 2]);
+
+        // the invalid defaults and the trailing chars
+        let defaults = crate::json!({"retries": "3"});
+        let json = br#"{"name": "", "main": {"host": "", "port": 0}, "replicas": [], "zones": {}}"#;
+        assert!(from_slice_with_defaults::<Config>(json, &defaults).is_err());
+        let json = br#"{"name": "", "main": {"host": "", "port": 0}, "replicas": [], "zones": {}, "retries": 1} x"#;
+        assert!(from_slice_with_defaults::<Config>(json, &defaults).is_err());
+    }
+
+    #[test]
+    fn test_checkpoint_rewind() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        // speculatively parse the object and the array representations
+        let json = r#" [1, 2] {"x": 3, "y": "\u0034"} {"x": 5, "y": 6}"#;
+        let mut de = Deserializer::from_str(json).track_pointer();
+        let mut points = Vec::new();
+        for _ in 0..3 {
+            let cp = de.checkpoint();
+            match de.deserialize::<Point>() {
+                Ok(p) => points.push(p),
+                Err(_) => {
+                    de.rewind(cp);
+                    assert!(de.current_pointer().is_empty());
+                    let value: Value = de.deserialize().unwrap();
+                    points.push(Point {
+                        x: value["x"].as_str().map_or(-1, |s| s.parse().unwrap()),
+                        y: value["y"].as_str().map_or(-1, |s| s.parse().unwrap()),
+                    });
+                }
+            }
+        }
+        assert_eq!(
+            points,
+            vec![
+                Point { x: 1, y: 2 },
+                Point { x: -1, y: 4 },
+                Point { x: 5, y: 6 }
+            ]
+        );
+
+        // rewind forward and backward
+        let mut de = Deserializer::from_str(r#""a\nb" "c" 1"#);
+        let start = de.checkpoint();
+        assert_eq!(de.deserialize::<String>().unwrap(), "a\nb");
+        let second = de.checkpoint();
+        de.rewind(start);
+        // the escaped string can not be borrowed
+        assert!(de.deserialize::<&str>().is_err());
+        de.rewind(second);
+        assert_eq!(de.deserialize::<&str>().unwrap(), "c");
+        de.rewind(start);
+        assert_eq!(de.deserialize::<String>().unwrap(), "a\nb");
+        de.rewind(second);
+        assert_eq!(de.deserialize::<&str>().unwrap(), "c");
+        assert_eq!(de.deserialize::<u8>().unwrap(), 1);
+
+        // the invalid UTF-8 is replaced again in the lossy mode
+        let json = b"\"a\xff\" \"\xfe\"";
+        let mut de = Deserializer::from_slice(json).utf8_lossy();
+        let cp = de.checkpoint();
+        for _ in 0..2 {
+            de.rewind(cp);
+            assert_eq!(de.deserialize::<String>().unwrap(), "a\u{fffd}");
+            assert_eq!(de.deserialize::<String>().unwrap(), "\u{fffd}");
+        }
+
+        let other = Deserializer::from_str("[1]").checkpoint();
+        let ret = std::panic::catch_unwind(move || Deserializer::from_str("2").rewind(other));
+        assert!(ret.is_err());
+    }
+
+    #[test]
+    fn test_from_reader_with_buffer() {
+        let mut buf = Vec::new();
+        let data = hashmap!(
+            "a".to_string() => vec![1, 2, 3],
+            "b".to_string() => vec![],
+        );
+        let json = crate::to_string(&data).unwrap();
+
+        let got: HashMap<String, Vec<i64>> =
+            from_reader_with_buffer(json.as_bytes(), &mut buf).unwrap();
+        assert_eq!(got, data);
+        assert_eq!(buf, json.as_bytes());
+
+        // the buffer is cleared and reused
+        let cap = buf.capacity();
+        let got: Vec<u8> = from_reader_with_buffer(&b"[1,2,3]"[..], &mut buf).unwrap();
+        assert_eq!(got, [1, 2, 3]);
+        assert_eq!(buf, b"[1,2,3]");
+        assert_eq!(buf.capacity(), cap);
+
+        let got: HashMap<String, Vec<i64>> =
+            from_reader_with_capacity(json.as_bytes(), 16).unwrap();
+        assert_eq!(got, data);
+        assert!(from_reader_with_capacity::<_, HashMap<String, Vec<i64>>>(&b"{"[..], 16).is_err());
+    }
+
+    #[test]
+    fn test_preserve_surrogates() {
+        let data = [
+            r#""\ud800""#,
+            r#""\udc00""#,
+            r#""\uD800\u0041\uDBFF""#,
+            r#"{"\ud800":["\ud800\ud800","\ud83d\ude00"]}"#,
+            r#"["\ud800abcdef","a\tb"]"#,
+        ];
+
+        for json in data {
+            let mut de = Deserializer::from_str(json).preserve_surrogates();
+            let value: Value = de.deserialize().unwrap();
+            assert_eq!(crate::to_string(&value).unwrap(), json);
+
+            let mut de = Deserializer::from_str(json).preserve_surrogates();
+            let _: IgnoredAny = de.deserialize().unwrap();
+        }
+
+        let mut de = Deserializer::from_str(r#""\uD800\u0041\ud800abcdef""#).preserve_surrogates();
+        // the lone surrogates are replaced in the typed strings
+        let value: String = de.deserialize().unwrap();
+        assert_eq!(value, "�A�abcdef");
+
+        // the lone surrogate is still invalid without the option
+        if !cfg!(feature = "utf8_lossy") {
+            assert!(crate::from_str::<Value>(r#""\ud800""#).is_err());
+            assert!(crate::from_str::<String>(r#""\ud800\u0041""#).is_err());
+        }
+    }
+
+    #[test]
+    fn test_deserialize_with_cancel() {
+        use crate::CancelToken;
+
+        let json = r#"{"a": [1, 2, {"b": null}], "c": "d"}"#;
+        let token = CancelToken::new();
+        let mut de = Deserializer::from_str(json).with_cancel(token.clone());
+        let value: Value = de.deserialize().unwrap();
+        assert_eq!(value["a"][2]["b"], Value::new());
+
+        token.cancel();
+        assert!(token.is_cancelled());
+        let mut de = Deserializer::from_str(json).with_cancel(token.clone());
+        let err = de.deserialize::<Value>().unwrap_err();
+        assert!(err.is_cancelled(), "{err}");
+
+        let mut de = Deserializer::from_str(json).with_cancel(token.clone());
+        let err = de.deserialize::<HashMap<String, IgnoredAny>>().unwrap_err();
+        assert!(err.is_cancelled(), "{err}");
+
+        // the skipped values are also cancelled
+        let mut de = Deserializer::from_str(json).with_cancel(token.clone());
+        let err = de.deserialize::<IgnoredAny>().unwrap_err();
+        assert!(err.is_cancelled(), "{err}");
+
+        // the scalars are not affected
+        let mut de = Deserializer::from_str("123").with_cancel(token);
+        assert_eq!(de.deserialize::<i32>().unwrap(), 123);
+    }
+
+    #[test]
+    fn test_deserialize_extended_numbers() {
+        use crate::JsonValueTrait;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            mask: u8,
+            size: i64,
+            offset: i32,
+            ratio: f64,
+            list: Vec<u64>,
+        }
+
+        let json = r#"{"mask": 0xff, "size": 1_000_000, "offset": -0X1_0, "ratio": 0.5, "list": [0x1, 2_0], "x": [0xA]}"#;
+        let mut de = Deserializer::from_str(json).extended_numbers();
+        let config: Config = de.deserialize().unwrap();
+        assert_eq!(
+            config,
+            Config {
+                mask: 0xff,
+                size: 1_000_000,
+                offset: -16,
+                ratio: 0.5,
+                list: vec![1, 20],
+            }
+        );
+
+        let mut de = Deserializer::from_str(json).extended_numbers();
+        let value: Value = de.deserialize().unwrap();
+        assert_eq!(value["offset"].as_i64(), Some(-16));
+        assert_eq!(value["x"][0].as_u64(), Some(10));
+
+        let mut de = Deserializer::from_str(r#"{"a": 0x1F}"#).extended_numbers();
+        let value: HashMap<String, IgnoredAny> = de.deserialize().unwrap();
+        assert!(value.contains_key("a"));
+
+        // the raw numbers of the extended numbers are normalized into valid JSON
+        let json = r#"[0xff, -0x1_0, 1_000, 1.50, "0X1F"]"#;
+        let mut de = Deserializer::from_str(json)
+            .extended_numbers()
+            .use_rawnumber();
+        let value: Value = de.deserialize().unwrap();
+        assert_eq!(to_string(&value).unwrap(), r#"[255,-16,1000,1.50,"0X1F"]"#);
+        let mut de = Deserializer::from_str(json).extended_numbers().use_raw();
+        let value: Value = de.deserialize().unwrap();
+        assert_eq!(to_string(&value).unwrap(), r#"[255,-16,1000,1.50,"0X1F"]"#);
+        let mut de = Deserializer::from_str(r#"[0xff, "1_0", 2.0]"#).extended_numbers();
+        let nums: Vec<RawNumber> = de.deserialize().unwrap();
+        let nums: Vec<&str> = nums.iter().map(|n| n.as_str()).collect();
+        assert_eq!(nums, ["255", "10", "2.0"]);
+
+        for invalid in ["0x", "1__0", "1_", "0x1_", "01_0", "1_0.5", "[0x1G]"] {
+            let mut de = Deserializer::from_str(invalid).extended_numbers();
+            assert!(de.deserialize::<Value>().is_err(), "{invalid}");
+            let mut de = Deserializer::from_str(invalid).extended_numbers();
+            assert!(de.deserialize::<u64>().is_err(), "{invalid}");
+        }
+        assert!(crate::from_str::<Config>(json).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_skip_bom() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        let json = "\u{FEFF} {\"x\": 1, \"y\": 2}";
+        let mut de = Deserializer::from_str(json).skip_bom();
+        let point: Point = de.deserialize().unwrap();
+        assert_eq!(point, Point { x: 1, y: 2 });
+
+        let mut de = Deserializer::from_slice(json.as_bytes()).skip_bom();
+        let value: Value = de.deserialize().unwrap();
+        assert_eq!(value, crate::json!({"x": 1, "y": 2}));
+
+        let mut de = Deserializer::from_str(json).skip_bom();
+        let lv: crate::LazyValue<'_> = de.deserialize().unwrap();
+        assert_eq!(lv.as_raw_str(), r#"{"x": 1, "y": 2}"#);
+
+        // only skip the BOM at the start
+        let mut de = Deserializer::from_str("\u{FEFF}\u{FEFF}1").skip_bom();
+        assert!(de.deserialize::<Value>().is_err());
+        let mut de = Deserializer::from_str("\u{FEFF}\u{FEFF}[1]").skip_bom();
+        assert!(de.deserialize::<Value>().is_err());
+        let mut de = Deserializer::from_str("\u{FEFF}\u{FEFF}1").skip_bom();
+        assert!(de.deserialize::<u32>().is_err());
+        let mut de = Deserializer::from_str(" \u{FEFF}1").skip_bom();
+        assert!(de.deserialize::<u32>().is_err());
+        let mut de = Deserializer::from_str("1").skip_bom();
+        assert_eq!(de.deserialize::<u32>().unwrap(), 1);
+
+        #[cfg(not(feature = "skip_bom"))]
+        {
+            assert!(crate::from_str::<Value>(json).is_err());
+            assert!(crate::from_str::<Point>(json).is_err());
+        }
+        #[cfg(feature = "skip_bom")]
+        {
+            assert_eq!(
+                crate::from_str::<Value>(json).unwrap(),
+                crate::json!({"x": 1, "y": 2})
+            );
+            assert_eq!(
+                crate::from_str::<Point>(json).unwrap(),
+                Point { x: 1, y: 2 }
+            );
+        }
+    }
+
+    #[test]
+    fn test_deserialize_struct_from_array() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Point {
+            a: f64,
+            b: f64,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Quote {
+            symbol: String,
+            bid: Point,
+            #[serde(default)]
+            volume: u64,
+        }
+
+        // the fields are matched positionally
+        assert_eq!(
+            from_str::<Point>("[1.0, 2.5]").unwrap(),
+            Point { a: 1.0, b: 2.5 }
+        );
+        assert_eq!(
+            from_str::<Vec<Quote>>(
+                r#"[["AAPL", [1, 2], 100], {"symbol": "X", "bid": {"a": 3, "b": 4}}]"#
+            )
+            .unwrap(),
+            vec![
+                Quote {
+                    symbol: "AAPL".to_string(),
+                    bid: Point { a: 1.0, b: 2.0 },
+                    volume: 100,
+                },
+                Quote {
+                    symbol: "X".to_string(),
+                    bid: Point { a: 3.0, b: 4.0 },
+                    volume: 0,
+                },
+            ]
+        );
+
+        // the missing and redundant elements are errors
+        assert!(from_str::<Point>("[1.0]").is_err());
+        assert!(from_str::<Point>("[1.0, 2.0, 3.0]").is_err());
+        assert!(from_str::<Point>("[1.0, \"2.0\"]").is_err());
+    }
+
+    #[test]
+    fn test_from_slice_padded_unchecked() {
+        fn parse(json: &str) -> Result<Value> {
+            let mut buf = json.as_bytes().to_vec();
+            buf.extend_from_slice(&[b'"'; PADDING_SIZE]);
+            let value = unsafe { from_slice_padded_unchecked(&mut buf) };
+            // the value borrows the buffer
+            value.map(|v| from_str(&to_string(&v).unwrap()).unwrap())
+        }
+
+        let jsons = [
+            "null",
+            " 123 ",
+            r#""hello\nworld""#,
+            r#"{"a": [1, 2.5, -3, true, {}], "b\u0000": "c\"d", "e": []}"#,
+        ];
+        for json in jsons {
+            assert_eq!(parse(json).unwrap(), from_str::<Value>(json).unwrap());
+        }
+
+        let invalids = [
+            "",
+            " ",
+            r#""abc"#,
+            r#"{"a": 1"#,
+            "[1, 2] x",
+            "[1, 2]]",
+            "tru",
+        ];
+        for json in invalids {
+            assert!(parse(json).is_err(), "{json}");
+            assert!(from_str::<Value>(json).is_err(), "{json}");
+        }
+    }
+
+    #[test]
+    fn test_with_cfg() {
+        use crate::{DeserializeCfg, SerializeCfg};
+
+        let mut cfg = DeserializeCfg {
+            skip_bom: true,
+            use_rawnumber: true,
+            ..Default::default()
+        };
+        let json = "\u{FEFF}{\"a\": 1.00000000000000000001}";
+        let value: Value = from_str_with_cfg(json, cfg).unwrap();
+        assert_eq!(
+            to_string(&value).unwrap(),
+            r#"{"a":1.00000000000000000001}"#
+        );
+        let value: Value = from_slice_with_cfg(json.as_bytes(), cfg).unwrap();
+        assert_eq!(
+            to_string(&value).unwrap(),
+            r#"{"a":1.00000000000000000001}"#
+        );
+        assert!(from_str_with_cfg::<Value>("{} {}", cfg).is_err());
+        if !cfg!(feature = "utf8_lossy") {
+            assert!(from_slice_with_cfg::<Value>(b"\"\xff\"", cfg).is_err());
+        }
+
+        cfg.utf8_lossy = true;
+        let s: String = from_slice_with_cfg(b"\"\xff\"", cfg).unwrap();
+        assert_eq!(s, "\u{FFFD}");
+        assert!(from_slice_with_cfg::<Value>(b"[1, \xff]", cfg).is_err());
+
+        // the builder methods are replaced
+        let mut de = Deserializer::from_str("[0x10]").extended_numbers();
+        assert_eq!(de.deserialize::<Vec<u32>>().unwrap(), [16]);
+        let mut de = Deserializer::from_str("[0x10]")
+            .extended_numbers()
+            .with_cfg(DeserializeCfg::default());
+        assert!(de.deserialize::<Vec<u32>>().is_err());
+
+        let value = crate::json!({"a": [1, 2]});
+        assert_eq!(
+            to_string_with_cfg(&value, SerializeCfg::default()).unwrap(),
+            to_string(&value).unwrap()
+        );
+        let mut cfg = SerializeCfg {
+            pretty: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_vec_with_cfg(&value, cfg).unwrap(),
+            to_vec_pretty(&value).unwrap()
+        );
+
+        let value = vec![vec![1, 2], vec![3]];
+        cfg.pretty_style = crate::PrettyStyle::Human {
+            max_width: 12,
+            align_keys: false,
+        };
+        assert_eq!(
+            to_string_with_cfg(&value, cfg).unwrap(),
+            "[\n  [1, 2],\n  [3]\n]"
+        );
+        let value = crate::json!([[1, 2], [3]]);
+        assert_eq!(
+            to_string_with_cfg(&value, cfg).unwrap(),
+            "[\n  [1, 2],\n  [3]\n]"
+        );
+        cfg.pretty = false;
+        assert_eq!(to_string_with_cfg(&value, cfg).unwrap(), "[[1,2],[3]]");
+
+        cfg.pretty = true;
+        cfg.pretty_style = crate::PrettyStyle::ElementPerLine;
+        // the single-key objects, so the output does not depend on the order of the keys
+        let value = crate::json!({"a": {"b": [[1, 2], {"c": 3}, []]}});
+        assert_eq!(
+            to_string_with_cfg(&value, cfg).unwrap(),
+            "{\n  \"a\": {\n    \"b\": [\n      [1,2],\n      {\"c\":3},\n      []\n    ]\n  }\n}"
+        );
+    }
 }
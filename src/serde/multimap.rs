@@ -0,0 +1,217 @@
+//! Deserialize the JSON objects with the duplicate keys into the ordered key-value pairs.
+
+use std::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use serde::{
+    de::{MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// The entries of a JSON object as the key-value pairs in order, which keeps all the duplicate
+/// keys.
+///
+/// The maps, such as `HashMap` and `BTreeMap`, keep only the last value of the duplicate keys.
+/// `MultiMap` deserializes each entry into a pair in the order of the JSON object, and is
+/// serialized as a JSON object with the same entries, so the duplicate keys are kept in a round
+/// trip. It works with any serde data format.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use sonic_rs::MultiMap;
+///
+/// #[derive(Debug, Deserialize, Serialize)]
+/// struct Request {
+///     headers: MultiMap<String, String>,
+/// }
+///
+/// let json = r#"{"headers":{"accept":"text/html","cookie":"a=1","cookie":"b=2"}}"#;
+/// let req: Request = sonic_rs::from_str(json).unwrap();
+/// let cookies: Vec<_> = req.headers.get_all("cookie").collect();
+/// assert_eq!(cookies, ["a=1", "b=2"]);
+/// assert_eq!(req.headers.len(), 3);
+/// assert_eq!(
+///     req.headers[0],
+///     ("accept".to_string(), "text/html".to_string())
+/// );
+///
+/// assert_eq!(sonic_rs::to_string(&req).unwrap(), json);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MultiMap<K, V>(pub Vec<(K, V)>);
+
+impl<K, V> MultiMap<K, V> {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Return the first value of the `key`.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: PartialEq<Q>,
+        Q: ?Sized,
+    {
+        self.get_all(key).next()
+    }
+
+    /// Iterate all the values of the `key` in order.
+    pub fn get_all<'a, Q>(&'a self, key: &'a Q) -> impl Iterator<Item = &'a V>
+    where
+        K: PartialEq<Q>,
+        Q: ?Sized,
+    {
+        self.0.iter().filter(move |(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Return the pairs.
+    pub fn into_inner(self) -> Vec<(K, V)> {
+        self.0
+    }
+}
+
+impl<K, V> Deref for MultiMap<K, V> {
+    type Target = Vec<(K, V)>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<K, V> DerefMut for MultiMap<K, V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<K, V> From<Vec<(K, V)>> for MultiMap<K, V> {
+    fn from(pairs: Vec<(K, V)>) -> Self {
+        Self(pairs)
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for MultiMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<K, V> IntoIterator for MultiMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a MultiMap<K, V> {
+    type Item = &'a (K, V);
+    type IntoIter = std::slice::Iter<'a, (K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<K: Serialize, V: Serialize> Serialize for MultiMap<K, V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (k, v) in &self.0 {
+            map.serialize_entry(k, v)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for MultiMap<K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(MultiMapVisitor(PhantomData))
+    }
+}
+
+struct MultiMapVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> Visitor<'de> for MultiMapVisitor<K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = MultiMap<K, V>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        // limit the preallocation, the size hint may be untrusted
+        let mut pairs = Vec::with_capacity(map.size_hint().unwrap_or(0).min(4096));
+        while let Some(pair) = map.next_entry()? {
+            pairs.push(pair);
+        }
+        Ok(MultiMap(pairs))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{from_str, json, to_string, Value};
+
+    #[test]
+    fn test_multimap() {
+        let json = r#"{"a": 1, "b": 2, "a": 3, "a": 4}"#;
+        let map: MultiMap<String, u8> = from_str(json).unwrap();
+        assert_eq!(
+            map.0,
+            [
+                ("a".to_string(), 1),
+                ("b".to_string(), 2),
+                ("a".to_string(), 3),
+                ("a".to_string(), 4)
+            ]
+        );
+        assert_eq!(map.get("a"), Some(&1));
+        assert_eq!(map.get_all("a").copied().collect::<Vec<_>>(), [1, 3, 4]);
+        assert_eq!(map.get("c"), None);
+        assert_eq!(to_string(&map).unwrap(), r#"{"a":1,"b":2,"a":3,"a":4}"#);
+
+        // the borrowed keys and the nested values
+        let json = r#"{"k": {"x": [1]}, "k": null}"#;
+        let map: MultiMap<&str, Value> = from_str(json).unwrap();
+        assert_eq!(map.get_all("k").count(), 2);
+        assert_eq!(map[0].1, json!({"x": [1]}));
+        assert_eq!(map[1].1, json!(null));
+
+        // the numeric keys
+        let map: MultiMap<u32, bool> = from_str(r#"{"1": true, "1": false}"#).unwrap();
+        assert_eq!(map.into_inner(), [(1, true), (1, false)]);
+
+        assert!(from_str::<MultiMap<String, u8>>("{}").unwrap().is_empty());
+        assert!(from_str::<MultiMap<String, u8>>(r#"{"a": "x"}"#).is_err());
+        assert!(from_str::<MultiMap<String, u8>>("[1]").is_err());
+
+        // the other data formats
+        let map: MultiMap<String, u8> = serde_json::from_str(r#"{"a": 1, "a": 2}"#).unwrap();
+        assert_eq!(map.len(), 2);
+    }
+}
@@ -5,6 +5,28 @@ use sonic_number::ParserNumber;
 use crate::{error::make_error, util::private::Sealed};
 
 /// Represents a JSON number, whether integer or floating point.
+///
+/// `Number` implements [`Eq`] and [`Hash`], so it can be used in the sets and as the keys of the
+/// maps. The comparison is by the type and the value: an integer is never equal to a float, so
+/// `1` and `1.0` are different numbers, while `0.0` and `-0.0` are the same. Use
+/// [`canonical`][Number::canonical] to compare the integral floats in the `i64` range with the
+/// integers.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// use sonic_rs::Number;
+///
+/// let one = Number::from(1);
+/// let one_f = Number::from_f64(1.0).unwrap();
+/// assert_ne!(one, one_f);
+/// assert_eq!(one, one_f.canonical());
+///
+/// let set: HashSet<Number> = [one, one_f].iter().map(Number::canonical).collect();
+/// assert_eq!(set.len(), 1);
+/// ```
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Number {
     pub(crate) n: N,
@@ -168,8 +190,33 @@ impl Number {
             None
         }
     }
+
+    /// Return the number with the integral floats in the `i64` range converted into integers,
+    /// such as `1.0` into `1` and `-0.0` into `0`, the same as the numbers in
+    /// [`canonical_hash_value`][crate::canonical_hash_value]. The other numbers are unchanged.
+    ///
+    /// The integral floats out of the `i64` range are still floats, so they are not equal to the
+    /// `u64` integers of the same value, such as `1e19` and `10000000000000000000`.
+    #[must_use]
+    pub fn canonical(&self) -> Number {
+        match self.n {
+            N::Float(f) if f.fract() == 0.0 && f.abs() < I64_RANGE => {
+                let i = f as i64;
+                let n = if i < 0 {
+                    N::NegInt(i)
+                } else {
+                    N::PosInt(i as u64)
+                };
+                Number { n }
+            }
+            _ => self.clone(),
+        }
+    }
 }
 
+// The integral floats in this range are converted to `i64` exactly.
+const I64_RANGE: f64 = 9223372036854775808.0;
+
 impl Display for Number {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         match self.n {
@@ -0,0 +1,209 @@
+//! Read the JSON text from a reader on another thread, while the read chunks are deserialized
+//! incrementally.
+
+use std::{
+    io::{self, Read},
+    sync::mpsc,
+    thread,
+};
+
+use serde::de::DeserializeOwned;
+
+use super::incremental::from_reader_with_chunk_size;
+use crate::error::{Error, Result};
+
+// A reader of the chunks read by the reader thread. The reader thread stops after the chunks are
+// dropped, such as the deserializing failed.
+struct ChunkReader {
+    chunks: mpsc::Receiver<io::Result<Vec<u8>>>,
+    // the consumed chunks are reused by the reader thread
+    free: mpsc::SyncSender<Vec<u8>>,
+    cur: Vec<u8>,
+    pos: usize,
+}
+
+impl io::Read for ChunkReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.cur.len() {
+            let next = match self.chunks.recv() {
+                Ok(chunk) => chunk?,
+                // the reader thread finished
+                Err(_) => return Ok(0),
+            };
+            let used = std::mem::replace(&mut self.cur, next);
+            self.pos = 0;
+            let _ = self.free.try_send(used);
+        }
+        let n = out.len().min(self.cur.len() - self.pos);
+        out[..n].copy_from_slice(&self.cur[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Deserialize `T` from `reader` with the incremental deserializer. The next chunk is read on
+/// another thread while the current chunk is deserialized, so the IO and the parsing are
+/// overlapped. At most two chunks are read ahead.
+///
+/// If the JSON text fits in the first chunk, it is parsed on the current thread, and no thread is
+/// spawned. The reader thread is not joined, so that a failed deserializing returns at once, even
+/// if the reader thread is blocked in reading, such as from an idle socket. The thread exits and
+/// drops the reader after its pending read returns.
+pub(crate) fn from_reader<R, T>(mut reader: R, chunk_size: usize) -> Result<T>
+where
+    R: io::Read + Send + 'static,
+    T: DeserializeOwned,
+{
+    let chunk_size = chunk_size.max(1);
+    let mut first = Vec::with_capacity(chunk_size);
+    if let Err(e) = read_chunk(&mut reader, &mut first, chunk_size) {
+        return Err(Error::io(e));
+    }
+    if first.len() < chunk_size {
+        return from_reader_with_chunk_size(first.as_slice(), chunk_size);
+    }
+
+    let (chunk_tx, chunk_rx) = mpsc::sync_channel(1);
+    let (free_tx, free_rx) = mpsc::sync_channel::<Vec<u8>>(2);
+    thread::spawn(move || loop {
+        let mut chunk = free_rx
+            .try_recv()
+            .unwrap_or_else(|_| Vec::with_capacity(chunk_size));
+        match read_chunk(&mut reader, &mut chunk, chunk_size) {
+            Ok(0) => break,
+            Ok(_) => {
+                // the deserializing is finished if the chunks are dropped
+                if chunk_tx.send(Ok(chunk)).is_err() {
+                    break;
+                }
+            }
+            Err(e) => {
+                let _ = chunk_tx.send(Err(e));
+                break;
+            }
+        }
+    });
+
+    let chunks = ChunkReader {
+        chunks: chunk_rx,
+        free: free_tx,
+        cur: first,
+        pos: 0,
+    };
+    from_reader_with_chunk_size(chunks, chunk_size)
+}
+
+// Read at most `chunk_size` bytes into the cleared `chunk`, and return the number of bytes. The
+// chunk is shorter than `chunk_size` only at the end of the reader.
+fn read_chunk<R: io::Read>(
+    reader: &mut R,
+    chunk: &mut Vec<u8>,
+    chunk_size: usize,
+) -> io::Result<usize> {
+    chunk.clear();
+    reader.by_ref().take(chunk_size as u64).read_to_end(chunk)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{from_str, Value};
+
+    // return at most `max` bytes in each read
+    struct Slow {
+        data: io::Cursor<Vec<u8>>,
+        max: usize,
+    }
+
+    impl Slow {
+        fn new(data: &[u8], max: usize) -> Self {
+            Self {
+                data: io::Cursor::new(data.to_vec()),
+                max,
+            }
+        }
+    }
+
+    impl io::Read for Slow {
+        fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+            let n = out.len().min(self.max);
+            self.data.read(&mut out[..n])
+        }
+    }
+
+    #[test]
+    fn test_from_reader_pipelined() {
+        let json = format!("[{}]", [r#"{"a": "😀é", "b": [1, 2.5]}"#; 200].join(", "));
+        let expect: Vec<Value> = from_str(&json).unwrap();
+        for chunk_size in [1, 7, 64, json.len(), json.len() + 1] {
+            for max in [1, 5, usize::MAX] {
+                let reader = Slow::new(json.as_bytes(), max);
+                let got: Vec<Value> = from_reader(reader, chunk_size).unwrap();
+                assert_eq!(got, expect, "chunk size {chunk_size}, max {max}");
+            }
+        }
+
+        // the errors of the JSON text and of the reader
+        let at = json.rfind("2.5").unwrap();
+        let invalid = format!("{}x{}", &json[..at], &json[at..]);
+        let expect = from_str::<Vec<Value>>(&invalid).unwrap_err();
+        let reader = Slow::new(invalid.as_bytes(), usize::MAX);
+        let err = from_reader::<_, Vec<Value>>(reader, 16).unwrap_err();
+        assert_eq!((err.line(), err.column()), (expect.line(), expect.column()));
+        let reader = Slow::new(&json.as_bytes()[..100], usize::MAX);
+        assert!(from_reader::<_, Vec<Value>>(reader, 16)
+            .unwrap_err()
+            .is_eof());
+
+        struct FailReader(usize);
+        impl io::Read for FailReader {
+            fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+                if self.0 == 0 {
+                    return Err(io::Error::new(io::ErrorKind::Other, "broken"));
+                }
+                self.0 -= 1;
+                out[0] = b'[';
+                Ok(1)
+            }
+        }
+        for n in [0, 100] {
+            let err = from_reader::<_, Vec<Value>>(FailReader(n), 16).unwrap_err();
+            assert!(err.is_io(), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_from_reader_pipelined_blocked() {
+        // a reader blocked until more data is sent, such as an idle socket
+        struct Blocking {
+            data: mpsc::Receiver<Vec<u8>>,
+            cur: io::Cursor<Vec<u8>>,
+        }
+        impl io::Read for Blocking {
+            fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+                loop {
+                    let n = self.cur.read(out)?;
+                    if n > 0 {
+                        return Ok(n);
+                    }
+                    match self.data.recv() {
+                        Ok(data) => self.cur = io::Cursor::new(data),
+                        Err(_) => return Ok(0),
+                    }
+                }
+            }
+        }
+
+        let (tx, rx) = mpsc::channel();
+        // the first chunk is full, so the rest is read on the reader thread
+        tx.send(b"[1, 2, x, 3, 4, 5".to_vec()).unwrap();
+        let reader = Blocking {
+            data: rx,
+            cur: io::Cursor::new(Vec::new()),
+        };
+        // the error is returned while the reader thread is still blocked
+        let err = from_reader::<_, Vec<Value>>(reader, 16).unwrap_err();
+        assert!(err.is_syntax(), "{err}");
+        drop(tx);
+    }
+}
@@ -0,0 +1,358 @@
+//! An unsized raw JSON text, borrowed as `&RawJson` or owned as `Box<RawJson>`.
+
+use std::fmt;
+
+use serde::{
+    de::{self, value::BorrowedStrDeserializer, DeserializeSeed, MapAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::de::tri;
+use crate::error::{Error, Result};
+
+// The same token as `serde_json::value::RawValue`, so that `RawJson` and `RawValue` work with both
+// `sonic_rs` and `serde_json`.
+pub(crate) const TOKEN: &str = "$serde_json::private::RawValue";
+
+/// A validated raw JSON text, which is kept as it is in the input.
+///
+/// It is an unsized type like `str`, so it is used as `&RawJson` borrowed from the input, or
+/// `Box<RawJson>` copied from the input, to pass a field through without parsing it into a tree.
+/// It uses the same protocol as `serde_json::value::RawValue`, so `RawJson` can be deserialized
+/// by `serde_json`, and `RawValue` can be deserialized by `sonic_rs`. Serializing `RawJson`
+/// writes the raw JSON text verbatim.
+///
+/// The differences from [`LazyValue`][crate::LazyValue]:
+/// - `&RawJson` is a plain reference instead of a wrapper with a lifetime parameter, and it always
+///   borrows from the input, so it can not be deserialized from a reader.
+/// - `RawJson` has no APIs to access the JSON value, such as `get` or iterators.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use sonic_rs::RawJson;
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct Event<'a> {
+///     id: u64,
+///     #[serde(borrow)]
+///     payload: &'a RawJson,
+///     extra: Box<RawJson>,
+/// }
+///
+/// let json = r#"{"id":1,"payload":{"a": [1, 2]},"extra":"A"}"#;
+/// let event: Event = sonic_rs::from_str(json).unwrap();
+/// assert_eq!(event.payload.as_raw_str(), r#"{"a": [1, 2]}"#);
+/// assert_eq!(event.extra.as_raw_str(), r#""A""#);
+///
+/// // the raw JSON text is written verbatim
+/// assert_eq!(sonic_rs::to_string(&event).unwrap(), json);
+///
+/// let raw = RawJson::from_string(" [true] ".to_string()).unwrap();
+/// assert_eq!(raw.as_raw_str(), "[true]");
+/// assert!(RawJson::from_string("[true".to_string()).is_err());
+/// ```
+#[repr(transparent)]
+pub struct RawJson {
+    json: str,
+}
+
+impl RawJson {
+    fn from_borrowed(json: &str) -> &Self {
+        // SAFETY: `RawJson` is `repr(transparent)` over `str`
+        unsafe { &*(json as *const str as *const RawJson) }
+    }
+
+    fn from_owned(json: Box<str>) -> Box<Self> {
+        // SAFETY: `RawJson` is `repr(transparent)` over `str`
+        unsafe { Box::from_raw(Box::into_raw(json) as *mut RawJson) }
+    }
+
+    fn into_owned(raw: Box<Self>) -> Box<str> {
+        // SAFETY: `RawJson` is `repr(transparent)` over `str`
+        unsafe { Box::from_raw(Box::into_raw(raw) as *mut str) }
+    }
+
+    /// Validate the JSON text and convert it into `Box<RawJson>`. The leading and trailing
+    /// whitespaces are trimmed.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if `json` is not a single valid JSON value.
+    pub fn from_string(json: String) -> Result<Box<Self>> {
+        let raw: &Self = tri!(crate::from_str(&json));
+        if raw.json.len() < json.len() {
+            return Ok(raw.to_owned());
+        }
+        Ok(Self::from_owned(json.into_boxed_str()))
+    }
+
+    /// Return the raw JSON text.
+    pub fn as_raw_str(&self) -> &str {
+        &self.json
+    }
+}
+
+/// Serialize `value` into a `Box<RawJson>`.
+///
+/// # Errors
+///
+/// Return an error if `value` fails to serialize.
+///
+/// # Examples
+///
+/// ```
+/// let raw = sonic_rs::to_raw_json(&vec![1, 2]).unwrap();
+/// assert_eq!(raw.as_raw_str(), "[1,2]");
+/// ```
+pub fn to_raw_json<T>(value: &T) -> Result<Box<RawJson>>
+where
+    T: ?Sized + Serialize,
+{
+    let json = tri!(crate::to_string(value));
+    Ok(RawJson::from_owned(json.into_boxed_str()))
+}
+
+impl Clone for Box<RawJson> {
+    fn clone(&self) -> Self {
+        (**self).to_owned()
+    }
+}
+
+impl ToOwned for RawJson {
+    type Owned = Box<RawJson>;
+
+    fn to_owned(&self) -> Self::Owned {
+        RawJson::from_owned(self.json.to_owned().into_boxed_str())
+    }
+}
+
+impl Default for Box<RawJson> {
+    fn default() -> Self {
+        RawJson::from_borrowed("null").to_owned()
+    }
+}
+
+impl From<Box<RawJson>> for Box<str> {
+    fn from(raw: Box<RawJson>) -> Self {
+        RawJson::into_owned(raw)
+    }
+}
+
+impl AsRef<str> for RawJson {
+    fn as_ref(&self) -> &str {
+        &self.json
+    }
+}
+
+impl fmt::Debug for RawJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RawJson")
+            .field(&format_args!("{}", &self.json))
+            .finish()
+    }
+}
+
+impl fmt::Display for RawJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.json)
+    }
+}
+
+impl Serialize for RawJson {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct(TOKEN, 1)?;
+        s.serialize_field(TOKEN, &self.json)?;
+        s.end()
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for &'a RawJson {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BorrowedVisitor;
+
+        impl<'de> Visitor<'de> for BorrowedVisitor {
+            type Value = &'de RawJson;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any valid JSON value")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                if map.next_key::<TokenKey>()?.is_none() {
+                    return Err(de::Error::invalid_type(de::Unexpected::Map, &self));
+                }
+                map.next_value::<&'de str>().map(RawJson::from_borrowed)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, BorrowedVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Box<RawJson> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BoxedVisitor;
+
+        impl<'de> Visitor<'de> for BoxedVisitor {
+            type Value = Box<RawJson>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any valid JSON value")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                if map.next_key::<TokenKey>()?.is_none() {
+                    return Err(de::Error::invalid_type(de::Unexpected::Map, &self));
+                }
+                let json: String = map.next_value()?;
+                Ok(RawJson::from_owned(json.into_boxed_str()))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, BoxedVisitor)
+    }
+}
+
+// The only key of the map visited by the raw JSON visitors.
+struct TokenKey;
+
+impl<'de> Deserialize<'de> for TokenKey {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct KeyVisitor;
+
+        impl Visitor<'_> for KeyVisitor {
+            type Value = TokenKey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("the raw JSON token")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Self::Value, E> {
+                if v == TOKEN {
+                    Ok(TokenKey)
+                } else {
+                    Err(de::Error::custom("unexpected raw JSON"))
+                }
+            }
+        }
+
+        deserializer.deserialize_identifier(KeyVisitor)
+    }
+}
+
+// Visit the raw JSON text as a map with the single entry `TOKEN`, the same as `serde_json`.
+pub(crate) struct RawJsonAccess<'de> {
+    raw: Option<&'de str>,
+}
+
+impl<'de> RawJsonAccess<'de> {
+    pub(crate) fn new(raw: &'de str) -> Self {
+        Self { raw: Some(raw) }
+    }
+}
+
+impl<'de> MapAccess<'de> for RawJsonAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.raw.is_none() {
+            return Ok(None);
+        }
+        seed.deserialize(BorrowedStrDeserializer::new(TOKEN))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let raw = self
+            .raw
+            .take()
+            .expect("next_value_seed is called before next_key_seed");
+        seed.deserialize(BorrowedStrDeserializer::new(raw))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{from_str, json, to_string, to_value, Value};
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Item<'a> {
+        #[serde(borrow)]
+        a: &'a RawJson,
+        b: Box<RawJson>,
+        c: Option<Box<RawJson>>,
+    }
+
+    #[test]
+    fn test_raw_json() {
+        let json = r#"{"a": [1, {"x": "\n"}] , "b":"\"s\"", "c": null}"#;
+        let item: Item = from_str(json).unwrap();
+        assert_eq!(item.a.as_raw_str(), r#"[1, {"x": "\n"}]"#);
+        assert_eq!(item.b.as_raw_str(), r#""\"s\"""#);
+        assert!(item.c.is_none());
+        assert_eq!(
+            to_string(&item).unwrap(),
+            r#"{"a":[1, {"x": "\n"}],"b":"\"s\"","c":null}"#
+        );
+        let value: Value = to_value(&item).unwrap();
+        assert_eq!(
+            value,
+            json!({"a": [1, {"x": "\n"}], "b": "\"s\"", "c": null})
+        );
+
+        // the interop with serde_json
+        let item: Item = serde_json::from_str(json).unwrap();
+        assert_eq!(item.a.as_raw_str(), r#"[1, {"x": "\n"}]"#);
+        assert_eq!(
+            serde_json::to_string(&item).unwrap(),
+            r#"{"a":[1, {"x": "\n"}],"b":"\"s\"","c":null}"#
+        );
+        let raw: &serde_json::value::RawValue = from_str(" {\"k\": 1} ").unwrap();
+        assert_eq!(raw.get(), r#"{"k": 1}"#);
+        assert_eq!(to_string(raw).unwrap(), r#"{"k": 1}"#);
+
+        // the owned raw JSON
+        let raw = RawJson::from_string("\t1.50 ".to_string()).unwrap();
+        assert_eq!(raw.as_raw_str(), "1.50");
+        assert_eq!(raw.clone().to_string(), "1.50");
+        assert_eq!(format!("{raw:?}"), "RawJson(1.50)");
+        assert_eq!(Box::<str>::from(raw).as_ref(), "1.50");
+        assert_eq!(Box::<RawJson>::default().as_raw_str(), "null");
+        assert_eq!(to_raw_json("a").unwrap().as_raw_str(), r#""a""#);
+
+        assert!(RawJson::from_string("{".to_string()).is_err());
+        assert!(RawJson::from_string("1 2".to_string()).is_err());
+        assert!(from_str::<Item>(r#"{"a": [1, "b": 1, "c": 1}"#).is_err());
+        assert!(from_str::<&RawJson>("").is_err());
+    }
+}
@@ -1,7 +1,10 @@
 use ::serde::{
     de, de::Visitor, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer,
 };
-use ::std::fmt;
+use ::std::{
+    fmt,
+    hash::{Hash, Hasher},
+};
 use faststr::FastStr;
 
 use super::number::Number;
@@ -28,11 +31,161 @@ use crate::{util::private::Sealed, Error, JsonNumberTrait};
 /// assert_eq!(num.as_f64(), Some(1.2333333333333334));
 /// assert_eq!(num.as_str(), "1.2333333333333333333333333333333333333333");
 /// ```
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+///
+/// Example2:
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// use sonic_rs::RawNumber;
+///
+/// // RawNumber is compared and hashed by the decimal value of the text, without precision loss.
+/// let a: RawNumber = sonic_rs::from_str("1.50").unwrap();
+/// let b: RawNumber = sonic_rs::from_str("15e-1").unwrap();
+/// let c: RawNumber = sonic_rs::from_str("1.5000000000000000000001").unwrap();
+/// assert_eq!(a, b);
+/// assert_ne!(a, c);
+///
+/// let set: HashSet<RawNumber> = [a, b, c].into_iter().collect();
+/// assert_eq!(set.len(), 2);
+/// ```
+#[derive(Clone, Debug)]
 pub struct RawNumber {
     n: FastStr,
 }
 
+// The decimal value of the number text, which is the digits of `int` and `frac` times `10^exp`.
+// The leading and trailing zeros of the digits are trimmed, so that the same values have the same
+// form. The zero has no digits and is not negative.
+struct Decimal<'a> {
+    neg: bool,
+    exp: i64,
+    int: &'a [u8],
+    frac: &'a [u8],
+}
+
+impl<'a> Decimal<'a> {
+    // Return `None` if the text is not a number.
+    fn parse(text: &'a str) -> Option<Self> {
+        let s = text.as_bytes();
+        let (neg, s) = match s.split_first() {
+            Some((b'-', rest)) => (true, rest),
+            _ => (false, s),
+        };
+        let (mant, exp) = match s.iter().position(|&c| c == b'e' || c == b'E') {
+            Some(i) => (&s[..i], parse_exp(&s[i + 1..])?),
+            None => (s, 0),
+        };
+        let (int, frac) = match mant.iter().position(|&c| c == b'.') {
+            Some(i) => (&mant[..i], &mant[i + 1..]),
+            None => (mant, &[][..]),
+        };
+        if int.is_empty() || !int.iter().chain(frac).all(u8::is_ascii_digit) {
+            return None;
+        }
+
+        let frac = trim_end_zeros(frac);
+        let mut exp = exp.saturating_sub(frac.len() as i64);
+        let mut int = trim_start_zeros(int);
+        let frac = if int.is_empty() {
+            trim_start_zeros(frac)
+        } else {
+            frac
+        };
+        if frac.is_empty() {
+            let trimmed = trim_end_zeros(int);
+            exp = exp.saturating_add((int.len() - trimmed.len()) as i64);
+            int = trimmed;
+        }
+        let zero = int.is_empty() && frac.is_empty();
+        Some(Decimal {
+            neg: neg && !zero,
+            exp: if zero { 0 } else { exp },
+            int,
+            frac,
+        })
+    }
+
+    fn digits(&self) -> impl Iterator<Item = u8> + '_ {
+        self.int.iter().chain(self.frac).copied()
+    }
+}
+
+impl PartialEq for Decimal<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.neg == other.neg
+            && self.exp == other.exp
+            && self.int.len() + self.frac.len() == other.int.len() + other.frac.len()
+            && self.digits().eq(other.digits())
+    }
+}
+
+impl Hash for Decimal<'_> {
+    fn hash<H: Hasher>(&self, h: &mut H) {
+        self.neg.hash(h);
+        self.exp.hash(h);
+        // the digits may be split differently, hash them one by one
+        for d in self.digits() {
+            h.write_u8(d);
+        }
+    }
+}
+
+// The exponent is saturated, the numbers with the huge exponents are not supported exactly.
+fn parse_exp(s: &[u8]) -> Option<i64> {
+    let (neg, s) = match s.split_first() {
+        Some((b'-', rest)) => (true, rest),
+        Some((b'+', rest)) => (false, rest),
+        _ => (false, s),
+    };
+    if s.is_empty() {
+        return None;
+    }
+    let exp = s.iter().try_fold(0i64, |exp, &c| {
+        c.is_ascii_digit()
+            .then(|| exp.saturating_mul(10).saturating_add((c - b'0') as i64))
+    })?;
+    Some(if neg { -exp } else { exp })
+}
+
+fn trim_start_zeros(s: &[u8]) -> &[u8] {
+    let n = s.iter().take_while(|&&c| c == b'0').count();
+    &s[n..]
+}
+
+fn trim_end_zeros(s: &[u8]) -> &[u8] {
+    let n = s.iter().rev().take_while(|&&c| c == b'0').count();
+    &s[..s.len() - n]
+}
+
+/// Compare two JSON number texts by the decimal values, the same as comparing [`RawNumber`]s.
+pub(crate) fn number_text_eq(a: &str, b: &str) -> bool {
+    match (Decimal::parse(a), Decimal::parse(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// The numbers are equal if their decimal values are equal, such as `1.50`, `15e-1` and `1.5`.
+/// The integers and the floats are compared in the same way, so `1` and `1.0` are equal.
+impl PartialEq for RawNumber {
+    fn eq(&self, other: &Self) -> bool {
+        number_text_eq(&self.n, &other.n)
+    }
+}
+
+impl Eq for RawNumber {}
+
+/// The numbers are hashed by the decimal value, consistent with [`PartialEq`].
+impl Hash for RawNumber {
+    fn hash<H: Hasher>(&self, h: &mut H) {
+        match Decimal::parse(&self.n) {
+            Some(d) => d.hash(h),
+            None => self.n.hash(h),
+        }
+    }
+}
+
 impl RawNumber {
     pub(crate) fn new(s: &str) -> Self {
         Self { n: FastStr::new(s) }
@@ -46,6 +199,112 @@ impl RawNumber {
     pub fn as_str(&self) -> &str {
         self.n.as_str()
     }
+
+    /// Classify the number text by its form, without converting it. It is cheap to decide how
+    /// to convert the number, such as into `i64`, `i128` or a decimal type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::{NumberKind, RawNumber};
+    ///
+    /// let raw = |s: &str| -> RawNumber { sonic_rs::from_str(s).unwrap() };
+    ///
+    /// assert_eq!(
+    ///     raw("-9223372036854775808").classify().kind,
+    ///     NumberKind::Integer
+    /// );
+    /// assert_eq!(
+    ///     raw("18446744073709551616").classify().kind,
+    ///     NumberKind::BigInt
+    /// );
+    /// assert_eq!(raw("1.50").classify().kind, NumberKind::Float);
+    ///
+    /// let class = raw("-12.345E+6").classify();
+    /// assert_eq!(class.kind, NumberKind::Scientific);
+    /// assert!(class.negative);
+    /// assert_eq!(
+    ///     (class.int_digits, class.frac_digits, class.exponent),
+    ///     (2, 3, 6)
+    /// );
+    /// ```
+    pub fn classify(&self) -> NumberClass {
+        let s = self.n.as_bytes();
+        let (negative, s) = match s.split_first() {
+            Some((b'-', rest)) => (true, rest),
+            _ => (false, s),
+        };
+        let (mant, exp) = match s.iter().position(|&c| c == b'e' || c == b'E') {
+            Some(i) => (&s[..i], Some(parse_exp(&s[i + 1..]).unwrap_or_default())),
+            None => (s, None),
+        };
+        let (int, frac) = match mant.iter().position(|&c| c == b'.') {
+            Some(i) => (&mant[..i], Some(&mant[i + 1..])),
+            None => (mant, None),
+        };
+        let kind = match (frac, exp) {
+            (_, Some(_)) => NumberKind::Scientific,
+            (Some(_), None) => NumberKind::Float,
+            (None, None) if fits_integer(negative, int) => NumberKind::Integer,
+            (None, None) => NumberKind::BigInt,
+        };
+        NumberClass {
+            kind,
+            negative,
+            int_digits: int.len(),
+            frac_digits: frac.map_or(0, <[u8]>::len),
+            exponent: exp.unwrap_or_default(),
+        }
+    }
+
+    /// Return whether the number text is an integer, without the fraction and the exponent. The
+    /// integer may be out of the range of `i64` and `u64`.
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self.classify().kind,
+            NumberKind::Integer | NumberKind::BigInt
+        )
+    }
+}
+
+/// The form of a JSON number text, returned by [`RawNumber::classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NumberKind {
+    /// An integer in the range of `i64` or `u64`, such as `-1` and `18446744073709551615`.
+    Integer,
+    /// An integer out of the range of `i64` and `u64`.
+    BigInt,
+    /// A number with the fraction and without the exponent, such as `1.0`.
+    Float,
+    /// A number with the exponent, such as `1e3` and `1.5E-3`.
+    Scientific,
+}
+
+/// The classification of a JSON number text, returned by [`RawNumber::classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct NumberClass {
+    /// The form of the number.
+    pub kind: NumberKind,
+    /// Whether the number has the minus sign, including `-0`.
+    pub negative: bool,
+    /// The count of the digits before the decimal point.
+    pub int_digits: usize,
+    /// The count of the digits after the decimal point, including the trailing zeros.
+    pub frac_digits: usize,
+    /// The exponent as written, or zero without the exponent. It is saturated if out of `i64`.
+    pub exponent: i64,
+}
+
+// Whether the integer digits are in the range of `i64` or `u64`. The JSON integers have no leading
+// zeros, so the digits are compared with the bounds by the length at first.
+fn fits_integer(negative: bool, digits: &[u8]) -> bool {
+    let max: &[u8] = if negative {
+        b"9223372036854775808"
+    } else {
+        b"18446744073709551615"
+    };
+    digits.len() < max.len() || (digits.len() == max.len() && digits <= max)
 }
 
 pub(crate) const TOKEN: &str = "$sonic_rs::private::JsonNumber";
@@ -71,6 +330,14 @@ impl<'de> Deserialize<'de> for RawNumber {
             {
                 Ok(RawNumber::new(raw))
             }
+
+            // the extended numbers are normalized into the owned texts
+            fn visit_str<E>(self, raw: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawNumber::new(raw))
+            }
         }
 
         deserializer.deserialize_newtype_struct(TOKEN, JsonNumberVisitor)
@@ -16,16 +16,23 @@ use serde::{
 
 use super::de::tri;
 use crate::{
+    config::{FloatFormat, PrettyStyle, SerializeCfg},
     error::{Error, ErrorCode, Result},
-    format::{CompactFormatter, Formatter, PrettyFormatter},
+    format::{format_float, write_string_escaped, CompactFormatter, Formatter, PrettyFormatter},
     lazyvalue::value::HasEsc,
-    writer::WriteExt,
+    util::string::Escapes,
+    writer::{BufferedWriter, FmtWriter, WriteExt},
     OwnedLazyValue,
 };
 /// A structure for serializing Rust values into JSON.
 pub struct Serializer<W, F = CompactFormatter> {
     writer: W,
     formatter: F,
+    // write the fields of structs sorted by name
+    sort_fields: bool,
+    // the chars escaped besides the ones required by JSON
+    escapes: Escapes,
+    float_format: FloatFormat,
     // TODO: record has_escape to optimize lazyvalue
     // has_escape: bool,
 }
@@ -39,6 +46,75 @@ where
     pub fn new(writer: W) -> Self {
         Serializer::with_formatter(writer, CompactFormatter)
     }
+
+    /// Write the fields of structs and struct variants sorted by name, instead of the declaration
+    /// order. The maps, including the `#[serde(flatten)]` fields, keep their order.
+    ///
+    /// The output is the same for the structs whose fields are reordered in the source, which is
+    /// useful to sign the JSON from services built at different versions. Each field value is
+    /// serialized into a buffer before the struct is written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use sonic_rs::Serializer;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Payload {
+    ///     nonce: u64,
+    ///     body: Body,
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// struct Body {
+    ///     z: bool,
+    ///     a: Vec<u8>,
+    /// }
+    ///
+    /// let payload = Payload {
+    ///     nonce: 7,
+    ///     body: Body {
+    ///         z: true,
+    ///         a: vec![1],
+    ///     },
+    /// };
+    /// let mut ser = Serializer::new(Vec::new()).sort_struct_fields();
+    /// payload.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     ser.into_inner(),
+    ///     br#"{"body":{"a":[1],"z":true},"nonce":7}"#
+    /// );
+    /// ```
+    #[inline]
+    pub fn sort_struct_fields(mut self) -> Self {
+        self.sort_fields = true;
+        self
+    }
+}
+
+impl Serializer<Vec<u8>> {
+    /// Creates a new JSON serializer writing into a `Vec<u8>` with the given initial capacity.
+    ///
+    /// It avoids growing the buffer many times if the size of the output is known, such as the
+    /// messages with a fixed schema. Use [`into_inner`][Serializer::into_inner] to get the output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use sonic_rs::Serializer;
+    ///
+    /// let mut ser = Serializer::with_capacity(1024);
+    /// vec![1, 2, 3].serialize(&mut ser).unwrap();
+    /// let out = ser.into_inner();
+    /// assert_eq!(out, b"[1,2,3]");
+    /// assert!(out.capacity() >= 1024);
+    /// ```
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Serializer::new(Vec::with_capacity(capacity))
+    }
 }
 
 impl<'a, W> Serializer<W, PrettyFormatter<'a>>
@@ -61,7 +137,97 @@ where
     /// specified.
     #[inline]
     pub fn with_formatter(writer: W, formatter: F) -> Self {
-        Serializer { writer, formatter }
+        Serializer {
+            writer,
+            formatter,
+            sort_fields: false,
+            escapes: Escapes::default(),
+            float_format: FloatFormat::Shortest,
+        }
+    }
+
+    /// Escape U+2028 LINE SEPARATOR and U+2029 PARAGRAPH SEPARATOR in the strings as `\u2028`
+    /// and `\u2029`.
+    ///
+    /// They are valid in the JSON strings, but terminate the string literals in JavaScript before
+    /// ES2019, such as the JSON embedded in a `<script>` tag or evaluated as JavaScript. The
+    /// escaped chars are found by the same SIMD scan as the quotes, so the strings without them
+    /// are still copied by the fast path. The raw JSON values, such as
+    /// [`LazyValue`][crate::LazyValue], are written as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use sonic_rs::Serializer;
+    ///
+    /// let mut ser = Serializer::new(Vec::new()).escape_line_separators();
+    /// "a\u{2028}b\u{2029}".serialize(&mut ser).unwrap();
+    /// assert_eq!(ser.into_inner(), br#""a\u2028b\u2029""#);
+    /// ```
+    #[inline]
+    pub fn escape_line_separators(mut self) -> Self {
+        self.escapes.line_separators = true;
+        self
+    }
+
+    /// Escape DEL (U+007F) and the C1 control chars (U+0080 to U+009F) in the strings, such as
+    /// `\u007f`, which are valid in JSON but invisible or unsafe in some terminals and logs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use sonic_rs::Serializer;
+    ///
+    /// let mut ser = Serializer::new(Vec::new()).escape_controls();
+    /// "\x7f\u{85}é".serialize(&mut ser).unwrap();
+    /// assert_eq!(ser.into_inner(), r#""\u007f\u0085é""#.as_bytes());
+    /// ```
+    #[inline]
+    pub fn escape_controls(mut self) -> Self {
+        self.escapes.controls = true;
+        self
+    }
+
+    /// Write the finite floats in `format`, such as the fixed number of decimals or the same text
+    /// as ECMAScript. The default is [`FloatFormat::Shortest`]. The floats in the map keys are
+    /// written in the same format, and the raw numbers, such as [`RawNumber`][crate::RawNumber],
+    /// are written as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use sonic_rs::{FloatFormat, Serializer};
+    ///
+    /// let mut ser = Serializer::new(Vec::new()).float_format(FloatFormat::EcmaScript);
+    /// (2.0, 1.5e-7, 1e300).serialize(&mut ser).unwrap();
+    /// assert_eq!(ser.into_inner(), b"[2,1.5e-7,1e+300]");
+    /// ```
+    #[inline]
+    pub fn float_format(mut self, format: FloatFormat) -> Self {
+        self.float_format = format;
+        self
+    }
+
+    /// Write the finite floats with `decimals` digits after the point, such as `1.50`, which keeps
+    /// the text of the same values stable for the textual diffs. It is the same as
+    /// [`float_format`][Serializer::float_format] with [`FloatFormat::Fixed`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use sonic_rs::Serializer;
+    ///
+    /// let mut ser = Serializer::new(Vec::new()).with_float_precision(2);
+    /// vec![1.5, 2.0, 0.126, -3.14159].serialize(&mut ser).unwrap();
+    /// assert_eq!(ser.into_inner(), b"[1.50,2.00,0.13,-3.14]");
+    /// ```
+    #[inline]
+    pub fn with_float_precision(self, decimals: usize) -> Self {
+        self.float_format(FloatFormat::Fixed(decimals))
     }
 
     /// Unwrap the `Writer` from the `Serializer`.
@@ -69,6 +235,125 @@ where
     pub fn into_inner(self) -> W {
         self.writer
     }
+
+    // Write the finite float in the format other than `FloatFormat::Shortest`.
+    fn write_formatted_float<T>(&mut self, value: T) -> Result<()>
+    where
+        T: ryu::Float + Display,
+    {
+        let mut buf = String::with_capacity(32);
+        format_float(value, self.float_format, &mut buf);
+        self.formatter
+            .write_number_str(&mut self.writer, &buf)
+            .map_err(Error::io)
+    }
+
+    // Write the object of the struct fields sorted by name, whose values are serialized in `buf`.
+    fn write_sorted_fields(&mut self, mut fields: Fields, buf: &[u8]) -> Result<()> {
+        use ser::Serializer as _;
+
+        // the field names of a struct are unique
+        fields.sort_unstable_by_key(|&(key, ..)| key);
+        tri!(self
+            .formatter
+            .begin_object(&mut self.writer)
+            .map_err(Error::io));
+        for (i, &(key, start, end)) in fields.iter().enumerate() {
+            tri!(self
+                .formatter
+                .begin_object_key(&mut self.writer, i == 0)
+                .map_err(Error::io));
+            tri!(self.serialize_str(key));
+            tri!(self
+                .formatter
+                .end_object_key(&mut self.writer)
+                .map_err(Error::io));
+            tri!(self
+                .formatter
+                .begin_object_value(&mut self.writer)
+                .map_err(Error::io));
+            // the serializer only writes valid UTF-8
+            let raw = unsafe { std::str::from_utf8_unchecked(&buf[start..end]) };
+            tri!(self
+                .formatter
+                .write_raw_value(&mut self.writer, raw)
+                .map_err(Error::io));
+            tri!(self
+                .formatter
+                .end_object_value(&mut self.writer)
+                .map_err(Error::io));
+        }
+        self.formatter
+            .end_object(&mut self.writer)
+            .map_err(Error::io)
+    }
+}
+
+// The names of the struct fields, and the ranges of their serialized values.
+type Fields = Vec<(&'static str, usize, usize)>;
+
+/// A map key which is already a quoted and escaped JSON string.
+///
+/// When serialized as a map key by [`Serializer`], the raw key is written verbatim, without being
+/// escaped again. This is useful to pass keys through from [`LazyValue`][`crate::LazyValue`] or
+/// other raw JSON sources without copying or re-escaping them.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use sonic_rs::RawKey;
+///
+/// let mut map = BTreeMap::new();
+/// map.insert(RawKey::new(r#""a\nb""#).unwrap(), 1);
+/// assert_eq!(sonic_rs::to_string(&map).unwrap(), r#"{"a\nb":1}"#);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RawKey<'a>(&'a str);
+
+impl<'a> RawKey<'a> {
+    /// Create a raw key from a quoted JSON string, such as `"a\nb"` including the quotes.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if `raw` is not a valid JSON string.
+    pub fn new(raw: &'a str) -> Result<Self> {
+        use crate::JsonValueTrait;
+
+        let lv: crate::LazyValue<'a> = tri!(crate::from_str(raw));
+        if lv.is_str() && lv.as_raw_str().len() == raw.len() {
+            Ok(RawKey(raw))
+        } else {
+            Err(key_must_be_str_or_num(Unexpected::Other("raw json")))
+        }
+    }
+
+    /// Create a raw key from a quoted JSON string without validation.
+    ///
+    /// The caller guarantees that `raw` is a valid JSON string including the quotes, otherwise the
+    /// serialized JSON will be invalid.
+    pub fn new_trusted(raw: &'a str) -> Self {
+        RawKey(raw)
+    }
+
+    /// Return the raw key, including the quotes.
+    pub fn as_raw_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl Serialize for RawKey<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        use ser::SerializeStruct;
+
+        let mut s = tri!(serializer.serialize_struct(crate::value::Value::RAW_TOKEN, 1));
+        tri!(s.serialize_field(crate::value::Value::RAW_TOKEN, self.0));
+        s.end()
+    }
 }
 
 impl<'a, W, F> ser::Serializer for &'a mut Serializer<W, F>
@@ -169,6 +454,7 @@ where
                 .formatter
                 .write_null(&mut self.writer)
                 .map_err(Error::io),
+            _ if self.float_format != FloatFormat::Shortest => self.write_formatted_float(value),
             _ => self
                 .formatter
                 .write_f32(&mut self.writer, value)
@@ -183,6 +469,7 @@ where
                 .formatter
                 .write_null(&mut self.writer)
                 .map_err(Error::io),
+            _ if self.float_format != FloatFormat::Shortest => self.write_formatted_float(value),
             _ => self
                 .formatter
                 .write_f64(&mut self.writer, value)
@@ -199,9 +486,13 @@ where
 
     #[inline]
     fn serialize_str(self, value: &str) -> Result<()> {
-        self.formatter
-            .write_string_fast(&mut self.writer, value, true)
-            .map_err(Error::io)
+        if self.escapes.is_none() {
+            self.formatter
+                .write_string_fast(&mut self.writer, value, true)
+        } else {
+            write_string_escaped(&mut self.writer, value, true, self.escapes)
+        }
+        .map_err(Error::io)
     }
 
     #[inline]
@@ -387,9 +678,16 @@ where
     #[inline]
     fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
         match name {
-            crate::serde::rawnumber::TOKEN | crate::lazyvalue::TOKEN => {
-                Ok(Compound::RawValue { ser: self })
-            }
+            crate::serde::rawnumber::TOKEN
+            | crate::lazyvalue::TOKEN
+            | crate::serde::rawjson::TOKEN
+            | crate::value::Value::RAW_TOKEN => Ok(Compound::RawValue { ser: self }),
+            _ if self.sort_fields => Ok(Compound::Sorted {
+                ser: self,
+                fields: Vec::with_capacity(len),
+                buf: Vec::new(),
+                variant: false,
+            }),
             _ => self.serialize_map(Some(len)),
         }
     }
@@ -419,6 +717,14 @@ where
             .formatter
             .begin_object_value(&mut self.writer)
             .map_err(Error::io));
+        if self.sort_fields {
+            return Ok(Compound::Sorted {
+                ser: self,
+                fields: Vec::with_capacity(len),
+                buf: Vec::new(),
+                variant: true,
+            });
+        }
         self.serialize_map(Some(len))
     }
 
@@ -432,6 +738,7 @@ where
         struct Adapter<'ser, W: 'ser, F: 'ser> {
             writer: &'ser mut W,
             formatter: &'ser mut F,
+            escapes: Escapes,
             error: Option<io::Error>,
         }
 
@@ -442,7 +749,12 @@ where
         {
             fn write_str(&mut self, s: &str) -> fmt::Result {
                 debug_assert!(self.error.is_none());
-                match self.formatter.write_string_fast(self.writer, s, false) {
+                let ret = if self.escapes.is_none() {
+                    self.formatter.write_string_fast(self.writer, s, false)
+                } else {
+                    write_string_escaped(self.writer, s, false, self.escapes)
+                };
+                match ret {
                     Ok(()) => Ok(()),
                     Err(err) => {
                         self.error = Some(err);
@@ -459,6 +771,7 @@ where
         let mut adapter = Adapter {
             writer: &mut self.writer,
             formatter: &mut self.formatter,
+            escapes: self.escapes,
             error: None,
         };
 
@@ -498,6 +811,14 @@ pub enum Compound<'a, W: 'a, F: 'a> {
     RawValue {
         ser: &'a mut Serializer<W, F>,
     },
+
+    Sorted {
+        ser: &'a mut Serializer<W, F>,
+        fields: Fields,
+        buf: Vec<u8>,
+        // whether the struct is the value of a struct variant
+        variant: bool,
+    },
 }
 
 impl<'a, W, F> ser::SerializeSeq for Compound<'a, W, F>
@@ -526,7 +847,7 @@ where
                     .map_err(Error::io)
             }
 
-            Compound::RawValue { .. } => unreachable!(),
+            Compound::RawValue { .. } | Compound::Sorted { .. } => unreachable!(),
         }
     }
 
@@ -538,7 +859,7 @@ where
                 _ => ser.formatter.end_array(&mut ser.writer).map_err(Error::io),
             },
 
-            Compound::RawValue { .. } => unreachable!(),
+            Compound::RawValue { .. } | Compound::Sorted { .. } => unreachable!(),
         }
     }
 }
@@ -618,7 +939,7 @@ where
                 ser.formatter.end_object(&mut ser.writer).map_err(Error::io)
             }
 
-            Compound::RawValue { .. } => unreachable!(),
+            Compound::RawValue { .. } | Compound::Sorted { .. } => unreachable!(),
         }
     }
 }
@@ -651,7 +972,7 @@ where
                     .map_err(Error::io)
             }
 
-            Compound::RawValue { .. } => unreachable!(),
+            Compound::RawValue { .. } | Compound::Sorted { .. } => unreachable!(),
         }
     }
 
@@ -672,7 +993,7 @@ where
                     .map_err(Error::io)
             }
 
-            Compound::RawValue { .. } => unreachable!(),
+            Compound::RawValue { .. } | Compound::Sorted { .. } => unreachable!(),
         }
     }
 
@@ -684,7 +1005,7 @@ where
                 _ => ser.formatter.end_object(&mut ser.writer).map_err(Error::io),
             },
 
-            Compound::RawValue { .. } => unreachable!(),
+            Compound::RawValue { .. } | Compound::Sorted { .. } => unreachable!(),
         }
     }
 }
@@ -708,13 +1029,27 @@ where
             Compound::RawValue { ser, .. } => {
                 if key == crate::serde::rawnumber::TOKEN
                     || key == crate::lazyvalue::TOKEN
+                    || key == crate::serde::rawjson::TOKEN
                     || key == crate::value::Value::RAW_TOKEN
                 {
-                    value.serialize(RawValueStrEmitter(ser))
+                    value.serialize(RawValueStrEmitter(ser, false))
                 } else {
                     Err(invalid_raw_value())
                 }
             }
+
+            Compound::Sorted {
+                ser, fields, buf, ..
+            } => {
+                let start = buf.len();
+                let (escapes, float_format) = (ser.escapes, ser.float_format);
+                let mut ser = Serializer::new(&mut *buf).sort_struct_fields();
+                ser.escapes = escapes;
+                ser.float_format = float_format;
+                tri!(value.serialize(&mut ser));
+                fields.push((key, start, buf.len()));
+                Ok(())
+            }
         }
     }
 
@@ -724,6 +1059,10 @@ where
             Compound::Map { .. } => ser::SerializeMap::end(self),
 
             Compound::RawValue { .. } => Ok(()),
+
+            Compound::Sorted {
+                ser, fields, buf, ..
+            } => ser.write_sorted_fields(fields, &buf),
         }
     }
 }
@@ -742,7 +1081,9 @@ where
         T: ?Sized + Serialize,
     {
         match *self {
-            Compound::Map { .. } => ser::SerializeStruct::serialize_field(self, key, value),
+            Compound::Map { .. } | Compound::Sorted { .. } => {
+                ser::SerializeStruct::serialize_field(self, key, value)
+            }
 
             Compound::RawValue { .. } => unreachable!(),
         }
@@ -763,6 +1104,21 @@ where
                 ser.formatter.end_object(&mut ser.writer).map_err(Error::io)
             }
 
+            Compound::Sorted {
+                ser,
+                fields,
+                buf,
+                variant,
+            } => {
+                debug_assert!(variant);
+                tri!(ser.write_sorted_fields(fields, &buf));
+                tri!(ser
+                    .formatter
+                    .end_object_value(&mut ser.writer)
+                    .map_err(Error::io));
+                ser.formatter.end_object(&mut ser.writer).map_err(Error::io)
+            }
+
             Compound::RawValue { .. } => unreachable!(),
         }
     }
@@ -833,7 +1189,7 @@ where
     type SerializeTupleStruct = Impossible<(), Error>;
     type SerializeTupleVariant = Impossible<(), Error>;
     type SerializeMap = Impossible<(), Error>;
-    type SerializeStruct = Compound<'a, W, F>;
+    type SerializeStruct = RawKeyCompound<'a, W, F>;
     type SerializeStructVariant = Impossible<(), Error>;
 
     fn serialize_bool(self, value: bool) -> Result<()> {
@@ -915,6 +1271,16 @@ where
 
     fn serialize_f32(self, value: f32) -> Result<()> {
         if value.is_finite() {
+            if self.ser.float_format != FloatFormat::Shortest {
+                let mut buf = String::with_capacity(32);
+                format_float(value, self.ser.float_format, &mut buf);
+                quote!(
+                    self,
+                    self.ser
+                        .formatter
+                        .write_number_str(&mut self.ser.writer, &buf)
+                );
+            }
             quote!(
                 self,
                 self.ser.formatter.write_f32(&mut self.ser.writer, value)
@@ -928,6 +1294,16 @@ where
 
     fn serialize_f64(self, value: f64) -> Result<()> {
         if value.is_finite() {
+            if self.ser.float_format != FloatFormat::Shortest {
+                let mut buf = String::with_capacity(32);
+                format_float(value, self.ser.float_format, &mut buf);
+                quote!(
+                    self,
+                    self.ser
+                        .formatter
+                        .write_number_str(&mut self.ser.writer, &buf)
+                );
+            }
             quote!(
                 self,
                 self.ser.formatter.write_f64(&mut self.ser.writer, value)
@@ -1010,8 +1386,12 @@ where
     }
 
     fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        if name == crate::value::Value::RAW_TOKEN {
-            Ok(Compound::RawValue { ser: self.ser })
+        if name == crate::value::Value::RAW_TOKEN
+            || name == crate::lazyvalue::TOKEN
+            || name == crate::serde::rawjson::TOKEN
+            || name == crate::serde::rawnumber::TOKEN
+        {
+            Ok(RawKeyCompound { ser: self.ser })
         } else {
             Err(key_must_be_str_or_num(Unexpected::Other(name)))
         }
@@ -1035,7 +1415,44 @@ where
     }
 }
 
-struct RawValueStrEmitter<'a, W: 'a + WriteExt, F: 'a + Formatter>(&'a mut Serializer<W, F>);
+// Serialize the raw keys from `RawKey`, `LazyValue`, `RawJson` and `RawNumber`. The raw strings are
+// written verbatim, and the raw numbers are quoted.
+struct RawKeyCompound<'a, W: 'a, F: 'a> {
+    ser: &'a mut Serializer<W, F>,
+}
+
+impl<'a, W, F> ser::SerializeStruct for RawKeyCompound<'a, W, F>
+where
+    W: WriteExt,
+    F: Formatter,
+{
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        if key == crate::value::Value::RAW_TOKEN
+            || key == crate::lazyvalue::TOKEN
+            || key == crate::serde::rawjson::TOKEN
+            || key == crate::serde::rawnumber::TOKEN
+        {
+            value.serialize(RawValueStrEmitter(self.ser, true))
+        } else {
+            Err(invalid_raw_value())
+        }
+    }
+
+    #[inline]
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// The second field is whether the raw value is written as a map key.
+struct RawValueStrEmitter<'a, W: 'a + WriteExt, F: 'a + Formatter>(&'a mut Serializer<W, F>, bool);
 
 impl<'a, W: WriteExt, F: Formatter> ser::Serializer for RawValueStrEmitter<'a, W, F> {
     type Ok = ();
@@ -1106,11 +1523,31 @@ impl<'a, W: WriteExt, F: Formatter> ser::Serializer for RawValueStrEmitter<'a, W
     }
 
     fn serialize_str(self, value: &str) -> Result<()> {
-        let RawValueStrEmitter(serializer) = self;
-        serializer
-            .formatter
-            .write_raw_value(&mut serializer.writer, value)
-            .map_err(Error::io)
+        let RawValueStrEmitter(serializer, is_key) = self;
+        if !is_key || value.starts_with('"') {
+            return serializer
+                .formatter
+                .write_raw_value(&mut serializer.writer, value)
+                .map_err(Error::io);
+        }
+
+        // the number keys should be quoted
+        if matches!(value.as_bytes().first(), Some(b'-' | b'0'..=b'9')) {
+            tri!(serializer
+                .formatter
+                .begin_string(&mut serializer.writer)
+                .map_err(Error::io));
+            tri!(serializer
+                .formatter
+                .write_raw_value(&mut serializer.writer, value)
+                .map_err(Error::io));
+            serializer
+                .formatter
+                .end_string(&mut serializer.writer)
+                .map_err(Error::io)
+        } else {
+            Err(key_must_be_str_or_num(Unexpected::Other("raw json")))
+        }
     }
 
     fn serialize_bytes(self, _value: &[u8]) -> Result<()> {
@@ -1254,6 +1691,73 @@ where
     value.serialize(&mut ser)
 }
 
+/// Serialize the given data structure as JSON into the [`fmt::Write`], such as a `String` or a
+/// [`fmt::Formatter`].
+///
+/// The JSON is written in chunks at the char boundaries, without an `io::Write` adapter or the
+/// intermediate vector of the whole output.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, if `T` contains a map with non-string keys, or if the writer returns an error.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt::Write;
+///
+/// let mut log = String::from("request: ");
+/// sonic_rs::to_fmt_writer(&mut log, &sonic_rs::json!({"id": 1, "name": "中文"})).unwrap();
+/// write!(log, ", done").unwrap();
+/// assert_eq!(log, r#"request: {"id":1,"name":"中文"}, done"#);
+/// ```
+#[inline]
+pub fn to_fmt_writer<W, T>(writer: &mut W, value: &T) -> Result<()>
+where
+    W: ?Sized + fmt::Write,
+    T: ?Sized + Serialize,
+{
+    let mut fmt_writer = FmtWriter::new(writer);
+    tri!(to_writer(&mut fmt_writer, value));
+    fmt_writer.finish().map_err(Error::io)
+}
+
+/// Serialize the given data structure as JSON into the I/O stream incrementally.
+///
+/// The output is buffered, and written into the stream whenever the buffered output reaches
+/// `flush_threshold` bytes, so the peak memory usage is bounded by the threshold rather than the
+/// size of the whole JSON, such as writing a large `Value` into a socket. The remaining output is
+/// written and the stream is flushed at the end. See [`BufferedWriter`] to reuse the buffered
+/// stream in other serializers.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, if `T` contains a map with non-string keys, or if the stream returns an error.
+///
+/// # Examples
+///
+/// ```
+/// let value = sonic_rs::json!({"items": vec![1; 10000]});
+/// let mut out = Vec::new();
+/// sonic_rs::to_writer_buffered(&mut out, &value, 4096).unwrap();
+/// assert_eq!(out, sonic_rs::to_vec(&value).unwrap());
+/// ```
+#[inline]
+pub fn to_writer_buffered<W, T>(writer: W, value: &T, flush_threshold: usize) -> Result<()>
+where
+    W: io::Write,
+    T: ?Sized + Serialize,
+{
+    let mut writer = BufferedWriter::with_threshold(writer, flush_threshold);
+    tri!(to_writer(&mut writer, value));
+    io::Write::flush(&mut writer).map_err(Error::io)
+}
+
+// The default initial capacity of the output in `to_vec` and `to_string`.
+const DEFAULT_VEC_CAPACITY: usize = 128;
+
 /// Serialize the given data structure as a JSON byte vector.
 ///
 /// # Errors
@@ -1265,7 +1769,35 @@ pub fn to_vec<T>(value: &T) -> Result<Vec<u8>>
 where
     T: ?Sized + Serialize,
 {
-    let mut writer = Vec::with_capacity(128);
+    to_vec_with_capacity(value, DEFAULT_VEC_CAPACITY)
+}
+
+/// Serialize the given data structure as a JSON byte vector, with the given initial capacity of
+/// the vector.
+///
+/// It avoids growing the vector many times if the size of the output is known, such as the
+/// messages with a fixed schema. The vector still grows if the output is larger than `capacity`.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, or if `T` contains a map with non-string keys.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{json, to_vec_with_capacity};
+///
+/// let out = to_vec_with_capacity(&json!({"a": [1, 2]}), 4096).unwrap();
+/// assert_eq!(out, br#"{"a":[1,2]}"#);
+/// assert!(out.capacity() >= 4096);
+/// ```
+#[inline]
+pub fn to_vec_with_capacity<T>(value: &T, capacity: usize) -> Result<Vec<u8>>
+where
+    T: ?Sized + Serialize,
+{
+    let mut writer = Vec::with_capacity(capacity);
     tri!(to_writer(&mut writer, value));
     Ok(writer)
 }
@@ -1281,7 +1813,7 @@ pub fn to_vec_pretty<T>(value: &T) -> Result<Vec<u8>>
 where
     T: ?Sized + Serialize,
 {
-    let mut writer = Vec::with_capacity(128);
+    let mut writer = Vec::with_capacity(DEFAULT_VEC_CAPACITY);
     tri!(to_writer_pretty(&mut writer, value));
     Ok(writer)
 }
@@ -1305,6 +1837,100 @@ where
     Ok(string)
 }
 
+/// Serialize the given data structure as a JSON byte vector, with the options in `cfg`.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, or if `T` contains a map with non-string keys.
+#[inline]
+pub fn to_vec_with_cfg<T>(value: &T, cfg: SerializeCfg) -> Result<Vec<u8>>
+where
+    T: ?Sized + Serialize,
+{
+    if !cfg.sort_struct_fields
+        && !cfg.escape_line_separators
+        && !cfg.escape_controls
+        && cfg.float_format == FloatFormat::Shortest
+    {
+        if !cfg.pretty {
+            return to_vec(value);
+        } else if cfg.pretty_style == PrettyStyle::Expanded {
+            return to_vec_pretty(value);
+        }
+    }
+
+    let escapes = Escapes {
+        line_separators: cfg.escape_line_separators,
+        controls: cfg.escape_controls,
+    };
+    let compact = if cfg.sort_struct_fields
+        || !escapes.is_none()
+        || cfg.float_format != FloatFormat::Shortest
+    {
+        let mut ser = Serializer::with_capacity(DEFAULT_VEC_CAPACITY);
+        ser.sort_fields = cfg.sort_struct_fields;
+        ser.escapes = escapes;
+        ser.float_format = cfg.float_format;
+        tri!(value.serialize(&mut ser));
+        ser.into_inner()
+    } else {
+        tri!(to_vec(value))
+    };
+    if !cfg.pretty {
+        return Ok(compact);
+    }
+    match cfg.pretty_style {
+        // all the non-empty containers are expanded, the same as `to_vec_pretty`
+        PrettyStyle::Expanded => Ok(super::human::reformat(&compact, 0, false)),
+        PrettyStyle::Human {
+            max_width,
+            align_keys,
+        } => Ok(super::human::reformat(&compact, max_width, align_keys)),
+        PrettyStyle::ElementPerLine => Ok(super::human::reformat_element_per_line(&compact)),
+    }
+}
+
+/// Serialize the given data structure as a String of JSON, with the options in `cfg`.
+///
+/// It is convenient in the generic code and macros, where the options are decided at runtime.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, or if `T` contains a map with non-string keys.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{json, to_string_with_cfg, SerializeCfg};
+///
+/// let value = json!({"a": [1]});
+/// assert_eq!(
+///     to_string_with_cfg(&value, SerializeCfg::default()).unwrap(),
+///     r#"{"a":[1]}"#
+/// );
+///
+/// let mut cfg = SerializeCfg::default();
+/// cfg.pretty = true;
+/// assert_eq!(
+///     to_string_with_cfg(&value, cfg).unwrap(),
+///     sonic_rs::to_string_pretty(&value).unwrap()
+/// );
+/// ```
+#[inline]
+pub fn to_string_with_cfg<T>(value: &T, cfg: SerializeCfg) -> Result<String>
+where
+    T: ?Sized + Serialize,
+{
+    let vec = tri!(to_vec_with_cfg(value, cfg));
+    let string = unsafe {
+        // We do not emit Invalid UTF-8.
+        String::from_utf8_unchecked(vec)
+    };
+    Ok(string)
+}
+
 /// Serialize the given data structure as a OwnedLazyValue of JSON.
 #[inline]
 pub fn to_lazyvalue<T>(value: &T) -> Result<OwnedLazyValue>
@@ -1363,4 +1989,347 @@ mod test {
 
         assert_eq!(vec, cursor.into_inner());
     }
+
+    #[test]
+    fn test_to_writer_buffered() {
+        // record the size of each write into the stream
+        struct Recorder {
+            out: Vec<u8>,
+            writes: Vec<usize>,
+        }
+
+        impl io::Write for Recorder {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.out.extend_from_slice(buf);
+                self.writes.push(buf.len());
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let value = json!({"a": vec!["hello"; 5000], "b": "x".repeat(10000)});
+        let mut stream = Recorder {
+            out: Vec::new(),
+            writes: Vec::new(),
+        };
+        super::to_writer_buffered(&mut stream, &value, 1024).unwrap();
+        assert_eq!(stream.out, crate::to_vec(&value).unwrap());
+        assert!(stream.writes.len() > 10);
+        // only the long string may exceed the threshold
+        assert!(stream.writes.iter().filter(|n| **n > 2048).count() <= 1);
+
+        let mut out = Vec::new();
+        super::to_writer_buffered(&mut out, &value, 0).unwrap();
+        assert_eq!(out, crate::to_vec(&value).unwrap());
+    }
+
+    #[test]
+    #[allow(clippy::mutable_key_type)]
+    fn test_serialize_raw_key() {
+        use std::collections::BTreeMap;
+
+        use crate::{LazyValue, RawKey, RawNumber};
+
+        let mut map = BTreeMap::new();
+        map.insert(RawKey::new(r#""\u0061\n""#).unwrap(), 1);
+        assert_eq!(crate::to_string(&map).unwrap(), r#"{"\u0061\n":1}"#);
+        assert!(RawKey::new("123").is_err());
+        assert!(RawKey::new(r#" "a""#).is_err());
+        assert!(RawKey::new(r#""a"#).is_err());
+
+        // the raw keys from lazyvalue are written verbatim
+        let json = r#"["\"a\"", 123, -1.5, [1]]"#;
+        let lvs: Vec<LazyValue> = crate::from_str(json).unwrap();
+        let mut map = BTreeMap::new();
+        map.insert(lvs[0].clone(), 1);
+        map.insert(lvs[1].clone(), 2);
+        map.insert(lvs[2].clone(), 3);
+        assert_eq!(
+            crate::to_string(&map).unwrap(),
+            r#"{"\"a\"":1,"-1.5":3,"123":2}"#
+        );
+
+        let mut map = BTreeMap::new();
+        map.insert(lvs[3].clone(), 1);
+        assert!(crate::to_string(&map).is_err());
+
+        // the raw number keys are quoted
+        let mut map = std::collections::HashMap::new();
+        map.insert(RawNumber::new("1e10"), "a");
+        assert_eq!(crate::to_string(&map).unwrap(), r#"{"1e10":"a"}"#);
+
+        // the raw key as a value is written verbatim
+        let key = RawKey::new(r#""a\tb""#).unwrap();
+        assert_eq!(crate::to_string(&key).unwrap(), r#""a\tb""#);
+    }
+
+    #[test]
+    fn test_to_fmt_writer() {
+        use std::fmt::{self, Write};
+
+        let mut out = String::from("[");
+        let data = (vec!["中文😀"; 2000], 1.5, ());
+        super::to_fmt_writer(&mut out, &data).unwrap();
+        out.push(']');
+        assert_eq!(out, format!("[{}]", crate::to_string(&data).unwrap()));
+
+        struct Failed;
+
+        impl Write for Failed {
+            fn write_str(&mut self, _: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        assert!(super::to_fmt_writer(&mut Failed, &data).is_err());
+    }
+
+    #[test]
+    fn test_sort_struct_fields() {
+        use std::collections::BTreeMap;
+
+        use serde::Serialize;
+
+        use crate::{PrettyStyle, RawNumber, SerializeCfg};
+
+        #[derive(Serialize)]
+        struct Inner {
+            y: Option<u8>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            skipped: Option<u8>,
+            b: RawNumber,
+        }
+
+        #[derive(Serialize)]
+        enum Kind {
+            Unit,
+            Struct { z: u8, a: Vec<Inner> },
+        }
+
+        #[derive(Serialize)]
+        struct Outer {
+            kind: Kind,
+            unit: Kind,
+            empty: BTreeMap<String, u8>,
+            #[serde(flatten)]
+            extra: BTreeMap<String, u8>,
+            a: Inner,
+        }
+
+        let value = Outer {
+            kind: Kind::Struct {
+                z: 1,
+                a: vec![Inner {
+                    y: None,
+                    skipped: None,
+                    b: RawNumber::new("1e3"),
+                }],
+            },
+            unit: Kind::Unit,
+            empty: BTreeMap::new(),
+            extra: [("x".to_string(), 1), ("c".to_string(), 2)].into(),
+            a: Inner {
+                y: Some(2),
+                skipped: Some(3),
+                b: RawNumber::new("-0"),
+            },
+        };
+
+        let mut cfg = SerializeCfg {
+            sort_struct_fields: true,
+            ..Default::default()
+        };
+        let sorted = crate::to_string_with_cfg(&value, cfg).unwrap();
+        // the struct with flattened fields is serialized as a map, and keeps its order
+        assert_eq!(
+            sorted,
+            r#"{"kind":{"Struct":{"a":[{"b":1e3,"y":null}],"z":1}},"unit":"Unit","empty":{},"c":2,"x":1,"a":{"b":-0,"skipped":3,"y":2}}"#
+        );
+
+        let value = crate::json!({"b": [], "a": {"y": 1, "x": [1, 2]}});
+        cfg.pretty = true;
+        assert_eq!(
+            crate::to_string_with_cfg(&value, cfg).unwrap(),
+            crate::to_string_pretty(&value).unwrap()
+        );
+        cfg.pretty_style = PrettyStyle::Human {
+            max_width: 80,
+            align_keys: false,
+        };
+        assert_eq!(
+            crate::to_string_with_cfg(&Kind::Struct { z: 1, a: vec![] }, cfg).unwrap(),
+            r#"{"Struct": {"a": [], "z": 1}}"#
+        );
+    }
+
+    #[test]
+    fn test_pretty_raw_value() {
+        use serde::Serialize;
+
+        use crate::{LazyValue, PrettyStyle, SerializeCfg};
+
+        #[derive(Serialize)]
+        struct Data<'a> {
+            z: u8,
+            #[serde(borrow)]
+            raw: LazyValue<'a>,
+        }
+
+        // the raw JSON is written verbatim, with its whitespaces
+        let json = "{ \"k\" : [ 1 , \"a b\" ] ,\n\t\"e\" : { } }";
+        let value = Data {
+            z: 1,
+            raw: crate::from_str(json).unwrap(),
+        };
+        let mut cfg = SerializeCfg {
+            sort_struct_fields: true,
+            pretty: true,
+            ..Default::default()
+        };
+        let expect = "{\n  \"raw\": {\n    \"k\": [\n      1,\n      \"a b\"\n    ],\n    \"e\": \
+                      {}\n  },\n  \"z\": 1\n}";
+        assert_eq!(crate::to_string_with_cfg(&value, cfg).unwrap(), expect);
+
+        cfg.pretty_style = PrettyStyle::Human {
+            max_width: 80,
+            align_keys: false,
+        };
+        let got = crate::to_string_with_cfg(&value, cfg).unwrap();
+        assert_eq!(got, r#"{"raw": {"k": [1, "a b"], "e": {}}, "z": 1}"#);
+        assert!(crate::from_str::<crate::Value>(&got).is_ok());
+
+        cfg.pretty_style = PrettyStyle::ElementPerLine;
+        assert_eq!(
+            crate::to_string_with_cfg(&value, cfg).unwrap(),
+            "{\n  \"raw\": {\n    \"k\": [\n      1,\n      \"a b\"\n    ],\n    \"e\": {}\n  \
+             },\n  \"z\": 1\n}"
+        );
+    }
+
+    #[test]
+    fn test_escape_line_separators() {
+        use std::collections::BTreeMap;
+
+        use serde::Serialize;
+
+        use crate::{SerializeCfg, Serializer};
+
+        #[derive(Serialize)]
+        struct Data {
+            text: &'static str,
+            map: BTreeMap<&'static str, &'static str>,
+        }
+
+        let value = Data {
+            text: "a\u{2028}b\u{7f}",
+            map: [("\u{2029}", "\u{80}\u{a0}")].into(),
+        };
+        let mut ser = Serializer::new(Vec::new()).escape_line_separators();
+        value.serialize(&mut ser).unwrap();
+        assert_eq!(
+            String::from_utf8(ser.into_inner()).unwrap(),
+            "{\"text\":\"a\\u2028b\u{7f}\",\"map\":{\"\\u2029\":\"\u{80}\u{a0}\"}}"
+        );
+
+        let cfg = SerializeCfg {
+            sort_struct_fields: true,
+            escape_line_separators: true,
+            escape_controls: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            crate::to_string_with_cfg(&value, cfg).unwrap(),
+            "{\"map\":{\"\\u2029\":\"\\u0080\u{a0}\"},\"text\":\"a\\u2028b\\u007f\"}"
+        );
+    }
+
+    #[test]
+    fn test_float_format() {
+        use std::collections::BTreeMap;
+
+        use serde::Serialize;
+
+        use crate::{FloatFormat, RawNumber, SerializeCfg, Serializer};
+
+        fn ecma(v: f64) -> String {
+            let mut ser = Serializer::new(Vec::new()).float_format(FloatFormat::EcmaScript);
+            v.serialize(&mut ser).unwrap();
+            String::from_utf8(ser.into_inner()).unwrap()
+        }
+
+        // the same as `String(v)` in JavaScript
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1.0, "1"),
+            (-1.5, "-1.5"),
+            (100.0, "100"),
+            (0.1 + 0.2, "0.30000000000000004"),
+            (123456.789, "123456.789"),
+            (1e20, "100000000000000000000"),
+            (1.2345678901234567e20, "123456789012345680000"),
+            (1e21, "1e+21"),
+            (2.5e25, "2.5e+25"),
+            (0.000001, "0.000001"),
+            (0.0000012, "0.0000012"),
+            (1e-7, "1e-7"),
+            (-1.5e-10, "-1.5e-10"),
+            (f64::MAX, "1.7976931348623157e+308"),
+            (5e-324, "5e-324"),
+            (f64::NAN, "null"),
+        ];
+        for (v, expect) in cases {
+            assert_eq!(ecma(v), expect, "{v}");
+        }
+
+        #[derive(Serialize)]
+        struct Data {
+            z: f32,
+            a: f64,
+            map: BTreeMap<u8, f64>,
+            keys: BTreeMap<String, f64>,
+            raw: RawNumber,
+        }
+
+        let value = Data {
+            z: 0.1,
+            a: 2.0,
+            map: [(1, 1e-3)].into(),
+            keys: [("k".to_string(), f64::INFINITY)].into(),
+            raw: RawNumber::new("1.50"),
+        };
+        let mut ser = Serializer::new(Vec::new()).with_float_precision(3);
+        value.serialize(&mut ser).unwrap();
+        assert_eq!(
+            String::from_utf8(ser.into_inner()).unwrap(),
+            r#"{"z":0.100,"a":2.000,"map":{"1":0.001},"keys":{"k":null},"raw":1.50}"#
+        );
+
+        let mut cfg = SerializeCfg {
+            sort_struct_fields: true,
+            float_format: FloatFormat::Fixed(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            crate::to_string_with_cfg(&value, cfg).unwrap(),
+            r#"{"a":2,"keys":{"k":null},"map":{"1":0},"raw":1.50,"z":0}"#
+        );
+        cfg.float_format = FloatFormat::EcmaScript;
+        assert_eq!(
+            crate::to_string_with_cfg(&value, cfg).unwrap(),
+            r#"{"a":2,"keys":{"k":null},"map":{"1":0.001},"raw":1.50,"z":0.1}"#
+        );
+
+        // the floats in the map keys are quoted
+        let map = crate::MultiMap(vec![(1.26f64, 1.0f64), (-0.5, 2.0)]);
+        let mut ser = Serializer::new(Vec::new()).with_float_precision(1);
+        map.serialize(&mut ser).unwrap();
+        assert_eq!(ser.into_inner(), br#"{"1.3":1.0,"-0.5":2.0}"#);
+        let mut ser = Serializer::new(Vec::new()).float_format(FloatFormat::EcmaScript);
+        map.serialize(&mut ser).unwrap();
+        assert_eq!(ser.into_inner(), br#"{"1.26":1,"-0.5":2}"#);
+    }
 }
@@ -0,0 +1,349 @@
+//! Deserialize the untagged enums by replaying the raw JSON text of the value.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::de::{
+    self, value::BorrowedStrDeserializer, Deserialize, DeserializeSeed, Deserializer, SeqAccess,
+    Visitor,
+};
+
+use super::de::Deserializer as JsonDeserializer;
+use crate::{
+    config::DeserializeCfg,
+    error::{invalid_utf8, Error, ErrorCode},
+    parser::{as_str, Parser},
+    reader::{Read, Reader},
+    util::utf8::from_utf8,
+};
+
+// The name of the newtype to get the raw JSON text borrowed from the input.
+pub(crate) const TOKEN: &str = "$sonic_rs::Untagged";
+
+/// A helper to deserialize an untagged enum, which tries the variants in order on the raw JSON
+/// text of the value.
+///
+/// The `#[serde(untagged)]` enums buffer the value into the serde `Content` tree, which copies
+/// the strings and converts the numbers before trying the variants, so the borrowed strings and
+/// [`RawNumber`][crate::RawNumber]s do not work in the variants. `Untagged` skips the value once
+/// and keeps its raw JSON text borrowed from the input, then each variant is parsed from the text
+/// directly, the same as a field of the variant type.
+///
+/// It requires the sonic-rs deserializer, the same as [`LazyValue`][crate::LazyValue].
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Deserializer};
+/// use sonic_rs::{RawNumber, Untagged};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Point<'a> {
+///     name: &'a str,
+///     x: RawNumber,
+/// }
+///
+/// #[derive(Debug)]
+/// enum Shape<'a> {
+///     Point(Point<'a>),
+///     Radius(f64),
+/// }
+///
+/// impl<'de> Deserialize<'de> for Shape<'de> {
+///     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+///         Untagged::new(deserializer)?
+///             .variant(Shape::Point)
+///             .variant(Shape::Radius)
+///             .finish("Shape")
+///     }
+/// }
+///
+/// let shapes: Vec<Shape> = sonic_rs::from_str(r#"[{"name": "p", "x": 1.50}, 2.5]"#).unwrap();
+/// assert!(matches!(&shapes[0], Shape::Point(p) if p.name == "p" && p.x.as_str() == "1.50"));
+/// assert!(matches!(shapes[1], Shape::Radius(r) if r == 2.5));
+///
+/// let err = sonic_rs::from_str::<Shape>("true").unwrap_err();
+/// assert!(err
+///     .to_string()
+///     .contains("data did not match any variant of untagged enum Shape"));
+/// ```
+pub struct Untagged<'de, T> {
+    span: RawSpan<'de>,
+    value: Option<T>,
+}
+
+impl<'de, T> Untagged<'de, T> {
+    /// Skip the value from the deserializer, and keep its raw JSON text.
+    pub fn new<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let visitor = RawVisitor::new(ErrorCode::IncompatibleUntagged);
+        let span = deserializer.deserialize_newtype_struct(TOKEN, visitor)?;
+        Ok(Self { span, value: None })
+    }
+
+    /// Return the raw JSON text of the value.
+    pub fn as_raw_str(&self) -> &'de str {
+        self.span.as_str()
+    }
+
+    /// Try the variant if no variant is matched yet. The variant is parsed from the raw JSON
+    /// text as `V` with the options of the deserializer, and converted by `f`.
+    pub fn variant<V, F>(mut self, f: F) -> Self
+    where
+        V: Deserialize<'de>,
+        F: FnOnce(V) -> T,
+    {
+        if self.value.is_none() {
+            if let Ok(v) = self.span.deserialize(PhantomData::<V>) {
+                self.value = Some(f(v));
+            }
+        }
+        self
+    }
+
+    /// Return the matched variant, or an error if no variant is matched.
+    pub fn finish<E: de::Error>(self, name: &str) -> Result<T, E> {
+        self.value.ok_or_else(|| {
+            E::custom(format_args!(
+                "data did not match any variant of untagged enum {name}"
+            ))
+        })
+    }
+}
+
+// The raw JSON text of a value in the input, with the options of the deserializer. The value is
+// parsed again in the input, so the errors have the positions in the input.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RawSpan<'de> {
+    input: &'de str,
+    start: usize,
+    end: usize,
+    cfg: DeserializeCfg,
+    // the input may have invalid UTF-8, which is not checked when skipping the value
+    check_utf8: bool,
+}
+
+impl<'de> RawSpan<'de> {
+    // The span of `raw` skipped by `parser`.
+    pub(crate) fn new<R: Reader<'de>>(parser: &Parser<R>, raw: &'de [u8]) -> Self {
+        let input = parser.read.as_u8_slice();
+        let start = raw.as_ptr() as usize - input.as_ptr() as usize;
+        Self {
+            input: as_str(input),
+            start,
+            end: start + raw.len(),
+            cfg: parser.cfg,
+            check_utf8: parser.read.next_invalid_utf8() != usize::MAX,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &'de str {
+        as_str(&self.input.as_bytes()[self.start..self.end])
+    }
+
+    // Deserialize the value in the span, as if it is deserialized by the origin deserializer.
+    pub(crate) fn deserialize<T>(&self, seed: T) -> crate::Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let mut read = Read::new(self.input.as_bytes(), false);
+        read.index = self.start;
+        let mut de = JsonDeserializer::new(read).with_cfg(self.cfg);
+        if self.check_utf8 {
+            if let Err(e) = from_utf8(self.as_str().as_bytes()) {
+                if !self.cfg.utf8_lossy {
+                    return Err(invalid_utf8(self.input.as_bytes(), self.start + e.offset()));
+                }
+                // find the invalid UTF-8 in the span, which is replaced in the lossy mode
+                de.parser.read.check_invalid_utf8();
+            }
+        }
+        seed.deserialize(&mut de)
+    }
+
+    // Pass the span to `RawVisitor`.
+    pub(crate) fn into_access(self) -> SpanAccess<'de> {
+        SpanAccess {
+            span: self,
+            visited: 0,
+        }
+    }
+}
+
+// Visit the span as the sequence of the input, the start, the end and the packed options.
+pub(crate) struct SpanAccess<'de> {
+    span: RawSpan<'de>,
+    visited: usize,
+}
+
+impl<'de> SeqAccess<'de> for SpanAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> crate::Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let span = &self.span;
+        let bits = (span.cfg.to_bits() << 1) | span.check_utf8 as u64;
+        let value = match self.visited {
+            0 => seed.deserialize(BorrowedStrDeserializer::new(span.input)),
+            1 => seed.deserialize(de::value::U64Deserializer::new(span.start as u64)),
+            2 => seed.deserialize(de::value::U64Deserializer::new(span.end as u64)),
+            3 => seed.deserialize(de::value::U64Deserializer::new(bits)),
+            _ => return Ok(None),
+        };
+        self.visited += 1;
+        value.map(Some)
+    }
+}
+
+// Visit the raw span of the value, requested by `TOKEN`.
+pub(crate) struct RawVisitor<'de> {
+    // the error if the deserializer does not know the token
+    incompatible: ErrorCode,
+    _marker: PhantomData<&'de str>,
+}
+
+impl RawVisitor<'_> {
+    pub(crate) fn new(incompatible: ErrorCode) -> Self {
+        Self {
+            incompatible,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for RawVisitor<'de> {
+    type Value = RawSpan<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let msg = self.incompatible.to_string();
+        let incompatible = || de::Error::custom(&msg);
+        let input: &'de str = seq.next_element()?.ok_or_else(incompatible)?;
+        let start: u64 = seq.next_element()?.ok_or_else(incompatible)?;
+        let end: u64 = seq.next_element()?.ok_or_else(incompatible)?;
+        let bits: u64 = seq.next_element()?.ok_or_else(incompatible)?;
+        let (start, end) = (start as usize, end as usize);
+        if start > end
+            || end > input.len()
+            || !input.is_char_boundary(start)
+            || !input.is_char_boundary(end)
+        {
+            return Err(incompatible());
+        }
+        Ok(RawSpan {
+            input,
+            start,
+            end,
+            cfg: DeserializeCfg::from_bits(bits >> 1),
+            check_utf8: bits & 1 == 1,
+        })
+    }
+
+    // the deserializer does not know the token, such as other JSON libraries
+    fn visit_newtype_struct<D>(self, _: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Err(de::Error::custom(self.incompatible))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{from_str, RawNumber};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Named<'a> {
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+        id: RawNumber,
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Item<'a> {
+        Named(Named<'a>),
+        Pair((u8, u8)),
+        Text(&'a str),
+        Any(crate::Value),
+    }
+
+    impl<'de> Deserialize<'de> for Item<'de> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Untagged::new(deserializer)?
+                .variant(Item::Named)
+                .variant(Item::Pair)
+                .variant(Item::Text)
+                .variant(Item::Any)
+                .finish("Item")
+        }
+    }
+
+    #[test]
+    fn test_untagged() {
+        let json = r#"[{"name": "a", "id": 12345678901234567890123}, [1, 2], "s", [1, 2, 3], {"name": "\n", "id": 1}]"#;
+        let items: Vec<Item> = from_str(json).unwrap();
+        match &items[0] {
+            Item::Named(named) => {
+                // the strings are borrowed from the input, and the numbers are raw
+                assert!(matches!(named.name, Cow::Borrowed("a")));
+                assert_eq!(named.id.as_str(), "12345678901234567890123");
+            }
+            item => panic!("unexpected {item:?}"),
+        }
+        assert_eq!(items[1], Item::Pair((1, 2)));
+        assert_eq!(items[2], Item::Text("s"));
+        assert_eq!(items[3], Item::Any(crate::json!([1, 2, 3])));
+        match &items[4] {
+            Item::Named(named) => assert_eq!(named.name, "\n"),
+            item => panic!("unexpected {item:?}"),
+        }
+
+        // the escaped string can not be borrowed as `&str`
+        let item: Item = from_str(r#""\t""#).unwrap();
+        assert_eq!(item, Item::Any(crate::json!("\t")));
+
+        #[derive(Debug)]
+        struct Small(u8);
+
+        impl<'de> Deserialize<'de> for Small {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Untagged::new(deserializer)?.variant(Small).finish("Small")
+            }
+        }
+
+        assert_eq!(from_str::<Small>("7").unwrap().0, 7);
+        let err = from_str::<Small>("256").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("data did not match any variant of untagged enum Small"));
+        assert!(from_str::<Vec<Small>>("[1, 2").is_err());
+        assert!(serde_json::from_str::<Small>("1").is_err());
+
+        // the variants are parsed with the options of the deserializer
+        let mut cfg = crate::DeserializeCfg::default();
+        cfg.extended_numbers = true;
+        assert_eq!(
+            crate::from_str_with_cfg::<Small>("0x10", cfg).unwrap().0,
+            16
+        );
+        let json = b"[\"a\xff\", 1]";
+        assert!(crate::from_slice_with_cfg::<Vec<Item>>(json, cfg).is_err());
+        cfg.utf8_lossy = true;
+        let items: Vec<Item> = crate::from_slice_with_cfg(json, cfg).unwrap();
+        assert_eq!(items[0], Item::Any(crate::json!("a\u{FFFD}")));
+    }
+}
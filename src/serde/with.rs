@@ -0,0 +1,1203 @@
+//! Efficient adapters for the common `#[serde(with = "...")]` patterns, which are compatible with
+//! the adapters in `serde_with`.
+//!
+//! The adapters write the strings directly into the output by `Serializer::collect_str`, and
+//! parse from the borrowed strings when deserializing, without intermediate `String`s.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Foo {
+//!     #[serde(with = "sonic_rs::serde::with::display_from_str")]
+//!     id: u64,
+//!     #[serde(with = "sonic_rs::serde::with::comma_separated")]
+//!     tags: Vec<u32>,
+//!     #[serde(with = "sonic_rs::serde::with::map_as_tuple_list")]
+//!     attrs: BTreeMap<u32, bool>,
+//! }
+//!
+//! let foo = Foo {
+//!     id: 123,
+//!     tags: vec![1, 2, 3],
+//!     attrs: BTreeMap::from([(1, true), (2, false)]),
+//! };
+//! let json = sonic_rs::to_string(&foo).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"id":"123","tags":"1,2,3","attrs":[[1,true],[2,false]]}"#
+//! );
+//! assert_eq!(sonic_rs::from_str::<Foo>(&json).unwrap(), foo);
+//! ```
+
+use std::{fmt, marker::PhantomData};
+
+use serde::de::{self, Deserializer, Visitor};
+
+// A visitor parsing the value from a string with `f`, accepting both borrowed and owned strings.
+struct StrVisitor<T, F> {
+    f: F,
+    expecting: &'static str,
+    _marker: PhantomData<T>,
+}
+
+impl<'de, T, F> Visitor<'de> for StrVisitor<T, F>
+where
+    F: FnOnce(&str) -> Result<T, String>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.expecting)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        (self.f)(v).map_err(E::custom)
+    }
+}
+
+fn deserialize_from_str<'de, D, T, F>(
+    deserializer: D,
+    expecting: &'static str,
+    f: F,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    F: FnOnce(&str) -> Result<T, String>,
+{
+    deserializer.deserialize_str(StrVisitor {
+        f,
+        expecting,
+        _marker: PhantomData,
+    })
+}
+
+/// Serialize a value as a JSON string with [`Display`][`fmt::Display`], and deserialize it with
+/// [`FromStr`].
+///
+/// It is useful for the large numbers, which may lose precision in other JSON implementations.
+pub mod display_from_str {
+    use std::{fmt::Display, str::FromStr};
+
+    use serde::{Deserializer, Serializer};
+
+    /// Serialize the value with `Display`.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Display,
+        S: Serializer,
+    {
+        serializer.collect_str(value)
+    }
+
+    /// Deserialize the value with `FromStr`.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        super::deserialize_from_str(deserializer, "a string", |s| {
+            s.parse().map_err(|e: T::Err| e.to_string())
+        })
+    }
+}
+
+/// Serialize a value as a string of its JSON text, such as `"{\"a\":1}"`, and deserialize it
+/// back.
+///
+/// The JSON text is an ordinary string on the wire, so it is stable across the sonic-rs versions,
+/// and works with the other serializers and deserializers. It is the escape hatch to exchange the
+/// [`LazyValue`][crate::LazyValue] and [`OwnedLazyValue`][crate::OwnedLazyValue] fields, which
+/// are only deserialized by the sonic-rs deserializer of a compatible version.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use sonic_rs::OwnedLazyValue;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Envelope {
+///     #[serde(with = "sonic_rs::serde::with::json_string")]
+///     payload: OwnedLazyValue,
+/// }
+///
+/// let json = r#"{"payload":"{\"a\": [1, 2]}"}"#;
+/// let envelope: Envelope = sonic_rs::from_str(json).unwrap();
+/// assert_eq!(envelope.payload.as_raw_str(), Some(r#"{"a": [1, 2]}"#));
+/// assert_eq!(sonic_rs::to_string(&envelope).unwrap(), json);
+/// ```
+pub mod json_string {
+    use serde::{de::DeserializeOwned, ser, Deserializer, Serialize, Serializer};
+
+    /// Serialize the JSON text of the value as a string.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + ?Sized,
+        S: Serializer,
+    {
+        let json = crate::to_string(value).map_err(ser::Error::custom)?;
+        serializer.serialize_str(&json)
+    }
+
+    /// Parse the value from the JSON text in the string.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: DeserializeOwned,
+        D: Deserializer<'de>,
+    {
+        super::deserialize_from_str(deserializer, "a string of JSON text", |s| {
+            crate::from_str(s).map_err(|e| e.to_string())
+        })
+    }
+}
+
+/// Serialize a slice, such as `Vec`, as a comma separated JSON string, such as `"1,2,3"`, and
+/// deserialize it back.
+///
+/// The empty string is the empty list.
+pub mod comma_separated {
+    use std::{
+        fmt::{self, Display},
+        str::FromStr,
+    };
+
+    use serde::{Deserializer, Serializer};
+
+    struct Joined<'a, E>(&'a [E]);
+
+    impl<E: Display> Display for Joined<'_, E> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for (i, item) in self.0.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(",")?;
+                }
+                item.fmt(f)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Serialize the items as a comma separated string.
+    pub fn serialize<T, E, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[E]> + ?Sized,
+        E: Display,
+        S: Serializer,
+    {
+        serializer.collect_str(&Joined(value.as_ref()))
+    }
+
+    /// Deserialize the items from a comma separated string.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromIterator<T::Item> + IntoIterator,
+        T::Item: FromStr,
+        <T::Item as FromStr>::Err: Display,
+        D: Deserializer<'de>,
+    {
+        super::deserialize_from_str(deserializer, "a comma separated string", |s| {
+            if s.is_empty() {
+                return Ok(std::iter::empty::<T::Item>().collect());
+            }
+            s.split(',')
+                .map(|item| {
+                    item.parse()
+                        .map_err(|e: <T::Item as FromStr>::Err| e.to_string())
+                })
+                .collect()
+        })
+    }
+}
+
+/// Serialize a map as a list of `[key, value]` tuples, and deserialize it back.
+///
+/// It is useful for the maps whose keys are not strings, such as structs.
+pub mod map_as_tuple_list {
+    use std::{fmt, marker::PhantomData};
+
+    use serde::{
+        de::{SeqAccess, Visitor},
+        ser::SerializeSeq,
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    /// Serialize the map as a list of tuples.
+    pub fn serialize<'a, T, K, V, S>(value: &'a T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        &'a T: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Serialize + 'a,
+        V: Serialize + 'a,
+        S: Serializer,
+    {
+        let iter = value.into_iter();
+        let (lower, upper) = iter.size_hint();
+        let len = if Some(lower) == upper {
+            Some(lower)
+        } else {
+            None
+        };
+        let mut seq = serializer.serialize_seq(len)?;
+        for (k, v) in iter {
+            seq.serialize_element(&(k, v))?;
+        }
+        seq.end()
+    }
+
+    struct TupleListVisitor<T, K, V>(PhantomData<(T, K, V)>);
+
+    impl<'de, T, K, V> Visitor<'de> for TupleListVisitor<T, K, V>
+    where
+        T: FromIterator<(K, V)>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = T;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a list of key-value tuples")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<T, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut err = None;
+            let iter = std::iter::from_fn(|| match seq.next_element::<(K, V)>() {
+                Ok(item) => item,
+                Err(e) => {
+                    err = Some(e);
+                    None
+                }
+            });
+            let map = iter.collect();
+            match err {
+                Some(e) => Err(e),
+                None => Ok(map),
+            }
+        }
+    }
+
+    /// Deserialize the map from a list of tuples.
+    pub fn deserialize<'de, T, K, V, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromIterator<(K, V)>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(TupleListVisitor(PhantomData))
+    }
+}
+
+/// Serialize a list of `(key, value)` pairs as a JSON object, and deserialize it back in the order
+/// of the object.
+///
+/// It is useful to reassemble an object from the filtered entries of
+/// [`to_object_iter`][crate::to_object_iter], such as `Vec<(Cow<str>, LazyValue)>`. The keys are
+/// escaped and the lazy values are written verbatim, so the raw JSON of the values is not parsed
+/// again. The duplicate keys are kept as they are.
+///
+/// The adapter can also be called directly to serialize the pairs as a whole:
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// use sonic_rs::{serde::with::pairs_as_map, to_object_iter, LazyValue};
+///
+/// let json = r#"{"id": 1, "secret": "x", "tags": ["a", "b"]}"#;
+/// let pairs: Vec<(Cow<str>, LazyValue)> = to_object_iter(json)
+///     .map(|kv| kv.unwrap())
+///     .filter(|(k, _)| k != "secret")
+///     .collect();
+///
+/// let mut buf = Vec::new();
+/// pairs_as_map::serialize(&pairs, &mut sonic_rs::Serializer::new(&mut buf)).unwrap();
+/// assert_eq!(buf, br#"{"id":1,"tags":["a", "b"]}"#);
+/// ```
+pub mod pairs_as_map {
+    use std::{fmt, marker::PhantomData};
+
+    use serde::{
+        de::{MapAccess, Visitor},
+        ser::SerializeMap,
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    /// Serialize the pairs as a map.
+    pub fn serialize<'a, T, K, V, S>(value: &'a T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        &'a T: IntoIterator<Item = &'a (K, V)>,
+        K: Serialize + 'a,
+        V: Serialize + 'a,
+        S: Serializer,
+    {
+        let iter = value.into_iter();
+        let (lower, upper) = iter.size_hint();
+        let len = if Some(lower) == upper {
+            Some(lower)
+        } else {
+            None
+        };
+        let mut map = serializer.serialize_map(len)?;
+        for (k, v) in iter {
+            map.serialize_entry(k, v)?;
+        }
+        map.end()
+    }
+
+    struct PairsVisitor<T, K, V>(PhantomData<(T, K, V)>);
+
+    impl<'de, T, K, V> Visitor<'de> for PairsVisitor<T, K, V>
+    where
+        T: FromIterator<(K, V)>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = T;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<T, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut err = None;
+            let iter = std::iter::from_fn(|| match map.next_entry::<K, V>() {
+                Ok(entry) => entry,
+                Err(e) => {
+                    err = Some(e);
+                    None
+                }
+            });
+            let pairs = iter.collect();
+            match err {
+                Some(e) => Err(e),
+                None => Ok(pairs),
+            }
+        }
+    }
+
+    /// Deserialize the pairs from a map.
+    pub fn deserialize<'de, T, K, V, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromIterator<(K, V)>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(PairsVisitor(PhantomData))
+    }
+}
+
+// A visitor parsing the value from the raw number text, without the intermediate `f64`. The
+// integers and floats from other deserializers are formatted into text first.
+#[cfg(any(feature = "rust_decimal", feature = "bigdecimal"))]
+struct RawNumberVisitor<T, F> {
+    f: F,
+    expecting: &'static str,
+    _marker: PhantomData<T>,
+}
+
+#[cfg(any(feature = "rust_decimal", feature = "bigdecimal"))]
+impl<'de, T, F> Visitor<'de> for RawNumberVisitor<T, F>
+where
+    F: FnOnce(&str) -> Result<T, String>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.expecting)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        (self.f)(v).map_err(E::custom)
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(itoa::Buffer::new().format(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(itoa::Buffer::new().format(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        if !v.is_finite() {
+            return Err(E::invalid_value(de::Unexpected::Float(v), &self));
+        }
+        self.visit_str(ryu::Buffer::new().format_finite(v))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+#[cfg(any(feature = "rust_decimal", feature = "bigdecimal"))]
+fn deserialize_from_raw_number<'de, D, T, F>(
+    deserializer: D,
+    expecting: &'static str,
+    f: F,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    F: FnOnce(&str) -> Result<T, String>,
+{
+    deserializer.deserialize_newtype_struct(
+        crate::serde::rawnumber::TOKEN,
+        RawNumberVisitor {
+            f,
+            expecting,
+            _marker: PhantomData,
+        },
+    )
+}
+
+// Write the number text into the output verbatim, as `RawNumber`.
+#[cfg(any(feature = "rust_decimal", feature = "bigdecimal"))]
+fn serialize_raw_number<S>(raw: &str, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::SerializeStruct;
+
+    let mut s = serializer.serialize_struct(crate::serde::rawnumber::TOKEN, 1)?;
+    s.serialize_field(crate::serde::rawnumber::TOKEN, raw)?;
+    s.end()
+}
+
+/// Serialize a [`rust_decimal::Decimal`] as a JSON number, and deserialize it from a JSON number
+/// without the `f64` rounding. Requires the `rust_decimal` feature.
+///
+/// The number text is parsed into the decimal directly, and an error is returned if the number
+/// can't be represented exactly, such as having more than 28 fractional digits. The JSON strings
+/// containing a number, such as `"1.5"`, are also accepted.
+///
+/// The decimal is formatted on the stack when serializing, without allocation.
+///
+/// # Examples
+///
+/// ```
+/// use rust_decimal::Decimal;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Bill {
+///     #[serde(with = "sonic_rs::serde::with::decimal")]
+///     amount: Decimal,
+/// }
+///
+/// let bill: Bill = sonic_rs::from_str(r#"{"amount": 0.30000000000000000001}"#).unwrap();
+/// assert_eq!(bill.amount.to_string(), "0.30000000000000000001");
+/// assert_eq!(
+///     sonic_rs::to_string(&bill).unwrap(),
+///     r#"{"amount":0.30000000000000000001}"#
+/// );
+/// ```
+#[cfg(feature = "rust_decimal")]
+pub mod decimal {
+    use std::fmt::{self, Write};
+
+    use rust_decimal::Decimal;
+    use serde::{Deserializer, Serializer};
+
+    // The longest decimal is `-0.` followed by 28 digits.
+    const MAX_DECIMAL_LEN: usize = 64;
+
+    struct StackBuf {
+        buf: [u8; MAX_DECIMAL_LEN],
+        len: usize,
+    }
+
+    impl Write for StackBuf {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let end = self.len + s.len();
+            if end > self.buf.len() {
+                return Err(fmt::Error);
+            }
+            self.buf[self.len..end].copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    /// Serialize the decimal as a JSON number.
+    pub fn serialize<S>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut buf = StackBuf {
+            buf: [0; MAX_DECIMAL_LEN],
+            len: 0,
+        };
+        if write!(buf, "{value}").is_err() {
+            return Err(serde::ser::Error::custom("the decimal is too long"));
+        }
+        // SAFETY: only the complete `str`s are written into the buffer
+        let raw = unsafe { std::str::from_utf8_unchecked(&buf.buf[..buf.len]) };
+        super::serialize_raw_number(raw, serializer)
+    }
+
+    /// Deserialize the decimal from a JSON number.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Decimal, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize_from_raw_number(deserializer, "a decimal number", |s| {
+            let decimal = if s.contains(['e', 'E']) {
+                Decimal::from_scientific(s)
+            } else {
+                Decimal::from_str_exact(s)
+            };
+            decimal.map_err(|e| e.to_string())
+        })
+    }
+}
+
+/// Serialize a [`bigdecimal::BigDecimal`] as a JSON number, and deserialize it from a JSON number
+/// without the `f64` rounding. Requires the `bigdecimal` feature.
+///
+/// The number text is parsed into the decimal directly, so the precision is never lost. The JSON
+/// strings containing a number, such as `"1.5"`, are also accepted.
+///
+/// # Examples
+///
+/// ```
+/// use bigdecimal::BigDecimal;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Bill {
+///     #[serde(with = "sonic_rs::serde::with::big_decimal")]
+///     amount: BigDecimal,
+/// }
+///
+/// let json = r#"{"amount":123456789012345678901234567890.123456789}"#;
+/// let bill: Bill = sonic_rs::from_str(json).unwrap();
+/// assert_eq!(
+///     bill.amount.to_string(),
+///     "123456789012345678901234567890.123456789"
+/// );
+/// assert_eq!(sonic_rs::to_string(&bill).unwrap(), json);
+/// ```
+#[cfg(feature = "bigdecimal")]
+pub mod big_decimal {
+    use std::str::FromStr;
+
+    use bigdecimal::BigDecimal;
+    use serde::{Deserializer, Serializer};
+
+    /// Serialize the decimal as a JSON number.
+    pub fn serialize<S>(value: &BigDecimal, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        super::serialize_raw_number(&value.to_string(), serializer)
+    }
+
+    /// Deserialize the decimal from a JSON number.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<BigDecimal, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize_from_raw_number(deserializer, "a decimal number", |s| {
+            BigDecimal::from_str(s).map_err(|e| e.to_string())
+        })
+    }
+}
+
+/// Serialize a [`uuid::Uuid`] as a hyphenated JSON string, and deserialize it from a JSON string
+/// without the intermediate `String`. Requires the `uuid` feature.
+///
+/// The hyphenated form, such as `"67e55044-10b1-426f-9247-bb680e5fe0c8"`, is parsed directly from
+/// the 36 bytes of the string, and the other forms accepted by [`uuid::Uuid::try_parse`] are also
+/// accepted. The UUID is formatted in lower case into a buffer on the stack when serializing.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use uuid::Uuid;
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct User {
+///     #[serde(with = "sonic_rs::serde::with::uuid")]
+///     id: Uuid,
+/// }
+///
+/// let json = r#"{"id":"67e55044-10b1-426f-9247-bb680e5fe0c8"}"#;
+/// let user: User = sonic_rs::from_str(json).unwrap();
+/// assert_eq!(user.id.as_u128(), 0x67e5504410b1426f9247bb680e5fe0c8);
+/// assert_eq!(sonic_rs::to_string(&user).unwrap(), json);
+/// ```
+#[cfg(feature = "uuid")]
+pub mod uuid {
+    use ::uuid::Uuid;
+    use serde::{Deserializer, Serializer};
+
+    // The length of the hyphenated UUID.
+    const HYPHENATED_LEN: usize = 36;
+
+    // The ranges of the hex groups in the hyphenated UUID, separated by the hyphens.
+    const GROUPS: [(usize, usize); 5] = [(0, 8), (9, 13), (14, 18), (19, 23), (24, 36)];
+
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    #[inline(always)]
+    fn hex_value(b: u8) -> Option<u8> {
+        match b {
+            b'0'..=b'9' => Some(b - b'0'),
+            b'a'..=b'f' => Some(b - b'a' + 10),
+            b'A'..=b'F' => Some(b - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    fn parse_hyphenated(s: &[u8]) -> Option<Uuid> {
+        if s.len() != HYPHENATED_LEN || GROUPS[1..].iter().any(|&(start, _)| s[start - 1] != b'-') {
+            return None;
+        }
+
+        let mut bytes = [0u8; 16];
+        let mut n = 0;
+        for (start, end) in GROUPS {
+            for pair in s[start..end].chunks_exact(2) {
+                bytes[n] = (hex_value(pair[0])? << 4) | hex_value(pair[1])?;
+                n += 1;
+            }
+        }
+        Some(Uuid::from_bytes(bytes))
+    }
+
+    /// Serialize the UUID as a hyphenated JSON string.
+    pub fn serialize<S>(value: &Uuid, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut buf = [0u8; HYPHENATED_LEN];
+        let mut n = 0;
+        for (i, b) in value.as_bytes().iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                buf[n] = b'-';
+                n += 1;
+            }
+            buf[n] = HEX[(b >> 4) as usize];
+            buf[n + 1] = HEX[(b & 0xf) as usize];
+            n += 2;
+        }
+        // SAFETY: only the ASCII hex digits and hyphens are written
+        let s = unsafe { std::str::from_utf8_unchecked(&buf) };
+        serializer.serialize_str(s)
+    }
+
+    /// Deserialize the UUID from a JSON string.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Uuid, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize_from_str(deserializer, "a UUID string", |s| {
+            match parse_hyphenated(s.as_bytes()) {
+                Some(uuid) => Ok(uuid),
+                // the other forms, or get the detailed error
+                None => Uuid::try_parse(s).map_err(|e| e.to_string()),
+            }
+        })
+    }
+}
+
+/// Serialize bytes as a base64 JSON string, and deserialize them from a base64 JSON string.
+///
+/// The standard alphabet with padding is written. Both the standard and the URL-safe alphabets
+/// are accepted when deserializing, and the padding is optional.
+///
+/// The sonic-rs deserializer first skips the string with the SIMD scanner, and then decodes the
+/// skipped bytes of the JSON text into the `Vec<u8>` in a second scalar pass, without copying the
+/// string into a buffer. Only the strings with escapes, such as `\/`, are unescaped into a buffer
+/// before decoding. The bytes are encoded on the stack in chunks when serializing.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Blob {
+///     #[serde(with = "sonic_rs::serde::with::base64")]
+///     data: Vec<u8>,
+/// }
+///
+/// let json = r#"{"data":"aGVsbG8="}"#;
+/// let blob: Blob = sonic_rs::from_str(json).unwrap();
+/// assert_eq!(blob.data, b"hello");
+/// assert_eq!(sonic_rs::to_string(&blob).unwrap(), json);
+/// ```
+pub mod base64 {
+    use std::fmt::{self, Display};
+
+    use serde::{
+        de::{self, Visitor},
+        Deserializer, Serializer,
+    };
+
+    // The name of the newtype to decode the base64 string directly in the sonic-rs deserializer.
+    pub(crate) const TOKEN: &str = "$sonic_rs::Base64";
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    const INVALID: u8 = 0xff;
+
+    // The values of the chars in both the standard and the URL-safe alphabets.
+    const DECODE: [u8; 256] = {
+        let mut table = [INVALID; 256];
+        let mut i = 0;
+        while i < ALPHABET.len() {
+            table[ALPHABET[i] as usize] = i as u8;
+            i += 1;
+        }
+        table[b'-' as usize] = 62;
+        table[b'_' as usize] = 63;
+        table
+    };
+
+    // The bytes encoded in one chunk, which are 1024 chars.
+    const ENCODE_CHUNK: usize = 768;
+
+    #[inline(always)]
+    fn value(c: u8) -> Option<u32> {
+        let v = DECODE[c as usize];
+        (v != INVALID).then_some(v as u32)
+    }
+
+    /// Decode the base64 text, return None if it is invalid.
+    pub(crate) fn decode(text: &[u8]) -> Option<Vec<u8>> {
+        // strip the padding, which is only at the end of the whole quanta
+        let mut end = text.len();
+        if end % 4 == 0 {
+            let min = end.saturating_sub(2);
+            while end > min && text[end - 1] == b'=' {
+                end -= 1;
+            }
+        }
+        let text = &text[..end];
+        if text.len() % 4 == 1 {
+            return None;
+        }
+
+        let mut out = Vec::with_capacity(text.len() / 4 * 3 + 2);
+        let mut quanta = text.chunks_exact(4);
+        for q in &mut quanta {
+            let n =
+                (value(q[0])? << 18) | (value(q[1])? << 12) | (value(q[2])? << 6) | value(q[3])?;
+            out.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8, n as u8]);
+        }
+        match *quanta.remainder() {
+            [] => {}
+            [a, b] => {
+                let n = (value(a)? << 18) | (value(b)? << 12);
+                out.push((n >> 16) as u8);
+            }
+            [a, b, c] => {
+                let n = (value(a)? << 18) | (value(b)? << 12) | (value(c)? << 6);
+                out.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8]);
+            }
+            _ => unreachable!("the remainder of the quanta is at most 3 chars"),
+        }
+        Some(out)
+    }
+
+    struct Encoded<'a>(&'a [u8]);
+
+    impl Display for Encoded<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let mut buf = [0u8; ENCODE_CHUNK / 3 * 4];
+            for chunk in self.0.chunks(ENCODE_CHUNK) {
+                let mut n = 0;
+                for group in chunk.chunks(3) {
+                    let b1 = group.get(1).copied().unwrap_or(0) as u32;
+                    let b2 = group.get(2).copied().unwrap_or(0) as u32;
+                    let v = ((group[0] as u32) << 16) | (b1 << 8) | b2;
+                    buf[n] = ALPHABET[(v >> 18) as usize & 63];
+                    buf[n + 1] = ALPHABET[(v >> 12) as usize & 63];
+                    buf[n + 2] = if group.len() > 1 {
+                        ALPHABET[(v >> 6) as usize & 63]
+                    } else {
+                        b'='
+                    };
+                    buf[n + 3] = if group.len() > 2 {
+                        ALPHABET[v as usize & 63]
+                    } else {
+                        b'='
+                    };
+                    n += 4;
+                }
+                // SAFETY: only the ASCII chars of the alphabet and the padding are written
+                f.write_str(unsafe { std::str::from_utf8_unchecked(&buf[..n]) })?;
+            }
+            Ok(())
+        }
+    }
+
+    struct Base64Visitor;
+
+    impl<'de> Visitor<'de> for Base64Visitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a base64 string")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Vec<u8>, E>
+        where
+            E: de::Error,
+        {
+            decode(v.as_bytes()).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+        }
+
+        // the bytes decoded by the sonic-rs deserializer
+        fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Vec<u8>, E>
+        where
+            E: de::Error,
+        {
+            Ok(v)
+        }
+
+        fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Vec<u8>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_str(self)
+        }
+    }
+
+    /// Serialize the bytes as a base64 string.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        serializer.collect_str(&Encoded(value.as_ref()))
+    }
+
+    /// Deserialize the bytes from a base64 string.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(TOKEN, Base64Visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, HashMap};
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{from_str, to_string, FastStr, LazyValue};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Adapters {
+        #[serde(with = "super::display_from_str")]
+        big: u128,
+        #[serde(with = "super::display_from_str")]
+        float: f64,
+        #[serde(with = "super::comma_separated")]
+        list: Vec<i64>,
+        #[serde(with = "super::comma_separated")]
+        empty: Vec<String>,
+        #[serde(with = "super::map_as_tuple_list")]
+        map: BTreeMap<(u8, u8), String>,
+        #[serde(with = "super::map_as_tuple_list")]
+        hmap: HashMap<bool, u32>,
+    }
+
+    #[test]
+    fn test_serde_with_adapters() {
+        let data = Adapters {
+            big: u128::MAX,
+            float: 1.5,
+            list: vec![-1, 0, 1],
+            empty: vec![],
+            map: BTreeMap::from([((1, 2), "a".to_string()), ((3, 4), "b\n".to_string())]),
+            hmap: HashMap::from([(true, 1)]),
+        };
+        let json = to_string(&data).unwrap();
+        assert_eq!(
+            json,
+            r#"{"big":"340282366920938463463374607431768211455","float":"1.5","list":"-1,0,1","empty":"","map":[[[1,2],"a"],[[3,4],"b\n"]],"hmap":[[true,1]]}"#
+        );
+        assert_eq!(from_str::<Adapters>(&json).unwrap(), data);
+
+        // the escaped strings are also supported
+        let json =
+            r#"{"big":"\u0031","float":"2","list":"1,\u0032","empty":"","map":[],"hmap":[]}"#;
+        let got: Adapters = from_str(json).unwrap();
+        assert_eq!(got.big, 1);
+        assert_eq!(got.list, [1, 2]);
+
+        let invalids = [
+            r#"{"big":"a","float":"2","list":"","empty":"","map":[],"hmap":[]}"#,
+            r#"{"big":"1","float":"2","list":"1,,2","empty":"","map":[],"hmap":[]}"#,
+            r#"{"big":"1","float":"2","list":"","empty":"","map":[[1]],"hmap":[]}"#,
+            r#"{"big":1,"float":"2","list":"","empty":"","map":[],"hmap":[]}"#,
+        ];
+        for json in invalids {
+            assert!(from_str::<Adapters>(json).is_err(), "{json}");
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Filtered<'a> {
+        #[serde(with = "super::pairs_as_map", borrow)]
+        fields: Vec<(FastStr, LazyValue<'a>)>,
+    }
+
+    #[test]
+    fn test_pairs_as_map() {
+        // the order and the duplicate keys are kept, the values are written verbatim
+        let json = r#"{"fields":{"b":[1, 2],"a\n":{"x": "y"},"b":null}}"#;
+        let filtered: Filtered = from_str(json).unwrap();
+        let keys: Vec<&str> = filtered.fields.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["b", "a\n", "b"]);
+        assert_eq!(filtered.fields[1].1.as_raw_str(), r#"{"x": "y"}"#);
+        assert_eq!(to_string(&filtered).unwrap(), json);
+
+        let empty: Filtered = from_str(r#"{"fields":{}}"#).unwrap();
+        assert!(empty.fields.is_empty());
+        assert_eq!(to_string(&empty).unwrap(), r#"{"fields":{}}"#);
+        assert!(from_str::<Filtered>(r#"{"fields":[]}"#).is_err());
+
+        // the maps of the lazy values are written in the same way
+        let lv: LazyValue = from_str(r#"[1, "x"]"#).unwrap();
+        let map = HashMap::from([(FastStr::new("k\""), lv)]);
+        assert_eq!(to_string(&map).unwrap(), r#"{"k\"":[1, "x"]}"#);
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Envelope {
+        #[serde(with = "super::json_string")]
+        lazy: LazyValue<'static>,
+        #[serde(with = "super::json_string")]
+        owned: crate::OwnedLazyValue,
+        #[serde(with = "super::json_string")]
+        value: crate::Value,
+    }
+
+    #[test]
+    fn test_json_string() {
+        let json = r#"{"lazy":"[1, \"x\"]","owned":"{\"a\": null}","value":"{\"b\":true}"}"#;
+        let envelope: Envelope = from_str(json).unwrap();
+        assert_eq!(envelope.lazy.as_raw_str(), r#"[1, "x"]"#);
+        assert_eq!(envelope.owned.as_raw_str(), Some(r#"{"a": null}"#));
+        assert_eq!(envelope.value, crate::json!({"b": true}));
+        assert_eq!(to_string(&envelope).unwrap(), json);
+
+        // the invalid JSON text in the string
+        let json = r#"{"lazy":"[1,","owned":"1","value":"1"}"#;
+        assert!(from_str::<Envelope>(json).is_err());
+        let json = r#"{"lazy":[1],"owned":"1","value":"1"}"#;
+        assert!(from_str::<Envelope>(json).is_err());
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn test_decimal() {
+        use rust_decimal::Decimal;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Bill {
+            #[serde(with = "super::decimal")]
+            amount: Decimal,
+        }
+
+        let parse = |json: &str| from_str::<Bill>(json).map(|b| b.amount.to_string());
+        assert_eq!(parse(r#"{"amount": 0.1}"#).unwrap(), "0.1");
+        assert_eq!(parse(r#"{"amount": -12.340}"#).unwrap(), "-12.340");
+        assert_eq!(parse(r#"{"amount": "99.99"}"#).unwrap(), "99.99");
+        let bill: Bill = from_str(r#"{"amount": 1.5e2}"#).unwrap();
+        assert_eq!(bill.amount, Decimal::from(150));
+        assert_eq!(
+            parse(r#"{"amount": 79228162514264337593543950335}"#).unwrap(),
+            "79228162514264337593543950335"
+        );
+        assert!(parse(r#"{"amount": 79228162514264337593543950336}"#).is_err());
+        assert!(parse(r#"{"amount": 0.00000000000000000000000000001}"#).is_err());
+        assert!(parse(r#"{"amount": "abc"}"#).is_err());
+        assert!(parse(r#"{"amount": null}"#).is_err());
+
+        let bill = Bill {
+            amount: Decimal::from_str_exact("-0.0000000000000000000000000001").unwrap(),
+        };
+        let json = to_string(&bill).unwrap();
+        assert_eq!(json, r#"{"amount":-0.0000000000000000000000000001}"#);
+        assert_eq!(from_str::<Bill>(&json).unwrap(), bill);
+
+        // from and into `Value`
+        let value = crate::to_value(&bill).unwrap();
+        assert_eq!(crate::to_string(&value).unwrap(), json);
+        let value = crate::json!({"amount": 2.5});
+        assert_eq!(
+            crate::from_value::<Bill>(&value)
+                .unwrap()
+                .amount
+                .to_string(),
+            "2.5"
+        );
+    }
+
+    #[cfg(feature = "bigdecimal")]
+    #[test]
+    fn test_big_decimal() {
+        use bigdecimal::BigDecimal;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Bill {
+            #[serde(with = "super::big_decimal")]
+            amount: BigDecimal,
+        }
+
+        let digits = "3.14159265358979323846264338327950288419716939937510";
+        let json = format!(r#"{{"amount":{digits}}}"#);
+        let bill: Bill = from_str(&json).unwrap();
+        assert_eq!(bill.amount, digits.parse::<BigDecimal>().unwrap());
+        assert_eq!(to_string(&bill).unwrap(), json);
+
+        let bill: Bill = from_str(r#"{"amount": "-1.25"}"#).unwrap();
+        assert_eq!(bill.amount, "-1.25".parse::<BigDecimal>().unwrap());
+        let bill: Bill = from_str(r#"{"amount": 1e3}"#).unwrap();
+        assert_eq!(bill.amount, BigDecimal::from(1000));
+        assert!(from_str::<Bill>(r#"{"amount": true}"#).is_err());
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_uuid() {
+        use ::uuid::Uuid;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct User {
+            #[serde(with = "super::uuid")]
+            id: Uuid,
+        }
+
+        let text = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+        let expect = Uuid::parse_str(text).unwrap();
+        let parse = |json: &str| from_str::<User>(json).map(|u| u.id);
+        assert_eq!(parse(&format!(r#"{{"id":"{text}"}}"#)).unwrap(), expect);
+        assert_eq!(
+            parse(&format!(r#"{{"id":"{}"}}"#, text.to_uppercase())).unwrap(),
+            expect
+        );
+        // the escaped strings and the other forms
+        assert_eq!(
+            parse(r#"{"id":"\u0036\u0037e55044-10b1-426f-9247-bb680e5fe0c8"}"#).unwrap(),
+            expect
+        );
+        assert_eq!(
+            parse(r#"{"id":"67e5504410b1426f9247bb680e5fe0c8"}"#).unwrap(),
+            expect
+        );
+        assert_eq!(parse(&format!(r#"{{"id":"{{{text}}}"}}"#)).unwrap(), expect);
+
+        for invalid in [
+            "67e55044-10b1-426f-9247-bb680e5fe0cg",
+            "67e55044-10b1-426f-9247_bb680e5fe0c8",
+            "67e55044-10b1-426f-9247-bb680e5fe0c",
+            "",
+        ] {
+            let json = format!(r#"{{"id":"{invalid}"}}"#);
+            assert!(parse(&json).is_err(), "{json}");
+        }
+        assert!(parse(r#"{"id":1}"#).is_err());
+
+        for id in [expect, Uuid::nil(), Uuid::from_u128(u128::MAX)] {
+            let user = User { id };
+            let json = to_string(&user).unwrap();
+            assert_eq!(json, format!(r#"{{"id":"{}"}}"#, id.hyphenated()));
+            assert_eq!(from_str::<User>(&json).unwrap(), user);
+        }
+    }
+
+    #[test]
+    fn test_base64() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Blob {
+            #[serde(with = "super::base64")]
+            data: Vec<u8>,
+        }
+
+        let parse = |json: &str| from_str::<Blob>(json).map(|b| b.data);
+        assert_eq!(parse(r#"{"data":""}"#).unwrap(), b"");
+        assert_eq!(parse(r#"{"data":"Zm9vYg=="}"#).unwrap(), b"foob");
+        assert_eq!(parse(r#"{"data":"Zm9vYmE="}"#).unwrap(), b"fooba");
+        // without the padding, the URL-safe alphabet, and the escapes
+        assert_eq!(parse(r#"{"data":"Zm9vYg"}"#).unwrap(), b"foob");
+        assert_eq!(parse(r#"{"data":"-_-_"}"#).unwrap(), [0xfb, 0xff, 0xbf]);
+        assert_eq!(
+            parse(r#"{"data":"+\/+\u002f"}"#).unwrap(),
+            [0xfb, 0xff, 0xbf]
+        );
+
+        for invalid in [
+            "Zm9vY",
+            "Zm9v=",
+            "Zm9vYg=",
+            "Zm9vYg===",
+            "Zm 9v",
+            "Zm9\n",
+            "Zé9v",
+        ] {
+            let json = format!(r#"{{"data":"{invalid}"}}"#);
+            assert!(parse(&json).is_err(), "{json}");
+        }
+        assert!(parse(r#"{"data":[1]}"#).is_err());
+        assert!(parse(r#"{"data":"Zm9v"#).is_err());
+
+        let data: Vec<u8> = (0..2000u32).map(|i| (i * 7 % 256) as u8).collect();
+        for len in [0, 1, 2, 3, 767, 768, 769, 2000] {
+            let blob = Blob {
+                data: data[..len].to_vec(),
+            };
+            let json = to_string(&blob).unwrap();
+            assert_eq!(json, serde_json::to_string(&blob).unwrap());
+            assert_eq!(json.len(), r#"{"data":""}"#.len() + len.div_ceil(3) * 4);
+            assert_eq!(from_str::<Blob>(&json).unwrap(), blob);
+            assert_eq!(serde_json::from_str::<Blob>(&json).unwrap(), blob);
+            let value: crate::Value = from_str(&json).unwrap();
+            assert_eq!(crate::from_value::<Blob>(&value).unwrap(), blob);
+        }
+        assert_eq!(
+            to_string(&Blob {
+                data: b"hello?>".to_vec()
+            })
+            .unwrap(),
+            r#"{"data":"aGVsbG8/Pg=="}"#
+        );
+    }
+}
@@ -0,0 +1,470 @@
+//! Helpers for the round-trip conformance tests of the user types, enabled by the `testing`
+//! feature.
+//!
+//! The values can be checked one by one with [`check_roundtrip`], or generated from a seed with
+//! [`Gen`] and checked with [`check_roundtrip_generated`]. The generated cases are deterministic,
+//! so a failed case is reproduced by its seed.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use sonic_rs::testing::assert_roundtrip;
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct User {
+//!     name: String,
+//!     tags: Vec<String>,
+//!     age: Option<u8>,
+//! }
+//!
+//! assert_roundtrip(&User {
+//!     name: "sonic".to_string(),
+//!     tags: vec!["a\"b".to_string()],
+//!     age: None,
+//! });
+//! ```
+
+use std::fmt::{self, Debug, Display};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    from_slice, from_str, from_value, to_string, to_string_pretty, to_value, to_vec, Object, Value,
+};
+
+/// The first mismatch found by [`check_roundtrip`].
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    stage: &'static str,
+    json: String,
+    detail: String,
+    seed: Option<u64>,
+}
+
+impl Mismatch {
+    /// The name of the round-trip, such as `"to_string/from_str"`.
+    pub fn stage(&self) -> &'static str {
+        self.stage
+    }
+
+    /// The JSON text used in the round-trip, empty if the value failed to serialize.
+    pub fn json(&self) -> &str {
+        &self.json
+    }
+
+    /// The seed of the generated case, if found by [`check_roundtrip_generated`].
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "round-trip mismatch in {}: {}", self.stage, self.detail)?;
+        if !self.json.is_empty() {
+            write!(f, "\n  json: {}", self.json)?;
+        }
+        if let Some(seed) = self.seed {
+            write!(f, "\n  seed: {seed}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+struct Checker<'a, T> {
+    value: &'a T,
+    stage: &'static str,
+}
+
+impl<'a, T> Checker<'a, T>
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    fn mismatch(&self, json: &str, detail: impl Display) -> Mismatch {
+        Mismatch {
+            stage: self.stage,
+            json: json.to_string(),
+            detail: detail.to_string(),
+            seed: None,
+        }
+    }
+
+    fn ok<V, E: Display>(&self, json: &str, what: &str, ret: Result<V, E>) -> Result<V, Mismatch> {
+        ret.map_err(|e| self.mismatch(json, format_args!("failed to {what}: {e}")))
+    }
+
+    fn compare(&self, json: &str, got: &T) -> Result<(), Mismatch> {
+        if got == self.value {
+            Ok(())
+        } else {
+            Err(self.mismatch(
+                json,
+                format_args!("expected {:?}, got {:?}", self.value, got),
+            ))
+        }
+    }
+}
+
+/// Check that `value` survives all the round-trips of sonic-rs, and return the first mismatch.
+///
+/// The round-trips are:
+///
+/// - `to_string/from_str`: the compact JSON string.
+/// - `to_vec/from_slice`: the compact JSON bytes, which should also be the same as `to_string`.
+/// - `to_value/from_value`: the [`Value`] built by `to_value`.
+/// - `from_str::<Value>/from_value`: the [`Value`] parsed from the compact JSON.
+/// - `to_string_pretty/from_str`: the pretty JSON, which should be parsed into the same `Value` as
+///   the compact JSON.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::testing::check_roundtrip;
+///
+/// assert!(check_roundtrip(&vec![1, 2, 3]).is_ok());
+///
+/// // NaN is serialized as `null`, which can't be parsed into `f64`
+/// let err = check_roundtrip(&f64::NAN).unwrap_err();
+/// assert_eq!(err.stage(), "to_string/from_str");
+/// assert_eq!(err.json(), "null");
+/// ```
+pub fn check_roundtrip<T>(value: &T) -> Result<(), Mismatch>
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let mut c = Checker {
+        value,
+        stage: "to_string/from_str",
+    };
+    let compact = c.ok("", "serialize", to_string(value))?;
+    let got: T = c.ok(&compact, "deserialize", from_str(&compact))?;
+    c.compare(&compact, &got)?;
+
+    c.stage = "to_vec/from_slice";
+    let bytes = c.ok("", "serialize", to_vec(value))?;
+    if bytes != compact.as_bytes() {
+        let text = String::from_utf8_lossy(&bytes);
+        return Err(c.mismatch(
+            &text,
+            format_args!("to_vec differs from to_string {compact}"),
+        ));
+    }
+    let got: T = c.ok(&compact, "deserialize", from_slice(&bytes))?;
+    c.compare(&compact, &got)?;
+
+    c.stage = "to_value/from_value";
+    let value_ser = c.ok("", "serialize", to_value(value))?;
+    let got: T = c.ok(&compact, "deserialize", from_value(&value_ser))?;
+    c.compare(&compact, &got)?;
+
+    c.stage = "from_str::<Value>/from_value";
+    let value_de: Value = c.ok(&compact, "parse", from_str(&compact))?;
+    let got: T = c.ok(&compact, "deserialize", from_value(&value_de))?;
+    c.compare(&compact, &got)?;
+
+    c.stage = "to_string_pretty/from_str";
+    let pretty = c.ok("", "serialize", to_string_pretty(value))?;
+    let got: T = c.ok(&pretty, "deserialize", from_str(&pretty))?;
+    c.compare(&pretty, &got)?;
+    let value_pretty: Value = c.ok(&pretty, "parse", from_str(&pretty))?;
+    if value_pretty != value_de {
+        return Err(c.mismatch(
+            &pretty,
+            format_args!("the pretty JSON differs from the compact JSON {compact}"),
+        ));
+    }
+    Ok(())
+}
+
+/// Assert that `value` survives all the round-trips in [`check_roundtrip`].
+///
+/// # Panics
+///
+/// Panic with the diagnostics of the first mismatch.
+#[track_caller]
+pub fn assert_roundtrip<T>(value: &T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    if let Err(e) = check_roundtrip(value) {
+        panic!("{e}");
+    }
+}
+
+/// A deterministic generator of the test inputs, seeded by a `u64`.
+///
+/// The generated strings include the escaped and the multi-byte characters, and the generated
+/// numbers include the edge values, such as `u64::MAX` and the subnormal `f64`.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::testing::Gen;
+///
+/// let mut a = Gen::new(7);
+/// let mut b = Gen::new(7);
+/// assert_eq!(a.value(), b.value());
+/// assert_eq!(a.string(), b.string());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Gen {
+    state: u64,
+}
+
+impl Gen {
+    const MAX_DEPTH: usize = 4;
+    const MAX_LEN: u64 = 5;
+
+    /// Create a generator from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Generate a random `u64`, with the splitmix64 algorithm.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Generate a `u64` in `0..n`. Return 0 if `n` is 0.
+    pub fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            0
+        } else {
+            self.next_u64() % n
+        }
+    }
+
+    /// Generate a random `bool`.
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    /// Generate an `i64`, which is an edge value at times.
+    pub fn i64(&mut self) -> i64 {
+        const EDGES: [i64; 6] = [0, -1, 1, i64::MIN, i64::MAX, -9007199254740993];
+        match self.below(4) {
+            0 => EDGES[self.below(EDGES.len() as u64) as usize],
+            1 => self.below(1000) as i64 - 500,
+            _ => self.next_u64() as i64,
+        }
+    }
+
+    /// Generate a `u64`, which is an edge value at times.
+    pub fn u64(&mut self) -> u64 {
+        const EDGES: [u64; 4] = [0, 1, u64::MAX, 9007199254740993];
+        match self.below(4) {
+            0 => EDGES[self.below(EDGES.len() as u64) as usize],
+            1 => self.below(1000),
+            _ => self.next_u64(),
+        }
+    }
+
+    /// Generate a finite `f64`, which is an edge value at times.
+    pub fn f64(&mut self) -> f64 {
+        const EDGES: [f64; 8] = [
+            0.0,
+            -0.0,
+            0.1,
+            1e300,
+            -1e-300,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            5e-324,
+        ];
+        match self.below(3) {
+            0 => EDGES[self.below(EDGES.len() as u64) as usize],
+            1 => (self.below(2000) as f64 - 1000.0) / 8.0,
+            _ => loop {
+                let f = f64::from_bits(self.next_u64());
+                if f.is_finite() {
+                    break f;
+                }
+            },
+        }
+    }
+
+    /// Generate a short string, with the escaped and the multi-byte characters.
+    pub fn string(&mut self) -> String {
+        const CHARS: [char; 14] = [
+            'a', 'Z', '0', ' ', '\0', '"', '\\', '/', '\n', '\u{1f}', 'é', '中', '😀', '\u{2028}',
+        ];
+        let len = self.below(Self::MAX_LEN * 2);
+        (0..len)
+            .map(|_| CHARS[self.below(CHARS.len() as u64) as usize])
+            .collect()
+    }
+
+    /// Generate a [`Value`] of at most 4 levels of nesting.
+    pub fn value(&mut self) -> Value {
+        self.value_in(0)
+    }
+
+    fn value_in(&mut self, depth: usize) -> Value {
+        let kinds = if depth < Self::MAX_DEPTH { 8 } else { 6 };
+        match self.below(kinds) {
+            0 => Value::new(),
+            1 => Value::from(self.bool()),
+            2 => Value::from(self.i64()),
+            3 => Value::from(self.u64()),
+            4 => Value::new_f64(self.f64()).unwrap_or_default(),
+            5 => Value::from(self.string().as_str()),
+            6 => {
+                let len = self.below(Self::MAX_LEN);
+                let mut arr = Vec::with_capacity(len as usize);
+                for _ in 0..len {
+                    arr.push(self.value_in(depth + 1));
+                }
+                Value::from(arr)
+            }
+            _ => {
+                let mut obj = Object::new();
+                for _ in 0..self.below(Self::MAX_LEN) {
+                    let key = self.string();
+                    let val = self.value_in(depth + 1);
+                    obj.insert(&key, val);
+                }
+                Value::from(obj)
+            }
+        }
+    }
+}
+
+/// Check `cases` values generated by `gen` with [`check_roundtrip`], and return the first
+/// mismatch. Each case is generated from its own [`Gen`], whose seed is derived from `seed` and
+/// reported by [`Mismatch::seed`], so that the case is reproduced by `gen(&mut Gen::new(seed))`.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::testing::{check_roundtrip_generated, Gen};
+///
+/// assert!(check_roundtrip_generated(1, 100, |g| g.value()).is_ok());
+/// assert!(check_roundtrip_generated(1, 100, |g| (g.string(), g.i64(), g.bool())).is_ok());
+/// ```
+pub fn check_roundtrip_generated<T, F>(seed: u64, cases: usize, mut gen: F) -> Result<(), Mismatch>
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+    F: FnMut(&mut Gen) -> T,
+{
+    let mut seeds = Gen::new(seed);
+    for _ in 0..cases {
+        let case = seeds.next_u64();
+        let value = gen(&mut Gen::new(case));
+        check_roundtrip(&value).map_err(|mut e| {
+            e.seed = Some(case);
+            e
+        })?;
+    }
+    Ok(())
+}
+
+/// Assert that `cases` values generated by `gen` survive all the round-trips, as
+/// [`check_roundtrip_generated`].
+///
+/// # Panics
+///
+/// Panic with the diagnostics and the seed of the first mismatch.
+#[track_caller]
+pub fn assert_roundtrip_generated<T, F>(seed: u64, cases: usize, gen: F)
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+    F: FnMut(&mut Gen) -> T,
+{
+    if let Err(e) = check_roundtrip_generated(seed, cases, gen) {
+        panic!("{e}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Deserializer};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Foo {
+        a: u64,
+        b: Option<String>,
+        c: Vec<f64>,
+        d: BTreeMap<String, bool>,
+    }
+
+    // always deserialized as the default value
+    #[derive(Debug, PartialEq, Serialize)]
+    struct Lossy(u32);
+
+    impl<'de> Deserialize<'de> for Lossy {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            serde::de::IgnoredAny::deserialize(d)?;
+            Ok(Lossy(0))
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        assert_roundtrip(&Foo {
+            a: u64::MAX,
+            b: Some("\u{0}\n😀".to_string()),
+            c: vec![0.1, -1e300],
+            d: [("x".to_string(), true)].into_iter().collect(),
+        });
+        assert_roundtrip(&Vec::<Foo>::new());
+        assert_roundtrip(&Lossy(0));
+
+        let err = check_roundtrip(&Lossy(1)).unwrap_err();
+        assert_eq!(err.stage(), "to_string/from_str");
+        assert_eq!(err.json(), "1");
+        assert_eq!(
+            err.to_string(),
+            "round-trip mismatch in to_string/from_str: expected Lossy(1), got Lossy(0)\n  json: 1"
+        );
+
+        let err = check_roundtrip(&f64::INFINITY).unwrap_err();
+        assert!(err.to_string().contains("failed to deserialize"), "{err}");
+    }
+
+    #[test]
+    fn test_roundtrip_generated() {
+        assert_roundtrip_generated(0, 500, |g| g.value());
+        assert_roundtrip_generated(1, 500, |g| Foo {
+            a: g.u64(),
+            b: g.bool().then(|| g.string()),
+            c: (0..g.below(4)).map(|_| g.f64()).collect(),
+            d: (0..g.below(4)).map(|_| (g.string(), g.bool())).collect(),
+        });
+
+        // the same seed generates the same cases
+        let mut a = Gen::new(42);
+        let mut b = Gen::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.value(), b.value());
+        }
+
+        // the failed case is reproduced by the reported seed
+        let err = check_roundtrip_generated(3, 100, |g| Lossy(g.below(10) as u32)).unwrap_err();
+        let seed = err.seed().unwrap();
+        let value = Lossy(Gen::new(seed).below(10) as u32);
+        assert_ne!(value, Lossy(0));
+        assert_eq!(err.json(), to_string(&value).unwrap());
+        assert!(
+            err.to_string().ends_with(&format!("\n  seed: {seed}")),
+            "{err}"
+        );
+        assert_eq!(check_roundtrip(&value).unwrap_err().seed(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "round-trip mismatch in to_string/from_str")]
+    fn test_assert_roundtrip() {
+        assert_roundtrip(&Lossy(2));
+    }
+}
@@ -0,0 +1,349 @@
+//! Record the fields of the [`tracing`] events and spans as JSON, which is enabled by the
+//! `tracing-integration` feature.
+//!
+//! [`ValueVisitor`] builds a [`Value`] object from the fields, and [`JsonVisitor`] writes the
+//! fields as a JSON object text directly, which is the fast path of [`to_string`] for the logging
+//! layers. The fields are recorded as the JSON types of their values, and the `Debug` and
+//! `Display` values, such as the `message` field, are recorded as strings.
+//!
+//! # Examples
+//!
+//! ```
+//! use sonic_rs::{json, tracing::ValueVisitor};
+//! use tracing::{span, Event, Metadata, Subscriber};
+//!
+//! struct JsonSubscriber;
+//!
+//! impl Subscriber for JsonSubscriber {
+//!     fn event(&self, event: &Event<'_>) {
+//!         let mut visitor = ValueVisitor::new();
+//!         event.record(&mut visitor);
+//!         let value = visitor.into_value();
+//!         assert_eq!(value, json!({"message": "hello", "id": 7, "ok": true}));
+//!         assert_eq!(
+//!             sonic_rs::tracing::to_string(event),
+//!             r#"{"message":"hello","id":7,"ok":true}"#
+//!         );
+//!     }
+//! #   fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+//! #   fn new_span(&self, _: &span::Attributes<'_>) -> span::Id { span::Id::from_u64(1) }
+//! #   fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+//! #   fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+//! #   fn enter(&self, _: &span::Id) {}
+//! #   fn exit(&self, _: &span::Id) {}
+//! }
+//!
+//! tracing::subscriber::with_default(JsonSubscriber, || {
+//!     tracing::info!(id = 7, ok = true, "hello");
+//! });
+//! ```
+
+use std::{error::Error as StdError, fmt};
+
+use ::tracing::{
+    field::{Field, Visit},
+    Event,
+};
+use serde::Serialize;
+
+use crate::{serde::Serializer, Object, Value};
+
+/// A visitor to build a [`Value`] object from the fields of the `tracing` events and spans.
+///
+/// The field recorded later replaces the one with the same name. The integers out of the range
+/// of `i64` and `u64` are kept as the raw numbers, and the non-finite floats are recorded as
+/// `null`.
+#[derive(Debug, Default)]
+pub struct ValueVisitor {
+    object: Object,
+}
+
+impl ValueVisitor {
+    /// Create a visitor with an empty object.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the object of the recorded fields.
+    pub fn as_object(&self) -> &Object {
+        &self.object
+    }
+
+    /// Return the object of the recorded fields as a [`Value`].
+    pub fn into_value(self) -> Value {
+        self.object.into()
+    }
+
+    fn insert(&mut self, field: &Field, value: Value) {
+        self.object.insert(&field.name(), value);
+    }
+}
+
+impl Visit for ValueVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, Value::new_f64(value).unwrap_or_default());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        let value = match i64::try_from(value) {
+            Ok(v) => v.into(),
+            Err(_) => Value::new_rawnum(&value.to_string()),
+        };
+        self.insert(field, value);
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        let value = match u64::try_from(value) {
+            Ok(v) => v.into(),
+            Err(_) => Value::new_rawnum(&value.to_string()),
+        };
+        self.insert(field, value);
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn StdError + 'static)) {
+        self.insert(field, value.to_string().as_str().into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{value:?}").as_str().into());
+    }
+}
+
+/// A visitor to write the fields of the `tracing` events and spans as a JSON object text,
+/// without building a [`Value`].
+///
+/// The fields are written in the order of recording, and the `Debug` and `Display` values are
+/// escaped into the JSON strings directly. The fields with the same name are all written, unlike
+/// [`ValueVisitor`].
+#[derive(Debug)]
+pub struct JsonVisitor {
+    buf: Vec<u8>,
+}
+
+impl Default for JsonVisitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonVisitor {
+    /// Create a visitor with an empty object.
+    pub fn new() -> Self {
+        Self::with_capacity(128)
+    }
+
+    /// Create a visitor whose buffer has the capacity of `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut buf = Vec::with_capacity(capacity.max(2));
+        buf.push(b'{');
+        Self { buf }
+    }
+
+    /// Close the object and return the JSON text.
+    pub fn finish(mut self) -> String {
+        self.buf.push(b'}');
+        // SAFETY: the serializer only writes valid UTF-8
+        unsafe { String::from_utf8_unchecked(self.buf) }
+    }
+
+    fn entry<T>(&mut self, field: &Field, value: &T)
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.buf.len() > 1 {
+            self.buf.push(b',');
+        }
+        field
+            .name()
+            .serialize(&mut Serializer::new(&mut self.buf))
+            .expect("writing a string never fails");
+        self.buf.push(b':');
+        value
+            .serialize(&mut Serializer::new(&mut self.buf))
+            .expect("writing a field value never fails");
+    }
+
+    fn entry_str(&mut self, field: &Field, value: fmt::Arguments<'_>) {
+        self.entry(field, &DisplayStr(value));
+    }
+}
+
+// Write the formatted text as a JSON string without the intermediate `String`.
+struct DisplayStr<'a>(fmt::Arguments<'a>);
+
+impl Serialize for DisplayStr<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        // the non-finite floats are written as `null`
+        self.entry(field, &value);
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.entry(field, &value);
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.entry(field, &value);
+    }
+
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        self.entry(field, &value);
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.entry(field, &value);
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.entry(field, &value);
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.entry(field, value);
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn StdError + 'static)) {
+        self.entry_str(field, format_args!("{value}"));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.entry_str(field, format_args!("{value:?}"));
+    }
+}
+
+/// Write the fields of the event as a JSON object text, by [`JsonVisitor`].
+pub fn to_string(event: &Event<'_>) -> String {
+    let mut visitor = JsonVisitor::new();
+    event.record(&mut visitor);
+    visitor.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use ::tracing::{span, Metadata, Subscriber};
+
+    use super::*;
+    use crate::{json, JsonValueTrait};
+
+    #[derive(Debug)]
+    struct TestError;
+
+    impl fmt::Display for TestError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("bad \"input\"")
+        }
+    }
+
+    impl StdError for TestError {}
+
+    // Record the events and the span attributes in both visitors.
+    #[derive(Default)]
+    struct Recorder(Arc<Mutex<Vec<(Value, String)>>>);
+
+    impl Recorder {
+        fn push(&self, record: impl Fn(&mut dyn Visit)) {
+            let mut visitor = ValueVisitor::new();
+            record(&mut visitor);
+            let mut json = JsonVisitor::with_capacity(0);
+            record(&mut json);
+            self.0
+                .lock()
+                .unwrap()
+                .push((visitor.into_value(), json.finish()));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+            self.push(|v| attrs.record(v));
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            self.push(|v| event.record(v));
+            let json = self.0.lock().unwrap().last().unwrap().1.clone();
+            assert_eq!(to_string(event), json);
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn test_tracing_visitors() {
+        let recorder = Recorder::default();
+        let records = recorder.0.clone();
+        ::tracing::subscriber::with_default(recorder, || {
+            let err = TestError;
+            ::tracing::info!(
+                a = -1,
+                b = 2u64,
+                c = 1.5,
+                d = f64::NAN,
+                e = "s\n",
+                f = ?vec![1, 2],
+                g = %"x\"y",
+                h = u128::MAX,
+                i = -5i128,
+                j = false,
+                err = &err as &(dyn StdError + 'static),
+                "hello {}",
+                "world"
+            );
+            let _span = ::tracing::info_span!("span", id = 3).entered();
+        });
+
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 2);
+        let (value, json) = &records[0];
+        assert_eq!(
+            json,
+            r#"{"message":"hello world","a":-1,"b":2,"c":1.5,"d":null,"e":"s\n","f":"[1, 2]","g":"x\"y","h":340282366920938463463374607431768211455,"i":-5,"j":false,"err":"bad \"input\""}"#
+        );
+        assert_eq!(value["message"], "hello world");
+        assert_eq!(
+            value["h"].as_raw_number().unwrap().as_str(),
+            u128::MAX.to_string()
+        );
+        assert_eq!(value["i"], -5);
+        assert!(value["d"].is_null());
+
+        assert_eq!(records[1].0, json!({"id": 3}));
+        assert_eq!(records[1].1, r#"{"id":3}"#);
+    }
+}
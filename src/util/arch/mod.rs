@@ -5,6 +5,9 @@ cfg_if::cfg_if! {
     } else if #[cfg(all(target_feature="neon", target_arch="aarch64"))] {
         mod aarch64;
         pub use aarch64::*;
+    } else if #[cfg(all(target_feature="simd128", target_arch="wasm32"))] {
+        mod wasm32;
+        pub use wasm32::*;
     } else {
         mod fallback;
         pub use fallback::*;
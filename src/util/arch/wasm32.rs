@@ -0,0 +1,42 @@
+use std::arch::wasm32::*;
+
+// There is no carry-less multiplication in wasm simd128, so use the same shifts as the fallback.
+#[inline(always)]
+pub unsafe fn prefix_xor(bitmask: u64) -> u64 {
+    let mut bitmask = bitmask;
+    bitmask ^= bitmask << 1;
+    bitmask ^= bitmask << 2;
+    bitmask ^= bitmask << 4;
+    bitmask ^= bitmask << 8;
+    bitmask ^= bitmask << 16;
+    bitmask ^= bitmask << 32;
+    bitmask
+}
+
+// The same table lookups as aarch64, `u8x16_swizzle` is the equivalent of `vqtbl1q_u8`.
+#[inline(always)]
+pub unsafe fn get_nonspace_bits(data: &[u8; 64]) -> u64 {
+    #[inline(always)]
+    unsafe fn chunk_space_mask(input: v128) -> v128 {
+        const LOW_TAB: v128 = u8x16(16, 0, 0, 0, 0, 0, 0, 0, 0, 8, 12, 1, 2, 9, 0, 0);
+        const HIGH_TAB: v128 = u8x16(8, 0, 18, 4, 0, 1, 0, 1, 0, 0, 0, 3, 2, 1, 0, 0);
+
+        let lo4 = v128_and(input, u8x16_splat(0xf));
+        let hi4 = u8x16_shr(input, 4);
+
+        let lo4_sf = u8x16_swizzle(LOW_TAB, lo4);
+        let hi4_sf = u8x16_swizzle(HIGH_TAB, hi4);
+
+        let v = v128_and(lo4_sf, hi4_sf);
+        let white = v128_and(v, u8x16_splat(0x18));
+        u8x16_ne(white, u8x16_splat(0))
+    }
+
+    let ptr = data.as_ptr() as *const v128;
+    !sonic_simd::wasm128::to_bitmask64(
+        chunk_space_mask(v128_load(ptr)),
+        chunk_space_mask(v128_load(ptr.add(1))),
+        chunk_space_mask(v128_load(ptr.add(2))),
+        chunk_space_mask(v128_load(ptr.add(3))),
+    )
+}
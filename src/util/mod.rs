@@ -2,6 +2,7 @@ pub(crate) mod arch;
 
 pub(crate) mod private;
 pub(crate) mod reborrow;
+pub(crate) mod scratch;
 pub(crate) mod string;
 pub(crate) mod unicode;
 pub(crate) mod utf8;
@@ -2,13 +2,17 @@ use bytes::Bytes;
 use faststr::FastStr;
 
 use crate::{
-    reader::{PaddedSliceRead, Read},
+    reader::{PaddedSliceRead, Read, Utf16Text},
     PointerNode,
 };
 
 // Prevent users from implementing the trait in sonic-rs.
 pub trait Sealed {}
 impl Sealed for usize {}
+impl Sealed for u64 {}
+impl Sealed for i32 {}
+impl Sealed for i64 {}
+impl Sealed for isize {}
 impl Sealed for str {}
 impl Sealed for std::string::String {}
 impl Sealed for FastStr {}
@@ -16,6 +20,9 @@ impl Sealed for Bytes {}
 impl Sealed for u8 {}
 impl<'de> Sealed for Read<'de> {}
 impl<'de> Sealed for PaddedSliceRead<'de> {}
+impl Sealed for Utf16Text {}
 impl<'a, T> Sealed for &'a T where T: ?Sized + Sealed {}
 impl<T> Sealed for [T] where T: Sized + Sealed {}
 impl Sealed for PointerNode {}
+impl<T> Sealed for std::sync::Arc<T> where T: ?Sized + Sealed {}
+impl<T> Sealed for std::rc::Rc<T> where T: ?Sized + Sealed {}
@@ -0,0 +1,185 @@
+use std::ops::{Deref, DerefMut};
+
+// The size of the inline storage, most of the keys and short strings fit in it.
+pub(crate) const INLINE_SCRATCH_SIZE: usize = 64;
+
+/// The buffer for the unescaped strings and keys when parsing. The short strings are stored
+/// inline, and it spills to the heap only if the strings are longer than `N` bytes, so parsing the
+/// small documents has no allocation for the escaped strings.
+///
+/// The heap buffer is kept after `clear`, to be reused by the following long strings.
+pub(crate) struct ScratchBuf<const N: usize = INLINE_SCRATCH_SIZE> {
+    inline: [u8; N],
+    // the length of the inline bytes, unused if spilled
+    len: usize,
+    heap: Option<Vec<u8>>,
+}
+
+impl<const N: usize> Default for ScratchBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> ScratchBuf<N> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self {
+            inline: [0; N],
+            len: 0,
+            heap: None,
+        }
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        match &self.heap {
+            Some(heap) => heap.len(),
+            None => self.len,
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        match &mut self.heap {
+            Some(heap) => heap.clear(),
+            None => self.len = 0,
+        }
+    }
+
+    /// Reserve the capacity for at least `additional` more bytes, the bytes after `len` are
+    /// writable by `as_mut_ptr` then.
+    #[inline(always)]
+    pub fn reserve(&mut self, additional: usize) {
+        match &mut self.heap {
+            Some(heap) => heap.reserve(additional),
+            None if self.len + additional <= N => {}
+            None => self.spill(additional),
+        }
+    }
+
+    #[cold]
+    fn spill(&mut self, additional: usize) {
+        let mut heap = Vec::with_capacity((self.len + additional).max(N * 2));
+        heap.extend_from_slice(&self.inline[..self.len]);
+        self.heap = Some(heap);
+    }
+
+    #[inline(always)]
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        match &mut self.heap {
+            Some(heap) => heap.as_mut_ptr(),
+            None => self.inline.as_mut_ptr(),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The bytes before `new_len` must be initialized, and `new_len` must not exceed the reserved
+    /// capacity.
+    #[inline(always)]
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        match &mut self.heap {
+            Some(heap) => heap.set_len(new_len),
+            None => {
+                debug_assert!(new_len <= N);
+                self.len = new_len;
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn push(&mut self, byte: u8) {
+        self.reserve(1);
+        unsafe {
+            let len = self.len();
+            *self.as_mut_ptr().add(len) = byte;
+            self.set_len(len + 1);
+        }
+    }
+
+    #[inline(always)]
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.reserve(bytes.len());
+        unsafe {
+            let len = self.len();
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), self.as_mut_ptr().add(len), bytes.len());
+            self.set_len(len + bytes.len());
+        }
+    }
+
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[u8] {
+        match &self.heap {
+            Some(heap) => heap.as_slice(),
+            None => &self.inline[..self.len],
+        }
+    }
+
+    #[inline(always)]
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        match &mut self.heap {
+            Some(heap) => heap.as_mut_slice(),
+            None => &mut self.inline[..self.len],
+        }
+    }
+}
+
+impl<const N: usize> Deref for ScratchBuf<N> {
+    type Target = [u8];
+
+    #[inline(always)]
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> DerefMut for ScratchBuf<N> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scratch_buf() {
+        let mut buf = ScratchBuf::<4>::new();
+        assert!(buf.is_empty());
+        buf.extend_from_slice(b"ab");
+        buf.push(b'c');
+        assert_eq!(&*buf, b"abc");
+        assert!(buf.heap.is_none());
+
+        // spill to the heap, and keep the bytes
+        buf.extend_from_slice(b"defg");
+        assert_eq!(&*buf, b"abcdefg");
+        assert!(buf.heap.is_some());
+
+        // the heap buffer is reused after clear
+        buf.clear();
+        assert!(buf.is_empty());
+        buf.reserve(3);
+        unsafe {
+            std::ptr::copy_nonoverlapping(b"xyz".as_ptr(), buf.as_mut_ptr(), 3);
+            buf.set_len(3);
+        }
+        assert_eq!(buf.as_slice(), b"xyz");
+        assert!(buf.heap.is_some());
+
+        let mut buf = ScratchBuf::<4>::new();
+        buf.reserve(4);
+        unsafe { buf.set_len(0) };
+        assert!(buf.heap.is_none());
+        buf.reserve(5);
+        assert!(buf.heap.is_some());
+    }
+}
@@ -22,6 +22,32 @@ pub unsafe fn str_from_raw_parts<'a>(ptr: *const u8, len: usize) -> &'a str {
     from_utf8_unchecked(from_raw_parts(ptr, len))
 }
 
+/// Decode the unknown escape `\c` in the lenient escapes mode, `next` is the bytes after `c`.
+///
+/// `\xHH` is decoded as the char `U+00HH`, and the other chars are kept literally. Return the
+/// UTF-8 bytes and its length, and the count of consumed bytes in `next`. The control chars are
+/// still invalid.
+#[inline]
+pub(crate) fn lenient_escape(c: u8, next: &[u8]) -> Option<([u8; 2], usize, usize)> {
+    if c < 0x20 {
+        return None;
+    }
+
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    if let (b'x', [h, l, ..]) = (c, next) {
+        if let (Some(h), Some(l)) = (hex(*h), hex(*l)) {
+            let ch = (h << 4) | l;
+            return Some(if ch < 0x80 {
+                ([ch, 0], 1, 2)
+            } else {
+                ([0xC0 | (ch >> 6), 0x80 | (ch & 0x3F)], 2, 2)
+            });
+        }
+    }
+    // the non-ASCII byte starts an UTF-8 char, and it is copied with the following bytes
+    Some(([c, 0], 1, 0))
+}
+
 pub const ESCAPED_TAB: [u8; 256] = [
     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
     0, 0, b'"', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, b'/', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
@@ -54,6 +80,15 @@ impl StringBlock<u32> {
             unescaped_bits: (v.le(&u8x32::splat(0x1f))).bitmask(),
         }
     }
+
+    /// Ignore the control chars if they are allowed, when repairing the JSON.
+    #[inline(always)]
+    pub fn allow_control(mut self, allow: bool) -> Self {
+        if allow {
+            self.unescaped_bits = 0;
+        }
+        self
+    }
 }
 
 #[cfg(all(target_feature = "neon", target_arch = "aarch64"))]
@@ -68,6 +103,15 @@ impl StringBlock<NeonBits> {
             unescaped_bits: (v.le(&u8x16::splat(0x1f))).bitmask(),
         }
     }
+
+    /// Ignore the control chars if they are allowed, when repairing the JSON.
+    #[inline(always)]
+    pub fn allow_control(mut self, allow: bool) -> Self {
+        if allow {
+            self.unescaped_bits = NeonBits::new(0);
+        }
+        self
+    }
 }
 
 impl<B: BitMask> StringBlock<B> {
@@ -109,12 +153,14 @@ pub(crate) unsafe fn load<V: Simd>(ptr: *const u8) -> V {
 }
 
 /// Return the size of the actual parsed string, `repr` means repr invalid UTF16 surrogate with
-/// `\uFFFD`
+/// `\uFFFD`, `lenient` means the lenient escapes as [`lenient_escape`], `ALLOW_CONTROL` means the
+/// control chars are kept in the string.
 /// TODO: fix me, there are repeat codes!!!
 #[inline(always)]
-pub(crate) unsafe fn parse_string_inplace(
+pub(crate) unsafe fn parse_string_inplace<const ALLOW_CONTROL: bool>(
     src: &mut *mut u8,
     repr: bool,
+    lenient: bool,
 ) -> std::result::Result<usize, ErrorCode> {
     #[cfg(all(target_feature = "neon", target_arch = "aarch64"))]
     let mut block: StringBlock<NeonBits>;
@@ -126,7 +172,7 @@ pub(crate) unsafe fn parse_string_inplace(
 
     // loop for string without escaped chars
     loop {
-        block = StringBlock::new(&unsafe { load(*src) });
+        block = StringBlock::new(&unsafe { load(*src) }).allow_control(ALLOW_CONTROL);
         if block.has_quote_first() {
             let idx = block.quote_index();
             *src = src.add(idx + 1);
@@ -153,13 +199,22 @@ pub(crate) unsafe fn parse_string_inplace(
                 if !handle_unicode_codepoint_mut(src, &mut dst, repr) {
                     return Err(InvalidUnicodeCodePoint);
                 }
-            } else {
+            } else if ESCAPED_TAB[escaped_char as usize] != 0 || !lenient {
                 *dst = ESCAPED_TAB[escaped_char as usize];
                 if *dst == 0 {
                     return Err(InvalidEscape);
                 }
                 *src = src.add(2);
                 dst = dst.add(1);
+            } else {
+                // the JSON is padded, so reading the next 2 bytes is safe
+                let next = from_raw_parts(src.add(2), 2);
+                let Some((bytes, len, eaten)) = lenient_escape(escaped_char, next) else {
+                    return Err(InvalidEscape);
+                };
+                std::ptr::copy_nonoverlapping(bytes.as_ptr(), dst, len);
+                *src = src.add(2 + eaten);
+                dst = dst.add(len);
             }
 
             // fast path for continuous escaped chars
@@ -171,7 +226,7 @@ pub(crate) unsafe fn parse_string_inplace(
 
         'find_and_move: loop {
             let v = unsafe { load(*src) };
-            let block = StringBlock::new(&v);
+            let block = StringBlock::new(&v).allow_control(ALLOW_CONTROL);
             if block.has_quote_first() {
                 while **src != b'"' {
                     *dst = **src;
@@ -202,6 +257,57 @@ pub(crate) unsafe fn parse_string_inplace(
     } // slow loop for escaped chars
 }
 
+/// Find the first control char in the strings of the raw JSON, which is only parsed if the control
+/// chars are allowed.
+pub(crate) fn find_control_char(json: &[u8]) -> Option<usize> {
+    let mut in_str = false;
+    let mut escaped = false;
+    for (i, &c) in json.iter().enumerate() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            b'"' => in_str = !in_str,
+            b'\\' if in_str => escaped = true,
+            0..=0x1f if in_str => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Escape the control chars in the strings of the raw JSON, so that the raw JSON is valid again.
+/// Return `None` if there is no control char.
+pub(crate) fn escape_control_chars(json: &str) -> Option<String> {
+    let bytes = json.as_bytes();
+    let first = find_control_char(bytes)?;
+    let mut out = String::with_capacity(json.len() + 16);
+    let mut start = 0;
+    // the first control char is in a string
+    let mut in_str = true;
+    let mut escaped = false;
+    for (i, &c) in bytes.iter().enumerate().skip(first) {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            b'"' => in_str = !in_str,
+            b'\\' if in_str => escaped = true,
+            0..=0x1f if in_str => {
+                let (len, quoted) = &QUOTE_TAB[c as usize];
+                out.push_str(&json[start..i]);
+                out.push_str(unsafe { from_utf8_unchecked(&quoted[..*len as usize]) });
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    out.push_str(&json[start..]);
+    Some(out)
+}
+
 pub const QUOTE_TAB: [(u8, [u8; 8]); 256] = [
     // 0x00 ~ 0x1f
     (6, *b"\\u0000\0\0"),
@@ -502,6 +608,78 @@ unsafe fn escape_unchecked(src: &mut *const u8, nb: &mut usize, dst: &mut *mut u
     }
 }
 
+/// The chars escaped by the serializer besides the quote, the backslash and the control chars
+/// below `0x20`, which are valid in JSON but not in some contexts, such as the JavaScript code.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Escapes {
+    // U+2028 LINE SEPARATOR and U+2029 PARAGRAPH SEPARATOR
+    pub line_separators: bool,
+    // DEL and the C1 control chars from U+0080 to U+009F
+    pub controls: bool,
+}
+
+impl Escapes {
+    #[inline(always)]
+    pub fn is_none(&self) -> bool {
+        !self.line_separators && !self.controls
+    }
+}
+
+// Escape the char flagged by the extra escapes, or copy the byte if the char is not escaped, such
+// as the other chars starting with `0xE2`.
+#[inline]
+unsafe fn escape_extra<const LINE: bool, const CTRL: bool>(
+    src: &mut *const u8,
+    nb: &mut usize,
+    dst: &mut *mut u8,
+) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    // the string is valid UTF-8, so the continuation bytes are in `src`
+    let s = from_raw_parts(*src, (*nb).min(3));
+    let (len, escaped) = match *s {
+        [0x7f, ..] if CTRL => (1, Some(*b"\\u007f")),
+        [0xc2, c @ 0x80..=0x9f, ..] if CTRL => (
+            2,
+            Some([
+                b'\\',
+                b'u',
+                b'0',
+                b'0',
+                HEX[(c >> 4) as usize],
+                HEX[(c & 0xf) as usize],
+            ]),
+        ),
+        [0xe2, 0x80, 0xa8, ..] if LINE => (3, Some(*b"\\u2028")),
+        [0xe2, 0x80, 0xa9, ..] if LINE => (3, Some(*b"\\u2029")),
+        _ => (1, None),
+    };
+    match escaped {
+        Some(escaped) => {
+            std::ptr::copy_nonoverlapping(escaped.as_ptr(), *dst, 6);
+            *dst = (*dst).add(6);
+        }
+        None => {
+            **dst = s[0];
+            *dst = (*dst).add(1);
+        }
+    }
+    *src = (*src).add(len);
+    *nb -= len;
+}
+
+#[inline(always)]
+unsafe fn escape_next<const LINE: bool, const CTRL: bool>(
+    src: &mut *const u8,
+    nb: &mut usize,
+    dst: &mut *mut u8,
+) {
+    if (LINE || CTRL) && NEED_ESCAPED[*(*src) as usize] == 0 {
+        escape_extra::<LINE, CTRL>(src, nb, dst)
+    } else {
+        escape_unchecked(src, nb, dst)
+    }
+}
+
 #[inline(always)]
 fn check_cross_page(ptr: *const u8, step: usize) -> bool {
     #[cfg(any(target_os = "linux", target_os = "macos"))]
@@ -517,10 +695,91 @@ fn check_cross_page(ptr: *const u8, step: usize) -> bool {
     }
 }
 
+// The short strings reserve the worst-case capacity directly, to avoid scanning them twice.
+const SHORT_STRING_LEN: usize = 256;
+
+// The extra capacity for the quotes and the unaligned SIMD stores when formatting strings.
+const FORMAT_STRING_SLACK: usize = 32 + 3;
+
+// Returns the exact length of the escaped string, without the quotes.
+#[inline]
+fn escaped_len(value: &str) -> usize {
+    value.bytes().fold(value.len(), |len, ch| {
+        len + (QUOTE_TAB[ch as usize].0 as usize).saturating_sub(1)
+    })
+}
+
+/// Returns the capacity of the destination buffer required by [`format_string`].
+///
+/// It is the worst case for the short strings, and the exact escaped length for the long
+/// strings, so that a huge string will not reserve six times of its length.
 #[inline(always)]
-pub fn format_string(value: &str, dst: &mut [MaybeUninit<u8>], need_quote: bool) -> usize {
-    assert!(dst.len() >= value.len() * 6 + 32 + 3);
+pub fn format_string_capacity(value: &str) -> usize {
+    if value.len() <= SHORT_STRING_LEN {
+        value.len() * 6 + FORMAT_STRING_SLACK
+    } else {
+        escaped_len(value) + FORMAT_STRING_SLACK
+    }
+}
+
+/// Returns the capacity of the destination buffer required by [`format_string_with`]. The long
+/// strings count the lead bytes of the extra escaped chars as the upper bound.
+#[inline]
+pub(crate) fn format_string_capacity_with(value: &str, escapes: Escapes) -> usize {
+    if value.len() <= SHORT_STRING_LEN || escapes.is_none() {
+        return format_string_capacity(value);
+    }
+    let extra: usize = value
+        .bytes()
+        .map(|ch| match ch {
+            0x7f if escapes.controls => 5,
+            0xc2 if escapes.controls => 4,
+            0xe2 if escapes.line_separators => 3,
+            _ => 0,
+        })
+        .sum();
+    escaped_len(value) + extra + FORMAT_STRING_SLACK
+}
+
+/// Format the string into `dst` with escaping, and return the written length.
+///
+/// # Safety
+///
+/// The length of `dst` must not be less than [`format_string_capacity`].
+#[inline(always)]
+pub unsafe fn format_string(value: &str, dst: &mut [MaybeUninit<u8>], need_quote: bool) -> usize {
+    debug_assert!(dst.len() >= format_string_capacity(value));
+    format_string_impl::<false, false>(value, dst, need_quote)
+}
 
+/// Same as [`format_string`], and also escape the chars in `escapes`.
+///
+/// # Safety
+///
+/// The length of `dst` must not be less than [`format_string_capacity_with`].
+#[inline]
+pub(crate) unsafe fn format_string_with(
+    value: &str,
+    dst: &mut [MaybeUninit<u8>],
+    need_quote: bool,
+    escapes: Escapes,
+) -> usize {
+    debug_assert!(dst.len() >= format_string_capacity_with(value, escapes));
+    // the extra escapes are checked in the SIMD mask, and the default has no extra cost
+    match (escapes.line_separators, escapes.controls) {
+        (false, false) => format_string_impl::<false, false>(value, dst, need_quote),
+        (true, false) => format_string_impl::<true, false>(value, dst, need_quote),
+        (false, true) => format_string_impl::<false, true>(value, dst, need_quote),
+        (true, true) => format_string_impl::<true, true>(value, dst, need_quote),
+    }
+}
+
+#[inline(always)]
+unsafe fn format_string_impl<const LINE: bool, const CTRL: bool>(
+    value: &str,
+    dst: &mut [MaybeUninit<u8>],
+    need_quote: bool,
+) -> usize {
     #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
     let mut v: u8x16;
     #[cfg(not(all(target_arch = "aarch64", target_feature = "neon")))]
@@ -533,22 +792,38 @@ pub fn format_string(value: &str, dst: &mut [MaybeUninit<u8>], need_quote: bool)
 
     #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
     #[inline]
-    fn escaped_mask(v: u8x16) -> NeonBits {
+    fn escaped_mask<const LINE: bool, const CTRL: bool>(v: u8x16) -> NeonBits {
         let x1f = u8x16::splat(0x1f); // 0x00 ~ 0x20
         let blash = u8x16::splat(b'\\');
         let quote = u8x16::splat(b'"');
-        let v = v.le(&x1f) | v.eq(&blash) | v.eq(&quote);
-        v.bitmask()
+        let mut m = v.le(&x1f) | v.eq(&blash) | v.eq(&quote);
+        // the lead bytes of the extra escaped chars
+        if LINE {
+            m |= v.eq(&u8x16::splat(0xe2));
+        }
+        if CTRL {
+            m |= v.eq(&u8x16::splat(0x7f));
+            m |= v.eq(&u8x16::splat(0xc2));
+        }
+        m.bitmask()
     }
 
     #[cfg(not(all(target_arch = "aarch64", target_feature = "neon")))]
     #[inline]
-    fn escaped_mask(v: u8x32) -> u32 {
+    fn escaped_mask<const LINE: bool, const CTRL: bool>(v: u8x32) -> u32 {
         let x1f = u8x32::splat(0x1f); // 0x00 ~ 0x20
         let blash = u8x32::splat(b'\\');
         let quote = u8x32::splat(b'"');
-        let v = v.le(&x1f) | v.eq(&blash) | v.eq(&quote);
-        v.bitmask()
+        let mut m = v.le(&x1f) | v.eq(&blash) | v.eq(&quote);
+        // the lead bytes of the extra escaped chars
+        if LINE {
+            m |= v.eq(&u8x32::splat(0xe2));
+        }
+        if CTRL {
+            m |= v.eq(&u8x32::splat(0x7f));
+            m |= v.eq(&u8x32::splat(0xc2));
+        }
+        m.bitmask()
     }
 
     unsafe {
@@ -565,7 +840,7 @@ pub fn format_string(value: &str, dst: &mut [MaybeUninit<u8>], need_quote: bool)
         while nb >= LANES {
             v = load(sptr);
             v.write_to_slice_unaligned_unchecked(std::slice::from_raw_parts_mut(dptr, LANES));
-            let mask = escaped_mask(v);
+            let mask = escaped_mask::<LINE, CTRL>(v);
             if mask.all_zero() {
                 nb -= LANES;
                 dptr = dptr.add(LANES);
@@ -575,7 +850,7 @@ pub fn format_string(value: &str, dst: &mut [MaybeUninit<u8>], need_quote: bool)
                 nb -= cn;
                 dptr = dptr.add(cn);
                 sptr = sptr.add(cn);
-                escape_unchecked(&mut sptr, &mut nb, &mut dptr);
+                escape_next::<LINE, CTRL>(&mut sptr, &mut nb, &mut dptr);
             }
         }
 
@@ -598,7 +873,7 @@ pub fn format_string(value: &str, dst: &mut [MaybeUninit<u8>], need_quote: bool)
             };
             v.write_to_slice_unaligned_unchecked(std::slice::from_raw_parts_mut(dptr, LANES));
 
-            let mask = escaped_mask(v).clear_high_bits(LANES - nb);
+            let mask = escaped_mask::<LINE, CTRL>(v).clear_high_bits(LANES - nb);
             if mask.all_zero() {
                 dptr = dptr.add(nb);
                 break;
@@ -607,7 +882,7 @@ pub fn format_string(value: &str, dst: &mut [MaybeUninit<u8>], need_quote: bool)
                 nb -= cn;
                 dptr = dptr.add(cn);
                 sptr = sptr.add(cn);
-                escape_unchecked(&mut sptr, &mut nb, &mut dptr);
+                escape_next::<LINE, CTRL>(&mut sptr, &mut nb, &mut dptr);
             }
         }
         if need_quote {
@@ -626,20 +901,101 @@ mod test {
     fn test_quote() {
         let mut dst = [0u8; 1000];
         let dst_ref = unsafe { std::mem::transmute::<&mut [u8], &mut [MaybeUninit<u8>]>(&mut dst) };
-        assert_eq!(format_string("", dst_ref, true), 2);
+        assert_eq!(unsafe { format_string("", dst_ref, true) }, 2);
         assert_eq!(dst[..2], *b"\"\"");
-        assert_eq!(format_string("\x00", dst_ref, true), 8);
+        assert_eq!(unsafe { format_string("\x00", dst_ref, true) }, 8);
         assert_eq!(dst[..8], *b"\"\\u0000\"");
-        assert_eq!(format_string("test", dst_ref, true), 6);
+        assert_eq!(unsafe { format_string("test", dst_ref, true) }, 6);
         assert_eq!(dst[..6], *b"\"test\"");
-        assert_eq!(format_string("test\"test", dst_ref, true), 12);
+        assert_eq!(unsafe { format_string("test\"test", dst_ref, true) }, 12);
         assert_eq!(dst[..12], *b"\"test\\\"test\"");
-        assert_eq!(format_string("\\testtest\"", dst_ref, true), 14);
+        assert_eq!(unsafe { format_string("\\testtest\"", dst_ref, true) }, 14);
         assert_eq!(dst[..14], *b"\"\\\\testtest\\\"\"");
 
         let long_str = "this is a long string that should be \\\"quoted and escaped multiple \
                         times to test the performance and correctness of the function.";
-        assert_eq!(format_string(long_str, dst_ref, true), 129 + 4);
+        assert_eq!(unsafe { format_string(long_str, dst_ref, true) }, 129 + 4);
         assert_eq!(dst[..133], *b"\"this is a long string that should be \\\\\\\"quoted and escaped multiple times to test the performance and correctness of the function.\"");
     }
+
+    #[test]
+    fn test_format_string_capacity() {
+        let mut dst = Vec::new();
+        for (value, escaped) in [
+            ("a".repeat(1000), 1000),
+            ("\"".repeat(1000), 2000),
+            ("\x01".repeat(1000), 6000),
+            (
+                format!("{}\n{}", "a".repeat(500), "\u{1F600}".repeat(100)),
+                500 + 2 + 400,
+            ),
+        ] {
+            let cap = format_string_capacity(&value);
+            assert_eq!(cap, escaped + FORMAT_STRING_SLACK);
+            dst.clear();
+            dst.reserve_exact(cap);
+            let cnt = unsafe { format_string(&value, &mut dst.spare_capacity_mut()[..cap], true) };
+            unsafe { dst.set_len(cnt) };
+            assert_eq!(cnt, escaped + 2);
+            assert_eq!(dst, serde_json::to_string(&value).unwrap().as_bytes());
+        }
+
+        // the short strings reserve the worst case
+        assert_eq!(format_string_capacity("abc"), 3 * 6 + FORMAT_STRING_SLACK);
+    }
+
+    #[test]
+    fn test_format_string_escapes() {
+        let all = Escapes {
+            line_separators: true,
+            controls: true,
+        };
+        let pad = "a".repeat(31);
+        let mut dst = Vec::new();
+        for (value, escapes, expect) in [
+            ("\u{2028}\u{2029}", all, r#""\u2028\u2029""#.to_string()),
+            (
+                "\u{2028}\u{7f}",
+                Escapes {
+                    line_separators: true,
+                    controls: false,
+                },
+                "\"\\u2028\u{7f}\"".to_string(),
+            ),
+            (
+                "\u{2028}\u{7f}\u{85}",
+                Escapes {
+                    line_separators: false,
+                    controls: true,
+                },
+                "\"\u{2028}\\u007f\\u0085\"".to_string(),
+            ),
+            // the other chars starting with 0xe2 or 0xc2 are not escaped
+            (
+                "\u{20ac}\u{a0}\u{e9}\"",
+                all,
+                "\"\u{20ac}\u{a0}\u{e9}\\\"\"".to_string(),
+            ),
+            // the chars across the SIMD lanes
+            (
+                format!("{pad}\u{2029}{pad}\u{9f}\u{2028}").as_str(),
+                all,
+                format!(r#""{pad}\u2029{pad}\u009f\u2028""#),
+            ),
+            (
+                "\u{2028}\u{7f}".repeat(100).as_str(),
+                all,
+                format!(r#""{}""#, r"\u2028\u007f".repeat(100)),
+            ),
+        ] {
+            let cap = format_string_capacity_with(value, escapes);
+            dst.clear();
+            dst.reserve_exact(cap);
+            let cnt = unsafe {
+                format_string_with(value, &mut dst.spare_capacity_mut()[..cap], true, escapes)
+            };
+            unsafe { dst.set_len(cnt) };
+            assert_eq!(std::str::from_utf8(&dst).unwrap(), expect, "{value:?}");
+        }
+    }
 }
@@ -0,0 +1,542 @@
+//! Validate the JSON text in one streaming pass, without building a DOM.
+
+use std::hash::Hasher;
+
+use ahash::{AHashSet, AHasher};
+use faststr::FastStr;
+
+use crate::{
+    error::{Error, ErrorCode, Result},
+    input::JsonInput,
+    parser::{ParsedSlice, Parser},
+    reader::{Read, Reader},
+    util::{scratch::ScratchBuf, utf8::from_utf8},
+    PointerNode,
+};
+
+// The keys are compared linearly by the hashes until the object has more keys than this.
+const SMALL_KEYS: usize = 16;
+
+// Where the bytes of a key are.
+#[derive(Clone, Copy)]
+enum KeyRef {
+    // the range in the JSON text, for the keys without escapes
+    Input(usize, usize),
+    // the range in `KeySet::unescaped`
+    Unescaped(usize, usize),
+}
+
+// The keys of an open object. The sets are kept for each depth and reused by the objects at the
+// same depth, so that the memory is bounded by the keys of the open objects.
+#[derive(Default)]
+struct KeySet {
+    keys: Vec<(u64, KeyRef)>,
+    unescaped: Vec<u8>,
+    // the hashes of `keys`, spilled from the linear scan when the object has many keys
+    spilled: AHashSet<u64>,
+}
+
+impl KeySet {
+    fn clear(&mut self) {
+        self.keys.clear();
+        self.unescaped.clear();
+        self.spilled.clear();
+    }
+
+    fn bytes<'a>(&'a self, json: &'a [u8], key: KeyRef) -> &'a [u8] {
+        match key {
+            KeyRef::Input(start, end) => &json[start..end],
+            KeyRef::Unescaped(start, end) => &self.unescaped[start..end],
+        }
+    }
+
+    // Insert the key, and return false if it is duplicated.
+    fn insert(&mut self, json: &[u8], key: &ParsedSlice) -> bool {
+        let mut hasher = AHasher::default();
+        hasher.write(key);
+        let hash = hasher.finish();
+
+        let maybe_dup = if self.keys.len() < SMALL_KEYS {
+            self.keys.iter().any(|(h, _)| *h == hash)
+        } else {
+            if self.spilled.is_empty() {
+                self.spilled.extend(self.keys.iter().map(|(h, _)| *h));
+            }
+            !self.spilled.insert(hash)
+        };
+        // compare the keys with the same hash
+        if maybe_dup
+            && self
+                .keys
+                .iter()
+                .any(|&(h, k)| h == hash && self.bytes(json, k) == &key[..])
+        {
+            return false;
+        }
+
+        let key = match key {
+            ParsedSlice::Borrowed { slice, .. } => {
+                let start = slice.as_ptr() as usize - json.as_ptr() as usize;
+                KeyRef::Input(start, start + slice.len())
+            }
+            ParsedSlice::Copied(_) => {
+                let start = self.unescaped.len();
+                self.unescaped.extend_from_slice(key);
+                KeyRef::Unescaped(start, self.unescaped.len())
+            }
+        };
+        self.keys.push((hash, key));
+        true
+    }
+
+    fn last_key(&self, json: &[u8]) -> FastStr {
+        let (_, key) = self.keys.last().expect("the object has a key");
+        FastStr::new(String::from_utf8_lossy(self.bytes(json, *key)))
+    }
+}
+
+enum Frame {
+    Object,
+    Array(usize),
+}
+
+struct Checker<'de> {
+    json: &'de [u8],
+    parser: Parser<Read<'de>>,
+    stack: Vec<Frame>,
+    // the key sets of the objects, indexed by the depth
+    sets: Vec<KeySet>,
+    buf: ScratchBuf,
+}
+
+impl Checker<'_> {
+    // The pointer of the duplicated key in the top object.
+    fn pointer(&self, key: FastStr) -> Vec<PointerNode> {
+        let mut pointer: Vec<PointerNode> = self.stack[..self.stack.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(depth, frame)| match frame {
+                Frame::Object => PointerNode::Key(self.sets[depth].last_key(self.json)),
+                Frame::Array(i) => PointerNode::Index(*i),
+            })
+            .collect();
+        pointer.push(PointerNode::Key(key));
+        pointer
+    }
+
+    // Parse the key after the quote, and check it in the key set of the top object.
+    fn check_key(&mut self) -> Result<()> {
+        let depth = self.stack.len() - 1;
+        let key = self.parser.parse_string_raw(&mut self.buf)?;
+        if !self.sets[depth].insert(self.json, &key) {
+            let key = FastStr::new(String::from_utf8_lossy(&key));
+            let pointer = self.pointer(key);
+            return Err(self.parser.error(ErrorCode::DuplicateKey(pointer)));
+        }
+        self.parser.parse_object_clo()
+    }
+
+    fn open_object(&mut self) -> Result<()> {
+        self.stack.push(Frame::Object);
+        let depth = self.stack.len() - 1;
+        // the array frames have no keys, but keep a set for them so that the sets are
+        // indexed by the depth
+        if self.sets.len() <= depth {
+            self.sets.resize_with(depth + 1, KeySet::default);
+        }
+        self.sets[depth].clear();
+        self.check_key()
+    }
+
+    fn check(&mut self) -> Result<()> {
+        'value: loop {
+            match self.parser.skip_space() {
+                Some(b'{') => match self.parser.skip_space() {
+                    Some(b'"') => {
+                        self.open_object()?;
+                        continue 'value;
+                    }
+                    Some(b'}') => {}
+                    None => return Err(self.parser.error(ErrorCode::EofWhileParsing)),
+                    Some(_) => return Err(self.parser.error(ErrorCode::ExpectObjectKeyOrEnd)),
+                },
+                Some(b'[') => match self.parser.skip_space_peek() {
+                    Some(b']') => self.parser.read.eat(1),
+                    Some(_) => {
+                        self.stack.push(Frame::Array(0));
+                        continue 'value;
+                    }
+                    None => return Err(self.parser.error(ErrorCode::EofWhileParsing)),
+                },
+                Some(b'"') => {
+                    self.parser.skip_string()?;
+                }
+                Some(c @ (b'-' | b'0'..=b'9')) => self.parser.skip_number(c)?,
+                Some(b't') => self.parser.parse_literal("rue")?,
+                Some(b'f') => self.parser.parse_literal("alse")?,
+                Some(b'n') => self.parser.parse_literal("ull")?,
+                Some(_) => return Err(self.parser.error(ErrorCode::InvalidJsonValue)),
+                None => return Err(self.parser.error(ErrorCode::EofWhileParsing)),
+            }
+
+            // close the containers after the value
+            loop {
+                let p = &mut self.parser;
+                match self.stack.last_mut() {
+                    None => return p.parse_trailing(),
+                    Some(Frame::Object) => match p.skip_space() {
+                        Some(b',') => match p.skip_space() {
+                            Some(b'"') => {
+                                self.check_key()?;
+                                continue 'value;
+                            }
+                            _ => return Err(p.error(ErrorCode::ExpectObjectKeyOrEnd)),
+                        },
+                        Some(b'}') => {
+                            self.stack.pop();
+                        }
+                        None => return Err(p.error(ErrorCode::EofWhileParsing)),
+                        Some(_) => return Err(p.error(ErrorCode::ExpectedObjectCommaOrEnd)),
+                    },
+                    Some(Frame::Array(i)) => match p.skip_space() {
+                        Some(b',') => {
+                            *i += 1;
+                            continue 'value;
+                        }
+                        Some(b']') => {
+                            self.stack.pop();
+                        }
+                        None => return Err(p.error(ErrorCode::EofWhileParsing)),
+                        Some(_) => return Err(p.error(ErrorCode::ExpectedArrayCommaOrEnd)),
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Validate the JSON text, and check that the keys in each object are unique, without building
+/// a DOM.
+///
+/// The keys are compared after unescaping, so `"a"` and `"\u0061"` are the same key. Only the
+/// hashes and the positions of the keys in the open objects are kept, the memory is bounded by
+/// the keys of the objects being validated, not by the whole document.
+///
+/// # Errors
+///
+/// Return the syntax error if the JSON is invalid. If a key is duplicated, return an error whose
+/// [`duplicate_key`][crate::Error::duplicate_key] is the pointer of the duplicated key.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{pointer, validate_unique_keys};
+///
+/// assert!(validate_unique_keys(r#"{"a": [{"b": 1}, {"b": 2}]}"#).is_ok());
+///
+/// let err = validate_unique_keys(r#"{"a": [{"b": 1}, {"b": 2, "b": 3}]}"#).unwrap_err();
+/// assert_eq!(err.duplicate_key(), Some(&pointer!["a", 1, "b"][..]));
+/// assert!(err.to_string().starts_with("Duplicate key at /a/1/b"));
+/// ```
+pub fn validate_unique_keys<'de, Input: JsonInput<'de>>(json: Input) -> Result<()> {
+    let slice = json.to_u8_slice();
+    let mut checker = Checker {
+        json: slice,
+        parser: Parser::new(Read::new(slice, false)),
+        stack: Vec::new(),
+        sets: Vec::new(),
+        buf: ScratchBuf::new(),
+    };
+    checker.check()?;
+
+    if json.need_utf8_valid() {
+        from_utf8(slice)?;
+    }
+    Ok(())
+}
+
+// Split the items of a top-level array by scanning the quotes and brackets only, so that a
+// malformed item does not hide the boundaries of the others. Return None if `json` is not a
+// single bracketed array.
+fn split_array(json: &[u8]) -> Option<Vec<(usize, usize)>> {
+    let open = json.iter().position(|c| !c.is_ascii_whitespace())?;
+    if json[open] != b'[' {
+        return None;
+    }
+
+    let mut items = Vec::new();
+    let mut start = open + 1;
+    let mut depth = 0usize;
+    let mut in_str = false;
+    let mut escaped = false;
+    for (i, &c) in json.iter().enumerate().skip(open + 1) {
+        if in_str {
+            match c {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_str = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            b'"' => in_str = true,
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' if depth > 0 => depth -= 1,
+            b',' if depth == 0 => {
+                items.push((start, i));
+                start = i + 1;
+            }
+            b']' => {
+                if !json[i + 1..].iter().all(|c| c.is_ascii_whitespace()) {
+                    return None;
+                }
+                // `[]` and `[ ]` have no items, but `[1, ]` has an empty item at the end
+                if !items.is_empty() || json[start..i].iter().any(|c| !c.is_ascii_whitespace()) {
+                    items.push((start, i));
+                }
+                return Some(items);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Split the non-blank lines of NDJSON.
+fn split_lines(json: &[u8]) -> Vec<(usize, usize)> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    for line in json.split(|&c| c == b'\n') {
+        let end = start + line.len();
+        if line.iter().any(|c| !c.is_ascii_whitespace()) {
+            items.push((start, end));
+        }
+        start = end + 1;
+    }
+    items
+}
+
+fn split_items(json: &[u8]) -> Vec<(usize, usize)> {
+    split_array(json).unwrap_or_else(|| split_lines(json))
+}
+
+// Validate one item, and report the error at its position in the whole `json`.
+fn validate_item(json: &[u8], (start, end): (usize, usize), utf8: bool) -> Result<()> {
+    let item = &json[start..end];
+    let check = || -> Result<()> {
+        let mut parser = Parser::new(Read::new(item, false));
+        parser.skip_one()?;
+        parser.parse_trailing()?;
+        if utf8 {
+            from_utf8(item)?;
+        }
+        Ok(())
+    };
+    check().map_err(|e| {
+        let index = start + e.offset();
+        Error::syntax(e.error_code(), json, index)
+    })
+}
+
+/// Validate many JSON documents at once, and return the result of each document.
+///
+/// The input is either a top-level JSON array, whose elements are the documents, or NDJSON with
+/// one document per line. It is a JSON array if it starts with `[` and its brackets match up to
+/// the end of the input, so a single-line NDJSON of an array is validated as the elements of that
+/// array. Blank lines in NDJSON are skipped.
+///
+/// The items are split by the quotes and brackets before validating them, so that one malformed
+/// item does not reject the others. The [`offset`][crate::Error::offset], line and column of each
+/// error are positions in the whole input.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::validate_each;
+///
+/// let ret = validate_each("{\"a\": 1}\n{\"a\": }\n[true]\n");
+/// assert_eq!(ret.len(), 3);
+/// assert!(ret[0].is_ok() && ret[2].is_ok());
+/// assert_eq!(ret[1].as_ref().unwrap_err().line(), 2);
+///
+/// let ret = validate_each(r#"[{"a": 1}, {"a": tru}, 3]"#);
+/// assert_eq!(ret.len(), 3);
+/// assert!(ret[0].is_ok() && ret[1].is_err() && ret[2].is_ok());
+/// ```
+pub fn validate_each<'de, Input: JsonInput<'de>>(json: Input) -> Vec<Result<()>> {
+    let utf8 = json.need_utf8_valid();
+    let slice = json.to_u8_slice();
+    split_items(slice)
+        .into_iter()
+        .map(|item| validate_item(slice, item, utf8))
+        .collect()
+}
+
+/// The same as [`validate_each`], but validate the documents with `threads` threads.
+///
+/// The documents are split into contiguous chunks, one for each thread, and the results are in the
+/// order of the documents. It runs on the current thread if `threads` is less than 2.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::validate_each_parallel;
+///
+/// let json: String = (0..100).map(|i| format!("{{\"id\": {i}}}\n")).collect();
+/// let ret = validate_each_parallel(&json, 4);
+/// assert_eq!(ret.len(), 100);
+/// assert!(ret.iter().all(|r| r.is_ok()));
+/// ```
+pub fn validate_each_parallel<'de, Input: JsonInput<'de>>(
+    json: Input,
+    threads: usize,
+) -> Vec<Result<()>> {
+    let utf8 = json.need_utf8_valid();
+    let slice = json.to_u8_slice();
+    let items = split_items(slice);
+    if threads < 2 || items.len() < 2 {
+        return items
+            .into_iter()
+            .map(|item| validate_item(slice, item, utf8))
+            .collect();
+    }
+
+    let chunk = items.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk)
+            .map(|items| {
+                scope.spawn(move || {
+                    items
+                        .iter()
+                        .map(|&item| validate_item(slice, item, utf8))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("the validating thread panicked"))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pointer;
+
+    #[test]
+    fn test_validate_unique_keys() {
+        let valids = [
+            r#"{}"#,
+            r#"[]"#,
+            r#"1"#,
+            r#" {"a": {"a": {"a": []}}, "b": [{"a": 1}, {"a": 2}], "c": {}} "#,
+            r#"[{"a": 1, "b": 2}, {"b": 1, "a": 2}, [], [[{"a": null}]]]"#,
+            r#"{"a": 1, "A": 2, "a\n": 3, "é": 4, "é": 5}"#,
+        ];
+        for json in valids {
+            assert!(validate_unique_keys(json).is_ok(), "{json}");
+        }
+
+        let dups = [
+            (r#"{"a": 1, "a": 2}"#, pointer!["a"].to_vec()),
+            (r#"{"a": 1, "\u0061": 2}"#, pointer!["a"].to_vec()),
+            (r#"{"\n": 1, "\u000a": 2}"#, pointer!["\n"].to_vec()),
+            (
+                r#"[0, {"x": [1, {"b": {}, "b": 1}]}]"#,
+                pointer![1, "x", 1, "b"].to_vec(),
+            ),
+            (
+                r#"{"a": {"c": 1}, "b": {"c": 1, "d": 2, "c": 3}}"#,
+                pointer!["b", "c"].to_vec(),
+            ),
+            (r#"[[{"a": 1, "a": 2}]]"#, pointer![0, 0, "a"].to_vec()),
+        ];
+        for (json, pointer) in dups {
+            let err = validate_unique_keys(json).unwrap_err();
+            assert_eq!(err.duplicate_key(), Some(&pointer[..]), "{json}");
+            assert!(err.is_syntax());
+        }
+
+        // the spilled key sets
+        let keys: Vec<String> = (0..100).map(|i| format!("\"k{i}\": {i}")).collect();
+        let json = format!("[{{{}}}, {{{}}}]", keys.join(","), keys[..50].join(","));
+        assert!(validate_unique_keys(&json).is_ok());
+        let json = format!("{{\"x\": {{{}, \"k99\": 0}}}}", keys.join(","));
+        let err = validate_unique_keys(&json).unwrap_err();
+        assert_eq!(err.duplicate_key(), Some(&pointer!["x", "k99"][..]));
+
+        let invalids = [
+            "",
+            "{",
+            r#"{"a"}"#,
+            r#"{"a": 1,}"#,
+            "[1,]",
+            "[1 2]",
+            "{} x",
+            r#"{"a": tru}"#,
+        ];
+        for json in invalids {
+            let err = validate_unique_keys(json).unwrap_err();
+            assert_eq!(err.duplicate_key(), None, "{json}");
+        }
+        assert!(validate_unique_keys(&b"{\"\xff\": 1}"[..]).is_err());
+    }
+
+    #[test]
+    fn test_validate_each() {
+        let oks = |ret: &[Result<()>]| ret.iter().map(|r| r.is_ok()).collect::<Vec<_>>();
+
+        assert!(validate_each("[]").is_empty());
+        assert!(validate_each(" [ ] ").is_empty());
+        assert!(validate_each("").is_empty());
+        assert!(validate_each("\n \r\n").is_empty());
+
+        // the brackets and commas in strings do not split the items
+        let json = r#"[{"a": "],["}, "\"]", [1, [2]], nul, 1,]"#;
+        let ret = validate_each(json);
+        assert_eq!(oks(&ret), [true, true, true, false, true, false]);
+        let offset = ret[3].as_ref().unwrap_err().offset();
+        let nul = json.find("nul").unwrap();
+        assert!((nul..=nul + 3).contains(&offset), "{offset}");
+
+        // the items are checked separately
+        let ret = validate_each("{\"a\": 1}\n\n{\"a\": 1\n[1] [2]\r\n\"\\x\"\n  null  ");
+        assert_eq!(oks(&ret), [true, false, false, false, true]);
+        assert!(ret[1].as_ref().unwrap_err().is_eof());
+        assert_eq!(ret[2].as_ref().unwrap_err().line(), 4);
+        assert_eq!(ret[3].as_ref().unwrap_err().line(), 5);
+
+        // not a single array, so validated as NDJSON
+        let ret = validate_each("[1]\n[2, x]\n");
+        assert_eq!(oks(&ret), [true, false]);
+        let ret = validate_each("[1, 2");
+        assert_eq!(oks(&ret), [false]);
+
+        let ret = validate_each(&b"[\"\xff\", \"a\"]"[..]);
+        assert_eq!(oks(&ret), [false, true]);
+
+        let json: String = (0..33)
+            .map(|i| {
+                if i % 5 == 0 {
+                    "{]\n".to_string()
+                } else {
+                    format!("{i}\n")
+                }
+            })
+            .collect();
+        let serial = validate_each(&json);
+        for threads in [0, 1, 2, 7, 64] {
+            let parallel = validate_each_parallel(&json, threads);
+            assert_eq!(oks(&parallel), oks(&serial));
+            for (a, b) in serial.iter().zip(&parallel) {
+                if let (Err(a), Err(b)) = (a, b) {
+                    assert_eq!(a.offset(), b.offset());
+                }
+            }
+        }
+    }
+}
@@ -9,12 +9,9 @@ use std::{
 use ref_cast::RefCast;
 
 use super::node::ValueMut;
-use crate::{
-    serde::tri,
-    value::{
-        node::{Value, ValueRefInner},
-        value_trait::JsonValueTrait,
-    },
+use crate::value::{
+    node::{Value, ValueRefInner},
+    value_trait::JsonValueTrait,
 };
 
 /// Array represents a JSON array. Its APIs are likes `Array<Value>`.
@@ -778,12 +775,7 @@ impl serde::ser::Serialize for Array {
     where
         S: serde::ser::Serializer,
     {
-        use serde::ser::SerializeSeq;
-        let mut seq = tri!(serializer.serialize_seq(Some(self.len())));
-        for v in self {
-            tri!(seq.serialize_element(v));
-        }
-        seq.end()
+        serde::ser::Serialize::serialize(&self.0, serializer)
     }
 }
 
@@ -0,0 +1,287 @@
+//! The builders to construct the nested [`Value`] documents by method chaining.
+
+use super::{
+    array::Array,
+    node::Value,
+    object::{Object, Pair},
+};
+
+/// The entry of the builders, which creates an [`ObjectBuilder`] or an [`ArrayBuilder`] with the
+/// capacity hint.
+///
+/// The builders allocate the arrays and objects with the given capacities once, and move the
+/// values into them directly, so the nested documents are built without the repeated
+/// reallocations of [`json!`][crate::json] or [`Object::insert`].
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{json, ValueBuilder};
+///
+/// let user = ValueBuilder::object(3)
+///     .insert("id", 1)
+///     .insert("name", "a\"b")
+///     .insert_array("tags", 2, |tags| tags.push("x").push(true))
+///     .build();
+/// assert_eq!(user, json!({"id": 1, "name": "a\"b", "tags": ["x", true]}));
+///
+/// let list = ValueBuilder::array(2)
+///     .push_object(1, |obj| obj.insert("k", ()))
+///     .push(user)
+///     .build();
+/// assert_eq!(list[0], json!({"k": null}));
+/// assert_eq!(list[1]["tags"][1], true);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ValueBuilder;
+
+impl ValueBuilder {
+    /// Build an object with the capacity of `capacity` keys.
+    pub fn object(capacity: usize) -> ObjectBuilder {
+        ObjectBuilder::with_capacity(capacity)
+    }
+
+    /// Build an array with the capacity of `capacity` elements.
+    pub fn array(capacity: usize) -> ArrayBuilder {
+        ArrayBuilder::with_capacity(capacity)
+    }
+}
+
+/// A builder of the JSON object, see [`ValueBuilder`].
+///
+/// The key inserted later replaces the value of the same key, as [`Object::insert`].
+#[derive(Debug, Clone)]
+pub struct ObjectBuilder {
+    value: Value,
+}
+
+impl Default for ObjectBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ObjectBuilder {
+    /// Create a builder of an empty object, which does not allocate until the first insertion.
+    pub fn new() -> Self {
+        Self {
+            value: Value::new_object(),
+        }
+    }
+
+    /// Create a builder of an object with the capacity of `capacity` keys.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            value: Value::new_object_with(capacity),
+        }
+    }
+
+    /// Reserve the capacity for at least `additional` more keys.
+    pub fn reserve(mut self, additional: usize) -> Self {
+        self.value.reserve::<Pair>(additional);
+        self
+    }
+
+    /// Insert the `value` of the `key`.
+    pub fn insert<V: Into<Value>>(mut self, key: &str, value: V) -> Self {
+        self.value.insert(key, value.into());
+        self
+    }
+
+    /// Insert a nested object of the `key`, which has the capacity of `capacity` keys and is built
+    /// by `build`.
+    pub fn insert_object<F>(self, key: &str, capacity: usize, build: F) -> Self
+    where
+        F: FnOnce(ObjectBuilder) -> ObjectBuilder,
+    {
+        self.insert(key, build(ObjectBuilder::with_capacity(capacity)))
+    }
+
+    /// Insert a nested array of the `key`, which has the capacity of `capacity` elements and is
+    /// built by `build`.
+    pub fn insert_array<F>(self, key: &str, capacity: usize, build: F) -> Self
+    where
+        F: FnOnce(ArrayBuilder) -> ArrayBuilder,
+    {
+        self.insert(key, build(ArrayBuilder::with_capacity(capacity)))
+    }
+
+    /// Return the number of the keys.
+    pub fn len(&self) -> usize {
+        self.value.as_obj_len()
+    }
+
+    /// Whether the object is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the object as a [`Value`].
+    pub fn build(self) -> Value {
+        self.value
+    }
+
+    /// Return the object.
+    pub fn build_object(self) -> Object {
+        Object(self.value)
+    }
+}
+
+impl From<ObjectBuilder> for Value {
+    fn from(builder: ObjectBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// A builder of the JSON array, see [`ValueBuilder`].
+#[derive(Debug, Clone)]
+pub struct ArrayBuilder {
+    value: Value,
+}
+
+impl Default for ArrayBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArrayBuilder {
+    /// Create a builder of an empty array, which does not allocate until the first push.
+    pub fn new() -> Self {
+        Self {
+            value: Value::new_array(),
+        }
+    }
+
+    /// Create a builder of an array with the capacity of `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            value: Value::new_array_with(capacity),
+        }
+    }
+
+    /// Reserve the capacity for at least `additional` more elements.
+    pub fn reserve(mut self, additional: usize) -> Self {
+        self.value.reserve::<Value>(additional);
+        self
+    }
+
+    /// Append the `value`.
+    pub fn push<V: Into<Value>>(mut self, value: V) -> Self {
+        self.value.append_value(value.into());
+        self
+    }
+
+    /// Append all the values of `iter`, reserving the capacity by its size hint.
+    #[allow(clippy::should_implement_trait)]
+    pub fn extend<I>(mut self, iter: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Value>,
+    {
+        let iter = iter.into_iter();
+        self.value.reserve::<Value>(iter.size_hint().0);
+        for value in iter {
+            self.value.append_value(value.into());
+        }
+        self
+    }
+
+    /// Append a nested object, which has the capacity of `capacity` keys and is built by `build`.
+    pub fn push_object<F>(self, capacity: usize, build: F) -> Self
+    where
+        F: FnOnce(ObjectBuilder) -> ObjectBuilder,
+    {
+        self.push(build(ObjectBuilder::with_capacity(capacity)))
+    }
+
+    /// Append a nested array, which has the capacity of `capacity` elements and is built by
+    /// `build`.
+    pub fn push_array<F>(self, capacity: usize, build: F) -> Self
+    where
+        F: FnOnce(ArrayBuilder) -> ArrayBuilder,
+    {
+        self.push(build(ArrayBuilder::with_capacity(capacity)))
+    }
+
+    /// Return the number of the elements.
+    pub fn len(&self) -> usize {
+        self.value.len()
+    }
+
+    /// Whether the array is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the array as a [`Value`].
+    pub fn build(self) -> Value {
+        self.value
+    }
+
+    /// Return the array.
+    pub fn build_array(self) -> Array {
+        Array(self.value)
+    }
+}
+
+impl From<ArrayBuilder> for Value {
+    fn from(builder: ArrayBuilder) -> Self {
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{json, JsonValueTrait};
+
+    #[test]
+    fn test_value_builder() {
+        let value = ValueBuilder::object(4)
+            .insert("a", 1)
+            .insert("b", "x")
+            .insert_object("c", 2, |c| {
+                c.insert("d", json!([1, null]))
+                    .insert_array("e", 0, |e| e.extend(["p", "q"]))
+            })
+            .insert_array("f", 3, |f| {
+                f.push_array(1, |g| g.push(false))
+                    .push_object(0, |h| h)
+                    .push(ArrayBuilder::new())
+            })
+            .insert("a", 2)
+            .build();
+        assert_eq!(
+            value,
+            json!({
+                "a": 2,
+                "b": "x",
+                "c": {"d": [1, null], "e": ["p", "q"]},
+                "f": [[false], {}, []]
+            })
+        );
+        assert_eq!(value.as_object().unwrap().len(), 4);
+
+        // the capacity hints
+        let obj = ObjectBuilder::with_capacity(16).insert("k", true);
+        assert_eq!(obj.len(), 1);
+        #[cfg(not(feature = "sort_keys"))]
+        assert!(obj.build_object().capacity() >= 16);
+        let arr = ArrayBuilder::new().reserve(8).extend(0..3);
+        assert_eq!(arr.len(), 3);
+        let arr = arr.build_array();
+        assert!(arr.capacity() >= 8);
+        assert_eq!(arr, json!([0, 1, 2]).into_array().unwrap());
+
+        // the empty builders
+        assert!(ObjectBuilder::default().is_empty());
+        assert_eq!(ArrayBuilder::default().build(), json!([]));
+        assert_eq!(ObjectBuilder::new().reserve(2).build(), json!({}));
+        assert!(ValueBuilder::array(0)
+            .build()
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+use std::hash::Hasher;
+
+use crate::{
+    error::Result, serde::tri, util::string::QUOTE_TAB, value::node::Value, JsonNumberTrait,
+    ValueRef,
+};
+
+/// Feed the canonical form of the JSON text into `hasher`, without building the canonical text.
+///
+/// The JSON text is parsed into a [`Value`] first, and then hashed as
+/// [`canonical_hash_value`][`crate::canonical_hash_value`]. Return an error if the JSON text is
+/// invalid.
+///
+/// # Examples
+///
+/// ```
+/// use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+///
+/// use sonic_rs::canonical_hash;
+///
+/// let hash = |json: &str| {
+///     let mut hasher = DefaultHasher::new();
+///     canonical_hash(json, &mut hasher).unwrap();
+///     hasher.finish()
+/// };
+///
+/// assert_eq!(
+///     hash(r#"{"b": [1.0, "x"], "a": null}"#),
+///     hash(r#"{ "a":null,"b":[1,"x"] }"#)
+/// );
+/// assert_ne!(hash(r#"{"a": 1}"#), hash(r#"{"a": 2}"#));
+/// ```
+pub fn canonical_hash<J, H>(json: J, hasher: &mut H) -> Result<()>
+where
+    J: AsRef<[u8]>,
+    H: Hasher,
+{
+    let value: Value = tri!(crate::from_slice(json.as_ref()));
+    canonical_hash_value(&value, hasher);
+    Ok(())
+}
+
+/// Feed the canonical form of the `Value` into `hasher`, without building the canonical text.
+///
+/// The canonical form is the compact JSON text, with the following rules:
+///
+/// - The object keys are sorted by their UTF-8 bytes. The duplicate keys keep their order.
+/// - The strings are escaped as [`to_string`][`crate::to_string`].
+/// - The integers and the integral floats in the `i64` range are written as integers, so `1.0` and
+///   `1` are the same. `-0.0` is written as `0`.
+/// - The other floats are written in the shortest representation.
+///
+/// The canonical text is fed by multiple [`Hasher::write`] calls, so the hasher should not depend
+/// on how the bytes are split, if the result is compared with hashing the whole text.
+///
+/// # Examples
+///
+/// ```
+/// use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+///
+/// use sonic_rs::{canonical_hash_value, json};
+///
+/// let hash = |value: &sonic_rs::Value| {
+///     let mut hasher = DefaultHasher::new();
+///     canonical_hash_value(value, &mut hasher);
+///     hasher.finish()
+/// };
+///
+/// assert_eq!(
+///     hash(&json!({"a": 1, "b": {"c": 2.5, "d": -0.0}})),
+///     hash(&json!({"b": {"d": 0, "c": 2.5}, "a": 1.0}))
+/// );
+/// ```
+pub fn canonical_hash_value<H: Hasher>(value: &Value, hasher: &mut H) {
+    match value.as_ref() {
+        ValueRef::Null => hasher.write(b"null"),
+        ValueRef::Bool(true) => hasher.write(b"true"),
+        ValueRef::Bool(false) => hasher.write(b"false"),
+        ValueRef::Number(num) => {
+            if let Some(u) = num.as_u64() {
+                hasher.write(itoa::Buffer::new().format(u).as_bytes());
+            } else if let Some(i) = num.as_i64() {
+                hasher.write(itoa::Buffer::new().format(i).as_bytes());
+            } else if let Some(f) = num.as_f64() {
+                write_f64(f, hasher);
+            }
+        }
+        ValueRef::String(s) => write_str(s, hasher),
+        ValueRef::Array(arr) => {
+            hasher.write(b"[");
+            for (i, v) in arr.iter().enumerate() {
+                if i > 0 {
+                    hasher.write(b",");
+                }
+                canonical_hash_value(v, hasher);
+            }
+            hasher.write(b"]");
+        }
+        ValueRef::Object(obj) => {
+            let mut pairs: Vec<(&str, &Value)> = obj.iter().collect();
+            pairs.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+            hasher.write(b"{");
+            for (i, (k, v)) in pairs.into_iter().enumerate() {
+                if i > 0 {
+                    hasher.write(b",");
+                }
+                write_str(k, hasher);
+                hasher.write(b":");
+                canonical_hash_value(v, hasher);
+            }
+            hasher.write(b"}");
+        }
+    }
+}
+
+// The integral floats in this range are converted to `i64` exactly.
+const I64_RANGE: f64 = 9223372036854775808.0;
+
+fn write_f64<H: Hasher>(f: f64, hasher: &mut H) {
+    if f.fract() == 0.0 && f.abs() < I64_RANGE {
+        hasher.write(itoa::Buffer::new().format(f as i64).as_bytes());
+    } else {
+        hasher.write(ryu::Buffer::new().format_finite(f).as_bytes());
+    }
+}
+
+// Write the quoted and escaped string, the unescaped parts are written without copying.
+fn write_str<H: Hasher>(s: &str, hasher: &mut H) {
+    let bytes = s.as_bytes();
+    hasher.write(b"\"");
+    let mut start = 0;
+    for (i, &ch) in bytes.iter().enumerate() {
+        let (len, escaped) = &QUOTE_TAB[ch as usize];
+        if *len == 0 {
+            continue;
+        }
+        if start < i {
+            hasher.write(&bytes[start..i]);
+        }
+        hasher.write(&escaped[..*len as usize]);
+        start = i + 1;
+    }
+    if start < bytes.len() {
+        hasher.write(&bytes[start..]);
+    }
+    hasher.write(b"\"");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::json;
+
+    // collect the fed bytes, to check the canonical text
+    #[derive(Default)]
+    struct BytesHasher(Vec<u8>);
+
+    impl Hasher for BytesHasher {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    fn canonical(json: &str) -> String {
+        let mut hasher = BytesHasher::default();
+        canonical_hash(json, &mut hasher).unwrap();
+        String::from_utf8(hasher.0).unwrap()
+    }
+
+    #[test]
+    fn test_canonical_hash() {
+        assert_eq!(canonical("null"), "null");
+        assert_eq!(canonical(" [true, false] "), "[true,false]");
+        assert_eq!(
+            canonical("[0, -1, 18446744073709551615]"),
+            "[0,-1,18446744073709551615]"
+        );
+        assert_eq!(
+            canonical("[1.0, -0.0, 2.5, 1e3, 1e20, -1.5e-7]"),
+            "[1,0,2.5,1000,1e20,-1.5e-7]"
+        );
+        assert_eq!(
+            canonical(r#""a\"b\\c\u0001\né😀""#),
+            "\"a\\\"b\\\\c\\u0001\\né😀\""
+        );
+        assert_eq!(
+            canonical(r#"{"b": {"z": [], "y": {}}, "a": 1, "ab": 2, "": 3, "é": 4}"#),
+            r#"{"":3,"a":1,"ab":2,"b":{"y":{},"z":[]},"é":4}"#
+        );
+
+        // the canonical text is the same as the sorted compact serialization
+        let value = json!({"c": [1, {"e": "x\ny", "d": null}], "b": "s", "a": 1.5});
+        let mut hasher = BytesHasher::default();
+        canonical_hash_value(&value, &mut hasher);
+        assert_eq!(
+            hasher.0,
+            br#"{"a":1.5,"b":"s","c":[1,{"d":null,"e":"x\ny"}]}"#
+        );
+
+        assert!(canonical_hash("[1,", &mut BytesHasher::default()).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    parser::Parser, reader::Reader, util::utf8::from_utf8, value::Value, JsonInput, Read, Result,
+    parser::Parser, reader::Reader, util::utf8::from_utf8, value::Value, JsonInput, PointerNode,
+    Read, Result,
 };
 
 /// get_by_schema returns new Value from the given schema and json data
@@ -12,6 +13,15 @@ use crate::{
 /// If a key path does'not exists in the schema, but exists in the data, then the
 /// value in the schema will be replaced with the corresponding value in the data.
 ///
+/// The arrays in the data are described by:
+/// - A non-empty array in the schema. Each element is the schema of the data element at the same
+///   index. The data elements beyond the schema are kept as they are, and the schema elements
+///   beyond the data are kept as the defaults.
+/// - An object with the only key `"$items"` in the schema. The value is the schema of every element
+///   in the data.
+///
+/// Use [`get_by_schema_with_report`] to know which keys are filled with the defaults.
+///
 /// # Examples
 /// ```
 /// use sonic_rs::json;
@@ -22,6 +32,7 @@ use crate::{
 ///         "b2": "default string" // default value is string
 ///     },
 ///     "c": [], // default value is []
+///     "e": {"$items": {"id": 0, "tag": "none"}}, // schema of every element
 /// });
 ///
 /// let data = r#"
@@ -31,7 +42,8 @@ use crate::{
 ///         "b1": 123
 ///     },
 ///     "c": [1, 2, 3],
-///     "d": "balabala..."
+///     "d": "balabala...",
+///     "e": [{"id": 1}, {"id": 2, "tag": "x", "extra": true}]
 /// }"#;
 ///
 /// // parse json data by schem, we can parse into the schema value inplace
@@ -44,28 +56,96 @@ use crate::{
 ///             "b1": 123,
 ///             "b2": "default string"
 ///         },
-///         "c": [1, 2, 3]
+///         "c": [1, 2, 3],
+///         "e": [{"id": 1, "tag": "none"}, {"id": 2, "tag": "x"}]
+///     })
+/// );
+/// ```
+pub fn get_by_schema<'de, Input: JsonInput<'de>>(json: Input, schema: Value) -> Result<Value> {
+    get_by_schema_with_report(json, schema).map(|(value, _)| value)
+}
+
+/// The report of [`get_by_schema_with_report`], which records the paths in the schema that are
+/// not found in the JSON data.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SchemaReport {
+    missing: Vec<Vec<PointerNode>>,
+}
+
+impl SchemaReport {
+    /// The paths of the schema keys and indexes that are not found in the JSON data, which are
+    /// filled with the defaults in the schema. The paths are sorted.
+    ///
+    /// Only the topmost missing path is reported, the paths nested in a missing value are not
+    /// reported. If a value in the data is not an object or array as the schema expects, it
+    /// replaces the schema value and its nested paths are not reported either.
+    pub fn missing(&self) -> &[Vec<PointerNode>] {
+        &self.missing
+    }
+
+    /// Whether all the paths in the schema are found in the JSON data.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Same as [`get_by_schema`], and also returns a [`SchemaReport`] of the schema paths that are not
+/// found in the JSON data.
+///
+/// # Examples
+/// ```
+/// use sonic_rs::{get_by_schema_with_report, json, pointer};
+///
+/// let schema = json!({
+///     "name": "unknown",
+///     "servers": {"$items": {"host": "localhost", "port": 80}},
+///     "limits": [10, 20],
+/// });
+///
+/// let data = r#"{"servers": [{"host": "a"}, {"host": "b", "port": 8080}], "limits": [1]}"#;
+///
+/// let (got, report) = get_by_schema_with_report(data, schema).unwrap();
+/// assert_eq!(
+///     got,
+///     json!({
+///         "name": "unknown",
+///         "servers": [{"host": "a", "port": 80}, {"host": "b", "port": 8080}],
+///         "limits": [1, 20],
 ///     })
 /// );
+/// assert!(!report.is_complete());
+/// assert_eq!(
+///     report.missing(),
+///     &[
+///         pointer!["limits", 1].to_vec(),
+///         pointer!["name"].to_vec(),
+///         pointer!["servers", 0, "port"].to_vec(),
+///     ]
+/// );
 /// ```
-pub fn get_by_schema<'de, Input: JsonInput<'de>>(json: Input, mut schema: Value) -> Result<Value> {
+pub fn get_by_schema_with_report<'de, Input: JsonInput<'de>>(
+    json: Input,
+    mut schema: Value,
+) -> Result<(Value, SchemaReport)> {
     let slice = json.to_u8_slice();
     let reader = Read::new(slice, false);
     let mut parser = Parser::new(reader);
-    parser.get_by_schema(&mut schema)?;
+    let mut missing = Vec::new();
+    parser.get_by_schema(&mut schema, &mut missing)?;
 
     // validate the utf-8 if slice
     let index = parser.read.index();
     if json.need_utf8_valid() {
         from_utf8(&slice[..index])?;
     }
-    Ok(schema)
+    missing.sort();
+    Ok((schema, SchemaReport { missing }))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::from_str;
+    use crate::{from_str, pointer};
 
     fn test_success(schema: &str, json: &str, expected: &str) {
         let schema = from_str(schema).unwrap();
@@ -266,4 +346,98 @@ mod test {
         let (schema, json) = (r#"{"a": 1}"#, "{123}");
         test_failed(schema, json);
     }
+
+    #[test]
+    fn test_failed_9() {
+        let (schema, json) = (r#"{"a": [1]}"#, r#"{"a": [1 2]}"#);
+        test_failed(schema, json);
+        let (schema, json) = (r#"{"a": [1, 2]}"#, r#"{"a": [1,"#);
+        test_failed(schema, json);
+    }
+
+    #[test]
+    fn test_success_array() {
+        // every element
+        let (schema, json, expected) = (
+            r#"{"a": {"$items": {"b": 1, "c": [0]}}}"#,
+            r#"{"a": [{"b": 2, "d": 3}, {"c": [true, "x"]}, {}], "e": 1}"#,
+            r#"{"a": [{"b": 2, "c": [0]}, {"b": 1, "c": [true, "x"]}, {"b": 1, "c": [0]}]}"#,
+        );
+        test_success(schema, json, expected);
+        let (schema, json, expected) = (
+            r#"{"a": {"$items": 1}, "b": {"$items": 1, "c": 2}}"#,
+            r#"{"a": [], "b": [3]}"#,
+            r#"{"a": [], "b": [3]}"#,
+        );
+        test_success(schema, json, expected);
+        let (schema, json, expected) = (
+            r#"{"a": {"$items": {"b": 1}}}"#,
+            r#"{"a": {"$items": {"c": 2}}}"#,
+            r#"{"a": {"$items": {"b": 1}}}"#,
+        );
+        test_success(schema, json, expected);
+
+        // a single-element array is the schema of the first element only
+        let (schema, json, expected) = (
+            r#"{"a": [{"b": 1}]}"#,
+            r#"{"a": [{"c": 2}, {"d": 3}]}"#,
+            r#"{"a": [{"b": 1}, {"d": 3}]}"#,
+        );
+        test_success(schema, json, expected);
+
+        // per-index
+        let (schema, json, expected) = (
+            r#"{"a": [{"b": 1}, 2, {"c": 3}]}"#,
+            r#"{"a": [{"b": 0, "x": 0}, "y"]}"#,
+            r#"{"a": [{"b": 0}, "y", {"c": 3}]}"#,
+        );
+        test_success(schema, json, expected);
+        let (schema, json, expected) = (
+            r#"{"a": [{"b": 1}, 2]}"#,
+            r#"{"a": [{}, 3, {"z": 4}, 5]}"#,
+            r#"{"a": [{"b": 1}, 3, {"z": 4}, 5]}"#,
+        );
+        test_success(schema, json, expected);
+
+        // the empty data array
+        let (schema, json, expected) = (
+            r#"{"a": {"$items": {"b": 1}}, "c": [1, 2]}"#,
+            r#"{"a": [ ], "c": []}"#,
+            r#"{"a": [], "c": [1, 2]}"#,
+        );
+        test_success(schema, json, expected);
+    }
+
+    #[test]
+    fn test_schema_report() {
+        let report = |schema: &str, json: &str| {
+            let schema = from_str(schema).unwrap();
+            get_by_schema_with_report(json, schema).unwrap().1
+        };
+
+        let got = report(r#"{"a": 1, "b": {"c": 2}}"#, r#"{"a": 0, "b": {"c": 0}}"#);
+        assert!(got.is_complete());
+        assert!(got.missing().is_empty());
+
+        let got = report(
+            r#"{"z": 1, "b": {"c": 2, "d": {"e": 3}}, "a": {"$items": {"x": 1}}, "i": [1, {"y": 2}, 3]}"#,
+            r#"{"b": {}, "a": [{"x": 0}, {}, {"x": 0}], "i": [0, {}]}"#,
+        );
+        assert!(!got.is_complete());
+        assert_eq!(
+            got.missing(),
+            &[
+                pointer!["a", 1, "x"].to_vec(),
+                pointer!["b", "c"].to_vec(),
+                pointer!["b", "d"].to_vec(),
+                pointer!["i", 1, "y"].to_vec(),
+                pointer!["i", 2].to_vec(),
+                pointer!["z"].to_vec(),
+            ]
+        );
+
+        // the mismatched types are replaced without reporting
+        let got = report(r#"{"a": {"b": 1}, "c": [1, 2]}"#, r#"{"a": 1, "c": {}}"#);
+        assert!(got.is_complete());
+    }
 }
@@ -0,0 +1,495 @@
+use std::{fmt, ops::Deref, sync::OnceLock};
+
+use super::node::Value;
+
+/// A JSON document embedded by `include_json!`, which has been validated when compiling.
+///
+/// The text is embedded as is. The [`Value`] is only constructed at compile time for the scalars
+/// without escapes or floats, and the empty containers. The other documents, including all the
+/// non-empty arrays and objects, are parsed when the value is first used, and the parsed value is
+/// shared by the later uses.
+///
+/// `IncludedJson` dereferences to the [`Value`].
+///
+/// # Examples
+///
+/// ```ignore
+/// use sonic_rs::{include_json, IncludedJson, JsonValueTrait};
+///
+/// static CONFIG: &IncludedJson = include_json!("config/default.json");
+///
+/// // the config is parsed at the first use
+/// let port = CONFIG.get("port").as_u64();
+/// // the original text
+/// let text: &'static str = CONFIG.text();
+/// ```
+pub struct IncludedJson {
+    text: &'static str,
+    // the value constructed at compile time
+    value: Option<Value>,
+    parsed: OnceLock<Value>,
+}
+
+impl IncludedJson {
+    #[doc(hidden)]
+    pub const fn new(text: &'static str, value: Option<Value>) -> Self {
+        Self {
+            text,
+            value,
+            parsed: OnceLock::new(),
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn is_valid(text: &str) -> bool {
+        check_json(text).is_ok()
+    }
+
+    /// Return the embedded JSON text.
+    pub fn text(&self) -> &'static str {
+        self.text
+    }
+
+    /// Return the value of the JSON, which is parsed at the first call if it is not constructed
+    /// at compile time.
+    pub fn value(&self) -> &Value {
+        match &self.value {
+            Some(value) => value,
+            None => self.parsed.get_or_init(|| {
+                crate::from_str(self.text).expect("the included JSON is validated when compiling")
+            }),
+        }
+    }
+}
+
+impl Deref for IncludedJson {
+    type Target = Value;
+
+    fn deref(&self) -> &Value {
+        self.value()
+    }
+}
+
+impl fmt::Debug for IncludedJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IncludedJson").field(&self.text).finish()
+    }
+}
+
+// The max nesting depth checked at compile time.
+const MAX_DEPTH: usize = 64 * 1024;
+
+// The least decimal which is rounded to the infinite `f64`, which is `2^1024 - 2^970`.
+const F64_OVERFLOW: &[u8] =
+    b"17976931348623158079372897140530341507993413271003782693617377898044496829276475\
+    09466490179775872070963302864166928879109465555478519404026306574886715058206819\
+    08902000708383676273854845817711531764475730270069855571366959622842914819860834\
+    936475292719074168444365510704342711559699508093042880177904174497792";
+
+// Check the JSON text at compile time, with the same grammar as the parser in the default options,
+// so that `include_json!` doesn't depend on another JSON parser. Return the index of the first
+// invalid byte.
+const fn check_json(text: &str) -> Result<(), usize> {
+    let b = text.as_bytes();
+    // the open containers as bits, `1` for the objects
+    let mut stack = [0u64; MAX_DEPTH / 64];
+    let mut depth = 0;
+    let mut i = 0;
+    if cfg!(feature = "skip_bom") && b.len() >= 3 && b[0] == 0xEF && b[1] == 0xBB && b[2] == 0xBF {
+        i = 3;
+    }
+
+    loop {
+        i = skip_space(b, i);
+        if i >= b.len() {
+            return Err(i);
+        }
+        let next = match b[i] {
+            c @ (b'{' | b'[') => {
+                let is_object = c == b'{';
+                let close = if is_object { b'}' } else { b']' };
+                i = skip_space(b, i + 1);
+                if i < b.len() && b[i] == close {
+                    Ok(i + 1)
+                } else if depth == MAX_DEPTH {
+                    return Err(i);
+                } else {
+                    let bit = 1u64 << (depth % 64);
+                    if is_object {
+                        stack[depth / 64] |= bit;
+                    } else {
+                        stack[depth / 64] &= !bit;
+                    }
+                    depth += 1;
+                    if is_object {
+                        i = match check_key(b, i) {
+                            Ok(next) => next,
+                            Err(e) => return Err(e),
+                        };
+                    }
+                    continue;
+                }
+            }
+            b'"' => check_string(b, i),
+            b'-' | b'0'..=b'9' => check_number(b, i),
+            b't' => check_literal(b, i, b"true"),
+            b'f' => check_literal(b, i, b"false"),
+            b'n' => check_literal(b, i, b"null"),
+            _ => Err(i),
+        };
+        i = match next {
+            Ok(next) => next,
+            Err(e) => return Err(e),
+        };
+
+        // close the containers after the value
+        loop {
+            i = skip_space(b, i);
+            if depth == 0 {
+                return if i == b.len() { Ok(()) } else { Err(i) };
+            }
+            let is_object = stack[(depth - 1) / 64] & (1u64 << ((depth - 1) % 64)) != 0;
+            if i >= b.len() {
+                return Err(i);
+            }
+            match b[i] {
+                b',' => {
+                    i += 1;
+                    if is_object {
+                        i = match check_key(b, skip_space(b, i)) {
+                            Ok(next) => next,
+                            Err(e) => return Err(e),
+                        };
+                    }
+                    break;
+                }
+                b'}' if is_object => depth -= 1,
+                b']' if !is_object => depth -= 1,
+                _ => return Err(i),
+            }
+            i += 1;
+        }
+    }
+}
+
+const fn skip_space(b: &[u8], mut i: usize) -> usize {
+    while i < b.len() && matches!(b[i], b' ' | b'\t' | b'\n' | b'\r') {
+        i += 1;
+    }
+    i
+}
+
+const fn check_literal(b: &[u8], i: usize, literal: &[u8]) -> Result<usize, usize> {
+    let mut k = 0;
+    while k < literal.len() {
+        if i + k >= b.len() || b[i + k] != literal[k] {
+            return Err(i + k);
+        }
+        k += 1;
+    }
+    Ok(i + k)
+}
+
+// Check the key at `i` and the colon after it, and return the index after the colon.
+const fn check_key(b: &[u8], i: usize) -> Result<usize, usize> {
+    if i >= b.len() || b[i] != b'"' {
+        return Err(i);
+    }
+    let i = match check_string(b, i) {
+        Ok(next) => skip_space(b, next),
+        Err(e) => return Err(e),
+    };
+    if i < b.len() && b[i] == b':' {
+        Ok(i + 1)
+    } else {
+        Err(i)
+    }
+}
+
+const fn hex4(b: &[u8], i: usize) -> Option<u32> {
+    if i + 4 > b.len() {
+        return None;
+    }
+    let mut v = 0;
+    let mut k = 0;
+    while k < 4 {
+        let d = match b[i + k] {
+            c @ b'0'..=b'9' => c - b'0',
+            c @ b'a'..=b'f' => c - b'a' + 10,
+            c @ b'A'..=b'F' => c - b'A' + 10,
+            _ => return None,
+        };
+        v = v * 16 + d as u32;
+        k += 1;
+    }
+    Some(v)
+}
+
+// Check the string starting with the quote at `i`, and return the index after the closing quote.
+// The lone surrogates are invalid.
+const fn check_string(b: &[u8], mut i: usize) -> Result<usize, usize> {
+    i += 1;
+    while i < b.len() {
+        match b[i] {
+            b'"' => return Ok(i + 1),
+            b'\\' if i + 1 < b.len() => match b[i + 1] {
+                b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => i += 2,
+                b'u' => {
+                    let high = match hex4(b, i + 2) {
+                        Some(cp) if cp < 0xDC00 || cp >= 0xE000 => cp,
+                        _ => return Err(i),
+                    };
+                    if high >= 0xD800 && high < 0xDC00 {
+                        let low = if i + 7 < b.len() && b[i + 6] == b'\\' && b[i + 7] == b'u' {
+                            hex4(b, i + 8)
+                        } else {
+                            None
+                        };
+                        match low {
+                            Some(cp) if cp >= 0xDC00 && cp < 0xE000 => i += 6,
+                            _ => return Err(i),
+                        }
+                    }
+                    i += 6;
+                }
+                _ => return Err(i),
+            },
+            b'\\' | 0..=0x1f => return Err(i),
+            _ => i += 1,
+        }
+    }
+    Err(i)
+}
+
+const fn skip_digits(b: &[u8], mut i: usize) -> usize {
+    while i < b.len() && b[i].is_ascii_digit() {
+        i += 1;
+    }
+    i
+}
+
+// Check the number at `i`, and return the index after it. The numbers are invalid if they are
+// rounded to the infinite `f64`, as the parser does.
+const fn check_number(b: &[u8], start: usize) -> Result<usize, usize> {
+    let mut i = start;
+    if b[i] == b'-' {
+        i += 1;
+    }
+    let int_start = i;
+    if i < b.len() && b[i] == b'0' {
+        i += 1;
+    } else if i < b.len() && b[i].is_ascii_digit() {
+        i = skip_digits(b, i);
+    } else {
+        return Err(i);
+    }
+    let int_end = i;
+
+    let mut frac_start = i;
+    if i < b.len() && b[i] == b'.' {
+        frac_start = i + 1;
+        i = skip_digits(b, frac_start);
+        if i == frac_start {
+            return Err(i);
+        }
+    }
+    let frac_end = i;
+
+    let mut exp: i64 = 0;
+    if i < b.len() && (b[i] == b'e' || b[i] == b'E') {
+        i += 1;
+        let negative = i < b.len() && b[i] == b'-';
+        if i < b.len() && (b[i] == b'+' || b[i] == b'-') {
+            i += 1;
+        }
+        let exp_start = i;
+        while i < b.len() && b[i].is_ascii_digit() {
+            // saturate the exponent, which is far beyond the range of `f64`
+            if exp < 1_000_000 {
+                exp = exp * 10 + (b[i] - b'0') as i64;
+            }
+            i += 1;
+        }
+        if i == exp_start {
+            return Err(i);
+        }
+        if negative {
+            exp = -exp;
+        }
+    }
+
+    let digits = Digits {
+        b,
+        int_start,
+        int_end,
+        frac_start,
+        frac_end,
+    };
+    if digits.overflows(exp) {
+        return Err(start);
+    }
+    Ok(i)
+}
+
+// The significant digits of a number, the integer digits and then the fraction digits.
+struct Digits<'a> {
+    b: &'a [u8],
+    int_start: usize,
+    int_end: usize,
+    frac_start: usize,
+    frac_end: usize,
+}
+
+impl Digits<'_> {
+    const fn len(&self) -> usize {
+        (self.int_end - self.int_start) + (self.frac_end - self.frac_start)
+    }
+
+    const fn at(&self, k: usize) -> u8 {
+        let int_len = self.int_end - self.int_start;
+        if k < int_len {
+            self.b[self.int_start + k]
+        } else {
+            self.b[self.frac_start + k - int_len]
+        }
+    }
+
+    // Whether `digits * 10^exp` is rounded to the infinite `f64`.
+    const fn overflows(&self, exp: i64) -> bool {
+        let len = self.len();
+        let mut first = 0;
+        while first < len && self.at(first) == b'0' {
+            first += 1;
+        }
+        if first == len {
+            return false;
+        }
+
+        // the decimal exponent of the first nonzero digit
+        let e = (self.int_end - self.int_start) as i64 - first as i64 - 1 + exp;
+        if e != (F64_OVERFLOW.len() - 1) as i64 {
+            return e > (F64_OVERFLOW.len() - 1) as i64;
+        }
+        let mut k = 0;
+        while k < F64_OVERFLOW.len() {
+            let d = if first + k < len {
+                self.at(first + k)
+            } else {
+                b'0'
+            };
+            if d != F64_OVERFLOW[k] {
+                return d > F64_OVERFLOW[k];
+            }
+            k += 1;
+        }
+        // the tie is rounded to the even, which is infinite
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::JsonValueTrait;
+
+    static SCALAR: IncludedJson =
+        IncludedJson::new("\"hello\"", Some(Value::new_static_str("hello")));
+    static DOC: IncludedJson = IncludedJson::new(r#"{"a": [1, 2.5]}"#, None);
+
+    #[test]
+    fn test_included_json() {
+        assert_eq!(SCALAR.text(), "\"hello\"");
+        assert_eq!(SCALAR.as_str(), Some("hello"));
+
+        assert_eq!(DOC.get("a").get(1).as_f64(), Some(2.5));
+        // parsed once
+        assert!(std::ptr::eq(DOC.value(), DOC.value()));
+
+        let consts = [
+            Value::new_null(),
+            Value::new_bool(true),
+            Value::new_u64(1),
+            Value::new_i64(-1),
+            Value::new_array(),
+            Value::new_object(),
+        ];
+        let texts = ["null", "true", "1", "-1", "[]", "{}"];
+        for (value, text) in consts.iter().zip(texts) {
+            assert_eq!(value, &crate::from_str::<Value>(text).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_check_json() {
+        let max = crate::parser::as_str(F64_OVERFLOW);
+        let below_max = format!("{}1", &max[..max.len() - 1]);
+        let deep = format!("{}{}", "[{\"a\":".repeat(1000), "}]".repeat(1000));
+        let mut texts = vec![
+            "null",
+            " true ",
+            "false",
+            "-0",
+            "0.5e-3",
+            "1E+2",
+            "[1, [2, {\"a\": []}], {}]",
+            "{\"a\" : {\"b\":[null, \"x\"]} }",
+            r#""\ud83d\ude00 \u00e9 \n \/ é""#,
+            "1e308",
+            "1e-400",
+            "100e306",
+            "0.00001797693134862315e313",
+            "1.7976931348623157e308",
+            "-1.7976931348623158e308",
+            max,
+            &below_max,
+            &deep,
+        ];
+        assert!(texts.iter().all(|text| IncludedJson::is_valid(text)));
+        let invalid = [
+            "",
+            " ",
+            "[1,]",
+            "[1 2]",
+            "{\"a\" 1}",
+            "{\"a\":1,}",
+            "{,}",
+            "{1: 2}",
+            "[}",
+            "{]",
+            "[1",
+            "01",
+            "1.",
+            ".1",
+            "1e",
+            "1e+",
+            "-",
+            "+1",
+            "tru",
+            "nul",
+            "[1] x",
+            "\"abc",
+            r#""\x""#,
+            r#""\u12""#,
+            r#""\ud800""#,
+            r#""\ud800\u0041""#,
+            r#""\udc00""#,
+            "\"\t\"",
+            "1e309",
+            "-1e309",
+            "200e306",
+            "1.7976931348623159e308",
+        ];
+        assert!(invalid.iter().all(|text| !IncludedJson::is_valid(text)));
+
+        // the same as the parser
+        texts.extend(invalid);
+        for text in texts {
+            assert_eq!(
+                IncludedJson::is_valid(text),
+                crate::from_str::<Value>(text).is_ok(),
+                "{text}"
+            );
+        }
+        assert_eq!(check_json("[1, 2 }"), Err(6));
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use faststr::FastStr;
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::{
+    error::Result, index::Index, serde::tri, value::node::Value, JsonContainerTrait, JsonValueTrait,
+};
+
+/// FieldMask is a tree of the allowed JSON paths, like the field masks in protobuf. It is used in
+/// [`to_string_filtered`][`crate::to_string_filtered`].
+///
+/// A path selects the whole subtree under it. The keys in a path match the fields of objects.
+/// The indexes in a path match the elements of arrays, and the keys are applied to every element
+/// if the array is not indexed by the mask.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{pointer, FieldMask};
+///
+/// let mut mask = FieldMask::new();
+/// mask.add_path(&["a", "b"]);
+/// mask.add_path(&pointer!["c", 0]);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct FieldMask {
+    root: MaskNode,
+}
+
+impl FieldMask {
+    /// Create an empty mask, which selects nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a path into the mask. An empty path selects the whole value.
+    pub fn add_path<Path: IntoIterator>(&mut self, path: Path)
+    where
+        Path::Item: Index,
+    {
+        self.root.add_path(path.into_iter());
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct MaskNode {
+    // the whole subtree is selected
+    all: bool,
+    keys: HashMap<FastStr, MaskNode>,
+    indexes: HashMap<usize, MaskNode>,
+}
+
+impl MaskNode {
+    fn add_path<I>(&mut self, mut path: I)
+    where
+        I: Iterator,
+        I::Item: Index,
+    {
+        if self.all {
+            return;
+        }
+
+        let Some(index) = path.next() else {
+            // the subtree is selected, the children are useless now
+            *self = Self {
+                all: true,
+                ..Default::default()
+            };
+            return;
+        };
+
+        let child = if let Some(key) = index.as_key() {
+            self.keys.entry(FastStr::new(key)).or_default()
+        } else if let Some(idx) = index.as_index() {
+            self.indexes.entry(idx).or_default()
+        } else {
+            return;
+        };
+        child.add_path(path);
+    }
+
+    // whether the value should be emitted under this node
+    fn matches(&self, value: &Value) -> bool {
+        self.all || value.is_object() || value.is_array()
+    }
+}
+
+// Serialize the value with the mask, without cloning the value.
+struct Filtered<'a> {
+    value: &'a Value,
+    node: &'a MaskNode,
+}
+
+impl<'a> Serialize for Filtered<'a> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let node = self.node;
+        if node.all {
+            return self.value.serialize(serializer);
+        }
+
+        if let Some(obj) = self.value.as_object() {
+            let mut map = tri!(serializer.serialize_map(None));
+            for (k, v) in obj.iter() {
+                let Some(child) = node.keys.get(k) else {
+                    continue;
+                };
+                if child.matches(v) {
+                    tri!(map.serialize_entry(
+                        k,
+                        &Filtered {
+                            value: v,
+                            node: child
+                        }
+                    ));
+                }
+            }
+            map.end()
+        } else if let Some(arr) = self.value.as_array() {
+            let mut seq = tri!(serializer.serialize_seq(None));
+            if node.indexes.is_empty() {
+                // the keys are applied to every element
+                for v in arr.iter() {
+                    if node.matches(v) {
+                        tri!(seq.serialize_element(&Filtered { value: v, node }));
+                    }
+                }
+            } else {
+                for (i, v) in arr.iter().enumerate() {
+                    let Some(child) = node.indexes.get(&i) else {
+                        continue;
+                    };
+                    if child.matches(v) {
+                        tri!(seq.serialize_element(&Filtered {
+                            value: v,
+                            node: child
+                        }));
+                    }
+                }
+            }
+            seq.end()
+        } else {
+            // the root is not a container and not selected
+            serializer.serialize_unit()
+        }
+    }
+}
+
+/// Serialize the `Value` into a JSON string, which only contains the fields selected by the
+/// `mask`.
+///
+/// The `Value` is not cloned or modified, the unselected subtrees are skipped when serializing.
+/// The objects and arrays on the selected paths are kept even if they become empty, and the
+/// non-container values at the middle of the paths are skipped. If the root is a non-container
+/// value and not selected, the result is `null`.
+///
+/// # Examples
+///
+/// ```
+/// use sonic_rs::{pointer, to_string_filtered, FieldMask, Value};
+///
+/// let value: Value = sonic_rs::from_str(
+///     r#"{
+///         "id": 1,
+///         "user": {"name": "sonic", "password": "123"},
+///         "items": [{"id": 1, "tag": "a"}, {"id": 2, "tag": "b"}],
+///         "scores": [10, 20, 30]
+///     }"#,
+/// )
+/// .unwrap();
+///
+/// let mut mask = FieldMask::new();
+/// mask.add_path(&["id"]);
+/// mask.add_path(&["user", "name"]);
+/// mask.add_path(&["items", "tag"]);
+/// mask.add_path(&pointer!["scores", 1]);
+///
+/// assert_eq!(
+///     to_string_filtered(&value, &mask).unwrap(),
+///     r#"{"id":1,"user":{"name":"sonic"},"items":[{"tag":"a"},{"tag":"b"}],"scores":[20]}"#
+/// );
+/// ```
+pub fn to_string_filtered(value: &Value, mask: &FieldMask) -> Result<String> {
+    crate::to_string(&Filtered {
+        value,
+        node: &mask.root,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{pointer, PointerNode};
+
+    // parse the output again, so the checks don't depend on the order of the object fields
+    fn filtered(value: &Value, paths: &[&[PointerNode]]) -> Value {
+        let mut mask = FieldMask::new();
+        for path in paths {
+            mask.add_path(*path);
+        }
+        crate::from_str(&to_string_filtered(value, &mask).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_to_string_filtered() {
+        let value = json!({
+            "a": {"b": 1, "c": [1, {"d": 2, "e": 3}], "f": null},
+            "g": "hello",
+            "h": [[{"i": 1, "j": 2}], {"i": 3}],
+        });
+
+        assert_eq!(filtered(&value, &[]), json!({}));
+        assert_eq!(filtered(&value, &[&pointer![]]), value);
+        assert_eq!(filtered(&value, &[&pointer!["g"]]), json!({"g": "hello"}));
+        assert_eq!(
+            filtered(&value, &[&pointer!["a", "b"], &pointer!["a"]]),
+            json!({"a": {"b": 1, "c": [1, {"d": 2, "e": 3}], "f": null}})
+        );
+        assert_eq!(
+            filtered(&value, &[&pointer!["a", "c", "d"]]),
+            json!({"a": {"c": [{"d": 2}]}})
+        );
+        assert_eq!(
+            filtered(&value, &[&pointer!["a", "c", 0]]),
+            json!({"a": {"c": [1]}})
+        );
+        assert_eq!(
+            filtered(&value, &[&pointer!["a", "b", "x"]]),
+            json!({"a": {}})
+        );
+        assert_eq!(
+            filtered(&value, &[&pointer!["h", "i"]]),
+            json!({"h": [[{"i": 1}], {"i": 3}]})
+        );
+        assert_eq!(
+            filtered(&value, &[&pointer!["x"], &pointer!["g", "y"]]),
+            json!({})
+        );
+
+        assert_eq!(filtered(&json!(1), &[]), json!(null));
+        assert_eq!(filtered(&json!([1, {}]), &[]), json!([{}]));
+    }
+}
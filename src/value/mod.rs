@@ -1,8 +1,11 @@
 //! A dynamic type to representing any valid JSON value.
 
 pub mod array;
+mod builder;
+mod canonical;
 pub(crate) mod de;
 mod from;
+mod included;
 pub(crate) mod node;
 #[doc(hidden)]
 pub mod shared;
@@ -10,9 +13,12 @@ mod tryfrom;
 #[macro_use]
 mod macros;
 pub mod get;
+mod mask;
 pub mod object;
 mod partial_eq;
+mod prune;
 mod ser;
+mod strings;
 mod tls_buffer;
 mod value_trait;
 pub(crate) mod visitor;
@@ -20,12 +26,22 @@ pub(crate) mod visitor;
 #[doc(inline)]
 pub use self::array::Array;
 #[doc(inline)]
+pub use self::builder::{ArrayBuilder, ObjectBuilder, ValueBuilder};
+#[doc(inline)]
+pub use self::canonical::{canonical_hash, canonical_hash_value};
+#[doc(inline)]
 pub use self::de::from_value;
 #[doc(inline)]
-pub use self::node::{Value, ValueRef};
+pub use self::included::IncludedJson;
+#[doc(inline)]
+pub use self::mask::{to_string_filtered, FieldMask};
+#[doc(inline)]
+pub use self::node::{ParseStats, Value, ValueRef};
 #[doc(inline)]
 pub use self::object::Object;
 #[doc(inline)]
 pub use self::ser::to_value;
 #[doc(inline)]
+pub use self::strings::{Strings, StringsMut};
+#[doc(inline)]
 pub use self::value_trait::{JsonContainerTrait, JsonType, JsonValueMutTrait, JsonValueTrait};
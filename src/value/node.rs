@@ -3,24 +3,24 @@ use core::mem::size_of;
 use std::collections::BTreeMap;
 use std::{
     alloc::Layout,
+    borrow::Cow,
     fmt::{Debug, Display, Formatter},
     marker::PhantomData,
     mem::{transmute, ManuallyDrop},
+    ops::Deref,
     ptr::NonNull,
     slice::from_raw_parts,
     str::from_utf8_unchecked,
-    sync::Arc,
+    sync::{Arc, OnceLock},
 };
 
 #[cfg(not(feature = "sort_keys"))]
 use ahash::AHashMap;
+use ahash::AHashSet;
 use bumpalo::Bump;
 use faststr::FastStr;
 use ref_cast::RefCast;
-use serde::{
-    ser::{Serialize, SerializeMap, SerializeSeq, SerializeStruct},
-    Serializer,
-};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq};
 
 use super::{
     object::Pair,
@@ -30,14 +30,16 @@ use super::{
     visitor::JsonVisitor,
 };
 use crate::{
-    config::DeserializeCfg,
-    error::Result,
+    config::{CancelToken, DedupPolicy, DeserializeCfg},
+    error::{ErrorCode, Result},
     index::Index,
     parser::Parser,
-    reader::{PaddedSliceRead, Reader},
-    serde::tri,
-    util::string::str_from_raw_parts,
+    pointer::PointerNode,
+    reader::{PaddedSliceRead, Read, Reader},
+    serde::{tri, RawKey},
+    util::{scratch::ScratchBuf, string::str_from_raw_parts},
     value::{array::Array, object::Object, value_trait::JsonValueTrait},
+    writer::FmtWriter,
     JsonNumberTrait, JsonType, Number, RawNumber,
 };
 
@@ -94,10 +96,11 @@ pub struct Value {
 // |  empty arr   |   0    |   6    |                                                                                    |
 // |  empty obj   |   0    |   7    |                                                                                    |
 // |  static str  |   0    |   8    |                   |           string length        +          *const u8            | excced will fallback |
+// |  small str   |   0    |   9    |   8 bits len      |              inlined string bytes (little endian only)             |    max len 14        |
 // |   faststr    |   1    |   0    |                                                    +         Box<FastStr>          |                      |
 // |rawnum_faststr|   1    |   1    |                                                    +         Box<FastStr>          |                      |
 // |   arr_mut    |   1    |   2    |                                                    +        Arc<Vec<Node>>         |                      |
-// |   obj_mut    |   1    |   3    |                                                    + Arc<AHashMap<FastStr, Value>> |                      |
+// |   obj_mut    |   1    |   3    |                                                    +        Arc<ObjectMap>         |                      |
 // |   str_node   |   2    |        node idx            |           string length        +          *const u8            |    max len 2^32      |
 // | raw_num_node |   3    |        node idx            |           string length        +          *const u8            |    max len 2^32      |
 // |   arr_node   |   4    |        node idx            |           array length         +          *const Node          |    max len 2^32      |
@@ -121,10 +124,8 @@ pub(crate) union Data {
     pub(crate) root: NonNull<Value>,
 
     pub(crate) str_own: ManuallyDrop<Box<FastStr>>,
-    #[cfg(not(feature = "sort_keys"))]
-    pub(crate) obj_own: ManuallyDrop<Arc<AHashMap<FastStr, Value>>>,
-    #[cfg(feature="sort_keys")]
-    pub(crate) obj_own: ManuallyDrop<Arc<BTreeMap<FastStr, Value>>>,
+    pub(crate) raw_json: ManuallyDrop<Box<RawJsonNode>>,
+    pub(crate) obj_own: ManuallyDrop<Arc<ObjectMap>>,
     pub(crate) arr_own: ManuallyDrop<Arc<Vec<Value>>>,
 
     pub(crate) parent: u64,
@@ -223,12 +224,14 @@ impl Meta {
     const EMPTY_ARR: u64 = (6 << Self::KIND_BITS);
     const EMPTY_OBJ: u64 = (7 << Self::KIND_BITS);
     const STATIC_STR: u64 = (8 << Self::KIND_BITS);
+    const SMALL_STR: u64 = (9 << Self::KIND_BITS);
 
     const OWNED_NODE: u64 = 1;
     const FASTSTR: u64 = 1 | (0 << Self::KIND_BITS);
     const RAWNUM_FASTSTR: u64 = 1 | (1 << Self::KIND_BITS);
     const ARR_MUT: u64 = 1 | (2 << Self::KIND_BITS);
     const OBJ_MUT: u64 = 1 | (3 << Self::KIND_BITS);
+    const RAWJSON_FASTSTR: u64 = 1 | (4 << Self::KIND_BITS);
 
     const STR_NODE: u64 = 2;
     const RAWNUM_NODE: u64 = 3;
@@ -246,6 +249,11 @@ impl Meta {
 
     const IDX_MASK: u64 = ((1 << Self::LEN_OFFSET) - 1) & !Self::KIND_MASK;
     const LEN_OFFSET: u64 = 32;
+
+    // the small string is inlined after the type and length bytes
+    const SMALL_STR_LEN_OFFSET: u64 = 8;
+    const SMALL_STR_OFFSET: usize = 2;
+    const SMALL_STR_MAX_LEN: usize = 2 * size_of::<u64>() - Self::SMALL_STR_OFFSET;
 }
 
 impl Meta {
@@ -264,13 +272,25 @@ impl Meta {
         Self { val }
     }
 
-    fn pack_static_str(kind: u64, len: usize) -> Self {
+    const fn pack_static_str(kind: u64, len: usize) -> Self {
         assert!(len < (u32::MAX as usize));
         assert!(kind == Self::STATIC_STR);
         let val = kind | ((len as u64) << Self::LEN_OFFSET);
         Self { val }
     }
 
+    fn pack_small_str_len(len: usize) -> Self {
+        debug_assert!(len <= Self::SMALL_STR_MAX_LEN);
+        let val = Self::SMALL_STR | ((len as u64) << Self::SMALL_STR_LEN_OFFSET);
+        Self { val }
+    }
+
+    fn unpack_small_strlen(&self) -> usize {
+        debug_assert!(self.get_type() == Self::SMALL_STR);
+        let val = unsafe { self.val };
+        ((val >> Self::SMALL_STR_LEN_OFFSET) & 0xff) as usize
+    }
+
     fn pack_shared(ptr: *const Shared) -> Self {
         unsafe { Arc::increment_strong_count(ptr) };
         let addr = ptr as usize as u64;
@@ -431,19 +451,35 @@ enum ValueDetail<'a> {
     Bool(bool),
     Number(Number),
     StaticStr(&'static str),
+    SmallStr(&'a str),
     FastStr(&'a FastStr),
     RawNumFasStr(&'a FastStr),
+    RawJsonFasStr(&'a RawJsonNode),
     Array(&'a Arc<Vec<Value>>),
-    #[cfg(not(feature = "sort_keys"))]
-    Object(&'a Arc<AHashMap<FastStr, Value>>),
-    #[cfg(feature = "sort_keys")]
-    Object(&'a Arc<BTreeMap<FastStr, Value>>),
+    Object(&'a Arc<ObjectMap>),
     Root(NodeInDom<'a>),
     NodeInDom(NodeInDom<'a>),
     EmptyArray,
     EmptyObject,
 }
 
+/// The statistics of a DOM parsed from JSON, returned by [`Value::parse_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParseStats {
+    /// The count of the nodes, including the root node and the object keys.
+    pub nodes: usize,
+    /// The bytes allocated in the arena, such as the arrays, objects and unescaped strings.
+    pub arena_bytes: usize,
+    /// The bytes of the JSON text copied with the padding, which the strings without escapes
+    /// borrow from.
+    pub json_bytes: usize,
+    /// The total length of the strings and raw numbers, including the object keys.
+    pub string_bytes: usize,
+    /// The max nesting depth of the arrays and objects.
+    pub max_depth: usize,
+}
+
 /// ValueRef is a immutable reference helper for `Value`.
 ///
 /// # Example
@@ -464,6 +500,17 @@ enum ValueDetail<'a> {
 ///    _ => unreachable!(),
 /// }
 /// ```
+///
+/// `ValueRef` can be serialized directly, so a subtree can be written without cloning it into a
+/// new `Value`.
+///
+/// ```
+/// use sonic_rs::json;
+///
+/// let v = json!({"a": {"b": [1, "c"]}});
+/// let sub = v["a"].as_ref();
+/// assert_eq!(sonic_rs::to_string(&sub).unwrap(), r#"{"b":[1,"c"]}"#);
+/// ```
 #[derive(Debug)]
 pub enum ValueRef<'a> {
     Null,
@@ -484,20 +531,14 @@ pub enum ValueRefInner<'a> {
     RawNum(&'a str),
     Array(&'a [Value]),
     Object(&'a [Pair]),
-    #[cfg(not(feature = "sort_keys"))]
-    ObjectOwned(&'a Arc<AHashMap<FastStr, Value>>),
-    #[cfg(feature = "sort_keys")]
-    ObjectOwned(&'a Arc<BTreeMap<FastStr, Value>>),
+    ObjectOwned(&'a Arc<ObjectMap>),
     EmptyArray,
     EmptyObject,
 }
 
 impl<'a> From<&'a [Pair]> for Value {
     fn from(value: &'a [Pair]) -> Self {
-        #[cfg(not(feature = "sort_keys"))]
-        let mut newd = AHashMap::with_capacity(value.len());
-        #[cfg(feature = "sort_keys")]
-        let mut newd = BTreeMap::new();
+        let mut newd = ObjectMap::with_capacity(value.len());
 
         for (k, v) in value {
             if let Some(k) = k.as_str() {
@@ -523,6 +564,7 @@ impl Drop for Value {
         unsafe {
             match self.meta.get_type() {
                 Meta::FASTSTR | Meta::RAWNUM_FASTSTR => ManuallyDrop::drop(&mut self.data.str_own),
+                Meta::RAWJSON_FASTSTR => ManuallyDrop::drop(&mut self.data.raw_json),
                 Meta::ARR_MUT => ManuallyDrop::drop(&mut self.data.arr_own),
                 Meta::OBJ_MUT => ManuallyDrop::drop(&mut self.data.obj_own),
                 Meta::ROOT_NODE => {
@@ -542,13 +584,230 @@ pub(crate) enum ValueMut<'a> {
     Str,
     RawNum,
     Array(&'a mut Vec<Value>),
+    Object(&'a mut ObjectMap),
+}
+
+#[cfg(not(feature = "sort_keys"))]
+pub type ObjectInner = AHashMap<FastStr, Value>;
+#[cfg(feature = "sort_keys")]
+pub type ObjectInner = BTreeMap<FastStr, Value>;
+
+/// The map of the mutable object. With the `preserve_order_on_mut` feature, it also remembers
+/// the key order.
+///
+/// The `order` has all the keys in the map, in the order of the origin JSON and the insertions.
+/// The removed keys are left in `order`, and compacted when there are too many of them. The map
+/// is only mutated by the methods here, so that the `order` is tracked.
+#[derive(Debug, Clone, Default)]
+pub struct ObjectMap {
+    map: ObjectInner,
+    #[cfg(all(not(feature = "sort_keys"), feature = "preserve_order_on_mut"))]
+    order: Vec<FastStr>,
+}
+
+impl Deref for ObjectMap {
+    type Target = ObjectInner;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl From<ObjectInner> for ObjectMap {
+    fn from(map: ObjectInner) -> Self {
+        Self {
+            #[cfg(all(not(feature = "sort_keys"), feature = "preserve_order_on_mut"))]
+            order: map.keys().cloned().collect(),
+            map,
+        }
+    }
+}
+
+impl ObjectMap {
+    #[cfg(not(feature = "sort_keys"))]
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            map: AHashMap::with_capacity(capacity),
+            #[cfg(feature = "preserve_order_on_mut")]
+            order: Vec::with_capacity(capacity),
+        }
+    }
+
+    #[cfg(feature = "sort_keys")]
+    pub(crate) fn with_capacity(_: usize) -> Self {
+        Self {
+            map: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, key: FastStr, val: Value) -> Option<Value> {
+        match self.map.get_mut(key.as_str()) {
+            Some(old) => Some(std::mem::replace(old, val)),
+            None => {
+                #[cfg(all(not(feature = "sort_keys"), feature = "preserve_order_on_mut"))]
+                self.order.push(key.clone());
+                self.map.insert(key, val);
+                self.compact_order();
+                None
+            }
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.map.get_mut(key)
+    }
+
+    // the values are mutable, but not the keys
     #[cfg(not(feature = "sort_keys"))]
-    Object(&'a mut AHashMap<FastStr, Value>),
+    pub(crate) fn iter_mut(&mut self) -> std::collections::hash_map::IterMut<'_, FastStr, Value> {
+        self.map.iter_mut()
+    }
+
     #[cfg(feature = "sort_keys")]
-    Object(&'a mut BTreeMap<FastStr, Value>),
+    pub(crate) fn iter_mut(&mut self) -> std::collections::btree_map::IterMut<'_, FastStr, Value> {
+        self.map.iter_mut()
+    }
+
+    #[cfg(not(feature = "sort_keys"))]
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional);
+        #[cfg(feature = "preserve_order_on_mut")]
+        self.order.reserve(additional);
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Option<Value> {
+        let old = self.map.remove(key);
+        self.compact_order();
+        old
+    }
+
+    pub(crate) fn retain<F: FnMut(&FastStr, &mut Value) -> bool>(&mut self, f: F) {
+        self.map.retain(f);
+        self.compact_order();
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.map.clear();
+        #[cfg(all(not(feature = "sort_keys"), feature = "preserve_order_on_mut"))]
+        self.order.clear();
+    }
+
+    /// Move all the pairs of `other` into self, in the order of `other`.
+    pub(crate) fn append(&mut self, other: &mut Self) {
+        let other = std::mem::take(other);
+        #[cfg(all(not(feature = "sort_keys"), feature = "preserve_order_on_mut"))]
+        {
+            let Self { mut map, order } = other;
+            self.map.reserve(map.len());
+            for key in order {
+                if let Some(val) = map.remove(key.as_str()) {
+                    self.insert(key, val);
+                }
+            }
+        }
+        #[cfg(not(all(not(feature = "sort_keys"), feature = "preserve_order_on_mut")))]
+        for (key, val) in other.map {
+            self.insert(key, val);
+        }
+    }
+
+    // drop the removed keys in `order` if they are more than the alive keys
+    #[cfg(all(not(feature = "sort_keys"), feature = "preserve_order_on_mut"))]
+    fn compact_order(&mut self) {
+        if self.order.len() > self.map.len() * 2 + 8 {
+            let map = &self.map;
+            let mut seen = AHashSet::with_capacity(map.len());
+            // keep the last one of the duplicated keys, as the key is inserted again after removed
+            let mut order: Vec<FastStr> = std::mem::take(&mut self.order)
+                .into_iter()
+                .rev()
+                .filter(|k| map.contains_key(k.as_str()) && seen.insert(k.clone()))
+                .collect();
+            order.reverse();
+            self.order = order;
+        }
+    }
+
+    #[cfg(not(all(not(feature = "sort_keys"), feature = "preserve_order_on_mut")))]
+    #[inline(always)]
+    fn compact_order(&mut self) {}
+
+    /// The pairs in the order of the origin JSON and the insertions.
+    #[cfg(all(not(feature = "sort_keys"), feature = "preserve_order_on_mut"))]
+    pub(crate) fn ordered(&self) -> Vec<(&str, &Value)> {
+        let mut pairs = Vec::with_capacity(self.map.len());
+        if self.order.len() == self.map.len() {
+            // no keys are removed, so the keys in `order` are unique
+            pairs.extend(
+                self.order
+                    .iter()
+                    .map(|k| (k.as_str(), &self.map[k.as_str()])),
+            );
+        } else {
+            let mut seen = AHashSet::with_capacity(self.map.len());
+            for k in self.order.iter().rev() {
+                if let Some((k, v)) = self.map.get_key_value(k.as_str()) {
+                    if seen.insert(k.as_str()) {
+                        pairs.push((k.as_str(), v));
+                    }
+                }
+            }
+            pairs.reverse();
+        }
+        debug_assert_eq!(pairs.len(), self.map.len());
+        pairs
+    }
+
+    /// The pairs in the iteration order of the map, as the key order is not tracked.
+    #[cfg(not(all(not(feature = "sort_keys"), feature = "preserve_order_on_mut")))]
+    pub(crate) fn ordered(&self) -> Vec<(&str, &Value)> {
+        self.map.iter().map(|(k, v)| (k.as_str(), v)).collect()
+    }
+}
+
+// The raw JSON of `Value::from_raw_json_trusted`. It is parsed at the first read, so that the
+// accessors can borrow from the parsed value, and it is still serialized verbatim.
+#[derive(Debug, Clone)]
+pub(crate) struct RawJsonNode {
+    raw: FastStr,
+    parsed: OnceLock<Value>,
+}
+
+impl RawJsonNode {
+    fn parsed(&self) -> Result<&Value> {
+        if let Some(value) = self.parsed.get() {
+            return Ok(value);
+        }
+        let value = tri!(crate::from_str(&self.raw));
+        Ok(self.parsed.get_or_init(|| value))
+    }
 }
 
 impl Value {
+    // Update the offset to the arena header, after the node is moved in its container.
+    unsafe fn set_dom_idx(&mut self, idx: usize) {
+        match self.meta.get_type() {
+            Meta::ESC_RAW_NODE => self.meta.unpack_rawstr_node().set_index(idx),
+            Meta::STR_NODE | Meta::RAWNUM_NODE | Meta::ARR_NODE | Meta::OBJ_NODE => {
+                let val = self.meta.val & !Meta::IDX_MASK;
+                self.meta.val = val | ((idx as u64) << Meta::KIND_BITS);
+            }
+            _ => {}
+        }
+    }
+
+    // The string of the key node in building, which can not find the arena header by its offset
+    // yet. The string is borrowed from the JSON or the arena.
+    unsafe fn building_key<'k>(&self) -> &'k str {
+        let ptr = self.data.dom_str.as_ptr();
+        let key: *const str = match self.meta.get_type() {
+            Meta::ESC_RAW_NODE => self.meta.unpack_rawstr_node().as_str(ptr),
+            Meta::STR_NODE => str_from_raw_parts(ptr, self.meta.unpack_dom_node().len as usize),
+            _ => "",
+        };
+        &*key
+    }
+
     fn is_node_kind(&self) -> bool {
         matches!(
             self.meta.get_kind(),
@@ -566,11 +825,15 @@ impl Value {
             Meta::NULL => ValueMut::Null,
             Meta::TRUE | Meta::FALSE => ValueMut::Bool,
             Meta::F64 | Meta::I64 | Meta::U64 => ValueMut::Number,
-            Meta::STATIC_STR | Meta::STR_NODE | Meta::FASTSTR | Meta::ESC_RAW_NODE => ValueMut::Str,
+            Meta::STATIC_STR
+            | Meta::SMALL_STR
+            | Meta::STR_NODE
+            | Meta::FASTSTR
+            | Meta::ESC_RAW_NODE => ValueMut::Str,
             Meta::RAWNUM_FASTSTR | Meta::RAWNUM_NODE => ValueMut::RawNum,
             Meta::ARR_MUT => ValueMut::Array(unsafe { Arc::make_mut(&mut self.data.arr_own) }),
             Meta::OBJ_MUT => ValueMut::Object(unsafe { Arc::make_mut(&mut self.data.obj_own) }),
-            Meta::ROOT_NODE | Meta::EMPTY_ARR | Meta::EMPTY_OBJ => {
+            Meta::ROOT_NODE | Meta::EMPTY_ARR | Meta::EMPTY_OBJ | Meta::RAWJSON_FASTSTR => {
                 /* convert to mutable */
                 self.to_mut();
                 self.as_mut()
@@ -597,10 +860,27 @@ impl Value {
             },
             ValueDetail::EmptyArray => *self = Value::new_array_with(8),
             ValueDetail::EmptyObject => *self = Value::new_object_with(8),
+            ValueDetail::RawJsonFasStr(node) => {
+                // the raw JSON is validated when created, so it is always parsed
+                if let Ok(parsed) = node.parsed() {
+                    *self = parsed.clone();
+                    self.to_mut();
+                }
+            }
             _ => {}
         }
     }
 
+    // The value parsed from the raw JSON node, or `None` if it is not a raw JSON node. It only
+    // compares the type for the other nodes, so it is cheap to check before their fast paths.
+    #[inline(always)]
+    pub(crate) fn parse_raw_json(&self) -> Option<Result<&Value>> {
+        if self.meta.get_type() != Meta::RAWJSON_FASTSTR {
+            return None;
+        }
+        Some(unsafe { self.data.raw_json.parsed() })
+    }
+
     fn unpack_static_str(&self) -> &'static str {
         debug_assert!(self.meta.get_type() == Meta::STATIC_STR);
         let ptr = unsafe { self.data.static_str.as_ptr() };
@@ -608,6 +888,15 @@ impl Value {
         unsafe { from_utf8_unchecked(from_raw_parts(ptr, len)) }
     }
 
+    fn unpack_small_str(&self) -> &str {
+        debug_assert!(self.meta.get_type() == Meta::SMALL_STR);
+        let len = self.meta.unpack_small_strlen();
+        unsafe {
+            let ptr = (self as *const Value as *const u8).add(Meta::SMALL_STR_OFFSET);
+            from_utf8_unchecked(from_raw_parts(ptr, len))
+        }
+    }
+
     fn forward_find_shared(current: *const Value, idx: usize) -> *const Shared {
         unsafe { (*(current.sub(idx) as *const MetaNode)).shared }
     }
@@ -632,9 +921,13 @@ impl Value {
             ValueDetail::Null => ValueRefInner::Null,
             ValueDetail::Bool(b) => ValueRefInner::Bool(b),
             ValueDetail::Number(n) => ValueRefInner::Number(n.clone()),
-            ValueDetail::StaticStr(s) => ValueRefInner::Str(s),
+            ValueDetail::StaticStr(s) | ValueDetail::SmallStr(s) => ValueRefInner::Str(s),
             ValueDetail::FastStr(s) => ValueRefInner::Str(s.as_str()),
             ValueDetail::RawNumFasStr(s) => ValueRefInner::RawNum(s.as_str()),
+            ValueDetail::RawJsonFasStr(node) => match node.parsed() {
+                Ok(parsed) => parsed.get_enum(),
+                Err(_) => ValueRefInner::Null,
+            },
             ValueDetail::Array(a) => ValueRefInner::Array(a),
             #[cfg(not(feature = "sort_keys"))]
             ValueDetail::Object(o) => ValueRefInner::ObjectOwned(o),
@@ -654,6 +947,7 @@ impl Value {
                 Meta::TRUE => ValueDetail::Bool(true),
                 Meta::FALSE => ValueDetail::Bool(false),
                 Meta::STATIC_STR => ValueDetail::StaticStr(self.unpack_static_str()),
+                Meta::SMALL_STR => ValueDetail::SmallStr(self.unpack_small_str()),
                 Meta::I64 => ValueDetail::Number(Number::from(self.data.ival)),
                 Meta::U64 => ValueDetail::Number(Number::from(self.data.uval)),
                 Meta::F64 => ValueDetail::Number(Number::try_from(self.data.fval).unwrap()),
@@ -669,6 +963,7 @@ impl Value {
                 }),
                 Meta::FASTSTR => ValueDetail::FastStr(&self.data.str_own),
                 Meta::RAWNUM_FASTSTR => ValueDetail::RawNumFasStr(&self.data.str_own),
+                Meta::RAWJSON_FASTSTR => ValueDetail::RawJsonFasStr(&self.data.raw_json),
                 Meta::ARR_MUT => ValueDetail::Array(&self.data.arr_own),
                 Meta::OBJ_MUT => ValueDetail::Object(&self.data.obj_own),
                 Meta::ROOT_NODE => ValueDetail::Root(NodeInDom {
@@ -702,10 +997,22 @@ impl Clone for Value {
             ValueDetail::Bool(b) => Value::new_bool(b),
             ValueDetail::Number(n) => n.into(),
             ValueDetail::StaticStr(s) => Value::from_static_str(s),
+            ValueDetail::SmallStr(_) => Value {
+                meta: self.meta,
+                data: Data {
+                    uval: unsafe { self.data.uval },
+                },
+            },
             ValueDetail::FastStr(s) => s.into(),
             ValueDetail::RawNumFasStr(s) => Value::new_rawnum_faststr(s),
+            ValueDetail::RawJsonFasStr(node) => Value::new_rawjson(node.clone()),
             ValueDetail::Array(a) => a.clone().into(),
-            ValueDetail::Object(o) => o.clone().into(),
+            ValueDetail::Object(o) => Value {
+                meta: Meta::new(Meta::OBJ_MUT),
+                data: Data {
+                    obj_own: ManuallyDrop::new(o.clone()),
+                },
+            },
             ValueDetail::EmptyArray => Value::new_array(),
             ValueDetail::EmptyObject => Value::new_object(),
         }
@@ -729,19 +1036,26 @@ impl From<Arc<AHashMap<FastStr, Value>>> for Value {
         Self {
             meta: Meta::new(Meta::OBJ_MUT),
             data: Data {
-                obj_own: ManuallyDrop::new(value),
+                obj_own: ManuallyDrop::new(Arc::new(into_object_map(value))),
             },
         }
     }
 }
 
+// the map maybe shared, and it is cloned if so
+fn into_object_map(map: Arc<ObjectInner>) -> ObjectMap {
+    Arc::try_unwrap(map)
+        .unwrap_or_else(|map| (*map).clone())
+        .into()
+}
+
 #[cfg(feature = "sort_keys")]
 impl From<Arc<BTreeMap<FastStr, Value>>> for Value {
     fn from(value: Arc<BTreeMap<FastStr, Value>>) -> Self {
         Self {
             meta: Meta::new(Meta::OBJ_MUT),
             data: Data {
-                obj_own: ManuallyDrop::new(value),
+                obj_own: ManuallyDrop::new(Arc::new(into_object_map(value))),
             },
         }
     }
@@ -763,7 +1077,10 @@ impl Default for Value {
 impl Value {
     /// Convert into `Object`. If the value is not an object, return `None`.
     #[inline]
-    pub fn into_object(self) -> Option<Object> {
+    pub fn into_object(mut self) -> Option<Object> {
+        if let Some(Ok(parsed)) = self.parse_raw_json() {
+            self = parsed.clone();
+        }
         if self.is_object() {
             Some(Object(self))
         } else {
@@ -773,7 +1090,10 @@ impl Value {
 
     /// Convert into `Array`. If the value is not an array, return `None`.
     #[inline]
-    pub fn into_array(self) -> Option<Array> {
+    pub fn into_array(mut self) -> Option<Array> {
+        if let Some(Ok(parsed)) = self.parse_raw_json() {
+            self = parsed.clone();
+        }
         if self.is_array() {
             Some(Array(self))
         } else {
@@ -784,7 +1104,10 @@ impl Value {
 
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", crate::to_string(self).expect("invalid value"))
+        // write into the formatter directly, without the intermediate string
+        let mut writer = FmtWriter::new(f);
+        crate::to_writer(&mut writer, self).map_err(|_| std::fmt::Error)?;
+        writer.finish().map_err(|_| std::fmt::Error)
     }
 }
 
@@ -861,6 +1184,7 @@ impl super::value_trait::JsonValueTrait for Value {
         match self.meta.get_type() {
             Meta::TRUE => Some(true),
             Meta::FALSE => Some(false),
+            Meta::RAWJSON_FASTSTR => self.parse_raw_json()?.ok()?.as_bool(),
             _ => None,
         }
     }
@@ -899,6 +1223,9 @@ impl JsonContainerTrait for Value {
 
     #[inline]
     fn as_array(&self) -> Option<&Self::ArrayType> {
+        if let Some(parsed) = self.parse_raw_json() {
+            return parsed.ok()?.as_array();
+        }
         if self.is_array() {
             Some(Self::ArrayType::ref_cast(self))
         } else {
@@ -908,6 +1235,9 @@ impl JsonContainerTrait for Value {
 
     #[inline]
     fn as_object(&self) -> Option<&Self::ObjectType> {
+        if let Some(parsed) = self.parse_raw_json() {
+            return parsed.ok()?.as_object();
+        }
         if self.is_object() {
             Some(Self::ObjectType::ref_cast(self))
         } else {
@@ -961,7 +1291,7 @@ impl JsonValueMutTrait for Value {
 }
 
 impl Value {
-    const PADDING_SIZE: usize = 64;
+    pub(crate) const PADDING_SIZE: usize = 64;
     pub(crate) const HEAD_NODE_COUNT: usize = 1;
 
     /// Create a new `null` Value. It is also the default value of `Value`.
@@ -1052,9 +1382,53 @@ impl Value {
         }
     }
 
+    /// Return the `&'static str` if the value is a string created from
+    /// [`from_static_str`][`Value::from_static_str`], otherwise return `None`.
+    ///
+    /// # Example
+    /// ```
+    /// use sonic_rs::{json, Value};
+    ///
+    /// let v = Value::from_static_str("hello");
+    /// assert_eq!(v.as_static_str(), Some("hello"));
+    /// assert_eq!(json!("hello").as_static_str(), None);
+    /// ```
+    #[inline]
+    pub fn as_static_str(&self) -> Option<&'static str> {
+        match self.unpack_ref() {
+            ValueDetail::StaticStr(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Return the string as a `FastStr`, or `None` if the value is not a string.
+    ///
+    /// It is a cheap clone when the string is backed by a `FastStr`, a `&'static str` or inlined
+    /// into the value. Otherwise, the string will be copied.
+    ///
+    /// # Example
+    /// ```
+    /// use faststr::FastStr;
+    /// use sonic_rs::{json, Value};
+    ///
+    /// let s = FastStr::new("a long string which is not inlined");
+    /// let v = Value::from(&s);
+    /// assert_eq!(v.as_faststr().unwrap().as_ptr(), s.as_ptr());
+    /// assert_eq!(json!("hello").as_faststr().unwrap(), "hello");
+    /// assert!(json!(123).as_faststr().is_none());
+    /// ```
+    #[inline]
+    pub fn as_faststr(&self) -> Option<FastStr> {
+        match self.unpack_ref() {
+            ValueDetail::FastStr(s) => Some(s.clone()),
+            ValueDetail::StaticStr(s) => Some(FastStr::from_static_str(s)),
+            _ => self.as_str().map(FastStr::new),
+        }
+    }
+
     #[doc(hidden)]
     #[inline]
-    pub fn new_u64(val: u64) -> Self {
+    pub const fn new_u64(val: u64) -> Self {
         Value {
             meta: Meta::new(Meta::U64),
             data: Data { uval: val },
@@ -1063,7 +1437,7 @@ impl Value {
 
     #[doc(hidden)]
     #[inline]
-    pub fn new_i64(ival: i64) -> Self {
+    pub const fn new_i64(ival: i64) -> Self {
         Value {
             meta: Meta::new(Meta::I64),
             data: Data { ival },
@@ -1094,7 +1468,7 @@ impl Value {
 
     #[doc(hidden)]
     #[inline]
-    pub fn new_null() -> Self {
+    pub const fn new_null() -> Self {
         Value {
             meta: Meta::new(Meta::NULL),
             data: Data { uval: 0 },
@@ -1119,6 +1493,20 @@ impl Value {
         }
     }
 
+    /// Create a string value in const contexts, the same as
+    /// [`from_static_str`][`Value::from_static_str`]. Panic if the string is not shorter than
+    /// `u32::MAX`.
+    #[doc(hidden)]
+    #[inline]
+    pub const fn new_static_str(val: &'static str) -> Self {
+        Value {
+            meta: Meta::pack_static_str(Meta::STATIC_STR, val.len()),
+            data: Data {
+                static_str: unsafe { NonNull::new_unchecked(val.as_ptr() as *mut u8) },
+            },
+        }
+    }
+
     #[doc(hidden)]
     #[inline]
     pub fn new_array_with(capacity: usize) -> Self {
@@ -1131,7 +1519,7 @@ impl Value {
 
     #[doc(hidden)]
     #[inline]
-    pub fn new_bool(val: bool) -> Self {
+    pub const fn new_bool(val: bool) -> Self {
         Value {
             meta: Meta::new(if val { Meta::TRUE } else { Meta::FALSE }),
             data: Data { uval: 0 },
@@ -1208,6 +1596,10 @@ impl Value {
     #[doc(hidden)]
     #[inline]
     pub fn copy_str(val: &str) -> Self {
+        if cfg!(target_endian = "little") && val.len() <= Meta::SMALL_STR_MAX_LEN {
+            return Self::new_small_str(val);
+        }
+
         let str_own = ManuallyDrop::new(Box::new(FastStr::new(val)));
         Value {
             meta: Meta::new(Meta::FASTSTR),
@@ -1215,6 +1607,21 @@ impl Value {
         }
     }
 
+    // inline the short string into the value, avoid boxed allocation
+    #[inline]
+    fn new_small_str(val: &str) -> Self {
+        debug_assert!(val.len() <= Meta::SMALL_STR_MAX_LEN);
+        let mut value = Value {
+            meta: Meta::pack_small_str_len(val.len()),
+            data: Data { uval: 0 },
+        };
+        unsafe {
+            let dst = (&mut value as *mut Value as *mut u8).add(Meta::SMALL_STR_OFFSET);
+            std::ptr::copy_nonoverlapping(val.as_ptr(), dst, val.len());
+        }
+        value
+    }
+
     #[doc(hidden)]
     #[inline]
     pub fn copy_str_in(kind: u64, val: &str, idx: usize, shared: &mut Shared) -> Self {
@@ -1229,6 +1636,70 @@ impl Value {
         }
     }
 
+    /// Create a value from the raw JSON text, which is serialized verbatim without re-escaping.
+    ///
+    /// The raw JSON is validated once here, and the leading and trailing whitespaces are trimmed
+    /// without copying. It is useful to embed the large pre-serialized fragments, such as the
+    /// cached sub-responses, into a `Value` without parsing them into nodes.
+    ///
+    /// The raw JSON is parsed at the first read, such as [`get`][crate::JsonValueTrait::get] or
+    /// [`as_object`][crate::JsonContainerTrait::as_object], and the parsed value is kept in the
+    /// node, so it can be read as the other values, and is still serialized verbatim. The mutable
+    /// APIs replace the node with the parsed value.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the raw JSON is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use faststr::FastStr;
+    /// use sonic_rs::{json, JsonValueMutTrait, JsonValueTrait, Value};
+    ///
+    /// let cached = FastStr::new(r#" {"list": [1, 2, 3], "name": "sonic"} "#);
+    /// let mut resp = json!({"code": 0});
+    /// resp["data"] = Value::from_raw_json_trusted(cached).unwrap();
+    /// assert_eq!(resp["data"]["list"][2], 3);
+    /// assert_eq!(
+    ///     sonic_rs::to_string(&resp["data"]).unwrap(),
+    ///     r#"{"list": [1, 2, 3], "name": "sonic"}"#
+    /// );
+    ///
+    /// // parse the raw JSON when mutating it
+    /// resp["data"].as_object_mut().unwrap().remove(&"list");
+    /// assert_eq!(resp["data"], json!({"name": "sonic"}));
+    ///
+    /// assert!(Value::from_raw_json_trusted(FastStr::new("[1,")).is_err());
+    /// ```
+    pub fn from_raw_json_trusted(json: FastStr) -> Result<Self> {
+        tri!(crate::from_str::<serde::de::IgnoredAny>(json.as_str()));
+        let trimmed = json.trim_matches(|c| matches!(c, ' ' | '\t' | '\n' | '\r'));
+        Ok(Value::new_rawjson(RawJsonNode {
+            raw: json.slice_ref(trimmed),
+            parsed: OnceLock::new(),
+        }))
+    }
+
+    /// Return the raw JSON text if the value is created by
+    /// [`from_raw_json_trusted`][Value::from_raw_json_trusted].
+    #[inline]
+    pub fn as_raw_json(&self) -> Option<&str> {
+        match self.unpack_ref() {
+            ValueDetail::RawJsonFasStr(node) => Some(node.raw.as_str()),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn new_rawjson(node: RawJsonNode) -> Self {
+        let raw_json = ManuallyDrop::new(Box::new(node));
+        Value {
+            meta: Meta::new(Meta::RAWJSON_FASTSTR),
+            data: Data { raw_json },
+        }
+    }
+
     #[doc(hidden)]
     #[inline]
     pub fn new_faststr(val: FastStr) -> Self {
@@ -1240,16 +1711,8 @@ impl Value {
     }
 
     #[doc(hidden)]
-    pub fn new_object_with(
-        #[cfg(not(feature = "sort_keys"))] capacity: usize,
-        #[cfg(feature = "sort_keys")] _: usize,
-    ) -> Self {
-        let obj_own = ManuallyDrop::new(Arc::new(
-            #[cfg(not(feature = "sort_keys"))]
-            AHashMap::with_capacity(capacity),
-            #[cfg(feature = "sort_keys")]
-            BTreeMap::new(),
-        ));
+    pub fn new_object_with(capacity: usize) -> Self {
+        let obj_own = ManuallyDrop::new(Arc::new(ObjectMap::with_capacity(capacity)));
         Value {
             meta: Meta::new(Meta::OBJ_MUT),
             data: Data { obj_own },
@@ -1379,58 +1842,274 @@ impl Value {
         std::mem::take(self)
     }
 
-    #[inline]
-    pub(crate) fn reserve<T>(&mut self, additional: usize) {
-        debug_assert!(self.is_object() || self.is_array());
-        debug_assert!(size_of::<T>() == size_of::<Value>() || size_of::<T>() == size_of::<Pair>());
-        match self.as_mut() {
-            ValueMut::Array(arr) => arr.reserve(additional),
-            #[cfg(not(feature = "sort_keys"))]
-            ValueMut::Object(obj) => obj.reserve(additional),
-            #[cfg(feature = "sort_keys")]
-            ValueMut::Object(_) => {}
-            _ => unreachable!("value is not array or object"),
-        }
-    }
-
-    #[doc(hidden)]
-    #[inline]
-    pub fn append_value(&mut self, val: Value) -> &mut Value {
-        debug_assert!(self.is_array());
-        match self.as_mut() {
-            ValueMut::Array(arr) => {
-                arr.push(val);
-                let len = arr.len();
-                &mut arr[len - 1]
-            }
-            _ => unreachable!("value is not array"),
+    /// Replace the contents of the string value with `s`, and return `false` if the value is not
+    /// a string.
+    ///
+    /// Only the string node is replaced, so the enclosing arrays and objects are not reallocated.
+    /// If the string is borrowed from the parsed JSON, the new string is owned by the node, and
+    /// the parsed JSON is not changed, such as in the other clones of the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::{json, Value};
+    ///
+    /// let mut value: Value = sonic_rs::from_str(r#"{"token": "secret", "id": 1}"#).unwrap();
+    /// let parsed = value.clone();
+    /// assert!(value["token"].set_str("***"));
+    /// assert!(!value["id"].set_str("***"));
+    /// assert_eq!(value, json!({"token": "***", "id": 1}));
+    /// assert_eq!(parsed["token"], "secret");
+    /// ```
+    pub fn set_str(&mut self, s: &str) -> bool {
+        if !self.is_str() {
+            return false;
         }
+        *self = Value::copy_str(s);
+        true
     }
 
-    #[doc(hidden)]
-    #[inline]
-    pub fn insert(&mut self, key: &str, val: Value) -> &mut Value {
-        debug_assert!(self.is_object());
-        match self.as_mut() {
-            ValueMut::Object(obj) => {
-                obj.insert(FastStr::new(key), val);
-                obj.get_mut(key).unwrap()
-            }
-            _ => unreachable!("value is not object"),
-        }
+    /// Replace the contents of the string value with the result of `f`, and return `false` if the
+    /// value is not a string.
+    ///
+    /// `f` gets the current string, and returns the new one. If `f` returns the current string
+    /// unchanged as `Cow::Borrowed`, the node is not replaced. The same as
+    /// [`set_str`][Value::set_str], the enclosing arrays and objects are not reallocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use sonic_rs::{json, JsonValueMutTrait, Value};
+    ///
+    /// let mut value: Value = sonic_rs::from_str(r#"[" a ", "B", "c"]"#).unwrap();
+    /// for elem in value.as_array_mut().unwrap().iter_mut() {
+    ///     elem.map_str(|s| match s.trim() {
+    ///         t if t.chars().all(|c| c.is_ascii_lowercase()) => Cow::Borrowed(t),
+    ///         t => Cow::Owned(t.to_ascii_lowercase()),
+    ///     });
+    /// }
+    /// assert_eq!(value, json!(["a", "b", "c"]));
+    /// ```
+    pub fn map_str<F>(&mut self, f: F) -> bool
+    where
+        F: FnOnce(&str) -> Cow<'_, str>,
+    {
+        let Some(old) = self.as_str() else {
+            return false;
+        };
+        let new = match f(old) {
+            Cow::Borrowed(s) if s.as_ptr() == old.as_ptr() && s.len() == old.len() => return true,
+            s => Value::from(s),
+        };
+        *self = new;
+        true
     }
 
-    #[inline]
-    pub(crate) fn pop(&mut self) -> Option<Value> {
-        debug_assert!(self.is_array());
-        match self.as_mut() {
-            ValueMut::Array(arr) => arr.pop(),
+    /// Re-pack the mutated value into a compact shared DOM, as the value parsed from JSON.
+    ///
+    /// The mutations convert the parsed arrays and objects into the separately allocated
+    /// `Vec`s and maps. After the build or mutation phase, `compact` packs them back into a
+    /// contiguous arena, which uses less memory and is faster to read, such as before caching the
+    /// value for a long time. The subtrees containing the raw numbers or raw JSON are kept as is,
+    /// and their siblings are still compacted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::{json, JsonValueMutTrait};
+    ///
+    /// let mut value = json!({"a": [1, 2], "b": "x"});
+    /// value["a"].as_array_mut().unwrap().push(3);
+    /// value["c"] = json!({"d": null});
+    ///
+    /// let compacted = value.clone().compact();
+    /// assert_eq!(compacted, value);
+    /// ```
+    pub fn compact(mut self) -> Self {
+        self.compact_in_place();
+        self
+    }
+
+    /// Return the statistics of the DOM, if the value is an array or object.
+    ///
+    /// It is useful to tune the memory usage for a workload, such as the capacity of the
+    /// buffers. The statistics are collected by traversing the DOM when called, so parsing pays
+    /// nothing for them. The `arena_bytes` and `json_bytes` are zero for the owned values, such as
+    /// the tiny documents parsed without the arena, the mutated values and the values built by
+    /// [`json!`][crate::json]. Return `None` for the scalars.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::Value;
+    ///
+    /// let value: Value = sonic_rs::from_str(r#"{"a": [1, "xy"], "b\\n": {}}"#).unwrap();
+    /// let stats = value.parse_stats().unwrap();
+    /// // the root, two keys, the array and its two elements, and the empty object
+    /// assert_eq!(stats.nodes, 7);
+    /// assert_eq!(stats.string_bytes, "a".len() + "xy".len() + "b\\n".len());
+    /// assert_eq!(stats.max_depth, 2);
+    ///
+    /// let stats = sonic_rs::json!({"a": 1}).parse_stats().unwrap();
+    /// assert_eq!((stats.nodes, stats.arena_bytes), (3, 0));
+    /// assert!(sonic_rs::json!(1).parse_stats().is_none());
+    /// ```
+    pub fn parse_stats(&self) -> Option<ParseStats> {
+        let mut stats = ParseStats::default();
+        if self.meta.get_type() == Meta::ROOT_NODE {
+            // SAFETY: the root node holds the shared dom
+            let shared = unsafe { &*self.meta.unpack_root() };
+            stats.arena_bytes = shared.allocated_bytes();
+            stats.json_bytes = shared.json_len();
+        }
+        if !self.is_array() && !self.is_object() {
+            return None;
+        }
+
+        // the explicit stack of (node, depth), so that the deeply nested values never overflow
+        // the call stack
+        let mut stack = vec![(self, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            stats.nodes += 1;
+            match node.as_ref2() {
+                ValueRefInner::Str(s) | ValueRefInner::RawNum(s) => stats.string_bytes += s.len(),
+                ValueRefInner::RawStr(raw) => stats.string_bytes += raw.str.len(),
+                ValueRefInner::Array(arr) => {
+                    stats.max_depth = stats.max_depth.max(depth + 1);
+                    stack.extend(arr.iter().map(|v| (v, depth + 1)));
+                }
+                ValueRefInner::Object(pairs) => {
+                    stats.max_depth = stats.max_depth.max(depth + 1);
+                    for (k, v) in pairs {
+                        stack.push((k, depth + 1));
+                        stack.push((v, depth + 1));
+                    }
+                }
+                ValueRefInner::ObjectOwned(obj) => {
+                    stats.max_depth = stats.max_depth.max(depth + 1);
+                    for (k, v) in obj.iter() {
+                        stats.nodes += 1;
+                        stats.string_bytes += k.len();
+                        stack.push((v, depth + 1));
+                    }
+                }
+                ValueRefInner::EmptyArray | ValueRefInner::EmptyObject => {
+                    stats.max_depth = stats.max_depth.max(depth + 1);
+                }
+                ValueRefInner::Null | ValueRefInner::Bool(_) | ValueRefInner::Number(_) => {}
+            }
+        }
+        Some(stats)
+    }
+
+    fn compact_in_place(&mut self) {
+        if !matches!(self.meta.get_type(), Meta::ARR_MUT | Meta::OBJ_MUT) {
+            return;
+        }
+
+        if !self.has_raw_nodes() {
+            // the JSON written from a value is always valid
+            if let Ok(v) = crate::to_vec(self).and_then(|json| crate::from_slice(&json)) {
+                *self = v;
+            }
+            return;
+        }
+
+        match self.as_mut() {
+            ValueMut::Array(arr) => arr.iter_mut().for_each(Value::compact_in_place),
+            ValueMut::Object(obj) => obj.iter_mut().for_each(|(_, v)| v.compact_in_place()),
+            _ => {}
+        }
+    }
+
+    // whether the value has the nodes which are changed when parsing from its JSON text again
+    fn has_raw_nodes(&self) -> bool {
+        if self.meta.get_type() == Meta::RAWJSON_FASTSTR {
+            return true;
+        }
+        match self.as_ref2() {
+            ValueRefInner::RawNum(_) | ValueRefInner::RawStr(_) => true,
+            ValueRefInner::Array(arr) => arr.iter().any(Value::has_raw_nodes),
+            ValueRefInner::Object(pairs) => pairs.iter().any(|(_, v)| v.has_raw_nodes()),
+            ValueRefInner::ObjectOwned(obj) => obj.values().any(Value::has_raw_nodes),
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn reserve<T>(&mut self, additional: usize) {
+        debug_assert!(self.is_object() || self.is_array());
+        debug_assert!(size_of::<T>() == size_of::<Value>() || size_of::<T>() == size_of::<Pair>());
+        match self.as_mut() {
+            ValueMut::Array(arr) => arr.reserve(additional),
+            #[cfg(not(feature = "sort_keys"))]
+            ValueMut::Object(obj) => obj.reserve(additional),
+            #[cfg(feature = "sort_keys")]
+            ValueMut::Object(_) => {}
+            _ => unreachable!("value is not array or object"),
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn append_value(&mut self, val: Value) -> &mut Value {
+        debug_assert!(self.is_array());
+        match self.as_mut() {
+            ValueMut::Array(arr) => {
+                arr.push(val);
+                let len = arr.len();
+                &mut arr[len - 1]
+            }
+            _ => unreachable!("value is not array"),
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn insert(&mut self, key: &str, val: Value) -> &mut Value {
+        debug_assert!(self.is_object());
+        match self.as_mut() {
+            ValueMut::Object(obj) => {
+                obj.insert(FastStr::new(key), val);
+                obj.get_mut(key).unwrap()
+            }
+            _ => unreachable!("value is not object"),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn pop(&mut self) -> Option<Value> {
+        debug_assert!(self.is_array());
+        match self.as_mut() {
+            ValueMut::Array(arr) => arr.pop(),
             _ => unreachable!("value is not object"),
         }
     }
 
+    // The documents of at most this number of nodes are parsed into the owned nodes first, see
+    // `SmallDocVisitor`.
+    const SMALL_DOC_NODES: usize = 32;
+
     #[inline(never)]
-    pub(crate) fn parse_with_padding(&mut self, json: &[u8], cfg: DeserializeCfg) -> Result<usize> {
+    pub(crate) fn parse_with_padding(
+        &mut self,
+        json: &[u8],
+        cfg: DeserializeCfg,
+        cancel: Option<CancelToken>,
+    ) -> Result<usize> {
+        // the raw strings are recorded in the arena, and the objects are parsed in the arena to
+        // keep the key order, so they are skipped before any work
+        if !cfg.use_raw
+            && DocumentVisitor::max_nodes(json.len()) <= Self::SMALL_DOC_NODES
+            && !json.contains(&b'{')
+        {
+            if let Some(idx) = self.parse_small(json, cfg) {
+                return Ok(idx);
+            }
+        }
+
         // allocate the padding buffer for the input json
         let mut shared = Arc::new(Shared::default());
         let mut buffer = Vec::with_capacity(json.len() + Self::PADDING_SIZE);
@@ -1440,9 +2119,11 @@ impl Value {
 
         let smut = Arc::get_mut(&mut shared).unwrap();
         let slice = PaddedSliceRead::new(buffer.as_mut_slice());
-        let mut parser = Parser::new(slice).with_config(cfg);
-        let mut vis = DocumentVisitor::new(json.len(), smut);
-        parser.parse_dom(&mut vis)?;
+        let mut parser = Parser::with_config(slice, cfg);
+        parser.cancel = cancel;
+        let mut vis = DocumentVisitor::new(json.len(), smut, cfg.dedup_keys);
+        let ret = parser.parse_dom(&mut vis);
+        vis.check_dup_key(&parser, ret)?;
         let idx = parser.read.index();
 
         // NOTE: root node should is the first node
@@ -1451,16 +2132,66 @@ impl Value {
         Ok(idx)
     }
 
+    // Parse the JSON in the `buffer` inplace, the last `PADDING_SIZE` bytes of the `buffer` are
+    // used as the padding.
+    //
+    // # Safety
+    // The JSON must be valid UTF-8, and the `buffer` must outlive the parsed value, because the
+    // strings in the value may be borrowed from the `buffer`.
+    #[inline(never)]
+    pub(crate) unsafe fn parse_padded_inplace(
+        buffer: &mut [u8],
+        cfg: DeserializeCfg,
+    ) -> Result<Value> {
+        assert!(
+            buffer.len() >= Self::PADDING_SIZE,
+            "the buffer is shorter than the padding"
+        );
+        let json_len = buffer.len() - Self::PADDING_SIZE;
+        let padding = &mut buffer[json_len..];
+        padding[..3].copy_from_slice(&b"x\"x"[..]);
+        padding[3..].fill(0);
+
+        let mut shared = Arc::new(Shared::default());
+        let smut = Arc::get_mut(&mut shared).unwrap();
+        let slice = PaddedSliceRead::new(buffer);
+        let mut parser = Parser::with_config(slice, cfg);
+        let mut vis = DocumentVisitor::new(json_len, smut, cfg.dedup_keys);
+        let ret = parser.parse_dom(&mut vis);
+        vis.check_dup_key(&parser, ret)?;
+        parser.parse_trailing()?;
+
+        // NOTE: root node should is the first node
+        Ok(vis.root.as_ref().clone())
+    }
+
+    // Parse the small document without objects into the owned nodes, without the arena, the
+    // padding buffer and the node buffer. Return `None` if the document is invalid, then it is
+    // parsed in the arena again, which also reports the errors.
+    fn parse_small(&mut self, json: &[u8], cfg: DeserializeCfg) -> Option<usize> {
+        let mut parser = Parser::with_config(Read::new(json, false), cfg);
+        let mut vis = SmallDocVisitor::default();
+        let mut strbuf = ScratchBuf::new();
+        parser.parse_dom2(&mut vis, &mut strbuf).ok()?;
+        *self = vis.root?;
+        Some(parser.read.index())
+    }
+
     #[inline(never)]
     pub(crate) fn parse_without_padding<'de, R: Reader<'de>>(
         &mut self,
         shared: &mut Shared,
-        strbuf: &mut Vec<u8>,
+        nodes: &mut Vec<ManuallyDrop<Value>>,
+        strbuf: &mut ScratchBuf,
         parser: &mut Parser<R>,
     ) -> Result<()> {
-        let remain_len = parser.read.remain();
-        let mut vis = DocumentVisitor::new(remain_len, shared);
-        parser.parse_dom2(&mut vis, strbuf)?;
+        // the node buffer is reserved for the remaining JSON, so it only grows for the first of
+        // the sibling values
+        // SAFETY: `nodes` outlives the visitor, and is only accessed by it in this function
+        let buf = unsafe { TlsBuf::reuse(nodes, DocumentVisitor::max_nodes(parser.read.remain())) };
+        let mut vis = DocumentVisitor::with_buf(buf, shared, parser.cfg.dedup_keys);
+        let ret = parser.parse_dom2(&mut vis, strbuf);
+        vis.check_dup_key(parser, ret)?;
         *self = unsafe { vis.root.as_ref().clone() };
         Ok(())
     }
@@ -1480,25 +2211,37 @@ pub(crate) struct DocumentVisitor<'a> {
     pub(crate) parent: usize,
     pub(crate) nodes_start: usize,
     pub(crate) root: NonNull<Value>,
+    dedup: DedupPolicy,
+    // the pointer of the duplicate key found with `DedupPolicy::Error`
+    dup_key: Option<Vec<PointerNode>>,
 }
 
 impl<'a> DocumentVisitor<'a> {
-    fn new(json_len: usize, shared: &'a mut Shared) -> Self {
-        // optimize: use a pre-allocated vec.
-        // If json is valid, the max number of value nodes should be
-        // half of the valid json length + 2. like as [1,2,3,1,2,3...]
-        // if the capacity is not enough, we will return a error.
-        let max_len = (json_len / 2) + 2;
-        let buf = TlsBuf::with_capacity(max_len);
+    fn new(json_len: usize, shared: &'a mut Shared, dedup: DedupPolicy) -> Self {
+        let buf = TlsBuf::with_capacity(Self::max_nodes(json_len));
+        Self::with_buf(buf, shared, dedup)
+    }
+
+    fn with_buf(buf: TlsBuf, shared: &'a mut Shared, dedup: DedupPolicy) -> Self {
         DocumentVisitor {
             shared,
             buf,
             parent: 0,
             nodes_start: 0,
             root: NonNull::dangling(),
+            dedup,
+            dup_key: None,
         }
     }
 
+    // optimize: use a pre-allocated vec.
+    // If json is valid, the max number of value nodes should be
+    // half of the valid json length + 2. like as [1,2,3,1,2,3...]
+    // if the capacity is not enough, we will return a error.
+    fn max_nodes(json_len: usize) -> usize {
+        (json_len / 2) + 2
+    }
+
     fn nodes(&mut self) -> &mut Vec<ManuallyDrop<Value>> {
         unsafe { NonNull::new_unchecked(self.buf.as_vec_mut() as *mut _).as_mut() }
     }
@@ -1506,6 +2249,168 @@ impl<'a> DocumentVisitor<'a> {
     fn index(&mut self) -> usize {
         self.nodes().len() - self.parent
     }
+
+    // Remove the duplicate keys in the object ending now, and return the new length. Return
+    // `None` if a key is duplicated with `DedupPolicy::Error`.
+    fn dedup_keys(&mut self, len: usize) -> Option<usize> {
+        let (policy, parent) = (self.dedup, self.parent);
+        let start = parent + 1;
+        let nodes = self.nodes();
+        debug_assert_eq!(nodes.len(), start + len * 2);
+
+        let mut seen = ahash::AHashMap::with_capacity(len);
+        let mut kept = 0;
+        let mut dup = None;
+        for i in 0..len {
+            let k = start + i * 2;
+            // the key is borrowed from the JSON or the arena, not from the moved nodes
+            let key = unsafe { nodes[k].building_key() };
+            match seen.get(key) {
+                None => {
+                    seen.insert(key, kept);
+                    let dst = start + kept * 2;
+                    nodes.swap(dst, k);
+                    nodes.swap(dst + 1, k + 1);
+                    kept += 1;
+                }
+                Some(&j) => match policy {
+                    DedupPolicy::LastWins => nodes.swap(start + j * 2 + 1, k + 1),
+                    DedupPolicy::Error => {
+                        dup = Some(key);
+                        break;
+                    }
+                    _ => {}
+                },
+            }
+        }
+
+        if let Some(key) = dup {
+            self.dup_key = Some(self.dup_pointer(key));
+            return None;
+        }
+        if kept < len {
+            // the moved nodes find the arena header by their positions
+            for pos in start..start + kept * 2 {
+                unsafe { nodes[pos].set_dom_idx(pos - parent) };
+            }
+            nodes.truncate(start + kept * 2);
+        }
+        Some(kept)
+    }
+
+    // The pointer of the duplicate `key` in the object ending now.
+    fn dup_pointer(&mut self, key: &str) -> Vec<PointerNode> {
+        let start = self.nodes_start;
+        let mut cur = self.parent;
+        let nodes = self.nodes();
+        let mut pointer = vec![PointerNode::Key(FastStr::new(key))];
+        while cur != start {
+            let parent = unsafe { nodes[cur].data.parent as usize };
+            let node = if nodes[parent].meta.get_type() == Meta::OBJ_NODE {
+                PointerNode::Key(FastStr::new(unsafe { nodes[cur - 1].building_key() }))
+            } else {
+                PointerNode::Index(cur - parent - 1)
+            };
+            pointer.push(node);
+            cur = parent;
+        }
+        pointer.reverse();
+        pointer
+    }
+
+    // Replace the error of the failed visiting with the duplicate key error.
+    fn check_dup_key<'de, R: Reader<'de>>(
+        &mut self,
+        parser: &Parser<R>,
+        ret: Result<()>,
+    ) -> Result<()> {
+        match self.dup_key.take() {
+            Some(pointer) => Err(parser.error(ErrorCode::DuplicateKey(pointer))),
+            None => ret,
+        }
+    }
+}
+
+// A visitor to build the small documents in the owned nodes, such as the inlined short strings
+// and the owned arrays, which skips the arena for the request-scoped tiny payloads.
+//
+// The objects are not supported, because the owned objects are hash maps, which lose the key
+// order and the duplicate keys of the JSON text. The visitor fails for them by default.
+#[derive(Default)]
+struct SmallDocVisitor {
+    // the arrays being visited
+    arrays: Vec<Value>,
+    root: Option<Value>,
+}
+
+impl SmallDocVisitor {
+    fn push_node(&mut self, node: Value) -> bool {
+        match self.arrays.last_mut() {
+            Some(arr) => {
+                arr.append_value(node);
+            }
+            None => self.root = Some(node),
+        }
+        true
+    }
+}
+
+impl<'de> JsonVisitor<'de> for SmallDocVisitor {
+    fn visit_dom_start(&mut self) -> bool {
+        true
+    }
+
+    fn visit_null(&mut self) -> bool {
+        self.push_node(Value::new_null())
+    }
+
+    fn visit_bool(&mut self, val: bool) -> bool {
+        self.push_node(Value::new_bool(val))
+    }
+
+    fn visit_u64(&mut self, val: u64) -> bool {
+        self.push_node(Value::new_u64(val))
+    }
+
+    fn visit_i64(&mut self, val: i64) -> bool {
+        self.push_node(Value::new_i64(val))
+    }
+
+    fn visit_f64(&mut self, val: f64) -> bool {
+        // # Safety
+        // we have checked the f64 in parsing number.
+        self.push_node(unsafe { Value::new_f64_unchecked(val) })
+    }
+
+    fn visit_raw_number(&mut self, val: &str) -> bool {
+        self.push_node(Value::new_rawnum(val))
+    }
+
+    fn visit_borrowed_raw_number(&mut self, val: &str) -> bool {
+        self.push_node(Value::new_rawnum(val))
+    }
+
+    fn visit_str(&mut self, val: &str) -> bool {
+        self.push_node(Value::copy_str(val))
+    }
+
+    fn visit_borrowed_str(&mut self, val: &'de str) -> bool {
+        self.push_node(Value::copy_str(val))
+    }
+
+    fn visit_array_start(&mut self, _hint: usize) -> bool {
+        self.arrays.push(Value::new_array());
+        true
+    }
+
+    fn visit_array_end(&mut self, _len: usize) -> bool {
+        let arr = self.arrays.pop().expect("the array has started");
+        self.push_node(arr)
+    }
+
+    fn visit_dom_end(&mut self) -> bool {
+        true
+    }
 }
 
 #[repr(C)]
@@ -1515,15 +2420,170 @@ struct MetaNode {
 }
 
 impl MetaNode {
+    const CANARY: [u8; 8] = *b"SONICRS\0";
+
     fn new(shared: *const Shared) -> Self {
-        let canary = b"SONICRS\0";
         MetaNode {
             shared,
-            canary: unsafe { transmute::<[u8; 8], u64>(*canary) },
+            canary: u64::from_ne_bytes(Self::CANARY),
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn is_valid(&self, shared: *const Shared) -> bool {
+        self.shared == shared && self.canary.to_ne_bytes() == Self::CANARY
+    }
+}
+
+#[cfg(debug_assertions)]
+impl Value {
+    // Check the structural invariants of the value recursively. `dom` is the shared dom which the
+    // value belongs to.
+    pub(crate) fn check_invariants(&self, dom: Option<*const Shared>) -> Result<()> {
+        use crate::error::make_error;
+
+        let val = unsafe { self.meta.val };
+        let typ = val & Meta::TYPE_MASK;
+        let invalid = |msg: &str| -> Result<()> {
+            Err(make_error(format!(
+                "invalid value (meta {val:#x}, type {typ}): {msg}"
+            )))
+        };
+        let check_utf8 = |ptr: *const u8, len: usize| -> Result<()> {
+            if std::str::from_utf8(unsafe { from_raw_parts(ptr, len) }).is_err() {
+                invalid("string is not valid utf-8")
+            } else {
+                Ok(())
+            }
+        };
+
+        let kind = self.meta.get_kind();
+        match kind {
+            Meta::STAIC_NODE => match typ {
+                Meta::NULL
+                | Meta::TRUE
+                | Meta::FALSE
+                | Meta::I64
+                | Meta::U64
+                | Meta::EMPTY_ARR
+                | Meta::EMPTY_OBJ => Ok(()),
+                Meta::F64 if !unsafe { self.data.fval }.is_finite() => invalid("f64 is not finite"),
+                Meta::F64 => Ok(()),
+                Meta::STATIC_STR => check_utf8(
+                    unsafe { self.data.static_str.as_ptr() },
+                    self.meta.unpack_strlen(),
+                ),
+                Meta::SMALL_STR if self.meta.unpack_small_strlen() > Meta::SMALL_STR_MAX_LEN => {
+                    invalid("small string is too long")
+                }
+                Meta::SMALL_STR => {
+                    let s = self.unpack_small_str();
+                    check_utf8(s.as_ptr(), s.len())
+                }
+                _ => invalid("unknown type tag"),
+            },
+            Meta::OWNED_NODE => {
+                if dom.is_some() {
+                    return invalid("owned node in the shared dom");
+                }
+                match typ {
+                    Meta::FASTSTR | Meta::RAWNUM_FASTSTR | Meta::RAWJSON_FASTSTR => Ok(()),
+                    Meta::ARR_MUT => {
+                        for v in unsafe { self.data.arr_own.iter() } {
+                            tri!(v.check_invariants(None));
+                        }
+                        Ok(())
+                    }
+                    Meta::OBJ_MUT => {
+                        for v in unsafe { self.data.obj_own.values() } {
+                            tri!(v.check_invariants(None));
+                        }
+                        Ok(())
+                    }
+                    _ => invalid("unknown type tag"),
+                }
+            }
+            Meta::ROOT_NODE => {
+                if dom.is_some() {
+                    return invalid("root node in the shared dom");
+                }
+                let shared = self.meta.unpack_root();
+                if shared.is_null() || !(shared as usize).is_multiple_of(8) {
+                    return invalid("shared dom pointer is null or not aligned");
+                }
+                let root = unsafe { self.data.root.as_ref() };
+                if !root.is_node_kind() && root.meta.get_kind() != Meta::STAIC_NODE {
+                    return invalid("root points to a node which is not in the shared dom");
+                }
+                root.check_invariants(Some(shared))
+            }
+            Meta::STR_NODE
+            | Meta::RAWNUM_NODE
+            | Meta::ARR_NODE
+            | Meta::OBJ_NODE
+            | Meta::ESC_RAW_NODE => {
+                let Some(dom) = dom else {
+                    return invalid("dom node out of the shared dom");
+                };
+                let (idx, len) = if kind == Meta::ESC_RAW_NODE {
+                    let hdr = self.meta.unpack_rawstr_node();
+                    if !(hdr.ptr.as_ptr() as usize).is_multiple_of(8) {
+                        return invalid("raw string header is not aligned");
+                    }
+                    (hdr.get_idx() as usize, 0)
+                } else {
+                    let NodeMeta { idx, len } = self.meta.unpack_dom_node();
+                    (idx as usize, len as usize)
+                };
+                if idx == 0 {
+                    return invalid("node index is zero");
+                }
+                let meta = unsafe { &*((self as *const Value).sub(idx) as *const MetaNode) };
+                if !meta.is_valid(dom) {
+                    return invalid("the canary or shared pointer in the meta node is corrupted");
+                }
+
+                match kind {
+                    Meta::STR_NODE | Meta::RAWNUM_NODE => {
+                        check_utf8(unsafe { self.data.dom_str.as_ptr() }, len)
+                    }
+                    Meta::ESC_RAW_NODE => {
+                        let hdr = self.meta.unpack_rawstr_node();
+                        let raw = hdr.as_raw();
+                        tri!(check_utf8(raw.as_ptr(), raw.len()));
+                        let str_len = unsafe { hdr.ptr.as_ref() }.str_len as usize;
+                        check_utf8(unsafe { self.data.dom_str.as_ptr() }, str_len)
+                    }
+                    Meta::ARR_NODE => {
+                        if len == 0 {
+                            return invalid("empty array in the dom node");
+                        }
+                        let elems = unsafe { from_raw_parts(self.data.arr_elems.as_ptr(), len) };
+                        for v in elems {
+                            tri!(v.check_invariants(Some(dom)));
+                        }
+                        Ok(())
+                    }
+                    _ => {
+                        if len == 0 {
+                            return invalid("empty object in the dom node");
+                        }
+                        let pairs = unsafe { from_raw_parts(self.data.obj_pairs.as_ptr(), len) };
+                        for (k, v) in pairs {
+                            tri!(k.check_invariants(Some(dom)));
+                            if !k.is_str() {
+                                return invalid("object key is not a string");
+                            }
+                            tri!(v.check_invariants(Some(dom)));
+                        }
+                        Ok(())
+                    }
+                }
+            }
+            _ => unreachable!(),
         }
     }
 }
-// TODO: add assert for Value
 
 impl<'a> DocumentVisitor<'a> {
     fn visit_container_start(&mut self, kind: u64) -> bool {
@@ -1569,8 +2629,7 @@ impl<'a> DocumentVisitor<'a> {
             std::ptr::copy_nonoverlapping(src, elems, visited_children.len());
 
             // record the `Shared` pointer
-            let meta = &mut *(hdr as *mut MetaNode);
-            meta.shared = vis.shared as *const _;
+            (hdr as *mut MetaNode).write(MetaNode::new(vis.shared as *const _));
 
             // update the container header
             let idx = (parent - vis.parent) as u32;
@@ -1685,6 +2744,14 @@ impl<'de, 'a> JsonVisitor<'de> for DocumentVisitor<'a> {
 
     #[inline(always)]
     fn visit_object_end(&mut self, len: usize) -> bool {
+        let len = if self.dedup != DedupPolicy::KeepAll && len > 1 {
+            match self.dedup_keys(len) {
+                Some(len) => len,
+                None => return false,
+            }
+        } else {
+            len
+        };
         self.visit_container_end(Meta::OBJ_NODE, len)
     }
 
@@ -1740,26 +2807,20 @@ impl Value {
     pub(crate) const RAW_TOKEN: &str = "_private:sonic_rs:raw";
 }
 
-#[derive(Debug)]
-struct RawKey<'a>(&'a str);
-
-impl Serialize for RawKey<'_> {
-    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let mut s = serializer.serialize_struct(Value::RAW_TOKEN, 1)?;
-        s.serialize_field(Value::RAW_TOKEN, &self.0)?;
-        s.end()
-    }
-}
-
 impl Serialize for Value {
     #[inline]
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: ::serde::Serializer,
     {
+        if let Some(raw) = self.as_raw_json() {
+            use serde::ser::SerializeStruct;
+
+            use crate::lazyvalue::TOKEN;
+            let mut struct_ = tri!(serializer.serialize_struct(TOKEN, 1));
+            tri!(struct_.serialize_field(TOKEN, raw));
+            return struct_.end();
+        }
         match self.as_ref2() {
             ValueRefInner::Null => serializer.serialize_unit(),
             ValueRefInner::Bool(b) => serializer.serialize_bool(b),
@@ -1791,7 +2852,7 @@ impl Serialize for Value {
                     let mut map = tri!(serializer.serialize_map(Some(kvs.len())));
                     for (k, v) in kvs {
                         if let Some(raw) = k.as_raw_str() {
-                            tri!(map.serialize_key(&RawKey(raw.raw)));
+                            tri!(map.serialize_key(&RawKey::new_trusted(raw.raw)));
                         } else {
                             tri!(map.serialize_key(k.as_str().unwrap()));
                         }
@@ -1805,7 +2866,7 @@ impl Serialize for Value {
                     let mut map = tri!(serializer.serialize_map(Some(entries.len())));
                     for (k, v) in entries {
                         if let Some(raw) = k.as_raw_str() {
-                            tri!(map.serialize_key(&RawKey(raw.raw)));
+                            tri!(map.serialize_key(&RawKey::new_trusted(raw.raw)));
                         } else {
                             tri!(map.serialize_key(k.as_str().unwrap()));
                         }
@@ -1814,7 +2875,16 @@ impl Serialize for Value {
                     map.end()
                 }
             }
-            #[cfg(not(feature = "sort_keys"))]
+            #[cfg(all(not(feature = "sort_keys"), feature = "preserve_order_on_mut"))]
+            ValueRefInner::ObjectOwned(o) => {
+                let mut map = tri!(serializer.serialize_map(Some(o.len())));
+                for (k, v) in o.ordered() {
+                    tri!(map.serialize_key(k));
+                    tri!(map.serialize_value(v));
+                }
+                map.end()
+            }
+            #[cfg(all(not(feature = "sort_keys"), not(feature = "preserve_order_on_mut")))]
             ValueRefInner::ObjectOwned(o) => {
                 let mut map = tri!(serializer.serialize_map(Some(o.len())));
                 for (k, v) in o.iter() {
@@ -1844,12 +2914,31 @@ impl Serialize for Value {
     }
 }
 
+impl Serialize for ValueRef<'_> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        match self {
+            ValueRef::Null => serializer.serialize_unit(),
+            ValueRef::Bool(b) => serializer.serialize_bool(*b),
+            ValueRef::Number(n) => n.serialize(serializer),
+            ValueRef::String(s) => s.serialize(serializer),
+            ValueRef::Array(a) => a.serialize(serializer),
+            ValueRef::Object(o) => o.serialize(serializer),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::path::Path;
 
     use super::*;
-    use crate::{error::make_error, from_slice, from_str, object, pointer, util::mock::MockString};
+    use crate::{
+        error::make_error, from_slice, from_str, json, object, pointer, util::mock::MockString,
+    };
 
     #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
     struct ValueInStruct {
@@ -2164,6 +3253,37 @@ mod test {
         .unwrap_err();
     }
 
+    #[test]
+    fn test_serialize_subtree() {
+        let json = r#"{"a":{"b\n":[1,-2.5,"c\u0041",null,true],"d":{}},"e":[]}"#;
+        let mut de = crate::Deserializer::from_str(json).use_raw();
+        let value: Value = de.deserialize().unwrap();
+
+        // the raw strings are kept when serializing the containers
+        let a = value["a"].as_object().unwrap();
+        assert_eq!(
+            crate::to_string(a).unwrap(),
+            r#"{"b\n":[1,-2.5,"c\u0041",null,true],"d":{}}"#
+        );
+        let b = a.get(&"b\n").unwrap();
+        assert_eq!(
+            crate::to_string(&b.as_ref()).unwrap(),
+            r#"[1,-2.5,"c\u0041",null,true]"#
+        );
+
+        let arr = b.as_array().unwrap();
+        assert_eq!(
+            crate::to_string(&arr.as_slice()[1..3]).unwrap(),
+            r#"[-2.5,"c\u0041"]"#
+        );
+        let expect = ["1", "-2.5", r#""cA""#, "null", "true"];
+        for (v, expect) in arr.iter().zip(expect) {
+            assert_eq!(crate::to_string(&v.as_ref()).unwrap(), expect);
+        }
+        assert_eq!(crate::to_string(&value["e"].as_ref()).unwrap(), "[]");
+        assert_eq!(crate::to_string(&a["d"].as_ref()).unwrap(), "{}");
+    }
+
     #[test]
     fn test_arbitrary_precision() {
         use crate::Deserializer;
@@ -2280,4 +3400,365 @@ mod test {
         let result = crate::to_string(&ret_obj).unwrap();
         assert_eq!(str, &result);
     }
+
+    #[test]
+    fn test_small_str() {
+        for s in [
+            "",
+            "a",
+            "hello",
+            "12345678901234",
+            "中文字符串",
+            "123456789012345",
+        ] {
+            let v = Value::copy_str(s);
+            assert_eq!(v.as_str(), Some(s));
+            assert!(v.is_str());
+
+            let cloned = v.clone();
+            drop(v);
+            assert_eq!(cloned.as_str(), Some(s));
+            assert_eq!(cloned.as_faststr().unwrap(), s);
+            assert_eq!(crate::to_string(&cloned).unwrap(), format!("\"{}\"", s));
+        }
+
+        let v = Value::copy_str("short");
+        if cfg!(target_endian = "little") {
+            assert_eq!(v.meta.get_type(), Meta::SMALL_STR);
+        }
+        assert_eq!(
+            Value::copy_str("a longer string").meta.get_type(),
+            Meta::FASTSTR
+        );
+
+        // mutate an inlined key and value
+        let mut obj = object! {"key": "val"};
+        obj.insert(&"key", Value::copy_str("new"));
+        assert_eq!(obj.get(&"key").unwrap().as_str(), Some("new"));
+
+        let static_v = Value::from_static_str("static");
+        assert_eq!(static_v.as_static_str(), Some("static"));
+        assert_eq!(Value::copy_str("static").as_static_str(), None);
+    }
+
+    #[test]
+    fn test_small_doc() {
+        let docs = [
+            "null",
+            " true ",
+            "-1",
+            "1.5e3",
+            "18446744073709551615",
+            r#""short""#,
+            r#""a longer string \u4e2d\n""#,
+            "[]",
+            "{}",
+            "[1, [2, []], {}, \"x\"]",
+            r#"{"ok":true}"#,
+            r#"[{"a": 1, "a": 2}]"#,
+        ];
+        for json in docs {
+            // the long documents are parsed in the arena
+            let padded = format!("{json}{}", " ".repeat(128));
+            let small: Value = from_str(json).unwrap();
+            let arena: Value = from_str(&padded).unwrap();
+            assert_eq!(small, arena, "{json}");
+            assert_eq!(
+                crate::to_string(&small).unwrap(),
+                crate::to_string(&arena).unwrap()
+            );
+            // the same stats without the arena
+            let stats = |v: &Value| {
+                v.parse_stats()
+                    .map(|s| (s.nodes, s.string_bytes, s.max_depth))
+            };
+            assert_eq!(stats(&small), stats(&arena), "{json}");
+        }
+
+        // the arrays are owned, and the objects are in the arena to keep the key order
+        let v: Value = from_str("[1, [true]]").unwrap();
+        assert_eq!(v.meta.get_type(), Meta::ARR_MUT);
+        assert_eq!(v.parse_stats().unwrap().arena_bytes, 0);
+        let v: Value = from_str(r#"[{}, "{"]"#).unwrap();
+        assert_eq!(v.meta.get_type(), Meta::ARR_NODE);
+        let v: Value = from_str(r#"{"b": 1, "a": 2, "b": 3}"#).unwrap();
+        assert_eq!(crate::to_string(&v).unwrap(), r#"{"b":1,"a":2,"b":3}"#);
+
+        // the raw numbers
+        let mut cfg = DeserializeCfg::default();
+        cfg.use_rawnumber = true;
+        let v: Value = crate::from_str_with_cfg("[1.00, -0]", cfg).unwrap();
+        assert_eq!(crate::to_string(&v).unwrap(), "[1.00,-0]");
+
+        // the errors are the same as parsing in the arena
+        for json in ["[1,]", "[1", "tru", r#"{"a" 1}"#, "[1] 2", r#""\x""#] {
+            let err = from_str::<Value>(json).unwrap_err();
+            let padded = from_str::<Value>(&format!("{json}{}", " ".repeat(128))).unwrap_err();
+            assert_eq!(err.classify(), padded.classify(), "{json}");
+        }
+    }
+
+    #[test]
+    fn test_from_raw_json_trusted() {
+        use std::collections::HashMap;
+
+        use crate::{json, JsonType};
+
+        let raw = |s: &str| Value::from_raw_json_trusted(FastStr::new(s)).unwrap();
+
+        let obj = raw(" \n{\"a\": [1, \"x\\n\"], \"b\": {}}\t");
+        assert_eq!(obj.as_raw_json(), Some("{\"a\": [1, \"x\\n\"], \"b\": {}}"));
+        assert_eq!(obj.get_type(), JsonType::Object);
+        assert!(obj.is_object());
+        assert_eq!(obj.as_object().unwrap().len(), 2);
+        // parsed once at the first read
+        assert!(std::ptr::eq(
+            obj.as_object().unwrap(),
+            obj.as_object().unwrap()
+        ));
+        assert_eq!(obj["a"][1].as_str(), Some("x\n"));
+        assert_eq!(obj.pointer(pointer!["a", 0]).unwrap().as_u64(), Some(1));
+        assert!(matches!(obj.as_ref(), ValueRef::Object(_)));
+        // still serialized verbatim after reading it
+        assert_eq!(obj.as_raw_json(), Some("{\"a\": [1, \"x\\n\"], \"b\": {}}"));
+        assert_eq!(obj, json!({"a": [1, "x\n"], "b": {}}));
+        assert_eq!(json!({"b": {}, "a": [1, "x\n"]}), obj);
+        assert_eq!(obj.clone(), obj);
+
+        // serialized verbatim
+        let mut value = json!([1]);
+        value.as_array_mut().unwrap().push(obj.clone());
+        assert_eq!(
+            crate::to_string(&value).unwrap(),
+            "[1,{\"a\": [1, \"x\\n\"], \"b\": {}}]"
+        );
+        value.as_array_mut().unwrap().push(raw("[]"));
+        let value: Value = from_str(&crate::to_string(&value).unwrap()).unwrap();
+        assert_eq!(value, json!([1, {"a": [1, "x\n"], "b": {}}, []]));
+
+        // the scalars
+        assert!(raw("null").is_null());
+        assert_eq!(raw("true").as_bool(), Some(true));
+        assert!(matches!(raw("false").as_ref(), ValueRef::Bool(false)));
+        assert_eq!(raw("-1.5").as_f64(), Some(-1.5));
+        assert_eq!(raw("18446744073709551615").as_u64(), Some(u64::MAX));
+        assert_eq!(raw("\"abc\"").as_str(), Some("abc"));
+        assert_eq!(raw("\"a\\u0062\"").as_str(), Some("ab"));
+        assert_eq!(raw("\"a\\u0062\"").get_type(), JsonType::String);
+        assert_eq!(raw("\"a\\u0062\""), Value::from("ab"));
+
+        // deserialize from the raw JSON
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Data {
+            a: (u8, String),
+            b: HashMap<String, u8>,
+        }
+        let data: Data = crate::from_value(&obj).unwrap();
+        assert_eq!(data.a, (1, "x\n".to_string()));
+        assert!(data.b.is_empty());
+        let nested = json!({"data": obj.clone()});
+        let got: HashMap<String, Data> = crate::from_value(&nested).unwrap();
+        assert_eq!(got["data"], data);
+        assert!(crate::from_value::<Vec<u8>>(&obj).is_err());
+        assert_eq!(
+            crate::to_value(&obj).unwrap().as_raw_json(),
+            obj.as_raw_json()
+        );
+
+        // the mutable APIs parse the raw JSON
+        let mut value = obj.clone();
+        value["a"][0] = Value::from(2);
+        assert_eq!(value, json!({"a": [2, "x\n"], "b": {}}));
+        assert!(value.as_raw_json().is_none());
+        assert_eq!(obj.into_object().unwrap().len(), 2);
+        assert_eq!(raw("[1, 2]").into_array().unwrap().len(), 2);
+
+        for invalid in ["", " ", "[1,", "{\"a\"}", "1 2", "\"\\x\""] {
+            assert!(Value::from_raw_json_trusted(FastStr::new(invalid)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut value: Value = from_str(r#"{"a": [1, "s", {"b": 2.5}], "c": "中文"}"#).unwrap();
+        value["a"].as_array_mut().unwrap().push(json!([true, null]));
+        value["d"] = json!({"e": "x".repeat(100)});
+        let expect = value.clone();
+
+        let value = value.compact();
+        assert_eq!(value.meta.get_type(), Meta::ROOT_NODE);
+        assert_eq!(value, expect);
+        value.check_invariants(None).unwrap();
+
+        // the raw numbers are kept, and the siblings are compacted
+        let mut value = json!({"a": [1, 2], "b": []});
+        value["b"] = json!([RawNumber::new("1.000"), {"c": 1}]);
+        value["a"].as_array_mut().unwrap().push(json!(3));
+        let value = value.compact();
+        assert_eq!(value.meta.get_type(), Meta::OBJ_MUT);
+        assert_eq!(value["a"].meta.get_type(), Meta::ROOT_NODE);
+        assert_eq!(value["b"][0].as_raw_number().unwrap().as_str(), "1.000");
+        assert_eq!(value["b"][1].meta.get_type(), Meta::ROOT_NODE);
+
+        assert_eq!(json!(1).compact(), 1);
+        assert_eq!(json!([]).compact(), json!([]));
+    }
+
+    #[test]
+    fn test_set_str() {
+        let json = r#"{"a": ["short", "a long string in the shared arena", "\\n"], "b": 1}"#;
+        let mut value: Value = from_str(json).unwrap();
+        let parsed = value.clone();
+        let arr = value["a"].as_array_mut().unwrap();
+        let ptr = arr.as_ptr();
+        assert!(arr[0].set_str("x".repeat(100).as_str()));
+        assert!(arr[1].set_str("y"));
+        assert!(arr[2].map_str(|s| Cow::Owned(s.replace('\n', " "))));
+        // the enclosing array is not reallocated
+        assert_eq!(arr.as_ptr(), ptr);
+        assert_eq!(value["a"], json!(["x".repeat(100), "y", " "]));
+        assert_eq!(parsed, from_str::<Value>(json).unwrap());
+
+        // the unchanged and sliced strings
+        assert!(value["a"][1].map_str(|s| s.into()));
+        assert_eq!(value["a"][1], "y");
+        assert!(value["a"][0].map_str(|s| Cow::Borrowed(&s[..2])));
+        assert_eq!(value["a"][0], "xx");
+
+        // the non-string values are not changed
+        assert!(!value["b"].set_str("x"));
+        assert!(!value["b"].map_str(|_| unreachable!()));
+        assert_eq!(value["b"], 1);
+
+        let mut root: Value = from_str(r#""root""#).unwrap();
+        assert!(root.set_str("new"));
+        assert_eq!(root, "new");
+    }
+
+    #[test]
+    fn test_dedup_keys() {
+        use crate::{DedupPolicy, Deserializer};
+
+        let json =
+            r#"{"a": [{"x": 1, "y": 2, "x": "3"}], "b": "s", "\u0061": {"c": []}, "d": 1.5}"#;
+        let parse = |policy, use_raw| {
+            let mut de = Deserializer::from_str(json).dedup_keys(policy);
+            if use_raw {
+                de = de.use_raw();
+            }
+            de.deserialize::<Value>()
+        };
+
+        for use_raw in [false, true] {
+            let value = parse(DedupPolicy::KeepAll, use_raw).unwrap();
+            assert_eq!(value.as_object().unwrap().len(), 4);
+
+            let value = parse(DedupPolicy::FirstWins, use_raw).unwrap();
+            value.check_invariants(None).unwrap();
+            assert_eq!(value, json!({"a": [{"x": 1, "y": 2}], "b": "s", "d": 1.5}));
+            assert_eq!(
+                crate::to_string(&value).unwrap(),
+                r#"{"a":[{"x":1,"y":2}],"b":"s","d":1.5}"#
+            );
+
+            let value = parse(DedupPolicy::LastWins, use_raw).unwrap();
+            value.check_invariants(None).unwrap();
+            assert_eq!(value, json!({"a": {"c": []}, "b": "s", "d": 1.5}));
+            let keys: Vec<_> = value.as_object().unwrap().iter().map(|(k, _)| k).collect();
+            assert_eq!(keys, ["a", "b", "d"]);
+
+            let err = parse(DedupPolicy::Error, use_raw).unwrap_err();
+            assert_eq!(err.duplicate_key(), Some(&pointer!["a", 0, "x"][..]));
+        }
+
+        // the value parsed in a struct, without the padding buffer
+        #[derive(serde::Deserialize)]
+        struct Wrapper {
+            val: Value,
+        }
+        let json = r#"{"val": {"k": {"m": 1, "m": 2}}}"#;
+        let mut de = Deserializer::from_str(json).dedup_keys(DedupPolicy::LastWins);
+        let wrapper: Wrapper = de.deserialize().unwrap();
+        wrapper.val.check_invariants(None).unwrap();
+        assert_eq!(wrapper.val, json!({"k": {"m": 2}}));
+        let mut de = Deserializer::from_str(json).dedup_keys(DedupPolicy::Error);
+        let err = de.deserialize::<Wrapper>().unwrap_err();
+        assert_eq!(err.duplicate_key(), Some(&pointer!["k", "m"][..]));
+    }
+
+    #[test]
+    fn test_parse_stats() {
+        let json = r#"[[1, "xy"], {"a": [true, "\n"]}, {}]"#;
+        let parsed: Value = from_str(json).unwrap();
+        let stats = parsed.parse_stats().unwrap();
+        assert_eq!(stats.nodes, 10);
+        assert_eq!(stats.string_bytes, "xy".len() + "a".len() + "\n".len());
+        assert_eq!(stats.max_depth, 3);
+        assert!(stats.arena_bytes > 0 && stats.json_bytes > json.len());
+
+        // the owned values have the same nodes, without the arena
+        let owned = json!([[1, "xy"], {"a": [true, "\n"]}, {}]);
+        let expect = ParseStats {
+            arena_bytes: 0,
+            json_bytes: 0,
+            ..stats
+        };
+        assert_eq!(owned.parse_stats(), Some(expect));
+        assert_eq!(json!("xy").parse_stats(), None);
+    }
+
+    #[test]
+    fn test_sibling_values() {
+        // the large array makes the node buffer exceed the thread-local buffer
+        let json = format!(
+            "[{}]",
+            (0..30000)
+                .map(|i| format!(r#"{{"id": {i}, "s": "\u00{:02x}"}}"#, i % 64 + 64))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let values: Vec<Value> = crate::from_str(&json).unwrap();
+        assert_eq!(values.len(), 30000);
+        for (i, value) in values.iter().enumerate() {
+            value.check_invariants(None).unwrap();
+            let s = char::from(b'@' + (i % 64) as u8).to_string();
+            assert_eq!(*value, json!({"id": i, "s": s}));
+        }
+
+        // the sibling values share the arena of the deserializer
+        let first = values[0].parse_stats().unwrap();
+        let last = values[29999].parse_stats().unwrap();
+        assert_eq!(first.arena_bytes, last.arena_bytes);
+        drop(values);
+
+        let values: Vec<Vec<Value>> = crate::from_str(r#"[[1, {"a": []}], [], ["x"]]"#).unwrap();
+        assert_eq!(
+            values,
+            [vec![json!(1), json!({"a": []})], vec![], vec![json!("x")]]
+        );
+    }
+
+    #[test]
+    fn test_index_key_types() {
+        let mut value = json!({"a": {"b": 1}, "c": [true]});
+        let key = FastStr::new("a");
+        let string = "a".to_string();
+        let s = "a";
+
+        assert_eq!(value[&key]["b"], 1);
+        assert_eq!(value[key.clone()]["b"], 1);
+        assert_eq!(value[&string]["b"], 1);
+        assert_eq!(value[string.clone()]["b"], 1);
+        assert_eq!(value[&s]["b"], 1);
+        assert_eq!(value.get(s).unwrap()["b"], 1);
+        assert_eq!(value.get(&key).get("b").as_u64(), Some(1));
+        assert_eq!(value.pointer(&[&key, &FastStr::new("b")]).as_u64(), Some(1));
+        assert!(value[String::from("x")].is_null());
+
+        value[String::from("d")] = json!(2);
+        value[&FastStr::new("e")] = json!(3);
+        assert_eq!(value["d"], 2);
+        assert_eq!(value[FastStr::new("e")], 3);
+        assert_eq!(value.get_mut(string).unwrap()["b"], 1);
+    }
 }
@@ -8,10 +8,11 @@ use super::{
     node::{ValueMut, ValueRefInner},
     value_trait::JsonValueTrait,
 };
-use crate::{serde::tri, util::reborrow::DormantMutRef, value::node::Value};
+use crate::{util::reborrow::DormantMutRef, value::node::Value};
 
 /// Represents the JSON object. The inner implement is a key-value array. Its order is as same as
-/// origin JSON.
+/// origin JSON, until the object is mutated. See [`Object::iter_ordered`] for the order after
+/// mutation.
 ///
 /// # Examples
 /// ```
@@ -142,6 +143,55 @@ impl Object {
         self.0.get_key(key.as_ref())
     }
 
+    /// Returns the values corresponding to the keys, in the order of `keys`.
+    ///
+    /// The pairs of the parsed object are walked once for all the keys, instead of once for each
+    /// key as [`get`][Object::get], and each pair is looked up in the sorted keys. If a key is
+    /// duplicated in the object, the first value is returned, the same as `get`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::{from_str, JsonValueTrait, Object};
+    ///
+    /// let obj: Object = from_str(r#"{"a": 1, "b": true, "c": null}"#).unwrap();
+    /// let values = obj.get_many_keys(&["c", "x", "a"]);
+    /// assert!(values[0].unwrap().is_null());
+    /// assert_eq!(values[1], None);
+    /// assert_eq!(values[2].unwrap().as_i64(), Some(1));
+    /// ```
+    pub fn get_many_keys(&self, keys: &[&str]) -> Vec<Option<&Value>> {
+        let mut values = vec![None; keys.len()];
+        match self.0.as_ref2() {
+            ValueRefInner::Object(pairs) => {
+                // the indexes of `keys` sorted by the keys, to look up each pair by binary search
+                let mut sorted: Vec<usize> = (0..keys.len()).collect();
+                sorted.sort_unstable_by_key(|&i| keys[i]);
+                let mut remain = keys.len();
+                for (k, v) in pairs {
+                    let k = k.as_str().expect("key is not string");
+                    let start = sorted.partition_point(|&i| keys[i] < k);
+                    for &i in sorted[start..].iter().take_while(|&&i| keys[i] == k) {
+                        if values[i].is_none() {
+                            values[i] = Some(v);
+                            remain -= 1;
+                        }
+                    }
+                    if remain == 0 {
+                        break;
+                    }
+                }
+            }
+            ValueRefInner::ObjectOwned(map) => {
+                for (key, value) in keys.iter().zip(values.iter_mut()) {
+                    *value = map.get(*key);
+                }
+            }
+            _ => {}
+        }
+        values
+    }
+
     /// Returns `true` if the map contains a value for the specified key.
     ///
     /// The key may be [`AsRef<str>`].
@@ -302,6 +352,10 @@ impl Object {
 
     /// Returns an immutable iterator over the key-value pairs of the object.
     ///
+    /// The pairs are in the order of the origin JSON if the object is not mutated. Otherwise, the
+    /// order is unspecified, or sorted by the keys with the `sort_keys` feature. Use
+    /// [`Object::iter_ordered`] to keep the order of the origin JSON after mutation.
+    ///
     /// # Examples
     /// ```
     /// use sonic_rs::object;
@@ -322,6 +376,62 @@ impl Object {
         })
     }
 
+    /// Returns an immutable iterator over the key-value pairs of the object, in the order of the
+    /// origin JSON even if the object has been mutated.
+    ///
+    /// The key order of the mutated object is only tracked with the `preserve_order_on_mut`
+    /// feature. The keys from the origin JSON keep their positions, and the inserted keys follow
+    /// in the insertion order. A key removed and inserted again is moved to the end. Without the
+    /// feature, or with the `sort_keys` feature, the mutated object is in the same order as
+    /// [`Object::iter`]. For the mutated object, the pairs are collected when the iterator is
+    /// created.
+    ///
+    /// # Examples
+    /// ```
+    /// use sonic_rs::{from_str, Object};
+    ///
+    /// let mut obj: Object = from_str(r#"{"z": 1, "a": 2, "m": 3}"#).unwrap();
+    /// obj.insert(&"b", 4);
+    /// obj.remove(&"a");
+    ///
+    /// let keys: Vec<&str> = obj.iter_ordered().map(|(k, _)| k).collect();
+    /// #[cfg(all(feature = "preserve_order_on_mut", not(feature = "sort_keys")))]
+    /// assert_eq!(keys, ["z", "m", "b"]);
+    /// ```
+    #[inline]
+    pub fn iter_ordered(&self) -> IterOrdered<'_> {
+        IterOrdered(match self.0.as_ref2() {
+            ValueRefInner::Object(obj) => IterOrderedInner::Slice(obj.iter()),
+            ValueRefInner::EmptyObject => IterOrderedInner::Slice([].iter()),
+            ValueRefInner::ObjectOwned(obj) => IterOrderedInner::Pairs(obj.ordered().into_iter()),
+            _ => unreachable!("should not used in non-object"),
+        })
+    }
+
+    /// Returns the position of the key in [`iter_ordered`][Object::iter_ordered], which is the
+    /// position in the origin JSON, or after the origin keys if it is inserted. If the key is
+    /// duplicated in the origin JSON, the first position is returned. The positions in the mutated
+    /// object follow [`iter_ordered`][Object::iter_ordered].
+    ///
+    /// # Examples
+    /// ```
+    /// use sonic_rs::{from_str, Object};
+    ///
+    /// let mut obj: Object = from_str(r#"{"z": 1, "a": 2}"#).unwrap();
+    /// assert_eq!(obj.key_index(&"a"), Some(1));
+    /// obj.insert(&"b", 3);
+    /// #[cfg(all(feature = "preserve_order_on_mut", not(feature = "sort_keys")))]
+    /// assert_eq!(obj.key_index(&"a"), Some(1));
+    /// #[cfg(all(feature = "preserve_order_on_mut", not(feature = "sort_keys")))]
+    /// assert_eq!(obj.key_index(&"b"), Some(2));
+    /// assert_eq!(obj.key_index(&"c"), None);
+    /// ```
+    #[inline]
+    pub fn key_index<Q: AsRef<str>>(&self, key: &Q) -> Option<usize> {
+        let key = key.as_ref();
+        self.iter_ordered().position(|(k, _)| k == key)
+    }
+
     /// Returns an mutable iterator over  the key-value pairs of the object.
     ///
     /// # Examples
@@ -431,15 +541,6 @@ impl Object {
     #[inline]
     pub fn append(&mut self, other: &mut Self) {
         if let ValueMut::Object(o) = self.0.as_mut() {
-            #[cfg(not(feature = "sort_keys"))]
-            if let ValueMut::Object(oo) = other.0.as_mut() {
-                o.reserve(oo.len());
-                o.extend(oo.drain());
-            } else {
-                unreachable!("should not used in array")
-            }
-
-            #[cfg(feature = "sort_keys")]
             if let ValueMut::Object(oo) = other.0.as_mut() {
                 o.append(oo);
             } else {
@@ -837,6 +938,39 @@ impl<'a> ExactSizeIterator for Iter<'a> {
 
 impl<'a> FusedIterator for Iter<'a> {}
 
+enum IterOrderedInner<'a> {
+    Slice(slice::Iter<'a, (Value, Value)>),
+    Pairs(std::vec::IntoIter<(&'a str, &'a Value)>),
+}
+
+/// An iterator over the entries of a `Object` in the order of the origin JSON, created by
+/// [`Object::iter_ordered`].
+pub struct IterOrdered<'a>(IterOrderedInner<'a>);
+
+impl<'a> Iterator for IterOrdered<'a> {
+    type Item = (&'a str, &'a Value);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IterOrderedInner::Slice(iter) => iter.next().map(|(k, v)| (k.as_str().unwrap(), v)),
+            IterOrderedInner::Pairs(iter) => iter.next(),
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for IterOrdered<'a> {
+    #[inline]
+    fn len(&self) -> usize {
+        match &self.0 {
+            IterOrderedInner::Slice(iter) => iter.len(),
+            IterOrderedInner::Pairs(iter) => iter.len(),
+        }
+    }
+}
+
+impl<'a> FusedIterator for IterOrdered<'a> {}
+
 /// A mutable iterator over the entries of a `Object`.
 pub struct IterMut<'a>(
     #[cfg(not(feature = "sort_keys"))] std::collections::hash_map::IterMut<'a, FastStr, Value>,
@@ -953,12 +1087,9 @@ impl serde::ser::Serialize for Object {
     where
         S: serde::ser::Serializer,
     {
-        use serde::ser::SerializeMap;
-        let mut map = tri!(serializer.serialize_map(Some(self.len())));
-        for (k, v) in self {
-            tri!(map.serialize_entry(k, v));
-        }
-        map.end()
+        // serialize the inner value directly, which keeps the raw keys and sorts the keys if
+        // needed, without cloning
+        serde::ser::Serialize::serialize(&self.0, serializer)
     }
 }
 
@@ -1028,4 +1159,90 @@ mod test {
             assert_eq!(v.1, &Value::from(&i.to_string()));
         }
     }
+
+    #[test]
+    #[cfg(all(feature = "preserve_order_on_mut", not(feature = "sort_keys")))]
+    fn test_object_iter_ordered() {
+        fn keys(obj: &Object) -> Vec<&str> {
+            obj.iter_ordered().map(|(k, _)| k).collect()
+        }
+
+        let mut obj: Object = from_str(r#"{"c": 1, "b": 2, "a": 3, "b": 4}"#).unwrap();
+        assert_eq!(keys(&obj), ["c", "b", "a", "b"]);
+
+        // the duplicated key keeps the first position and the last value
+        obj.insert(&"d", 5);
+        assert_eq!(keys(&obj), ["c", "b", "a", "d"]);
+        assert_eq!(obj.iter_ordered().nth(1).unwrap().1, &Value::from(4));
+
+        obj.remove(&"c");
+        obj.insert(&"c", 6);
+        obj.entry(&"e").or_insert(7);
+        obj.retain(|k, _| k != "a");
+        let iter = obj.iter_ordered();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(keys(&obj), ["b", "d", "c", "e"]);
+
+        // the removed keys are compacted
+        for i in 0..100 {
+            let k = i.to_string();
+            obj.insert(&k, i);
+            obj.remove(&k);
+        }
+        assert_eq!(keys(&obj), ["b", "d", "c", "e"]);
+
+        let mut other = object! {"y": 1, "x": 2};
+        obj.append(&mut other);
+        assert_eq!(keys(&obj), ["b", "d", "c", "e", "y", "x"]);
+        assert!(other.is_empty());
+
+        obj.clear();
+        assert_eq!(keys(&obj), Vec::<&str>::new());
+        assert_eq!(keys(&Object::new()), Vec::<&str>::new());
+    }
+
+    #[test]
+    #[cfg(all(feature = "preserve_order_on_mut", not(feature = "sort_keys")))]
+    fn test_object_key_index() {
+        let mut obj: Object = from_str(r#"{"c": 1, "b": 2, "a": 3}"#).unwrap();
+        assert_eq!(obj.key_index(&"b"), Some(1));
+        obj.remove(&"c");
+        obj.insert(&"d", 4);
+        assert_eq!(obj.key_index(&"b"), Some(0));
+        assert_eq!(obj.key_index(&"d"), Some(2));
+        assert_eq!(obj.key_index(&"c"), None);
+
+        // the mutated object is serialized in the origin order
+        assert_eq!(to_string(&obj).unwrap(), r#"{"b":2,"a":3,"d":4}"#);
+    }
+
+    #[test]
+    fn test_object_get_many_keys() {
+        let mut obj: Object = from_str(r#"{"a": 1, "b": 2, "a": 3, "c": 4}"#).unwrap();
+        let get = |obj: &Object, keys: &[&str]| -> Vec<Option<i64>> {
+            let values = obj.get_many_keys(keys);
+            assert_eq!(values.len(), keys.len());
+            for (key, value) in keys.iter().zip(&values) {
+                assert_eq!(*value, obj.get(key), "{key}");
+            }
+            values.iter().map(|v| v.and_then(|v| v.as_i64())).collect()
+        };
+        assert_eq!(
+            get(&obj, &["c", "a", "x", "a"]),
+            [Some(4), Some(1), None, Some(1)]
+        );
+        assert_eq!(get(&obj, &[]), []);
+
+        // more keys than the pairs, in the reversed order
+        let keys: Vec<String> = (0..100).rev().map(|i| format!("k{i}")).collect();
+        let keys: Vec<&str> = keys.iter().map(|k| k.as_str()).chain(["a", "c"]).collect();
+        let values = get(&obj, &keys);
+        assert!(values[..100].iter().all(|v| v.is_none()));
+        assert_eq!(values[100..], [Some(1), Some(4)]);
+
+        // the mutated object
+        obj.insert(&"d", 5);
+        assert_eq!(get(&obj, &["d", "b", "y"]), [Some(5), Some(2), None]);
+        assert_eq!(get(&Object::new(), &["a"]), [None]);
+    }
 }
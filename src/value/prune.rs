@@ -0,0 +1,209 @@
+use crate::{value::node::Value, JsonContainerTrait, JsonValueMutTrait, JsonValueTrait};
+
+impl Value {
+    /// Retain the array elements and object fields recursively, which the predicate `f` returns
+    /// `true`. The key is `None` for the array elements.
+    ///
+    /// The value is visited before its children, so the children of a removed value are not
+    /// visited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::{json, JsonValueTrait};
+    ///
+    /// let mut value = json!({"a": null, "b": [1, null, {"c": null, "d": 2}]});
+    /// value.retain_recursive(|_, v| !v.is_null());
+    /// assert_eq!(value, json!({"b": [1, {"d": 2}]}));
+    ///
+    /// let mut value = json!({"password": "123", "user": {"name": "sonic", "password": "456"}});
+    /// value.retain_recursive(|key, _| key != Some("password"));
+    /// assert_eq!(value, json!({"user": {"name": "sonic"}}));
+    /// ```
+    pub fn retain_recursive<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Option<&str>, &mut Value) -> bool,
+    {
+        self.retain_recursive_impl(&mut f)
+    }
+
+    fn retain_recursive_impl<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(Option<&str>, &mut Value) -> bool,
+    {
+        if let Some(obj) = self.as_object_mut() {
+            obj.retain(|k, v| {
+                let retained = f(Some(k), v);
+                if retained {
+                    v.retain_recursive_impl(f);
+                }
+                retained
+            });
+        } else if let Some(arr) = self.as_array_mut() {
+            arr.retain_mut(|v| {
+                let retained = f(None, v);
+                if retained {
+                    v.retain_recursive_impl(f);
+                }
+                retained
+            });
+        }
+    }
+
+    /// Truncate the value in place, for the size-bounded representations in logs or debugging.
+    ///
+    /// - The non-empty arrays and objects nested deeper than `max_depth` are replaced with a marker
+    ///   string `"...TRUNCATED N items"`. The root container is at depth 1, so all the non-empty
+    ///   containers are replaced if `max_depth` is 0.
+    /// - The arrays longer than `max_array_len` keep the first `max_array_len` elements, and the
+    ///   marker string `"...TRUNCATED N items"` is appended as the last element.
+    /// - The strings longer than `max_string_len` bytes keep the leading `max_string_len` bytes at
+    ///   most, without splitting a char, followed by `"...TRUNCATED N bytes"`.
+    ///
+    /// The `N` is the count of the removed items or bytes. The object keys are not truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::json;
+    ///
+    /// let mut value = json!({
+    ///     "id": 1,
+    ///     "data": "a very long payload",
+    ///     "list": [1, 2, 3, 4, 5],
+    ///     "nested": {"a": {"b": {"c": 1}}},
+    /// });
+    /// value.prune(3, 2, 6);
+    /// assert_eq!(
+    ///     value,
+    ///     json!({
+    ///         "id": 1,
+    ///         "data": "a very...TRUNCATED 13 bytes",
+    ///         "list": [1, 2, "...TRUNCATED 3 items"],
+    ///         "nested": {"a": {"b": "...TRUNCATED 1 items"}},
+    ///     })
+    /// );
+    /// ```
+    pub fn prune(&mut self, max_depth: usize, max_array_len: usize, max_string_len: usize) {
+        if let Some(s) = self.as_str() {
+            if s.len() > max_string_len {
+                let mut end = max_string_len;
+                while !s.is_char_boundary(end) {
+                    end -= 1;
+                }
+                let truncated = format!("{}...TRUNCATED {} bytes", &s[..end], s.len() - end);
+                *self = Value::from(&truncated);
+            }
+            return;
+        }
+
+        let len = match (self.as_array(), self.as_object()) {
+            (Some(arr), _) => arr.len(),
+            (_, Some(obj)) => obj.len(),
+            _ => return,
+        };
+        if len == 0 {
+            return;
+        }
+        if max_depth == 0 {
+            *self = Value::from(&format!("...TRUNCATED {len} items"));
+            return;
+        }
+
+        if let Some(obj) = self.as_object_mut() {
+            for (_, v) in obj.iter_mut() {
+                v.prune(max_depth - 1, max_array_len, max_string_len);
+            }
+        } else if let Some(arr) = self.as_array_mut() {
+            arr.truncate(max_array_len);
+            for v in arr.iter_mut() {
+                v.prune(max_depth - 1, max_array_len, max_string_len);
+            }
+            if len > max_array_len {
+                arr.push(Value::from(&format!(
+                    "...TRUNCATED {} items",
+                    len - max_array_len
+                )));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{JsonValueTrait, Value};
+
+    #[test]
+    fn test_retain_recursive() {
+        let mut value: Value =
+            crate::from_str(r#"{"a": [1, "x", {"b": "x", "c": [2, "x"]}], "d": "x", "e": {}}"#)
+                .unwrap();
+        let mut visited = 0;
+        value.retain_recursive(|_, v| {
+            visited += 1;
+            v.as_str() != Some("x")
+        });
+        assert_eq!(value, json!({"a": [1, {"c": [2]}], "e": {}}));
+        assert_eq!(visited, 10);
+
+        // the removed subtrees are not visited
+        let mut value = json!({"a": {"b": 1}, "c": {"d": 2}});
+        let mut keys = Vec::new();
+        value.retain_recursive(|k, _| {
+            keys.push(k.unwrap().to_string());
+            k != Some("a")
+        });
+        keys.sort();
+        assert_eq!(keys, ["a", "c", "d"]);
+        assert_eq!(value, json!({"c": {"d": 2}}));
+
+        // the values can be modified in the predicate
+        let mut value = json!([1, [2, 3]]);
+        value.retain_recursive(|_, v| {
+            if let Some(n) = v.as_u64() {
+                *v = Value::from(n * 10);
+            }
+            true
+        });
+        assert_eq!(value, json!([10, [20, 30]]));
+    }
+
+    #[test]
+    fn test_prune() {
+        let json = r#"{"s": "héllo", "a": [[1, 2, 3], [], {}], "o": {"p": {"q": [true]}}}"#;
+        let pruned = |max_depth, max_array_len, max_string_len| {
+            let mut value: Value = crate::from_str(json).unwrap();
+            value.prune(max_depth, max_array_len, max_string_len);
+            value
+        };
+
+        assert_eq!(pruned(usize::MAX, usize::MAX, usize::MAX), {
+            let value: Value = crate::from_str(json).unwrap();
+            value
+        });
+        assert_eq!(pruned(0, 0, 0), json!("...TRUNCATED 3 items"));
+        assert_eq!(
+            pruned(1, 10, 2),
+            json!({
+                "s": "h...TRUNCATED 5 bytes",
+                "a": "...TRUNCATED 3 items",
+                "o": "...TRUNCATED 1 items",
+            })
+        );
+        assert_eq!(
+            pruned(3, 1, 100),
+            json!({
+                "s": "héllo",
+                "a": [[1, "...TRUNCATED 2 items"], "...TRUNCATED 2 items"],
+                "o": {"p": {"q": "...TRUNCATED 1 items"}},
+            })
+        );
+        assert_eq!(pruned(2, 0, 100)["a"], json!(["...TRUNCATED 3 items"]));
+
+        let mut value = json!("abc");
+        value.prune(0, 0, 3);
+        assert_eq!(value, "abc");
+        value.prune(0, 0, 0);
+        assert_eq!(value, "...TRUNCATED 3 bytes");
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use faststr::FastStr;
 use serde::{
     de::Unexpected,
     ser::{Impossible, Serialize},
@@ -292,6 +293,9 @@ impl serde::Serializer for Serializer {
             crate::serde::rawnumber::TOKEN => Ok(SerializeMap {
                 map: MapInner::RawNumber { out_value: None },
             }),
+            crate::lazyvalue::TOKEN | crate::serde::rawjson::TOKEN => Ok(SerializeMap {
+                map: MapInner::RawJson { out_value: None },
+            }),
             _ => self.serialize_map(Some(len)),
         }
     }
@@ -343,6 +347,9 @@ enum MapInner {
     RawNumber {
         out_value: Option<Value>,
     },
+    RawJson {
+        out_value: Option<Value>,
+    },
 }
 
 /// Serializing Rust struct variant into `Value`.
@@ -432,7 +439,7 @@ impl serde::ser::SerializeMap for SerializeMap {
                 *next_key = Some(tri!(key.serialize(MapKeySerializer)));
                 Ok(())
             }
-            MapInner::RawNumber { .. } => unreachable!(),
+            MapInner::RawNumber { .. } | MapInner::RawJson { .. } => unreachable!(),
         }
     }
 
@@ -449,14 +456,14 @@ impl serde::ser::SerializeMap for SerializeMap {
                 object.insert(key.as_str().unwrap(), tri!(to_value(value)));
                 Ok(())
             }
-            MapInner::RawNumber { .. } => unreachable!(),
+            MapInner::RawNumber { .. } | MapInner::RawJson { .. } => unreachable!(),
         }
     }
 
     fn end(self) -> Result<Value> {
         match self.map {
             MapInner::Object { object, .. } => Ok(object),
-            MapInner::RawNumber { .. } => unreachable!(),
+            MapInner::RawNumber { .. } | MapInner::RawJson { .. } => unreachable!(),
         }
     }
 }
@@ -834,6 +841,16 @@ impl serde::ser::SerializeStruct for SerializeMap {
                     unreachable!()
                 }
             }
+            MapInner::RawJson { out_value } => {
+                if key == crate::lazyvalue::TOKEN || key == crate::serde::rawjson::TOKEN {
+                    let raw = tri!(value.serialize(Serializer));
+                    let raw = FastStr::new(raw.as_str().unwrap_or_default());
+                    *out_value = Some(tri!(Value::from_raw_json_trusted(raw)));
+                    Ok(())
+                } else {
+                    unreachable!()
+                }
+            }
         }
     }
 
@@ -843,6 +860,7 @@ impl serde::ser::SerializeStruct for SerializeMap {
             MapInner::RawNumber { out_value, .. } => {
                 Ok(out_value.expect("number value was not emitted"))
             }
+            MapInner::RawJson { out_value } => Ok(out_value.expect("raw JSON was not emitted")),
         }
     }
 }
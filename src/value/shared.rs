@@ -19,6 +19,14 @@ impl Shared {
     pub fn set_json(&mut self, json: Vec<u8>) {
         self.json = json;
     }
+
+    pub fn allocated_bytes(&self) -> usize {
+        self.alloc.allocated_bytes()
+    }
+
+    pub fn json_len(&self) -> usize {
+        self.json.len()
+    }
 }
 
 // #safety
@@ -0,0 +1,136 @@
+use std::iter::FusedIterator;
+
+use crate::{value::node::Value, JsonContainerTrait, JsonValueMutTrait, JsonValueTrait};
+
+impl Value {
+    /// Returns an iterator over all the string values in the tree, in the iteration order of the
+    /// arrays and objects. The object keys are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::Value;
+    ///
+    /// let value: Value = sonic_rs::from_str(r#"{"a": "x", "b": [1, "y", {"c": "z"}]}"#).unwrap();
+    /// let strings: Vec<&str> = value.strings().collect();
+    /// assert_eq!(strings, ["x", "y", "z"]);
+    /// ```
+    pub fn strings(&self) -> Strings<'_> {
+        Strings { stack: vec![self] }
+    }
+
+    /// Returns an iterator over all the string values in the tree, which yields the mutable
+    /// values. The object keys are not included.
+    ///
+    /// The arrays and objects are converted into the mutable ones when visited, as the other
+    /// mutable APIs. So, the order of the object fields may be changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::{json, JsonValueTrait, Value};
+    ///
+    /// let mut value = json!({"name": "sonic", "emails": ["a@b.com", "c@d.com"], "age": 1});
+    /// for s in value.strings_mut() {
+    ///     if s.as_str().unwrap().contains('@') {
+    ///         *s = Value::from("<redacted>");
+    ///     }
+    /// }
+    /// assert_eq!(
+    ///     value,
+    ///     json!({"name": "sonic", "emails": ["<redacted>", "<redacted>"], "age": 1})
+    /// );
+    /// ```
+    pub fn strings_mut(&mut self) -> StringsMut<'_> {
+        StringsMut { stack: vec![self] }
+    }
+}
+
+/// An iterator over the string values in a `Value`, created by [`Value::strings`].
+pub struct Strings<'a> {
+    // the values to visit, the next one is at the top
+    stack: Vec<&'a Value>,
+}
+
+impl<'a> Iterator for Strings<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(value) = self.stack.pop() {
+            if let Some(s) = value.as_str() {
+                return Some(s);
+            }
+
+            let start = self.stack.len();
+            if let Some(arr) = value.as_array() {
+                self.stack.extend(arr.iter());
+            } else if let Some(obj) = value.as_object() {
+                self.stack.extend(obj.iter().map(|(_, v)| v));
+            }
+            self.stack[start..].reverse();
+        }
+        None
+    }
+}
+
+impl FusedIterator for Strings<'_> {}
+
+/// An iterator over the mutable string values in a `Value`, created by [`Value::strings_mut`].
+pub struct StringsMut<'a> {
+    stack: Vec<&'a mut Value>,
+}
+
+impl<'a> Iterator for StringsMut<'a> {
+    type Item = &'a mut Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(value) = self.stack.pop() {
+            if value.is_str() {
+                return Some(value);
+            }
+
+            let start = self.stack.len();
+            if value.is_array() {
+                self.stack.extend(value.as_array_mut().unwrap().iter_mut());
+            } else if value.is_object() {
+                let obj = value.as_object_mut().unwrap();
+                self.stack.extend(obj.iter_mut().map(|(_, v)| v));
+            }
+            self.stack[start..].reverse();
+        }
+        None
+    }
+}
+
+impl FusedIterator for StringsMut<'_> {}
+
+#[cfg(test)]
+mod test {
+    use crate::{JsonValueTrait, Value};
+
+    #[test]
+    fn test_strings() {
+        let json = r#"{"a": ["x", 1, {"b": "y", "c": [], "d": {}}], "e": "z\n", "f": null}"#;
+        let value: Value = crate::from_str(json).unwrap();
+        assert_eq!(value.strings().collect::<Vec<_>>(), ["x", "y", "z\n"]);
+        assert_eq!(json!("s").strings().collect::<Vec<_>>(), ["s"]);
+        assert_eq!(json!([1, [], {}]).strings().count(), 0);
+
+        let mut value: Value = crate::from_str(json).unwrap();
+        let mut visited = Vec::new();
+        for s in value.strings_mut() {
+            visited.push(s.as_str().unwrap().to_string());
+            *s = Value::from(&s.as_str().unwrap().to_uppercase());
+        }
+        visited.sort();
+        assert_eq!(visited, ["x", "y", "z\n"]);
+        assert_eq!(
+            value,
+            json!({"a": ["X", 1, {"b": "Y", "c": [], "d": {}}], "e": "Z\n", "f": null})
+        );
+
+        let mut value = json!("s");
+        *value.strings_mut().next().unwrap() = Value::from(1);
+        assert_eq!(value, 1);
+    }
+}
@@ -43,6 +43,23 @@ impl TlsBuf {
         }
     }
 
+    /// Use the `vec` as the buffer, which is reused by the sibling values parsed by the same
+    /// deserializer.
+    ///
+    /// # Safety
+    ///
+    /// The returned buffer keeps a pointer to `vec` without its lifetime, so `vec` must outlive
+    /// the returned buffer and must not be accessed while the buffer is alive.
+    #[inline]
+    pub unsafe fn reuse(vec: &mut Vec<ManuallyDrop<Value>>, n: usize) -> Self {
+        vec.clear();
+        vec.reserve(n);
+        Self {
+            buf: NonNull::from(vec),
+            need_drop: false,
+        }
+    }
+
     #[inline]
     pub fn as_vec_mut(&mut self) -> &mut Vec<ManuallyDrop<Value>> {
         unsafe { self.buf.as_mut() }
@@ -36,6 +36,25 @@ impl From<u8> for JsonType {
     }
 }
 
+/// Truncates `f` toward zero, returning None if it is non-finite or out of the i64 range.
+fn f64_to_i64_trunc(f: f64) -> Option<i64> {
+    // i64::MIN is exactly representable, while i64::MAX rounds up to 2^63.
+    if f.is_finite() && f >= i64::MIN as f64 && f < -(i64::MIN as f64) {
+        Some(f.trunc() as i64)
+    } else {
+        None
+    }
+}
+
+/// Parses a numeric string as f64, rejecting non-finite spellings such as `NaN` or `inf`.
+fn parse_numeric_str(s: &str) -> Option<f64> {
+    let first = s.bytes().next()?;
+    if !(first.is_ascii_digit() || first == b'-' || first == b'+' || first == b'.') {
+        return None;
+    }
+    s.parse::<f64>().ok().filter(|f| f.is_finite())
+}
+
 /// A trait for all JSON values. Used by `Value` and `LazyValue`.
 ///
 /// The `Option<V: JsonValueTrait>` and `Result<V: JsonValueTrait, E>` also implement this trait.
@@ -303,6 +322,69 @@ pub trait JsonValueTrait {
         self.as_number().and_then(|n| n.as_f64())
     }
 
+    /// Converts `self` to i64 across number subtypes and numeric strings. Returns None if the
+    /// value cannot be represented.
+    ///
+    /// Unlike the strict [`as_i64`](Self::as_i64), this accepts:
+    /// - floats, truncated toward zero if the result fits in i64,
+    /// - strings holding a JSON-like number (surrounding whitespace is ignored), converted with the
+    ///   same rules.
+    ///
+    /// Unsigned integers above `i64::MAX`, non-finite or out-of-range floats, and any other types
+    /// return None.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::{json, JsonValueTrait};
+    ///
+    /// assert_eq!(json!(42).as_i64_lossy(), Some(42));
+    /// assert_eq!(json!(-1.9).as_i64_lossy(), Some(-1));
+    /// assert_eq!(json!("42").as_i64_lossy(), Some(42));
+    /// assert_eq!(json!(" 3.7 ").as_i64_lossy(), Some(3));
+    ///
+    /// assert_eq!(json!(u64::MAX).as_i64_lossy(), None);
+    /// assert_eq!(json!(1e20).as_i64_lossy(), None);
+    /// assert_eq!(json!("abc").as_i64_lossy(), None);
+    /// assert_eq!(json!(true).as_i64_lossy(), None);
+    /// ```
+    fn as_i64_lossy(&self) -> Option<i64> {
+        if let Some(n) = self.as_number() {
+            return n.as_i64().or_else(|| n.as_f64().and_then(f64_to_i64_trunc));
+        }
+        let s = self.as_str()?.trim();
+        s.parse::<i64>()
+            .ok()
+            .or_else(|| parse_numeric_str(s).and_then(f64_to_i64_trunc))
+    }
+
+    /// Converts `self` to f64 across number subtypes and numeric strings. Returns None if the
+    /// value cannot be represented.
+    ///
+    /// Unlike the strict [`as_f64`](Self::as_f64), this also accepts strings holding a JSON-like
+    /// number (surrounding whitespace is ignored). Integers beyond 2^53 are rounded to the
+    /// nearest f64. Strings such as `"NaN"` or `"inf"` are rejected, since JSON has no
+    /// non-finite numbers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sonic_rs::{json, JsonValueTrait};
+    ///
+    /// assert_eq!(json!(42).as_f64_coerce(), Some(42.0));
+    /// assert_eq!(json!("1.5").as_f64_coerce(), Some(1.5));
+    /// assert_eq!(json!("-2e3").as_f64_coerce(), Some(-2000.0));
+    ///
+    /// assert_eq!(json!("NaN").as_f64_coerce(), None);
+    /// assert_eq!(json!(null).as_f64_coerce(), None);
+    /// ```
+    fn as_f64_coerce(&self) -> Option<f64> {
+        if let Some(n) = self.as_number() {
+            return n.as_f64();
+        }
+        parse_numeric_str(self.as_str()?.trim())
+    }
+
     /// Returns the `Number` if `self` is a `Number`.
     ///
     /// # Examples
@@ -1,6 +1,9 @@
 //! Extend trait from io::Write for JSON serializing.
 
-use std::{io, io::BufWriter as IoBufWriter, mem::MaybeUninit, slice::from_raw_parts_mut};
+use std::{
+    fmt, io, io::BufWriter as IoBufWriter, mem::MaybeUninit, slice::from_raw_parts_mut,
+    str::from_utf8_unchecked,
+};
 
 use bytes::{buf::Writer, BytesMut};
 
@@ -20,24 +23,64 @@ pub trait WriteExt: io::Write {
     ///
     /// Must be used after `reserve_with`
     unsafe fn flush_len(&mut self, additional: usize) -> io::Result<()>;
+
+    /// The growth strategy hook, which returns the capacity to reserve when `additional` bytes
+    /// will be written, such as a long string.
+    ///
+    /// The default is `additional`. A writer can reserve more to reduce the reallocations if it
+    /// knows the following data is large. The result less than `additional` is ignored.
+    #[inline(always)]
+    fn grow_hint(&self, additional: usize) -> usize {
+        additional
+    }
 }
 
+// The default size of the buffered output before writing into the I/O stream.
+const DEFAULT_FLUSH_THRESHOLD: usize = 8 * 1024;
+
 /// Wrapper around generic I/O streams implementing [`WriteExt`]
 ///
-/// It internally maintains a buffer for fast operations which it then flushes
-/// to the underlying I/O stream when requested.
-pub struct BufferedWriter<W> {
+/// It internally maintains a buffer for fast operations, and writes the buffered output into the
+/// underlying I/O stream when the buffer exceeds the flush threshold, or when flushed. So the
+/// memory usage is bounded by the threshold instead of the size of the whole output.
+///
+/// The remaining output is written when the writer is dropped, and the errors are ignored in
+/// dropping. Call [`flush`][io::Write::flush] to handle the errors.
+pub struct BufferedWriter<W: io::Write> {
     inner: W,
     buffer: Vec<u8>,
+    threshold: usize,
 }
 
-impl<W> BufferedWriter<W> {
-    /// Construct a new buffered writer
+impl<W: io::Write> BufferedWriter<W> {
+    /// Construct a new buffered writer, with the default flush threshold of 8 KiB.
     pub fn new(inner: W) -> Self {
+        Self::with_threshold(inner, DEFAULT_FLUSH_THRESHOLD)
+    }
+
+    /// Construct a new buffered writer, which writes into the I/O stream when the buffered
+    /// output reaches `threshold` bytes. The `threshold` of zero writes each piece of output
+    /// directly.
+    pub fn with_threshold(inner: W, threshold: usize) -> Self {
         Self {
             inner,
-            buffer: Vec::new(),
+            buffer: Vec::with_capacity(threshold),
+            threshold,
+        }
+    }
+
+    /// Get a reference to the underlying I/O stream.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    // write all the buffered output into the I/O stream
+    fn write_buffer(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            self.inner.write_all(&self.buffer)?;
+            self.buffer.clear();
         }
+        Ok(())
     }
 }
 
@@ -45,13 +88,18 @@ impl<W> io::Write for BufferedWriter<W>
 where
     W: io::Write,
 {
-    #[inline(always)]
+    #[inline]
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.inner.write(buf)
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= self.threshold {
+            self.write_buffer()?;
+        }
+        Ok(buf.len())
     }
 
-    #[inline(always)]
+    #[inline]
     fn flush(&mut self) -> io::Result<()> {
+        self.write_buffer()?;
         self.inner.flush()
     }
 }
@@ -68,13 +116,19 @@ where
     #[inline(always)]
     unsafe fn flush_len(&mut self, additional: usize) -> io::Result<()> {
         self.buffer.flush_len(additional)?;
-        self.inner.write_all(&self.buffer)?;
-        self.buffer.clear();
-
+        if self.buffer.len() >= self.threshold {
+            self.write_buffer()?;
+        }
         Ok(())
     }
 }
 
+impl<W: io::Write> Drop for BufferedWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.write_buffer();
+    }
+}
+
 impl WriteExt for Vec<u8> {
     #[inline(always)]
     fn reserve_with(&mut self, additional: usize) -> io::Result<&mut [MaybeUninit<u8>]> {
@@ -140,6 +194,10 @@ impl<W: WriteExt + ?Sized> WriteExt for IoBufWriter<W> {
     unsafe fn flush_len(&mut self, additional: usize) -> io::Result<()> {
         self.get_mut().flush_len(additional)
     }
+
+    fn grow_hint(&self, additional: usize) -> usize {
+        self.get_ref().grow_hint(additional)
+    }
 }
 
 impl<W: WriteExt + ?Sized> WriteExt for &mut W {
@@ -152,6 +210,11 @@ impl<W: WriteExt + ?Sized> WriteExt for &mut W {
     fn reserve_with(&mut self, additional: usize) -> io::Result<&mut [MaybeUninit<u8>]> {
         (*self).reserve_with(additional)
     }
+
+    #[inline(always)]
+    fn grow_hint(&self, additional: usize) -> usize {
+        (**self).grow_hint(additional)
+    }
 }
 
 impl<W: WriteExt + ?Sized> WriteExt for Box<W> {
@@ -164,6 +227,102 @@ impl<W: WriteExt + ?Sized> WriteExt for Box<W> {
     fn reserve_with(&mut self, additional: usize) -> io::Result<&mut [MaybeUninit<u8>]> {
         (**self).reserve_with(additional)
     }
+
+    #[inline(always)]
+    fn grow_hint(&self, additional: usize) -> usize {
+        (**self).grow_hint(additional)
+    }
+}
+
+// The buffered size before writing into the `fmt::Write`.
+const FMT_BUF_SIZE: usize = 4096;
+
+/// Adapt a [`fmt::Write`] into [`WriteExt`]. The JSON is buffered in chunks, and the chunks are
+/// written into the `fmt::Write` at the char boundaries.
+pub(crate) struct FmtWriter<'a, W: fmt::Write + ?Sized> {
+    inner: &'a mut W,
+    buffer: Vec<u8>,
+}
+
+impl<'a, W: fmt::Write + ?Sized> FmtWriter<'a, W> {
+    pub(crate) fn new(inner: &'a mut W) -> Self {
+        Self {
+            inner,
+            buffer: Vec::new(),
+        }
+    }
+
+    // Write the complete chars in the buffer, the incomplete char at the end is kept.
+    fn write_chars(&mut self) -> io::Result<()> {
+        let len = char_boundary(&self.buffer);
+        // # Safety
+        // the serializer only writes the valid UTF-8
+        let s = unsafe { from_utf8_unchecked(&self.buffer[..len]) };
+        self.inner.write_str(s).map_err(io::Error::other)?;
+        self.buffer.copy_within(len.., 0);
+        self.buffer.truncate(self.buffer.len() - len);
+        Ok(())
+    }
+
+    /// Write all the buffered JSON into the `fmt::Write`.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        self.write_chars()?;
+        if self.buffer.is_empty() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "incomplete UTF-8 char at the end",
+            ))
+        }
+    }
+}
+
+// The length of the complete chars in the UTF-8 bytes.
+fn char_boundary(buf: &[u8]) -> usize {
+    for i in 1..=buf.len().min(4) {
+        let b = buf[buf.len() - i];
+        if b & 0xC0 != 0x80 {
+            let width = match b {
+                0..=0x7F => 1,
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                _ => 4,
+            };
+            return if width <= i { buf.len() } else { buf.len() - i };
+        }
+    }
+    buf.len()
+}
+
+impl<W: fmt::Write + ?Sized> io::Write for FmtWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= FMT_BUF_SIZE {
+            self.write_chars()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_chars()
+    }
+}
+
+impl<W: fmt::Write + ?Sized> WriteExt for FmtWriter<'_, W> {
+    #[inline(always)]
+    fn reserve_with(&mut self, additional: usize) -> io::Result<&mut [MaybeUninit<u8>]> {
+        self.buffer.reserve_with(additional)
+    }
+
+    #[inline(always)]
+    unsafe fn flush_len(&mut self, additional: usize) -> io::Result<()> {
+        self.buffer.flush_len(additional)?;
+        if self.buffer.len() >= FMT_BUF_SIZE {
+            self.write_chars()?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -172,6 +331,7 @@ mod test {
 
     use bytes::{BufMut, BytesMut};
 
+    use super::FmtWriter;
     use crate::writer::WriteExt;
 
     #[test]
@@ -188,4 +348,27 @@ mod test {
         assert_eq!(writer.get_ref().capacity(), 20);
         assert_eq!(writer.get_ref().as_ref(), &data[..]);
     }
+
+    #[test]
+    fn test_fmt_writer() {
+        // the char is split between the writes
+        let mut out = String::new();
+        let mut writer = FmtWriter::new(&mut out);
+        writer.write_all(b"a\xC3").unwrap();
+        writer.flush().unwrap();
+        writer.write_all(b"\xA9\xF0\x9F").unwrap();
+        writer.flush().unwrap();
+        writer.write_all(b"\x98\x80").unwrap();
+        writer.finish().unwrap();
+        assert_eq!(out, "aé😀");
+
+        let mut out = String::new();
+        let mut writer = FmtWriter::new(&mut out);
+        writer.write_all(b"\xE4\xB8").unwrap();
+        assert!(writer.finish().is_err());
+
+        // the large value is written in chunks
+        let value = crate::to_value(&(vec!["中文😀"; 2000], [1.0, 2.5])).unwrap();
+        assert_eq!(format!("{value}"), crate::to_string(&value).unwrap());
+    }
 }